@@ -0,0 +1,5 @@
+-- Add arbitrary key/value tags to sources, used to organize and filter them
+alter table app.sources
+add column tags jsonb not null default '{}'::jsonb;
+
+create index sources_tags_idx on app.sources using gin (tags);
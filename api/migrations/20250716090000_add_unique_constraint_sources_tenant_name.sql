@@ -0,0 +1,17 @@
+-- Rename existing sources sharing a name within a tenant, keeping the oldest
+-- source untouched and suffixing the others with their id, so that the
+-- unique constraint below can be added
+update app.sources s
+set name = s.name || ' (' || s.id || ')'
+where exists (
+    select 1
+    from app.sources o
+    where o.tenant_id = s.tenant_id
+        and o.name = s.name
+        and o.id < s.id
+);
+
+-- Add unique constraint to ensure source names are unique within a tenant
+alter table app.sources
+add constraint sources_tenant_id_name_unique
+unique (tenant_id, name);
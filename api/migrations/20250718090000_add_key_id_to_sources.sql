@@ -0,0 +1,9 @@
+-- Record the id of the key which encrypted the config of each source, so that the sources still
+-- encrypted with a previous key can be found while rotating the encryption key
+alter table app.sources
+add column key_id bigint;
+
+update app.sources
+set key_id = (config -> 'password' ->> 'id')::bigint;
+
+create index sources_key_id_idx on app.sources (key_id);
@@ -0,0 +1,13 @@
+-- Soft delete sources, so that a deleted source can be restored until it is purged
+alter table app.sources
+add column deleted_at timestamptz;
+
+-- The names of deleted sources can be reused, so names are only unique among the sources which
+-- are not deleted. The index keeps the name of the constraint it replaces, so that violations
+-- are still reported as duplicate names.
+alter table app.sources
+drop constraint sources_tenant_id_name_unique;
+
+create unique index sources_tenant_id_name_unique
+on app.sources (tenant_id, name)
+where deleted_at is null;
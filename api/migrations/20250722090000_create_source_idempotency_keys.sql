@@ -0,0 +1,12 @@
+-- The idempotency keys of the requests which created sources, so that retrying a request returns
+-- the source it created instead of creating another one. A key is inserted before its source is
+-- created, so that concurrent requests with the same key wait for each other, which is why the
+-- source id is only null until the transaction creating the source commits.
+create table
+    app.source_idempotency_keys (
+        tenant_id text references app.tenants (id) on delete cascade not null,
+        key text not null,
+        source_id bigint references app.sources (id) on delete cascade,
+        created_at timestamptz not null default now(),
+        primary key (tenant_id, key)
+    );
@@ -0,0 +1,16 @@
+-- The mutations of sources, for compliance. The entries are kept when their source is purged, so
+-- the source id isn't a foreign key. Configs are only recorded as a hash of their fields without
+-- the password, so that no secret ends up in the log.
+create table
+    app.audit_log (
+        id bigint generated always as identity primary key,
+        tenant_id text references app.tenants (id) on delete cascade not null,
+        source_id bigint not null,
+        action text not null check (
+            action in ('create', 'update', 'rotate_credentials', 'delete', 'restore')
+        ),
+        config_hash text,
+        created_at timestamptz not null default now()
+    );
+
+create index audit_log_tenant_id_source_id_idx on app.audit_log (tenant_id, source_id, id);
@@ -0,0 +1,13 @@
+-- The api keys of tenants, which authenticate requests as their tenant. Only a SHA-256 hash of the
+-- keys is stored, the keys themselves are returned once when they are created.
+create table
+    app.api_keys (
+        id bigint generated always as identity primary key,
+        tenant_id text references app.tenants (id) on delete cascade not null,
+        name text not null,
+        key_hash text not null unique,
+        scope text not null check (scope in ('read', 'read_write')),
+        created_at timestamptz not null default now()
+    );
+
+create index api_keys_tenant_id_idx on app.api_keys (tenant_id);
@@ -1,47 +1,267 @@
-use actix_web::{Error, dev::ServiceRequest, web::Data};
+use actix_web::{
+    Error, HttpResponse, ResponseError,
+    dev::ServiceRequest,
+    http::{
+        Method, StatusCode,
+        header::{ContentType, HeaderName, HeaderValue},
+    },
+    web::Data,
+};
 use actix_web_httpauth::extractors::{
     AuthenticationError,
     bearer::{BearerAuth, Config},
 };
 use constant_time_eq::constant_time_eq_n;
+use sqlx::PgPool;
+use thiserror::Error;
 
 use crate::config::{ApiConfig, ApiKey};
+use crate::db;
+use crate::db::api_keys::{ApiKeyScope, ApiKeysDbError, TenantApiKey};
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE};
+
+#[derive(Debug, Error)]
+pub enum ApiKeyAuthError {
+    #[error("The api key only has the read scope, which doesn't allow {0} requests")]
+    ReadOnlyScope(Method),
+
+    #[error("The api keys of tenants are not allowed to {0} {1}")]
+    DeploymentOnly(Method, String),
+
+    #[error("The api keys of tenants are not allowed to {0} {1}, which matches no endpoint")]
+    UnmatchedPath(Method, String),
+
+    #[error("The tenant id of the api key can't be set in the request")]
+    TenantIdIllFormed,
+
+    #[error(transparent)]
+    ApiKeysDb(#[from] ApiKeysDbError),
+}
+
+impl ApiKeyAuthError {
+    pub fn to_message(&self) -> String {
+        match self {
+            // Do not expose internal database details in error messages
+            ApiKeyAuthError::ApiKeysDb(_) | ApiKeyAuthError::TenantIdIllFormed => {
+                "internal server error".to_string()
+            }
+            // Every other message is ok, as they do not divulge sensitive information
+            e => e.to_string(),
+        }
+    }
 
+    pub fn to_code(&self) -> &'static str {
+        match self {
+            ApiKeyAuthError::ReadOnlyScope(_) => "read_only_api_key",
+            ApiKeyAuthError::DeploymentOnly(_, _) | ApiKeyAuthError::UnmatchedPath(_, _) => {
+                "deployment_api_key_required"
+            }
+            ApiKeyAuthError::ApiKeysDb(_) | ApiKeyAuthError::TenantIdIllFormed => {
+                INTERNAL_ERROR_CODE
+            }
+        }
+    }
+}
+
+impl ResponseError for ApiKeyAuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiKeyAuthError::ApiKeysDb(_) | ApiKeyAuthError::TenantIdIllFormed => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            ApiKeyAuthError::ReadOnlyScope(_)
+            | ApiKeyAuthError::DeploymentOnly(_, _)
+            | ApiKeyAuthError::UnmatchedPath(_, _) => StatusCode::FORBIDDEN,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error_message = ErrorMessage {
+            code: self.to_code(),
+            error: self.to_message(),
+        };
+        let body =
+            serde_json::to_string(&error_message).expect("failed to serialize error message");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+/// Validates requests to the `/v1` endpoints.
+///
+/// Requests are either authenticated by the api key of the deployment, which can access every
+/// endpoint of every tenant, or by an api key of a tenant, which is checked by
+/// [`authorize_tenant_api_key`]. The `tenant_id` header of the requests authenticated by a key of
+/// a tenant is set to the tenant of the key, so that they can't access other tenants.
 pub async fn auth_validator(
+    mut req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    let api_key = req
+        .app_data::<Data<ApiConfig>>()
+        .expect("missing api configuration")
+        .api_key
+        .clone();
+
+    if token_matches(credentials.token(), Some(&api_key)) {
+        return Ok(req);
+    }
+
+    let pool = req
+        .app_data::<Data<PgPool>>()
+        .expect("missing connection pool")
+        .clone();
+    let key_hash = db::api_keys::hash_api_key(credentials.token());
+    let tenant_api_key = match db::api_keys::read_api_key_by_hash(&**pool, &key_hash).await {
+        Ok(Some(tenant_api_key)) => tenant_api_key,
+        Ok(None) => return Err((authentication_error(&req, "v1"), req)),
+        Err(e) => return Err((ApiKeyAuthError::from(e).into(), req)),
+    };
+
+    // The routes are matched against the percent-decoded path, e.g. `/v1/%74enants` is routed to
+    // `/v1/tenants`, so the key is authorized against the pattern of the route matching the path
+    // rather than the path itself. A path matching no route can't be authorized.
+    let Some(route) = req.match_pattern() else {
+        let e = ApiKeyAuthError::UnmatchedPath(req.method().clone(), req.path().to_string());
+        return Err((e.into(), req));
+    };
+    if let Err(e) = authorize_tenant_api_key(&tenant_api_key, req.method(), &route) {
+        return Err((e.into(), req));
+    }
+
+    let Ok(tenant_id) = HeaderValue::from_str(&tenant_api_key.tenant_id) else {
+        return Err((ApiKeyAuthError::TenantIdIllFormed.into(), req));
+    };
+    req.headers_mut()
+        .insert(HeaderName::from_static("tenant_id"), tenant_id);
+
+    Ok(req)
+}
+
+/// Checks that an api key of a tenant may send a `method` request to the endpoint whose route has
+/// the `route` pattern, e.g. `/v1/sources/{source_id}`.
+///
+/// Keys of tenants can't manage tenants or api keys, nor change the images shared by all the
+/// tenants, which is left to the api key of the deployment. Keys with the [`ApiKeyScope::Read`]
+/// scope can only send `GET` and `HEAD` requests.
+pub fn authorize_tenant_api_key(
+    tenant_api_key: &TenantApiKey,
+    method: &Method,
+    route: &str,
+) -> Result<(), ApiKeyAuthError> {
+    let is_read = *method == Method::GET || *method == Method::HEAD;
+
+    // Also matches the `/v1/tenants-sources` endpoint, which creates tenants.
+    let deployment_only = route.starts_with("/v1/tenants")
+        || route.starts_with("/v1/api-keys")
+        || (route.starts_with("/v1/images") && !is_read);
+    if deployment_only {
+        return Err(ApiKeyAuthError::DeploymentOnly(
+            method.clone(),
+            route.to_string(),
+        ));
+    }
+
+    if tenant_api_key.scope == ApiKeyScope::Read && !is_read {
+        return Err(ApiKeyAuthError::ReadOnlyScope(method.clone()));
+    }
+
+    Ok(())
+}
+
+/// Validates requests to the `/admin` endpoints, which are not scoped to a tenant and are only
+/// meant for operators.
+///
+/// Requests are rejected if no admin api key is configured.
+pub async fn admin_auth_validator(
     req: ServiceRequest,
     credentials: BearerAuth,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    let admin_api_key = req
+        .app_data::<Data<ApiConfig>>()
+        .expect("missing api configuration")
+        .admin_api_key
+        .clone();
+
+    if !token_matches(credentials.token(), admin_api_key.as_deref()) {
+        return Err((authentication_error(&req, "admin"), req));
+    }
+
+    Ok(req)
+}
+
+fn authentication_error(req: &ServiceRequest, scope: &'static str) -> Error {
     let config = req
         .app_data::<Config>()
         .cloned()
         .unwrap_or_default()
-        .scope("v1");
+        .scope(scope);
 
-    let api_key = req
-        .app_data::<Data<ApiConfig>>()
-        .expect("missing api configuration")
-        .api_key
-        .as_str();
+    AuthenticationError::from(config).into()
+}
 
-    let token = credentials.token();
+/// Returns whether `token` is `api_key`, compared in constant time.
+fn token_matches(token: &str, api_key: Option<&str>) -> bool {
+    let Some(api_key) = api_key else {
+        return false;
+    };
 
-    let api_key: ApiKey = match api_key.try_into() {
-        Ok(api_key) => api_key,
-        Err(_) => {
-            return Err((AuthenticationError::from(config).into(), req));
-        }
+    let Ok(api_key): Result<ApiKey, _> = api_key.try_into() else {
+        return false;
     };
 
-    let token: ApiKey = match token.try_into() {
-        Ok(token) => token,
-        Err(_) => {
-            return Err((AuthenticationError::from(config).into(), req));
-        }
+    let Ok(token): Result<ApiKey, _> = token.try_into() else {
+        return false;
     };
 
-    if !constant_time_eq_n(&api_key.key, &token.key) {
-        return Err((AuthenticationError::from(config).into(), req));
+    constant_time_eq_n(&api_key.key, &token.key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenant_api_key(scope: ApiKeyScope) -> TenantApiKey {
+        TenantApiKey {
+            id: 1,
+            tenant_id: "abczjjlmfsijwrlnwatw".to_string(),
+            scope,
+        }
     }
 
-    Ok(req)
+    #[test]
+    fn read_keys_can_only_read() {
+        let key = tenant_api_key(ApiKeyScope::Read);
+
+        assert!(authorize_tenant_api_key(&key, &Method::GET, "/v1/sources").is_ok());
+        assert!(authorize_tenant_api_key(&key, &Method::GET, "/v1/images").is_ok());
+        assert!(matches!(
+            authorize_tenant_api_key(&key, &Method::POST, "/v1/sources"),
+            Err(ApiKeyAuthError::ReadOnlyScope(method)) if method == Method::POST
+        ));
+        assert!(matches!(
+            authorize_tenant_api_key(&key, &Method::DELETE, "/v1/sources/{source_id}"),
+            Err(ApiKeyAuthError::ReadOnlyScope(method)) if method == Method::DELETE
+        ));
+    }
+
+    #[test]
+    fn tenant_keys_cannot_access_deployment_endpoints() {
+        let key = tenant_api_key(ApiKeyScope::ReadWrite);
+
+        assert!(authorize_tenant_api_key(&key, &Method::POST, "/v1/sources").is_ok());
+        for (method, path) in [
+            (Method::GET, "/v1/tenants"),
+            (Method::PUT, "/v1/tenants/{tenant_id}"),
+            (Method::POST, "/v1/tenants-sources"),
+            (Method::GET, "/v1/api-keys"),
+            (Method::POST, "/v1/images"),
+        ] {
+            assert!(matches!(
+                authorize_tenant_api_key(&key, &method, path),
+                Err(ApiKeyAuthError::DeploymentOnly(_, _))
+            ));
+        }
+    }
 }
@@ -2,15 +2,17 @@ use base64::{Engine, prelude::BASE64_STANDARD};
 use config::shared::{PgConnectionConfig, SentryConfig};
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, de};
+use std::collections::HashMap;
 use std::fmt;
+use std::num::NonZeroUsize;
 use thiserror::Error;
 
 /// The length in bytes required for a valid API key.
-const API_KEY_LENGTH_IN_BYTES: usize = 32;
+pub const API_KEY_LENGTH_IN_BYTES: usize = 32;
 
 /// Top-level configuration for the API service.
 ///
-/// Contains database, application, encryption, API key, and optional Sentry settings.
+/// Contains database, application, encryption, API keys, and optional Sentry settings.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ApiConfig {
     /// Database connection configuration.
@@ -19,10 +21,102 @@ pub struct ApiConfig {
     pub application: ApplicationSettings,
     /// Encryption key configuration.
     pub encryption_key: EncryptionKey,
+    /// The previous global encryption key, while the global key is rotated.
+    ///
+    /// Values encrypted with this key are still decrypted, and can be re-encrypted with
+    /// [`ApiConfig::encryption_key`] with the `/admin/encryption/reencrypt-sources` endpoint. Its
+    /// id must differ from the ids of all the other keys.
+    #[serde(default)]
+    pub previous_encryption_key: Option<EncryptionKey>,
+    /// Per-tenant encryption keys, by tenant id.
+    ///
+    /// Tenants without a key here use [`ApiConfig::encryption_key`]. The ids of these keys must
+    /// differ from the id of the global key, which is still used to decrypt the values encrypted
+    /// before a tenant got its own key.
+    #[serde(default)]
+    pub tenant_encryption_keys: HashMap<String, EncryptionKey>,
     /// Base64-encoded API key string.
     pub api_key: String,
+    /// Base64-encoded API key string for the operator-only `/admin` endpoints.
+    ///
+    /// If not set, every request to the admin endpoints is rejected.
+    #[serde(default)]
+    pub admin_api_key: Option<String>,
     /// Optional Sentry configuration for error tracking.
     pub sentry: Option<SentryConfig>,
+    /// How tenant ids are written to the request logs and spans.
+    #[serde(default)]
+    pub tenant_id_logging: TenantIdLogging,
+    /// Limits of the number of requests each tenant can make to the `/v1` endpoints.
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Limits of the connections opened to the source databases to validate and introspect them.
+    #[serde(default)]
+    pub source_connections: SourceConnectionsConfig,
+}
+
+/// Limits of the number of requests each tenant can make, in fixed windows of time.
+///
+/// Requests over the limit are rejected with a `429 Too Many Requests` until the window of the
+/// tenant ends.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    /// Maximum number of requests of a tenant in a window.
+    pub requests_per_window: u32,
+    /// Length of a window, in seconds.
+    pub window_secs: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_window: 1000,
+            window_secs: 60,
+        }
+    }
+}
+
+/// Limits of the connections the API opens to each source database, e.g. to read its tables or
+/// manage its publications.
+///
+/// Requests which can't connect to a source within the timeout fail, so that many concurrent
+/// requests can't exhaust the connections of a source database.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceConnectionsConfig {
+    /// Maximum number of connections open at the same time to a source database.
+    ///
+    /// Configs setting it to `0` are rejected when loaded, since no request could connect to a
+    /// source.
+    pub max_connections_per_source: NonZeroUsize,
+    /// Maximum time to connect to a source database, including the time spent waiting for other
+    /// connections to it to be closed, in seconds.
+    pub connect_timeout_secs: u64,
+}
+
+impl Default for SourceConnectionsConfig {
+    fn default() -> Self {
+        Self {
+            max_connections_per_source: NonZeroUsize::new(5).unwrap(),
+            connect_timeout_secs: 10,
+        }
+    }
+}
+
+/// How tenant ids are written to the request logs and spans.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantIdLogging {
+    /// Tenant ids are written as is.
+    #[default]
+    Raw,
+    /// Tenant ids are replaced by a salted hash, so that the logs of a tenant can be correlated
+    /// without exposing its id.
+    ///
+    /// The salt is generated randomly when the API starts, so a tenant id is always hashed to the
+    /// same value within a process, but not across restarts or replicas. Tenant ids which are
+    /// part of a request's path, i.e. on the `/tenants/{tenant_id}` routes, are still written as
+    /// is in the `http.target` field of the request span.
+    Hashed,
 }
 
 /// Network and server settings for the API.
@@ -0,0 +1,213 @@
+use aws_lc_rs::digest::{SHA256, digest};
+use aws_lc_rs::rand::fill;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::config::API_KEY_LENGTH_IN_BYTES;
+
+/// The operations allowed to an api key of a tenant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Only reads, i.e. `GET` and `HEAD` requests.
+    Read,
+    /// Reads and writes.
+    ReadWrite,
+}
+
+impl ApiKeyScope {
+    /// Returns the name of the scope, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => "read",
+            ApiKeyScope::ReadWrite => "read_write",
+        }
+    }
+
+    fn from_db(scope: &str) -> Result<Self, ApiKeysDbError> {
+        match scope {
+            "read" => Ok(ApiKeyScope::Read),
+            "read_write" => Ok(ApiKeyScope::ReadWrite),
+            scope => Err(ApiKeysDbError::UnknownScope(scope.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ApiKeysDbError {
+    #[error("Error while interacting with PostgreSQL for api keys: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("The api key scope {0} is unknown")]
+    UnknownScope(String),
+}
+
+pub struct ApiKey {
+    pub id: i64,
+    pub tenant_id: String,
+    pub name: String,
+    pub scope: ApiKeyScope,
+    /// When the key was created, in the RFC 3339 format in UTC.
+    pub created_at: String,
+}
+
+/// The tenant and scope of an api key, looked up when authenticating a request.
+pub struct TenantApiKey {
+    pub id: i64,
+    pub tenant_id: String,
+    pub scope: ApiKeyScope,
+}
+
+/// Generates a new api key, in the same base64 format as the api key of the deployment.
+pub fn generate_api_key() -> Result<String, aws_lc_rs::error::Unspecified> {
+    let mut key = [0u8; API_KEY_LENGTH_IN_BYTES];
+    fill(&mut key)?;
+
+    Ok(BASE64_STANDARD.encode(key))
+}
+
+/// Returns the hex encoded SHA-256 hash of `api_key`, which is what is stored of the key.
+///
+/// The keys are random, so a fast unsalted hash is enough to keep them from being recovered.
+pub fn hash_api_key(api_key: &str) -> String {
+    digest(&SHA256, api_key.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub async fn create_api_key<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    name: &str,
+    key_hash: &str,
+    scope: ApiKeyScope,
+) -> Result<i64, ApiKeysDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let record = sqlx::query!(
+        r#"
+        insert into app.api_keys (tenant_id, name, key_hash, scope)
+        values ($1, $2, $3, $4)
+        returning id
+        "#,
+        tenant_id,
+        name,
+        key_hash,
+        scope.as_str()
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(record.id)
+}
+
+/// Reads the api key whose hash is `key_hash`, as returned by [`hash_api_key`].
+pub async fn read_api_key_by_hash<'c, E>(
+    executor: E,
+    key_hash: &str,
+) -> Result<Option<TenantApiKey>, ApiKeysDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let record = sqlx::query!(
+        r#"
+        select id, tenant_id, scope
+        from app.api_keys
+        where key_hash = $1
+        "#,
+        key_hash
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    record
+        .map(|record| {
+            Ok(TenantApiKey {
+                id: record.id,
+                tenant_id: record.tenant_id,
+                scope: ApiKeyScope::from_db(&record.scope)?,
+            })
+        })
+        .transpose()
+}
+
+pub async fn read_all_api_keys<'c, E>(
+    executor: E,
+    tenant_id: &str,
+) -> Result<Vec<ApiKey>, ApiKeysDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let records = sqlx::query!(
+        r#"
+        select id, tenant_id, name, scope,
+            to_char(created_at at time zone 'utc', 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"') as "created_at!"
+        from app.api_keys
+        where tenant_id = $1
+        order by id
+        "#,
+        tenant_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    records
+        .into_iter()
+        .map(|record| {
+            Ok(ApiKey {
+                id: record.id,
+                tenant_id: record.tenant_id,
+                name: record.name,
+                scope: ApiKeyScope::from_db(&record.scope)?,
+                created_at: record.created_at,
+            })
+        })
+        .collect()
+}
+
+pub async fn delete_api_key<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    api_key_id: i64,
+) -> Result<Option<i64>, ApiKeysDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let record = sqlx::query!(
+        r#"
+        delete from app.api_keys
+        where tenant_id = $1 and id = $2
+        returning id
+        "#,
+        tenant_id,
+        api_key_id
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(record.map(|r| r.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_api_keys_are_distinct_and_hashed_to_hex() {
+        let first = generate_api_key().unwrap();
+        let second = generate_api_key().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(BASE64_STANDARD.decode(&first).unwrap().len(), 32);
+
+        let hash = hash_api_key(&first);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key(&first));
+        assert_ne!(hash, hash_api_key(&second));
+    }
+}
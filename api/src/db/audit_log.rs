@@ -0,0 +1,152 @@
+use aws_lc_rs::digest::{SHA256, digest};
+use sqlx::PgExecutor;
+use thiserror::Error;
+
+use crate::db::sources::SourceConfig;
+
+/// A mutation of a source recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceAction {
+    Create,
+    Update,
+    RotateCredentials,
+    Delete,
+    Restore,
+}
+
+impl SourceAction {
+    /// Returns the name of the action, as stored in the audit log.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceAction::Create => "create",
+            SourceAction::Update => "update",
+            SourceAction::RotateCredentials => "rotate_credentials",
+            SourceAction::Delete => "delete",
+            SourceAction::Restore => "restore",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AuditLogDbError {
+    #[error("Error while interacting with PostgreSQL for the audit log: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub source_id: i64,
+    pub action: String,
+    pub config_hash: Option<String>,
+    /// When the action was recorded, in the RFC 3339 format in UTC.
+    pub created_at: String,
+}
+
+/// Returns the hex encoded SHA-256 hash of `config`, which tells whether two configs are the same
+/// without revealing them.
+///
+/// The password is left out of the hash, since the other fields are known to the readers of the
+/// audit log who could otherwise brute force it. Changes of credentials are recorded by their own
+/// [`SourceAction::RotateCredentials`] action instead.
+pub fn hash_source_config(config: &SourceConfig) -> String {
+    let mut config = config.clone();
+    config.password = None;
+    let config = serde_json::to_vec(&config).expect("failed to serialize source config");
+
+    digest(&SHA256, &config)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Records `action` on the source `source_id` in the audit log, along with the hash of its new
+/// config returned by [`hash_source_config`] if the action changed it.
+pub async fn insert_source_audit_entry<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    source_id: i64,
+    action: SourceAction,
+    config_hash: Option<&str>,
+) -> Result<(), AuditLogDbError>
+where
+    E: PgExecutor<'c>,
+{
+    sqlx::query!(
+        r#"
+        insert into app.audit_log (tenant_id, source_id, action, config_hash)
+        values ($1, $2, $3, $4)
+        "#,
+        tenant_id,
+        source_id,
+        action.as_str(),
+        config_hash
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Reads the audit log of the source `source_id`, from the oldest to the newest entry.
+pub async fn read_source_audit_log<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    source_id: i64,
+) -> Result<Vec<AuditLogEntry>, AuditLogDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let records = sqlx::query!(
+        r#"
+        select id, source_id, action, config_hash,
+            to_char(created_at at time zone 'utc', 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"') as "created_at!"
+        from app.audit_log
+        where tenant_id = $1 and source_id = $2
+        order by id
+        "#,
+        tenant_id,
+        source_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| AuditLogEntry {
+            id: record.id,
+            source_id: record.source_id,
+            action: record.action,
+            config_hash: record.config_hash,
+            created_at: record.created_at,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use config::SerializableSecretString;
+
+    use super::*;
+
+    #[test]
+    pub fn source_config_hash_ignores_the_password() {
+        let mut config = SourceConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            name: "postgres".to_string(),
+            username: "postgres".to_string(),
+            password: Some(SerializableSecretString::from("postgres".to_string())),
+            ssl_mode: None,
+            root_cert: None,
+        };
+        let hash = hash_source_config(&config);
+        assert_eq!(hash.len(), 64);
+
+        config.password = Some(SerializableSecretString::from("supersecret".to_string()));
+        assert_eq!(hash_source_config(&config), hash);
+
+        config.port = 6543;
+        assert_ne!(hash_source_config(&config), hash);
+    }
+}
@@ -1,5 +1,9 @@
+use async_trait::async_trait;
 use config::SerializableSecretString;
-use config::shared::DestinationConfig;
+use config::shared::{
+    ColumnFilterConfig, DestinationConfig, IdentifierOverflowPolicy,
+    RowSizeLimitConfig, SchemaMapping,
+};
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 use sqlx::PgExecutor;
@@ -11,14 +15,14 @@ use crate::db::serde::{
     encrypt_and_serialize,
 };
 use crate::encryption::{
-    Decrypt, DecryptionError, Encrypt, EncryptedValue, EncryptionError, EncryptionKey,
-    decrypt_text, encrypt_text,
+    Decrypt, DecryptionError, Encrypt, EncryptedValue, EncryptionError, Encryptor,
 };
 
+#[async_trait]
 impl Encrypt<EncryptedDestinationConfig> for DestinationConfig {
-    fn encrypt(
+    async fn encrypt(
         self,
-        encryption_key: &EncryptionKey,
+        encryptor: &dyn Encryptor,
     ) -> Result<EncryptedDestinationConfig, EncryptionError> {
         match self {
             Self::Memory => Ok(EncryptedDestinationConfig::Memory),
@@ -27,17 +31,26 @@ impl Encrypt<EncryptedDestinationConfig> for DestinationConfig {
                 dataset_id,
                 service_account_key,
                 max_staleness_mins,
+                column_filter,
+                max_concurrent_writes,
+                identifier_overflow,
+                row_size_limit,
+                schema_mapping,
             } => {
-                let encrypted_service_account_key = encrypt_text(
-                    service_account_key.expose_secret().to_owned(),
-                    encryption_key,
-                )?;
+                let encrypted_service_account_key = encryptor
+                    .encrypt(service_account_key.expose_secret().to_owned())
+                    .await?;
 
                 Ok(EncryptedDestinationConfig::BigQuery {
                     project_id,
                     dataset_id,
                     service_account_key: encrypted_service_account_key,
                     max_staleness_mins,
+                    column_filter,
+                    max_concurrent_writes,
+                    identifier_overflow,
+                    row_size_limit,
+                    schema_mapping,
                 })
             }
         }
@@ -54,11 +67,25 @@ pub enum EncryptedDestinationConfig {
         service_account_key: EncryptedValue,
         #[serde(skip_serializing_if = "Option::is_none")]
         max_staleness_mins: Option<u16>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        column_filter: Option<ColumnFilterConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_concurrent_writes: Option<u16>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        identifier_overflow: Option<IdentifierOverflowPolicy>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        row_size_limit: Option<RowSizeLimitConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        schema_mapping: Option<SchemaMapping>,
     },
 }
 
+#[async_trait]
 impl Decrypt<DestinationConfig> for EncryptedDestinationConfig {
-    fn decrypt(self, encryption_key: &EncryptionKey) -> Result<DestinationConfig, DecryptionError> {
+    async fn decrypt(
+        self,
+        encryptor: &dyn Encryptor,
+    ) -> Result<DestinationConfig, DecryptionError> {
         match self {
             Self::Memory => Ok(DestinationConfig::Memory),
             Self::BigQuery {
@@ -66,17 +93,26 @@ impl Decrypt<DestinationConfig> for EncryptedDestinationConfig {
                 dataset_id,
                 service_account_key: encrypted_service_account_key,
                 max_staleness_mins,
+                column_filter,
+                max_concurrent_writes,
+                identifier_overflow,
+                row_size_limit,
+                schema_mapping,
             } => {
-                let service_account_key = SerializableSecretString::from(decrypt_text(
-                    encrypted_service_account_key,
-                    encryption_key,
-                )?);
+                let service_account_key = SerializableSecretString::from(
+                    encryptor.decrypt(encrypted_service_account_key).await?,
+                );
 
                 Ok(DestinationConfig::BigQuery {
                     project_id,
                     dataset_id,
                     service_account_key,
                     max_staleness_mins,
+                    column_filter,
+                    max_concurrent_writes,
+                    identifier_overflow,
+                    row_size_limit,
+                    schema_mapping,
                 })
             }
         }
@@ -107,12 +143,12 @@ pub async fn create_destination<'c, E>(
     tenant_id: &str,
     name: &str,
     config: DestinationConfig,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<i64, DestinationsDbError>
 where
     E: PgExecutor<'c>,
 {
-    let config = encrypt_and_serialize(config, encryption_key)?;
+    let config = encrypt_and_serialize(config, encryptor).await?;
 
     let record = sqlx::query!(
         r#"
@@ -134,7 +170,7 @@ pub async fn read_destination<'c, E>(
     executor: E,
     tenant_id: &str,
     destination_id: i64,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<Option<Destination>, DestinationsDbError>
 where
     E: PgExecutor<'c>,
@@ -156,7 +192,8 @@ where
             let config = decrypt_and_deserialize_from_value::<
                 EncryptedDestinationConfig,
                 DestinationConfig,
-            >(record.config, encryption_key)?;
+            >(record.config, encryptor)
+            .await?;
 
             let destination = Destination {
                 id: record.id,
@@ -179,12 +216,12 @@ pub async fn update_destination<'c, E>(
     name: &str,
     destination_id: i64,
     config: DestinationConfig,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<Option<i64>, DestinationsDbError>
 where
     E: PgExecutor<'c>,
 {
-    let config = encrypt_and_serialize(config, encryption_key)?;
+    let config = encrypt_and_serialize(config, encryptor).await?;
 
     let record = sqlx::query!(
         r#"
@@ -230,7 +267,7 @@ where
 pub async fn read_all_destinations<'c, E>(
     executor: E,
     tenant_id: &str,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<Vec<Destination>, DestinationsDbError>
 where
     E: PgExecutor<'c>,
@@ -251,7 +288,8 @@ where
         let config = decrypt_and_deserialize_from_value::<
             EncryptedDestinationConfig,
             DestinationConfig,
-        >(record.config.clone(), encryption_key)?;
+        >(record.config.clone(), encryptor)
+        .await?;
 
         let destination = Destination {
             id: record.id,
@@ -320,13 +358,18 @@ mod tests {
             dataset_id: "dataset-id".to_string(),
             service_account_key: SerializableSecretString::from("service-account-key".to_string()),
             max_staleness_mins: Some(42),
+            column_filter: None,
+            max_concurrent_writes: None,
+            identifier_overflow: None,
+            row_size_limit: None,
+            schema_mapping: None,
         };
 
         insta::assert_json_snapshot!(config);
     }
 
-    #[test]
-    pub fn destination_config_json_encryption() {
+    #[tokio::test]
+    pub async fn destination_config_json_encryption() {
         let key_bytes = [42u8; 32];
         let key = RandomizedNonceKey::new(&aws_lc_rs::aead::AES_256_GCM, &key_bytes).unwrap();
         let encryption_key = EncryptionKey { id: 1, key };
@@ -336,12 +379,18 @@ mod tests {
             dataset_id: "dataset-id".to_string(),
             service_account_key: SerializableSecretString::from("supersecretkey".to_string()),
             max_staleness_mins: Some(99),
+            column_filter: None,
+            max_concurrent_writes: None,
+            identifier_overflow: None,
+            row_size_limit: None,
+            schema_mapping: None,
         };
 
         let config_in_db = encrypt_and_serialize::<DestinationConfig, EncryptedDestinationConfig>(
             config.clone(),
             &encryption_key,
         )
+        .await
         .unwrap();
         insta::assert_json_snapshot!(config_in_db, {
             ".big_query.service_account_key" => "[key]"
@@ -351,6 +400,7 @@ mod tests {
             EncryptedDestinationConfig,
             DestinationConfig,
         >(config_in_db, &encryption_key)
+        .await
         .unwrap();
         insta::assert_debug_snapshot!(deserialized_config);
     }
@@ -6,7 +6,7 @@ use thiserror::Error;
 use crate::db::destinations::{DestinationsDbError, create_destination, update_destination};
 use crate::db::pipelines::{PipelineConfig, PipelinesDbError, create_pipeline, update_pipeline};
 use crate::db::serde::{DbDeserializationError, DbSerializationError};
-use crate::encryption::EncryptionKey;
+use crate::encryption::Encryptor;
 
 #[derive(Debug, Error)]
 pub enum DestinationPipelinesDbError {
@@ -41,14 +41,14 @@ pub async fn create_destination_and_pipeline(
     destination_config: DestinationConfig,
     image_id: i64,
     pipeline_config: PipelineConfig,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<(i64, i64), DestinationPipelinesDbError> {
     let destination_id = create_destination(
         txn.deref_mut(),
         tenant_id,
         destination_name,
         destination_config,
-        encryption_key,
+        encryptor,
     )
     .await?;
 
@@ -75,7 +75,7 @@ pub async fn update_destination_and_pipeline(
     destination_name: &str,
     destination_config: DestinationConfig,
     pipeline_config: PipelineConfig,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<(), DestinationPipelinesDbError> {
     let destination_id_res = update_destination(
         txn.deref_mut(),
@@ -83,7 +83,7 @@ pub async fn update_destination_and_pipeline(
         destination_name,
         destination_id,
         destination_config,
-        encryption_key,
+        encryptor,
     )
     .await?;
 
@@ -0,0 +1,57 @@
+use pg_escape::quote_identifier;
+use sqlx::{Executor, PgPool, Row};
+use thiserror::Error;
+
+/// The schema holding the control-plane tables.
+const APP_SCHEMA: &str = "app";
+
+#[derive(Debug, Error)]
+pub enum MaintenanceDbError {
+    #[error("Error while running maintenance on the control-plane tables: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Runs `analyze`, or `vacuum (analyze)` when `vacuum` is set, on every control-plane table.
+///
+/// Returns the qualified names of the processed tables. The statements are sent outside of any
+/// transaction, since `vacuum` can't run inside a transaction block.
+pub async fn run_maintenance(
+    pool: &PgPool,
+    vacuum: bool,
+) -> Result<Vec<String>, MaintenanceDbError> {
+    let table_names: Vec<String> = sqlx::query(
+        r#"
+        select tablename
+        from pg_catalog.pg_tables
+        where schemaname = $1
+        order by tablename
+        "#,
+    )
+    .bind(APP_SCHEMA)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| row.get("tablename"))
+    .collect();
+
+    let command = if vacuum {
+        "vacuum (analyze)"
+    } else {
+        "analyze"
+    };
+
+    let mut processed_tables = Vec::with_capacity(table_names.len());
+    for table_name in table_names {
+        let qualified_name = format!(
+            "{}.{}",
+            quote_identifier(APP_SCHEMA),
+            quote_identifier(&table_name)
+        );
+        pool.execute(format!("{command} {qualified_name}").as_str())
+            .await?;
+
+        processed_tables.push(format!("{APP_SCHEMA}.{table_name}"));
+    }
+
+    Ok(processed_tables)
+}
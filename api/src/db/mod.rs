@@ -1,11 +1,17 @@
+pub mod api_keys;
+pub mod audit_log;
 pub mod destinations;
 pub mod destinations_pipelines;
 pub mod images;
+pub mod maintenance;
 pub mod pipelines;
 pub mod publications;
+pub mod replication_slots;
 pub mod replicators;
 mod serde;
 pub mod sources;
+pub mod table_copies;
+pub mod table_resyncs;
 pub mod tables;
 pub mod tenants;
 pub mod tenants_sources;
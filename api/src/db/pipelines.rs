@@ -1,4 +1,4 @@
-use config::shared::{BatchConfig, RetryConfig};
+use config::shared::{BatchConfig, RetryConfig, ValidationError};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgExecutor, PgTransaction};
 use std::ops::DerefMut;
@@ -20,6 +20,18 @@ pub struct PipelineConfig {
     pub max_table_sync_workers: Option<u16>,
 }
 
+impl PipelineConfig {
+    /// Validates the settings of the [`PipelineConfig`] which have constraints, i.e. the
+    /// [`PipelineConfig::batch`] config if it's set.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(batch) = &self.batch {
+            batch.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
 pub struct Pipeline {
     pub id: i64,
     pub tenant_id: String,
@@ -229,6 +241,31 @@ where
     Ok(pipelines)
 }
 
+/// Reads the ids of the pipelines replicating from the source with id `source_id`.
+pub async fn read_source_pipeline_ids<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    source_id: i64,
+) -> Result<Vec<i64>, PipelinesDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let records = sqlx::query!(
+        r#"
+        select id
+        from app.pipelines
+        where tenant_id = $1 and source_id = $2
+        order by id
+        "#,
+        tenant_id,
+        source_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(records.into_iter().map(|r| r.id).collect())
+}
+
 /// Helper function to check if an sqlx error is a duplicate pipeline constraint violation
 pub fn is_duplicate_pipeline_error(err: &sqlx::Error) -> bool {
     match err {
@@ -1,26 +1,62 @@
 use pg_escape::{quote_identifier, quote_literal};
-use serde::Serialize;
-use sqlx::{Connection, Executor, PgConnection, Row, postgres::PgConnectOptions};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgConnection, Row, postgres::PgConnectOptions};
 use std::collections::HashMap;
 use thiserror::Error;
 use utoipa::ToSchema;
 
-use crate::db::tables::Table;
+use crate::source_connections::SourceConnectionLimiter;
 
 #[derive(Debug, Error)]
 pub enum PublicationsDbError {
     #[error("Error while interacting with PostgreSQL for publications: {0}")]
     Database(#[from] sqlx::Error),
+
+    #[error(
+        "The replica identity of the tables {} is `nothing`, so their updates and deletes can't be replicated",
+        .0.join(", ")
+    )]
+    MissingReplicaIdentity(Vec<String>),
+
+    #[error("The column list of the table {0} is empty")]
+    EmptyColumnList(String),
+
+    #[error("The row filter of the table {0} is invalid: {1}")]
+    InvalidRowFilter(String, String),
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct Publication {
     pub name: String,
-    pub tables: Vec<Table>,
+    pub tables: Vec<PublicationTable>,
+}
+
+/// A table of a publication.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PublicationTable {
+    pub schema: String,
+    pub name: String,
+    /// Names of the published columns, or all of them if not set.
+    ///
+    /// Only the published columns are copied and streamed by the pipelines using the publication.
+    /// Postgres rejects the updates and deletes of rows whose replica identity columns aren't
+    /// published, so they should always be listed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<String>>,
+    /// SQL expression selecting the published rows, e.g. `tenant_id = 42`, or all of them if not
+    /// set.
+    ///
+    /// The rows which don't match the filter are neither copied nor streamed. An update is
+    /// published as an insert or a delete when the row starts or stops matching the filter. Since
+    /// updates and deletes are matched against the replica identity of the old row, Postgres
+    /// rejects the updates and deletes of a table whose filter references other columns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_filter: Option<String>,
 }
 
 pub async fn create_publication(
     publication: &Publication,
+    limiter: &SourceConnectionLimiter,
     options: &PgConnectOptions,
 ) -> Result<(), PublicationsDbError> {
     let mut query = String::new();
@@ -29,19 +65,11 @@ pub async fn create_publication(
     query.push_str(&quoted_publication_name);
     query.push_str(" for table only ");
 
-    for (i, table) in publication.tables.iter().enumerate() {
-        let quoted_schema = quote_identifier(&table.schema);
-        let quoted_name = quote_identifier(&table.name);
-        query.push_str(&quoted_schema);
-        query.push('.');
-        query.push_str(&quoted_name);
+    push_tables(&mut query, &publication.tables)?;
 
-        if i < publication.tables.len() - 1 {
-            query.push(',')
-        }
-    }
-
-    let mut connection = PgConnection::connect_with(options).await?;
+    let mut connection = limiter.connect(options).await?;
+    check_replica_identities(&mut *connection, &publication.tables).await?;
+    check_row_filters(&mut *connection, &publication.tables).await?;
     connection.execute(query.as_str()).await?;
 
     Ok(())
@@ -49,6 +77,7 @@ pub async fn create_publication(
 
 pub async fn update_publication(
     publication: &Publication,
+    limiter: &SourceConnectionLimiter,
     options: &PgConnectOptions,
 ) -> Result<(), PublicationsDbError> {
     let mut query = String::new();
@@ -57,26 +86,275 @@ pub async fn update_publication(
     query.push_str(&quoted_publication_name);
     query.push_str(" set table only ");
 
-    for (i, table) in publication.tables.iter().enumerate() {
+    push_tables(&mut query, &publication.tables)?;
+
+    let mut connection = limiter.connect(options).await?;
+    check_replica_identities(&mut *connection, &publication.tables).await?;
+    check_row_filters(&mut *connection, &publication.tables).await?;
+    connection.execute(query.as_str()).await?;
+
+    Ok(())
+}
+
+/// The outcome of a request to add a table to a publication.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AddPublicationTable {
+    /// The table was added to the publication.
+    Added,
+    PublicationNotFound,
+    TableNotFound,
+    TableAlreadyPublished,
+}
+
+/// The outcome of a request to drop a table from a publication.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DropPublicationTable {
+    /// The table was dropped from the publication.
+    Dropped,
+    PublicationNotFound,
+    TableNotPublished,
+}
+
+/// Whether a publication and a table exist, and whether the table is published.
+struct PublicationTableMembership {
+    publication_exists: bool,
+    table_exists: bool,
+    table_published: bool,
+}
+
+async fn read_publication_table_membership(
+    connection: &mut PgConnection,
+    publication_name: &str,
+    schema: &str,
+    name: &str,
+) -> Result<PublicationTableMembership, PublicationsDbError> {
+    let query = r#"
+        select
+            exists (select 1 from pg_catalog.pg_publication where pubname = $1) as publication_exists,
+            to_regclass(quote_ident($2) || '.' || quote_ident($3)) is not null as table_exists,
+            exists (
+                select 1 from pg_catalog.pg_publication_tables
+                where pubname = $1 and schemaname = $2 and tablename = $3
+            ) as table_published;
+        "#;
+
+    let row = sqlx::query(query)
+        .bind(publication_name)
+        .bind(schema)
+        .bind(name)
+        .fetch_one(&mut *connection)
+        .await?;
+
+    Ok(PublicationTableMembership {
+        publication_exists: row.get("publication_exists"),
+        table_exists: row.get("table_exists"),
+        table_published: row.get("table_published"),
+    })
+}
+
+/// Adds `table` to the publication named `publication_name`, leaving its other tables as they
+/// are.
+///
+/// The pipelines using the publication start copying the table while they're running.
+pub async fn add_publication_table(
+    publication_name: &str,
+    table: &PublicationTable,
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+) -> Result<AddPublicationTable, PublicationsDbError> {
+    let mut query = String::new();
+    query.push_str("alter publication ");
+    query.push_str(&quote_identifier(publication_name));
+    query.push_str(" add table only ");
+
+    let tables = std::slice::from_ref(table);
+    push_tables(&mut query, tables)?;
+
+    let mut connection = limiter.connect(options).await?;
+    let membership = read_publication_table_membership(
+        &mut *connection,
+        publication_name,
+        &table.schema,
+        &table.name,
+    )
+    .await?;
+    let outcome = if !membership.publication_exists {
+        AddPublicationTable::PublicationNotFound
+    } else if !membership.table_exists {
+        AddPublicationTable::TableNotFound
+    } else if membership.table_published {
+        AddPublicationTable::TableAlreadyPublished
+    } else {
+        check_replica_identities(&mut *connection, tables).await?;
+        check_row_filters(&mut *connection, tables).await?;
+        connection.execute(query.as_str()).await?;
+
+        AddPublicationTable::Added
+    };
+
+    connection.close().await?;
+
+    Ok(outcome)
+}
+
+/// Drops the table `schema`.`name` from the publication named `publication_name`, leaving its
+/// other tables as they are.
+///
+/// The pipelines using the publication stop replicating the table while they're running, the rows
+/// they already replicated are kept in their destinations.
+pub async fn drop_publication_table(
+    publication_name: &str,
+    schema: &str,
+    name: &str,
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+) -> Result<DropPublicationTable, PublicationsDbError> {
+    let mut query = String::new();
+    query.push_str("alter publication ");
+    query.push_str(&quote_identifier(publication_name));
+    query.push_str(" drop table only ");
+    query.push_str(&quote_identifier(schema));
+    query.push('.');
+    query.push_str(&quote_identifier(name));
+
+    let mut connection = limiter.connect(options).await?;
+    let membership =
+        read_publication_table_membership(&mut *connection, publication_name, schema, name).await?;
+    let outcome = if !membership.publication_exists {
+        DropPublicationTable::PublicationNotFound
+    } else if !membership.table_published {
+        DropPublicationTable::TableNotPublished
+    } else {
+        connection.execute(query.as_str()).await?;
+
+        DropPublicationTable::Dropped
+    };
+
+    connection.close().await?;
+
+    Ok(outcome)
+}
+
+/// Appends `tables` to `query`, each with its column list and row filter if it has them.
+fn push_tables(query: &mut String, tables: &[PublicationTable]) -> Result<(), PublicationsDbError> {
+    for (i, table) in tables.iter().enumerate() {
         let quoted_schema = quote_identifier(&table.schema);
         let quoted_name = quote_identifier(&table.name);
         query.push_str(&quoted_schema);
         query.push('.');
         query.push_str(&quoted_name);
 
-        if i < publication.tables.len() - 1 {
+        if let Some(columns) = &table.columns {
+            if columns.is_empty() {
+                return Err(PublicationsDbError::EmptyColumnList(format!(
+                    "{quoted_schema}.{quoted_name}"
+                )));
+            }
+
+            let quoted_columns = columns
+                .iter()
+                .map(|column| quote_identifier(column))
+                .collect::<Vec<_>>();
+            query.push_str(" (");
+            query.push_str(&quoted_columns.join(", "));
+            query.push(')');
+        }
+
+        if let Some(row_filter) = &table.row_filter {
+            query.push_str(" where (");
+            query.push_str(row_filter);
+            query.push(')');
+        }
+
+        if i < tables.len() - 1 {
             query.push(',')
         }
     }
 
-    let mut connection = PgConnection::connect_with(options).await?;
-    connection.execute(query.as_str()).await?;
+    Ok(())
+}
+
+/// Fails with [`PublicationsDbError::MissingReplicaIdentity`] if the replica identity of any of
+/// `tables` is `nothing`, since the updates and deletes of such tables are not replicated.
+async fn check_replica_identities(
+    connection: &mut PgConnection,
+    tables: &[PublicationTable],
+) -> Result<(), PublicationsDbError> {
+    let (schemas, names): (Vec<&str>, Vec<&str>) = tables
+        .iter()
+        .map(|table| (table.schema.as_str(), table.name.as_str()))
+        .unzip();
+
+    let query = r#"
+        select t.schema, t.name
+        from unnest($1::text[], $2::text[]) with ordinality as t(schema, name, position)
+            join pg_catalog.pg_namespace n on n.nspname = t.schema
+            join pg_catalog.pg_class c on c.relnamespace = n.oid and c.relname = t.name
+        where c.relreplident = 'n'
+        order by t.position;
+        "#;
+
+    let tables_without_identity: Vec<String> = sqlx::query(query)
+        .bind(schemas)
+        .bind(names)
+        .fetch_all(&mut *connection)
+        .await?
+        .iter()
+        .map(|row| {
+            let schema: String = row.get("schema");
+            let name: String = row.get("name");
+            format!("{}.{}", quote_identifier(&schema), quote_identifier(&name))
+        })
+        .collect();
+
+    if !tables_without_identity.is_empty() {
+        return Err(PublicationsDbError::MissingReplicaIdentity(
+            tables_without_identity,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fails with [`PublicationsDbError::InvalidRowFilter`] if the row filter of any of `tables` isn't
+/// a valid condition on the table.
+///
+/// The filters are checked by preparing a query selecting the rows of the table, which validates
+/// the filter against the schema of the table without running it, and rejects filters which
+/// would add other statements to the query creating the publication.
+async fn check_row_filters(
+    connection: &mut PgConnection,
+    tables: &[PublicationTable],
+) -> Result<(), PublicationsDbError> {
+    for table in tables {
+        let Some(row_filter) = &table.row_filter else {
+            continue;
+        };
+
+        let quoted_table_name = format!(
+            "{}.{}",
+            quote_identifier(&table.schema),
+            quote_identifier(&table.name)
+        );
+        let query = format!("select 1 from {quoted_table_name} where ({row_filter})");
+        match connection.prepare(query.as_str()).await {
+            Ok(_) => {}
+            Err(sqlx::Error::Database(err)) => {
+                return Err(PublicationsDbError::InvalidRowFilter(
+                    quoted_table_name,
+                    err.message().to_string(),
+                ));
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
 
     Ok(())
 }
 
 pub async fn drop_publication(
     publication_name: &str,
+    limiter: &SourceConnectionLimiter,
     options: &PgConnectOptions,
 ) -> Result<(), PublicationsDbError> {
     let mut query = String::new();
@@ -84,7 +362,7 @@ pub async fn drop_publication(
     let quoted_publication_name = quote_identifier(publication_name);
     query.push_str(&quoted_publication_name);
 
-    let mut connection = PgConnection::connect_with(options).await?;
+    let mut connection = limiter.connect(options).await?;
     connection.execute(query.as_str()).await?;
 
     Ok(())
@@ -92,6 +370,7 @@ pub async fn drop_publication(
 
 pub async fn read_publication(
     publication_name: &str,
+    limiter: &SourceConnectionLimiter,
     options: &PgConnectOptions,
 ) -> Result<Option<Publication>, PublicationsDbError> {
     let mut query = String::new();
@@ -99,9 +378,13 @@ pub async fn read_publication(
         r#"
         select p.pubname,
             pt.schemaname as "schemaname?",
-            pt.tablename as "tablename?"
+            pt.tablename as "tablename?",
+            case when pr.prattrs is null then null else pt.attnames::text[] end as "columns?",
+            pt.rowfilter as "rowfilter?"
         from pg_publication p
         left join pg_publication_tables pt on p.pubname = pt.pubname
+        left join pg_publication_rel pr on pr.prpubid = p.oid
+            and pr.prrelid = to_regclass(quote_ident(pt.schemaname) || '.' || quote_ident(pt.tablename))
         where
            	p.puballtables = false
            	and p.pubinsert = true
@@ -115,7 +398,7 @@ pub async fn read_publication(
     let quoted_publication_name = quote_literal(publication_name);
     query.push_str(&quoted_publication_name);
 
-    let mut connection = PgConnection::connect_with(options).await?;
+    let mut connection = limiter.connect(options).await?;
 
     let mut tables = vec![];
     let mut name: Option<String> = None;
@@ -129,8 +412,15 @@ pub async fn read_publication(
         }
         let schema: Option<String> = row.get("schemaname?");
         let name: Option<String> = row.get("tablename?");
+        let columns: Option<Vec<String>> = row.get("columns?");
+        let row_filter: Option<String> = row.get("rowfilter?");
         if let (Some(schema), Some(name)) = (schema, name) {
-            tables.push(Table { schema, name });
+            tables.push(PublicationTable {
+                schema,
+                name,
+                columns,
+                row_filter,
+            });
         }
     }
 
@@ -140,14 +430,19 @@ pub async fn read_publication(
 }
 
 pub async fn read_all_publications(
+    limiter: &SourceConnectionLimiter,
     options: &PgConnectOptions,
 ) -> Result<Vec<Publication>, PublicationsDbError> {
     let query = r#"
         select p.pubname,
             pt.schemaname as "schemaname?",
-            pt.tablename as "tablename?"
+            pt.tablename as "tablename?",
+            case when pr.prattrs is null then null else pt.attnames::text[] end as "columns?",
+            pt.rowfilter as "rowfilter?"
         from pg_publication p
         left join pg_publication_tables pt on p.pubname = pt.pubname
+        left join pg_publication_rel pr on pr.prpubid = p.oid
+            and pr.prrelid = to_regclass(quote_ident(pt.schemaname) || '.' || quote_ident(pt.tablename))
         where
            	p.puballtables = false
            	and p.pubinsert = true
@@ -156,18 +451,25 @@ pub async fn read_all_publications(
            	and p.pubtruncate = true;
 	   "#;
 
-    let mut connection = PgConnection::connect_with(options).await?;
+    let mut connection = limiter.connect(options).await?;
 
-    let mut pub_name_to_tables: HashMap<String, Vec<Table>> = HashMap::new();
+    let mut pub_name_to_tables: HashMap<String, Vec<PublicationTable>> = HashMap::new();
 
     for row in connection.fetch_all(query).await? {
         let pub_name: String = row.get("pubname");
         let schema: Option<String> = row.get("schemaname?");
         let name: Option<String> = row.get("tablename?");
+        let columns: Option<Vec<String>> = row.get("columns?");
+        let row_filter: Option<String> = row.get("rowfilter?");
         let tables = pub_name_to_tables.entry(pub_name).or_default();
 
         if let (Some(schema), Some(name)) = (schema, name) {
-            tables.push(Table { schema, name });
+            tables.push(PublicationTable {
+                schema,
+                name,
+                columns,
+                row_filter,
+            });
         }
     }
 
@@ -178,3 +480,59 @@ pub async fn read_all_publications(
 
     Ok(publications)
 }
+
+/// The replication state of a table, as stored by a pipeline in the `etl` schema of its source.
+#[derive(Debug)]
+pub struct TableReplicationState {
+    pub pipeline_id: i64,
+    pub schema: String,
+    pub name: String,
+    /// The phase of the replication of the table, e.g. `data_sync` while it's copied or `ready`
+    /// once its changes are streamed.
+    pub state: String,
+}
+
+/// Reads the replication states of the tables stored by the pipelines with ids `pipeline_ids` in
+/// the database that `options` connects to.
+///
+/// The states of the tables which were dropped since are not returned.
+pub async fn read_table_replication_states(
+    pipeline_ids: &[i64],
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+) -> Result<Vec<TableReplicationState>, PublicationsDbError> {
+    let mut connection = limiter.connect(options).await?;
+
+    // The table only exists once a pipeline ran the migrations of its state store.
+    let state_table_exists: bool =
+        sqlx::query_scalar("select to_regclass('etl.replication_state') is not null")
+            .fetch_one(&mut *connection)
+            .await?;
+    if !state_table_exists {
+        return Ok(vec![]);
+    }
+
+    let query = r#"
+        select rs.pipeline_id, n.nspname as schema, c.relname as name, rs.state::text as state
+        from etl.replication_state rs
+            join pg_catalog.pg_class c on c.oid = rs.table_id
+            join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+        where rs.pipeline_id = any($1)
+        order by rs.pipeline_id, schema, name;
+        "#;
+
+    let states = sqlx::query(query)
+        .bind(pipeline_ids)
+        .fetch_all(&mut *connection)
+        .await?
+        .iter()
+        .map(|row| TableReplicationState {
+            pipeline_id: row.get("pipeline_id"),
+            schema: row.get("schema"),
+            name: row.get("name"),
+            state: row.get("state"),
+        })
+        .collect();
+
+    Ok(states)
+}
@@ -0,0 +1,116 @@
+use sqlx::{Executor, Row, postgres::PgConnectOptions};
+use thiserror::Error;
+
+use crate::source_connections::SourceConnectionLimiter;
+
+#[derive(Debug, Error)]
+pub enum ReplicationSlotsDbError {
+    #[error("Error while interacting with PostgreSQL for replication slots: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// A replication slot of the source database, with its lag behind the current WAL position.
+#[derive(Debug)]
+pub struct ReplicationSlot {
+    pub slot_name: String,
+    pub confirmed_flush_lsn: Option<String>,
+    pub restart_lsn: Option<String>,
+    pub lag_bytes: Option<i64>,
+}
+
+/// Reads the logical replication slots of the database that `options` connects to.
+///
+/// The lag is the number of bytes of WAL between the current WAL position and the
+/// `confirmed_flush_lsn` of the slot, so it is `None` for slots without a confirmed position.
+pub async fn get_replication_slots(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+) -> Result<Vec<ReplicationSlot>, ReplicationSlotsDbError> {
+    let mut connection = limiter.connect(options).await?;
+
+    // On a standby the current WAL position is the last replayed one.
+    let query = r#"
+        select
+            slot_name::text as slot_name,
+            confirmed_flush_lsn::text as confirmed_flush_lsn,
+            restart_lsn::text as restart_lsn,
+            pg_wal_lsn_diff(
+                case when pg_is_in_recovery() then pg_last_wal_replay_lsn()
+                else pg_current_wal_lsn() end,
+                confirmed_flush_lsn
+            )::bigint as lag_bytes
+        from pg_catalog.pg_replication_slots
+        where database = current_database()
+        order by slot_name;
+        "#;
+
+    let slots = connection
+        .fetch_all(query)
+        .await?
+        .iter()
+        .map(|r| ReplicationSlot {
+            slot_name: r.get("slot_name"),
+            confirmed_flush_lsn: r.get("confirmed_flush_lsn"),
+            restart_lsn: r.get("restart_lsn"),
+            lag_bytes: r.get("lag_bytes"),
+        })
+        .collect();
+
+    connection.close().await?;
+
+    Ok(slots)
+}
+
+/// The positions of a pipeline in the WAL of its source database.
+#[derive(Debug)]
+pub struct PipelineLsns {
+    pub snapshot_lsn: Option<String>,
+    pub confirmed_flush_lsn: Option<String>,
+}
+
+/// Reads the LSN after the initial snapshot of the pipeline with id `pipeline_id`, as stored by
+/// the pipeline in the `etl` schema of the database that `options` connects to, and the
+/// `confirmed_flush_lsn` of its apply slot named `slot_name`.
+///
+/// The snapshot LSN is `None` until the pipeline created its apply slot, and the confirmed LSN is
+/// `None` when the slot doesn't exist.
+pub async fn get_pipeline_lsns(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+    pipeline_id: i64,
+    slot_name: &str,
+) -> Result<PipelineLsns, ReplicationSlotsDbError> {
+    let mut connection = limiter.connect(options).await?;
+
+    // The table only exists once a pipeline ran the migrations of its state store.
+    let state_table_exists: bool =
+        sqlx::query_scalar("select to_regclass('etl.pipeline_state') is not null")
+            .fetch_one(&mut *connection)
+            .await?;
+    let snapshot_lsn = if state_table_exists {
+        sqlx::query_scalar("select snapshot_lsn from etl.pipeline_state where pipeline_id = $1")
+            .bind(pipeline_id)
+            .fetch_optional(&mut *connection)
+            .await?
+    } else {
+        None
+    };
+
+    let confirmed_flush_lsn: Option<Option<String>> = sqlx::query_scalar(
+        r#"
+        select confirmed_flush_lsn::text
+        from pg_catalog.pg_replication_slots
+        where database = current_database() and slot_name = $1
+        "#,
+    )
+    .bind(slot_name)
+    .fetch_optional(&mut *connection)
+    .await?;
+
+    connection.close().await?;
+
+    Ok(PipelineLsns {
+        snapshot_lsn,
+        confirmed_flush_lsn: confirmed_flush_lsn.flatten(),
+    })
+}
@@ -13,6 +13,12 @@ pub struct Replicator {
     pub image_id: i64,
 }
 
+/// A replicator together with the id of the pipeline that it runs.
+pub struct ReplicatorPipeline {
+    pub replicator: Replicator,
+    pub pipeline_id: i64,
+}
+
 pub async fn create_replicator<'c, E>(
     executor: E,
     tenant_id: &str,
@@ -93,6 +99,67 @@ where
         .collect())
 }
 
+/// Reads the replicators of all tenants, together with the pipelines they run.
+pub async fn read_all_replicator_pipelines<'c, E>(
+    executor: E,
+) -> Result<Vec<ReplicatorPipeline>, ReplicatorsDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let mut records = sqlx::query!(
+        r#"
+        select r.id, r.tenant_id, r.image_id, p.id as pipeline_id
+        from app.replicators r
+        join app.pipelines p on r.id = p.replicator_id and r.tenant_id = p.tenant_id
+        order by r.id
+        "#,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(records
+        .drain(..)
+        .map(|r| ReplicatorPipeline {
+            replicator: Replicator {
+                id: r.id,
+                tenant_id: r.tenant_id,
+                image_id: r.image_id,
+            },
+            pipeline_id: r.pipeline_id,
+        })
+        .collect())
+}
+
+/// Reads a replicator of any tenant, together with the pipeline it runs.
+pub async fn read_replicator_pipeline<'c, E>(
+    executor: E,
+    replicator_id: i64,
+) -> Result<Option<ReplicatorPipeline>, ReplicatorsDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let record = sqlx::query!(
+        r#"
+        select r.id, r.tenant_id, r.image_id, p.id as pipeline_id
+        from app.replicators r
+        join app.pipelines p on r.id = p.replicator_id and r.tenant_id = p.tenant_id
+        where r.id = $1
+        "#,
+        replicator_id,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(record.map(|r| ReplicatorPipeline {
+        replicator: Replicator {
+            id: r.id,
+            tenant_id: r.tenant_id,
+            image_id: r.image_id,
+        },
+        pipeline_id: r.pipeline_id,
+    }))
+}
+
 pub async fn update_replicator_image<'c, E>(
     executor: E,
     tenant_id: &str,
@@ -2,7 +2,7 @@ use serde::Serialize;
 use serde::de::DeserializeOwned;
 use thiserror::Error;
 
-use crate::encryption::{Decrypt, DecryptionError, Encrypt, EncryptionError, EncryptionKey};
+use crate::encryption::{Decrypt, DecryptionError, Encrypt, EncryptionError, Encryptor};
 
 /// Errors that can occur during serialization or encryption for database storage.
 #[derive(Debug, Error)]
@@ -42,17 +42,17 @@ where
 
 /// Encrypts a value and serializes it to a [`serde_json::Value`] for database storage.
 ///
-/// The value is first encrypted using the provided [`EncryptionKey`], then serialized.
+/// The value is first encrypted using the provided [`Encryptor`], then serialized.
 /// Returns an error if encryption or serialization fails.
-pub fn encrypt_and_serialize<T, S>(
+pub async fn encrypt_and_serialize<T, S>(
     value: T,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<serde_json::Value, DbSerializationError>
 where
     T: Encrypt<S>,
     S: Serialize,
 {
-    let value = value.encrypt(encryption_key)?;
+    let value = value.encrypt(encryptor).await?;
     let serialized_value = serde_json::to_value(value)?;
 
     Ok(serialized_value)
@@ -73,19 +73,19 @@ where
 /// Deserializes and decrypts a [`serde_json::Value`] into a value of type `S`.
 ///
 /// The value is first deserialized into a type implementing [`Decrypt`], then decrypted using
-/// the provided [`EncryptionKey`].
+/// the provided [`Encryptor`].
 ///
 /// Returns an error if deserialization or decryption fails.
-pub fn decrypt_and_deserialize_from_value<T, S>(
+pub async fn decrypt_and_deserialize_from_value<T, S>(
     value: serde_json::Value,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<S, DbDeserializationError>
 where
     T: Decrypt<S>,
     T: DeserializeOwned,
 {
     let deserialized_value: T = serde_json::from_value(value)?;
-    let value = deserialized_value.decrypt(encryption_key)?;
+    let value = deserialized_value.decrypt(encryptor).await?;
 
     Ok(value)
 }
@@ -1,19 +1,114 @@
+use async_trait::async_trait;
 use config::SerializableSecretString;
-use config::shared::{PgConnectionConfig, TlsConfig};
+use config::shared::{
+    PgConnectionConfig, TlsConfig, TlsVerification, ValidationError, parse_pg_hosts,
+};
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
-use sqlx::PgExecutor;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{Executor, PgExecutor, PgPool, PgTransaction};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
+use std::ops::DerefMut;
+use std::time::Duration;
 use thiserror::Error;
 
 use crate::db::serde::{
     DbDeserializationError, DbSerializationError, decrypt_and_deserialize_from_value,
-    encrypt_and_serialize,
+    deserialize_from_value, serialize,
 };
 use crate::encryption::{
-    Decrypt, DecryptionError, Encrypt, EncryptedValue, EncryptionError, EncryptionKey,
-    decrypt_text, encrypt_text,
+    Decrypt, DecryptionError, Encrypt, EncryptedValue, EncryptionError, EncryptionKey, Encryptor,
 };
+use crate::source_connections::SourceConnectionLimiter;
+
+/// Maximum number of tags that can be attached to a source.
+pub const MAX_SOURCE_TAGS: usize = 32;
+
+/// Maximum length in bytes of a tag key.
+pub const MAX_SOURCE_TAG_KEY_LENGTH: usize = 64;
+
+/// Maximum length in bytes of a tag value.
+pub const MAX_SOURCE_TAG_VALUE_LENGTH: usize = 256;
+
+/// Arbitrary key/value tags attached to a source, stored as `jsonb`.
+pub type SourceTags = BTreeMap<String, String>;
+
+#[derive(Debug, Error)]
+pub enum SourceTagsError {
+    #[error("A source can have at most {MAX_SOURCE_TAGS} tags, but {0} were provided")]
+    TooManyTags(usize),
+
+    #[error("The tag key '{0}' must be between 1 and {MAX_SOURCE_TAG_KEY_LENGTH} bytes long")]
+    InvalidKeyLength(String),
+
+    #[error("The value of tag '{0}' must be at most {MAX_SOURCE_TAG_VALUE_LENGTH} bytes long")]
+    InvalidValueLength(String),
+
+    #[error("The tag filter '{0}' is invalid, expected the format 'key:value'")]
+    InvalidFilter(String),
+}
+
+/// Validates the number of tags and the length of their keys and values.
+pub fn validate_source_tags(tags: &SourceTags) -> Result<(), SourceTagsError> {
+    if tags.len() > MAX_SOURCE_TAGS {
+        return Err(SourceTagsError::TooManyTags(tags.len()));
+    }
+
+    for (key, value) in tags {
+        if key.is_empty() || key.len() > MAX_SOURCE_TAG_KEY_LENGTH {
+            return Err(SourceTagsError::InvalidKeyLength(key.clone()));
+        }
+
+        if value.len() > MAX_SOURCE_TAG_VALUE_LENGTH {
+            return Err(SourceTagsError::InvalidValueLength(key.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a tag filter in the `key:value` format into a single tag.
+pub fn parse_source_tag_filter(filter: &str) -> Result<SourceTags, SourceTagsError> {
+    let Some((key, value)) = filter.split_once(':') else {
+        return Err(SourceTagsError::InvalidFilter(filter.to_string()));
+    };
+
+    let tags = SourceTags::from([(key.to_string(), value.to_string())]);
+    validate_source_tags(&tags).map_err(|_| SourceTagsError::InvalidFilter(filter.to_string()))?;
+
+    Ok(tags)
+}
+
+/// The TLS mode of the connections to a source, like the `sslmode` of libpq.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceSslMode {
+    /// The connection is not encrypted.
+    Disable,
+    /// The connection is encrypted, but the certificate of the server isn't verified.
+    Require,
+    /// The certificate of the server must be signed by the root certificate.
+    VerifyCa,
+    /// The certificate of the server must be signed by the root certificate, and be issued for
+    /// the host of the source.
+    VerifyFull,
+}
+
+#[derive(Debug, Error)]
+pub enum SourceConfigError {
+    #[error("{0}")]
+    InvalidHost(ValidationError),
+
+    #[error("A root certificate is required with the 'verify-ca' and 'verify-full' ssl modes")]
+    MissingRootCert,
+
+    #[error("A root certificate can only be set with an ssl mode other than 'disable'")]
+    UnexpectedRootCert,
+
+    #[error("The root certificate must be a PEM encoded certificate")]
+    InvalidRootCert,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -23,36 +118,94 @@ pub struct SourceConfig {
     pub name: String,
     pub username: String,
     pub password: Option<SerializableSecretString>,
+    /// The TLS mode of the connections to the source.
+    ///
+    /// Sources without a mode, like the ones created before it could be set, are connected to
+    /// without TLS by the api, and replicated with `verify-full` against the trusted root
+    /// certificates of the deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssl_mode: Option<SourceSslMode>,
+    /// The PEM encoded certificate which the certificate of the server is verified against.
+    ///
+    /// Like the password, it is encrypted at rest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_cert: Option<String>,
 }
 
 impl SourceConfig {
+    /// Checks that the hosts of the config are valid, and that the root certificate is
+    /// consistent with its ssl mode.
+    pub fn validate(&self) -> Result<(), SourceConfigError> {
+        parse_pg_hosts(&self.host).map_err(SourceConfigError::InvalidHost)?;
+
+        match (self.ssl_mode, &self.root_cert) {
+            (Some(SourceSslMode::VerifyCa | SourceSslMode::VerifyFull), None) => {
+                Err(SourceConfigError::MissingRootCert)
+            }
+            (None | Some(SourceSslMode::Disable), Some(_)) => {
+                Err(SourceConfigError::UnexpectedRootCert)
+            }
+            (_, Some(root_cert)) if !root_cert.contains("-----BEGIN CERTIFICATE-----") => {
+                Err(SourceConfigError::InvalidRootCert)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the [`TlsConfig`] of the connections to the source, as set by its ssl mode.
+    pub fn tls_config(&self) -> TlsConfig {
+        let verification = match self.ssl_mode {
+            None | Some(SourceSslMode::Disable) => {
+                return TlsConfig {
+                    trusted_root_certs: String::new(),
+                    enabled: false,
+                    verification: TlsVerification::default(),
+                };
+            }
+            Some(SourceSslMode::Require) => TlsVerification::Require,
+            Some(SourceSslMode::VerifyCa) => TlsVerification::VerifyCa,
+            Some(SourceSslMode::VerifyFull) => TlsVerification::VerifyFull,
+        };
+
+        TlsConfig {
+            trusted_root_certs: self.root_cert.clone().unwrap_or_default(),
+            enabled: true,
+            verification,
+        }
+    }
+
     pub fn into_connection_config(self) -> PgConnectionConfig {
+        let tls = self.tls_config();
+
         PgConnectionConfig {
             host: self.host,
             port: self.port,
             name: self.name,
             username: self.username,
             password: self.password,
-            // TODO: enable TLS
-            tls: TlsConfig {
-                trusted_root_certs: String::new(),
-                enabled: false,
-            },
+            tls,
         }
     }
 }
 
+#[async_trait]
 impl Encrypt<EncryptedSourceConfig> for SourceConfig {
-    fn encrypt(
+    async fn encrypt(
         self,
-        encryption_key: &EncryptionKey,
+        encryptor: &dyn Encryptor,
     ) -> Result<EncryptedSourceConfig, EncryptionError> {
         let mut encrypted_password = None;
         if let Some(password) = self.password {
-            encrypted_password = Some(encrypt_text(
-                password.expose_secret().to_owned(),
-                encryption_key,
-            )?);
+            encrypted_password = Some(
+                encryptor
+                    .encrypt(password.expose_secret().to_owned())
+                    .await?,
+            );
+        }
+
+        let mut encrypted_root_cert = None;
+        if let Some(root_cert) = self.root_cert {
+            encrypted_root_cert = Some(encryptor.encrypt(root_cert).await?);
         }
 
         Ok(EncryptedSourceConfig {
@@ -61,6 +214,8 @@ impl Encrypt<EncryptedSourceConfig> for SourceConfig {
             name: self.name,
             username: self.username,
             password: encrypted_password,
+            ssl_mode: self.ssl_mode,
+            root_cert: encrypted_root_cert,
         })
     }
 }
@@ -73,32 +228,66 @@ pub struct EncryptedSourceConfig {
     name: String,
     username: String,
     password: Option<EncryptedValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ssl_mode: Option<SourceSslMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    root_cert: Option<EncryptedValue>,
 }
 
+#[async_trait]
 impl Decrypt<SourceConfig> for EncryptedSourceConfig {
-    fn decrypt(self, encryption_key: &EncryptionKey) -> Result<SourceConfig, DecryptionError> {
+    async fn decrypt(self, encryptor: &dyn Encryptor) -> Result<SourceConfig, DecryptionError> {
         let mut decrypted_password = None;
         if let Some(password) = self.password {
-            let pwd = decrypt_text(password, encryption_key)?;
+            let pwd = encryptor.decrypt(password).await?;
             decrypted_password = Some(SerializableSecretString::from(pwd));
         }
 
+        let mut decrypted_root_cert = None;
+        if let Some(root_cert) = self.root_cert {
+            decrypted_root_cert = Some(encryptor.decrypt(root_cert).await?);
+        }
+
         Ok(SourceConfig {
             host: self.host,
             port: self.port,
             name: self.name,
             username: self.username,
             password: decrypted_password,
+            ssl_mode: self.ssl_mode,
+            root_cert: decrypted_root_cert,
         })
     }
 }
 
+impl EncryptedSourceConfig {
+    /// Returns the id of the key which encrypted the config, if it has an encrypted value.
+    fn key_id(&self) -> Option<i64> {
+        self.password
+            .as_ref()
+            .or(self.root_cert.as_ref())
+            .map(|value| i64::from(value.id))
+    }
+}
+
+/// Encrypts and serializes `config`, returning it with the id of the key which encrypted it.
+async fn encrypt_source_config(
+    config: SourceConfig,
+    encryptor: &dyn Encryptor,
+) -> Result<(serde_json::Value, Option<i64>), DbSerializationError> {
+    let config = config.encrypt(encryptor).await?;
+    let key_id = config.key_id();
+
+    Ok((serialize(config)?, key_id))
+}
+
 #[derive(Debug)]
 pub struct Source {
     pub id: i64,
     pub tenant_id: String,
     pub name: String,
     pub config: SourceConfig,
+    pub tags: SourceTags,
 }
 
 #[derive(Debug, Error)]
@@ -118,23 +307,26 @@ pub async fn create_source<'c, E>(
     tenant_id: &str,
     name: &str,
     config: SourceConfig,
-    encryption_key: &EncryptionKey,
+    tags: &SourceTags,
+    encryptor: &dyn Encryptor,
 ) -> Result<i64, SourcesDbError>
 where
     E: PgExecutor<'c>,
 {
-    let config =
-        encrypt_and_serialize::<SourceConfig, EncryptedSourceConfig>(config, encryption_key)?;
+    let (config, key_id) = encrypt_source_config(config, encryptor).await?;
+    let tags = serialize(tags)?;
 
     let record = sqlx::query!(
         r#"
-        insert into app.sources (tenant_id, name, config)
-        values ($1, $2, $3)
+        insert into app.sources (tenant_id, name, config, tags, key_id)
+        values ($1, $2, $3, $4, $5)
         returning id
         "#,
         tenant_id,
         name,
-        config
+        config,
+        tags,
+        key_id
     )
     .fetch_one(executor)
     .await?;
@@ -142,20 +334,150 @@ where
     Ok(record.id)
 }
 
+/// Creates a source like [`create_source`], unless the idempotency key `key` of the tenant was
+/// used less than `ttl` ago, in which case the id of the source created with the key is returned
+/// instead. Returns the id of the source along with whether it was created by this call.
+///
+/// The key is claimed before the source is created, so that a concurrent request with the same
+/// key waits for `txn` to end, and then returns the source of `txn` if it was committed.
+#[expect(clippy::too_many_arguments)]
+pub async fn create_source_idempotently(
+    txn: &mut PgTransaction<'_>,
+    tenant_id: &str,
+    name: &str,
+    config: SourceConfig,
+    tags: &SourceTags,
+    encryptor: &dyn Encryptor,
+    key: &str,
+    ttl: Duration,
+) -> Result<(i64, bool), SourcesDbError> {
+    let ttl_secs = ttl.as_secs_f64();
+
+    let claimed = sqlx::query!(
+        r#"
+        insert into app.source_idempotency_keys (tenant_id, key)
+        values ($1, $2)
+        on conflict (tenant_id, key) do update
+        set source_id = null, created_at = now()
+        where app.source_idempotency_keys.created_at < now() - make_interval(secs => $3)
+        "#,
+        tenant_id,
+        key,
+        ttl_secs
+    )
+    .execute(txn.deref_mut())
+    .await?
+    .rows_affected()
+        == 1;
+
+    if !claimed {
+        // The key can only be found unclaimed once the transaction which claimed it committed,
+        // along with the id of its source.
+        let record = sqlx::query!(
+            r#"
+            select source_id as "source_id!"
+            from app.source_idempotency_keys
+            where tenant_id = $1 and key = $2
+            "#,
+            tenant_id,
+            key
+        )
+        .fetch_one(txn.deref_mut())
+        .await?;
+
+        return Ok((record.source_id, false));
+    }
+
+    let source_id =
+        create_source(txn.deref_mut(), tenant_id, name, config, tags, encryptor).await?;
+
+    sqlx::query!(
+        r#"
+        update app.source_idempotency_keys
+        set source_id = $3
+        where tenant_id = $1 and key = $2
+        "#,
+        tenant_id,
+        key,
+        source_id
+    )
+    .execute(txn.deref_mut())
+    .await?;
+
+    Ok((source_id, true))
+}
+
+/// A source to create with [`create_sources`].
+#[derive(Debug)]
+pub struct NewSource {
+    pub name: String,
+    pub config: SourceConfig,
+    pub tags: SourceTags,
+}
+
+/// Creates `sources` with a single insert, returning the ids of the created sources by name.
+///
+/// The sources whose name is already used by another source of the tenant are skipped instead of
+/// failing the insert, and are missing from the returned ids.
+pub async fn create_sources<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    sources: Vec<NewSource>,
+    encryptor: &dyn Encryptor,
+) -> Result<HashMap<String, i64>, SourcesDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let mut names = Vec::with_capacity(sources.len());
+    let mut configs = Vec::with_capacity(sources.len());
+    let mut tags = Vec::with_capacity(sources.len());
+    let mut key_ids = Vec::with_capacity(sources.len());
+    for source in sources {
+        let (config, key_id) = encrypt_source_config(source.config, encryptor).await?;
+        names.push(source.name);
+        configs.push(config);
+        tags.push(serialize(source.tags)?);
+        key_ids.push(key_id);
+    }
+
+    let records = sqlx::query!(
+        r#"
+        insert into app.sources (tenant_id, name, config, tags, key_id)
+        select $1, s.name, s.config, s.tags, s.key_id
+        from unnest($2::text[], $3::jsonb[], $4::jsonb[], $5::bigint[])
+            as s(name, config, tags, key_id)
+        on conflict (tenant_id, name) where deleted_at is null do nothing
+        returning id, name
+        "#,
+        tenant_id,
+        &names,
+        &configs,
+        &tags,
+        &key_ids,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| (record.name, record.id))
+        .collect())
+}
+
 pub async fn read_source<'c, E>(
     executor: E,
     tenant_id: &str,
     source_id: i64,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<Option<Source>, SourcesDbError>
 where
     E: PgExecutor<'c>,
 {
     let record = sqlx::query!(
         r#"
-        select id, tenant_id, name, config
+        select id, tenant_id, name, config, tags
         from app.sources
-        where tenant_id = $1 and id = $2
+        where tenant_id = $1 and id = $2 and deleted_at is null
         "#,
         tenant_id,
         source_id,
@@ -167,14 +489,17 @@ where
         Some(record) => {
             let config = decrypt_and_deserialize_from_value::<EncryptedSourceConfig, SourceConfig>(
                 record.config,
-                encryption_key,
-            )?;
+                encryptor,
+            )
+            .await?;
+            let tags = deserialize_from_value::<SourceTags>(record.tags)?;
 
             Some(Source {
                 id: record.id,
                 tenant_id: record.tenant_id,
                 name: record.name,
                 config,
+                tags,
             })
         }
         None => None,
@@ -189,23 +514,56 @@ pub async fn update_source<'c, E>(
     name: &str,
     source_id: i64,
     config: SourceConfig,
-    encryption_key: &EncryptionKey,
+    tags: &SourceTags,
+    encryptor: &dyn Encryptor,
 ) -> Result<Option<i64>, SourcesDbError>
 where
     E: PgExecutor<'c>,
 {
-    let config =
-        encrypt_and_serialize::<SourceConfig, EncryptedSourceConfig>(config, encryption_key)?;
+    let (config, key_id) = encrypt_source_config(config, encryptor).await?;
+    let tags = serialize(tags)?;
 
     let record = sqlx::query!(
         r#"
         update app.sources
-        set config = $1, name = $2
-        where tenant_id = $3 and id = $4
+        set config = $1, name = $2, tags = $3, key_id = $4
+        where tenant_id = $5 and id = $6 and deleted_at is null
         returning id
         "#,
         config,
         name,
+        tags,
+        key_id,
+        tenant_id,
+        source_id
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(record.map(|r| r.id))
+}
+
+pub async fn update_source_config<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    source_id: i64,
+    config: SourceConfig,
+    encryptor: &dyn Encryptor,
+) -> Result<Option<i64>, SourcesDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let (config, key_id) = encrypt_source_config(config, encryptor).await?;
+
+    let record = sqlx::query!(
+        r#"
+        update app.sources
+        set config = $1, key_id = $2
+        where tenant_id = $3 and id = $4 and deleted_at is null
+        returning id
+        "#,
+        config,
+        key_id,
         tenant_id,
         source_id
     )
@@ -215,6 +573,103 @@ where
     Ok(record.map(|r| r.id))
 }
 
+/// Re-encrypts the configs of all the sources encrypted with `old_key` with `new_key`.
+///
+/// The sources are identified by their `key_id`, so sources encrypted with any other key, like
+/// the ones already re-encrypted or encrypted with a tenant key, are left untouched. Every source
+/// is re-encrypted in a single transaction. Returns the number of re-encrypted sources.
+pub async fn reencrypt_all(
+    pool: &PgPool,
+    old_key: &EncryptionKey,
+    new_key: &EncryptionKey,
+) -> Result<u64, SourcesDbError> {
+    let mut txn = pool.begin().await?;
+
+    let records = sqlx::query!(
+        r#"
+        select id, config
+        from app.sources
+        where key_id = $1
+        order by id
+        for update
+        "#,
+        i64::from(old_key.id)
+    )
+    .fetch_all(&mut *txn)
+    .await?;
+
+    for record in &records {
+        let config = decrypt_and_deserialize_from_value::<EncryptedSourceConfig, SourceConfig>(
+            record.config.clone(),
+            old_key,
+        )
+        .await?;
+        let (config, key_id) = encrypt_source_config(config, new_key).await?;
+
+        sqlx::query!(
+            r#"
+        update app.sources
+        set config = $1, key_id = $2
+        where id = $3
+        "#,
+            config,
+            key_id,
+            record.id
+        )
+        .execute(&mut *txn)
+        .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(records.len() as u64)
+}
+
+/// Errors that can occur when checking that a source can be replicated from.
+#[derive(Debug, Error)]
+pub enum SourceValidationError {
+    #[error("Could not connect to the source: {0}")]
+    Connection(#[from] sqlx::Error),
+
+    #[error("The source has `wal_level` set to '{0}', but it must be 'logical'")]
+    WalLevelNotLogical(String),
+}
+
+/// Opens a connection to the source database, runs a trivial query and checks that its
+/// `wal_level` allows logical replication.
+pub async fn validate_source_connection(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+) -> Result<(), SourceValidationError> {
+    let mut connection = limiter.connect(options).await?;
+    connection.execute("select 1").await?;
+    let wal_level: String = sqlx::query_scalar("select current_setting('wal_level')")
+        .fetch_one(&mut *connection)
+        .await?;
+    connection.close().await?;
+
+    if wal_level != "logical" {
+        return Err(SourceValidationError::WalLevelNotLogical(wal_level));
+    }
+
+    Ok(())
+}
+
+/// Opens a connection to the source database and runs a trivial query, to check that the
+/// connection config and its credentials are valid.
+pub async fn test_source_connection(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+) -> Result<(), sqlx::Error> {
+    let mut connection = limiter.connect(options).await?;
+    connection.execute("select 1").await?;
+    connection.close().await?;
+
+    Ok(())
+}
+
+/// Soft deletes a source by setting its `deleted_at`, after which it is hidden from every read
+/// until it is restored with [`restore_source`] or purged with [`purge_deleted_sources`].
 pub async fn delete_source<'c, E>(
     executor: E,
     tenant_id: &str,
@@ -225,8 +680,34 @@ where
 {
     let record = sqlx::query!(
         r#"
-        delete from app.sources
-        where tenant_id = $1 and id = $2
+        update app.sources
+        set deleted_at = now()
+        where tenant_id = $1 and id = $2 and deleted_at is null
+        returning id
+        "#,
+        tenant_id,
+        source_id
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(record.map(|r| r.id))
+}
+
+/// Restores a source deleted with [`delete_source`] which wasn't purged yet.
+pub async fn restore_source<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    source_id: i64,
+) -> Result<Option<i64>, SourcesDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let record = sqlx::query!(
+        r#"
+        update app.sources
+        set deleted_at = null
+        where tenant_id = $1 and id = $2 and deleted_at is not null
         returning id
         "#,
         tenant_id,
@@ -238,21 +719,67 @@ where
     Ok(record.map(|r| r.id))
 }
 
+/// Permanently deletes the sources of every tenant which were deleted more than `grace_period`
+/// ago, returning the number of purged sources.
+///
+/// Sources still used by a pipeline are kept, since the pipelines reference them.
+pub async fn purge_deleted_sources<'c, E>(
+    executor: E,
+    grace_period: Duration,
+) -> Result<u64, SourcesDbError>
+where
+    E: PgExecutor<'c>,
+{
+    let grace_period_secs = grace_period.as_secs_f64();
+
+    let result = sqlx::query!(
+        r#"
+        delete from app.sources s
+        where s.deleted_at < now() - make_interval(secs => $1)
+            and not exists (select 1 from app.pipelines p where p.source_id = s.id)
+        "#,
+        grace_period_secs
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Reads all sources of a tenant.
+///
+/// If `tags` is not empty, only the sources having all the given tags are returned. The filter is
+/// pushed down into the query with the `jsonb` containment operator.
+/// Reads at most `limit` sources of a tenant with all the `tags`, ordered by id and starting after
+/// the source with id `after`.
 pub async fn read_all_sources<'c, E>(
     executor: E,
     tenant_id: &str,
-    encryption_key: &EncryptionKey,
+    tags: &SourceTags,
+    after: Option<i64>,
+    limit: i64,
+    encryptor: &dyn Encryptor,
 ) -> Result<Vec<Source>, SourcesDbError>
 where
     E: PgExecutor<'c>,
 {
+    let tags = serialize(tags)?;
+
     let records = sqlx::query!(
         r#"
-        select id, tenant_id, name, config
+        select id, tenant_id, name, config, tags
         from app.sources
         where tenant_id = $1
+            and deleted_at is null
+            and tags @> $2
+            and ($3::bigint is null or id > $3)
+        order by id
+        limit $4
         "#,
         tenant_id,
+        tags,
+        after,
+        limit,
     )
     .fetch_all(executor)
     .await?;
@@ -261,13 +788,16 @@ where
     for record in records {
         let config = decrypt_and_deserialize_from_value::<EncryptedSourceConfig, SourceConfig>(
             record.config.clone(),
-            encryption_key,
-        )?;
+            encryptor,
+        )
+        .await?;
+        let tags = deserialize_from_value::<SourceTags>(record.tags)?;
         let source = Source {
             id: record.id,
             tenant_id: record.tenant_id,
             name: record.name,
             config,
+            tags,
         };
         sources.push(source);
     }
@@ -287,7 +817,7 @@ where
         r#"
         select exists (select id
         from app.sources
-        where tenant_id = $1 and id = $2) as "exists!"
+        where tenant_id = $1 and id = $2 and deleted_at is null) as "exists!"
         "#,
         tenant_id,
         source_id
@@ -298,13 +828,36 @@ where
     Ok(record.exists)
 }
 
+/// Helper function to check if an sqlx error is a duplicate source name constraint violation
+pub fn is_duplicate_source_name_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => {
+            // 23505 is PostgreSQL's unique constraint violation code
+            // Check for our unique constraint name defined
+            // in the migrations/20250720090000_add_deleted_at_to_sources.sql file
+            db_err.code().as_deref() == Some("23505")
+                && db_err.constraint() == Some("sources_tenant_id_name_unique")
+        }
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::db::serde::{decrypt_and_deserialize_from_value, encrypt_and_serialize};
-    use crate::db::sources::{EncryptedSourceConfig, SourceConfig};
-    use crate::encryption::EncryptionKey;
+    use crate::db::sources::{
+        EncryptedSourceConfig, MAX_SOURCE_TAGS, SourceConfig, SourceConfigError, SourceSslMode,
+        SourceTags, SourceTagsError, encrypt_source_config, parse_source_tag_filter,
+        validate_source_tags,
+    };
+    use crate::encryption::{
+        DecryptionError, EncryptedValue, EncryptionError, EncryptionKey, Encryptor,
+    };
+    use async_trait::async_trait;
     use aws_lc_rs::aead::RandomizedNonceKey;
     use config::SerializableSecretString;
+    use config::shared::TlsVerification;
+    use secrecy::ExposeSecret;
     use serde_json;
 
     #[test]
@@ -329,13 +882,15 @@ mod tests {
             name: "postgres".to_string(),
             username: "postgres".to_string(),
             password: Some(SerializableSecretString::from("postgres".to_string())),
+            ssl_mode: None,
+            root_cert: None,
         };
 
         insta::assert_json_snapshot!(config);
     }
 
-    #[test]
-    pub fn source_config_json_encryption() {
+    #[tokio::test]
+    pub async fn source_config_json_encryption() {
         let key_bytes = [42u8; 32];
         let key = RandomizedNonceKey::new(&aws_lc_rs::aead::AES_256_GCM, &key_bytes).unwrap();
         let encryption_key = EncryptionKey { id: 1, key };
@@ -346,12 +901,15 @@ mod tests {
             name: "postgres".to_string(),
             username: "postgres".to_string(),
             password: Some(SerializableSecretString::from("supersecret".to_string())),
+            ssl_mode: None,
+            root_cert: None,
         };
 
         let config_in_db = encrypt_and_serialize::<SourceConfig, EncryptedSourceConfig>(
             config.clone(),
             &encryption_key,
         )
+        .await
         .unwrap();
         insta::assert_json_snapshot!(config_in_db, {
             ".password" => "[password]"
@@ -361,7 +919,254 @@ mod tests {
             EncryptedSourceConfig,
             SourceConfig,
         >(config_in_db, &encryption_key)
+        .await
         .unwrap();
         insta::assert_debug_snapshot!(deserialized_config);
     }
+
+    /// An [`Encryptor`] which reverses the text, used to check that encryption goes through the
+    /// encryptor without depending on real key material.
+    struct MockEncryptor;
+
+    #[async_trait]
+    impl Encryptor for MockEncryptor {
+        async fn encrypt(&self, value: String) -> Result<EncryptedValue, EncryptionError> {
+            Ok(EncryptedValue {
+                id: 42,
+                nonce: "mock".to_string(),
+                value: value.chars().rev().collect(),
+            })
+        }
+
+        async fn decrypt(
+            &self,
+            encrypted_value: EncryptedValue,
+        ) -> Result<String, DecryptionError> {
+            Ok(encrypted_value.value.chars().rev().collect())
+        }
+    }
+
+    #[tokio::test]
+    pub async fn source_config_json_encryption_with_mock_encryptor() {
+        let config = SourceConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            name: "postgres".to_string(),
+            username: "postgres".to_string(),
+            password: Some(SerializableSecretString::from("supersecret".to_string())),
+            ssl_mode: None,
+            root_cert: None,
+        };
+
+        let config_in_db =
+            encrypt_and_serialize::<SourceConfig, EncryptedSourceConfig>(config, &MockEncryptor)
+                .await
+                .unwrap();
+        assert_eq!(
+            config_in_db["password"],
+            serde_json::json!({"id": 42, "nonce": "mock", "value": "terceserpus"})
+        );
+
+        let deserialized_config = decrypt_and_deserialize_from_value::<
+            EncryptedSourceConfig,
+            SourceConfig,
+        >(config_in_db, &MockEncryptor)
+        .await
+        .unwrap();
+        assert_eq!(
+            deserialized_config.password.unwrap().expose_secret(),
+            "supersecret"
+        );
+    }
+
+    #[tokio::test]
+    pub async fn source_config_records_the_id_of_its_key() {
+        let mut config = SourceConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            name: "postgres".to_string(),
+            username: "postgres".to_string(),
+            password: Some(SerializableSecretString::from("supersecret".to_string())),
+            ssl_mode: None,
+            root_cert: None,
+        };
+
+        let (_, key_id) = encrypt_source_config(config.clone(), &MockEncryptor)
+            .await
+            .unwrap();
+        assert_eq!(key_id, Some(42));
+
+        // Nothing is encrypted without a password or a root certificate.
+        config.password = None;
+        let (_, key_id) = encrypt_source_config(config, &MockEncryptor).await.unwrap();
+        assert_eq!(key_id, None);
+    }
+
+    const ROOT_CERT: &str = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+
+    #[tokio::test]
+    pub async fn source_config_root_cert_is_encrypted() {
+        let config = SourceConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            name: "postgres".to_string(),
+            username: "postgres".to_string(),
+            password: None,
+            ssl_mode: Some(SourceSslMode::VerifyFull),
+            root_cert: Some(ROOT_CERT.to_string()),
+        };
+
+        let (config_in_db, key_id) = encrypt_source_config(config, &MockEncryptor).await.unwrap();
+        assert_eq!(key_id, Some(42));
+        assert_eq!(config_in_db["ssl_mode"], "verify-full");
+        assert_eq!(
+            config_in_db["root_cert"]["value"],
+            ROOT_CERT.chars().rev().collect::<String>()
+        );
+
+        let deserialized_config = decrypt_and_deserialize_from_value::<
+            EncryptedSourceConfig,
+            SourceConfig,
+        >(config_in_db, &MockEncryptor)
+        .await
+        .unwrap();
+        assert_eq!(deserialized_config.root_cert.as_deref(), Some(ROOT_CERT));
+
+        let tls = deserialized_config.tls_config();
+        assert!(tls.enabled);
+        assert_eq!(tls.verification, TlsVerification::VerifyFull);
+        assert_eq!(tls.trusted_root_certs, ROOT_CERT);
+    }
+
+    #[test]
+    pub fn source_config_ssl_mode_validation() {
+        let config = |ssl_mode, root_cert: Option<&str>| SourceConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            name: "postgres".to_string(),
+            username: "postgres".to_string(),
+            password: None,
+            ssl_mode,
+            root_cert: root_cert.map(str::to_string),
+        };
+
+        assert!(config(None, None).validate().is_ok());
+        assert!(
+            config(Some(SourceSslMode::Require), None)
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            config(Some(SourceSslMode::VerifyCa), Some(ROOT_CERT))
+                .validate()
+                .is_ok()
+        );
+        assert!(matches!(
+            config(Some(SourceSslMode::VerifyFull), None).validate(),
+            Err(SourceConfigError::MissingRootCert)
+        ));
+        assert!(matches!(
+            config(Some(SourceSslMode::Disable), Some(ROOT_CERT)).validate(),
+            Err(SourceConfigError::UnexpectedRootCert)
+        ));
+        assert!(matches!(
+            config(Some(SourceSslMode::VerifyFull), Some("not a certificate")).validate(),
+            Err(SourceConfigError::InvalidRootCert)
+        ));
+    }
+
+    #[test]
+    pub fn source_config_hosts_validation() {
+        let config = |host: &str| SourceConfig {
+            host: host.to_string(),
+            port: 5432,
+            name: "postgres".to_string(),
+            username: "postgres".to_string(),
+            password: None,
+            ssl_mode: None,
+            root_cert: None,
+        };
+
+        for host in [
+            "localhost",
+            "db-1.example.com",
+            "10.0.0.1",
+            "::1",
+            "[2001:db8::1]",
+            "/var/run/postgresql",
+            "primary.example.com, standby.example.com",
+        ] {
+            assert!(config(host).validate().is_ok(), "{host} should be valid");
+        }
+
+        for host in [
+            "",
+            "localhost:5432",
+            "[::1",
+            "[localhost]",
+            "10.0.0.256",
+            "-db.example.com",
+            "db..example.com",
+            "db example.com",
+            "primary.example.com,",
+        ] {
+            assert!(
+                matches!(
+                    config(host).validate(),
+                    Err(SourceConfigError::InvalidHost(
+                        ValidationError::InvalidHost { .. }
+                    ))
+                ),
+                "{host} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    pub fn source_tags_validation() {
+        let tags = SourceTags::from([("env".to_string(), "prod".to_string())]);
+        assert!(validate_source_tags(&tags).is_ok());
+
+        let tags = SourceTags::from([(String::new(), "prod".to_string())]);
+        assert!(matches!(
+            validate_source_tags(&tags),
+            Err(SourceTagsError::InvalidKeyLength(_))
+        ));
+
+        let tags = SourceTags::from([("env".to_string(), "a".repeat(257))]);
+        assert!(matches!(
+            validate_source_tags(&tags),
+            Err(SourceTagsError::InvalidValueLength(_))
+        ));
+
+        let tags = (0..=MAX_SOURCE_TAGS)
+            .map(|i| (format!("key{i}"), "value".to_string()))
+            .collect::<SourceTags>();
+        assert!(matches!(
+            validate_source_tags(&tags),
+            Err(SourceTagsError::TooManyTags(_))
+        ));
+    }
+
+    #[test]
+    pub fn source_tag_filter_parsing() {
+        let tags = parse_source_tag_filter("env:prod").unwrap();
+        assert_eq!(tags.get("env").map(String::as_str), Some("prod"));
+
+        // Only the first colon separates the key from the value.
+        let tags = parse_source_tag_filter("url:http://localhost").unwrap();
+        assert_eq!(
+            tags.get("url").map(String::as_str),
+            Some("http://localhost")
+        );
+
+        assert!(matches!(
+            parse_source_tag_filter("env"),
+            Err(SourceTagsError::InvalidFilter(_))
+        ));
+        assert!(matches!(
+            parse_source_tag_filter(":prod"),
+            Err(SourceTagsError::InvalidFilter(_))
+        ));
+    }
 }
@@ -0,0 +1,80 @@
+use sqlx::Row;
+use sqlx::postgres::PgConnectOptions;
+use thiserror::Error;
+
+use crate::source_connections::SourceConnectionLimiter;
+
+#[derive(Debug, Error)]
+pub enum TableCopiesDbError {
+    #[error("Error while interacting with PostgreSQL for table copies: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// The progress of the copy of a table of a pipeline, as last reported by the pipeline.
+#[derive(Debug)]
+pub struct TableCopy {
+    pub schema: String,
+    pub name: String,
+    /// The replication state of the table, or `None` if the table is not replicated anymore.
+    pub state: Option<String>,
+    pub rows_copied: i64,
+    /// The number of rows of the table estimated when the copy started, if any.
+    pub estimated_rows: Option<i64>,
+    pub updated_at: String,
+}
+
+/// Reads the progress of the table copies of the pipeline with id `pipeline_id`, stored in the
+/// `etl` schema of the database that `options` connects to.
+pub async fn read_table_copies(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+    pipeline_id: i64,
+) -> Result<Vec<TableCopy>, TableCopiesDbError> {
+    let mut connection = limiter.connect(options).await?;
+
+    // The table only exists once a pipeline ran the migrations of its state store.
+    let progress_table_exists: bool =
+        sqlx::query_scalar("select to_regclass('etl.table_copy_progress') is not null")
+            .fetch_one(&mut *connection)
+            .await?;
+    if !progress_table_exists {
+        connection.close().await?;
+        return Ok(vec![]);
+    }
+
+    let query = r#"
+        select
+            n.nspname as schema,
+            c.relname as name,
+            rs.state::text as state,
+            cp.rows_copied,
+            cp.estimated_rows,
+            to_char(cp.updated_at at time zone 'utc', 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"') as updated_at
+        from etl.table_copy_progress cp
+            join pg_catalog.pg_class c on c.oid = cp.table_id
+            join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+            left join etl.replication_state rs
+                on rs.pipeline_id = cp.pipeline_id and rs.table_id = cp.table_id
+        where cp.pipeline_id = $1
+        order by schema, name;
+        "#;
+
+    let copies = sqlx::query(query)
+        .bind(pipeline_id)
+        .fetch_all(&mut *connection)
+        .await?
+        .iter()
+        .map(|row| TableCopy {
+            schema: row.get("schema"),
+            name: row.get("name"),
+            state: row.get("state"),
+            rows_copied: row.get("rows_copied"),
+            estimated_rows: row.get("estimated_rows"),
+            updated_at: row.get("updated_at"),
+        })
+        .collect();
+
+    connection.close().await?;
+
+    Ok(copies)
+}
@@ -0,0 +1,159 @@
+use sqlx::Row;
+use sqlx::postgres::{PgConnectOptions, types::Oid};
+use thiserror::Error;
+
+use crate::source_connections::SourceConnectionLimiter;
+
+#[derive(Debug, Error)]
+pub enum TableResyncsDbError {
+    #[error("Error while interacting with PostgreSQL for table resyncs: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// The outcome of a request to copy a table of a pipeline again.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TableResyncRequest {
+    /// The request was stored, and is picked up by the pipeline while it's running.
+    Requested,
+    /// The table doesn't exist or isn't replicated by the pipeline.
+    TableNotReplicated,
+    /// The table is still being copied, either initially or by a previous resync.
+    TableSyncInProgress,
+}
+
+/// A request to copy a table of a pipeline again, with its progress.
+#[derive(Debug)]
+pub struct TableResync {
+    pub schema: String,
+    pub name: String,
+    /// The replication state of the table, or `None` if the table is not replicated anymore.
+    pub state: Option<String>,
+    pub requested_at: String,
+    /// When the pipeline started copying the table, or `None` while the request is pending.
+    pub started_at: Option<String>,
+    pub rows_copied: i64,
+}
+
+/// Requests the pipeline with id `pipeline_id` to copy the table `schema`.`name` again, by storing
+/// the request in the `etl` schema of the database that `options` connects to.
+///
+/// Only tables whose copy is done can be copied again. A previous request for the same table is
+/// replaced.
+pub async fn request_table_resync(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+    pipeline_id: i64,
+    schema: &str,
+    name: &str,
+) -> Result<TableResyncRequest, TableResyncsDbError> {
+    let mut connection = limiter.connect(options).await?;
+
+    // The tables only exist once a pipeline ran the migrations of its state store.
+    let resyncs_table_exists: bool = sqlx::query_scalar(
+        "select to_regclass('etl.replication_state') is not null and to_regclass('etl.table_resyncs') is not null",
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+    if !resyncs_table_exists {
+        connection.close().await?;
+        return Ok(TableResyncRequest::TableNotReplicated);
+    }
+
+    let query = r#"
+        select c.oid as table_id, rs.state::text as state
+        from pg_catalog.pg_class c
+            join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+            join etl.replication_state rs on rs.table_id = c.oid and rs.pipeline_id = $1
+        where n.nspname = $2 and c.relname = $3;
+        "#;
+    let table = sqlx::query(query)
+        .bind(pipeline_id)
+        .bind(schema)
+        .bind(name)
+        .fetch_optional(&mut *connection)
+        .await?;
+
+    let Some(table) = table else {
+        connection.close().await?;
+        return Ok(TableResyncRequest::TableNotReplicated);
+    };
+
+    let state: String = table.get("state");
+    if state != "ready" && state != "skipped" {
+        connection.close().await?;
+        return Ok(TableResyncRequest::TableSyncInProgress);
+    }
+
+    let table_id: Oid = table.get("table_id");
+    sqlx::query(
+        r#"
+        insert into etl.table_resyncs (pipeline_id, table_id)
+        values ($1, $2)
+        on conflict (pipeline_id, table_id)
+        do update set requested_at = now(), started_at = null, rows_copied = 0
+        "#,
+    )
+    .bind(pipeline_id)
+    .bind(table_id)
+    .execute(&mut *connection)
+    .await?;
+
+    connection.close().await?;
+
+    Ok(TableResyncRequest::Requested)
+}
+
+/// Reads the requests of the pipeline with id `pipeline_id` to copy tables again, stored in the
+/// `etl` schema of the database that `options` connects to.
+pub async fn read_table_resyncs(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+    pipeline_id: i64,
+) -> Result<Vec<TableResync>, TableResyncsDbError> {
+    let mut connection = limiter.connect(options).await?;
+
+    let resyncs_table_exists: bool =
+        sqlx::query_scalar("select to_regclass('etl.table_resyncs') is not null")
+            .fetch_one(&mut *connection)
+            .await?;
+    if !resyncs_table_exists {
+        connection.close().await?;
+        return Ok(vec![]);
+    }
+
+    let query = r#"
+        select
+            n.nspname as schema,
+            c.relname as name,
+            rs.state::text as state,
+            to_char(tr.requested_at at time zone 'utc', 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"') as requested_at,
+            to_char(tr.started_at at time zone 'utc', 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"') as started_at,
+            tr.rows_copied
+        from etl.table_resyncs tr
+            join pg_catalog.pg_class c on c.oid = tr.table_id
+            join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+            left join etl.replication_state rs
+                on rs.pipeline_id = tr.pipeline_id and rs.table_id = tr.table_id
+        where tr.pipeline_id = $1
+        order by schema, name;
+        "#;
+
+    let resyncs = sqlx::query(query)
+        .bind(pipeline_id)
+        .fetch_all(&mut *connection)
+        .await?
+        .iter()
+        .map(|row| TableResync {
+            schema: row.get("schema"),
+            name: row.get("name"),
+            state: row.get("state"),
+            requested_at: row.get("requested_at"),
+            started_at: row.get("started_at"),
+            rows_copied: row.get("rows_copied"),
+        })
+        .collect();
+
+    connection.close().await?;
+
+    Ok(resyncs)
+}
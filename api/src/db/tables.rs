@@ -1,11 +1,27 @@
+use pg_escape::quote_identifier;
+use postgres::schema::ColumnSchema;
+use postgres::types::convert_named_type_oid_to_type;
 use serde::{Deserialize, Serialize};
-use sqlx::{Connection, Executor, PgConnection, Row, postgres::PgConnectOptions};
+use sqlx::postgres::{PgConnectOptions, types::Oid};
+use sqlx::{Executor, Row};
 use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::source_connections::SourceConnectionLimiter;
+
+/// Estimated number of rows under which a table is read without sampling.
+///
+/// Sampling a small table can return no rows at all, especially with [`TableSampleMethod::System`]
+/// which samples whole pages, so small tables are read with a plain `limit` instead.
+pub const MIN_ROWS_FOR_SAMPLING: i64 = 1000;
 
 #[derive(Debug, Error)]
 pub enum TablesDbError {
     #[error("Error while interacting with PostgreSQL for tables: {0}")]
     Database(#[from] sqlx::Error),
+
+    #[error("The table {0}.{1} was not found")]
+    TableNotFound(String, String),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,34 +30,191 @@ pub struct Table {
     pub name: String,
 }
 
-pub async fn get_tables(options: &PgConnectOptions) -> Result<Vec<Table>, TablesDbError> {
-    let mut connection = PgConnection::connect_with(options).await?;
+/// A table with the schemas of its columns, in the order of the table.
+#[derive(Debug)]
+pub struct TableWithColumns {
+    pub table: Table,
+    pub columns: Vec<ColumnSchema>,
+}
+
+/// Reads the tables visible in the search path of the connection, with their columns.
+///
+/// If `schema` is set, only the tables of this schema are returned.
+pub async fn get_tables(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+    schema: Option<&str>,
+) -> Result<Vec<TableWithColumns>, TablesDbError> {
+    let mut connection = limiter.connect(options).await?;
 
     let query = r#"
         select
-           	n.nspname as schema,
-           	c.relname as name
+            n.nspname as schema,
+            c.relname as name,
+            a.attname as column_name,
+            a.atttypid as type_oid,
+            a.atttypmod as modifier,
+            not a.attnotnull as nullable,
+            coalesce(a.attnum = any(i.indkey), false) as is_primary,
+            t.typname as type_name,
+            tn.nspname as type_schema
         from pg_catalog.pg_class c
-           	left join pg_catalog.pg_namespace n on n.oid = c.relnamespace
-            left join pg_catalog.pg_am am on am.oid = c.relam
+            left join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+            left join pg_catalog.pg_attribute a
+                on a.attrelid = c.oid and a.attnum > 0 and not a.attisdropped
+            left join pg_catalog.pg_type t on t.oid = a.atttypid
+            left join pg_catalog.pg_namespace tn on tn.oid = t.typnamespace
+            left join pg_catalog.pg_index i on i.indrelid = c.oid and i.indisprimary
         where
-           	c.relkind = 'r'
-           	and n.nspname <> 'pg_catalog'
+            c.relkind = 'r'
+            and n.nspname <> 'pg_catalog'
             and n.nspname !~ '^pg_toast'
             and n.nspname <> 'information_schema'
-           	and pg_catalog.pg_table_is_visible(c.oid)
-        order by schema, name;
+            and pg_catalog.pg_table_is_visible(c.oid)
+            and ($1::text is null or n.nspname = $1)
+        order by schema, name, a.attnum;
         "#;
 
-    let tables = connection
-        .fetch_all(query)
+    let rows = sqlx::query(query)
+        .bind(schema)
+        .fetch_all(&mut *connection)
+        .await?;
+
+    // The rows are ordered by table, with a row per column, or a single row without a column for
+    // the tables without columns.
+    let mut tables: Vec<TableWithColumns> = vec![];
+    for row in rows {
+        let table = Table {
+            schema: row.get("schema"),
+            name: row.get("name"),
+        };
+        let is_same_table = tables
+            .last()
+            .is_some_and(|last| last.table.schema == table.schema && last.table.name == table.name);
+        if !is_same_table {
+            tables.push(TableWithColumns {
+                table,
+                columns: vec![],
+            });
+        }
+
+        let Some(column_name) = row.get::<Option<String>, _>("column_name") else {
+            continue;
+        };
+        let type_oid: Oid = row.get("type_oid");
+        let typ = convert_named_type_oid_to_type(
+            type_oid.0,
+            row.get("type_name"),
+            row.get("type_schema"),
+        );
+        let column = ColumnSchema::new(
+            column_name,
+            typ,
+            row.get("modifier"),
+            row.get("nullable"),
+            row.get("is_primary"),
+        );
+        tables
+            .last_mut()
+            .expect("the table of the column was pushed")
+            .columns
+            .push(column);
+    }
+
+    Ok(tables)
+}
+
+/// The method used by `tablesample` to select the sampled rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TableSampleMethod {
+    /// Samples whole pages, which is cheap but less random.
+    System,
+    /// Samples each row individually, which scans the whole table.
+    Bernoulli,
+}
+
+impl TableSampleMethod {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            TableSampleMethod::System => "system",
+            TableSampleMethod::Bernoulli => "bernoulli",
+        }
+    }
+}
+
+/// Sampling applied when previewing a table.
+#[derive(Debug, Clone, Copy)]
+pub struct TableSample {
+    pub method: TableSampleMethod,
+    /// Percentage of the table to sample, in the `(0, 100]` range.
+    pub percent: f64,
+}
+
+/// Rows read from a table, each encoded as a JSON object.
+#[derive(Debug)]
+pub struct TablePreview {
+    pub rows: Vec<serde_json::Value>,
+    /// Whether the rows were sampled, which is not the case for small tables.
+    pub sampled: bool,
+}
+
+/// Reads at most `limit` rows from `table`, sampling them with `sample` if the table is large
+/// enough.
+pub async fn preview_table(
+    limiter: &SourceConnectionLimiter,
+    options: &PgConnectOptions,
+    table: &Table,
+    sample: Option<TableSample>,
+    limit: i64,
+) -> Result<TablePreview, TablesDbError> {
+    let mut connection = limiter.connect(options).await?;
+
+    // The estimate comes from the statistics, it's -1 if the table was never analyzed.
+    let estimated_rows: Option<i64> = sqlx::query_scalar(
+        r#"
+        select c.reltuples::int8
+        from pg_catalog.pg_class c
+            join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+        where n.nspname = $1 and c.relname = $2 and c.relkind in ('r', 'p')
+        "#,
+    )
+    .bind(&table.schema)
+    .bind(&table.name)
+    .fetch_optional(&mut *connection)
+    .await?;
+    let Some(estimated_rows) = estimated_rows else {
+        return Err(TablesDbError::TableNotFound(
+            table.schema.clone(),
+            table.name.clone(),
+        ));
+    };
+
+    let sample = sample.filter(|_| estimated_rows >= MIN_ROWS_FOR_SAMPLING);
+
+    let mut query = String::new();
+    query.push_str("select to_jsonb(t) as data from (select * from ");
+    query.push_str(&quote_identifier(&table.schema));
+    query.push('.');
+    query.push_str(&quote_identifier(&table.name));
+    if let Some(sample) = sample {
+        query.push_str(&format!(
+            " tablesample {} ({})",
+            sample.method.as_sql(),
+            sample.percent
+        ));
+    }
+    query.push_str(&format!(" limit {limit}) t"));
+
+    let rows = connection
+        .fetch_all(query.as_str())
         .await?
         .iter()
-        .map(|r| Table {
-            schema: r.get("schema"),
-            name: r.get("name"),
-        })
+        .map(|r| r.get("data"))
         .collect();
 
-    Ok(tables)
+    Ok(TablePreview {
+        rows,
+        sampled: sample.is_some(),
+    })
 }
@@ -2,10 +2,13 @@ use sqlx::PgTransaction;
 use std::ops::DerefMut;
 use thiserror::Error;
 
+use crate::db::audit_log::{
+    AuditLogDbError, SourceAction, hash_source_config, insert_source_audit_entry,
+};
 use crate::db::serde::DbSerializationError;
-use crate::db::sources::{SourceConfig, SourcesDbError, create_source};
+use crate::db::sources::{SourceConfig, SourceTags, SourcesDbError, create_source};
 use crate::db::tenants::{TenantsDbError, create_tenant};
-use crate::encryption::EncryptionKey;
+use crate::encryption::Encryptor;
 
 #[derive(Debug, Error)]
 pub enum TenantSourceDbError {
@@ -20,6 +23,9 @@ pub enum TenantSourceDbError {
 
     #[error(transparent)]
     Tenants(#[from] TenantsDbError),
+
+    #[error(transparent)]
+    AuditLog(#[from] AuditLogDbError),
 }
 
 pub async fn create_tenant_and_source(
@@ -28,15 +34,25 @@ pub async fn create_tenant_and_source(
     tenant_name: &str,
     source_name: &str,
     source_config: SourceConfig,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<(String, i64), TenantSourceDbError> {
+    let config_hash = hash_source_config(&source_config);
     let tenant_id = create_tenant(txn.deref_mut(), tenant_id, tenant_name).await?;
     let source_id = create_source(
         txn.deref_mut(),
         &tenant_id,
         source_name,
         source_config,
-        encryption_key,
+        &SourceTags::new(),
+        encryptor,
+    )
+    .await?;
+    insert_source_audit_entry(
+        txn.deref_mut(),
+        &tenant_id,
+        source_id,
+        SourceAction::Create,
+        Some(&config_hash),
     )
     .await?;
 
@@ -1,3 +1,4 @@
+use async_trait::async_trait;
 use aws_lc_rs::{
     aead::{AES_256_GCM, Aad, Nonce, RandomizedNonceKey},
     rand::fill,
@@ -5,7 +6,9 @@ use aws_lc_rs::{
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::string;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Errors that can occur during encryption operations.
@@ -36,16 +39,31 @@ pub enum DecryptionError {
     MismatchedKeyId(u32, u32),
 }
 
+/// A backend able to encrypt and decrypt text values.
+///
+/// The trait is asynchronous so that it can be implemented both by local keys, like
+/// [`EncryptionKey`], and by external key management services which need network calls.
+#[async_trait]
+pub trait Encryptor: Send + Sync {
+    /// Encrypts `value` into an [`EncryptedValue`].
+    async fn encrypt(&self, value: String) -> Result<EncryptedValue, EncryptionError>;
+
+    /// Decrypts an [`EncryptedValue`] back into the original text.
+    async fn decrypt(&self, encrypted_value: EncryptedValue) -> Result<String, DecryptionError>;
+}
+
 /// Trait for types that can be encrypted into another type.
+#[async_trait]
 pub trait Encrypt<T> {
-    /// Encrypts `self` using the provided [`EncryptionKey`].
-    fn encrypt(self, encryption_key: &EncryptionKey) -> Result<T, EncryptionError>;
+    /// Encrypts `self` using the provided [`Encryptor`].
+    async fn encrypt(self, encryptor: &dyn Encryptor) -> Result<T, EncryptionError>;
 }
 
 /// Trait for types that can be decrypted into another type.
+#[async_trait]
 pub trait Decrypt<T> {
-    /// Decrypts `self` using the provided [`EncryptionKey`].
-    fn decrypt(self, encryption_key: &EncryptionKey) -> Result<T, DecryptionError>;
+    /// Decrypts `self` using the provided [`Encryptor`].
+    async fn decrypt(self, encryptor: &dyn Encryptor) -> Result<T, DecryptionError>;
 }
 
 /// Holds an encryption key and its identifier.
@@ -56,6 +74,108 @@ pub struct EncryptionKey {
     pub key: RandomizedNonceKey,
 }
 
+#[async_trait]
+impl Encryptor for EncryptionKey {
+    async fn encrypt(&self, value: String) -> Result<EncryptedValue, EncryptionError> {
+        encrypt_text(value, self)
+    }
+
+    async fn decrypt(&self, encrypted_value: EncryptedValue) -> Result<String, DecryptionError> {
+        decrypt_text(encrypted_value, self)
+    }
+}
+
+/// Resolves the [`Encryptor`] used for the data of each tenant.
+///
+/// Resolution is synchronous since it only selects among already loaded encryptors, while the
+/// encryptors themselves can still call external services.
+pub trait KeyProvider: Send + Sync {
+    /// Returns the [`Encryptor`] to use for the data of `tenant_id`.
+    fn encryptor(&self, tenant_id: &str) -> &dyn Encryptor;
+}
+
+/// A [`KeyProvider`] backed by per-tenant keys, which falls back to a global key.
+///
+/// Tenants without their own key use the global key. Tenants with their own key encrypt new
+/// values with it, but can still decrypt the values encrypted with the global key before their
+/// key was configured, so that existing data keeps working while it is migrated. This relies on
+/// the tenant keys having ids different from the global key id.
+pub struct TenantKeyProvider {
+    global: Arc<dyn Encryptor>,
+    tenants: HashMap<String, FallbackEncryptor>,
+}
+
+impl TenantKeyProvider {
+    /// Creates a new [`TenantKeyProvider`] using `global` for the tenants missing from
+    /// `tenant_keys`.
+    pub fn new(
+        global: Arc<dyn Encryptor>,
+        tenant_keys: HashMap<String, Arc<dyn Encryptor>>,
+    ) -> Self {
+        let tenants = tenant_keys
+            .into_iter()
+            .map(|(tenant_id, tenant_key)| {
+                let encryptor = FallbackEncryptor::new(tenant_key, global.clone());
+                (tenant_id, encryptor)
+            })
+            .collect();
+
+        Self { global, tenants }
+    }
+}
+
+impl KeyProvider for TenantKeyProvider {
+    fn encryptor(&self, tenant_id: &str) -> &dyn Encryptor {
+        match self.tenants.get(tenant_id) {
+            Some(encryptor) => encryptor,
+            None => self.global.as_ref(),
+        }
+    }
+}
+
+/// An [`Encryptor`] which encrypts with a primary encryptor and falls back to another one to
+/// decrypt the values of other keys.
+///
+/// It is used for tenants with their own key, whose values encrypted with the global key before
+/// their key was configured must still be decrypted, and for the global key while it is rotated,
+/// so that the values encrypted with the previous key keep working until they are re-encrypted.
+pub struct FallbackEncryptor {
+    primary: Arc<dyn Encryptor>,
+    fallback: Arc<dyn Encryptor>,
+}
+
+impl FallbackEncryptor {
+    /// Creates a new [`FallbackEncryptor`] which decrypts the values whose key id doesn't match
+    /// `primary` with `fallback`.
+    pub fn new(primary: Arc<dyn Encryptor>, fallback: Arc<dyn Encryptor>) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+#[async_trait]
+impl Encryptor for FallbackEncryptor {
+    async fn encrypt(&self, value: String) -> Result<EncryptedValue, EncryptionError> {
+        self.primary.encrypt(value).await
+    }
+
+    async fn decrypt(&self, encrypted_value: EncryptedValue) -> Result<String, DecryptionError> {
+        match self.primary.decrypt(encrypted_value.clone()).await {
+            Err(DecryptionError::MismatchedKeyId(..)) => {
+                self.fallback.decrypt(encrypted_value).await
+            }
+            result => result,
+        }
+    }
+}
+
+/// The keys of a rotation of the global encryption key.
+pub struct KeyRotation {
+    /// The key which values are re-encrypted from.
+    pub previous: EncryptionKey,
+    /// The key which values are re-encrypted with.
+    pub current: EncryptionKey,
+}
+
 /// Represents an encrypted value with its key ID and nonce.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct EncryptedValue {
@@ -153,3 +273,127 @@ pub fn generate_random_key<const T: usize>()
 
     Ok(key)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn encryption_key_round_trips_through_encryptor() {
+        let key = generate_random_key::<32>().unwrap();
+        let encryptor: Box<dyn Encryptor> = Box::new(EncryptionKey { id: 1, key });
+
+        let encrypted_value = encryptor.encrypt("supersecret".to_string()).await.unwrap();
+        assert_eq!(encrypted_value.id, 1);
+        assert_ne!(encrypted_value.value, "supersecret");
+
+        let decrypted_value = encryptor.decrypt(encrypted_value).await.unwrap();
+        assert_eq!(decrypted_value, "supersecret");
+    }
+
+    #[tokio::test]
+    async fn encryption_key_rejects_values_of_other_keys() {
+        let key = generate_random_key::<32>().unwrap();
+        let encryptor: Box<dyn Encryptor> = Box::new(EncryptionKey { id: 1, key });
+
+        let mut encrypted_value = encryptor.encrypt("supersecret".to_string()).await.unwrap();
+        encrypted_value.id = 2;
+
+        let result = encryptor.decrypt(encrypted_value).await;
+        assert!(matches!(
+            result,
+            Err(DecryptionError::MismatchedKeyId(2, 1))
+        ));
+    }
+
+    fn tenant_key_provider() -> TenantKeyProvider {
+        let global: Arc<dyn Encryptor> = Arc::new(EncryptionKey {
+            id: 0,
+            key: generate_random_key::<32>().unwrap(),
+        });
+        let tenant_key: Arc<dyn Encryptor> = Arc::new(EncryptionKey {
+            id: 1,
+            key: generate_random_key::<32>().unwrap(),
+        });
+
+        TenantKeyProvider::new(
+            global,
+            HashMap::from([("tenant-a".to_string(), tenant_key)]),
+        )
+    }
+
+    #[tokio::test]
+    async fn tenant_scoped_values_are_decrypted_with_the_tenant_key() {
+        let key_provider = tenant_key_provider();
+        let encryptor = key_provider.encryptor("tenant-a");
+
+        let encrypted_value = encryptor.encrypt("supersecret".to_string()).await.unwrap();
+        assert_eq!(encrypted_value.id, 1);
+
+        let decrypted_value = encryptor.decrypt(encrypted_value).await.unwrap();
+        assert_eq!(decrypted_value, "supersecret");
+    }
+
+    #[tokio::test]
+    async fn tenant_scoped_values_cant_be_decrypted_by_other_tenants() {
+        let key_provider = tenant_key_provider();
+
+        let encrypted_value = key_provider
+            .encryptor("tenant-a")
+            .encrypt("supersecret".to_string())
+            .await
+            .unwrap();
+
+        let result = key_provider
+            .encryptor("tenant-b")
+            .decrypt(encrypted_value)
+            .await;
+        assert!(matches!(
+            result,
+            Err(DecryptionError::MismatchedKeyId(1, 0))
+        ));
+    }
+
+    #[tokio::test]
+    async fn values_of_the_previous_key_are_decrypted_during_a_rotation() {
+        let previous: Arc<dyn Encryptor> = Arc::new(EncryptionKey {
+            id: 0,
+            key: generate_random_key::<32>().unwrap(),
+        });
+        let current: Arc<dyn Encryptor> = Arc::new(EncryptionKey {
+            id: 1,
+            key: generate_random_key::<32>().unwrap(),
+        });
+        let encrypted_value = previous.encrypt("supersecret".to_string()).await.unwrap();
+
+        let encryptor = FallbackEncryptor::new(current, previous);
+        let decrypted_value = encryptor.decrypt(encrypted_value).await.unwrap();
+        assert_eq!(decrypted_value, "supersecret");
+
+        // New values are encrypted with the current key.
+        let encrypted_value = encryptor.encrypt("supersecret".to_string()).await.unwrap();
+        assert_eq!(encrypted_value.id, 1);
+    }
+
+    #[tokio::test]
+    async fn tenants_fall_back_to_the_global_key() {
+        let key_provider = tenant_key_provider();
+
+        // Values of tenants without their own key are encrypted with the global key.
+        let encrypted_value = key_provider
+            .encryptor("tenant-b")
+            .encrypt("supersecret".to_string())
+            .await
+            .unwrap();
+        assert_eq!(encrypted_value.id, 0);
+
+        // Values encrypted with the global key before a tenant got its own key can still be
+        // decrypted by the tenant.
+        let decrypted_value = key_provider
+            .encryptor("tenant-a")
+            .decrypt(encrypted_value)
+            .await
+            .unwrap();
+        assert_eq!(decrypted_value, "supersecret");
+    }
+}
@@ -4,7 +4,9 @@ use k8s_openapi::api::{
     apps::v1::StatefulSet,
     core::v1::{ConfigMap, Pod, Secret},
 };
+use k8s_openapi::chrono::Utc;
 use serde_json::json;
+use std::time::Duration;
 use thiserror::Error;
 use tracing::*;
 
@@ -50,7 +52,7 @@ impl From<&str> for PodPhase {
 }
 
 #[async_trait]
-pub trait K8sClient {
+pub trait K8sClient: Send + Sync {
     async fn create_or_update_postgres_secret(
         &self,
         prefix: &str,
@@ -88,6 +90,8 @@ pub trait K8sClient {
 
     async fn get_pod_phase(&self, prefix: &str) -> Result<PodPhase, K8sError>;
 
+    async fn get_pod_uptime(&self, prefix: &str) -> Result<Option<Duration>, K8sError>;
+
     async fn get_replicator_container_error(
         &self,
         prefix: &str,
@@ -533,6 +537,24 @@ impl K8sClient for HttpK8sClient {
         Ok(phase)
     }
 
+    async fn get_pod_uptime(&self, prefix: &str) -> Result<Option<Duration>, K8sError> {
+        info!("getting pod uptime");
+
+        let pod_name = format!("{prefix}-{STATEFUL_SET_NAME_SUFFIX}-0");
+        let pod = match self.pods_api.get(&pod_name).await {
+            Ok(pod) => pod,
+            Err(kube::Error::Api(ref er)) if er.code == 404 => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let uptime = pod
+            .status
+            .and_then(|status| status.start_time)
+            .and_then(|start_time| (Utc::now() - start_time.0).to_std().ok());
+
+        Ok(uptime)
+    }
+
     async fn get_replicator_container_error(
         &self,
         prefix: &str,
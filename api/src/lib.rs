@@ -3,7 +3,9 @@ pub mod config;
 pub mod db;
 pub mod encryption;
 pub mod k8s_client;
+pub mod rate_limit;
 pub mod routes;
+pub mod source_connections;
 pub mod span_builder;
 pub mod startup;
 pub mod utils;
@@ -3,12 +3,12 @@ use api::{config::ApiConfig, startup::Application};
 use config::{Environment, load_config, shared::PgConnectionConfig};
 use std::env;
 use std::sync::Arc;
-use telemetry::init_tracing;
+use telemetry::{TracingConfig, init_tracing};
 use tracing::{error, info};
 
 fn main() -> anyhow::Result<()> {
     // Initialize tracing from the binary name
-    let _log_flusher = init_tracing(env!("CARGO_BIN_NAME"))?;
+    let _log_flusher = init_tracing(env!("CARGO_BIN_NAME"), &TracingConfig::default())?;
 
     // Initialize Sentry before the async runtime starts
     let _sentry_guard = init_sentry()?;
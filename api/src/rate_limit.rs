@@ -0,0 +1,152 @@
+use actix_web::{
+    Error, HttpResponse,
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{self, ContentType},
+    middleware::Next,
+    web::Data,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::RateLimitConfig;
+use crate::routes::{ErrorMessage, extract_tenant_id};
+
+/// The requests counted in the current window of a tenant.
+struct Window {
+    start: Instant,
+    requests: u32,
+}
+
+/// Counts the requests of each tenant in fixed windows of time, as configured by
+/// [`RateLimitConfig`].
+///
+/// Windows start with the first request of a tenant, and are kept in memory, so the limits apply
+/// per replica of the API.
+pub struct TenantRateLimiter {
+    requests_per_window: u32,
+    window: Duration,
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl TenantRateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            requests_per_window: config.requests_per_window,
+            window: Duration::from_secs(config.window_secs),
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a request of `tenant_id` made at `now`.
+    ///
+    /// Returns the time until the window of the tenant ends if the request is over the limit.
+    pub fn check(&self, tenant_id: &str, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().expect("rate limiter lock poisoned");
+
+        // The windows which ended are dropped whenever a tenant without a window makes a request,
+        // so that tenants which stopped making requests don't take memory forever.
+        if !windows.contains_key(tenant_id) {
+            windows.retain(|_, window| now.duration_since(window.start) < self.window);
+        }
+
+        let window = windows.entry(tenant_id.to_string()).or_insert(Window {
+            start: now,
+            requests: 0,
+        });
+        let elapsed = now.duration_since(window.start);
+        if elapsed >= self.window {
+            window.start = now;
+            window.requests = 0;
+        }
+
+        if window.requests >= self.requests_per_window {
+            return Err(self.window - now.duration_since(window.start));
+        }
+        window.requests += 1;
+
+        Ok(())
+    }
+}
+
+/// Rejects the requests of tenants over their limit with a `429 Too Many Requests`, whose
+/// `Retry-After` header is the number of seconds until the window of the tenant ends.
+///
+/// Requests without a valid tenant id are let through, to be rejected by their handler.
+pub async fn rate_limit_tenants<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let retry_after = match (
+        req.app_data::<Data<TenantRateLimiter>>(),
+        extract_tenant_id(req.request()),
+    ) {
+        (Some(limiter), Ok(tenant_id)) => limiter.check(tenant_id, Instant::now()).err(),
+        _ => None,
+    };
+
+    let Some(retry_after) = retry_after else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    let error_message = ErrorMessage {
+        code: "too_many_requests",
+        error: "too many requests, retry later".to_string(),
+    };
+    let body = serde_json::to_string(&error_message).expect("failed to serialize error message");
+    // The header is rounded up, so that retrying after it is never too early.
+    let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let response = HttpResponse::TooManyRequests()
+        .insert_header(ContentType::json())
+        .insert_header((header::RETRY_AFTER, retry_after_secs))
+        .body(body);
+
+    Ok(req.into_response(response).map_into_right_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests_per_window: u32) -> TenantRateLimiter {
+        TenantRateLimiter::new(&RateLimitConfig {
+            requests_per_window,
+            window_secs: 60,
+        })
+    }
+
+    #[test]
+    fn requests_over_the_limit_are_rejected_until_the_window_ends() {
+        let limiter = limiter(2);
+        let start = Instant::now();
+
+        assert!(limiter.check("tenant_a", start).is_ok());
+        assert!(
+            limiter
+                .check("tenant_a", start + Duration::from_secs(1))
+                .is_ok()
+        );
+        assert_eq!(
+            limiter.check("tenant_a", start + Duration::from_secs(10)),
+            Err(Duration::from_secs(50))
+        );
+
+        // The window of the tenant ends 60 seconds after its first request.
+        assert!(
+            limiter
+                .check("tenant_a", start + Duration::from_secs(60))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn tenants_are_limited_independently() {
+        let limiter = limiter(1);
+        let now = Instant::now();
+
+        assert!(limiter.check("tenant_a", now).is_ok());
+        assert!(limiter.check("tenant_a", now).is_err());
+        assert!(limiter.check("tenant_b", now).is_ok());
+    }
+}
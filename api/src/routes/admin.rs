@@ -0,0 +1,473 @@
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get,
+    http::{StatusCode, header::ContentType},
+    post,
+    web::{Data, Json, Path},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::db;
+use crate::db::maintenance::MaintenanceDbError;
+use crate::db::replicators::ReplicatorsDbError;
+use crate::db::sources::SourcesDbError;
+use crate::encryption::KeyRotation;
+use crate::k8s_client::{K8sClient, K8sError, PodPhase};
+use crate::routes::pipelines::create_k8s_object_prefix;
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE};
+use crate::span_builder::TenantIdMasker;
+
+#[derive(Debug, Error)]
+enum TaskError {
+    #[error("The task with id {0} was not found")]
+    TaskNotFound(i64),
+
+    #[error("A K8s error occurred: {0}")]
+    K8s(#[from] K8sError),
+
+    #[error(transparent)]
+    ReplicatorsDb(#[from] ReplicatorsDbError),
+}
+
+impl TaskError {
+    fn to_message(&self) -> String {
+        match self {
+            // Do not expose internal database details in error messages
+            TaskError::ReplicatorsDb(ReplicatorsDbError::Database(_)) => {
+                "internal server error".to_string()
+            }
+            // Every other message is ok, as they do not divulge sensitive information
+            e => e.to_string(),
+        }
+    }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            TaskError::TaskNotFound(_) => "task_not_found",
+            TaskError::K8s(_) => "k8s_error",
+            TaskError::ReplicatorsDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
+}
+
+impl ResponseError for TaskError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            TaskError::K8s(_) | TaskError::ReplicatorsDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            TaskError::TaskNotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error_message = ErrorMessage {
+            code: self.to_code(),
+            error: self.to_message(),
+        };
+        let body =
+            serde_json::to_string(&error_message).expect("failed to serialize error message");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Starting,
+    Running,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadTaskResponse {
+    /// The id of the task, which is the id of the replicator running the pipeline.
+    #[schema(example = 1)]
+    pub id: i64,
+    #[schema(example = 1)]
+    pub pipeline_id: i64,
+    #[schema(example = "abcdefghijklmnopqrst")]
+    pub tenant_id: String,
+    pub state: TaskState,
+    /// Seconds since the task started, if it has started.
+    #[schema(example = 3600)]
+    pub uptime_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadTasksResponse {
+    pub tasks: Vec<ReadTaskResponse>,
+}
+
+#[utoipa::path(
+    context_path = "/admin",
+    responses(
+        (status = 200, description = "Return all running tasks across tenants", body = ReadTasksResponse),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Admin"
+)]
+#[get("/tasks")]
+pub async fn read_all_tasks(
+    pool: Data<PgPool>,
+    k8s_client: Data<Arc<dyn K8sClient>>,
+) -> Result<impl Responder, TaskError> {
+    let replicator_pipelines = db::replicators::read_all_replicator_pipelines(&**pool).await?;
+
+    let mut tasks = vec![];
+    for replicator_pipeline in replicator_pipelines {
+        let replicator = replicator_pipeline.replicator;
+        let prefix = create_k8s_object_prefix(&replicator.tenant_id, replicator.id);
+
+        // Only pods which are starting or running are tasks, stopped pipelines have no pod and
+        // failed ones are not doing any work.
+        let state = match k8s_client.get_pod_phase(&prefix).await? {
+            PodPhase::Pending => TaskState::Starting,
+            PodPhase::Running => TaskState::Running,
+            PodPhase::Succeeded | PodPhase::Failed | PodPhase::Unknown => continue,
+        };
+        let uptime = k8s_client.get_pod_uptime(&prefix).await?;
+
+        tasks.push(ReadTaskResponse {
+            id: replicator.id,
+            pipeline_id: replicator_pipeline.pipeline_id,
+            tenant_id: replicator.tenant_id,
+            state,
+            uptime_secs: uptime.map(|uptime| uptime.as_secs()),
+        });
+    }
+
+    let response = ReadTasksResponse { tasks };
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    context_path = "/admin",
+    params(
+        ("task_id" = i64, Path, description = "Id of the task"),
+    ),
+    responses(
+        (status = 200, description = "Cancel the task"),
+        (status = 404, description = "Task not found", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Admin"
+)]
+#[post("/tasks/{task_id}/cancel")]
+pub async fn cancel_task(
+    pool: Data<PgPool>,
+    k8s_client: Data<Arc<dyn K8sClient>>,
+    tenant_id_masker: Data<TenantIdMasker>,
+    task_id: Path<i64>,
+) -> Result<impl Responder, TaskError> {
+    let task_id = task_id.into_inner();
+
+    let replicator_pipeline = db::replicators::read_replicator_pipeline(&**pool, task_id)
+        .await?
+        .ok_or(TaskError::TaskNotFound(task_id))?;
+    let replicator = replicator_pipeline.replicator;
+
+    info!(
+        "cancelling task {} of pipeline {} of tenant {}",
+        replicator.id,
+        replicator_pipeline.pipeline_id,
+        tenant_id_masker.mask(&replicator.tenant_id)
+    );
+
+    // Deleting the stateful set terminates the replicator, which closes its replication
+    // connection on shutdown so that the slot is released and stops receiving status updates.
+    // The slot itself is kept, so that the pipeline resumes where it stopped once started again.
+    let prefix = create_k8s_object_prefix(&replicator.tenant_id, replicator.id);
+    k8s_client.delete_postgres_secret(&prefix).await?;
+    k8s_client.delete_bq_secret(&prefix).await?;
+    k8s_client.delete_config_map(&prefix).await?;
+    k8s_client.delete_stateful_set(&prefix).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(Debug, Error)]
+enum MaintenanceError {
+    #[error(transparent)]
+    MaintenanceDb(#[from] MaintenanceDbError),
+}
+
+impl MaintenanceError {
+    fn to_message(&self) -> String {
+        match self {
+            // Do not expose internal database details in error messages
+            MaintenanceError::MaintenanceDb(MaintenanceDbError::Database(_)) => {
+                "internal server error".to_string()
+            }
+        }
+    }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            MaintenanceError::MaintenanceDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
+}
+
+impl ResponseError for MaintenanceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MaintenanceError::MaintenanceDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error_message = ErrorMessage {
+            code: self.to_code(),
+            error: self.to_message(),
+        };
+        let body =
+            serde_json::to_string(&error_message).expect("failed to serialize error message");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct RunMaintenanceRequest {
+    /// Whether to `vacuum` the tables in addition to analyzing them.
+    #[serde(default)]
+    #[schema(example = false)]
+    pub vacuum: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RunMaintenanceResponse {
+    /// The control-plane tables on which maintenance was run.
+    #[schema(example = json!(["app.pipelines", "app.sources"]))]
+    pub tables: Vec<String>,
+}
+
+#[utoipa::path(
+    context_path = "/admin",
+    request_body = RunMaintenanceRequest,
+    responses(
+        (status = 200, description = "Run maintenance on the control-plane tables", body = RunMaintenanceResponse),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Admin"
+)]
+#[post("/maintenance")]
+pub async fn run_maintenance(
+    pool: Data<PgPool>,
+    maintenance: Option<Json<RunMaintenanceRequest>>,
+) -> Result<impl Responder, MaintenanceError> {
+    let maintenance = maintenance
+        .map(|maintenance| maintenance.into_inner())
+        .unwrap_or_default();
+
+    info!(
+        "running maintenance on the control-plane tables (vacuum: {})",
+        maintenance.vacuum
+    );
+
+    let tables = db::maintenance::run_maintenance(&pool, maintenance.vacuum).await?;
+    let response = RunMaintenanceResponse { tables };
+
+    Ok(Json(response))
+}
+
+#[derive(Debug, Error)]
+enum ReencryptionError {
+    #[error("No previous encryption key is configured, so there is nothing to re-encrypt")]
+    NoPreviousKey,
+
+    #[error(transparent)]
+    SourcesDb(#[from] SourcesDbError),
+}
+
+impl ReencryptionError {
+    fn to_message(&self) -> String {
+        match self {
+            // Do not expose internal database details in error messages
+            ReencryptionError::SourcesDb(SourcesDbError::Database(_)) => {
+                "internal server error".to_string()
+            }
+            // Every other message is ok, as they do not divulge sensitive information
+            e => e.to_string(),
+        }
+    }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            ReencryptionError::NoPreviousKey => "no_previous_encryption_key",
+            ReencryptionError::SourcesDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
+}
+
+impl ResponseError for ReencryptionError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ReencryptionError::NoPreviousKey => StatusCode::BAD_REQUEST,
+            ReencryptionError::SourcesDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error_message = ErrorMessage {
+            code: self.to_code(),
+            error: self.to_message(),
+        };
+        let body =
+            serde_json::to_string(&error_message).expect("failed to serialize error message");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReencryptSourcesResponse {
+    /// The number of sources re-encrypted with the current encryption key.
+    #[schema(example = 3)]
+    pub reencrypted_sources: u64,
+}
+
+#[utoipa::path(
+    context_path = "/admin",
+    responses(
+        (status = 200, description = "Re-encrypt the sources encrypted with the previous encryption key", body = ReencryptSourcesResponse),
+        (status = 400, description = "No previous encryption key is configured", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Admin"
+)]
+#[post("/encryption/reencrypt-sources")]
+pub async fn reencrypt_sources(
+    pool: Data<PgPool>,
+    key_rotation: Data<Option<KeyRotation>>,
+) -> Result<impl Responder, ReencryptionError> {
+    let key_rotation = key_rotation
+        .get_ref()
+        .as_ref()
+        .ok_or(ReencryptionError::NoPreviousKey)?;
+
+    info!(
+        "re-encrypting the sources encrypted with key {} with key {}",
+        key_rotation.previous.id, key_rotation.current.id
+    );
+
+    let reencrypted_sources =
+        db::sources::reencrypt_all(&pool, &key_rotation.previous, &key_rotation.current).await?;
+    let response = ReencryptSourcesResponse {
+        reencrypted_sources,
+    };
+
+    Ok(Json(response))
+}
+
+/// Default number of seconds after which deleted sources are purged.
+const DEFAULT_PURGE_GRACE_PERIOD_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Error)]
+enum PurgeError {
+    #[error(transparent)]
+    SourcesDb(#[from] SourcesDbError),
+}
+
+impl PurgeError {
+    fn to_message(&self) -> String {
+        match self {
+            // Do not expose internal database details in error messages
+            PurgeError::SourcesDb(SourcesDbError::Database(_)) => {
+                "internal server error".to_string()
+            }
+            // Every other message is ok, as they do not divulge sensitive information
+            e => e.to_string(),
+        }
+    }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            PurgeError::SourcesDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
+}
+
+impl ResponseError for PurgeError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PurgeError::SourcesDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error_message = ErrorMessage {
+            code: self.to_code(),
+            error: self.to_message(),
+        };
+        let body =
+            serde_json::to_string(&error_message).expect("failed to serialize error message");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PurgeDeletedSourcesRequest {
+    /// Only the sources deleted more than this many seconds ago are purged, defaults to 7 days.
+    #[serde(default = "default_purge_grace_period_secs")]
+    #[schema(example = 604800)]
+    pub grace_period_secs: u64,
+}
+
+impl Default for PurgeDeletedSourcesRequest {
+    fn default() -> Self {
+        Self {
+            grace_period_secs: default_purge_grace_period_secs(),
+        }
+    }
+}
+
+fn default_purge_grace_period_secs() -> u64 {
+    DEFAULT_PURGE_GRACE_PERIOD_SECS
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PurgeDeletedSourcesResponse {
+    /// The number of deleted sources which were permanently removed.
+    #[schema(example = 3)]
+    pub purged_sources: u64,
+}
+
+#[utoipa::path(
+    context_path = "/admin",
+    request_body = PurgeDeletedSourcesRequest,
+    responses(
+        (status = 200, description = "Permanently remove the sources deleted before the grace period", body = PurgeDeletedSourcesResponse),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Admin"
+)]
+#[post("/sources/purge-deleted")]
+pub async fn purge_deleted_sources(
+    pool: Data<PgPool>,
+    purge: Option<Json<PurgeDeletedSourcesRequest>>,
+) -> Result<impl Responder, PurgeError> {
+    let purge = purge.map(|purge| purge.into_inner()).unwrap_or_default();
+
+    info!(
+        "purging the sources deleted more than {} seconds ago",
+        purge.grace_period_secs
+    );
+
+    let purged_sources =
+        db::sources::purge_deleted_sources(&**pool, Duration::from_secs(purge.grace_period_secs))
+            .await?;
+    let response = PurgeDeletedSourcesResponse { purged_sources };
+
+    Ok(Json(response))
+}
@@ -0,0 +1,209 @@
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, ResponseError, delete, get,
+    http::{StatusCode, header::ContentType},
+    post,
+    web::{Data, Json, Path},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::db;
+use crate::db::api_keys::{ApiKeyScope, ApiKeysDbError};
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE, TenantIdError, extract_tenant_id};
+
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    #[error("The api key with id {0} was not found")]
+    ApiKeyNotFound(i64),
+
+    #[error("The api key could not be generated")]
+    KeyGeneration,
+
+    #[error(transparent)]
+    TenantId(#[from] TenantIdError),
+
+    #[error(transparent)]
+    ApiKeysDb(#[from] ApiKeysDbError),
+}
+
+impl ApiKeyError {
+    pub fn to_message(&self) -> String {
+        match self {
+            // Do not expose internal database details in error messages
+            ApiKeyError::ApiKeysDb(_) => "internal server error".to_string(),
+            // Every other message is ok, as they do not divulge sensitive information
+            e => e.to_string(),
+        }
+    }
+
+    pub fn to_code(&self) -> &'static str {
+        match self {
+            ApiKeyError::ApiKeyNotFound(_) => "api_key_not_found",
+            ApiKeyError::TenantId(e) => e.to_code(),
+            ApiKeyError::KeyGeneration | ApiKeyError::ApiKeysDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
+}
+
+impl ResponseError for ApiKeyError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiKeyError::ApiKeysDb(_) | ApiKeyError::KeyGeneration => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            ApiKeyError::ApiKeyNotFound(_) => StatusCode::NOT_FOUND,
+            ApiKeyError::TenantId(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error_message = ErrorMessage {
+            code: self.to_code(),
+            error: self.to_message(),
+        };
+        let body =
+            serde_json::to_string(&error_message).expect("failed to serialize error message");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateApiKeyRequest {
+    #[schema(example = "Nightly sync", required = true)]
+    pub name: String,
+    #[schema(required = true)]
+    pub scope: ApiKeyScope,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateApiKeyResponse {
+    #[schema(example = 1)]
+    pub id: i64,
+    /// The api key, which is only returned here since only its hash is stored.
+    #[schema(example = "XOUbHmWbt9h7nWl15wWwyWQnctmFGNjpawMc3lT5CFs=")]
+    pub key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadApiKeyResponse {
+    #[schema(example = 1)]
+    pub id: i64,
+    #[schema(example = "abczjjlmfsijwrlnwatw")]
+    pub tenant_id: String,
+    #[schema(example = "Nightly sync")]
+    pub name: String,
+    pub scope: ApiKeyScope,
+    #[schema(example = "2025-08-01T09:00:00.000000Z")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadApiKeysResponse {
+    pub api_keys: Vec<ReadApiKeyResponse>,
+}
+
+/// Creates an api key for the tenant, which authenticates requests as the tenant with the
+/// permissions of its scope. Api keys can only be managed with the api key of the deployment.
+#[utoipa::path(
+    context_path = "/v1",
+    request_body = CreateApiKeyRequest,
+    params(
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Create new api key", body = CreateApiKeyResponse),
+        (status = 400, description = "Bad request", body = ErrorMessage),
+        (status = 403, description = "Forbidden to the api keys of tenants", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Api Keys"
+)]
+#[post("/api-keys")]
+pub async fn create_api_key(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    api_key: Json<CreateApiKeyRequest>,
+) -> Result<impl Responder, ApiKeyError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let api_key = api_key.into_inner();
+
+    let key = db::api_keys::generate_api_key().map_err(|_| ApiKeyError::KeyGeneration)?;
+    let key_hash = db::api_keys::hash_api_key(&key);
+    let id =
+        db::api_keys::create_api_key(&**pool, tenant_id, &api_key.name, &key_hash, api_key.scope)
+            .await?;
+
+    let response = CreateApiKeyResponse { id, key };
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    params(
+        ("api_key_id" = i64, Path, description = "Id of the api key"),
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Delete an api key"),
+        (status = 403, description = "Forbidden to the api keys of tenants", body = ErrorMessage),
+        (status = 404, description = "Api key not found", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Api Keys"
+)]
+#[delete("/api-keys/{api_key_id}")]
+pub async fn delete_api_key(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    api_key_id: Path<i64>,
+) -> Result<impl Responder, ApiKeyError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let api_key_id = api_key_id.into_inner();
+
+    db::api_keys::delete_api_key(&**pool, tenant_id, api_key_id)
+        .await?
+        .ok_or(ApiKeyError::ApiKeyNotFound(api_key_id))?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    params(
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "A list of all the api keys of a tenant, without the keys", body = ReadApiKeysResponse),
+        (status = 403, description = "Forbidden to the api keys of tenants", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Api Keys"
+)]
+#[get("/api-keys")]
+pub async fn read_all_api_keys(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+) -> Result<impl Responder, ApiKeyError> {
+    let tenant_id = extract_tenant_id(&req)?;
+
+    let api_keys = db::api_keys::read_all_api_keys(&**pool, tenant_id)
+        .await?
+        .into_iter()
+        .map(|api_key| ReadApiKeyResponse {
+            id: api_key.id,
+            tenant_id: api_key.tenant_id,
+            name: api_key.name,
+            scope: api_key.scope,
+            created_at: api_key.created_at,
+        })
+        .collect();
+
+    let response = ReadApiKeysResponse { api_keys };
+
+    Ok(Json(response))
+}
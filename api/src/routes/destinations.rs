@@ -7,13 +7,14 @@ use actix_web::{
 use config::shared::DestinationConfig;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
 use thiserror::Error;
 use utoipa::ToSchema;
 
 use crate::db;
 use crate::db::destinations::DestinationsDbError;
-use crate::encryption::EncryptionKey;
-use crate::routes::{ErrorMessage, TenantIdError, extract_tenant_id};
+use crate::encryption::KeyProvider;
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE, TenantIdError, extract_tenant_id};
 
 #[derive(Debug, Error)]
 pub enum DestinationError {
@@ -38,6 +39,14 @@ impl DestinationError {
             e => e.to_string(),
         }
     }
+
+    pub fn to_code(&self) -> &'static str {
+        match self {
+            DestinationError::DestinationNotFound(_) => "destination_not_found",
+            DestinationError::TenantId(e) => e.to_code(),
+            DestinationError::DestinationsDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
 }
 
 impl ResponseError for DestinationError {
@@ -51,6 +60,7 @@ impl ResponseError for DestinationError {
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -116,10 +126,11 @@ pub struct ReadDestinationsResponse {
 pub async fn create_destination(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     destination: Json<CreateDestinationRequest>,
 ) -> Result<impl Responder, DestinationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let destination = destination.into_inner();
 
     let id = db::destinations::create_destination(
@@ -127,7 +138,7 @@ pub async fn create_destination(
         tenant_id,
         &destination.name,
         destination.config,
-        &encryption_key,
+        encryptor,
     )
     .await?;
 
@@ -153,14 +164,15 @@ pub async fn create_destination(
 pub async fn read_destination(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     destination_id: Path<i64>,
 ) -> Result<impl Responder, DestinationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let destination_id = destination_id.into_inner();
 
     let response =
-        db::destinations::read_destination(&**pool, tenant_id, destination_id, &encryption_key)
+        db::destinations::read_destination(&**pool, tenant_id, destination_id, encryptor)
             .await?
             .map(|s| ReadDestinationResponse {
                 id: s.id,
@@ -192,10 +204,11 @@ pub async fn update_destination(
     req: HttpRequest,
     pool: Data<PgPool>,
     destination_id: Path<i64>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     destination: Json<UpdateDestinationRequest>,
 ) -> Result<impl Responder, DestinationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let destination_id = destination_id.into_inner();
     let destination = destination.into_inner();
 
@@ -205,7 +218,7 @@ pub async fn update_destination(
         &destination.name,
         destination_id,
         destination.config,
-        &encryption_key,
+        encryptor,
     )
     .await?
     .ok_or(DestinationError::DestinationNotFound(destination_id))?;
@@ -257,13 +270,14 @@ pub async fn delete_destination(
 pub async fn read_all_destinations(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
 ) -> Result<impl Responder, DestinationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
 
     let mut destinations = vec![];
     for destination in
-        db::destinations::read_all_destinations(&**pool, tenant_id, &encryption_key).await?
+        db::destinations::read_all_destinations(&**pool, tenant_id, encryptor).await?
     {
         let destination = ReadDestinationResponse {
             id: destination.id,
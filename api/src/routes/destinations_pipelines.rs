@@ -8,6 +8,7 @@ use config::shared::DestinationConfig;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::ops::DerefMut;
+use std::sync::Arc;
 use thiserror::Error;
 use utoipa::ToSchema;
 
@@ -17,9 +18,12 @@ use crate::db::destinations_pipelines::DestinationPipelinesDbError;
 use crate::db::images::ImagesDbError;
 use crate::db::pipelines::PipelineConfig;
 use crate::db::sources::{SourcesDbError, source_exists};
-use crate::encryption::EncryptionKey;
+use crate::encryption::KeyProvider;
 
-use super::{ErrorMessage, TenantIdError, destinations::DestinationError, extract_tenant_id};
+use super::{
+    ErrorMessage, INTERNAL_ERROR_CODE, TenantIdError, destinations::DestinationError,
+    extract_tenant_id,
+};
 
 #[derive(Debug, Error)]
 enum DestinationPipelineError {
@@ -88,6 +92,23 @@ impl DestinationPipelineError {
             e => e.to_string(),
         }
     }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            DestinationPipelineError::NoDefaultImageFound => "default_image_not_found",
+            DestinationPipelineError::TenantId(e) => e.to_code(),
+            DestinationPipelineError::SourceNotFound(_) => "source_not_found",
+            DestinationPipelineError::DestinationNotFound(_) => "destination_not_found",
+            DestinationPipelineError::PipelineNotFound(_) => "pipeline_not_found",
+            DestinationPipelineError::Destination(e) => e.to_code(),
+            DestinationPipelineError::DuplicatePipeline => "duplicate_pipeline",
+            DestinationPipelineError::DestinationPipelinesDb(_)
+            | DestinationPipelineError::DestinationsDb(_)
+            | DestinationPipelineError::ImagesDb(_)
+            | DestinationPipelineError::SourcesDb(_)
+            | DestinationPipelineError::Database(_) => INTERNAL_ERROR_CODE,
+        }
+    }
 }
 
 impl ResponseError for DestinationPipelineError {
@@ -110,6 +131,7 @@ impl ResponseError for DestinationPipelineError {
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -171,9 +193,10 @@ pub async fn create_destination_and_pipeline(
     req: HttpRequest,
     pool: Data<PgPool>,
     destination_and_pipeline: Json<CreateDestinationPipelineRequest>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
 ) -> Result<impl Responder, DestinationPipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let destination_and_pipeline = destination_and_pipeline.into_inner();
 
     let mut txn = pool.begin().await?;
@@ -201,7 +224,7 @@ pub async fn create_destination_and_pipeline(
             destination_and_pipeline.destination_config,
             image.id,
             destination_and_pipeline.pipeline_config,
-            &encryption_key,
+            encryptor,
         )
         .await?;
     txn.commit().await?;
@@ -236,9 +259,10 @@ pub async fn update_destination_and_pipeline(
     pool: Data<PgPool>,
     destination_and_pipeline_ids: Path<(i64, i64)>,
     destination_and_pipeline: Json<UpdateDestinationPipelineRequest>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
 ) -> Result<impl Responder, DestinationPipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let (destination_id, pipeline_id) = destination_and_pipeline_ids.into_inner();
     let destination_and_pipeline = destination_and_pipeline.into_inner();
 
@@ -270,7 +294,7 @@ pub async fn update_destination_and_pipeline(
         &destination_and_pipeline.destination_name,
         destination_and_pipeline.destination_config,
         destination_and_pipeline.pipeline_config,
-        &encryption_key,
+        encryptor,
     )
     .await
     .map_err(|e| match e {
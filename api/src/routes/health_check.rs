@@ -1,4 +1,73 @@
-use actix_web::{HttpResponse, Responder, get};
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get,
+    http::{StatusCode, header::ContentType},
+    web::{Data, Json},
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use tracing::error;
+use utoipa::ToSchema;
+
+#[derive(Debug, Error)]
+pub enum ReadinessError {
+    #[error("no connection to the database could be acquired")]
+    DatabaseConnection(#[source] sqlx::Error),
+
+    #[error("the database could not run a query")]
+    DatabaseQuery(#[source] sqlx::Error),
+}
+
+impl ReadinessError {
+    fn failed_check(&self) -> ReadinessCheck {
+        match self {
+            ReadinessError::DatabaseConnection(_) => ReadinessCheck::DatabaseConnection,
+            ReadinessError::DatabaseQuery(_) => ReadinessCheck::DatabaseQuery,
+        }
+    }
+}
+
+impl ResponseError for ReadinessError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        // The database errors are only logged, since the probes don't require authentication.
+        let (ReadinessError::DatabaseConnection(e) | ReadinessError::DatabaseQuery(e)) = self;
+        error!("readiness check failed, {self}: {e}");
+
+        let response = ReadinessFailedResponse {
+            failed_check: self.failed_check(),
+            error: self.to_string(),
+        };
+        let body = serde_json::to_string(&response).expect("failed to serialize error response");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+/// A check run by the readiness probe.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessCheck {
+    /// A connection to the database is acquired from the pool.
+    DatabaseConnection,
+    /// A query is run on the acquired connection.
+    DatabaseQuery,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessFailedResponse {
+    pub failed_check: ReadinessCheck,
+    pub error: String,
+}
 
 #[utoipa::path(
     tag = "Health",
@@ -10,3 +79,40 @@ use actix_web::{HttpResponse, Responder, get};
 pub async fn health_check() -> impl Responder {
     HttpResponse::Ok().body("ok")
 }
+
+/// Liveness probe, which succeeds as long as the process serves requests.
+#[utoipa::path(
+    tag = "Health",
+    responses(
+        (status = 200, description = "API is live", body = String),
+    )
+)]
+#[get("/health/live")]
+pub async fn health_live() -> impl Responder {
+    HttpResponse::Ok().body("ok")
+}
+
+/// Readiness probe, which succeeds when a connection to the database can be acquired from the
+/// pool and run a query, so that traffic is only routed to instances able to serve it.
+#[utoipa::path(
+    tag = "Health",
+    responses(
+        (status = 200, description = "API is ready", body = ReadinessResponse),
+        (status = 503, description = "A readiness check failed", body = ReadinessFailedResponse),
+    )
+)]
+#[get("/health/ready")]
+pub async fn health_ready(pool: Data<PgPool>) -> Result<impl Responder, ReadinessError> {
+    let mut connection = pool
+        .acquire()
+        .await
+        .map_err(ReadinessError::DatabaseConnection)?;
+    sqlx::query("select 1")
+        .execute(&mut *connection)
+        .await
+        .map_err(ReadinessError::DatabaseQuery)?;
+
+    Ok(Json(ReadinessResponse {
+        status: "ok".to_string(),
+    }))
+}
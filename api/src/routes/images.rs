@@ -11,7 +11,7 @@ use utoipa::ToSchema;
 
 use crate::db;
 use crate::db::images::ImagesDbError;
-use crate::routes::ErrorMessage;
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE};
 
 #[derive(Debug, Error)]
 enum ImageError {
@@ -31,6 +31,13 @@ impl ImageError {
             e => e.to_string(),
         }
     }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            ImageError::ImageNotFound(_) => "image_not_found",
+            ImageError::ImagesDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
 }
 
 impl ResponseError for ImageError {
@@ -43,6 +50,7 @@ impl ResponseError for ImageError {
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -0,0 +1,57 @@
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get,
+    http::{StatusCode, header::ContentType},
+};
+use prometheus::{Encoder, Registry, TextEncoder};
+use std::sync::LazyLock;
+use thiserror::Error;
+
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE};
+
+/// Registry of the metrics of the api.
+///
+/// It's kept apart from the default registry, in which the etl crate registers the metrics of the
+/// pipelines, since those are counted and served by the replicators running them.
+pub static API_REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+#[derive(Debug, Error)]
+enum MetricsError {
+    #[error("The metrics could not be encoded: {0}")]
+    Encoding(#[from] prometheus::Error),
+}
+
+impl ResponseError for MetricsError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error_message = ErrorMessage {
+            code: INTERNAL_ERROR_CODE,
+            error: self.to_string(),
+        };
+        let body =
+            serde_json::to_string(&error_message).expect("failed to serialize error message");
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(body)
+    }
+}
+
+#[utoipa::path(
+    tag = "Metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", body = String),
+        (status = 500, description = "Internal server error")
+    )
+)]
+#[get("/metrics")]
+pub async fn metrics() -> Result<impl Responder, MetricsError> {
+    let encoder = TextEncoder::new();
+    let mut body = vec![];
+    encoder.encode(&API_REGISTRY.gather(), &mut body)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(encoder.format_type())
+        .body(body))
+}
@@ -1,18 +1,31 @@
-use actix_web::HttpRequest;
+use actix_web::body::BoxBody;
+use actix_web::http::header;
+use actix_web::web::Json;
+use actix_web::{HttpRequest, HttpResponse, Responder};
 use serde::Serialize;
 use thiserror::Error;
+use tracing::error;
 
+pub mod admin;
+pub mod api_keys;
 pub mod destinations;
 pub mod destinations_pipelines;
 pub mod health_check;
 pub mod images;
+pub mod metrics;
 pub mod pipelines;
 pub mod sources;
 pub mod tenants;
 pub mod tenants_sources;
 
+/// The code of the errors whose details are not exposed, like database errors.
+pub const INTERNAL_ERROR_CODE: &str = "internal_error";
+
 #[derive(Serialize)]
 pub struct ErrorMessage {
+    /// A machine-readable code of the error, e.g. `source_not_found`, which clients can branch on.
+    pub code: &'static str,
+    /// A description of the error for humans, which may change over time.
     pub error: String,
 }
 
@@ -25,7 +38,16 @@ pub enum TenantIdError {
     TenantIdIllFormed,
 }
 
-fn extract_tenant_id(req: &HttpRequest) -> Result<&str, TenantIdError> {
+impl TenantIdError {
+    pub fn to_code(&self) -> &'static str {
+        match self {
+            TenantIdError::TenantIdMissing => "missing_tenant_id",
+            TenantIdError::TenantIdIllFormed => "invalid_tenant_id",
+        }
+    }
+}
+
+pub(crate) fn extract_tenant_id(req: &HttpRequest) -> Result<&str, TenantIdError> {
     let headers = req.headers();
     let tenant_id = headers
         .get("tenant_id")
@@ -35,3 +57,48 @@ fn extract_tenant_id(req: &HttpRequest) -> Result<&str, TenantIdError> {
 
     Ok(tenant_id)
 }
+
+/// Content type used for MessagePack encoded response bodies.
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Response body which is serialized according to the request's `Accept` header.
+///
+/// Requests accepting `application/msgpack` get a MessagePack body with named fields, all other
+/// requests get JSON.
+pub struct Negotiated<T>(pub T);
+
+impl<T: Serialize> Responder for Negotiated<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        if !accepts_msgpack(req) {
+            return Json(self.0).respond_to(req).map_into_boxed_body();
+        }
+
+        match rmp_serde::to_vec_named(&self.0) {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(MSGPACK_CONTENT_TYPE)
+                .body(body),
+            Err(e) => {
+                error!("failed to serialize response as MessagePack: {e}");
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+fn accepts_msgpack(req: &HttpRequest) -> bool {
+    let Some(accept) = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    accept.split(',').any(|media_range| {
+        let media_type = media_range.split(';').next().unwrap_or_default().trim();
+        media_type.eq_ignore_ascii_case(MSGPACK_CONTENT_TYPE)
+            || media_type.eq_ignore_ascii_case("application/x-msgpack")
+    })
+}
@@ -5,9 +5,17 @@ use actix_web::{
     web::{Data, Json, Path},
 };
 use config::shared::{
-    DestinationConfig, PgConnectionConfig, PipelineConfig as SharedPipelineConfig,
-    ReplicatorConfig, SupabaseConfig, TlsConfig,
+    BatchFlushMode, CopyConfig, DestinationConfig, IntoConnectOptions, NullPolicy,
+    PgConnectionConfig, PipelineConfig as SharedPipelineConfig, ReplicationMode, ReplicatorConfig,
+    StatementTimeoutConfig, SupabaseConfig, TlsConfig, TlsVerification, ValidationError,
 };
+use etl::destination::bigquery::{BigQueryDestination, BigQueryDestinationError};
+use etl::destination::column_filter::ColumnFilter;
+use etl::destination::compatibility::CompatibilityReport;
+use etl::encryption::bigquery::install_crypto_provider_once;
+use etl::replication::client::{PgReplicationClient, PgReplicationError};
+use etl::replication::slot::{SlotError, get_slot_name};
+use etl::workers::base::WorkerType;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, PgTransaction};
 use std::ops::DerefMut;
@@ -19,13 +27,15 @@ use crate::db;
 use crate::db::destinations::{Destination, DestinationsDbError, destination_exists};
 use crate::db::images::{Image, ImagesDbError};
 use crate::db::pipelines::{Pipeline, PipelineConfig, PipelinesDbError};
+use crate::db::replication_slots::ReplicationSlotsDbError;
 use crate::db::replicators::{Replicator, ReplicatorsDbError};
 use crate::db::sources::{Source, SourceConfig, SourcesDbError, source_exists};
-use crate::encryption::EncryptionKey;
-use crate::k8s_client::{
-    HttpK8sClient, K8sClient, K8sError, PodPhase, TRUSTED_ROOT_CERT_CONFIG_MAP_NAME,
-};
-use crate::routes::{ErrorMessage, TenantIdError, extract_tenant_id};
+use crate::db::table_copies::TableCopiesDbError;
+use crate::db::table_resyncs::{TableResyncRequest, TableResyncsDbError};
+use crate::encryption::{Encryptor, KeyProvider};
+use crate::k8s_client::{K8sClient, K8sError, PodPhase, TRUSTED_ROOT_CERT_CONFIG_MAP_NAME};
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE, TenantIdError, extract_tenant_id};
+use crate::source_connections::SourceConnectionLimiter;
 use secrecy::ExposeSecret;
 
 #[derive(Debug, Error)]
@@ -81,6 +91,36 @@ enum PipelineError {
     #[error("The specified image with id {0} was not found")]
     ImageNotFoundById(i64),
 
+    #[error(transparent)]
+    InvalidPipelineConfig(#[from] ValidationError),
+
+    #[error("Only BigQuery destinations can be verified")]
+    UnverifiableDestination,
+
+    #[error("Failed to read the source table schemas: {0}")]
+    PgReplication(#[from] PgReplicationError),
+
+    #[error("Failed to read the destination tables: {0}")]
+    BigQueryDestination(#[from] BigQueryDestinationError),
+
+    #[error(transparent)]
+    ReplicationSlotsDb(#[from] ReplicationSlotsDbError),
+
+    #[error(transparent)]
+    Slot(#[from] SlotError),
+
+    #[error(transparent)]
+    TableCopiesDb(#[from] TableCopiesDbError),
+
+    #[error(transparent)]
+    TableResyncsDb(#[from] TableResyncsDbError),
+
+    #[error("The table {0} is not replicated by the pipeline")]
+    TableNotReplicated(String),
+
+    #[error("The table {0} is still being copied")]
+    TableSyncInProgress(String),
+
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 }
@@ -105,11 +145,48 @@ impl PipelineError {
             | PipelineError::PipelinesDb(PipelinesDbError::Database(_))
             | PipelineError::ReplicatorsDb(ReplicatorsDbError::Database(_))
             | PipelineError::ImagesDb(ImagesDbError::Database(_))
+            | PipelineError::ReplicationSlotsDb(ReplicationSlotsDbError::Database(_))
+            | PipelineError::TableCopiesDb(TableCopiesDbError::Database(_))
+            | PipelineError::TableResyncsDb(TableResyncsDbError::Database(_))
             | PipelineError::Database(_) => "internal server error".to_string(),
             // Every other message is ok, as they do not divulge sensitive information
             e => e.to_string(),
         }
     }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            PipelineError::PipelineNotFound(_) => "pipeline_not_found",
+            PipelineError::SourceNotFound(_) => "source_not_found",
+            PipelineError::DestinationNotFound(_) => "destination_not_found",
+            PipelineError::ReplicatorNotFound(_) => "replicator_not_found",
+            PipelineError::ImageNotFound(_) | PipelineError::ImageNotFoundById(_) => {
+                "image_not_found"
+            }
+            PipelineError::NoDefaultImageFound => "default_image_not_found",
+            PipelineError::TenantId(e) => e.to_code(),
+            PipelineError::InvalidConfig(_) => "invalid_destination_config",
+            PipelineError::K8s(_) => "k8s_error",
+            PipelineError::TrustedRootCertsConfigMissing => "trusted_root_certs_config_missing",
+            PipelineError::DuplicatePipeline => "duplicate_pipeline",
+            PipelineError::InvalidPipelineConfig(_) => "invalid_pipeline_config",
+            PipelineError::UnverifiableDestination => "unverifiable_destination",
+            PipelineError::PgReplication(_) => "source_tables_unreadable",
+            PipelineError::BigQueryDestination(_) => "destination_tables_unreadable",
+            PipelineError::TableNotReplicated(_) => "table_not_replicated",
+            PipelineError::TableSyncInProgress(_) => "table_sync_in_progress",
+            PipelineError::SourcesDb(_)
+            | PipelineError::DestinationsDb(_)
+            | PipelineError::PipelinesDb(_)
+            | PipelineError::ReplicatorsDb(_)
+            | PipelineError::ImagesDb(_)
+            | PipelineError::ReplicationSlotsDb(_)
+            | PipelineError::Slot(_)
+            | PipelineError::TableCopiesDb(_)
+            | PipelineError::TableResyncsDb(_)
+            | PipelineError::Database(_) => INTERNAL_ERROR_CODE,
+        }
+    }
 }
 
 impl ResponseError for PipelineError {
@@ -126,19 +203,30 @@ impl ResponseError for PipelineError {
             | PipelineError::ImagesDb(_)
             | PipelineError::K8s(_)
             | PipelineError::TrustedRootCertsConfigMissing
+            | PipelineError::PgReplication(_)
+            | PipelineError::BigQueryDestination(_)
+            | PipelineError::ReplicationSlotsDb(_)
+            | PipelineError::Slot(_)
+            | PipelineError::TableCopiesDb(_)
+            | PipelineError::TableResyncsDb(_)
             | PipelineError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            PipelineError::PipelineNotFound(_) | PipelineError::ImageNotFoundById(_) => {
-                StatusCode::NOT_FOUND
-            }
+            PipelineError::PipelineNotFound(_)
+            | PipelineError::ImageNotFoundById(_)
+            | PipelineError::TableNotReplicated(_) => StatusCode::NOT_FOUND,
             PipelineError::TenantId(_)
             | PipelineError::SourceNotFound(_)
-            | PipelineError::DestinationNotFound(_) => StatusCode::BAD_REQUEST,
-            PipelineError::DuplicatePipeline => StatusCode::CONFLICT,
+            | PipelineError::DestinationNotFound(_)
+            | PipelineError::InvalidPipelineConfig(_)
+            | PipelineError::UnverifiableDestination => StatusCode::BAD_REQUEST,
+            PipelineError::DuplicatePipeline | PipelineError::TableSyncInProgress(_) => {
+                StatusCode::CONFLICT
+            }
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -205,6 +293,16 @@ pub struct UpdatePipelineImageRequest {
     pub image_id: Option<i64>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct StartPipelineRequest {
+    /// LSN from which streaming starts, overriding the one stored in the replication slot.
+    ///
+    /// Must not be before the slot's `restart_lsn`, otherwise the pipeline fails to start.
+    #[schema(example = "16/B374D848")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_lsn: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct GetPipelineStatusResponse {
     #[schema(example = 1)]
@@ -212,6 +310,75 @@ pub struct GetPipelineStatusResponse {
     pub status: PipelineStatus,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GetPipelineReplicationStatusResponse {
+    #[schema(example = 1)]
+    pub pipeline_id: i64,
+    /// LSN after the initial snapshot of the tables, from which the changes are streamed.
+    ///
+    /// It's `None` until the pipeline created its replication slot.
+    #[schema(example = "0/1A2B3C4D")]
+    pub snapshot_lsn: Option<String>,
+    /// LSN up to which the changes were written to the destination and confirmed to the source.
+    ///
+    /// It's `None` when the replication slot of the pipeline doesn't exist.
+    #[schema(example = "0/1A2B3D00")]
+    pub confirmed_flush_lsn: Option<String>,
+    /// The progress of the copy of each table, as last reported by the pipeline.
+    pub table_copies: Vec<TableCopyStatus>,
+    /// The tables which were requested to be copied again, with the progress of their copy.
+    pub table_resyncs: Vec<TableResyncStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TableCopyStatus {
+    #[schema(example = "public")]
+    pub schema: String,
+    #[schema(example = "users")]
+    pub name: String,
+    /// The replication state of the table, which is `data_sync` while the table is copied.
+    #[schema(example = "data_sync")]
+    pub state: Option<String>,
+    #[schema(example = 250000)]
+    pub rows_copied: i64,
+    /// The number of rows of the table estimated by Postgres when the copy started, which is
+    /// `None` if the table was never vacuumed nor analyzed.
+    #[schema(example = 1000000)]
+    pub estimated_rows: Option<i64>,
+    /// When the progress was last reported, which happens every few seconds while the table is
+    /// copied, so an old value with a `data_sync` state hints at a stuck copy.
+    #[schema(example = "2025-08-01T09:00:05.000000Z")]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TableResyncStatus {
+    #[schema(example = "public")]
+    pub schema: String,
+    #[schema(example = "users")]
+    pub name: String,
+    /// The replication state of the table, e.g. `data_sync` while it's copied and `ready` once it
+    /// caught up with the other tables.
+    #[schema(example = "data_sync")]
+    pub state: Option<String>,
+    #[schema(example = "2025-08-01T09:00:00.000000Z")]
+    pub requested_at: String,
+    /// When the pipeline started copying the table, which is `None` until the running pipeline
+    /// picks up the request.
+    #[schema(example = "2025-08-01T09:00:05.000000Z")]
+    pub started_at: Option<String>,
+    #[schema(example = 1000)]
+    pub rows_copied: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ResyncTableRequest {
+    #[schema(example = "public", required = true)]
+    pub schema: String,
+    #[schema(example = "users", required = true)]
+    pub name: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "name")]
@@ -228,6 +395,49 @@ pub enum PipelineStatus {
     },
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VerifyDestinationRequest {
+    #[schema(example = 1, required = true)]
+    pub source_id: i64,
+    #[schema(example = 1, required = true)]
+    pub destination_id: i64,
+    #[schema(example = "my_publication", required = true)]
+    pub publication_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VerifyDestinationResponse {
+    /// Whether the rows of every table in the publication can be written to the destination.
+    pub compatible: bool,
+    pub tables: Vec<TableCompatibility>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TableCompatibility {
+    #[schema(example = "public")]
+    pub schema: String,
+    #[schema(example = "users")]
+    pub name: String,
+    /// Whether the destination table exists. A missing table is compatible, since it's created
+    /// when the pipeline starts.
+    pub exists: bool,
+    pub compatible: bool,
+    pub missing_columns: Vec<String>,
+    pub type_mismatches: Vec<ColumnTypeMismatch>,
+    /// Nullable source columns which are required in the destination table.
+    pub nullability_conflicts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ColumnTypeMismatch {
+    #[schema(example = "age")]
+    pub column_name: String,
+    #[schema(example = "int64")]
+    pub expected_type: String,
+    #[schema(example = "string")]
+    pub actual_type: String,
+}
+
 #[utoipa::path(
     context_path = "/v1",
     request_body = CreatePipelineRequest,
@@ -249,6 +459,7 @@ pub async fn create_pipeline(
 ) -> Result<impl Responder, PipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
     let pipeline = pipeline.into_inner();
+    pipeline.config.validate()?;
 
     let mut txn = pool.begin().await?;
     if !source_exists(txn.deref_mut(), tenant_id, pipeline.source_id).await? {
@@ -330,6 +541,7 @@ pub async fn read_pipeline(
     ),
     responses(
         (status = 200, description = "Update pipeline with id = pipeline_id"),
+        (status = 400, description = "Bad request", body = ErrorMessage),
         (status = 404, description = "Pipeline not found", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage)
     ),
@@ -345,6 +557,7 @@ pub async fn update_pipeline(
     let tenant_id = extract_tenant_id(&req)?;
     let pipeline_id = pipeline_id.into_inner();
     let pipeline = pipeline.into_inner();
+    pipeline.config.validate()?;
 
     let mut txn = pool.begin().await?;
     if !source_exists(txn.deref_mut(), tenant_id, pipeline.source_id).await? {
@@ -439,12 +652,14 @@ pub async fn read_all_pipelines(
 
 #[utoipa::path(
     context_path = "/v1",
+    request_body(content = StartPipelineRequest, description = "Optional overrides of how the pipeline starts"),
     params(
         ("pipeline_id" = i64, Path, description = "Id of the pipeline"),
         ("tenant_id" = String, Header, description = "The tenant ID")
     ),
     responses(
         (status = 200, description = "Start a pipeline"),
+        (status = 400, description = "Bad request", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage)
     )
 )]
@@ -452,26 +667,33 @@ pub async fn read_all_pipelines(
 pub async fn start_pipeline(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
-    k8s_client: Data<Arc<HttpK8sClient>>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    k8s_client: Data<Arc<dyn K8sClient>>,
     pipeline_id: Path<i64>,
+    start_pipeline: Option<Json<StartPipelineRequest>>,
 ) -> Result<impl Responder, PipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let pipeline_id = pipeline_id.into_inner();
+    let start_lsn = start_pipeline.and_then(|start_pipeline| start_pipeline.into_inner().start_lsn);
+    if let Some(start_lsn) = &start_lsn {
+        SharedPipelineConfig::parse_start_lsn(start_lsn)?;
+    }
 
     let mut txn = pool.begin().await?;
     let (pipeline, replicator, image, source, destination) =
-        read_all_required_data(&mut txn, tenant_id, pipeline_id, &encryption_key).await?;
+        read_all_required_data(&mut txn, tenant_id, pipeline_id, encryptor).await?;
 
     // We update the pipeline in K8s.
     create_or_update_pipeline_in_k8s(
-        &k8s_client,
+        k8s_client.as_ref(),
         tenant_id,
         pipeline,
         replicator,
         image,
         source,
         destination,
+        start_lsn,
     )
     .await?;
     txn.commit().await?;
@@ -494,7 +716,7 @@ pub async fn start_pipeline(
 pub async fn stop_pipeline(
     req: HttpRequest,
     pool: Data<PgPool>,
-    k8s_client: Data<Arc<HttpK8sClient>>,
+    k8s_client: Data<Arc<dyn K8sClient>>,
     pipeline_id: Path<i64>,
 ) -> Result<impl Responder, PipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
@@ -526,7 +748,7 @@ pub async fn stop_pipeline(
 pub async fn stop_all_pipelines(
     req: HttpRequest,
     pool: Data<PgPool>,
-    k8s_client: Data<Arc<HttpK8sClient>>,
+    k8s_client: Data<Arc<dyn K8sClient>>,
 ) -> Result<impl Responder, PipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
 
@@ -555,7 +777,7 @@ pub async fn stop_all_pipelines(
 pub async fn get_pipeline_status(
     req: HttpRequest,
     pool: Data<PgPool>,
-    k8s_client: Data<Arc<HttpK8sClient>>,
+    k8s_client: Data<Arc<dyn K8sClient>>,
     pipeline_id: Path<i64>,
 ) -> Result<impl Responder, PipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
@@ -607,6 +829,147 @@ pub async fn get_pipeline_status(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    context_path = "/v1",
+    params(
+        ("pipeline_id" = i64, Path, description = "Id of the pipeline"),
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Get the replication positions of a pipeline", body = GetPipelineReplicationStatusResponse),
+        (status = 404, description = "Pipeline not found", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage)
+    )
+)]
+#[get("/pipelines/{pipeline_id}/replication-status")]
+pub async fn get_pipeline_replication_status(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    pipeline_id: Path<i64>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, PipelineError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let pipeline_id = pipeline_id.into_inner();
+
+    let pipeline = db::pipelines::read_pipeline(&**pool, tenant_id, pipeline_id)
+        .await?
+        .ok_or(PipelineError::PipelineNotFound(pipeline_id))?;
+    let source = db::sources::read_source(&**pool, tenant_id, pipeline.source_id, encryptor)
+        .await?
+        .ok_or(PipelineError::SourceNotFound(pipeline.source_id))?;
+
+    let options = source.config.into_connection_config().with_db();
+    let slot_name = get_slot_name(pipeline_id as u64, WorkerType::Apply)?;
+    let lsns = db::replication_slots::get_pipeline_lsns(
+        &source_connections,
+        &options,
+        pipeline_id,
+        &slot_name,
+    )
+    .await?;
+    let table_copies =
+        db::table_copies::read_table_copies(&source_connections, &options, pipeline_id)
+            .await?
+            .into_iter()
+            .map(|copy| TableCopyStatus {
+                schema: copy.schema,
+                name: copy.name,
+                state: copy.state,
+                rows_copied: copy.rows_copied,
+                estimated_rows: copy.estimated_rows,
+                updated_at: copy.updated_at,
+            })
+            .collect();
+    let table_resyncs =
+        db::table_resyncs::read_table_resyncs(&source_connections, &options, pipeline_id)
+            .await?
+            .into_iter()
+            .map(|resync| TableResyncStatus {
+                schema: resync.schema,
+                name: resync.name,
+                state: resync.state,
+                requested_at: resync.requested_at,
+                started_at: resync.started_at,
+                rows_copied: resync.rows_copied,
+            })
+            .collect();
+
+    let response = GetPipelineReplicationStatusResponse {
+        pipeline_id,
+        snapshot_lsn: lsns.snapshot_lsn,
+        confirmed_flush_lsn: lsns.confirmed_flush_lsn,
+        table_copies,
+        table_resyncs,
+    };
+
+    Ok(Json(response))
+}
+
+/// Copies a table of the pipeline again from a fresh snapshot, e.g. after its rows in the
+/// destination got corrupted.
+///
+/// The running pipeline truncates the table in the destination and copies it again while the
+/// other tables keep streaming, then applies the changes committed after the copy. The progress
+/// of the copy is reported by the replication status of the pipeline.
+#[utoipa::path(
+    context_path = "/v1",
+    request_body = ResyncTableRequest,
+    params(
+        ("pipeline_id" = i64, Path, description = "Id of the pipeline"),
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Table resync requested"),
+        (status = 404, description = "Pipeline or table not found", body = ErrorMessage),
+        (status = 409, description = "Table still being copied", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage)
+    ),
+    tag = "Pipelines"
+)]
+#[post("/pipelines/{pipeline_id}/resync-table")]
+pub async fn resync_table(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    pipeline_id: Path<i64>,
+    resync_request: Json<ResyncTableRequest>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, PipelineError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let pipeline_id = pipeline_id.into_inner();
+    let resync_request = resync_request.into_inner();
+
+    let pipeline = db::pipelines::read_pipeline(&**pool, tenant_id, pipeline_id)
+        .await?
+        .ok_or(PipelineError::PipelineNotFound(pipeline_id))?;
+    let source = db::sources::read_source(&**pool, tenant_id, pipeline.source_id, encryptor)
+        .await?
+        .ok_or(PipelineError::SourceNotFound(pipeline.source_id))?;
+
+    let options = source.config.into_connection_config().with_db();
+    let table_name = format!("{}.{}", resync_request.schema, resync_request.name);
+    match db::table_resyncs::request_table_resync(
+        &source_connections,
+        &options,
+        pipeline_id,
+        &resync_request.schema,
+        &resync_request.name,
+    )
+    .await?
+    {
+        TableResyncRequest::Requested => Ok(HttpResponse::Ok().finish()),
+        TableResyncRequest::TableNotReplicated => {
+            Err(PipelineError::TableNotReplicated(table_name))
+        }
+        TableResyncRequest::TableSyncInProgress => {
+            Err(PipelineError::TableSyncInProgress(table_name))
+        }
+    }
+}
+
 #[utoipa::path(
     context_path = "/v1",
     request_body = UpdatePipelineImageRequest,
@@ -626,18 +989,19 @@ pub async fn get_pipeline_status(
 pub async fn update_pipeline_image(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
-    k8s_client: Option<Data<Arc<HttpK8sClient>>>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    k8s_client: Option<Data<Arc<dyn K8sClient>>>,
     pipeline_id: Path<i64>,
     update_request: Json<UpdatePipelineImageRequest>,
 ) -> Result<impl Responder, PipelineError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let pipeline_id = pipeline_id.into_inner();
     let update_request = update_request.into_inner();
 
     let mut txn = pool.begin().await?;
     let (pipeline, replicator, current_image, source, destination) =
-        read_all_required_data(&mut txn, tenant_id, pipeline_id, &encryption_key).await?;
+        read_all_required_data(&mut txn, tenant_id, pipeline_id, encryptor).await?;
 
     let target_image = match update_request.image_id {
         Some(image_id) => db::images::read_image(txn.deref_mut(), image_id)
@@ -671,13 +1035,14 @@ pub async fn update_pipeline_image(
     // We update the pipeline in K8s if client is available.
     if let Some(k8s_client) = k8s_client {
         create_or_update_pipeline_in_k8s(
-            &k8s_client,
+            k8s_client.as_ref(),
             tenant_id,
             pipeline,
             replicator,
             target_image,
             source,
             destination,
+            None,
         )
         .await?;
     }
@@ -686,6 +1051,112 @@ pub async fn update_pipeline_image(
     Ok(HttpResponse::Ok().finish())
 }
 
+#[utoipa::path(
+    context_path = "/v1",
+    request_body = VerifyDestinationRequest,
+    params(
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Compare the destination tables with the source tables", body = VerifyDestinationResponse),
+        (status = 400, description = "Bad request", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Pipelines"
+)]
+#[post("/pipelines/verify-destination")]
+pub async fn verify_destination(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    verify_request: Json<VerifyDestinationRequest>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, PipelineError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let verify_request = verify_request.into_inner();
+
+    let source_id = verify_request.source_id;
+    let source = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
+        .await?
+        .ok_or(PipelineError::SourceNotFound(source_id))?;
+
+    let destination_id = verify_request.destination_id;
+    let destination =
+        db::destinations::read_destination(&**pool, tenant_id, destination_id, encryptor)
+            .await?
+            .ok_or(PipelineError::DestinationNotFound(destination_id))?;
+
+    let DestinationConfig::BigQuery {
+        project_id,
+        dataset_id,
+        service_account_key,
+        max_staleness_mins,
+        column_filter,
+        identifier_overflow,
+        schema_mapping,
+        ..
+    } = destination.config
+    else {
+        return Err(PipelineError::UnverifiableDestination);
+    };
+
+    install_crypto_provider_once();
+    let mut bigquery_destination = BigQueryDestination::new_with_key(
+        project_id,
+        dataset_id,
+        service_account_key.expose_secret(),
+        max_staleness_mins,
+    )
+    .await?;
+    if let Some(column_filter) = &column_filter {
+        bigquery_destination =
+            bigquery_destination.with_column_filter(ColumnFilter::new(column_filter));
+    }
+    if let Some(identifier_overflow) = identifier_overflow {
+        bigquery_destination = bigquery_destination.with_identifier_overflow(identifier_overflow);
+    }
+    if let Some(schema_mapping) = schema_mapping {
+        bigquery_destination = bigquery_destination.with_schema_mapping(schema_mapping);
+    }
+
+    // The schemas are read like the pipeline reads them, so that the expected columns are the
+    // ones it would write. The replication client opens its own connection, which still counts
+    // towards the limit of the source database.
+    let connection_config = source.config.into_connection_config();
+    let _permit = source_connections
+        .acquire(&connection_config.with_db())
+        .await?;
+    let replication_client = PgReplicationClient::connect(connection_config).await?;
+    let publication_name = verify_request.publication_name;
+    let table_ids = replication_client
+        .get_publication_table_ids(&publication_name)
+        .await?;
+
+    let mut tables = Vec::with_capacity(table_ids.len());
+    for table_id in table_ids {
+        let table_schema = replication_client
+            .get_table_schema(table_id, Some(&publication_name))
+            .await?;
+        let report = bigquery_destination
+            .verify_table_compatibility(&table_schema)
+            .await?;
+
+        tables.push(table_compatibility(
+            table_schema.name.schema,
+            table_schema.name.name,
+            report,
+        ));
+    }
+
+    let response = VerifyDestinationResponse {
+        compatible: tables.iter().all(|table| table.compatible),
+        tables,
+    };
+
+    Ok(Json(response))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Secrets {
     postgres_password: String,
@@ -693,14 +1164,16 @@ struct Secrets {
 }
 
 #[allow(clippy::too_many_arguments)]
+#[expect(clippy::too_many_arguments)]
 async fn create_or_update_pipeline_in_k8s(
-    k8s_client: &HttpK8sClient,
+    k8s_client: &dyn K8sClient,
     tenant_id: &str,
     pipeline: Pipeline,
     replicator: Replicator,
     image: Image,
     source: Source,
     destination: Destination,
+    start_lsn: Option<String>,
 ) -> Result<(), PipelineError> {
     let prefix = create_k8s_object_prefix(tenant_id, replicator.id);
 
@@ -717,6 +1190,7 @@ async fn create_or_update_pipeline_in_k8s(
         SupabaseConfig {
             project_ref: tenant_id.to_owned(),
         },
+        start_lsn,
     )
     .await?;
     create_or_update_config(k8s_client, &prefix, replicator_config).await?;
@@ -728,7 +1202,7 @@ async fn create_or_update_pipeline_in_k8s(
 }
 
 async fn delete_pipeline_in_k8s(
-    k8s_client: &HttpK8sClient,
+    k8s_client: &dyn K8sClient,
     tenant_id: &str,
     replicator: Replicator,
 ) -> Result<(), PipelineError> {
@@ -745,7 +1219,7 @@ async fn read_all_required_data(
     txn: &mut PgTransaction<'_>,
     tenant_id: &str,
     pipeline_id: i64,
-    encryption_key: &EncryptionKey,
+    encryptor: &dyn Encryptor,
 ) -> Result<(Pipeline, Replicator, Image, Source, Destination), PipelineError> {
     let pipeline = db::pipelines::read_pipeline(txn.deref_mut(), tenant_id, pipeline_id)
         .await?
@@ -761,19 +1235,15 @@ async fn read_all_required_data(
         .ok_or(PipelineError::ImageNotFound(replicator.id))?;
 
     let source_id = pipeline.source_id;
-    let source = db::sources::read_source(txn.deref_mut(), tenant_id, source_id, encryption_key)
+    let source = db::sources::read_source(txn.deref_mut(), tenant_id, source_id, encryptor)
         .await?
         .ok_or(PipelineError::SourceNotFound(source_id))?;
 
     let destination_id = pipeline.destination_id;
-    let destination = db::destinations::read_destination(
-        txn.deref_mut(),
-        tenant_id,
-        destination_id,
-        encryption_key,
-    )
-    .await?
-    .ok_or(PipelineError::DestinationNotFound(destination_id))?;
+    let destination =
+        db::destinations::read_destination(txn.deref_mut(), tenant_id, destination_id, encryptor)
+            .await?
+            .ok_or(PipelineError::DestinationNotFound(destination_id))?;
 
     Ok((pipeline, replicator, image, source, destination))
 }
@@ -800,21 +1270,34 @@ fn build_secrets(source_config: &SourceConfig, destination_config: &DestinationC
 }
 
 async fn build_replicator_config(
-    k8s_client: &HttpK8sClient,
+    k8s_client: &dyn K8sClient,
     source_config: SourceConfig,
     destination_config: DestinationConfig,
     pipeline: Pipeline,
     supabase_config: SupabaseConfig,
+    start_lsn: Option<String>,
 ) -> Result<ReplicatorConfig, PipelineError> {
-    // We load the trusted root certificates from the config map.
-    let trusted_root_certs = k8s_client
-        .get_config_map(TRUSTED_ROOT_CERT_CONFIG_MAP_NAME)
-        .await?
-        .data
-        .ok_or(PipelineError::TrustedRootCertsConfigMissing)?
-        .get("trusted_root_certs")
-        .ok_or(PipelineError::TrustedRootCertsConfigMissing)?
-        .clone();
+    let tls = match source_config.ssl_mode {
+        Some(_) => source_config.tls_config(),
+        // Sources without an ssl mode are verified against the trusted root certificates from
+        // the config map.
+        None => {
+            let trusted_root_certs = k8s_client
+                .get_config_map(TRUSTED_ROOT_CERT_CONFIG_MAP_NAME)
+                .await?
+                .data
+                .ok_or(PipelineError::TrustedRootCertsConfigMissing)?
+                .get("trusted_root_certs")
+                .ok_or(PipelineError::TrustedRootCertsConfigMissing)?
+                .clone();
+
+            TlsConfig {
+                trusted_root_certs,
+                enabled: true,
+                verification: TlsVerification::VerifyFull,
+            }
+        }
+    };
 
     let pg_connection = PgConnectionConfig {
         host: source_config.host,
@@ -822,10 +1305,7 @@ async fn build_replicator_config(
         name: source_config.name,
         username: source_config.username,
         password: source_config.password,
-        tls: TlsConfig {
-            trusted_root_certs,
-            enabled: true,
-        },
+        tls,
     };
 
     let pipeline_config = SharedPipelineConfig {
@@ -844,6 +1324,23 @@ async fn build_replicator_config(
         apply_worker_init_retry: pipeline.config.apply_worker_init_retry.unwrap_or_default(),
         // Hardcoding a value of 4 for now for maximum number of parallel table sync workers
         max_table_sync_workers: pipeline.config.max_table_sync_workers.unwrap_or(4),
+        // Pipelines managed by the api always copy tables before streaming for now.
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
     };
 
     let config = ReplicatorConfig {
@@ -852,17 +1349,49 @@ async fn build_replicator_config(
         // The Sentry config will be injected via env variables for security purposes.
         sentry: None,
         supabase: Some(supabase_config),
+        // Lag alerts are not configurable for pipelines managed by the api for now.
+        lag_alert: None,
+        // The metrics of pipelines managed by the api are not scraped for now.
+        metrics: None,
+        shutdown_timeout_ms: ReplicatorConfig::DEFAULT_SHUTDOWN_TIMEOUT_MS,
     };
 
     Ok(config)
 }
 
-fn create_k8s_object_prefix(tenant_id: &str, replicator_id: i64) -> String {
+fn table_compatibility(
+    schema: String,
+    name: String,
+    report: Option<CompatibilityReport>,
+) -> TableCompatibility {
+    let exists = report.is_some();
+    let report = report.unwrap_or_default();
+
+    TableCompatibility {
+        schema,
+        name,
+        exists,
+        compatible: report.is_compatible(),
+        missing_columns: report.missing_columns,
+        type_mismatches: report
+            .type_mismatches
+            .into_iter()
+            .map(|mismatch| ColumnTypeMismatch {
+                column_name: mismatch.column_name,
+                expected_type: mismatch.expected_type,
+                actual_type: mismatch.actual_type,
+            })
+            .collect(),
+        nullability_conflicts: report.nullability_conflicts,
+    }
+}
+
+pub(crate) fn create_k8s_object_prefix(tenant_id: &str, replicator_id: i64) -> String {
     format!("{tenant_id}-{replicator_id}")
 }
 
 async fn create_or_update_secrets(
-    k8s_client: &HttpK8sClient,
+    k8s_client: &dyn K8sClient,
     prefix: &str,
     secrets: Secrets,
 ) -> Result<(), PipelineError> {
@@ -880,7 +1409,7 @@ async fn create_or_update_secrets(
 }
 
 async fn create_or_update_config(
-    k8s_client: &HttpK8sClient,
+    k8s_client: &dyn K8sClient,
     prefix: &str,
     config: ReplicatorConfig,
 ) -> Result<(), PipelineError> {
@@ -895,7 +1424,7 @@ async fn create_or_update_config(
 }
 
 async fn create_or_update_replicator(
-    k8s_client: &HttpK8sClient,
+    k8s_client: &dyn K8sClient,
     prefix: &str,
     replicator_image: String,
 ) -> Result<(), PipelineError> {
@@ -906,20 +1435,20 @@ async fn create_or_update_replicator(
     Ok(())
 }
 
-async fn delete_secrets(k8s_client: &HttpK8sClient, prefix: &str) -> Result<(), PipelineError> {
+async fn delete_secrets(k8s_client: &dyn K8sClient, prefix: &str) -> Result<(), PipelineError> {
     k8s_client.delete_postgres_secret(prefix).await?;
     k8s_client.delete_bq_secret(prefix).await?;
 
     Ok(())
 }
 
-async fn delete_config(k8s_client: &HttpK8sClient, prefix: &str) -> Result<(), PipelineError> {
+async fn delete_config(k8s_client: &dyn K8sClient, prefix: &str) -> Result<(), PipelineError> {
     k8s_client.delete_config_map(prefix).await?;
 
     Ok(())
 }
 
-async fn delete_replicator(k8s_client: &HttpK8sClient, prefix: &str) -> Result<(), PipelineError> {
+async fn delete_replicator(k8s_client: &dyn K8sClient, prefix: &str) -> Result<(), PipelineError> {
     k8s_client.delete_stateful_set(prefix).await?;
 
     Ok(())
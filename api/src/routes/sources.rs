@@ -1,16 +1,35 @@
 use crate::db;
-use crate::db::sources::{SourceConfig, SourcesDbError};
-use crate::encryption::EncryptionKey;
-use crate::routes::{ErrorMessage, TenantIdError, extract_tenant_id};
+use crate::db::audit_log::{
+    AuditLogDbError, SourceAction, hash_source_config, insert_source_audit_entry,
+};
+use crate::db::pipelines::PipelinesDbError;
+use crate::db::replication_slots::ReplicationSlotsDbError;
+use crate::db::sources::{
+    NewSource, SourceConfig, SourceConfigError, SourceSslMode, SourceTags, SourceTagsError,
+    SourceValidationError, SourcesDbError, parse_source_tag_filter, validate_source_tags,
+};
+use crate::encryption::KeyProvider;
+use crate::routes::{
+    ErrorMessage, INTERNAL_ERROR_CODE, Negotiated, TenantIdError, extract_tenant_id,
+};
+use crate::source_connections::SourceConnectionLimiter;
 use actix_web::{
     HttpRequest, HttpResponse, Responder, ResponseError, delete, get,
     http::{StatusCode, header::ContentType},
-    post,
-    web::{Data, Json, Path},
+    patch, post,
+    web::{Data, Json, Path, Query},
 };
+use config::SerializableSecretString;
+use config::shared::IntoConnectOptions;
+use etl::replication::slot::is_pipeline_slot;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
+use tracing::warn;
 use utoipa::ToSchema;
 
 pub mod publications;
@@ -24,34 +43,118 @@ pub enum SourceError {
     #[error(transparent)]
     TenantId(#[from] TenantIdError),
 
+    #[error(transparent)]
+    InvalidTags(#[from] SourceTagsError),
+
+    #[error(transparent)]
+    InvalidConfig(#[from] SourceConfigError),
+
+    #[error("A source with the name '{0}' already exists")]
+    DuplicateName(String),
+
+    #[error("The source with id {0} can't be restored, since another source has its name")]
+    RestoreConflict(i64),
+
     #[error(transparent)]
     SourcesDb(#[from] SourcesDbError),
+
+    #[error("The source failed validation: {0}")]
+    ValidationFailed(#[from] SourceValidationError),
+
+    #[error("The limit must be between 1 and {max}, got {0}", max = MAX_SOURCES_PAGE_SIZE)]
+    InvalidLimit(i64),
+
+    #[error(
+        "The idempotency key must be between 1 and {MAX_IDEMPOTENCY_KEY_LENGTH} visible ASCII characters long"
+    )]
+    InvalidIdempotencyKey,
+
+    #[error("A batch must contain between 1 and {max} sources, got {0}", max = MAX_SOURCES_BATCH_SIZE)]
+    InvalidBatchSize(usize),
+
+    #[error("The source was not created, since other sources of the batch failed")]
+    BatchAborted,
+
+    #[error(transparent)]
+    PipelinesDb(#[from] PipelinesDbError),
+
+    #[error(transparent)]
+    ReplicationSlotsDb(#[from] ReplicationSlotsDbError),
+
+    #[error(transparent)]
+    AuditLogDb(#[from] AuditLogDbError),
 }
 
 impl SourceError {
+    /// Maps a [`SourcesDbError`] to [`SourceError::DuplicateName`] when it was caused by the
+    /// per-tenant unique source name constraint.
+    fn from_sources_db(err: SourcesDbError, name: &str) -> Self {
+        match err {
+            SourcesDbError::Database(e) if db::sources::is_duplicate_source_name_error(&e) => {
+                SourceError::DuplicateName(name.to_string())
+            }
+            e => SourceError::SourcesDb(e),
+        }
+    }
+
     pub fn to_message(&self) -> String {
         match self {
             // Do not expose internal database details in error messages
-            SourceError::SourcesDb(SourcesDbError::Database(_)) => {
+            SourceError::SourcesDb(SourcesDbError::Database(_))
+            | SourceError::PipelinesDb(PipelinesDbError::Database(_))
+            | SourceError::ReplicationSlotsDb(ReplicationSlotsDbError::Database(_))
+            | SourceError::AuditLogDb(AuditLogDbError::Database(_)) => {
                 "internal server error".to_string()
             }
             // Every other message is ok, as they do not divulge sensitive information
             e => e.to_string(),
         }
     }
+
+    pub fn to_code(&self) -> &'static str {
+        match self {
+            SourceError::SourceNotFound(_) => "source_not_found",
+            SourceError::TenantId(e) => e.to_code(),
+            SourceError::InvalidTags(_) => "invalid_source_tags",
+            SourceError::InvalidConfig(_) => "invalid_source_config",
+            SourceError::DuplicateName(_) => "duplicate_source_name",
+            SourceError::RestoreConflict(_) => "source_restore_conflict",
+            SourceError::ValidationFailed(_) => "source_validation_failed",
+            SourceError::InvalidLimit(_) => "invalid_limit",
+            SourceError::InvalidIdempotencyKey => "invalid_idempotency_key",
+            SourceError::InvalidBatchSize(_) => "invalid_batch_size",
+            SourceError::BatchAborted => "batch_aborted",
+            SourceError::SourcesDb(_)
+            | SourceError::PipelinesDb(_)
+            | SourceError::ReplicationSlotsDb(_)
+            | SourceError::AuditLogDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
 }
 
 impl ResponseError for SourceError {
     fn status_code(&self) -> StatusCode {
         match self {
-            SourceError::SourcesDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            SourceError::SourcesDb(_)
+            | SourceError::PipelinesDb(_)
+            | SourceError::ReplicationSlotsDb(_)
+            | SourceError::AuditLogDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
             SourceError::SourceNotFound(_) => StatusCode::NOT_FOUND,
-            SourceError::TenantId(_) => StatusCode::BAD_REQUEST,
+            SourceError::DuplicateName(_) | SourceError::RestoreConflict(_) => StatusCode::CONFLICT,
+            SourceError::TenantId(_)
+            | SourceError::InvalidTags(_)
+            | SourceError::InvalidConfig(_)
+            | SourceError::InvalidLimit(_)
+            | SourceError::InvalidIdempotencyKey
+            | SourceError::InvalidBatchSize(_)
+            | SourceError::BatchAborted
+            | SourceError::ValidationFailed(_) => StatusCode::BAD_REQUEST,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -62,6 +165,25 @@ impl ResponseError for SourceError {
     }
 }
 
+/// Default number of sources returned by a page of [`read_all_sources`].
+const DEFAULT_SOURCES_PAGE_SIZE: i64 = 50;
+
+/// Maximum number of sources returned by a page of [`read_all_sources`].
+const MAX_SOURCES_PAGE_SIZE: i64 = 200;
+
+/// The header holding the key which makes retries of [`create_source`] return the source created
+/// by the first request instead of creating another one.
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Maximum length of an idempotency key.
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// How long an idempotency key returns the source created with it, after which it can be reused.
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Maximum number of sources created by a single [`create_sources_batch`] request.
+const MAX_SOURCES_BATCH_SIZE: usize = 100;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct StrippedSourceConfig {
@@ -69,6 +191,8 @@ pub struct StrippedSourceConfig {
     pub port: u16,
     pub name: String,
     pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssl_mode: Option<SourceSslMode>,
 }
 
 impl From<SourceConfig> for StrippedSourceConfig {
@@ -78,6 +202,7 @@ impl From<SourceConfig> for StrippedSourceConfig {
             port: source.port,
             name: source.name,
             username: source.username,
+            ssl_mode: source.ssl_mode,
         }
     }
 }
@@ -88,6 +213,17 @@ pub struct CreateSourceRequest {
     pub name: String,
     #[schema(required = true)]
     pub config: SourceConfig,
+    #[serde(default)]
+    #[schema(value_type = Object, example = json!({"env": "prod"}))]
+    pub tags: SourceTags,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSourceQuery {
+    /// Whether to check that the source is reachable and allows logical replication before
+    /// creating it.
+    #[serde(default)]
+    pub validate: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -96,12 +232,122 @@ pub struct CreateSourceResponse {
     pub id: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateSourcesBatchRequest {
+    #[schema(required = true)]
+    pub sources: Vec<CreateSourceRequest>,
+    /// Whether the valid sources are created when other sources of the batch fail. By default,
+    /// no source is created unless all of them can be.
+    #[serde(default)]
+    #[schema(example = false)]
+    pub partial_success: bool,
+}
+
+/// The outcome of a source of a batch, which has either an id or an error.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateSourcesBatchResult {
+    /// The id of the created source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 1)]
+    pub id: Option<i64>,
+    /// The machine-readable code of the error, like the `code` of error responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "duplicate_source_name")]
+    pub code: Option<String>,
+    /// Why the source was not created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateSourcesBatchResponse {
+    /// The outcome of each source, in the order of the request.
+    pub results: Vec<CreateSourcesBatchResult>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateSourceRequest {
     #[schema(example = "My Updated Postgres Source", required = true)]
     pub name: String,
     #[schema(required = true)]
     pub config: SourceConfig,
+    #[serde(default)]
+    #[schema(value_type = Object, example = json!({"env": "prod"}))]
+    pub tags: SourceTags,
+}
+
+/// The fields of a source to change, the other ones are left untouched.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PatchSourceRequest {
+    #[schema(example = "My Renamed Postgres Source")]
+    pub name: Option<String>,
+    pub config: Option<PatchSourceConfig>,
+    #[schema(value_type = Option<Object>, example = json!({"env": "prod"}))]
+    pub tags: Option<SourceTags>,
+}
+
+/// The fields of the config of a source to change, merged into its stored config.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PatchSourceConfig {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub name: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<SerializableSecretString>,
+    pub ssl_mode: Option<SourceSslMode>,
+    pub root_cert: Option<String>,
+}
+
+impl PatchSourceConfig {
+    /// Sets the fields of `config` which are set in the patch.
+    fn merge_into(self, config: &mut SourceConfig) {
+        if let Some(host) = self.host {
+            config.host = host;
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(name) = self.name {
+            config.name = name;
+        }
+        if let Some(username) = self.username {
+            config.username = username;
+        }
+        if let Some(password) = self.password {
+            config.password = Some(password);
+        }
+        if let Some(ssl_mode) = self.ssl_mode {
+            config.ssl_mode = Some(ssl_mode);
+        }
+        if let Some(root_cert) = self.root_cert {
+            config.root_cert = Some(root_cert);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RotateSourceCredentialsRequest {
+    #[schema(value_type = String, example = "my-new-password", required = true)]
+    pub password: SerializableSecretString,
+    /// The new username, the current one is kept if not set.
+    #[schema(example = "postgres")]
+    pub username: Option<String>,
+    /// Whether to test a connection with the new credentials before storing them.
+    #[serde(default)]
+    #[schema(example = true)]
+    pub verify: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RotateSourceCredentialsResponse {
+    /// Whether the new credentials were stored.
+    #[schema(example = true)]
+    pub rotated: bool,
+    /// Whether a connection with the new credentials succeeded. Always `false` when the
+    /// verification was not requested.
+    #[schema(example = true)]
+    pub verified: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -113,22 +359,96 @@ pub struct ReadSourceResponse {
     #[schema(example = "My Postgres Source")]
     pub name: String,
     pub config: StrippedSourceConfig,
+    #[schema(value_type = Object, example = json!({"env": "prod"}))]
+    pub tags: SourceTags,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ReadSourcesResponse {
     pub sources: Vec<ReadSourceResponse>,
+    /// The cursor to pass as `after` to read the next page, not set on the last page.
+    #[schema(example = 50)]
+    pub next_cursor: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReplicationSlotStatus {
+    #[schema(example = "supabase_etl_apply_1")]
+    pub slot_name: String,
+    /// The id of the pipeline that the slot belongs to.
+    #[schema(example = 1)]
+    pub pipeline_id: i64,
+    /// The position up to which the changes were confirmed as received, in the `X/X` format.
+    #[schema(example = "0/16B3748")]
+    pub confirmed_flush_lsn: Option<String>,
+    /// The oldest position of the WAL which is still retained for the slot, in the `X/X`
+    /// format.
+    #[schema(example = "0/16B3710")]
+    pub restart_lsn: Option<String>,
+    /// The number of bytes of WAL between the current position and `confirmed_flush_lsn`.
+    #[schema(example = 1024)]
+    pub lag_bytes: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SourceStatusResponse {
+    pub slots: Vec<ReplicationSlotStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SourceAuditEntry {
+    #[schema(example = 1)]
+    pub id: i64,
+    /// One of `create`, `update`, `rotate_credentials`, `delete` and `restore`.
+    #[schema(example = "update")]
+    pub action: String,
+    /// The hex encoded SHA-256 hash of the config set by the action, without its password. Not
+    /// set for the actions which don't change the config.
+    #[schema(example = "3f2a9c0d5e7b41c6a8d9e0f1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5")]
+    pub config_hash: Option<String>,
+    /// When the action was recorded, in the RFC 3339 format in UTC.
+    #[schema(example = "2025-07-26T09:00:00.000000Z")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SourceAuditLogResponse {
+    pub entries: Vec<SourceAuditEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadSourcesQuery {
+    /// Filters sources by a tag in the `key:value` format.
+    pub tag: Option<String>,
+    /// Maximum number of sources to return, defaults to 50.
+    pub limit: Option<i64>,
+    /// Only returns sources after this cursor, taken from the `next_cursor` of the previous page.
+    pub after: Option<i64>,
+}
+
+impl ReadSourcesQuery {
+    fn limit(&self) -> Result<i64, SourceError> {
+        let limit = self.limit.unwrap_or(DEFAULT_SOURCES_PAGE_SIZE);
+        if !(1..=MAX_SOURCES_PAGE_SIZE).contains(&limit) {
+            return Err(SourceError::InvalidLimit(limit));
+        }
+
+        Ok(limit)
+    }
 }
 
 #[utoipa::path(
     context_path = "/v1",
     request_body = CreateSourceRequest,
     params(
-        ("tenant_id" = String, Header, description = "The tenant ID")
+        ("tenant_id" = String, Header, description = "The tenant ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "A key identifying the request, so that retrying it within 24 hours returns the source it created instead of creating another one"),
+        ("validate" = Option<bool>, Query, description = "Whether to connect to the source and check that `wal_level` is `logical` before creating it")
     ),
     responses(
         (status = 200, description = "Create new source", body = CreateSourceResponse),
         (status = 400, description = "Bad request", body = ErrorMessage),
+        (status = 409, description = "A source with the same name already exists", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage),
     ),
     tag = "Sources"
@@ -137,26 +457,214 @@ pub struct ReadSourcesResponse {
 pub async fn create_source(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    query: Query<CreateSourceQuery>,
     source: Json<CreateSourceRequest>,
+    source_connections: Data<SourceConnectionLimiter>,
 ) -> Result<impl Responder, SourceError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let idempotency_key = extract_idempotency_key(&req)?;
     let source = source.into_inner();
+    validate_source_tags(&source.tags)?;
+    source.config.validate()?;
 
-    let id = db::sources::create_source(
-        &**pool,
-        tenant_id,
-        &source.name,
-        source.config,
-        &encryption_key,
-    )
-    .await?;
+    if query.validate {
+        let options = source.config.clone().into_connection_config().with_db();
+        db::sources::validate_source_connection(&source_connections, &options).await?;
+    }
+
+    let config_hash = hash_source_config(&source.config);
+    let mut txn = pool.begin().await.map_err(SourcesDbError::from)?;
+    let (id, created) = match idempotency_key {
+        Some(key) => db::sources::create_source_idempotently(
+            &mut txn,
+            tenant_id,
+            &source.name,
+            source.config,
+            &source.tags,
+            encryptor,
+            key,
+            IDEMPOTENCY_KEY_TTL,
+        )
+        .await
+        .map_err(|e| SourceError::from_sources_db(e, &source.name))?,
+        None => {
+            let id = db::sources::create_source(
+                txn.deref_mut(),
+                tenant_id,
+                &source.name,
+                source.config,
+                &source.tags,
+                encryptor,
+            )
+            .await
+            .map_err(|e| SourceError::from_sources_db(e, &source.name))?;
+
+            (id, true)
+        }
+    };
+    // Retries returning the source created by the first request didn't create anything.
+    if created {
+        insert_source_audit_entry(
+            txn.deref_mut(),
+            tenant_id,
+            id,
+            SourceAction::Create,
+            Some(&config_hash),
+        )
+        .await?;
+    }
+    txn.commit().await.map_err(SourcesDbError::from)?;
 
     let response = CreateSourceResponse { id };
 
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    context_path = "/v1",
+    request_body = CreateSourcesBatchRequest,
+    params(
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Create new sources, with the outcome of each of them", body = CreateSourcesBatchResponse),
+        (status = 400, description = "Bad request", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Sources"
+)]
+#[post("/sources/batch")]
+pub async fn create_sources_batch(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    batch: Json<CreateSourcesBatchRequest>,
+) -> Result<impl Responder, SourceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let batch = batch.into_inner();
+    if !(1..=MAX_SOURCES_BATCH_SIZE).contains(&batch.sources.len()) {
+        return Err(SourceError::InvalidBatchSize(batch.sources.len()));
+    }
+
+    // Each source is validated on its own, so that its error is reported at its position.
+    let mut names = HashSet::new();
+    let mut outcomes = Vec::with_capacity(batch.sources.len());
+    let mut new_sources = vec![];
+    let mut config_hashes = HashMap::new();
+    for source in batch.sources {
+        match validate_batch_source(&source, &mut names) {
+            Ok(()) => {
+                outcomes.push(Ok(source.name.clone()));
+                config_hashes.insert(source.name.clone(), hash_source_config(&source.config));
+                new_sources.push(NewSource {
+                    name: source.name,
+                    config: source.config,
+                    tags: source.tags,
+                });
+            }
+            Err(e) => outcomes.push(Err(e)),
+        }
+    }
+
+    // The ids of the inserted sources by name, with whether they were committed, unless the
+    // batch failed before inserting them.
+    let num_invalid = outcomes.len() - new_sources.len();
+    let inserted = if num_invalid > 0 && !batch.partial_success {
+        None
+    } else {
+        let num_new_sources = new_sources.len();
+        let mut txn = pool.begin().await.map_err(SourcesDbError::from)?;
+        let ids =
+            db::sources::create_sources(txn.deref_mut(), tenant_id, new_sources, encryptor).await?;
+
+        // The sources whose name is already used are skipped by the insert, so the transaction is
+        // rolled back when they must fail the whole batch.
+        let committed = batch.partial_success || ids.len() == num_new_sources;
+        if committed {
+            for (name, &id) in &ids {
+                insert_source_audit_entry(
+                    txn.deref_mut(),
+                    tenant_id,
+                    id,
+                    SourceAction::Create,
+                    config_hashes.get(name).map(String::as_str),
+                )
+                .await?;
+            }
+            txn.commit().await.map_err(SourcesDbError::from)?;
+        } else {
+            txn.rollback().await.map_err(SourcesDbError::from)?;
+        }
+
+        Some((ids, committed))
+    };
+
+    let results = outcomes
+        .into_iter()
+        .map(|outcome| {
+            let id = outcome.and_then(|name| match &inserted {
+                Some((ids, committed)) => match ids.get(&name) {
+                    Some(&id) if *committed => Ok(id),
+                    Some(_) => Err(SourceError::BatchAborted),
+                    None => Err(SourceError::DuplicateName(name)),
+                },
+                None => Err(SourceError::BatchAborted),
+            });
+
+            match id {
+                Ok(id) => CreateSourcesBatchResult {
+                    id: Some(id),
+                    code: None,
+                    error: None,
+                },
+                Err(e) => CreateSourcesBatchResult {
+                    id: None,
+                    code: Some(e.to_code().to_string()),
+                    error: Some(e.to_message()),
+                },
+            }
+        })
+        .collect();
+
+    let response = CreateSourcesBatchResponse { results };
+
+    Ok(Json(response))
+}
+
+/// Returns the idempotency key of `req`, if it has one.
+fn extract_idempotency_key(req: &HttpRequest) -> Result<Option<&str>, SourceError> {
+    let Some(key) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = key
+        .to_str()
+        .map_err(|_| SourceError::InvalidIdempotencyKey)?;
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(SourceError::InvalidIdempotencyKey);
+    }
+
+    Ok(Some(key))
+}
+
+/// Validates a source of a batch, whose name must not be used by a previous valid source of the
+/// batch, in which case it is added to `names`.
+fn validate_batch_source(
+    source: &CreateSourceRequest,
+    names: &mut HashSet<String>,
+) -> Result<(), SourceError> {
+    validate_source_tags(&source.tags)?;
+    source.config.validate()?;
+    if !names.insert(source.name.clone()) {
+        return Err(SourceError::DuplicateName(source.name.clone()));
+    }
+
+    Ok(())
+}
+
 #[utoipa::path(
     context_path = "/v1",
     params(
@@ -174,23 +682,25 @@ pub async fn create_source(
 pub async fn read_source(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source_id: Path<i64>,
 ) -> Result<impl Responder, SourceError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let source_id = source_id.into_inner();
 
-    let response = db::sources::read_source(&**pool, tenant_id, source_id, &encryption_key)
+    let response = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
         .await?
         .map(|s| ReadSourceResponse {
             id: s.id,
             tenant_id: s.tenant_id,
             name: s.name,
             config: s.config.into(),
+            tags: s.tags,
         })
         .ok_or(SourceError::SourceNotFound(source_id))?;
 
-    Ok(Json(response))
+    Ok(Negotiated(response))
 }
 
 #[utoipa::path(
@@ -203,6 +713,7 @@ pub async fn read_source(
     responses(
         (status = 200, description = "Update source with id = source_id"),
         (status = 404, description = "Source not found", body = ErrorMessage),
+        (status = 409, description = "A source with the same name already exists", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage),
     ),
     tag = "Sources"
@@ -212,35 +723,305 @@ pub async fn update_source(
     req: HttpRequest,
     pool: Data<PgPool>,
     source_id: Path<i64>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source: Json<UpdateSourceRequest>,
 ) -> Result<impl Responder, SourceError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let source_id = source_id.into_inner();
     let source = source.into_inner();
+    validate_source_tags(&source.tags)?;
+    source.config.validate()?;
 
+    let config_hash = hash_source_config(&source.config);
+    let mut txn = pool.begin().await.map_err(SourcesDbError::from)?;
     db::sources::update_source(
-        &**pool,
+        txn.deref_mut(),
         tenant_id,
         &source.name,
         source_id,
         source.config,
-        &encryption_key,
+        &source.tags,
+        encryptor,
     )
-    .await?
+    .await
+    .map_err(|e| SourceError::from_sources_db(e, &source.name))?
     .ok_or(SourceError::SourceNotFound(source_id))?;
+    insert_source_audit_entry(
+        txn.deref_mut(),
+        tenant_id,
+        source_id,
+        SourceAction::Update,
+        Some(&config_hash),
+    )
+    .await?;
+    txn.commit().await.map_err(SourcesDbError::from)?;
 
     Ok(HttpResponse::Ok().finish())
 }
 
+/// Updates only the fields of the source which are set in the request, merging the config with
+/// the stored one, so that clients don't need to send the connection details again, e.g. to
+/// rename a source.
 #[utoipa::path(
     context_path = "/v1",
+    request_body = PatchSourceRequest,
     params(
         ("source_id" = i64, Path, description = "Id of the source"),
         ("tenant_id" = String, Header, description = "The tenant ID")
     ),
     responses(
-        (status = 200, description = "Delete source with id = source_id"),
+        (status = 200, description = "Update the given fields of source with id = source_id"),
+        (status = 400, description = "Bad request", body = ErrorMessage),
+        (status = 404, description = "Source not found", body = ErrorMessage),
+        (status = 409, description = "A source with the same name already exists", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Sources"
+)]
+#[patch("/sources/{source_id}")]
+pub async fn patch_source(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    source_id: Path<i64>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    patch: Json<PatchSourceRequest>,
+) -> Result<impl Responder, SourceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let source_id = source_id.into_inner();
+    let patch = patch.into_inner();
+
+    let mut source = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
+        .await?
+        .ok_or(SourceError::SourceNotFound(source_id))?;
+
+    if let Some(name) = patch.name {
+        source.name = name;
+    }
+    if let Some(config) = patch.config {
+        config.merge_into(&mut source.config);
+    }
+    if let Some(tags) = patch.tags {
+        source.tags = tags;
+    }
+    validate_source_tags(&source.tags)?;
+    source.config.validate()?;
+
+    let config_hash = hash_source_config(&source.config);
+    let mut txn = pool.begin().await.map_err(SourcesDbError::from)?;
+    db::sources::update_source(
+        txn.deref_mut(),
+        tenant_id,
+        &source.name,
+        source_id,
+        source.config,
+        &source.tags,
+        encryptor,
+    )
+    .await
+    .map_err(|e| SourceError::from_sources_db(e, &source.name))?
+    .ok_or(SourceError::SourceNotFound(source_id))?;
+    insert_source_audit_entry(
+        txn.deref_mut(),
+        tenant_id,
+        source_id,
+        SourceAction::Update,
+        Some(&config_hash),
+    )
+    .await?;
+    txn.commit().await.map_err(SourcesDbError::from)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    request_body = RotateSourceCredentialsRequest,
+    params(
+        ("source_id" = i64, Path, description = "Id of the source"),
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Rotate the credentials of source with id = source_id", body = RotateSourceCredentialsResponse),
+        (status = 404, description = "Source not found", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Sources"
+)]
+#[post("/sources/{source_id}/rotate-credentials")]
+pub async fn rotate_source_credentials(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    source_id: Path<i64>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    credentials: Json<RotateSourceCredentialsRequest>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, SourceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let source_id = source_id.into_inner();
+    let credentials = credentials.into_inner();
+
+    let mut config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
+        .await?
+        .map(|s| s.config)
+        .ok_or(SourceError::SourceNotFound(source_id))?;
+
+    // Only the credentials are replaced, the rest of the connection config is left untouched.
+    config.password = Some(credentials.password);
+    if let Some(username) = credentials.username {
+        config.username = username;
+    }
+
+    let mut verified = false;
+    if credentials.verify {
+        let options = config.clone().into_connection_config().with_db();
+        if let Err(err) = db::sources::test_source_connection(&source_connections, &options).await {
+            warn!(source_id, error = %err, "could not connect to source with rotated credentials");
+
+            let response = RotateSourceCredentialsResponse {
+                rotated: false,
+                verified: false,
+            };
+
+            return Ok(Json(response));
+        }
+
+        verified = true;
+    }
+
+    let config_hash = hash_source_config(&config);
+    let mut txn = pool.begin().await.map_err(SourcesDbError::from)?;
+    db::sources::update_source_config(txn.deref_mut(), tenant_id, source_id, config, encryptor)
+        .await?
+        .ok_or(SourceError::SourceNotFound(source_id))?;
+    insert_source_audit_entry(
+        txn.deref_mut(),
+        tenant_id,
+        source_id,
+        SourceAction::RotateCredentials,
+        Some(&config_hash),
+    )
+    .await?;
+    txn.commit().await.map_err(SourcesDbError::from)?;
+
+    let response = RotateSourceCredentialsResponse {
+        rotated: true,
+        verified,
+    };
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    params(
+        ("source_id" = i64, Path, description = "Id of the source"),
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Return the replication slots of the pipelines of source with id = source_id", body = SourceStatusResponse),
+        (status = 404, description = "Source not found", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Sources"
+)]
+#[get("/sources/{source_id}/status")]
+pub async fn source_status(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    source_id: Path<i64>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, SourceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let source_id = source_id.into_inner();
+
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
+        .await?
+        .map(|s| s.config)
+        .ok_or(SourceError::SourceNotFound(source_id))?;
+    let pipeline_ids =
+        db::pipelines::read_source_pipeline_ids(&**pool, tenant_id, source_id).await?;
+
+    let options = config.into_connection_config().with_db();
+    let mut slots = vec![];
+    // Other slots of the source database are not reported, they can belong to other tenants.
+    for slot in db::replication_slots::get_replication_slots(&source_connections, &options).await? {
+        let Some(pipeline_id) = pipeline_ids
+            .iter()
+            .find(|&&id| is_pipeline_slot(&slot.slot_name, id as u64))
+        else {
+            continue;
+        };
+
+        slots.push(ReplicationSlotStatus {
+            slot_name: slot.slot_name,
+            pipeline_id: *pipeline_id,
+            confirmed_flush_lsn: slot.confirmed_flush_lsn,
+            restart_lsn: slot.restart_lsn,
+            lag_bytes: slot.lag_bytes,
+        });
+    }
+
+    let response = SourceStatusResponse { slots };
+
+    Ok(Json(response))
+}
+
+/// Returns the audit log of the source, which is kept after the source is deleted.
+#[utoipa::path(
+    context_path = "/v1",
+    params(
+        ("source_id" = i64, Path, description = "Id of the source"),
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Return the audit log of source with id = source_id, from the oldest to the newest entry", body = SourceAuditLogResponse),
+        (status = 404, description = "Source not found", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Sources"
+)]
+#[get("/sources/{source_id}/audit")]
+pub async fn read_source_audit_log(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    source_id: Path<i64>,
+) -> Result<impl Responder, SourceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let source_id = source_id.into_inner();
+
+    let entries = db::audit_log::read_source_audit_log(&**pool, tenant_id, source_id).await?;
+    // Sources created before the audit log existed have no entries until they are changed.
+    if entries.is_empty() && !db::sources::source_exists(&**pool, tenant_id, source_id).await? {
+        return Err(SourceError::SourceNotFound(source_id));
+    }
+
+    let entries = entries
+        .into_iter()
+        .map(|entry| SourceAuditEntry {
+            id: entry.id,
+            action: entry.action,
+            config_hash: entry.config_hash,
+            created_at: entry.created_at,
+        })
+        .collect();
+    let response = SourceAuditLogResponse { entries };
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    params(
+        ("source_id" = i64, Path, description = "Id of the source"),
+        ("tenant_id" = String, Header, description = "The tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Delete source with id = source_id, which can be restored until it is purged"),
         (status = 404, description = "Source not found", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage),
     ),
@@ -255,9 +1036,19 @@ pub async fn delete_source(
     let tenant_id = extract_tenant_id(&req)?;
     let source_id = source_id.into_inner();
 
-    db::sources::delete_source(&**pool, tenant_id, source_id)
+    let mut txn = pool.begin().await.map_err(SourcesDbError::from)?;
+    db::sources::delete_source(txn.deref_mut(), tenant_id, source_id)
         .await?
         .ok_or(SourceError::SourceNotFound(source_id))?;
+    insert_source_audit_entry(
+        txn.deref_mut(),
+        tenant_id,
+        source_id,
+        SourceAction::Delete,
+        None,
+    )
+    .await?;
+    txn.commit().await.map_err(SourcesDbError::from)?;
 
     Ok(HttpResponse::Ok().finish())
 }
@@ -265,10 +1056,60 @@ pub async fn delete_source(
 #[utoipa::path(
     context_path = "/v1",
     params(
+        ("source_id" = i64, Path, description = "Id of the deleted source"),
         ("tenant_id" = String, Header, description = "The tenant ID")
     ),
     responses(
-        (status = 200, description = "Return all sources", body = ReadSourcesResponse),
+        (status = 200, description = "Restore deleted source with id = source_id"),
+        (status = 404, description = "Deleted source not found", body = ErrorMessage),
+        (status = 409, description = "Another source has the name of the deleted source", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage),
+    ),
+    tag = "Sources"
+)]
+#[post("/sources/{source_id}/restore")]
+pub async fn restore_source(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    source_id: Path<i64>,
+) -> Result<impl Responder, SourceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let source_id = source_id.into_inner();
+
+    let mut txn = pool.begin().await.map_err(SourcesDbError::from)?;
+    db::sources::restore_source(txn.deref_mut(), tenant_id, source_id)
+        .await
+        .map_err(|e| match e {
+            SourcesDbError::Database(e) if db::sources::is_duplicate_source_name_error(&e) => {
+                SourceError::RestoreConflict(source_id)
+            }
+            e => SourceError::SourcesDb(e),
+        })?
+        .ok_or(SourceError::SourceNotFound(source_id))?;
+    insert_source_audit_entry(
+        txn.deref_mut(),
+        tenant_id,
+        source_id,
+        SourceAction::Restore,
+        None,
+    )
+    .await?;
+    txn.commit().await.map_err(SourcesDbError::from)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    params(
+        ("tenant_id" = String, Header, description = "The tenant ID"),
+        ("tag" = Option<String>, Query, description = "Only return sources with this tag, in the `key:value` format"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of sources to return, between 1 and 200, defaults to 50"),
+        ("after" = Option<i64>, Query, description = "Only return sources after this cursor, as returned in `next_cursor`")
+    ),
+    responses(
+        (status = 200, description = "Return a page of sources", body = ReadSourcesResponse),
+        (status = 400, description = "Bad request", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage),
     ),
     tag = "Sources"
@@ -277,22 +1118,44 @@ pub async fn delete_source(
 pub async fn read_all_sources(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    query: Query<ReadSourcesQuery>,
 ) -> Result<impl Responder, SourceError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let tags = match &query.tag {
+        Some(tag) => parse_source_tag_filter(tag)?,
+        None => SourceTags::new(),
+    };
+    let limit = query.limit()?;
+
+    // One more source than the limit is read to know whether there is a next page.
+    let mut page =
+        db::sources::read_all_sources(&**pool, tenant_id, &tags, query.after, limit + 1, encryptor)
+            .await?;
+    let next_cursor = if page.len() as i64 > limit {
+        page.truncate(limit as usize);
+        page.last().map(|source| source.id)
+    } else {
+        None
+    };
 
     let mut sources = vec![];
-    for source in db::sources::read_all_sources(&**pool, tenant_id, &encryption_key).await? {
+    for source in page {
         let source = ReadSourceResponse {
             id: source.id,
             tenant_id: source.tenant_id,
             name: source.name,
             config: source.config.into(),
+            tags: source.tags,
         };
         sources.push(source);
     }
 
-    let response = ReadSourcesResponse { sources };
+    let response = ReadSourcesResponse {
+        sources,
+        next_cursor,
+    };
 
-    Ok(Json(response))
+    Ok(Negotiated(response))
 }
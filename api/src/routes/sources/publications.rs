@@ -7,14 +7,24 @@ use actix_web::{
 use config::shared::IntoConnectOptions;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
 use thiserror::Error;
 use utoipa::ToSchema;
 
+use crate::db::pipelines::PipelinesDbError;
 use crate::db::publications::PublicationsDbError;
 use crate::{
-    db::{self, publications::Publication, sources::SourcesDbError, tables::Table},
-    encryption::EncryptionKey,
-    routes::{ErrorMessage, TenantIdError, extract_tenant_id},
+    db::{
+        self,
+        publications::{
+            AddPublicationTable, DropPublicationTable, Publication, PublicationTable,
+        },
+        sources::SourcesDbError,
+    },
+    encryption::KeyProvider,
+    routes::{ErrorMessage, INTERNAL_ERROR_CODE, TenantIdError, extract_tenant_id},
+    source_connections::SourceConnectionLimiter,
 };
 
 #[derive(Debug, Error)]
@@ -25,6 +35,15 @@ enum PublicationError {
     #[error("The publication with name {0} was not found")]
     PublicationNotFound(String),
 
+    #[error("The table {0} was not found")]
+    TableNotFound(String),
+
+    #[error("The table {1} is already in the publication {0}")]
+    TableAlreadyPublished(String, String),
+
+    #[error("The table {1} is not in the publication {0}")]
+    TableNotPublished(String, String),
+
     #[error(transparent)]
     TenantId(#[from] TenantIdError),
 
@@ -33,6 +52,9 @@ enum PublicationError {
 
     #[error(transparent)]
     PublicationsDb(#[from] PublicationsDbError),
+
+    #[error(transparent)]
+    PipelinesDb(#[from] PipelinesDbError),
 }
 
 impl PublicationError {
@@ -40,30 +62,62 @@ impl PublicationError {
         match self {
             // Do not expose internal database details in error messages
             PublicationError::SourcesDb(SourcesDbError::Database(_))
-            | PublicationError::PublicationsDb(PublicationsDbError::Database(_)) => {
+            | PublicationError::PublicationsDb(PublicationsDbError::Database(_))
+            | PublicationError::PipelinesDb(PipelinesDbError::Database(_)) => {
                 "internal server error".to_string()
             }
             // Every other message is ok, as they do not divulge sensitive information
             e => e.to_string(),
         }
     }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            PublicationError::SourceNotFound(_) => "source_not_found",
+            PublicationError::PublicationNotFound(_) => "publication_not_found",
+            PublicationError::TableNotFound(_) => "table_not_found",
+            PublicationError::TableAlreadyPublished(..) => "table_already_published",
+            PublicationError::TableNotPublished(..) => "table_not_published",
+            PublicationError::TenantId(e) => e.to_code(),
+            PublicationError::PublicationsDb(PublicationsDbError::MissingReplicaIdentity(_)) => {
+                "missing_replica_identity"
+            }
+            PublicationError::PublicationsDb(PublicationsDbError::EmptyColumnList(_)) => {
+                "empty_column_list"
+            }
+            PublicationError::PublicationsDb(PublicationsDbError::InvalidRowFilter(..)) => {
+                "invalid_row_filter"
+            }
+            PublicationError::SourcesDb(_)
+            | PublicationError::PublicationsDb(PublicationsDbError::Database(_))
+            | PublicationError::PipelinesDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
 }
 
 impl ResponseError for PublicationError {
     fn status_code(&self) -> StatusCode {
         match self {
-            PublicationError::SourcesDb(_) | PublicationError::PublicationsDb(_) => {
-                StatusCode::INTERNAL_SERVER_ERROR
-            }
-            PublicationError::SourceNotFound(_) | PublicationError::PublicationNotFound(_) => {
-                StatusCode::NOT_FOUND
-            }
+            PublicationError::PublicationsDb(
+                PublicationsDbError::MissingReplicaIdentity(_)
+                | PublicationsDbError::EmptyColumnList(_)
+                | PublicationsDbError::InvalidRowFilter(..),
+            ) => StatusCode::BAD_REQUEST,
+            PublicationError::SourcesDb(_)
+            | PublicationError::PublicationsDb(_)
+            | PublicationError::PipelinesDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            PublicationError::SourceNotFound(_)
+            | PublicationError::PublicationNotFound(_)
+            | PublicationError::TableNotFound(_)
+            | PublicationError::TableNotPublished(..) => StatusCode::NOT_FOUND,
+            PublicationError::TableAlreadyPublished(..) => StatusCode::CONFLICT,
             PublicationError::TenantId(_) => StatusCode::BAD_REQUEST,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -74,23 +128,60 @@ impl ResponseError for PublicationError {
     }
 }
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreatePublicationRequest {
     #[schema(example = "my_publication", required = true)]
-    name: String,
+    pub name: String,
     #[schema(required = true)]
-    tables: Vec<Table>,
+    pub tables: Vec<PublicationTable>,
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct UpdatePublicationRequest {
     #[schema(required = true)]
-    tables: Vec<Table>,
+    tables: Vec<PublicationTable>,
 }
 
-#[derive(Serialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct ReadPublicationsResponse {
-    pub publications: Vec<Publication>,
+    pub publications: Vec<PublicationCoverage>,
+}
+
+/// A publication of a source, with the pipelines of the source which use it.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PublicationCoverage {
+    #[schema(example = "my_publication")]
+    pub name: String,
+    /// Ids of the pipelines of the source which replicate the publication.
+    pub pipeline_ids: Vec<i64>,
+    pub tables: Vec<PublicationTableCoverage>,
+}
+
+/// A table of a publication, with its replication state in each pipeline using the publication.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PublicationTableCoverage {
+    #[schema(example = "public")]
+    pub schema: String,
+    #[schema(example = "orders")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_filter: Option<String>,
+    /// Whether the table is replicated by a pipeline, i.e. whether a pipeline using the
+    /// publication is copying or streaming the table and didn't skip it after an error.
+    pub replicated: bool,
+    pub pipelines: Vec<TablePipelineState>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TablePipelineState {
+    #[schema(example = 1)]
+    pub pipeline_id: i64,
+    /// The replication phase of the table in the pipeline, as stored by the pipeline in the
+    /// source, or `None` if the pipeline didn't start replicating the table yet.
+    #[schema(example = "ready")]
+    pub state: Option<String>,
 }
 
 #[utoipa::path(
@@ -102,6 +193,7 @@ pub struct ReadPublicationsResponse {
     ),
     responses(
         (status = 200, description = "Create new publication"),
+        (status = 400, description = "A table of the publication has no replica identity, an empty column list or an invalid row filter", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage)
     )
 )]
@@ -109,14 +201,16 @@ pub struct ReadPublicationsResponse {
 pub async fn create_publication(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source_id: Path<i64>,
     publication: Json<CreatePublicationRequest>,
+    source_connections: Data<SourceConnectionLimiter>,
 ) -> Result<impl Responder, PublicationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let source_id = source_id.into_inner();
 
-    let config = db::sources::read_source(&**pool, tenant_id, source_id, &encryption_key)
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
         .await?
         .map(|s| s.config)
         .ok_or(PublicationError::SourceNotFound(source_id))?;
@@ -127,7 +221,7 @@ pub async fn create_publication(
         name: publication.name,
         tables: publication.tables,
     };
-    db::publications::create_publication(&publication, &options).await?;
+    db::publications::create_publication(&publication, &source_connections, &options).await?;
 
     Ok(HttpResponse::Ok().finish())
 }
@@ -149,21 +243,24 @@ pub async fn create_publication(
 pub async fn read_publication(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source_id_and_pub_name: Path<(i64, String)>,
+    source_connections: Data<SourceConnectionLimiter>,
 ) -> Result<impl Responder, PublicationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let (source_id, publication_name) = source_id_and_pub_name.into_inner();
 
-    let config = db::sources::read_source(&**pool, tenant_id, source_id, &encryption_key)
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
         .await?
         .map(|s| s.config)
         .ok_or(PublicationError::SourceNotFound(source_id))?;
 
     let options = config.into_connection_config().with_db();
-    let publications = db::publications::read_publication(&publication_name, &options)
-        .await?
-        .ok_or(PublicationError::PublicationNotFound(publication_name))?;
+    let publications =
+        db::publications::read_publication(&publication_name, &source_connections, &options)
+            .await?
+            .ok_or(PublicationError::PublicationNotFound(publication_name))?;
 
     Ok(Json(publications))
 }
@@ -178,6 +275,7 @@ pub async fn read_publication(
     ),
     responses(
         (status = 200, description = "Update publication with name = publication_name from source with id = source_id"),
+        (status = 400, description = "A table of the publication has no replica identity, an empty column list or an invalid row filter", body = ErrorMessage),
         (status = 404, description = "Publication not found", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage)
     )
@@ -186,14 +284,16 @@ pub async fn read_publication(
 pub async fn update_publication(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source_id_and_pub_name: Path<(i64, String)>,
     publication: Json<UpdatePublicationRequest>,
+    source_connections: Data<SourceConnectionLimiter>,
 ) -> Result<impl Responder, PublicationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let (source_id, publication_name) = source_id_and_pub_name.into_inner();
 
-    let config = db::sources::read_source(&**pool, tenant_id, source_id, &encryption_key)
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
         .await?
         .map(|s| s.config)
         .ok_or(PublicationError::SourceNotFound(source_id))?;
@@ -204,7 +304,7 @@ pub async fn update_publication(
         name: publication_name,
         tables: publication.tables,
     };
-    db::publications::update_publication(&publication, &options).await?;
+    db::publications::update_publication(&publication, &source_connections, &options).await?;
 
     Ok(HttpResponse::Ok().finish())
 }
@@ -226,19 +326,21 @@ pub async fn update_publication(
 pub async fn delete_publication(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source_id_and_pub_name: Path<(i64, String)>,
+    source_connections: Data<SourceConnectionLimiter>,
 ) -> Result<impl Responder, PublicationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let (source_id, publication_name) = source_id_and_pub_name.into_inner();
 
-    let config = db::sources::read_source(&**pool, tenant_id, source_id, &encryption_key)
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
         .await?
         .map(|s| s.config)
         .ok_or(PublicationError::SourceNotFound(source_id))?;
 
     let options = config.into_connection_config().with_db();
-    db::publications::drop_publication(&publication_name, &options).await?;
+    db::publications::drop_publication(&publication_name, &source_connections, &options).await?;
 
     Ok(HttpResponse::Ok().finish())
 }
@@ -246,11 +348,122 @@ pub async fn delete_publication(
 #[utoipa::path(
     context_path = "/v1",
     tag = "Publications",
+    request_body = PublicationTable,
     params(
         ("source_id" = i64, Path, description = "Id of the source"),
+        ("publication_name" = String, Path, description = "Name of the publication"),
     ),
     responses(
-        (status = 200, description = "Return all publications", body = ReadPublicationsResponse),
+        (status = 200, description = "Add a table to the publication, which the running pipelines using it start copying"),
+        (status = 400, description = "The table has no replica identity, an empty column list or an invalid row filter", body = ErrorMessage),
+        (status = 404, description = "Source, publication or table not found", body = ErrorMessage),
+        (status = 409, description = "The table is already in the publication", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage)
+    )
+)]
+#[post("/sources/{source_id}/publications/{publication_name}/tables")]
+pub async fn add_publication_table(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    source_id_and_pub_name: Path<(i64, String)>,
+    table: Json<PublicationTable>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, PublicationError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let (source_id, publication_name) = source_id_and_pub_name.into_inner();
+    let table = table.into_inner();
+
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
+        .await?
+        .map(|s| s.config)
+        .ok_or(PublicationError::SourceNotFound(source_id))?;
+
+    let options = config.into_connection_config().with_db();
+    let table_name = format!("{}.{}", table.schema, table.name);
+    match db::publications::add_publication_table(
+        &publication_name,
+        &table,
+        &source_connections,
+        &options,
+    )
+    .await?
+    {
+        AddPublicationTable::Added => Ok(HttpResponse::Ok().finish()),
+        AddPublicationTable::PublicationNotFound => {
+            Err(PublicationError::PublicationNotFound(publication_name))
+        }
+        AddPublicationTable::TableNotFound => Err(PublicationError::TableNotFound(table_name)),
+        AddPublicationTable::TableAlreadyPublished => Err(
+            PublicationError::TableAlreadyPublished(publication_name, table_name),
+        ),
+    }
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    tag = "Publications",
+    params(
+        ("source_id" = i64, Path, description = "Id of the source"),
+        ("publication_name" = String, Path, description = "Name of the publication"),
+        ("schema" = String, Path, description = "Schema of the table"),
+        ("table_name" = String, Path, description = "Name of the table"),
+    ),
+    responses(
+        (status = 200, description = "Drop a table from the publication, which the running pipelines using it stop replicating"),
+        (status = 404, description = "Source or publication not found, or the table is not in the publication", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage)
+    )
+)]
+#[delete("/sources/{source_id}/publications/{publication_name}/tables/{schema}/{table_name}")]
+pub async fn drop_publication_table(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    path: Path<(i64, String, String, String)>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, PublicationError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let (source_id, publication_name, schema, name) = path.into_inner();
+
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
+        .await?
+        .map(|s| s.config)
+        .ok_or(PublicationError::SourceNotFound(source_id))?;
+
+    let options = config.into_connection_config().with_db();
+    let table_name = format!("{schema}.{name}");
+    match db::publications::drop_publication_table(
+        &publication_name,
+        &schema,
+        &name,
+        &source_connections,
+        &options,
+    )
+    .await?
+    {
+        DropPublicationTable::Dropped => Ok(HttpResponse::Ok().finish()),
+        DropPublicationTable::PublicationNotFound => {
+            Err(PublicationError::PublicationNotFound(publication_name))
+        }
+        DropPublicationTable::TableNotPublished => Err(PublicationError::TableNotPublished(
+            publication_name,
+            table_name,
+        )),
+    }
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    tag = "Publications",
+    params(
+        ("source_id" = i64, Path, description = "Id of the source"),
+    ),
+    responses(
+        (status = 200, description = "Return all publications, with the pipelines replicating each of their tables", body = ReadPublicationsResponse),
+        (status = 404, description = "Source not found", body = ErrorMessage),
         (status = 500, description = "Internal server error", body = ErrorMessage)
     )
 )]
@@ -258,19 +471,88 @@ pub async fn delete_publication(
 pub async fn read_all_publications(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source_id: Path<i64>,
+    source_connections: Data<SourceConnectionLimiter>,
 ) -> Result<impl Responder, PublicationError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let source_id = source_id.into_inner();
 
-    let config = db::sources::read_source(&**pool, tenant_id, source_id, &encryption_key)
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
         .await?
         .map(|s| s.config)
         .ok_or(PublicationError::SourceNotFound(source_id))?;
 
+    let pipelines: Vec<_> = db::pipelines::read_all_pipelines(&**pool, tenant_id)
+        .await?
+        .into_iter()
+        .filter(|pipeline| pipeline.source_id == source_id)
+        .collect();
+    let pipeline_ids: Vec<i64> = pipelines.iter().map(|pipeline| pipeline.id).collect();
+
     let options = config.into_connection_config().with_db();
-    let publications = db::publications::read_all_publications(&options).await?;
+    let publications =
+        db::publications::read_all_publications(&source_connections, &options).await?;
+    let states: HashMap<_, _> = db::publications::read_table_replication_states(
+        &pipeline_ids,
+        &source_connections,
+        &options,
+    )
+    .await?
+    .into_iter()
+    .map(|state| ((state.pipeline_id, state.schema, state.name), state.state))
+    .collect();
+
+    let mut publications: Vec<_> = publications
+        .into_iter()
+        .map(|publication| {
+            let pipeline_ids: Vec<i64> = pipelines
+                .iter()
+                .filter(|pipeline| pipeline.config.publication_name == publication.name)
+                .map(|pipeline| pipeline.id)
+                .collect();
+            let tables = publication
+                .tables
+                .into_iter()
+                .map(|table| {
+                    let pipelines: Vec<_> = pipeline_ids
+                        .iter()
+                        .map(|&pipeline_id| TablePipelineState {
+                            pipeline_id,
+                            state: states
+                                .get(&(pipeline_id, table.schema.clone(), table.name.clone()))
+                                .cloned(),
+                        })
+                        .collect();
+                    // Tables are skipped by the pipelines which failed to replicate them.
+                    let replicated = pipelines.iter().any(|pipeline| {
+                        pipeline
+                            .state
+                            .as_deref()
+                            .is_some_and(|state| state != "skipped")
+                    });
+
+                    PublicationTableCoverage {
+                        schema: table.schema,
+                        name: table.name,
+                        columns: table.columns,
+                        row_filter: table.row_filter,
+                        replicated,
+                        pipelines,
+                    }
+                })
+                .collect();
+
+            PublicationCoverage {
+                name: publication.name,
+                pipeline_ids,
+                tables,
+            }
+        })
+        .collect();
+    publications.sort_by(|a, b| a.name.cmp(&b.name));
+
     let response = ReadPublicationsResponse { publications };
 
     Ok(Json(response))
@@ -1,19 +1,22 @@
 use actix_web::{
     HttpRequest, HttpResponse, Responder, ResponseError, get,
     http::{StatusCode, header::ContentType},
-    web::{Data, Json, Path},
+    web::{Data, Json, Path, Query},
 };
 use config::shared::IntoConnectOptions;
+use postgres::schema::ColumnSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
 use thiserror::Error;
 use utoipa::ToSchema;
 
-use crate::db::tables::TablesDbError;
+use crate::db::tables::{TableSample, TableSampleMethod, TableWithColumns, TablesDbError};
 use crate::{
     db::{self, sources::SourcesDbError, tables::Table},
-    encryption::EncryptionKey,
-    routes::{ErrorMessage, TenantIdError, extract_tenant_id},
+    encryption::KeyProvider,
+    routes::{ErrorMessage, INTERNAL_ERROR_CODE, TenantIdError, extract_tenant_id},
+    source_connections::SourceConnectionLimiter,
 };
 
 #[derive(Debug, Error)]
@@ -29,6 +32,15 @@ enum TableError {
 
     #[error(transparent)]
     TablesDb(#[from] TablesDbError),
+
+    #[error("The sample percentage must be greater than 0 and at most 100, got {0}")]
+    InvalidSamplePercent(f64),
+
+    #[error("The sample percentage is required when a sample method is set")]
+    MissingSamplePercent,
+
+    #[error("The limit must be between 1 and {max}, got {0}", max = MAX_PREVIEW_ROWS)]
+    InvalidLimit(i64),
 }
 
 impl TableError {
@@ -43,25 +55,160 @@ impl TableError {
             e => e.to_string(),
         }
     }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            TableError::SourceNotFound(_) => "source_not_found",
+            TableError::TenantId(e) => e.to_code(),
+            TableError::TablesDb(TablesDbError::TableNotFound(..)) => "table_not_found",
+            TableError::InvalidSamplePercent(_) => "invalid_sample_percent",
+            TableError::MissingSamplePercent => "missing_sample_percent",
+            TableError::InvalidLimit(_) => "invalid_limit",
+            TableError::SourcesDb(_) | TableError::TablesDb(TablesDbError::Database(_)) => {
+                INTERNAL_ERROR_CODE
+            }
+        }
+    }
+}
+
+/// Default number of rows returned by a table preview.
+const DEFAULT_PREVIEW_ROWS: i64 = 100;
+
+/// Maximum number of rows returned by a table preview.
+const MAX_PREVIEW_ROWS: i64 = 1000;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReadTablesQuery {
+    /// Only returns the tables of this schema.
+    pub schema: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct ReadTablesResponse {
     #[schema(required = true)]
-    pub tables: Vec<Table>,
+    pub tables: Vec<ReadTableResponse>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReadTableResponse {
+    #[schema(example = "public", required = true)]
+    pub schema: String,
+    #[schema(example = "orders", required = true)]
+    pub name: String,
+    /// The columns of the table, in their order in the table.
+    #[schema(required = true)]
+    pub columns: Vec<ReadColumnResponse>,
+}
+
+impl From<TableWithColumns> for ReadTableResponse {
+    fn from(table: TableWithColumns) -> Self {
+        Self {
+            schema: table.table.schema,
+            name: table.table.name,
+            columns: table.columns.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReadColumnResponse {
+    #[schema(example = "id", required = true)]
+    pub name: String,
+    /// The name of the type of the column, without its modifier.
+    #[schema(example = "int8", required = true)]
+    pub type_name: String,
+    #[schema(example = 20, required = true)]
+    pub type_oid: u32,
+    /// The type modifier of the column, e.g. the length of a `varchar`, or -1 if it has none.
+    #[schema(example = -1, required = true)]
+    pub modifier: i32,
+    #[schema(example = false, required = true)]
+    pub nullable: bool,
+    /// Whether the column is part of the primary key of the table.
+    #[schema(example = true, required = true)]
+    pub primary: bool,
+}
+
+impl From<ColumnSchema> for ReadColumnResponse {
+    fn from(column: ColumnSchema) -> Self {
+        Self {
+            name: column.name,
+            type_name: column.typ.name().to_string(),
+            type_oid: column.typ.oid(),
+            modifier: column.modifier,
+            nullable: column.nullable,
+            primary: column.primary,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PreviewTableQuery {
+    /// Maximum number of rows to return, defaults to 100.
+    pub limit: Option<i64>,
+    /// Method used to sample the rows, defaults to `system` when a sample percentage is set.
+    pub sample_method: Option<TableSampleMethod>,
+    /// Percentage of the table to sample, in the `(0, 100]` range.
+    pub sample_percent: Option<f64>,
+}
+
+impl PreviewTableQuery {
+    fn limit(&self) -> Result<i64, TableError> {
+        let limit = self.limit.unwrap_or(DEFAULT_PREVIEW_ROWS);
+        if !(1..=MAX_PREVIEW_ROWS).contains(&limit) {
+            return Err(TableError::InvalidLimit(limit));
+        }
+
+        Ok(limit)
+    }
+
+    fn sample(&self) -> Result<Option<TableSample>, TableError> {
+        let percent = match (self.sample_method, self.sample_percent) {
+            (None, None) => return Ok(None),
+            (Some(_), None) => return Err(TableError::MissingSamplePercent),
+            (_, Some(percent)) => percent,
+        };
+        // The negated comparison also rejects NaN.
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(TableError::InvalidSamplePercent(percent));
+        }
+
+        Ok(Some(TableSample {
+            method: self.sample_method.unwrap_or(TableSampleMethod::System),
+            percent,
+        }))
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PreviewTableResponse {
+    /// The rows of the table, each encoded as a JSON object keyed by column name.
+    #[schema(required = true)]
+    pub rows: Vec<serde_json::Value>,
+    /// Whether the rows were sampled. Tables too small to be sampled are read with a plain
+    /// limit instead.
+    #[schema(required = true)]
+    pub sampled: bool,
 }
 
 impl ResponseError for TableError {
     fn status_code(&self) -> StatusCode {
         match self {
-            TableError::SourcesDb(_) | TableError::TablesDb(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            TableError::SourceNotFound(_) => StatusCode::NOT_FOUND,
-            TableError::TenantId(_) => StatusCode::BAD_REQUEST,
+            TableError::SourcesDb(_) | TableError::TablesDb(TablesDbError::Database(_)) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            TableError::SourceNotFound(_)
+            | TableError::TablesDb(TablesDbError::TableNotFound(..)) => StatusCode::NOT_FOUND,
+            TableError::TenantId(_)
+            | TableError::InvalidSamplePercent(_)
+            | TableError::MissingSamplePercent
+            | TableError::InvalidLimit(_) => StatusCode::BAD_REQUEST,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -77,9 +224,10 @@ impl ResponseError for TableError {
     tag = "Tables",
     params(
         ("source_id" = i64, Path, description = "Id of the source"),
+        ("schema" = Option<String>, Query, description = "Only return the tables of this schema"),
     ),
     responses(
-        (status = 200, description = "Return all tables from source with id = source_id", body = ReadTablesResponse),
+        (status = 200, description = "Return the tables in the search path of the source with id = source_id, with their columns", body = ReadTablesResponse),
         (status = 500, description = "Internal server error", body = ErrorMessage)
     )
 )]
@@ -87,20 +235,76 @@ impl ResponseError for TableError {
 pub async fn read_table_names(
     req: HttpRequest,
     pool: Data<PgPool>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
     source_id: Path<i64>,
+    query: Query<ReadTablesQuery>,
+    source_connections: Data<SourceConnectionLimiter>,
 ) -> Result<impl Responder, TableError> {
     let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
     let source_id = source_id.into_inner();
 
-    let config = db::sources::read_source(&**pool, tenant_id, source_id, &encryption_key)
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
+        .await?
+        .map(|s| s.config)
+        .ok_or(TableError::SourceNotFound(source_id))?;
+
+    let options = config.into_connection_config().with_db();
+    let tables =
+        db::tables::get_tables(&source_connections, &options, query.schema.as_deref()).await?;
+    let response = ReadTablesResponse {
+        tables: tables.into_iter().map(Into::into).collect(),
+    };
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    context_path = "/v1",
+    tag = "Tables",
+    params(
+        ("source_id" = i64, Path, description = "Id of the source"),
+        ("schema" = String, Path, description = "Schema of the table"),
+        ("name" = String, Path, description = "Name of the table"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of rows to return"),
+        ("sample_method" = Option<TableSampleMethod>, Query, description = "Method used to sample the rows"),
+        ("sample_percent" = Option<f64>, Query, description = "Percentage of the table to sample"),
+    ),
+    responses(
+        (status = 200, description = "Return a preview of the rows of the table", body = PreviewTableResponse),
+        (status = 400, description = "Bad request", body = ErrorMessage),
+        (status = 404, description = "Source or table not found", body = ErrorMessage),
+        (status = 500, description = "Internal server error", body = ErrorMessage)
+    )
+)]
+#[get("/sources/{source_id}/tables/{schema}/{name}/preview")]
+pub async fn preview_table(
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    path: Path<(i64, String, String)>,
+    query: Query<PreviewTableQuery>,
+    source_connections: Data<SourceConnectionLimiter>,
+) -> Result<impl Responder, TableError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let encryptor = key_provider.encryptor(tenant_id);
+    let (source_id, schema, name) = path.into_inner();
+    let limit = query.limit()?;
+    let sample = query.sample()?;
+
+    let config = db::sources::read_source(&**pool, tenant_id, source_id, encryptor)
         .await?
         .map(|s| s.config)
         .ok_or(TableError::SourceNotFound(source_id))?;
 
     let options = config.into_connection_config().with_db();
-    let tables = db::tables::get_tables(&options).await?;
-    let response = ReadTablesResponse { tables };
+    let table = Table { schema, name };
+    let preview =
+        db::tables::preview_table(&source_connections, &options, &table, sample, limit).await?;
+    let response = PreviewTableResponse {
+        rows: preview.rows,
+        sampled: preview.sampled,
+    };
 
     Ok(Json(response))
 }
@@ -12,7 +12,8 @@ use utoipa::ToSchema;
 
 use crate::db;
 use crate::db::tenants::TenantsDbError;
-use crate::routes::ErrorMessage;
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE};
+use crate::span_builder::TenantIdMasker;
 
 #[derive(Debug, Error)]
 pub enum TenantError {
@@ -34,6 +35,13 @@ impl TenantError {
             e => e.to_string(),
         }
     }
+
+    pub fn to_code(&self) -> &'static str {
+        match self {
+            TenantError::TenantNotFound(_) => "tenant_not_found",
+            TenantError::TenantsDb(_) => INTERNAL_ERROR_CODE,
+        }
+    }
 }
 
 impl ResponseError for TenantError {
@@ -46,6 +54,7 @@ impl ResponseError for TenantError {
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -115,11 +124,12 @@ pub struct ReadTenantsResponse {
 pub async fn create_tenant(
     pool: Data<PgPool>,
     tenant: Json<CreateTenantRequest>,
+    tenant_id_masker: Data<TenantIdMasker>,
     root_span: RootSpan,
 ) -> Result<impl Responder, TenantError> {
     let tenant = tenant.into_inner();
 
-    root_span.record("project", &tenant.id);
+    tenant_id_masker.record_project(&root_span, &tenant.id);
 
     let id = db::tenants::create_tenant(&**pool, &tenant.id, &tenant.name).await?;
 
@@ -146,12 +156,13 @@ pub async fn create_or_update_tenant(
     pool: Data<PgPool>,
     tenant_id: Path<String>,
     tenant: Json<CreateOrUpdateTenantRequest>,
+    tenant_id_masker: Data<TenantIdMasker>,
     root_span: RootSpan,
 ) -> Result<impl Responder, TenantError> {
     let tenant_id = tenant_id.into_inner();
     let tenant = tenant.into_inner();
 
-    root_span.record("project", &tenant_id);
+    tenant_id_masker.record_project(&root_span, &tenant_id);
 
     let id = db::tenants::create_or_update_tenant(&**pool, &tenant_id, &tenant.name).await?;
     let response = CreateOrUpdateTenantResponse { id };
@@ -175,11 +186,12 @@ pub async fn create_or_update_tenant(
 pub async fn read_tenant(
     pool: Data<PgPool>,
     tenant_id: Path<String>,
+    tenant_id_masker: Data<TenantIdMasker>,
     root_span: RootSpan,
 ) -> Result<impl Responder, TenantError> {
     let tenant_id = tenant_id.into_inner();
 
-    root_span.record("project", &tenant_id);
+    tenant_id_masker.record_project(&root_span, &tenant_id);
 
     let response = db::tenants::read_tenant(&**pool, &tenant_id)
         .await?
@@ -210,12 +222,13 @@ pub async fn update_tenant(
     pool: Data<PgPool>,
     tenant_id: Path<String>,
     tenant: Json<UpdateTenantRequest>,
+    tenant_id_masker: Data<TenantIdMasker>,
     root_span: RootSpan,
 ) -> Result<impl Responder, TenantError> {
     let tenant = tenant.into_inner();
     let tenant_id = tenant_id.into_inner();
 
-    root_span.record("project", &tenant_id);
+    tenant_id_masker.record_project(&root_span, &tenant_id);
 
     db::tenants::update_tenant(&**pool, &tenant_id, &tenant.name)
         .await?
@@ -240,11 +253,12 @@ pub async fn update_tenant(
 pub async fn delete_tenant(
     pool: Data<PgPool>,
     tenant_id: Path<String>,
+    tenant_id_masker: Data<TenantIdMasker>,
     root_span: RootSpan,
 ) -> Result<impl Responder, TenantError> {
     let tenant_id = tenant_id.into_inner();
 
-    root_span.record("project", &tenant_id);
+    tenant_id_masker.record_project(&root_span, &tenant_id);
 
     db::tenants::delete_tenant(&**pool, &tenant_id).await?;
 
@@ -6,15 +6,18 @@ use actix_web::{
 };
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
 use thiserror::Error;
 use tracing_actix_web::RootSpan;
 use utoipa::ToSchema;
 
 use crate::db;
-use crate::db::sources::SourceConfig;
+use crate::db::audit_log::AuditLogDbError;
+use crate::db::sources::{SourceConfig, SourceConfigError};
 use crate::db::tenants_sources::TenantSourceDbError;
-use crate::encryption::EncryptionKey;
-use crate::routes::ErrorMessage;
+use crate::encryption::KeyProvider;
+use crate::routes::{ErrorMessage, INTERNAL_ERROR_CODE};
+use crate::span_builder::TenantIdMasker;
 
 #[derive(Debug, Error)]
 enum TenantSourceError {
@@ -23,18 +26,33 @@ enum TenantSourceError {
 
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
+
+    #[error(transparent)]
+    InvalidSourceConfig(#[from] SourceConfigError),
 }
 
 impl TenantSourceError {
     fn to_message(&self) -> String {
         match self {
             // Do not expose internal database details in error messages
-            TenantSourceError::TenantSourceDb(TenantSourceDbError::Database(_))
+            TenantSourceError::TenantSourceDb(
+                TenantSourceDbError::Database(_)
+                | TenantSourceDbError::AuditLog(AuditLogDbError::Database(_)),
+            )
             | TenantSourceError::Database(_) => "internal server error".to_string(),
             // Every other message is ok, as they do not divulge sensitive information
             e => e.to_string(),
         }
     }
+
+    fn to_code(&self) -> &'static str {
+        match self {
+            TenantSourceError::InvalidSourceConfig(_) => "invalid_source_config",
+            TenantSourceError::TenantSourceDb(_) | TenantSourceError::Database(_) => {
+                INTERNAL_ERROR_CODE
+            }
+        }
+    }
 }
 
 impl ResponseError for TenantSourceError {
@@ -43,11 +61,13 @@ impl ResponseError for TenantSourceError {
             TenantSourceError::TenantSourceDb(_) | TenantSourceError::Database(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
+            TenantSourceError::InvalidSourceConfig(_) => StatusCode::BAD_REQUEST,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let error_message = ErrorMessage {
+            code: self.to_code(),
             error: self.to_message(),
         };
         let body =
@@ -92,12 +112,15 @@ pub struct CreateTenantSourceResponse {
 pub async fn create_tenant_and_source(
     pool: Data<PgPool>,
     tenant_and_source: Json<CreateTenantSourceRequest>,
-    encryption_key: Data<EncryptionKey>,
+    key_provider: Data<Arc<dyn KeyProvider>>,
+    tenant_id_masker: Data<TenantIdMasker>,
     root_span: RootSpan,
 ) -> Result<impl Responder, TenantSourceError> {
     let tenant_and_source = tenant_and_source.into_inner();
+    tenant_and_source.source_config.validate()?;
+    let encryptor = key_provider.encryptor(&tenant_and_source.tenant_id);
 
-    root_span.record("project", &tenant_and_source.tenant_id);
+    tenant_id_masker.record_project(&root_span, &tenant_and_source.tenant_id);
 
     let mut txn = pool.begin().await?;
     let (tenant_id, source_id) = db::tenants_sources::create_tenant_and_source(
@@ -106,7 +129,7 @@ pub async fn create_tenant_and_source(
         &tenant_and_source.tenant_name,
         &tenant_and_source.source_name,
         tenant_and_source.source_config,
-        &encryption_key,
+        encryptor,
     )
     .await?;
     txn.commit().await?;
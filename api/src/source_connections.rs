@@ -0,0 +1,216 @@
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{Connection, PgConnection};
+use std::collections::HashMap;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::SourceConnectionsConfig;
+
+/// Identifies a source database, as connected to by a set of [`PgConnectOptions`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SourceDatabase {
+    host: String,
+    port: u16,
+    database: Option<String>,
+}
+
+impl SourceDatabase {
+    fn new(options: &PgConnectOptions) -> Self {
+        Self {
+            host: options.get_host().to_string(),
+            port: options.get_port(),
+            database: options.get_database().map(str::to_string),
+        }
+    }
+}
+
+/// Limits the connections the API opens at the same time to each source database, as configured
+/// by [`SourceConnectionsConfig`].
+///
+/// Connections are counted in memory, so the limits apply per replica of the API.
+pub struct SourceConnectionLimiter {
+    max_connections_per_source: usize,
+    connect_timeout: Duration,
+    semaphores: Mutex<HashMap<SourceDatabase, Arc<Semaphore>>>,
+}
+
+impl SourceConnectionLimiter {
+    pub fn new(config: &SourceConnectionsConfig) -> Self {
+        Self {
+            max_connections_per_source: config.max_connections_per_source.get(),
+            connect_timeout: Duration::from_secs(config.connect_timeout_secs),
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Opens a connection to the source database that `options` connects to, once fewer than the
+    /// maximum number of connections are open to it.
+    ///
+    /// Fails with a [`io::ErrorKind::TimedOut`] error if the connection is not open within the
+    /// connect timeout, including the time spent waiting for other connections to be closed.
+    pub async fn connect(
+        &self,
+        options: &PgConnectOptions,
+    ) -> Result<SourceConnection, sqlx::Error> {
+        let semaphore = self.semaphore(options);
+        let connect = async {
+            let permit = semaphore
+                .acquire_owned()
+                .await
+                .expect("source connection semaphores are never closed");
+            let connection = PgConnection::connect_with(options).await?;
+
+            Ok(SourceConnection {
+                connection,
+                _permit: permit,
+            })
+        };
+
+        tokio::time::timeout(self.connect_timeout, connect)
+            .await
+            .map_err(|_| connect_timed_out())?
+    }
+
+    /// Waits until fewer than the maximum number of connections are open to the source database
+    /// that `options` connects to, for connections which are not opened by
+    /// [`SourceConnectionLimiter::connect`].
+    ///
+    /// The connection is counted until the returned permit is dropped.
+    pub async fn acquire(
+        &self,
+        options: &PgConnectOptions,
+    ) -> Result<OwnedSemaphorePermit, sqlx::Error> {
+        let permit = tokio::time::timeout(
+            self.connect_timeout,
+            self.semaphore(options).acquire_owned(),
+        )
+        .await
+        .map_err(|_| connect_timed_out())?
+        .expect("source connection semaphores are never closed");
+
+        Ok(permit)
+    }
+
+    fn semaphore(&self, options: &PgConnectOptions) -> Arc<Semaphore> {
+        let mut semaphores = self
+            .semaphores
+            .lock()
+            .expect("source connection limiter lock poisoned");
+
+        let source_database = SourceDatabase::new(options);
+        // The semaphores without permits in use, i.e. only referenced by the map, are dropped
+        // whenever a new source database is connected to, so that the sources which aren't used
+        // anymore don't take memory forever.
+        if !semaphores.contains_key(&source_database) {
+            semaphores.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        }
+
+        semaphores
+            .entry(source_database)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_connections_per_source)))
+            .clone()
+    }
+}
+
+fn connect_timed_out() -> sqlx::Error {
+    sqlx::Error::Io(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "timed out while connecting to the source database",
+    ))
+}
+
+/// A connection to a source database opened by [`SourceConnectionLimiter::connect`], which counts
+/// towards the limit of its source database until it is dropped.
+pub struct SourceConnection {
+    connection: PgConnection,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl SourceConnection {
+    /// Closes the connection, see [`Connection::close`].
+    pub async fn close(self) -> Result<(), sqlx::Error> {
+        self.connection.close().await
+    }
+}
+
+impl Deref for SourceConnection {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.connection
+    }
+}
+
+impl DerefMut for SourceConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.connection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+
+    fn limiter(max_connections_per_source: usize) -> SourceConnectionLimiter {
+        SourceConnectionLimiter::new(&SourceConnectionsConfig {
+            max_connections_per_source: NonZeroUsize::new(max_connections_per_source).unwrap(),
+            connect_timeout_secs: 1,
+        })
+    }
+
+    fn options(host: &str, database: &str) -> PgConnectOptions {
+        PgConnectOptions::new()
+            .host(host)
+            .port(5432)
+            .database(database)
+    }
+
+    #[tokio::test]
+    async fn connections_over_the_limit_wait_until_a_connection_is_closed() {
+        let limiter = limiter(2);
+        let options = options("source.example.com", "postgres");
+
+        let first = limiter.acquire(&options).await.unwrap();
+        let _second = limiter.acquire(&options).await.unwrap();
+        let err = limiter.acquire(&options).await.unwrap_err();
+        assert!(matches!(err, sqlx::Error::Io(e) if e.kind() == io::ErrorKind::TimedOut));
+
+        drop(first);
+        assert!(limiter.acquire(&options).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn source_databases_are_limited_independently() {
+        let limiter = limiter(1);
+
+        let _permit = limiter
+            .acquire(&options("source.example.com", "postgres"))
+            .await
+            .unwrap();
+        assert!(
+            limiter
+                .acquire(&options("source.example.com", "other"))
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .acquire(&options("other.example.com", "postgres"))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn configs_without_connections_per_source_are_rejected() {
+        let config = serde_json::from_str::<SourceConnectionsConfig>(
+            r#"{"max_connections_per_source": 0, "connect_timeout_secs": 10}"#,
+        );
+        assert!(config.is_err());
+    }
+}
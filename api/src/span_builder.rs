@@ -2,13 +2,75 @@ use actix_web::{
     Error,
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
+    web::Data,
 };
+use aws_lc_rs::{hmac, rand::fill};
+use std::{borrow::Cow, fmt};
 use tracing::{Span, info};
 use tracing_actix_web::{DefaultRootSpanBuilder, RootSpanBuilder};
 
+use crate::config::TenantIdLogging;
+
+/// Number of bytes of the salted hash kept in a masked tenant id.
+const MASKED_TENANT_ID_LEN: usize = 8;
+
+/// Masks the tenant ids written to the logs and spans, as configured by [`TenantIdLogging`].
+///
+/// Clones share the same salt, so a tenant id is masked to the same value by all of them.
+#[derive(Clone)]
+pub struct TenantIdMasker {
+    key: Option<hmac::Key>,
+}
+
+impl TenantIdMasker {
+    /// Creates a new [`TenantIdMasker`], generating a random salt if tenant ids are hashed.
+    pub fn new(logging: TenantIdLogging) -> Result<Self, aws_lc_rs::error::Unspecified> {
+        let key = match logging {
+            TenantIdLogging::Raw => None,
+            TenantIdLogging::Hashed => {
+                let mut salt = [0u8; 32];
+                fill(&mut salt)?;
+
+                Some(hmac::Key::new(hmac::HMAC_SHA256, &salt))
+            }
+        };
+
+        Ok(Self { key })
+    }
+
+    /// Returns the value to log for `tenant_id`.
+    pub fn mask<'a>(&self, tenant_id: &'a str) -> Cow<'a, str> {
+        let Some(key) = &self.key else {
+            return Cow::Borrowed(tenant_id);
+        };
+
+        let tag = hmac::sign(key, tenant_id.as_bytes());
+        let masked = tag.as_ref()[..MASKED_TENANT_ID_LEN]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        Cow::Owned(masked)
+    }
+
+    /// Records `tenant_id` as the project of `span`.
+    pub fn record_project(&self, span: &Span, tenant_id: &str) {
+        span.record("project", self.mask(tenant_id).as_ref());
+    }
+}
+
+impl fmt::Debug for TenantIdMasker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantIdMasker")
+            .field("hashed", &self.key.is_some())
+            .finish()
+    }
+}
+
 /// The `RootSpanBuilder` implementation for the API service.
 ///
-/// It extracts the project ref from the `tenant_id` header and sets it as a field in the root span.
+/// It extracts the project ref from the `tenant_id` header and sets it as a field in the root span,
+/// masked by the [`TenantIdMasker`] of the app if there is one.
 /// It also emits info logs for request start and completion.
 #[derive(Debug)]
 pub struct ApiRootSpanBuilder;
@@ -24,7 +86,11 @@ impl RootSpanBuilder for ApiRootSpanBuilder {
                 // However, this is an edge case, as the project ref is generated by the system and
                 // should be valid UTF-8.
                 let project = String::from_utf8_lossy(project.as_bytes());
-                let project = project.as_ref();
+                let project = match request.app_data::<Data<TenantIdMasker>>() {
+                    Some(tenant_id_masker) => tenant_id_masker.mask(&project).into_owned(),
+                    None => project.into_owned(),
+                };
+                let project = project.as_str();
                 tracing_actix_web::root_span!(request, project = project)
             }
             None => tracing_actix_web::root_span!(request, project = tracing::field::Empty),
@@ -60,3 +126,99 @@ impl RootSpanBuilder for ApiRootSpanBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, test, web};
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing_actix_web::TracingLogger;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// Collects the logs written by a subscriber.
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl CapturedLogs {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    async fn read_sources() -> HttpResponse {
+        info!("reading sources");
+
+        HttpResponse::Ok().finish()
+    }
+
+    #[test]
+    fn hashed_tenant_ids_are_stable_within_a_process() {
+        let raw = TenantIdMasker::new(TenantIdLogging::Raw).unwrap();
+        assert_eq!(raw.mask("tenant_a"), "tenant_a");
+
+        let hashed = TenantIdMasker::new(TenantIdLogging::Hashed).unwrap();
+        let masked = hashed.mask("tenant_a");
+        assert_ne!(masked, "tenant_a");
+        assert_eq!(masked.len(), MASKED_TENANT_ID_LEN * 2);
+        assert_eq!(hashed.clone().mask("tenant_a"), masked);
+        assert_ne!(hashed.mask("tenant_b"), masked);
+
+        // Another process uses another salt.
+        let other = TenantIdMasker::new(TenantIdLogging::Hashed).unwrap();
+        assert_ne!(other.mask("tenant_a"), masked);
+    }
+
+    #[actix_web::test]
+    async fn hashed_tenant_ids_never_appear_in_request_logs() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let tenant_id = "sensitive_tenant_id";
+        let tenant_id_masker = TenantIdMasker::new(TenantIdLogging::Hashed).unwrap();
+        let masked = tenant_id_masker.mask(tenant_id).into_owned();
+        let app = test::init_service(
+            App::new()
+                .wrap(TracingLogger::<ApiRootSpanBuilder>::new())
+                .app_data(Data::new(tenant_id_masker))
+                .route("/sources", web::get().to(read_sources)),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/sources")
+            .insert_header(("tenant_id", tenant_id))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert!(response.status().is_success());
+
+        let logs = logs.contents();
+        assert!(logs.contains("HTTP request received"));
+        assert!(logs.contains("reading sources"));
+        assert!(logs.contains(&format!("project={masked}")));
+        assert!(!logs.contains(tenant_id));
+    }
+}
@@ -1,6 +1,6 @@
-use std::{net::TcpListener, sync::Arc};
+use std::{collections::HashMap, net::TcpListener, sync::Arc};
 
-use actix_web::{App, HttpServer, dev::Server, web};
+use actix_web::{App, HttpServer, dev::Server, middleware::from_fn, web};
 use actix_web_httpauth::middleware::HttpAuthentication;
 use aws_lc_rs::aead::{AES_256_GCM, RandomizedNonceKey};
 use base64::{Engine, prelude::BASE64_STANDARD};
@@ -12,12 +12,27 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
-    authentication::auth_validator,
-    config::ApiConfig,
-    db::publications::Publication,
-    encryption,
-    k8s_client::HttpK8sClient,
+    authentication::{admin_auth_validator, auth_validator},
+    config::{ApiConfig, EncryptionKey as EncryptionKeyConfig},
+    db::{
+        api_keys::ApiKeyScope,
+        publications::{Publication, PublicationTable},
+        tables::TableSampleMethod,
+    },
+    encryption::{self, Encryptor, FallbackEncryptor, KeyProvider, KeyRotation, TenantKeyProvider},
+    k8s_client::{HttpK8sClient, K8sClient},
+    rate_limit::{TenantRateLimiter, rate_limit_tenants},
     routes::{
+        admin::{
+            PurgeDeletedSourcesRequest, PurgeDeletedSourcesResponse, ReadTaskResponse,
+            ReadTasksResponse, ReencryptSourcesResponse, RunMaintenanceRequest,
+            RunMaintenanceResponse, TaskState, cancel_task, purge_deleted_sources, read_all_tasks,
+            reencrypt_sources, run_maintenance,
+        },
+        api_keys::{
+            CreateApiKeyRequest, CreateApiKeyResponse, ReadApiKeyResponse, ReadApiKeysResponse,
+            create_api_key, delete_api_key, read_all_api_keys,
+        },
         destinations::{
             CreateDestinationRequest, CreateDestinationResponse, ReadDestinationResponse,
             ReadDestinationsResponse, UpdateDestinationRequest, create_destination,
@@ -28,28 +43,46 @@ use crate::{
             UpdateDestinationPipelineRequest, create_destination_and_pipeline,
             update_destination_and_pipeline,
         },
-        health_check::health_check,
+        health_check::{
+            ReadinessCheck, ReadinessFailedResponse, ReadinessResponse, health_check, health_live,
+            health_ready,
+        },
         images::{
             CreateImageRequest, CreateImageResponse, ReadImageResponse, ReadImagesResponse,
             UpdateImageRequest, create_image, delete_image, read_all_images, read_image,
             update_image,
         },
+        metrics::metrics,
         pipelines::{
-            CreatePipelineRequest, CreatePipelineResponse, GetPipelineStatusResponse,
-            ReadPipelineResponse, ReadPipelinesResponse, UpdatePipelineImageRequest,
-            UpdatePipelineRequest, create_pipeline, delete_pipeline, get_pipeline_status,
-            read_all_pipelines, read_pipeline, start_pipeline, stop_all_pipelines, stop_pipeline,
-            update_pipeline, update_pipeline_image,
+            ColumnTypeMismatch, CreatePipelineRequest, CreatePipelineResponse,
+            GetPipelineReplicationStatusResponse, GetPipelineStatusResponse, ReadPipelineResponse,
+            ReadPipelinesResponse, ResyncTableRequest, StartPipelineRequest, TableCompatibility,
+            TableCopyStatus, TableResyncStatus, UpdatePipelineImageRequest, UpdatePipelineRequest,
+            VerifyDestinationRequest, VerifyDestinationResponse, create_pipeline, delete_pipeline,
+            get_pipeline_replication_status, get_pipeline_status, read_all_pipelines,
+            read_pipeline, resync_table, start_pipeline, stop_all_pipelines, stop_pipeline,
+            update_pipeline, update_pipeline_image, verify_destination,
         },
         sources::{
-            CreateSourceRequest, CreateSourceResponse, ReadSourceResponse, ReadSourcesResponse,
-            UpdateSourceRequest, create_source, delete_source,
+            CreateSourceRequest, CreateSourceResponse, CreateSourcesBatchRequest,
+            CreateSourcesBatchResponse, CreateSourcesBatchResult, PatchSourceRequest,
+            ReadSourceResponse, ReadSourcesResponse, ReplicationSlotStatus,
+            RotateSourceCredentialsRequest, RotateSourceCredentialsResponse, SourceAuditEntry,
+            SourceAuditLogResponse, SourceStatusResponse, UpdateSourceRequest, create_source,
+            create_sources_batch, delete_source, patch_source,
             publications::{
-                CreatePublicationRequest, UpdatePublicationRequest, create_publication,
-                delete_publication, read_all_publications, read_publication, update_publication,
+                CreatePublicationRequest, PublicationCoverage, PublicationTableCoverage,
+                ReadPublicationsResponse, TablePipelineState, UpdatePublicationRequest,
+                add_publication_table, create_publication, delete_publication,
+                drop_publication_table, read_all_publications, read_publication,
+                update_publication,
+            },
+            read_all_sources, read_source, read_source_audit_log, restore_source,
+            rotate_source_credentials, source_status,
+            tables::{
+                PreviewTableResponse, ReadColumnResponse, ReadTableResponse, ReadTablesResponse,
+                preview_table, read_table_names,
             },
-            read_all_sources, read_source,
-            tables::read_table_names,
             update_source,
         },
         tenants::{
@@ -62,7 +95,8 @@ use crate::{
             CreateTenantSourceRequest, CreateTenantSourceResponse, create_tenant_and_source,
         },
     },
-    span_builder::ApiRootSpanBuilder,
+    source_connections::SourceConnectionLimiter,
+    span_builder::{ApiRootSpanBuilder, TenantIdMasker},
 };
 
 pub struct Application {
@@ -78,15 +112,10 @@ impl Application {
         let listener = TcpListener::bind(address)?;
         let port = listener.local_addr()?.port();
 
-        let key_bytes = BASE64_STANDARD.decode(&config.encryption_key.key)?;
-        let key = RandomizedNonceKey::new(&AES_256_GCM, &key_bytes)?;
-        let encryption_key = encryption::EncryptionKey {
-            id: config.encryption_key.id,
-            key,
-        };
+        let key_provider = build_key_provider(&config)?;
 
         let k8s_client = match HttpK8sClient::new().await {
-            Ok(client) => Some(client),
+            Ok(client) => Some(Arc::new(client) as Arc<dyn K8sClient>),
             Err(e) => {
                 warn!(
                     "Failed to create Kubernetes client: {}. Running without Kubernetes support.",
@@ -96,14 +125,7 @@ impl Application {
             }
         };
 
-        let server = run(
-            config,
-            listener,
-            connection_pool,
-            encryption_key,
-            k8s_client,
-        )
-        .await?;
+        let server = run(config, listener, connection_pool, key_provider, k8s_client).await?;
 
         Ok(Self { port, server })
     }
@@ -129,25 +151,102 @@ pub fn get_connection_pool(config: &PgConnectionConfig) -> PgPool {
     PgPoolOptions::new().connect_lazy_with(config.with_db())
 }
 
-// HttpK8sClient is wrapped in an option because creating it
+/// Builds the [`KeyProvider`] resolving the encryption key of each tenant from the global and
+/// per-tenant keys of `config`.
+///
+/// While the global key is rotated, the values encrypted with the previous global key are still
+/// decrypted.
+pub fn build_key_provider(config: &ApiConfig) -> Result<Arc<dyn KeyProvider>, anyhow::Error> {
+    let mut global: Arc<dyn Encryptor> = Arc::new(decode_encryption_key(&config.encryption_key)?);
+    if let Some(previous_key) = &config.previous_encryption_key {
+        if previous_key.id == config.encryption_key.id {
+            anyhow::bail!(
+                "the previous encryption key has the same id as the global encryption key ({})",
+                previous_key.id
+            );
+        }
+
+        let previous: Arc<dyn Encryptor> = Arc::new(decode_encryption_key(previous_key)?);
+        global = Arc::new(FallbackEncryptor::new(global, previous));
+    }
+
+    let mut tenant_keys: HashMap<String, Arc<dyn Encryptor>> = HashMap::new();
+    for (tenant_id, tenant_key) in &config.tenant_encryption_keys {
+        if tenant_key.id == config.encryption_key.id {
+            anyhow::bail!(
+                "the encryption key of tenant {tenant_id} has the same id as the global encryption key ({})",
+                tenant_key.id
+            );
+        }
+        if let Some(previous_key) = &config.previous_encryption_key
+            && tenant_key.id == previous_key.id
+        {
+            anyhow::bail!(
+                "the encryption key of tenant {tenant_id} has the same id as the previous encryption key ({})",
+                tenant_key.id
+            );
+        }
+
+        tenant_keys.insert(
+            tenant_id.clone(),
+            Arc::new(decode_encryption_key(tenant_key)?),
+        );
+    }
+
+    Ok(Arc::new(TenantKeyProvider::new(global, tenant_keys)))
+}
+
+/// Builds the [`KeyRotation`] of the global key, if a previous global key is configured.
+fn build_key_rotation(config: &ApiConfig) -> Result<Option<KeyRotation>, anyhow::Error> {
+    let Some(previous_key) = &config.previous_encryption_key else {
+        return Ok(None);
+    };
+
+    Ok(Some(KeyRotation {
+        previous: decode_encryption_key(previous_key)?,
+        current: decode_encryption_key(&config.encryption_key)?,
+    }))
+}
+
+fn decode_encryption_key(
+    encryption_key: &EncryptionKeyConfig,
+) -> Result<encryption::EncryptionKey, anyhow::Error> {
+    let key_bytes = BASE64_STANDARD.decode(&encryption_key.key)?;
+    let key = RandomizedNonceKey::new(&AES_256_GCM, &key_bytes)?;
+
+    Ok(encryption::EncryptionKey {
+        id: encryption_key.id,
+        key,
+    })
+}
+
+// The K8s client is wrapped in an option because creating an HttpK8sClient
 // in tests involves setting a default CryptoProvider and it
 // interferes with parallel tasks because only one can be set.
 pub async fn run(
     config: ApiConfig,
     listener: TcpListener,
     connection_pool: PgPool,
-    encryption_key: encryption::EncryptionKey,
-    http_k8s_client: Option<HttpK8sClient>,
+    key_provider: Arc<dyn KeyProvider>,
+    k8s_client: Option<Arc<dyn K8sClient>>,
 ) -> Result<Server, anyhow::Error> {
+    let tenant_id_masker = web::Data::new(TenantIdMasker::new(config.tenant_id_logging)?);
+    let rate_limiter = web::Data::new(TenantRateLimiter::new(&config.rate_limit));
+    let source_connections =
+        web::Data::new(SourceConnectionLimiter::new(&config.source_connections));
+    let key_rotation = web::Data::new(build_key_rotation(&config)?);
     let config = web::Data::new(config);
     let connection_pool = web::Data::new(connection_pool);
-    let encryption_key = web::Data::new(encryption_key);
-    let k8s_client = http_k8s_client.map(|client| web::Data::new(Arc::new(client)));
+    let key_provider = web::Data::new(key_provider);
+    let k8s_client = k8s_client.map(web::Data::new);
 
     #[derive(OpenApi)]
     #[openapi(
         paths(
             crate::routes::health_check::health_check,
+            crate::routes::health_check::health_live,
+            crate::routes::health_check::health_ready,
+            crate::routes::metrics::metrics,
             crate::routes::images::create_image,
             crate::routes::images::read_image,
             crate::routes::images::update_image,
@@ -159,24 +258,39 @@ pub async fn run(
             crate::routes::pipelines::delete_pipeline,
             crate::routes::pipelines::read_all_pipelines,
             crate::routes::pipelines::get_pipeline_status,
+            crate::routes::pipelines::get_pipeline_replication_status,
+            crate::routes::pipelines::resync_table,
             crate::routes::pipelines::update_pipeline_image,
+            crate::routes::pipelines::verify_destination,
             crate::routes::tenants::create_tenant,
             crate::routes::tenants::create_or_update_tenant,
             crate::routes::tenants::read_tenant,
             crate::routes::tenants::update_tenant,
             crate::routes::tenants::delete_tenant,
             crate::routes::tenants::read_all_tenants,
+            crate::routes::api_keys::create_api_key,
+            crate::routes::api_keys::delete_api_key,
+            crate::routes::api_keys::read_all_api_keys,
             crate::routes::sources::create_source,
+            crate::routes::sources::create_sources_batch,
             crate::routes::sources::read_source,
             crate::routes::sources::update_source,
+            crate::routes::sources::patch_source,
             crate::routes::sources::delete_source,
+            crate::routes::sources::restore_source,
             crate::routes::sources::read_all_sources,
+            crate::routes::sources::rotate_source_credentials,
+            crate::routes::sources::source_status,
+            crate::routes::sources::read_source_audit_log,
             crate::routes::sources::publications::create_publication,
             crate::routes::sources::publications::read_publication,
             crate::routes::sources::publications::update_publication,
             crate::routes::sources::publications::delete_publication,
+            crate::routes::sources::publications::add_publication_table,
+            crate::routes::sources::publications::drop_publication_table,
             crate::routes::sources::publications::read_all_publications,
             crate::routes::sources::tables::read_table_names,
+            crate::routes::sources::tables::preview_table,
             crate::routes::destinations::create_destination,
             crate::routes::destinations::read_destination,
             crate::routes::destinations::update_destination,
@@ -185,8 +299,16 @@ pub async fn run(
             crate::routes::tenants_sources::create_tenant_and_source,
             crate::routes::destinations_pipelines::create_destination_and_pipeline,
             crate::routes::destinations_pipelines::update_destination_and_pipeline,
+            crate::routes::admin::read_all_tasks,
+            crate::routes::admin::cancel_task,
+            crate::routes::admin::run_maintenance,
+            crate::routes::admin::reencrypt_sources,
+            crate::routes::admin::purge_deleted_sources,
         ),
         components(schemas(
+            ReadinessResponse,
+            ReadinessFailedResponse,
+            ReadinessCheck,
             CreateImageRequest,
             CreateImageResponse,
             UpdateImageRequest,
@@ -198,7 +320,16 @@ pub async fn run(
             ReadPipelineResponse,
             ReadPipelinesResponse,
             UpdatePipelineImageRequest,
+            StartPipelineRequest,
             GetPipelineStatusResponse,
+            GetPipelineReplicationStatusResponse,
+            TableCopyStatus,
+            TableResyncStatus,
+            ResyncTableRequest,
+            VerifyDestinationRequest,
+            VerifyDestinationResponse,
+            TableCompatibility,
+            ColumnTypeMismatch,
             CreateTenantRequest,
             CreateTenantResponse,
             CreateOrUpdateTenantRequest,
@@ -206,14 +337,39 @@ pub async fn run(
             UpdateTenantRequest,
             ReadTenantResponse,
             ReadTenantsResponse,
+            CreateApiKeyRequest,
+            CreateApiKeyResponse,
+            ReadApiKeyResponse,
+            ReadApiKeysResponse,
+            ApiKeyScope,
             CreateSourceRequest,
             CreateSourceResponse,
+            CreateSourcesBatchRequest,
+            CreateSourcesBatchResult,
+            CreateSourcesBatchResponse,
             UpdateSourceRequest,
+            PatchSourceRequest,
             ReadSourceResponse,
             ReadSourcesResponse,
+            RotateSourceCredentialsRequest,
+            RotateSourceCredentialsResponse,
+            SourceStatusResponse,
+            ReplicationSlotStatus,
+            SourceAuditEntry,
+            SourceAuditLogResponse,
             CreatePublicationRequest,
             UpdatePublicationRequest,
             Publication,
+            PublicationTable,
+            ReadPublicationsResponse,
+            PublicationCoverage,
+            PublicationTableCoverage,
+            TablePipelineState,
+            ReadTablesResponse,
+            ReadTableResponse,
+            ReadColumnResponse,
+            PreviewTableResponse,
+            TableSampleMethod,
             CreateDestinationRequest,
             CreateDestinationResponse,
             UpdateDestinationRequest,
@@ -224,6 +380,14 @@ pub async fn run(
             CreateDestinationPipelineRequest,
             CreateDestinationPipelineResponse,
             UpdateDestinationPipelineRequest,
+            TaskState,
+            ReadTaskResponse,
+            ReadTasksResponse,
+            RunMaintenanceRequest,
+            RunMaintenanceResponse,
+            ReencryptSourcesResponse,
+            PurgeDeletedSourcesRequest,
+            PurgeDeletedSourcesResponse,
         ))
     )]
     struct ApiDoc;
@@ -235,6 +399,7 @@ pub async fn run(
     let server = HttpServer::new(move || {
         let tracing_logger = TracingLogger::<ApiRootSpanBuilder>::new();
         let authentication = HttpAuthentication::bearer(auth_validator);
+        let admin_authentication = HttpAuthentication::bearer(admin_auth_validator);
         let app = App::new()
             .wrap(
                 sentry::integrations::actix::Sentry::builder()
@@ -244,11 +409,17 @@ pub async fn run(
             )
             .wrap(tracing_logger)
             .service(health_check)
+            .service(health_live)
+            .service(health_ready)
+            .service(metrics)
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
             )
             .service(
                 web::scope("v1")
+                    // Registered before the authentication, so that it runs after it and only
+                    // counts authenticated requests.
+                    .wrap(from_fn(rate_limit_tenants))
                     .wrap(authentication)
                     //tenants
                     .service(create_tenant)
@@ -257,12 +428,24 @@ pub async fn run(
                     .service(update_tenant)
                     .service(delete_tenant)
                     .service(read_all_tenants)
+                    //api keys
+                    .service(create_api_key)
+                    .service(delete_api_key)
+                    .service(read_all_api_keys)
                     //sources
                     .service(create_source)
+                    // Registered before the `/sources/{source_id}` routes, which would otherwise
+                    // match it.
+                    .service(create_sources_batch)
                     .service(read_source)
                     .service(update_source)
+                    .service(patch_source)
                     .service(delete_source)
+                    .service(restore_source)
                     .service(read_all_sources)
+                    .service(rotate_source_credentials)
+                    .service(source_status)
+                    .service(read_source_audit_log)
                     //destinations
                     .service(create_destination)
                     .service(read_destination)
@@ -271,6 +454,9 @@ pub async fn run(
                     .service(read_all_destinations)
                     //pipelines
                     .service(create_pipeline)
+                    // Registered before the `/pipelines/{pipeline_id}` routes, which would
+                    // otherwise match it.
+                    .service(verify_destination)
                     .service(read_pipeline)
                     .service(update_pipeline)
                     .service(delete_pipeline)
@@ -279,14 +465,19 @@ pub async fn run(
                     .service(stop_pipeline)
                     .service(stop_all_pipelines)
                     .service(get_pipeline_status)
+                    .service(get_pipeline_replication_status)
+                    .service(resync_table)
                     .service(update_pipeline_image)
                     //tables
                     .service(read_table_names)
+                    .service(preview_table)
                     //publications
                     .service(create_publication)
                     .service(read_publication)
                     .service(update_publication)
                     .service(delete_publication)
+                    .service(add_publication_table)
+                    .service(drop_publication_table)
                     .service(read_all_publications)
                     //images
                     .service(create_image)
@@ -300,9 +491,26 @@ pub async fn run(
                     .service(create_destination_and_pipeline)
                     .service(update_destination_and_pipeline),
             )
+            .service(
+                web::scope("admin")
+                    .wrap(admin_authentication)
+                    //tasks
+                    .service(read_all_tasks)
+                    .service(cancel_task)
+                    //maintenance
+                    .service(run_maintenance)
+                    //encryption
+                    .service(reencrypt_sources)
+                    //sources
+                    .service(purge_deleted_sources),
+            )
             .app_data(config.clone())
             .app_data(connection_pool.clone())
-            .app_data(encryption_key.clone());
+            .app_data(key_provider.clone())
+            .app_data(key_rotation.clone())
+            .app_data(tenant_id_masker.clone())
+            .app_data(rate_limiter.clone())
+            .app_data(source_connections.clone());
 
         if let Some(k8s_client) = k8s_client.clone() {
             app.app_data(k8s_client.clone())
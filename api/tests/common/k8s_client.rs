@@ -0,0 +1,150 @@
+use api::k8s_client::{ContainerError, K8sClient, K8sError, PodPhase};
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::ConfigMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A [`K8sClient`] keeping the objects of the replicators in memory instead of creating them in a
+/// cluster.
+///
+/// The objects are named `<kind>/<prefix>`, e.g. `stateful-set/<prefix>`, and the pod of a
+/// replicator is running as long as its stateful set exists.
+#[derive(Debug, Default)]
+pub struct MockK8sClient {
+    objects: Mutex<BTreeSet<String>>,
+}
+
+impl MockK8sClient {
+    /// Returns the names of the objects which currently exist.
+    pub fn objects(&self) -> BTreeSet<String> {
+        self.objects.lock().unwrap().clone()
+    }
+
+    fn insert(&self, kind: &str, prefix: &str) {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(format!("{kind}/{prefix}"));
+    }
+
+    fn remove(&self, kind: &str, prefix: &str) {
+        self.objects
+            .lock()
+            .unwrap()
+            .remove(&format!("{kind}/{prefix}"));
+    }
+
+    fn contains(&self, kind: &str, prefix: &str) -> bool {
+        self.objects
+            .lock()
+            .unwrap()
+            .contains(&format!("{kind}/{prefix}"))
+    }
+}
+
+#[async_trait]
+impl K8sClient for MockK8sClient {
+    async fn create_or_update_postgres_secret(
+        &self,
+        prefix: &str,
+        _postgres_password: &str,
+    ) -> Result<(), K8sError> {
+        self.insert("postgres-secret", prefix);
+        Ok(())
+    }
+
+    async fn create_or_update_bq_secret(
+        &self,
+        prefix: &str,
+        _bq_service_account_key: &str,
+    ) -> Result<(), K8sError> {
+        self.insert("bq-secret", prefix);
+        Ok(())
+    }
+
+    async fn delete_postgres_secret(&self, prefix: &str) -> Result<(), K8sError> {
+        self.remove("postgres-secret", prefix);
+        Ok(())
+    }
+
+    async fn delete_bq_secret(&self, prefix: &str) -> Result<(), K8sError> {
+        self.remove("bq-secret", prefix);
+        Ok(())
+    }
+
+    async fn get_config_map(&self, _config_map_name: &str) -> Result<ConfigMap, K8sError> {
+        // Only the trusted root certificates are read, which are not used by the tests.
+        Ok(ConfigMap {
+            data: Some(BTreeMap::from([(
+                "trusted_root_certs".to_string(),
+                String::new(),
+            )])),
+            ..ConfigMap::default()
+        })
+    }
+
+    async fn create_or_update_config_map(
+        &self,
+        prefix: &str,
+        _base_config: &str,
+        _prod_config: &str,
+    ) -> Result<(), K8sError> {
+        self.insert("config-map", prefix);
+        Ok(())
+    }
+
+    async fn delete_config_map(&self, prefix: &str) -> Result<(), K8sError> {
+        self.remove("config-map", prefix);
+        Ok(())
+    }
+
+    async fn create_or_update_stateful_set(
+        &self,
+        prefix: &str,
+        _replicator_image: &str,
+    ) -> Result<(), K8sError> {
+        self.insert("stateful-set", prefix);
+        Ok(())
+    }
+
+    async fn delete_stateful_set(&self, prefix: &str) -> Result<(), K8sError> {
+        self.remove("stateful-set", prefix);
+        Ok(())
+    }
+
+    async fn get_pod_phase(&self, prefix: &str) -> Result<PodPhase, K8sError> {
+        // Like a cluster, a missing pod is reported as succeeded.
+        if self.contains("stateful-set", prefix) {
+            Ok(PodPhase::Running)
+        } else {
+            Ok(PodPhase::Succeeded)
+        }
+    }
+
+    async fn get_pod_uptime(&self, prefix: &str) -> Result<Option<Duration>, K8sError> {
+        Ok(self
+            .contains("stateful-set", prefix)
+            .then_some(Duration::from_secs(60)))
+    }
+
+    async fn get_replicator_container_error(
+        &self,
+        _prefix: &str,
+    ) -> Result<Option<ContainerError>, K8sError> {
+        Ok(None)
+    }
+
+    async fn get_container_logs(
+        &self,
+        _pod_name: &str,
+        _container_name: &str,
+        _previous: bool,
+    ) -> Result<String, K8sError> {
+        Ok(String::new())
+    }
+
+    async fn delete_pod(&self, _prefix: &str) -> Result<(), K8sError> {
+        Ok(())
+    }
+}
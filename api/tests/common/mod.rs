@@ -13,6 +13,10 @@
 //!   - Handle database migrations
 //!   - Provide connection pools for tests
 //!
+//! - `k8s_client`: A K8s client keeping the objects of the replicators in memory, so that tests
+//!   can check which objects a request created or deleted
+//!
 //! These utilities help maintain consistency across tests and reduce code duplication.
 pub mod database;
+pub mod k8s_client;
 pub mod test_app;
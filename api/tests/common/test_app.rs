@@ -1,25 +1,36 @@
 use crate::common::database::create_etl_api_database;
+use api::db::publications::PublicationTable;
+use api::routes::admin::{PurgeDeletedSourcesRequest, RunMaintenanceRequest};
+use api::routes::api_keys::CreateApiKeyRequest;
 use api::routes::destinations::{CreateDestinationRequest, UpdateDestinationRequest};
 use api::routes::destinations_pipelines::{
     CreateDestinationPipelineRequest, UpdateDestinationPipelineRequest,
 };
 use api::routes::images::{CreateImageRequest, UpdateImageRequest};
 use api::routes::pipelines::{
-    CreatePipelineRequest, UpdatePipelineImageRequest, UpdatePipelineRequest,
+    CreatePipelineRequest, ResyncTableRequest, UpdatePipelineImageRequest, UpdatePipelineRequest,
+    VerifyDestinationRequest,
+};
+use api::routes::sources::publications::CreatePublicationRequest;
+use api::routes::sources::tables::{PreviewTableQuery, ReadTablesQuery};
+use api::routes::sources::{
+    CreateSourceRequest, CreateSourcesBatchRequest, PatchSourceRequest,
+    RotateSourceCredentialsRequest, UpdateSourceRequest,
 };
-use api::routes::sources::{CreateSourceRequest, UpdateSourceRequest};
 use api::routes::tenants::{CreateOrUpdateTenantRequest, CreateTenantRequest, UpdateTenantRequest};
 use api::routes::tenants_sources::CreateTenantSourceRequest;
 use api::{
     config::ApiConfig,
-    encryption::{self, generate_random_key},
-    startup::run,
+    k8s_client::K8sClient,
+    startup::{build_key_provider, run},
 };
+use config::shared::PgConnectionConfig;
 use config::{Environment, load_config};
 use postgres::sqlx::test_utils::drop_pg_database;
 use reqwest::{IntoUrl, RequestBuilder};
 use std::io;
 use std::net::TcpListener;
+use std::sync::Arc;
 use tokio::runtime::Handle;
 use uuid::Uuid;
 
@@ -27,6 +38,7 @@ pub struct TestApp {
     pub address: String,
     pub api_client: reqwest::Client,
     pub api_key: String,
+    pub admin_api_key: String,
     config: ApiConfig,
     server_handle: tokio::task::JoinHandle<io::Result<()>>,
 }
@@ -46,6 +58,12 @@ impl Drop for TestApp {
 }
 
 impl TestApp {
+    /// Returns the config of the database backing the api, which tests can also use as a
+    /// reachable source database.
+    pub fn database_config(&self) -> &PgConnectionConfig {
+        &self.config.database
+    }
+
     fn get_authenticated<U: IntoUrl>(&self, url: U) -> RequestBuilder {
         self.api_client.get(url).bearer_auth(self.api_key.clone())
     }
@@ -54,6 +72,10 @@ impl TestApp {
         self.api_client.post(url).bearer_auth(self.api_key.clone())
     }
 
+    fn patch_authenticated<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.api_client.patch(url).bearer_auth(self.api_key.clone())
+    }
+
     fn put_authenticated<U: IntoUrl>(&self, url: U) -> RequestBuilder {
         self.api_client.put(url).bearer_auth(self.api_key.clone())
     }
@@ -128,6 +150,35 @@ impl TestApp {
             .expect("failed to execute request")
     }
 
+    pub async fn create_api_key(
+        &self,
+        tenant_id: &str,
+        api_key: &CreateApiKeyRequest,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!("{}/v1/api-keys", &self.address))
+            .header("tenant_id", tenant_id)
+            .json(api_key)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn read_all_api_keys(&self, tenant_id: &str) -> reqwest::Response {
+        self.get_authenticated(format!("{}/v1/api-keys", &self.address))
+            .header("tenant_id", tenant_id)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn delete_api_key(&self, tenant_id: &str, api_key_id: i64) -> reqwest::Response {
+        self.delete_authenticated(format!("{}/v1/api-keys/{api_key_id}", &self.address))
+            .header("tenant_id", tenant_id)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
     pub async fn create_source(
         &self,
         tenant_id: &str,
@@ -141,6 +192,20 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    pub async fn create_validated_source(
+        &self,
+        tenant_id: &str,
+        source: &CreateSourceRequest,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!("{}/v1/sources", &self.address))
+            .header("tenant_id", tenant_id)
+            .query(&[("validate", "true")])
+            .json(source)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     pub async fn read_source(&self, tenant_id: &str, source_id: i64) -> reqwest::Response {
         self.get_authenticated(format!("{}/v1/sources/{source_id}", &self.address))
             .header("tenant_id", tenant_id)
@@ -149,6 +214,20 @@ impl TestApp {
             .expect("failed to execute request")
     }
 
+    pub async fn read_source_with_accept(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        accept: &str,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!("{}/v1/sources/{source_id}", &self.address))
+            .header("tenant_id", tenant_id)
+            .header("Accept", accept)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
     pub async fn update_source(
         &self,
         tenant_id: &str,
@@ -163,6 +242,57 @@ impl TestApp {
             .expect("failed to execute request")
     }
 
+    pub async fn patch_source(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        patch: &PatchSourceRequest,
+    ) -> reqwest::Response {
+        self.patch_authenticated(format!("{}/v1/sources/{source_id}", &self.address))
+            .header("tenant_id", tenant_id)
+            .json(patch)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn rotate_source_credentials(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        credentials: &RotateSourceCredentialsRequest,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!(
+            "{}/v1/sources/{source_id}/rotate-credentials",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .json(credentials)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn source_status(&self, tenant_id: &str, source_id: i64) -> reqwest::Response {
+        self.get_authenticated(format!("{}/v1/sources/{source_id}/status", &self.address))
+            .header("tenant_id", tenant_id)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn read_source_audit_log(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!("{}/v1/sources/{source_id}/audit", &self.address))
+            .header("tenant_id", tenant_id)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
     pub async fn delete_source(&self, tenant_id: &str, source_id: i64) -> reqwest::Response {
         self.delete_authenticated(format!("{}/v1/sources/{source_id}", &self.address))
             .header("tenant_id", tenant_id)
@@ -171,6 +301,42 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    pub async fn create_source_with_idempotency_key(
+        &self,
+        tenant_id: &str,
+        source: &CreateSourceRequest,
+        idempotency_key: &str,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!("{}/v1/sources", &self.address))
+            .header("tenant_id", tenant_id)
+            .header("Idempotency-Key", idempotency_key)
+            .json(source)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn create_sources_batch(
+        &self,
+        tenant_id: &str,
+        batch: &CreateSourcesBatchRequest,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!("{}/v1/sources/batch", &self.address))
+            .header("tenant_id", tenant_id)
+            .json(batch)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn restore_source(&self, tenant_id: &str, source_id: i64) -> reqwest::Response {
+        self.post_authenticated(format!("{}/v1/sources/{source_id}/restore", &self.address))
+            .header("tenant_id", tenant_id)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
     pub async fn read_all_sources(&self, tenant_id: &str) -> reqwest::Response {
         self.get_authenticated(format!("{}/v1/sources", &self.address))
             .header("tenant_id", tenant_id)
@@ -179,6 +345,164 @@ impl TestApp {
             .expect("failed to execute request")
     }
 
+    pub async fn read_all_sources_with_accept(
+        &self,
+        tenant_id: &str,
+        accept: &str,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!("{}/v1/sources", &self.address))
+            .header("tenant_id", tenant_id)
+            .header("Accept", accept)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn read_all_sources_with_tag(&self, tenant_id: &str, tag: &str) -> reqwest::Response {
+        self.get_authenticated(format!("{}/v1/sources", &self.address))
+            .header("tenant_id", tenant_id)
+            .query(&[("tag", tag)])
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn read_sources_page(
+        &self,
+        tenant_id: &str,
+        limit: i64,
+        after: Option<i64>,
+    ) -> reqwest::Response {
+        let mut query = vec![("limit", limit)];
+        if let Some(after) = after {
+            query.push(("after", after));
+        }
+
+        self.get_authenticated(format!("{}/v1/sources", &self.address))
+            .header("tenant_id", tenant_id)
+            .query(&query)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn create_publication(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        publication: &CreatePublicationRequest,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!(
+            "{}/v1/sources/{source_id}/publications",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .json(publication)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn read_publication(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        publication_name: &str,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!(
+            "{}/v1/sources/{source_id}/publications/{publication_name}",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn add_publication_table(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        publication_name: &str,
+        table: &PublicationTable,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!(
+            "{}/v1/sources/{source_id}/publications/{publication_name}/tables",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .json(table)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn drop_publication_table(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        publication_name: &str,
+        schema: &str,
+        name: &str,
+    ) -> reqwest::Response {
+        self.delete_authenticated(format!(
+            "{}/v1/sources/{source_id}/publications/{publication_name}/tables/{schema}/{name}",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn read_all_publications(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!(
+            "{}/v1/sources/{source_id}/publications",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn read_tables(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        query: &ReadTablesQuery,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!("{}/v1/sources/{source_id}/tables", &self.address))
+            .header("tenant_id", tenant_id)
+            .query(query)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn preview_table(
+        &self,
+        tenant_id: &str,
+        source_id: i64,
+        schema: &str,
+        name: &str,
+        query: &PreviewTableQuery,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!(
+            "{}/v1/sources/{source_id}/tables/{schema}/{name}/preview",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .query(query)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
     pub async fn create_destination(
         &self,
         tenant_id: &str,
@@ -290,6 +614,14 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    pub async fn start_pipeline(&self, tenant_id: &str, pipeline_id: i64) -> reqwest::Response {
+        self.post_authenticated(format!("{}/v1/pipelines/{pipeline_id}/start", &self.address))
+            .header("tenant_id", tenant_id)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
     pub async fn read_all_pipelines(&self, tenant_id: &str) -> reqwest::Response {
         self.get_authenticated(format!("{}/v1/pipelines", &self.address))
             .header("tenant_id", tenant_id)
@@ -298,6 +630,51 @@ impl TestApp {
             .expect("failed to execute request")
     }
 
+    pub async fn read_pipeline_replication_status(
+        &self,
+        tenant_id: &str,
+        pipeline_id: i64,
+    ) -> reqwest::Response {
+        self.get_authenticated(format!(
+            "{}/v1/pipelines/{pipeline_id}/replication-status",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn resync_table(
+        &self,
+        tenant_id: &str,
+        pipeline_id: i64,
+        resync_request: &ResyncTableRequest,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!(
+            "{}/v1/pipelines/{pipeline_id}/resync-table",
+            &self.address
+        ))
+        .header("tenant_id", tenant_id)
+        .json(resync_request)
+        .send()
+        .await
+        .expect("failed to execute request")
+    }
+
+    pub async fn verify_destination(
+        &self,
+        tenant_id: &str,
+        verify_request: &VerifyDestinationRequest,
+    ) -> reqwest::Response {
+        self.post_authenticated(format!("{}/v1/pipelines/verify-destination", &self.address))
+            .header("tenant_id", tenant_id)
+            .json(verify_request)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
     pub async fn create_destination_pipeline(
         &self,
         tenant_id: &str,
@@ -370,6 +747,64 @@ impl TestApp {
             .expect("failed to execute request")
     }
 
+    pub async fn read_all_tasks(&self, api_key: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/admin/tasks", &self.address))
+            .bearer_auth(api_key)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn cancel_task(&self, task_id: i64, api_key: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/tasks/{task_id}/cancel", &self.address))
+            .bearer_auth(api_key)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn reencrypt_sources(&self, api_key: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/admin/encryption/reencrypt-sources",
+                &self.address
+            ))
+            .bearer_auth(api_key)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn purge_deleted_sources(
+        &self,
+        purge: &PurgeDeletedSourcesRequest,
+        api_key: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/sources/purge-deleted", &self.address))
+            .bearer_auth(api_key)
+            .json(purge)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
+    pub async fn run_maintenance(
+        &self,
+        maintenance: &RunMaintenanceRequest,
+        api_key: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/maintenance", &self.address))
+            .bearer_auth(api_key)
+            .json(maintenance)
+            .send()
+            .await
+            .expect("failed to execute request")
+    }
+
     pub async fn update_pipeline_image(
         &self,
         tenant_id: &str,
@@ -389,6 +824,24 @@ impl TestApp {
 }
 
 pub async fn spawn_test_app() -> TestApp {
+    spawn_test_app_with_config(|_| {}).await
+}
+
+/// Spawns a test app whose config is changed by `configure` before the app starts, including the
+/// encryption keys that the app uses.
+pub async fn spawn_test_app_with_config(configure: impl FnOnce(&mut ApiConfig)) -> TestApp {
+    spawn_test_app_with(configure, None).await
+}
+
+/// Spawns a test app which manages the replicators of pipelines with `k8s_client`.
+pub async fn spawn_test_app_with_k8s_client(k8s_client: Arc<dyn K8sClient>) -> TestApp {
+    spawn_test_app_with(|_| {}, Some(k8s_client)).await
+}
+
+async fn spawn_test_app_with(
+    configure: impl FnOnce(&mut ApiConfig),
+    k8s_client: Option<Arc<dyn K8sClient>>,
+) -> TestApp {
     // We set the environment to dev.
     Environment::Dev.set();
 
@@ -400,19 +853,21 @@ pub async fn spawn_test_app() -> TestApp {
     let mut config = load_config::<ApiConfig>().expect("Failed to read configuration");
     // We use a random database name.
     config.database.name = Uuid::new_v4().to_string();
+    configure(&mut config);
 
     let connection_pool = create_etl_api_database(&config.database).await;
 
-    let key = generate_random_key::<32>().expect("failed to generate random key");
-    let encryption_key = encryption::EncryptionKey { id: 0, key };
+    let key_provider = build_key_provider(&config).expect("failed to build the key provider");
     let api_key = "XOUbHmWbt9h7nWl15wWwyWQnctmFGNjpawMc3lT5CFs=".to_string();
+    let admin_api_key = "MtEGpmX33mHZ8UWz7Uuq+k1A6M4sMQdZyaOg7FZCHrs=".to_string();
+    config.admin_api_key = Some(admin_api_key.clone());
 
     let server = run(
         config.clone(),
         listener,
         connection_pool,
-        encryption_key,
-        None,
+        key_provider,
+        k8s_client,
     )
     .await
     .expect("failed to bind address");
@@ -423,6 +878,7 @@ pub async fn spawn_test_app() -> TestApp {
         address: format!("http://{base_address}:{port}"),
         api_client: reqwest::Client::new(),
         api_key,
+        admin_api_key,
         config,
         server_handle,
     }
@@ -0,0 +1,327 @@
+use api::config::EncryptionKey as EncryptionKeyConfig;
+use api::db::sources::{SourceConfig, SourceTags};
+use api::encryption::EncryptionKey;
+use api::routes::admin::{
+    PurgeDeletedSourcesRequest, PurgeDeletedSourcesResponse, ReadTasksResponse,
+    ReencryptSourcesResponse, RunMaintenanceRequest, RunMaintenanceResponse,
+};
+use aws_lc_rs::aead::{AES_256_GCM, RandomizedNonceKey};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use config::shared::IntoConnectOptions;
+use reqwest::StatusCode;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{Connection, PgConnection};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use telemetry::init_test_tracing;
+
+use crate::common::k8s_client::MockK8sClient;
+use crate::common::test_app::{
+    spawn_test_app, spawn_test_app_with_config, spawn_test_app_with_k8s_client,
+};
+use crate::integration::destination_test::create_destination;
+use crate::integration::pipelines_test::{create_pipeline_with_config, new_pipeline_config};
+use crate::integration::sources_test::create_source;
+use crate::integration::tenants_test::create_tenant;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tasks_cannot_be_read_with_tenant_api_key() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app.read_all_tasks(&app.api_key).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn task_cannot_be_cancelled_with_tenant_api_key() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app.cancel_task(42, &app.api_key).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelled_task_has_its_replicator_deleted() {
+    init_test_tracing();
+    // Arrange
+    let k8s_client = Arc::new(MockK8sClient::default());
+    let app = spawn_test_app_with_k8s_client(k8s_client.clone()).await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+    let destination_id = create_destination(&app, tenant_id).await;
+    let pipeline_id = create_pipeline_with_config(
+        &app,
+        tenant_id,
+        source_id,
+        destination_id,
+        new_pipeline_config(),
+    )
+    .await;
+    let response = app.start_pipeline(tenant_id, pipeline_id).await;
+    assert!(response.status().is_success());
+
+    let response: ReadTasksResponse = app
+        .read_all_tasks(&app.admin_api_key)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.tasks.len(), 1);
+    let task = &response.tasks[0];
+    assert_eq!(task.pipeline_id, pipeline_id);
+    let prefix = format!("{tenant_id}-{}", task.id);
+    let objects = ["postgres-secret", "bq-secret", "config-map", "stateful-set"]
+        .map(|kind| format!("{kind}/{prefix}"));
+    assert_eq!(k8s_client.objects(), BTreeSet::from(objects));
+
+    // Act
+    let response = app.cancel_task(task.id, &app.admin_api_key).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    assert!(k8s_client.objects().is_empty());
+    let response: ReadTasksResponse = app
+        .read_all_tasks(&app.admin_api_key)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(response.tasks.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_api_key_cannot_access_tenant_endpoints() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(format!("{}/v1/images", &app.address))
+        .bearer_auth(&app.admin_api_key)
+        .send()
+        .await
+        .expect("failed to execute request");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn maintenance_can_be_run_on_control_plane_tables() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    for vacuum in [false, true] {
+        // Act
+        let response = app
+            .run_maintenance(&RunMaintenanceRequest { vacuum }, &app.admin_api_key)
+            .await;
+
+        // Assert
+        assert!(response.status().is_success());
+        let response: RunMaintenanceResponse = response
+            .json()
+            .await
+            .expect("failed to deserialize response");
+        assert!(response.tables.contains(&"app.pipelines".to_string()));
+        assert!(response.tables.contains(&"app.sources".to_string()));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn maintenance_cannot_be_run_with_tenant_api_key() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app
+        .run_maintenance(&RunMaintenanceRequest::default(), &app.api_key)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+fn decode_encryption_key(config: &EncryptionKeyConfig) -> EncryptionKey {
+    let key_bytes = BASE64_STANDARD
+        .decode(&config.key)
+        .expect("failed to decode the encryption key");
+
+    EncryptionKey {
+        id: config.id,
+        key: RandomizedNonceKey::new(&AES_256_GCM, &key_bytes).expect("invalid encryption key"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_of_the_previous_key_are_reencrypted() {
+    init_test_tracing();
+    // Arrange
+    let mut previous_key = None;
+    let app = spawn_test_app_with_config(|config| {
+        previous_key = Some(config.encryption_key.clone());
+        config.previous_encryption_key = Some(config.encryption_key.clone());
+        config.encryption_key = EncryptionKeyConfig {
+            id: config.encryption_key.id + 1,
+            key: "piaZLYq38uTyBkIdPrFogZOW7T9oQg/76eF9mVObZMM=".to_string(),
+        };
+    })
+    .await;
+    let previous_key = decode_encryption_key(&previous_key.unwrap());
+    let tenant_id = &create_tenant(&app).await;
+
+    // The source is stored as it was before the rotation, encrypted with the previous key.
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+    let database = app.database_config();
+    let source_config = SourceConfig {
+        host: database.host.clone(),
+        port: database.port,
+        name: database.name.clone(),
+        username: database.username.clone(),
+        password: database.password.clone(),
+        ssl_mode: None,
+        root_cert: None,
+    };
+    let source_id = api::db::sources::create_source(
+        &mut connection,
+        tenant_id,
+        "Postgres Source",
+        source_config,
+        &SourceTags::new(),
+        &previous_key,
+    )
+    .await
+    .expect("failed to create the source");
+
+    // Sources of the previous key can still be read during the rotation.
+    let response = app.read_source(tenant_id, source_id).await;
+    assert!(response.status().is_success());
+
+    // Act
+    let response = app.reencrypt_sources(&app.admin_api_key).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: ReencryptSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.reencrypted_sources, 1);
+
+    let key_id: Option<i64> = sqlx::query_scalar("select key_id from app.sources where id = $1")
+        .bind(source_id)
+        .fetch_one(&mut connection)
+        .await
+        .expect("failed to read the key id of the source");
+    assert_eq!(key_id, Some(i64::from(previous_key.id) + 1));
+
+    let response = app.read_source(tenant_id, source_id).await;
+    assert!(response.status().is_success());
+
+    // Sources which were already re-encrypted are left untouched.
+    let response = app.reencrypt_sources(&app.admin_api_key).await;
+    let response: ReencryptSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.reencrypted_sources, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_cannot_be_reencrypted_without_a_previous_key() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app.reencrypt_sources(&app.admin_api_key).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_cannot_be_reencrypted_with_tenant_api_key() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app.reencrypt_sources(&app.api_key).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deleted_sources_are_purged_after_the_grace_period() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let deleted_source_id = create_source(&app, tenant_id).await;
+    let source_id = create_source(&app, tenant_id).await;
+    app.delete_source(tenant_id, deleted_source_id).await;
+
+    // Act
+    let response = app
+        .purge_deleted_sources(&PurgeDeletedSourcesRequest::default(), &app.admin_api_key)
+        .await;
+
+    // Assert
+    // The source was deleted less than the default grace period ago.
+    let response: PurgeDeletedSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.purged_sources, 0);
+
+    // Act
+    let purge = PurgeDeletedSourcesRequest {
+        grace_period_secs: 0,
+    };
+    let response = app.purge_deleted_sources(&purge, &app.admin_api_key).await;
+
+    // Assert
+    let response: PurgeDeletedSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.purged_sources, 1);
+    let response = app.restore_source(tenant_id, deleted_source_id).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = app.read_source(tenant_id, source_id).await;
+    assert!(response.status().is_success());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deleted_sources_cannot_be_purged_with_tenant_api_key() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app
+        .purge_deleted_sources(&PurgeDeletedSourcesRequest::default(), &app.api_key)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,235 @@
+use api::db::api_keys::ApiKeyScope;
+use api::db::sources::SourceTags;
+use api::routes::api_keys::{CreateApiKeyRequest, CreateApiKeyResponse, ReadApiKeysResponse};
+use api::routes::sources::{CreateSourceRequest, ReadSourcesResponse};
+use reqwest::StatusCode;
+use telemetry::init_test_tracing;
+
+use crate::{
+    common::test_app::{TestApp, spawn_test_app},
+    integration::sources_test::{create_source, new_name, new_source_config},
+    integration::tenants_test::{create_tenant, create_tenant_with_id_and_name},
+};
+
+async fn create_api_key(app: &TestApp, tenant_id: &str, scope: ApiKeyScope) -> String {
+    let api_key = CreateApiKeyRequest {
+        name: "Automation".to_string(),
+        scope,
+    };
+    let response = app.create_api_key(tenant_id, &api_key).await;
+    assert!(response.status().is_success());
+    let response: CreateApiKeyResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    response.key
+}
+
+async fn read_all_sources_with_key(app: &TestApp, api_key: &str) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/v1/sources", &app.address))
+        .bearer_auth(api_key)
+        .send()
+        .await
+        .expect("failed to execute request")
+}
+
+async fn create_source_with_key(
+    app: &TestApp,
+    api_key: &str,
+    tenant_id: &str,
+) -> reqwest::Response {
+    let source = CreateSourceRequest {
+        name: new_name(),
+        config: new_source_config(),
+        tags: SourceTags::new(),
+    };
+    app.api_client
+        .post(format!("{}/v1/sources", &app.address))
+        .bearer_auth(api_key)
+        .header("tenant_id", tenant_id)
+        .json(&source)
+        .send()
+        .await
+        .expect("failed to execute request")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_key_with_read_scope_can_read_but_not_write() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+    let api_key = create_api_key(&app, tenant_id, ApiKeyScope::Read).await;
+
+    // Act
+    let read_response = read_all_sources_with_key(&app, &api_key).await;
+    let create_response = create_source_with_key(&app, &api_key, tenant_id).await;
+
+    // Assert
+    assert!(read_response.status().is_success());
+    let response: ReadSourcesResponse = read_response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.sources.len(), 1);
+    assert_eq!(response.sources[0].id, source_id);
+
+    assert_eq!(create_response.status(), StatusCode::FORBIDDEN);
+    let response: serde_json::Value = create_response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response["code"], "read_only_api_key");
+    assert_eq!(
+        response["error"].as_str().unwrap(),
+        "The api key only has the read scope, which doesn't allow POST requests"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_key_with_read_write_scope_only_writes_to_its_tenant() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let other_tenant_id = &create_tenant_with_id_and_name(
+        &app,
+        "zyxwvutsrqponmlkjihg".to_string(),
+        "OtherTenant".to_string(),
+    )
+    .await;
+    let api_key = create_api_key(&app, tenant_id, ApiKeyScope::ReadWrite).await;
+
+    // Act
+    let response = create_source_with_key(&app, &api_key, other_tenant_id).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: ReadSourcesResponse = app
+        .read_all_sources(tenant_id)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.sources.len(), 1);
+    let response: ReadSourcesResponse = app
+        .read_all_sources(other_tenant_id)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(response.sources.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_key_cannot_manage_tenants_or_api_keys() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let api_key = create_api_key(&app, tenant_id, ApiKeyScope::ReadWrite).await;
+
+    // Act
+    let tenants_response = app
+        .api_client
+        .get(format!("{}/v1/tenants", &app.address))
+        .bearer_auth(&api_key)
+        .send()
+        .await
+        .expect("failed to execute request");
+    let api_keys_response = app
+        .api_client
+        .post(format!("{}/v1/api-keys", &app.address))
+        .bearer_auth(&api_key)
+        .json(&CreateApiKeyRequest {
+            name: "Escalation".to_string(),
+            scope: ApiKeyScope::ReadWrite,
+        })
+        .send()
+        .await
+        .expect("failed to execute request");
+
+    // Assert
+    assert_eq!(tenants_response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(api_keys_response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_key_cannot_manage_tenants_or_api_keys_with_percent_encoded_paths() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let api_key = create_api_key(&app, tenant_id, ApiKeyScope::ReadWrite).await;
+
+    // Act
+    // The paths are routed to `/v1/tenants` and `/v1/api-keys` once decoded.
+    let tenants_response = app
+        .api_client
+        .get(format!("{}/v1/%74enants", &app.address))
+        .bearer_auth(&api_key)
+        .send()
+        .await
+        .expect("failed to execute request");
+    let api_keys_response = app
+        .api_client
+        .post(format!("{}/v1/%61pi-keys", &app.address))
+        .bearer_auth(&api_key)
+        .json(&CreateApiKeyRequest {
+            name: "Escalation".to_string(),
+            scope: ApiKeyScope::ReadWrite,
+        })
+        .send()
+        .await
+        .expect("failed to execute request");
+
+    // Assert
+    for response in [tenants_response, api_keys_response] {
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response: serde_json::Value = response
+            .json()
+            .await
+            .expect("failed to deserialize response");
+        assert_eq!(response["code"], "deployment_api_key_required");
+    }
+    let response: ReadApiKeysResponse = app
+        .read_all_api_keys(tenant_id)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.api_keys.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_keys_are_listed_without_the_key_and_rejected_once_deleted() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let api_key = create_api_key(&app, tenant_id, ApiKeyScope::Read).await;
+
+    // Act
+    let response = app.read_all_api_keys(tenant_id).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let body = response.text().await.expect("failed to read response");
+    assert!(!body.contains(&api_key));
+    let response: ReadApiKeysResponse =
+        serde_json::from_str(&body).expect("failed to deserialize response");
+    assert_eq!(response.api_keys.len(), 1);
+    assert_eq!(response.api_keys[0].tenant_id, *tenant_id);
+    assert_eq!(response.api_keys[0].name, "Automation");
+    assert_eq!(response.api_keys[0].scope, ApiKeyScope::Read);
+
+    // Act
+    let response = app.delete_api_key(tenant_id, response.api_keys[0].id).await;
+    assert!(response.status().is_success());
+    let response = read_all_sources_with_key(&app, &api_key).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -22,6 +22,11 @@ pub fn new_destination_config() -> DestinationConfig {
         dataset_id: "dataset-id".to_string(),
         service_account_key: SerializableSecretString::from("service-account-key".to_string()),
         max_staleness_mins: None,
+        column_filter: None,
+        max_concurrent_writes: None,
+        identifier_overflow: None,
+        row_size_limit: None,
+        schema_mapping: None,
     }
 }
 
@@ -37,6 +42,11 @@ pub fn updated_destination_config() -> DestinationConfig {
             "service-account-key-updated".to_string(),
         ),
         max_staleness_mins: Some(10),
+        column_filter: None,
+        max_concurrent_writes: None,
+        identifier_overflow: None,
+        row_size_limit: None,
+        schema_mapping: None,
     }
 }
 
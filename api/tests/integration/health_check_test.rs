@@ -1,3 +1,4 @@
+use reqwest::StatusCode;
 use telemetry::init_test_tracing;
 
 use crate::common::test_app::spawn_test_app;
@@ -21,3 +22,44 @@ async fn health_check_works() {
     assert!(response.status().is_success());
     assert_eq!(Some(2), response.content_length());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn liveness_probe_succeeds() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(format!("{}/health/live", app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn readiness_probe_succeeds_when_the_database_is_reachable() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(format!("{}/health/ready", app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(body, serde_json::json!({"status": "ok"}));
+}
@@ -0,0 +1,54 @@
+use etl::metrics::TableCopyMetrics;
+use telemetry::init_test_tracing;
+
+use crate::common::test_app::spawn_test_app;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn metrics_are_exposed_in_the_prometheus_format() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+
+    let client = reqwest::Client::new();
+
+    // Act
+    let response = client
+        .get(format!("{}/metrics", app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(response.status().is_success());
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .expect("the content type should be set")
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(content_type.starts_with("text/plain"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipeline_metrics_are_not_exposed() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    // The copy counters are registered in the process, as they are in the replicators.
+    TableCopyMetrics::for_table(42).inc_row(10);
+
+    let client = reqwest::Client::new();
+
+    // Act
+    let response = client
+        .get(format!("{}/metrics", app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(response.status().is_success());
+    let body = response.text().await.expect("failed to read the body");
+    assert!(!body.contains("rows_copied_total"));
+}
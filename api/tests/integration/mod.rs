@@ -1,8 +1,13 @@
+mod admin_test;
+mod api_keys_test;
 mod destination_test;
 mod destinations_pipelines_test;
 mod health_check_test;
 mod images_test;
+mod metrics_test;
 mod pipelines_test;
+mod publications_test;
 mod sources_test;
+mod tables_test;
 mod tenants_sources_test;
 mod tenants_test;
@@ -1,17 +1,19 @@
 use api::db::pipelines::PipelineConfig;
 use api::routes::pipelines::{
-    CreatePipelineRequest, CreatePipelineResponse, ReadPipelineResponse, ReadPipelinesResponse,
-    UpdatePipelineImageRequest, UpdatePipelineRequest,
+    CreatePipelineRequest, CreatePipelineResponse, GetPipelineReplicationStatusResponse,
+    ReadPipelineResponse, ReadPipelinesResponse, ResyncTableRequest, UpdatePipelineImageRequest,
+    UpdatePipelineRequest, VerifyDestinationRequest,
 };
-use config::shared::{BatchConfig, RetryConfig};
+use config::shared::{BatchConfig, DestinationConfig, IntoConnectOptions, RetryConfig};
 use reqwest::StatusCode;
+use sqlx::{Connection, PgConnection, postgres::PgConnectOptions};
 use telemetry::init_test_tracing;
 
 use crate::{
     common::test_app::{TestApp, spawn_test_app},
-    integration::destination_test::create_destination,
+    integration::destination_test::{create_destination, create_destination_with_config},
     integration::images_test::create_default_image,
-    integration::sources_test::create_source,
+    integration::sources_test::{create_reachable_source, create_source},
     integration::tenants_test::create_tenant,
     integration::tenants_test::create_tenant_with_id_and_name,
 };
@@ -164,6 +166,33 @@ async fn pipeline_with_another_tenants_destination_cant_be_created() {
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn pipeline_with_an_empty_batch_cant_be_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    create_default_image(&app).await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+    let destination_id = create_destination(&app, tenant_id).await;
+
+    // Act
+    let mut config = new_pipeline_config();
+    config.batch = Some(BatchConfig {
+        max_size: 0,
+        max_fill_ms: 5,
+    });
+    let pipeline = CreatePipelineRequest {
+        source_id,
+        destination_id,
+        config,
+    };
+    let response = app.create_pipeline(tenant_id, &pipeline).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn an_existing_pipeline_can_be_read() {
     init_test_tracing();
@@ -773,3 +802,400 @@ async fn update_image_fails_when_no_default_image_exists() {
     // Assert
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_memory_destination_cant_be_verified() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+    let destination_id = create_destination_with_config(
+        &app,
+        tenant_id,
+        "Memory Destination".to_string(),
+        DestinationConfig::Memory,
+    )
+    .await;
+
+    // Act
+    let verify_request = VerifyDestinationRequest {
+        source_id,
+        destination_id,
+        publication_name: "publication".to_string(),
+    };
+    let response = app.verify_destination(tenant_id, &verify_request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_destination_cant_be_verified_against_another_tenants_source() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant1_id = &create_tenant_with_id_and_name(
+        &app,
+        "abcdefghijklmnopqrst".to_string(),
+        "tenant_1".to_string(),
+    )
+    .await;
+    let tenant2_id = &create_tenant_with_id_and_name(
+        &app,
+        "tsrqponmlkjihgfedcba".to_string(),
+        "tenant_2".to_string(),
+    )
+    .await;
+    let source2_id = create_source(&app, tenant2_id).await;
+    let destination1_id = create_destination(&app, tenant1_id).await;
+
+    // Act
+    let verify_request = VerifyDestinationRequest {
+        source_id: source2_id,
+        destination_id: destination1_id,
+        publication_name: "publication".to_string(),
+    };
+    let response = app.verify_destination(tenant1_id, &verify_request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replication_status_reports_the_snapshot_and_confirmed_lsns() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    let destination_id = create_destination(&app, tenant_id).await;
+    let pipeline_id = create_pipeline_with_config(
+        &app,
+        tenant_id,
+        source_id,
+        destination_id,
+        new_pipeline_config(),
+    )
+    .await;
+
+    // Act
+    let response = app
+        .read_pipeline_replication_status(tenant_id, pipeline_id)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: GetPipelineReplicationStatusResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.pipeline_id, pipeline_id);
+    assert_eq!(response.snapshot_lsn, None);
+    assert_eq!(response.confirmed_flush_lsn, None);
+
+    // Arrange
+    // The slot is temporary, so that it's dropped with the connection before the test database is
+    // dropped.
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+    let slot_name = format!("supabase_etl_apply_{pipeline_id}");
+    let snapshot_lsn: String = sqlx::query_scalar(
+        "select lsn::text from pg_create_logical_replication_slot($1, 'pgoutput', true)",
+    )
+    .bind(&slot_name)
+    .fetch_one(&mut connection)
+    .await
+    .expect("failed to create the replication slot");
+    sqlx::raw_sql(
+        r#"
+        create schema etl;
+        create table etl.pipeline_state (pipeline_id bigint primary key, snapshot_lsn text not null);
+        "#,
+    )
+    .execute(&mut connection)
+    .await
+    .expect("failed to create the pipeline state table");
+    sqlx::query("insert into etl.pipeline_state (pipeline_id, snapshot_lsn) values ($1, $2)")
+        .bind(pipeline_id)
+        .bind(&snapshot_lsn)
+        .execute(&mut connection)
+        .await
+        .expect("failed to store the snapshot lsn");
+
+    // Act
+    let response = app
+        .read_pipeline_replication_status(tenant_id, pipeline_id)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: GetPipelineReplicationStatusResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.snapshot_lsn, Some(snapshot_lsn));
+    assert!(response.confirmed_flush_lsn.is_some());
+
+    connection
+        .close()
+        .await
+        .expect("failed to close the connection");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replication_status_of_a_non_existing_pipeline_cant_be_read() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let response = app.read_pipeline_replication_status(tenant_id, 42).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn table_resync_is_requested_and_reported_in_the_replication_status() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    let destination_id = create_destination(&app, tenant_id).await;
+    let pipeline_id = create_pipeline_with_config(
+        &app,
+        tenant_id,
+        source_id,
+        destination_id,
+        new_pipeline_config(),
+    )
+    .await;
+    let resync_request = ResyncTableRequest {
+        schema: "public".to_string(),
+        name: "users".to_string(),
+    };
+
+    // Act
+    let response = app
+        .resync_table(tenant_id, pipeline_id, &resync_request)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response: serde_json::Value = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response["code"], "table_not_replicated");
+
+    // Arrange
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+    sqlx::raw_sql(
+        r#"
+        create table public.users (id bigint primary key);
+        create table public.orders (id bigint primary key);
+        create schema etl;
+        create type etl.table_state as enum (
+            'init', 'data_sync', 'finished_copy', 'sync_done', 'ready', 'skipped'
+        );
+        create table etl.replication_state (
+            pipeline_id bigint not null,
+            table_id oid not null,
+            state etl.table_state not null,
+            sync_done_lsn text null,
+            primary key (pipeline_id, table_id)
+        );
+        create table etl.table_resyncs (
+            pipeline_id bigint not null,
+            table_id oid not null,
+            requested_at timestamptz not null default now(),
+            started_at timestamptz null,
+            rows_copied bigint not null default 0,
+            primary key (pipeline_id, table_id)
+        );
+        "#,
+    )
+    .execute(&mut connection)
+    .await
+    .expect("failed to create the state tables");
+    sqlx::query(
+        r#"
+        insert into etl.replication_state (pipeline_id, table_id, state)
+        values ($1, 'public.users'::regclass, 'ready'), ($1, 'public.orders'::regclass, 'data_sync')
+        "#,
+    )
+    .bind(pipeline_id)
+    .execute(&mut connection)
+    .await
+    .expect("failed to store the table states");
+
+    // Act
+    let response = app
+        .resync_table(tenant_id, pipeline_id, &resync_request)
+        .await;
+    let in_progress_response = app
+        .resync_table(
+            tenant_id,
+            pipeline_id,
+            &ResyncTableRequest {
+                schema: "public".to_string(),
+                name: "orders".to_string(),
+            },
+        )
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(in_progress_response.status(), StatusCode::CONFLICT);
+    let response: GetPipelineReplicationStatusResponse = app
+        .read_pipeline_replication_status(tenant_id, pipeline_id)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.table_resyncs.len(), 1);
+    assert_eq!(response.table_resyncs[0].schema, "public");
+    assert_eq!(response.table_resyncs[0].name, "users");
+    assert_eq!(response.table_resyncs[0].state.as_deref(), Some("ready"));
+    assert_eq!(response.table_resyncs[0].started_at, None);
+    assert_eq!(response.table_resyncs[0].rows_copied, 0);
+
+    // Arrange
+    // The pipeline reports the progress of the copy once it started it.
+    sqlx::raw_sql(
+        r#"
+        update etl.table_resyncs set started_at = now(), rows_copied = 42;
+        update etl.replication_state set state = 'data_sync'
+        where table_id = 'public.users'::regclass;
+        "#,
+    )
+    .execute(&mut connection)
+    .await
+    .expect("failed to update the table resync");
+
+    // Act
+    let response: GetPipelineReplicationStatusResponse = app
+        .read_pipeline_replication_status(tenant_id, pipeline_id)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+
+    // Assert
+    assert_eq!(
+        response.table_resyncs[0].state.as_deref(),
+        Some("data_sync")
+    );
+    assert!(response.table_resyncs[0].started_at.is_some());
+    assert_eq!(response.table_resyncs[0].rows_copied, 42);
+
+    connection
+        .close()
+        .await
+        .expect("failed to close the connection");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replication_status_reports_the_progress_of_the_table_copies() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    let destination_id = create_destination(&app, tenant_id).await;
+    let pipeline_id = create_pipeline_with_config(
+        &app,
+        tenant_id,
+        source_id,
+        destination_id,
+        new_pipeline_config(),
+    )
+    .await;
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+    sqlx::raw_sql(
+        r#"
+        create table public.users (id bigint primary key);
+        create table public.orders (id bigint primary key);
+        create schema etl;
+        create type etl.table_state as enum (
+            'init', 'data_sync', 'finished_copy', 'sync_done', 'ready', 'skipped'
+        );
+        create table etl.replication_state (
+            pipeline_id bigint not null,
+            table_id oid not null,
+            state etl.table_state not null,
+            sync_done_lsn text null,
+            primary key (pipeline_id, table_id)
+        );
+        create table etl.table_copy_progress (
+            pipeline_id bigint not null,
+            table_id oid not null,
+            rows_copied bigint not null,
+            estimated_rows bigint null,
+            updated_at timestamptz not null default now(),
+            primary key (pipeline_id, table_id)
+        );
+        "#,
+    )
+    .execute(&mut connection)
+    .await
+    .expect("failed to create the state tables");
+    sqlx::query(
+        r#"
+        insert into etl.replication_state (pipeline_id, table_id, state)
+        values ($1, 'public.users'::regclass, 'data_sync'), ($1, 'public.orders'::regclass, 'ready')
+        "#,
+    )
+    .bind(pipeline_id)
+    .execute(&mut connection)
+    .await
+    .expect("failed to store the table states");
+    sqlx::query(
+        r#"
+        insert into etl.table_copy_progress (pipeline_id, table_id, rows_copied, estimated_rows)
+        values ($1, 'public.users'::regclass, 250, 1000), ($1, 'public.orders'::regclass, 10, null)
+        "#,
+    )
+    .bind(pipeline_id)
+    .execute(&mut connection)
+    .await
+    .expect("failed to store the copy progress");
+
+    // Act
+    let response = app
+        .read_pipeline_replication_status(tenant_id, pipeline_id)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: GetPipelineReplicationStatusResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.table_copies.len(), 2);
+    assert_eq!(response.table_copies[0].name, "orders");
+    assert_eq!(response.table_copies[0].state.as_deref(), Some("ready"));
+    assert_eq!(response.table_copies[0].rows_copied, 10);
+    assert_eq!(response.table_copies[0].estimated_rows, None);
+    assert_eq!(response.table_copies[1].name, "users");
+    assert_eq!(response.table_copies[1].state.as_deref(), Some("data_sync"));
+    assert_eq!(response.table_copies[1].rows_copied, 250);
+    assert_eq!(response.table_copies[1].estimated_rows, Some(1000));
+    assert!(response.table_resyncs.is_empty());
+
+    connection
+        .close()
+        .await
+        .expect("failed to close the connection");
+}
@@ -0,0 +1,489 @@
+use api::db::publications::PublicationTable;
+use api::routes::sources::publications::{CreatePublicationRequest, ReadPublicationsResponse};
+use config::shared::IntoConnectOptions;
+use reqwest::StatusCode;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{Connection, Executor, PgConnection};
+use telemetry::init_test_tracing;
+
+use crate::{
+    common::test_app::{TestApp, spawn_test_app},
+    integration::destination_test::create_destination,
+    integration::pipelines_test::{create_pipeline_with_config, new_pipeline_config},
+    integration::sources_test::create_reachable_source,
+    integration::tenants_test::create_tenant,
+};
+
+/// Runs `statements` in the database backing the test app.
+async fn execute(app: &TestApp, statements: &str) {
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+    connection
+        .execute(statements)
+        .await
+        .expect("failed to execute the statements");
+}
+
+fn publication(name: &str, tables: &[&str]) -> CreatePublicationRequest {
+    CreatePublicationRequest {
+        name: name.to_string(),
+        tables: tables
+            .iter()
+            .map(|name| PublicationTable {
+                schema: "public".to_string(),
+                name: name.to_string(),
+                columns: None,
+                row_filter: None,
+            })
+            .collect(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_publication_can_be_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(&app, "create table public.orders (id bigint primary key)").await;
+
+    // Act
+    let response = app
+        .create_publication(
+            tenant_id,
+            source_id,
+            &publication("orders_pub", &["orders"]),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_publication_with_a_table_without_replica_identity_cant_be_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(
+        &app,
+        "create table public.orders (id bigint primary key);
+        create table public.events (id bigint);
+        alter table public.events replica identity nothing;",
+    )
+    .await;
+
+    // Act
+    let response = app
+        .create_publication(
+            tenant_id,
+            source_id,
+            &publication("my_pub", &["orders", "events"]),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response: serde_json::Value = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response["code"], "missing_replica_identity");
+    let error = response["error"].as_str().unwrap();
+    assert!(error.contains("events"), "{error}");
+    assert!(!error.contains("orders"), "{error}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_publication_can_be_created_with_a_column_list() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(
+        &app,
+        "create table public.documents (id bigint primary key, title text, content bytea);
+        create table public.orders (id bigint primary key);",
+    )
+    .await;
+    let mut publication = publication("documents_pub", &["documents", "orders"]);
+    publication.tables[0].columns = Some(vec!["id".to_string(), "title".to_string()]);
+
+    // Act
+    let response = app
+        .create_publication(tenant_id, source_id, &publication)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: serde_json::Value = app
+        .read_publication(tenant_id, source_id, "documents_pub")
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let tables = response["tables"].as_array().unwrap();
+    let documents = tables
+        .iter()
+        .find(|table| table["name"] == "documents")
+        .unwrap();
+    assert_eq!(documents["columns"], serde_json::json!(["id", "title"]));
+    let orders = tables
+        .iter()
+        .find(|table| table["name"] == "orders")
+        .unwrap();
+    assert!(orders.get("columns").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_publication_with_an_empty_column_list_cant_be_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(&app, "create table public.orders (id bigint primary key)").await;
+    let mut publication = publication("orders_pub", &["orders"]);
+    publication.tables[0].columns = Some(vec![]);
+
+    // Act
+    let response = app
+        .create_publication(tenant_id, source_id, &publication)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_publication_can_be_created_with_a_row_filter() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(
+        &app,
+        "create table public.orders (id bigint primary key, tenant_id bigint)",
+    )
+    .await;
+    let mut publication = publication("orders_pub", &["orders"]);
+    publication.tables[0].row_filter = Some("tenant_id = 42".to_string());
+
+    // Act
+    let response = app
+        .create_publication(tenant_id, source_id, &publication)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: serde_json::Value = app
+        .read_publication(tenant_id, source_id, "orders_pub")
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response["tables"][0]["row_filter"], "(tenant_id = 42)");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_publication_with_an_invalid_row_filter_cant_be_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(&app, "create table public.orders (id bigint primary key)").await;
+    let mut publication = publication("orders_pub", &["orders"]);
+    publication.tables[0].row_filter = Some("missing_column = 42".to_string());
+
+    // Act
+    let response = app
+        .create_publication(tenant_id, source_id, &publication)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response: serde_json::Value = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let error = response["error"].as_str().unwrap();
+    assert!(error.contains("missing_column"), "{error}");
+}
+
+fn public_table(name: &str) -> PublicationTable {
+    PublicationTable {
+        schema: "public".to_string(),
+        name: name.to_string(),
+        columns: None,
+        row_filter: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_table_can_be_added_to_a_publication() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(
+        &app,
+        "create table public.orders (id bigint primary key);
+        create table public.customers (id bigint primary key);",
+    )
+    .await;
+    app.create_publication(
+        tenant_id,
+        source_id,
+        &publication("orders_pub", &["orders"]),
+    )
+    .await;
+
+    // Act
+    let response = app
+        .add_publication_table(
+            tenant_id,
+            source_id,
+            "orders_pub",
+            &public_table("customers"),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: serde_json::Value = app
+        .read_publication(tenant_id, source_id, "orders_pub")
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let mut names: Vec<_> = response["tables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|table| table["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["customers", "orders"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_published_table_cant_be_added_to_a_publication_again() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(&app, "create table public.orders (id bigint primary key)").await;
+    app.create_publication(
+        tenant_id,
+        source_id,
+        &publication("orders_pub", &["orders"]),
+    )
+    .await;
+
+    // Act
+    let response = app
+        .add_publication_table(tenant_id, source_id, "orders_pub", &public_table("orders"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let response: serde_json::Value = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response["code"], "table_already_published");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_table_cant_be_added_to_a_non_existing_publication() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(&app, "create table public.orders (id bigint primary key)").await;
+
+    // Act
+    let response = app
+        .add_publication_table(tenant_id, source_id, "orders_pub", &public_table("orders"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_table_can_be_dropped_from_a_publication() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(
+        &app,
+        "create table public.orders (id bigint primary key);
+        create table public.customers (id bigint primary key);",
+    )
+    .await;
+    app.create_publication(
+        tenant_id,
+        source_id,
+        &publication("orders_pub", &["orders", "customers"]),
+    )
+    .await;
+
+    // Act
+    let response = app
+        .drop_publication_table(tenant_id, source_id, "orders_pub", "public", "customers")
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: serde_json::Value = app
+        .read_publication(tenant_id, source_id, "orders_pub")
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let tables = response["tables"].as_array().unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0]["name"], "orders");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_table_which_isnt_published_cant_be_dropped_from_a_publication() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(
+        &app,
+        "create table public.orders (id bigint primary key);
+        create table public.customers (id bigint primary key);",
+    )
+    .await;
+    app.create_publication(
+        tenant_id,
+        source_id,
+        &publication("orders_pub", &["orders"]),
+    )
+    .await;
+
+    // Act
+    let response = app
+        .drop_publication_table(tenant_id, source_id, "orders_pub", "public", "customers")
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response: serde_json::Value = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response["code"], "table_not_published");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publications_are_read_with_the_pipelines_replicating_their_tables() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    execute(
+        &app,
+        "create table public.orders (id bigint primary key);
+        create table public.events (id bigint primary key);
+        create table public.items (id bigint primary key);",
+    )
+    .await;
+    let response = app
+        .create_publication(
+            tenant_id,
+            source_id,
+            &publication("orders_pub", &["orders", "events", "items"]),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .create_publication(tenant_id, source_id, &publication("items_pub", &["items"]))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let destination_id = create_destination(&app, tenant_id).await;
+    let mut config = new_pipeline_config();
+    config.publication_name = "orders_pub".to_string();
+    let pipeline_id =
+        create_pipeline_with_config(&app, tenant_id, source_id, destination_id, config).await;
+
+    // The states are stored like the pipeline does in the source.
+    execute(
+        &app,
+        &format!(
+            "create schema etl;
+            create type etl.table_state as enum (
+                'init', 'data_sync', 'finished_copy', 'sync_done', 'ready', 'skipped'
+            );
+            create table etl.replication_state (
+                pipeline_id bigint not null,
+                table_id oid not null,
+                state etl.table_state not null,
+                sync_done_lsn text null,
+                primary key (pipeline_id, table_id)
+            );
+            insert into etl.replication_state (pipeline_id, table_id, state)
+            values
+                ({pipeline_id}, 'public.orders'::regclass, 'ready'),
+                ({pipeline_id}, 'public.events'::regclass, 'skipped');"
+        ),
+    )
+    .await;
+
+    // Act
+    let response = app.read_all_publications(tenant_id, source_id).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: ReadPublicationsResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.publications.len(), 2);
+
+    let items_pub = &response.publications[0];
+    assert_eq!(items_pub.name, "items_pub");
+    assert!(items_pub.pipeline_ids.is_empty());
+    assert_eq!(items_pub.tables.len(), 1);
+    assert!(!items_pub.tables[0].replicated);
+    assert!(items_pub.tables[0].pipelines.is_empty());
+
+    let orders_pub = &response.publications[1];
+    assert_eq!(orders_pub.name, "orders_pub");
+    assert_eq!(orders_pub.pipeline_ids, vec![pipeline_id]);
+    for (name, replicated, state) in [
+        ("orders", true, Some("ready")),
+        ("events", false, Some("skipped")),
+        ("items", false, None),
+    ] {
+        let table = orders_pub
+            .tables
+            .iter()
+            .find(|table| table.name == name)
+            .unwrap();
+        assert_eq!(table.replicated, replicated, "{name}");
+        assert_eq!(table.pipelines.len(), 1);
+        assert_eq!(table.pipelines[0].pipeline_id, pipeline_id);
+        assert_eq!(table.pipelines[0].state.as_deref(), state, "{name}");
+    }
+}
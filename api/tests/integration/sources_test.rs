@@ -1,21 +1,35 @@
-use api::db::sources::SourceConfig;
+use api::db::sources::{SourceConfig, SourceSslMode, SourceTags};
+use api::routes::MSGPACK_CONTENT_TYPE;
 use api::routes::sources::{
-    CreateSourceRequest, CreateSourceResponse, ReadSourceResponse, ReadSourcesResponse,
-    UpdateSourceRequest,
+    CreateSourceRequest, CreateSourceResponse, CreateSourcesBatchRequest,
+    CreateSourcesBatchResponse, PatchSourceConfig, PatchSourceRequest, ReadSourceResponse,
+    ReadSourcesResponse, RotateSourceCredentialsRequest, RotateSourceCredentialsResponse,
+    SourceAuditLogResponse, SourceStatusResponse, UpdateSourceRequest,
 };
 use config::SerializableSecretString;
+use config::shared::IntoConnectOptions;
 use reqwest::StatusCode;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{Connection, PgConnection};
 use telemetry::init_test_tracing;
+use uuid::Uuid;
 
 use crate::{
-    common::test_app::{TestApp, spawn_test_app},
-    integration::tenants_test::create_tenant,
+    common::test_app::{TestApp, spawn_test_app, spawn_test_app_with_config},
+    integration::destination_test::create_destination,
+    integration::pipelines_test::{create_pipeline_with_config, new_pipeline_config},
+    integration::tenants_test::{create_tenant, create_tenant_with_id_and_name},
 };
 
 pub fn new_name() -> String {
     "Postgres Source".to_string()
 }
 
+/// Returns a source name that is unique within a tenant, since source names must not repeat.
+fn unique_name() -> String {
+    format!("{} {}", new_name(), Uuid::new_v4())
+}
+
 pub fn new_source_config() -> SourceConfig {
     SourceConfig {
         host: "localhost".to_string(),
@@ -23,6 +37,8 @@ pub fn new_source_config() -> SourceConfig {
         name: "postgres".to_string(),
         username: "postgres".to_string(),
         password: Some(SerializableSecretString::from("postgres".to_string())),
+        ssl_mode: None,
+        root_cert: None,
     }
 }
 
@@ -37,11 +53,13 @@ fn updated_source_config() -> SourceConfig {
         name: "sergtsop".to_string(),
         username: "sergtsop".to_string(),
         password: Some(SerializableSecretString::from("sergtsop".to_string())),
+        ssl_mode: None,
+        root_cert: None,
     }
 }
 
 pub async fn create_source(app: &TestApp, tenant_id: &str) -> i64 {
-    create_source_with_config(app, tenant_id, new_name(), new_source_config()).await
+    create_source_with_config(app, tenant_id, unique_name(), new_source_config()).await
 }
 
 pub async fn create_source_with_config(
@@ -50,7 +68,11 @@ pub async fn create_source_with_config(
     name: String,
     config: SourceConfig,
 ) -> i64 {
-    let source = CreateSourceRequest { name, config };
+    let source = CreateSourceRequest {
+        name,
+        config,
+        tags: SourceTags::new(),
+    };
     let response = app.create_source(tenant_id, &source).await;
     let response: CreateSourceResponse = response
         .json()
@@ -70,6 +92,7 @@ async fn source_can_be_created() {
     let source = CreateSourceRequest {
         name: new_name(),
         config: new_source_config(),
+        tags: SourceTags::new(),
     };
     let response = app.create_source(tenant_id, &source).await;
 
@@ -92,6 +115,7 @@ async fn an_existing_source_can_be_read() {
     let source = CreateSourceRequest {
         name: new_name(),
         config: new_source_config(),
+        tags: SourceTags::new(),
     };
     let response = app.create_source(tenant_id, &source).await;
     let response: CreateSourceResponse = response
@@ -115,6 +139,64 @@ async fn an_existing_source_can_be_read() {
     insta::assert_debug_snapshot!(response.config);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn an_existing_source_can_be_read_as_msgpack() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id =
+        create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+
+    // Act
+    let response = app
+        .read_source_with_accept(tenant_id, source_id, MSGPACK_CONTENT_TYPE)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(content_type(&response), MSGPACK_CONTENT_TYPE);
+    let body = response
+        .bytes()
+        .await
+        .expect("failed to read response body");
+    let response: ReadSourceResponse =
+        rmp_serde::from_slice(&body).expect("failed to deserialize response");
+    let config = new_source_config();
+    assert_eq!(response.id, source_id);
+    assert_eq!(&response.tenant_id, tenant_id);
+    assert_eq!(response.name, new_name());
+    assert_eq!(response.config.host, config.host);
+    assert_eq!(response.config.port, config.port);
+    assert_eq!(response.config.name, config.name);
+    assert_eq!(response.config.username, config.username);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn an_existing_source_is_read_as_json_by_default() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id =
+        create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+
+    // Act
+    let response = app
+        .read_source_with_accept(tenant_id, source_id, "application/json")
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(content_type(&response), "application/json");
+    let response: ReadSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.id, source_id);
+    assert_eq!(response.name, new_name());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn a_non_existing_source_cant_be_read() {
     init_test_tracing();
@@ -127,6 +209,12 @@ async fn a_non_existing_source_cant_be_read() {
 
     // Assert
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response: serde_json::Value = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response["code"], "source_not_found");
+    assert_eq!(response["error"], "The source with id 42 was not found");
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -139,6 +227,7 @@ async fn an_existing_source_can_be_updated() {
     let source = CreateSourceRequest {
         name: new_name(),
         config: new_source_config(),
+        tags: SourceTags::new(),
     };
     let response = app.create_source(tenant_id, &source).await;
     let response: CreateSourceResponse = response
@@ -151,6 +240,7 @@ async fn an_existing_source_can_be_updated() {
     let updated_config = UpdateSourceRequest {
         name: updated_name(),
         config: updated_source_config(),
+        tags: SourceTags::new(),
     };
     let response = app
         .update_source(tenant_id, source_id, &updated_config)
@@ -180,6 +270,7 @@ async fn a_non_existing_source_cant_be_updated() {
     let updated_config = UpdateSourceRequest {
         name: updated_name(),
         config: updated_source_config(),
+        tags: SourceTags::new(),
     };
     let response = app.update_source(tenant_id, 42, &updated_config).await;
 
@@ -187,6 +278,160 @@ async fn a_non_existing_source_cant_be_updated() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn only_the_patched_fields_of_a_source_are_updated() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+
+    // Act
+    let rename = PatchSourceRequest {
+        name: Some(updated_name()),
+        ..Default::default()
+    };
+    let rename_response = app.patch_source(tenant_id, source_id, &rename).await;
+    let change_port = PatchSourceRequest {
+        config: Some(PatchSourceConfig {
+            port: Some(2345),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let change_port_response = app.patch_source(tenant_id, source_id, &change_port).await;
+
+    // Assert
+    assert!(rename_response.status().is_success());
+    assert!(change_port_response.status().is_success());
+    let response = app.read_source(tenant_id, source_id).await;
+    let response: ReadSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let config = new_source_config();
+    assert_eq!(response.name, updated_name());
+    assert_eq!(response.config.host, config.host);
+    assert_eq!(response.config.port, 2345);
+    assert_eq!(response.config.name, config.name);
+    assert_eq!(response.config.username, config.username);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_patch_making_the_source_config_invalid_is_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+
+    // Act
+    let patch = PatchSourceRequest {
+        config: Some(PatchSourceConfig {
+            ssl_mode: Some(SourceSslMode::VerifyFull),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let response = app.patch_source(tenant_id, source_id, &patch).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_non_existing_source_cant_be_patched() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let patch = PatchSourceRequest {
+        name: Some(updated_name()),
+        ..Default::default()
+    };
+    let response = app.patch_source(tenant_id, 42, &patch).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_mutations_are_recorded_in_the_audit_log() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+
+    // Act
+    let updated_source = UpdateSourceRequest {
+        name: updated_name(),
+        config: updated_source_config(),
+        tags: SourceTags::new(),
+    };
+    app.update_source(tenant_id, source_id, &updated_source)
+        .await;
+    let credentials = RotateSourceCredentialsRequest {
+        password: SerializableSecretString::from("rotated".to_string()),
+        username: None,
+        verify: false,
+    };
+    app.rotate_source_credentials(tenant_id, source_id, &credentials)
+        .await;
+    app.delete_source(tenant_id, source_id).await;
+    app.restore_source(tenant_id, source_id).await;
+    let response = app.read_source_audit_log(tenant_id, source_id).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: SourceAuditLogResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let actions: Vec<_> = response
+        .entries
+        .iter()
+        .map(|entry| entry.action.as_str())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "create",
+            "update",
+            "rotate_credentials",
+            "delete",
+            "restore"
+        ]
+    );
+    let config_hashes: Vec<_> = response
+        .entries
+        .iter()
+        .map(|entry| entry.config_hash.as_deref())
+        .collect();
+    // The password is left out of the hash, so rotating it doesn't change the hash.
+    assert!(config_hashes[0].is_some());
+    assert_ne!(config_hashes[0], config_hashes[1]);
+    assert_eq!(config_hashes[1], config_hashes[2]);
+    assert_eq!(config_hashes[3], None);
+    assert_eq!(config_hashes[4], None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_audit_log_of_a_non_existing_source_cant_be_read() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let response = app.read_source_audit_log(tenant_id, 42).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn an_existing_source_can_be_deleted() {
     init_test_tracing();
@@ -197,6 +442,7 @@ async fn an_existing_source_can_be_deleted() {
     let source = CreateSourceRequest {
         name: new_name(),
         config: new_source_config(),
+        tags: SourceTags::new(),
     };
     let response = app.create_source(tenant_id, &source).await;
     let response: CreateSourceResponse = response
@@ -214,6 +460,58 @@ async fn an_existing_source_can_be_deleted() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn a_deleted_source_is_hidden_until_restored() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id =
+        create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+    let response = app.delete_source(tenant_id, source_id).await;
+    assert!(response.status().is_success());
+
+    // Assert
+    let response: ReadSourcesResponse = app
+        .read_all_sources(tenant_id)
+        .await
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(response.sources.is_empty());
+    let response = app.delete_source(tenant_id, source_id).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Act
+    let response = app.restore_source(tenant_id, source_id).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response = app.read_source(tenant_id, source_id).await;
+    assert!(response.status().is_success());
+    let response = app.restore_source(tenant_id, source_id).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_deleted_source_cant_be_restored_when_its_name_was_reused() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id =
+        create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+    app.delete_source(tenant_id, source_id).await;
+    // The name of a deleted source can be used by a new source.
+    create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+
+    // Act
+    let response = app.restore_source(tenant_id, source_id).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn a_non_existing_source_cant_be_deleted() {
     init_test_tracing();
@@ -262,3 +560,922 @@ async fn all_sources_can_be_read() {
         }
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_can_be_read_in_pages() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let mut source_ids = vec![];
+    for _ in 0..3 {
+        source_ids.push(
+            create_source_with_config(&app, tenant_id, unique_name(), new_source_config()).await,
+        );
+    }
+
+    // Act
+    let mut pages = vec![];
+    let mut after = None;
+    loop {
+        let response = app.read_sources_page(tenant_id, 2, after).await;
+        assert!(response.status().is_success());
+        let response: ReadSourcesResponse = response
+            .json()
+            .await
+            .expect("failed to deserialize response");
+        let ids: Vec<i64> = response.sources.iter().map(|source| source.id).collect();
+        pages.push(ids);
+
+        after = response.next_cursor;
+        if after.is_none() {
+            break;
+        }
+    }
+
+    // Assert
+    assert_eq!(
+        pages,
+        vec![vec![source_ids[0], source_ids[1]], vec![source_ids[2]]]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_cant_be_read_with_an_invalid_limit() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    for limit in [0, 201] {
+        // Act
+        let response = app.read_sources_page(tenant_id, limit, None).await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requests_of_a_tenant_over_the_rate_limit_are_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app_with_config(|config| {
+        config.rate_limit.requests_per_window = 2;
+        config.rate_limit.window_secs = 3600;
+    })
+    .await;
+    let tenant_id = &create_tenant(&app).await;
+    let other_tenant_id = &create_tenant_with_id_and_name(
+        &app,
+        "tsrqponmlkjihgfedcba".to_string(),
+        "OtherTenant".to_string(),
+    )
+    .await;
+    // Creating a tenant doesn't count as a request of the tenant, since it has no tenant id
+    // header.
+    for _ in 0..2 {
+        let response = app.read_all_sources(tenant_id).await;
+        assert!(response.status().is_success());
+    }
+
+    // Act
+    let response = app.read_all_sources(tenant_id).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after > 0 && retry_after <= 3600);
+
+    let response = app.read_all_sources(other_tenant_id).await;
+    assert!(response.status().is_success());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn all_sources_can_be_read_as_msgpack() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source1_id =
+        create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+    let source2_id =
+        create_source_with_config(&app, tenant_id, updated_name(), updated_source_config()).await;
+
+    // Act
+    let response = app
+        .read_all_sources_with_accept(tenant_id, MSGPACK_CONTENT_TYPE)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    assert_eq!(content_type(&response), MSGPACK_CONTENT_TYPE);
+    let body = response
+        .bytes()
+        .await
+        .expect("failed to read response body");
+    let response: ReadSourcesResponse =
+        rmp_serde::from_slice(&body).expect("failed to deserialize response");
+    let mut ids: Vec<i64> = response.sources.iter().map(|source| source.id).collect();
+    ids.sort();
+    assert_eq!(ids, vec![source1_id, source2_id]);
+    for source in response.sources {
+        assert_eq!(&source.tenant_id, tenant_id);
+        if source.id == source1_id {
+            assert_eq!(source.name, new_name());
+        } else {
+            assert_eq!(source.name, updated_name());
+        }
+    }
+}
+
+fn content_type(response: &reqwest::Response) -> &str {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .expect("missing content type")
+        .to_str()
+        .expect("content type is not valid ascii")
+}
+
+fn tags(tags: &[(&str, &str)]) -> SourceTags {
+    tags.iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+async fn create_source_with_tags(app: &TestApp, tenant_id: &str, tags: SourceTags) -> i64 {
+    let source = CreateSourceRequest {
+        name: unique_name(),
+        config: new_source_config(),
+        tags,
+    };
+    let response = app.create_source(tenant_id, &source).await;
+    let response: CreateSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    response.id
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_tags_can_be_set_and_read() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id =
+        create_source_with_tags(&app, tenant_id, tags(&[("env", "prod"), ("team", "data")])).await;
+
+    // Act
+    let response = app.read_source(tenant_id, source_id).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: ReadSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.tags, tags(&[("env", "prod"), ("team", "data")]));
+
+    // Act
+    let updated_source = UpdateSourceRequest {
+        name: updated_name(),
+        config: updated_source_config(),
+        tags: tags(&[("env", "staging")]),
+    };
+    let response = app
+        .update_source(tenant_id, source_id, &updated_source)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response = app.read_source(tenant_id, source_id).await;
+    let response: ReadSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.tags, tags(&[("env", "staging")]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_can_be_filtered_by_tag() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let prod_source_id =
+        create_source_with_tags(&app, tenant_id, tags(&[("env", "prod"), ("team", "data")])).await;
+    create_source_with_tags(&app, tenant_id, tags(&[("env", "staging")])).await;
+    create_source_with_tags(&app, tenant_id, SourceTags::new()).await;
+
+    // Act
+    let response = app.read_all_sources_with_tag(tenant_id, "env:prod").await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: ReadSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.sources.len(), 1);
+    assert_eq!(response.sources[0].id, prod_source_id);
+
+    // Act
+    let response = app.read_all_sources(tenant_id).await;
+
+    // Assert
+    let response: ReadSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.sources.len(), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_with_invalid_tags_are_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let source = CreateSourceRequest {
+        name: new_name(),
+        config: new_source_config(),
+        tags: tags(&[("", "prod")]),
+    };
+    let response = app.create_source(tenant_id, &source).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Act
+    let response = app.read_all_sources_with_tag(tenant_id, "env").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_source_with_an_ssl_mode_can_be_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let config = SourceConfig {
+        ssl_mode: Some(SourceSslMode::Require),
+        ..new_source_config()
+    };
+    let source_id = create_source_with_config(&app, tenant_id, new_name(), config).await;
+
+    // Act
+    let response = app.read_source(tenant_id, source_id).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: ReadSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.config.ssl_mode, Some(SourceSslMode::Require));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_with_an_invalid_ssl_config_are_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let root_cert = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+    let configs = [
+        // The certificate of the server can't be verified without a root certificate.
+        SourceConfig {
+            ssl_mode: Some(SourceSslMode::VerifyFull),
+            ..new_source_config()
+        },
+        // A root certificate is useless without TLS.
+        SourceConfig {
+            ssl_mode: Some(SourceSslMode::Disable),
+            root_cert: Some(root_cert.to_string()),
+            ..new_source_config()
+        },
+    ];
+
+    for config in configs {
+        // Act
+        let source = CreateSourceRequest {
+            name: unique_name(),
+            config,
+            tags: SourceTags::new(),
+        };
+        let response = app.create_source(tenant_id, &source).await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_source_with_a_duplicate_name_cant_be_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+
+    // Act
+    let source = CreateSourceRequest {
+        name: new_name(),
+        config: updated_source_config(),
+        tags: SourceTags::new(),
+    };
+    let response = app.create_source(tenant_id, &source).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_retried_source_creation_returns_the_source_created_first() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source = CreateSourceRequest {
+        name: new_name(),
+        config: new_source_config(),
+        tags: SourceTags::new(),
+    };
+
+    // Act
+    let mut ids = vec![];
+    for _ in 0..2 {
+        let response = app
+            .create_source_with_idempotency_key(tenant_id, &source, "retried-request")
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: CreateSourceResponse = response
+            .json()
+            .await
+            .expect("failed to deserialize response");
+        ids.push(response.id);
+    }
+
+    // Assert
+    assert_eq!(ids[0], ids[1]);
+    let response = app.read_all_sources(tenant_id).await;
+    let response: ReadSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.sources.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idempotency_keys_are_scoped_to_a_tenant() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant1_id = &create_tenant_with_id_and_name(
+        &app,
+        "abcdefghijklmnopqrst".to_string(),
+        "tenant_1".to_string(),
+    )
+    .await;
+    let tenant2_id = &create_tenant_with_id_and_name(
+        &app,
+        "tsrqponmlkjihgfedcba".to_string(),
+        "tenant_2".to_string(),
+    )
+    .await;
+    let source = CreateSourceRequest {
+        name: new_name(),
+        config: new_source_config(),
+        tags: SourceTags::new(),
+    };
+
+    // Act
+    let mut ids = vec![];
+    for tenant_id in [tenant1_id, tenant2_id] {
+        let response = app
+            .create_source_with_idempotency_key(tenant_id, &source, "shared-key")
+            .await;
+        let response: CreateSourceResponse = response
+            .json()
+            .await
+            .expect("failed to deserialize response");
+        ids.push(response.id);
+    }
+
+    // Assert
+    assert_ne!(ids[0], ids[1]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_source_with_an_empty_idempotency_key_is_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source = CreateSourceRequest {
+        name: new_name(),
+        config: new_source_config(),
+        tags: SourceTags::new(),
+    };
+
+    // Act
+    let response = app
+        .create_source_with_idempotency_key(tenant_id, &source, "")
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+fn batch_source(name: &str, tags: SourceTags) -> CreateSourceRequest {
+    CreateSourceRequest {
+        name: name.to_string(),
+        config: new_source_config(),
+        tags,
+    }
+}
+
+async fn create_sources_batch(
+    app: &TestApp,
+    tenant_id: &str,
+    batch: &CreateSourcesBatchRequest,
+) -> CreateSourcesBatchResponse {
+    let response = app.create_sources_batch(tenant_id, batch).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+        .json()
+        .await
+        .expect("failed to deserialize response")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_can_be_created_in_a_batch() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let batch = CreateSourcesBatchRequest {
+        sources: vec![
+            batch_source("first", SourceTags::new()),
+            batch_source("second", tags(&[("env", "prod")])),
+        ],
+        partial_success: false,
+    };
+    let response = create_sources_batch(&app, tenant_id, &batch).await;
+
+    // Assert
+    assert_eq!(response.results.len(), 2);
+    for (result, name) in response.results.iter().zip(["first", "second"]) {
+        assert!(result.error.is_none());
+        let response = app
+            .read_source(tenant_id, result.id.expect("the source was not created"))
+            .await;
+        let response: ReadSourceResponse = response
+            .json()
+            .await
+            .expect("failed to deserialize response");
+        assert_eq!(response.name, name);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_source_of_a_batch_is_created_when_one_fails() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    create_source_with_config(&app, tenant_id, "taken".to_string(), new_source_config()).await;
+
+    // Act
+    let batch = CreateSourcesBatchRequest {
+        sources: vec![
+            batch_source("first", SourceTags::new()),
+            batch_source("taken", SourceTags::new()),
+        ],
+        partial_success: false,
+    };
+    let response = create_sources_batch(&app, tenant_id, &batch).await;
+
+    // Assert
+    assert!(response.results.iter().all(|result| result.id.is_none()));
+    assert_eq!(
+        response.results[1].code.as_deref(),
+        Some("duplicate_source_name")
+    );
+    assert_eq!(
+        response.results[1].error.as_deref(),
+        Some("A source with the name 'taken' already exists")
+    );
+    let response = app.read_all_sources(tenant_id).await;
+    let response: ReadSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.sources.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn valid_sources_of_a_batch_are_created_with_partial_success() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    create_source_with_config(&app, tenant_id, "taken".to_string(), new_source_config()).await;
+
+    // Act
+    let batch = CreateSourcesBatchRequest {
+        sources: vec![
+            batch_source("first", SourceTags::new()),
+            batch_source("taken", SourceTags::new()),
+            batch_source("first", SourceTags::new()),
+            batch_source("invalid tags", tags(&[("", "value")])),
+            batch_source("last", SourceTags::new()),
+        ],
+        partial_success: true,
+    };
+    let response = create_sources_batch(&app, tenant_id, &batch).await;
+
+    // Assert
+    let created = response
+        .results
+        .iter()
+        .map(|result| result.id.is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(created, vec![true, false, false, false, true]);
+    assert!(response.results[3].error.is_some());
+    let response = app.read_all_sources(tenant_id).await;
+    let response: ReadSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.sources.len(), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn an_empty_batch_of_sources_is_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let batch = CreateSourcesBatchRequest {
+        sources: vec![],
+        partial_success: false,
+    };
+    let response = app.create_sources_batch(tenant_id, &batch).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_source_cant_be_renamed_to_a_duplicate_name() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    create_source_with_config(&app, tenant_id, new_name(), new_source_config()).await;
+    let source_id =
+        create_source_with_config(&app, tenant_id, updated_name(), updated_source_config()).await;
+
+    // Act
+    let updated_source = UpdateSourceRequest {
+        name: new_name(),
+        config: updated_source_config(),
+        tags: SourceTags::new(),
+    };
+    let response = app
+        .update_source(tenant_id, source_id, &updated_source)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_with_the_same_name_can_exist_in_different_tenants() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant1_id = &create_tenant(&app).await;
+    let tenant2_id = &create_tenant_with_id_and_name(
+        &app,
+        "tsrqponmlkjihgfedcba".to_string(),
+        "NewTenant2".to_string(),
+    )
+    .await;
+    create_source_with_config(&app, tenant1_id, new_name(), new_source_config()).await;
+
+    // Act
+    let source = CreateSourceRequest {
+        name: new_name(),
+        config: new_source_config(),
+        tags: SourceTags::new(),
+    };
+    let response = app.create_source(tenant2_id, &source).await;
+
+    // Assert
+    assert!(response.status().is_success());
+}
+
+/// Creates a source pointing to the database backing the test app, so that connections to it
+/// can actually succeed.
+pub async fn create_reachable_source(app: &TestApp, tenant_id: &str) -> i64 {
+    let database = app.database_config();
+    let config = SourceConfig {
+        host: database.host.clone(),
+        port: database.port,
+        name: database.name.clone(),
+        username: database.username.clone(),
+        password: database.password.clone(),
+        ssl_mode: None,
+        root_cert: None,
+    };
+
+    create_source_with_config(app, tenant_id, unique_name(), config).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reachable_source_can_be_created_with_validation() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let database = app.database_config();
+    let source = CreateSourceRequest {
+        name: unique_name(),
+        config: SourceConfig {
+            host: database.host.clone(),
+            port: database.port,
+            name: database.name.clone(),
+            username: database.username.clone(),
+            password: database.password.clone(),
+            ssl_mode: None,
+            root_cert: None,
+        },
+        tags: SourceTags::new(),
+    };
+
+    // Act
+    let response = app.create_validated_source(tenant_id, &source).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: CreateSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let response = app.read_source(tenant_id, response.id).await;
+    assert!(response.status().is_success());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_failing_validation_is_not_created() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let database = app.database_config();
+    let source = CreateSourceRequest {
+        name: unique_name(),
+        config: SourceConfig {
+            host: database.host.clone(),
+            port: database.port,
+            name: database.name.clone(),
+            username: "nonexistent_user".to_string(),
+            password: Some(SerializableSecretString::from("wrong".to_string())),
+            ssl_mode: None,
+            root_cert: None,
+        },
+        tags: SourceTags::new(),
+    };
+
+    // Act
+    let response = app.create_validated_source(tenant_id, &source).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.text().await.expect("failed to read response body");
+    assert!(body.contains("The source failed validation"));
+
+    let response = app.read_all_sources(tenant_id).await;
+    let response: ReadSourcesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(response.sources.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_credentials_can_be_rotated_without_verification() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_source(&app, tenant_id).await;
+
+    // Act
+    let credentials = RotateSourceCredentialsRequest {
+        password: SerializableSecretString::from("rotated".to_string()),
+        username: Some("rotated_user".to_string()),
+        verify: false,
+    };
+    let response = app
+        .rotate_source_credentials(tenant_id, source_id, &credentials)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: RotateSourceCredentialsResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(response.rotated);
+    assert!(!response.verified);
+
+    let response = app.read_source(tenant_id, source_id).await;
+    let response: ReadSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let expected_config = new_source_config();
+    assert_eq!(response.config.username, "rotated_user");
+    assert_eq!(response.config.host, expected_config.host);
+    assert_eq!(response.config.port, expected_config.port);
+    assert_eq!(response.config.name, expected_config.name);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_credentials_can_be_rotated_with_verification() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+
+    // Act
+    let database = app.database_config();
+    let credentials = RotateSourceCredentialsRequest {
+        password: database
+            .password
+            .clone()
+            .expect("test database has a password"),
+        username: None,
+        verify: true,
+    };
+    let response = app
+        .rotate_source_credentials(tenant_id, source_id, &credentials)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: RotateSourceCredentialsResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(response.rotated);
+    assert!(response.verified);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_credentials_are_not_rotated_when_verification_fails() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+
+    // Act
+    let credentials = RotateSourceCredentialsRequest {
+        password: SerializableSecretString::from("wrong".to_string()),
+        username: Some("nonexistent_user".to_string()),
+        verify: true,
+    };
+    let response = app
+        .rotate_source_credentials(tenant_id, source_id, &credentials)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: RotateSourceCredentialsResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(!response.rotated);
+    assert!(!response.verified);
+
+    let response = app.read_source(tenant_id, source_id).await;
+    let response: ReadSourceResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert_eq!(response.config.username, app.database_config().username);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn credentials_of_a_non_existing_source_cant_be_rotated() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let credentials = RotateSourceCredentialsRequest {
+        password: SerializableSecretString::from("rotated".to_string()),
+        username: None,
+        verify: false,
+    };
+    let response = app
+        .rotate_source_credentials(tenant_id, 42, &credentials)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_status_reports_the_slots_of_its_pipelines() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    let destination_id = create_destination(&app, tenant_id).await;
+    let pipeline_id = create_pipeline_with_config(
+        &app,
+        tenant_id,
+        source_id,
+        destination_id,
+        new_pipeline_config(),
+    )
+    .await;
+
+    // The slots are temporary, so that they are dropped with the connection before the test
+    // database is dropped.
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+    let apply_slot = format!("supabase_etl_apply_{pipeline_id}");
+    let table_sync_slot = format!("supabase_etl_table_sync_{pipeline_id}_16384");
+    // The slot of another pipeline whose id starts with the same digits.
+    let other_slot = format!("supabase_etl_apply_{pipeline_id}0");
+    for slot in [&apply_slot, &table_sync_slot, &other_slot] {
+        sqlx::query("select pg_create_logical_replication_slot($1, 'pgoutput', true)")
+            .bind(slot)
+            .execute(&mut connection)
+            .await
+            .expect("failed to create the replication slot");
+    }
+
+    // Act
+    let response = app.source_status(tenant_id, source_id).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: SourceStatusResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let slot_names: Vec<_> = response.slots.iter().map(|s| &s.slot_name).collect();
+    assert_eq!(slot_names, vec![&apply_slot, &table_sync_slot]);
+    for slot in &response.slots {
+        assert_eq!(slot.pipeline_id, pipeline_id);
+        assert!(slot.confirmed_flush_lsn.is_some());
+        assert!(slot.restart_lsn.is_some());
+        assert!(slot.lag_bytes.unwrap() >= 0);
+    }
+
+    connection
+        .close()
+        .await
+        .expect("failed to close the connection");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn status_of_a_non_existing_source_cant_be_read() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+
+    // Act
+    let response = app.source_status(tenant_id, 42).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
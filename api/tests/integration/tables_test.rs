@@ -0,0 +1,280 @@
+use api::db::tables::TableSampleMethod;
+use api::routes::sources::tables::{
+    PreviewTableQuery, PreviewTableResponse, ReadTablesQuery, ReadTablesResponse,
+};
+use config::shared::IntoConnectOptions;
+use reqwest::StatusCode;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{Connection, Executor, PgConnection};
+use telemetry::init_test_tracing;
+
+use crate::{
+    common::test_app::{TestApp, spawn_test_app},
+    integration::sources_test::create_reachable_source,
+    integration::tenants_test::create_tenant,
+};
+
+/// Creates the `public.<name>` table with `rows` rows in the database backing the test app.
+async fn create_table_with_rows(app: &TestApp, name: &str, rows: i64) {
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+
+    connection
+        .execute(
+            format!(
+                "create table public.{name} as select g as id, 'row_' || g as value from generate_series(1, {rows}) g"
+            )
+            .as_str(),
+        )
+        .await
+        .expect("failed to create the table");
+    // Sampling relies on the statistics to know whether the table is large enough.
+    connection
+        .execute(format!("analyze public.{name}").as_str())
+        .await
+        .expect("failed to analyze the table");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tables_are_read_with_their_columns() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    let options: PgConnectOptions = app.database_config().with_db();
+    let mut connection = PgConnection::connect_with(&options)
+        .await
+        .expect("failed to connect to the test database");
+    connection
+        .execute("create table public.orders (id bigint primary key, note varchar(10))")
+        .await
+        .expect("failed to create the table");
+
+    // Act
+    let query = ReadTablesQuery {
+        schema: Some("public".to_string()),
+    };
+    let response = app.read_tables(tenant_id, source_id, &query).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: ReadTablesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    let orders = response
+        .tables
+        .iter()
+        .find(|table| table.name == "orders")
+        .expect("the table was not returned");
+    assert_eq!(orders.schema, "public");
+    let columns = orders
+        .columns
+        .iter()
+        .map(|column| {
+            (
+                column.name.as_str(),
+                column.type_name.as_str(),
+                column.nullable,
+                column.primary,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        columns,
+        vec![
+            ("id", "int8", false, true),
+            ("note", "varchar", true, false)
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tables_can_be_filtered_by_schema() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    create_table_with_rows(&app, "small", 3).await;
+
+    // Act
+    let query = ReadTablesQuery {
+        schema: Some("missing".to_string()),
+    };
+    let response = app.read_tables(tenant_id, source_id, &query).await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: ReadTablesResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(response.tables.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_table_can_be_previewed() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    create_table_with_rows(&app, "small", 3).await;
+
+    // Act
+    let response = app
+        .preview_table(
+            tenant_id,
+            source_id,
+            "public",
+            "small",
+            &PreviewTableQuery::default(),
+        )
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: PreviewTableResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(!response.sampled);
+    assert_eq!(response.rows.len(), 3);
+    assert_eq!(response.rows[0]["value"], "row_1");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sampling_a_large_table_returns_a_bounded_number_of_rows() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    create_table_with_rows(&app, "large", 100_000).await;
+
+    for sample_method in [TableSampleMethod::System, TableSampleMethod::Bernoulli] {
+        // Act
+        let query = PreviewTableQuery {
+            limit: Some(50),
+            sample_method: Some(sample_method),
+            sample_percent: Some(10.0),
+        };
+        let response = app
+            .preview_table(tenant_id, source_id, "public", "large", &query)
+            .await;
+
+        // Assert
+        assert!(response.status().is_success());
+        let response: PreviewTableResponse = response
+            .json()
+            .await
+            .expect("failed to deserialize response");
+        assert!(response.sampled);
+        assert!(!response.rows.is_empty());
+        assert!(response.rows.len() <= 50);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sampling_a_small_table_falls_back_to_a_limit() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    create_table_with_rows(&app, "tiny", 2).await;
+
+    // Act
+    let query = PreviewTableQuery {
+        limit: None,
+        sample_method: Some(TableSampleMethod::System),
+        sample_percent: Some(1.0),
+    };
+    let response = app
+        .preview_table(tenant_id, source_id, "public", "tiny", &query)
+        .await;
+
+    // Assert
+    assert!(response.status().is_success());
+    let response: PreviewTableResponse = response
+        .json()
+        .await
+        .expect("failed to deserialize response");
+    assert!(!response.sampled);
+    assert_eq!(response.rows.len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn an_invalid_sample_percent_is_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    create_table_with_rows(&app, "small", 3).await;
+
+    for sample_percent in [0.0, -5.0, 100.5] {
+        // Act
+        let query = PreviewTableQuery {
+            limit: None,
+            sample_method: Some(TableSampleMethod::Bernoulli),
+            sample_percent: Some(sample_percent),
+        };
+        let response = app
+            .preview_table(tenant_id, source_id, "public", "small", &query)
+            .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_sample_method_without_percent_is_rejected() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+    create_table_with_rows(&app, "small", 3).await;
+
+    // Act
+    let query = PreviewTableQuery {
+        limit: None,
+        sample_method: Some(TableSampleMethod::System),
+        sample_percent: None,
+    };
+    let response = app
+        .preview_table(tenant_id, source_id, "public", "small", &query)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_non_existing_table_cant_be_previewed() {
+    init_test_tracing();
+    // Arrange
+    let app = spawn_test_app().await;
+    let tenant_id = &create_tenant(&app).await;
+    let source_id = create_reachable_source(&app, tenant_id).await;
+
+    // Act
+    let response = app
+        .preview_table(
+            tenant_id,
+            source_id,
+            "public",
+            "missing",
+            &PreviewTableQuery::default(),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
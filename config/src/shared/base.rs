@@ -6,7 +6,50 @@ pub enum ValidationError {
     /// Max table sync workers can't be zero
     #[error("`max_table_sync_workers` cannot be zero")]
     MaxTableSyncWorkersZero,
+    /// The batch config has a zero `max_size` or `max_fill_ms`.
+    #[error("Invalid batch config: `max_size` and `max_fill_ms` must be greater than zero")]
+    InvalidBatch,
+    /// A host of the connection config is not valid.
+    #[error("Invalid host '{host}': {reason}")]
+    InvalidHost { host: String, reason: String },
     /// TLS is enabled but no trusted root certificates are provided.
     #[error("Invalid TLS config: `trusted_root_certs` must be set when `enabled` is true")]
     MissingTrustedRootCerts,
+    /// Skipping the initial snapshot was requested outside of the `stream_only` mode.
+    #[error("`skip_initial_snapshot` can only be enabled when `mode` is `stream_only`")]
+    SkipInitialSnapshotRequiresStreamOnly,
+    /// The publication to create automatically has no tables or no published operations.
+    #[error("Invalid auto create publication: `tables` and `publish` must not be empty")]
+    InvalidAutoCreatePublication,
+    /// The start LSN override is not a valid LSN.
+    #[error("Invalid start LSN '{0}': expected the `X/X` format, e.g. `16/B374D848`")]
+    InvalidStartLsn(String),
+    /// The lag alert has no webhook URL or a zero `check_interval_ms`.
+    #[error(
+        "Invalid lag alert: `webhook_url` must be set and `check_interval_ms` must be greater than zero"
+    )]
+    InvalidLagAlert,
+    /// The sequence sync interval is zero.
+    #[error("`sequence_sync_interval_ms` cannot be zero")]
+    SequenceSyncIntervalZero,
+    /// The destination down policy has a zero `resume_check_interval_ms`, `max_pause_ms` or
+    /// `max_retained_wal_bytes`.
+    #[error(
+        "Invalid destination down policy: `resume_check_interval_ms`, `max_pause_ms` and `max_retained_wal_bytes` must be greater than zero"
+    )]
+    InvalidDestinationDown,
+    /// The heartbeat has an empty `schema` or `table_name`, or a zero `interval_ms`.
+    #[error(
+        "Invalid heartbeat: `schema` and `table_name` must not be empty and `interval_ms` must be greater than zero"
+    )]
+    InvalidHeartbeat,
+    /// The consistency check has a zero `range_size`.
+    #[error("Invalid consistency check: `range_size` must be greater than zero")]
+    InvalidConsistencyCheck,
+    /// The consistency check is enabled but the tables aren't copied in the text format.
+    #[error("`consistency_check` can only be set with the `text` copy format")]
+    ConsistencyCheckRequiresTextCopy,
+    /// The replication slot has an invalid name or an empty plugin.
+    #[error("Invalid replication slot: {0}")]
+    InvalidReplicationSlot(String),
 }
@@ -1,20 +1,77 @@
 use serde::{Deserialize, Serialize};
 
+use crate::shared::ValidationError;
+
 /// Batch processing configuration for pipelines.
+///
+/// A batch is written to the destination as soon as it holds [`BatchConfig::max_size`] items or
+/// [`BatchConfig::max_fill_ms`] milliseconds after it started filling, whichever comes first.
+///
+/// Larger batches and longer fill times raise throughput and make fewer writes, which matters for
+/// destinations billing or limiting writes, like BigQuery, but delay rows and keep more of them in
+/// memory, and very large batches risk timing out. Smaller batches and shorter fill times lower
+/// latency at the cost of more, smaller writes.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct BatchConfig {
     /// Maximum number of items in a batch for table copy and event streaming.
+    #[serde(default = "default_max_size")]
     pub max_size: usize,
     /// Maximum time, in milliseconds, to wait for a batch to fill before processing.
+    #[serde(default = "default_max_fill_ms")]
     pub max_fill_ms: u64,
 }
 
+fn default_max_size() -> usize {
+    1000
+}
+
+fn default_max_fill_ms() -> u64 {
+    1000
+}
+
+impl BatchConfig {
+    /// Validates the [`BatchConfig`].
+    ///
+    /// Returns [`ValidationError::InvalidBatch`] if [`BatchConfig::max_size`] or
+    /// [`BatchConfig::max_fill_ms`] is zero.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.max_size == 0 || self.max_fill_ms == 0 {
+            return Err(ValidationError::InvalidBatch);
+        }
+
+        Ok(())
+    }
+}
+
 impl Default for BatchConfig {
     fn default() -> Self {
         Self {
-            max_size: 1000,
-            max_fill_ms: 1000,
+            max_size: default_max_size(),
+            max_fill_ms: default_max_fill_ms(),
         }
     }
 }
+
+/// When the apply worker flushes its batch of events to the destination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchFlushMode {
+    /// Flushes once the batch reaches [`BatchConfig::max_size`] events or after
+    /// [`BatchConfig::max_fill_ms`] milliseconds.
+    #[default]
+    SizeOrTime,
+    /// Flushes exactly at each upstream `COMMIT`, so that the destination receives every
+    /// transaction whole in a single batch.
+    ///
+    /// The size and time limits of [`BatchConfig`] are ignored, which lowers throughput on many
+    /// small transactions and keeps large transactions entirely in memory until they commit.
+    TransactionBoundary,
+    /// Flushes at the first upstream `COMMIT` after the batch reaches [`BatchConfig::max_size`]
+    /// events or after [`BatchConfig::max_fill_ms`] milliseconds.
+    ///
+    /// Many small transactions, possibly touching different tables, are coalesced into a single
+    /// write while the destination still receives every transaction whole and in order. A batch
+    /// can exceed [`BatchConfig::max_size`] by the size of the transaction completing it.
+    CoalescedTransactions,
+}
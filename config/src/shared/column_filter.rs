@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Which columns of a table are kept, either by a destination or by a publication.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnFilterRule {
+    /// Only the listed columns are kept.
+    Allow(Vec<String>),
+    /// All columns except the listed ones are kept.
+    Deny(Vec<String>),
+}
+
+/// Column filter of a single table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TableColumnFilterConfig {
+    pub schema: String,
+    pub name: String,
+    pub rule: ColumnFilterRule,
+}
+
+/// Destination side filter of the columns of replicated tables.
+///
+/// Unlike the publication, which decides what is read from the source, the filter only decides what
+/// a single destination receives, so that destinations fed by the same source can see different
+/// subsets of columns. Tables without a filter are sent whole.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ColumnFilterConfig {
+    pub tables: Vec<TableColumnFilterConfig>,
+}
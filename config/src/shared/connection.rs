@@ -1,7 +1,11 @@
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::{PgConnectOptions as SqlxConnectOptions, PgSslMode as SqlxSslMode};
-use tokio_postgres::{Config as TokioPgConnectOptions, config::SslMode as TokioPgSslMode};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use tokio_postgres::{
+    Config as TokioPgConnectOptions,
+    config::{SslMode as TokioPgSslMode, TargetSessionAttrs},
+};
 
 use crate::SerializableSecretString;
 use crate::shared::ValidationError;
@@ -13,6 +17,10 @@ use crate::shared::ValidationError;
 #[serde(rename_all = "snake_case")]
 pub struct PgConnectionConfig {
     /// Hostname or IP address of the Postgres server.
+    ///
+    /// IPv6 addresses can be bracketed or not, a path starting with `/` is the directory of the
+    /// Unix-domain socket of the server, and several hosts separated by commas are tried in order
+    /// until one accepts connections, like with libpq. See [`parse_pg_hosts`].
     pub host: String,
     /// Port number on which the Postgres server is listening.
     pub port: u16,
@@ -26,6 +34,110 @@ pub struct PgConnectionConfig {
     pub tls: TlsConfig,
 }
 
+impl PgConnectionConfig {
+    /// Validates the [`PgConnectionConfig`].
+    ///
+    /// Returns [`ValidationError::InvalidHost`] if [`PgConnectionConfig::host`] can't be parsed
+    /// by [`parse_pg_hosts`], and the errors of [`TlsConfig::validate`].
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        parse_pg_hosts(&self.host)?;
+        self.tls.validate()
+    }
+
+    /// Returns the hosts of [`PgConnectionConfig::host`], or the host as is when it's not valid,
+    /// for the connection to fail with the error of the driver.
+    fn hosts(&self) -> Vec<PgHost> {
+        parse_pg_hosts(&self.host).unwrap_or_else(|_| vec![PgHost::Tcp(self.host.clone())])
+    }
+}
+
+/// A host of a Postgres server, as parsed by [`parse_pg_hosts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgHost {
+    /// A hostname, or an IPv4 or IPv6 address without brackets, connected to over TCP.
+    Tcp(String),
+    /// The directory of the Unix-domain socket of the server.
+    UnixSocket(String),
+}
+
+impl PgHost {
+    /// Returns the host in the format of the drivers, which connect to the Unix-domain socket of
+    /// hosts starting with `/`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            PgHost::Tcp(host) | PgHost::UnixSocket(host) => host,
+        }
+    }
+}
+
+/// Parses and normalizes the comma separated hosts of a [`PgConnectionConfig::host`].
+///
+/// Each host is either an absolute path to the directory of a Unix-domain socket, an IPv6
+/// address which may be enclosed in brackets, an IPv4 address, or a hostname made of labels of
+/// letters, digits, `-` and `_`. Whitespace around the hosts is ignored.
+///
+/// Returns [`ValidationError::InvalidHost`] for any other host, e.g. one with a port, which must
+/// be set in [`PgConnectionConfig::port`] instead.
+pub fn parse_pg_hosts(hosts: &str) -> Result<Vec<PgHost>, ValidationError> {
+    hosts
+        .split(',')
+        .map(|host| parse_pg_host(host.trim()))
+        .collect()
+}
+
+fn parse_pg_host(host: &str) -> Result<PgHost, ValidationError> {
+    let invalid = |reason: &str| ValidationError::InvalidHost {
+        host: host.to_string(),
+        reason: reason.to_string(),
+    };
+
+    if host.is_empty() {
+        return Err(invalid("hosts can't be empty"));
+    }
+
+    if host.starts_with('/') {
+        return Ok(PgHost::UnixSocket(host.to_string()));
+    }
+
+    if let Some(address) = host.strip_prefix('[') {
+        let address = address
+            .strip_suffix(']')
+            .ok_or_else(|| invalid("a bracketed IPv6 address must end with `]`"))?;
+        let address: Ipv6Addr = address
+            .parse()
+            .map_err(|_| invalid("not a valid IPv6 address"))?;
+        return Ok(PgHost::Tcp(address.to_string()));
+    }
+
+    if host.contains(':') {
+        let address: Ipv6Addr = host.parse().map_err(|_| {
+            invalid("not a valid IPv6 address, ports must be set in `port` rather than the host")
+        })?;
+        return Ok(PgHost::Tcp(address.to_string()));
+    }
+
+    if host.parse::<Ipv4Addr>().is_ok() {
+        return Ok(PgHost::Tcp(host.to_string()));
+    }
+
+    let labels: Vec<_> = host.split('.').collect();
+    let valid_label = |label: &&str| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    // Hostnames made only of numbers are malformed IPv4 addresses, like `10.0.0.256`.
+    let numeric_label = |label: &&str| label.chars().all(|c| c.is_ascii_digit());
+    if host.len() > 253 || !labels.iter().all(valid_label) || labels.iter().all(numeric_label) {
+        return Err(invalid("not a valid hostname or IP address"));
+    }
+
+    Ok(PgHost::Tcp(host.to_ascii_lowercase()))
+}
+
 /// TLS settings for secure Postgres connections.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -34,16 +146,39 @@ pub struct TlsConfig {
     pub trusted_root_certs: String,
     /// Whether TLS is enabled for the connection.
     pub enabled: bool,
+    /// How the certificate of the server is verified when TLS is enabled.
+    #[serde(default)]
+    pub verification: TlsVerification,
+}
+
+/// How the certificate of the server is verified, like the `require`, `verify-ca` and
+/// `verify-full` values of the `sslmode` of libpq.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TlsVerification {
+    /// The connection is encrypted, but the certificate of the server isn't verified.
+    Require,
+    /// The certificate of the server must be signed by one of the trusted root certificates.
+    VerifyCa,
+    /// The certificate of the server must be signed by one of the trusted root certificates, and
+    /// be issued for the host connected to.
+    #[default]
+    VerifyFull,
 }
 
 impl TlsConfig {
     /// Validates the [`TlsConfig`].
     ///
-    /// If [`TlsConfig::enabled`] is true, this method checks that [`TlsConfig::trusted_root_certs`] is not empty.
+    /// If [`TlsConfig::enabled`] is true and the certificate of the server is verified, this
+    /// method checks that [`TlsConfig::trusted_root_certs`] is not empty.
     ///
-    /// Returns [`ValidationError::MissingTrustedRootCerts`] if TLS is enabled but no certificates are provided.
+    /// Returns [`ValidationError::MissingTrustedRootCerts`] if the certificates are needed but
+    /// not provided.
     pub fn validate(&self) -> Result<(), ValidationError> {
-        if self.enabled && self.trusted_root_certs.is_empty() {
+        if self.enabled
+            && self.verification != TlsVerification::Require
+            && self.trusted_root_certs.is_empty()
+        {
             return Err(ValidationError::MissingTrustedRootCerts);
         }
 
@@ -75,13 +210,16 @@ pub trait IntoConnectOptions<Output> {
 
 impl IntoConnectOptions<SqlxConnectOptions> for PgConnectionConfig {
     fn without_db(&self) -> SqlxConnectOptions {
-        let ssl_mode = if self.tls.enabled {
-            SqlxSslMode::VerifyFull
-        } else {
-            SqlxSslMode::Prefer
+        let ssl_mode = match (self.tls.enabled, self.tls.verification) {
+            (false, _) => SqlxSslMode::Prefer,
+            (true, TlsVerification::Require) => SqlxSslMode::Require,
+            (true, TlsVerification::VerifyCa) => SqlxSslMode::VerifyCa,
+            (true, TlsVerification::VerifyFull) => SqlxSslMode::VerifyFull,
         };
+        // sqlx connects to a single host, so connections to several hosts only go to the first.
+        let hosts = self.hosts();
         let options = SqlxConnectOptions::new_without_pgpass()
-            .host(&self.host)
+            .host(hosts[0].as_str())
             .username(&self.username)
             .port(self.port)
             .ssl_mode(ssl_mode)
@@ -108,8 +246,16 @@ impl IntoConnectOptions<TokioPgConnectOptions> for PgConnectionConfig {
             TokioPgSslMode::Prefer
         };
         let mut config = TokioPgConnectOptions::new();
+        let hosts = self.hosts();
+        for host in &hosts {
+            config.host(host.as_str());
+        }
+        // When failing over between several hosts, standbys are skipped, since only the primary
+        // can be replicated from.
+        if hosts.len() > 1 {
+            config.target_session_attrs(TargetSessionAttrs::ReadWrite);
+        }
         config
-            .host(self.host.clone())
             .port(self.port)
             .user(self.username.clone())
             //
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// Checksums verifying that the rows copied from each table match the table at the copy's
+/// snapshot.
+///
+/// The rows are split in ranges of consecutive primary key values, and for each range a checksum
+/// of the rows is computed on the source, within the copy's snapshot, and compared with the
+/// checksum of the rows handed to the destination. A table whose checksums don't match fails its
+/// sync, reporting the mismatched key ranges. Only tables with a single integer primary key
+/// column are checked, and the tables must be copied in the text format.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ConsistencyCheckConfig {
+    /// Number of consecutive primary key values covered by each checksum.
+    #[serde(default = "default_range_size")]
+    pub range_size: u64,
+}
+
+fn default_range_size() -> u64 {
+    100_000
+}
+
+impl Default for ConsistencyCheckConfig {
+    fn default() -> Self {
+        Self {
+            range_size: default_range_size(),
+        }
+    }
+}
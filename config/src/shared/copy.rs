@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// Format of the data produced by the `COPY` commands copying the tables.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyFormat {
+    #[default]
+    Text,
+    /// Values containing the delimiter, a quote or a newline are quoted with `"`, and the quotes
+    /// within them are doubled. A quoted value is never the `NULL` string.
+    Csv,
+    Binary,
+}
+
+/// Options of the `COPY ... TO STDOUT` commands copying the tables.
+///
+/// The rows produced by `COPY` are parsed with the same options, which are checked when the
+/// pipeline starts: options the row parser can't handle, e.g. a non UTF-8 encoding, fail the start
+/// instead of silently mis-parsing rows.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CopyConfig {
+    /// `FORMAT` option.
+    #[serde(default)]
+    pub format: CopyFormat,
+    /// `DELIMITER` option, the character separating the columns of a row.
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// `NULL` option, the string representing a `NULL` value.
+    #[serde(default = "default_null")]
+    pub null: String,
+    /// `ENCODING` option, the encoding to which the server transcodes the copied data.
+    ///
+    /// Independently of this option, the sessions of a pipeline always use the `UTF8`
+    /// `client_encoding`, so that catalog queries and streamed changes are transcoded as well.
+    #[serde(default = "default_encoding")]
+    pub encoding: String,
+}
+
+fn default_delimiter() -> char {
+    '\t'
+}
+
+fn default_null() -> String {
+    "\\N".to_string()
+}
+
+fn default_encoding() -> String {
+    "UTF8".to_string()
+}
+
+impl Default for CopyConfig {
+    fn default() -> Self {
+        Self {
+            format: CopyFormat::default(),
+            delimiter: default_delimiter(),
+            null: default_null(),
+            encoding: default_encoding(),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::SerializableSecretString;
+use crate::shared::ColumnFilterConfig;
 
 /// Configuration options for supported data destinations.
 ///
@@ -29,9 +30,98 @@ pub enum DestinationConfig {
         /// <https://cloud.google.com/bigquery/docs/change-data-capture#create-max-staleness>.
         #[serde(skip_serializing_if = "Option::is_none")]
         max_staleness_mins: Option<u16>,
+        /// Optional filter of the columns sent to BigQuery.
+        ///
+        /// If not set, all the columns of the replicated tables are sent. Primary key columns
+        /// can't be filtered out, since they are needed to apply changes.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        column_filter: Option<ColumnFilterConfig>,
+        /// Optional maximum number of writes sent to BigQuery at the same time.
+        ///
+        /// The table sync workers and the apply worker of a pipeline share these permits, so
+        /// that they don't exhaust the project's quotas. If not set, writes are not limited.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_concurrent_writes: Option<u16>,
+        /// Optional policy applied to table and column names exceeding BigQuery's length limits.
+        ///
+        /// If not set, such names fail the pipeline when the table schema is written.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        identifier_overflow: Option<IdentifierOverflowPolicy>,
+        /// Optional limit on the encoded size of the rows sent to BigQuery.
+        ///
+        /// If not set, rows larger than the maximum size of a write request fail the batch
+        /// containing them.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        row_size_limit: Option<RowSizeLimitConfig>,
+        /// Optional mapping of the schemas of the replicated tables to BigQuery.
+        ///
+        /// If not set, the tables of every schema are written to the dataset, as
+        /// [`SchemaMapping::Flatten`] does.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        schema_mapping: Option<SchemaMapping>,
     },
 }
 
+/// How the Postgres schemas of the replicated tables map to the namespaces of a destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaMapping {
+    /// Writes every table to the namespace of the destination, prefixing its name with its
+    /// schema, e.g. `analytics.events` is written to `analytics_events`.
+    ///
+    /// Names containing underscores can be written to the same destination table, e.g. `a_b.c`
+    /// and `a.b_c`, in which case the pipeline fails instead of mixing their rows.
+    #[default]
+    Flatten,
+    /// Writes the tables of each schema to a namespace named after the schema, keeping their
+    /// name, e.g. `analytics.events` is written to `events` in the `analytics` namespace.
+    ///
+    /// For BigQuery, the namespaces are datasets of the project of the destination, which are
+    /// created in the location of its dataset if missing.
+    Namespace,
+}
+
+/// What to do with a destination identifier, derived from a Postgres identifier, which exceeds
+/// the length limit of the destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierOverflowPolicy {
+    /// Fails with an error naming the identifier.
+    #[default]
+    Error,
+    /// Truncates the identifier and appends a hash of the whole identifier to it, so that
+    /// identifiers sharing a long prefix stay distinct.
+    Truncate,
+}
+
+/// Limit on the encoded size of the rows written to a destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RowSizeLimitConfig {
+    /// Maximum encoded size of a row, in bytes.
+    ///
+    /// Destinations cap it to their own limit, so that a row is never rejected by the
+    /// destination because of its size.
+    pub max_bytes: u32,
+    /// What to do with a row exceeding the limit.
+    #[serde(default)]
+    pub policy: OversizedRowPolicy,
+}
+
+/// What to do with a row whose encoded size exceeds the limit of the destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizedRowPolicy {
+    /// Fails the batch containing the row with an error naming its table and size.
+    #[default]
+    Error,
+    /// Skips the row, so that the rest of the batch is written, and logs its table and size.
+    ///
+    /// The row is dropped silently apart from the warning log and the `oversized_rows_total`
+    /// metric, since there is no dead-letter destination it could be routed to.
+    Skip,
+}
+
 impl Default for DestinationConfig {
     fn default() -> Self {
         Self::Memory
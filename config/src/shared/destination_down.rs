@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::retry::RetryConfig;
+
+/// How a pipeline reacts when writes of streamed changes to the destination keep failing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DestinationDownConfig {
+    /// Retry policy of failed writes. The destination is considered down once all the attempts
+    /// failed.
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// Whether the pipeline pauses while the destination is down, instead of failing.
+    ///
+    /// A paused pipeline holds its position: the LSN confirmed to the slot isn't advanced, so
+    /// Postgres retains the WAL of the changes that weren't written yet, and the write is retried
+    /// until the destination recovers.
+    #[serde(default)]
+    pub pause_on_destination_down: bool,
+    /// Time, in milliseconds, between two writes attempted while paused.
+    ///
+    /// Should stay below the source's `wal_sender_timeout`, since the replication connection is
+    /// only kept alive at each attempt.
+    #[serde(default = "default_resume_check_interval_ms")]
+    pub resume_check_interval_ms: u64,
+    /// Maximum time, in milliseconds, for which the pipeline stays paused before failing.
+    #[serde(default = "default_max_pause_ms")]
+    pub max_pause_ms: u64,
+    /// Maximum amount of WAL, in bytes, retained for the pipeline while paused before failing.
+    #[serde(default = "default_max_retained_wal_bytes")]
+    pub max_retained_wal_bytes: u64,
+}
+
+fn default_resume_check_interval_ms() -> u64 {
+    5_000
+}
+
+fn default_max_pause_ms() -> u64 {
+    // 1 hour.
+    60 * 60 * 1000
+}
+
+fn default_max_retained_wal_bytes() -> u64 {
+    // 10 GiB.
+    10 * 1024 * 1024 * 1024
+}
+
+impl Default for DestinationDownConfig {
+    fn default() -> Self {
+        Self {
+            retry: RetryConfig::default(),
+            pause_on_destination_down: false,
+            resume_check_interval_ms: default_resume_check_interval_ms(),
+            max_pause_ms: default_max_pause_ms(),
+            max_retained_wal_bytes: default_max_retained_wal_bytes(),
+        }
+    }
+}
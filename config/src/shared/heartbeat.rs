@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Heartbeat written periodically to a dedicated table, so that the replication slot keeps
+/// advancing when the replicated tables are idle while other tables of the database are busy.
+///
+/// Without a recent change to confirm, the slot's `confirmed_flush_lsn` stalls and Postgres
+/// retains the WAL generated by the other tables. The heartbeat table is created if it doesn't
+/// exist and added to the publication, but it's never copied or streamed to the destination.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct HeartbeatConfig {
+    /// Schema of the heartbeat table.
+    #[serde(default = "default_schema")]
+    pub schema: String,
+    /// Name of the heartbeat table.
+    #[serde(default = "default_table_name")]
+    pub table_name: String,
+    /// Time, in milliseconds, between two heartbeats.
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+}
+
+fn default_schema() -> String {
+    "public".to_string()
+}
+
+fn default_table_name() -> String {
+    "etl_heartbeat".to_string()
+}
+
+fn default_interval_ms() -> u64 {
+    60_000
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            schema: default_schema(),
+            table_name: default_table_name(),
+            interval_ms: default_interval_ms(),
+        }
+    }
+}
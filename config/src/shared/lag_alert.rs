@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Replication lag after which an alert is sent to a webhook.
+///
+/// The lag is measured as the amount of WAL, in bytes, between the current WAL position of the
+/// source database and the position confirmed by the pipeline's replication slot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LagAlertConfig {
+    /// URL to which alerts are sent with a `POST` request.
+    pub webhook_url: String,
+    /// Lag, in bytes, above which replication is considered to be falling behind.
+    pub max_lag_bytes: u64,
+    /// Time, in milliseconds, for which the lag must stay above `max_lag_bytes` before alerting.
+    pub duration_ms: u64,
+    /// Minimum time, in milliseconds, between two alerts while the lag stays above the threshold.
+    #[serde(default = "default_repeat_interval_ms")]
+    pub repeat_interval_ms: u64,
+    /// Time, in milliseconds, between two lag checks.
+    #[serde(default = "default_check_interval_ms")]
+    pub check_interval_ms: u64,
+}
+
+fn default_repeat_interval_ms() -> u64 {
+    // 15 minutes.
+    15 * 60 * 1000
+}
+
+fn default_check_interval_ms() -> u64 {
+    10_000
+}
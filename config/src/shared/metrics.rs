@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Address on which the metrics of a process are served in the Prometheus text format.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct MetricsConfig {
+    /// Host on which the metrics listener binds.
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// Port on which the metrics listener binds.
+    pub port: u16,
+}
+
+fn default_host() -> String {
+    "0.0.0.0".to_string()
+}
@@ -1,19 +1,39 @@
 mod base;
 mod batch;
+mod column_filter;
 mod connection;
+mod consistency_check;
+mod copy;
 mod destination;
+mod destination_down;
+mod heartbeat;
+mod lag_alert;
+mod metrics;
 mod pipeline;
+mod publication;
+mod replication_slot;
 mod replicator;
 mod retry;
 mod sentry;
+mod statement_timeout;
 mod supabase;
 
 pub use base::*;
 pub use batch::*;
+pub use column_filter::*;
 pub use connection::*;
+pub use consistency_check::*;
+pub use copy::*;
 pub use destination::*;
+pub use destination_down::*;
+pub use heartbeat::*;
+pub use lag_alert::*;
+pub use metrics::*;
 pub use pipeline::*;
+pub use publication::*;
+pub use replication_slot::*;
 pub use replicator::*;
 pub use retry::*;
 pub use sentry::*;
+pub use statement_timeout::*;
 pub use supabase::*;
@@ -1,6 +1,37 @@
 use serde::{Deserialize, Serialize};
+use tokio_postgres::types::PgLsn;
 
-use crate::shared::{PgConnectionConfig, ValidationError, batch::BatchConfig, retry::RetryConfig};
+use crate::shared::{
+    AutoCreatePublicationConfig, BatchFlushMode, ConsistencyCheckConfig, CopyConfig, CopyFormat,
+    DestinationDownConfig, HeartbeatConfig, PgConnectionConfig,
+    ReplicationSlotConfig, StatementTimeoutConfig, ValidationError, batch::BatchConfig,
+    retry::RetryConfig,
+};
+
+/// How a pipeline brings the tables of a publication into the destination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicationMode {
+    /// Copies the existing rows of each table and then streams changes from the copy's
+    /// consistent point.
+    #[default]
+    CopyAndStream,
+    /// Only streams changes, assuming that the destination was seeded externally with the rows
+    /// that existed before the pipeline started.
+    StreamOnly,
+}
+
+/// How `NULL` values read from Postgres are handed to the destination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NullPolicy {
+    /// `NULL`s are kept as `NULL`s.
+    #[default]
+    KeepNull,
+    /// `NULL`s are replaced by a default value of the column's type, e.g. an empty string, `0` or
+    /// the Unix epoch, for destinations or consumers that can't deal with `NULL`s.
+    TypeDefault,
+}
 
 /// Configuration for a pipeline's batching and worker retry behavior.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,21 +60,210 @@ pub struct PipelineConfig {
 
     /// Maximum number of table sync workers that can run at a time
     pub max_table_sync_workers: u16,
+
+    /// Whether tables are copied before streaming or only streamed.
+    #[serde(default)]
+    pub mode: ReplicationMode,
+
+    /// Creates the replication slots without exporting a snapshot, so that no transaction is kept
+    /// open on the source while a table is being synced.
+    ///
+    /// Streaming then starts from the slot's consistent point and table schemas are read outside
+    /// of the slot's snapshot. This relaxes consistency: changes committed between the moment the
+    /// destination was seeded and the slot's creation are never replicated, and a schema change
+    /// racing with the slot creation may be missed. Only allowed in [`ReplicationMode::StreamOnly`].
+    #[serde(default)]
+    pub skip_initial_snapshot: bool,
+
+    /// `statement_timeout` of the catalog and table copy sessions.
+    #[serde(default)]
+    pub statement_timeout: StatementTimeoutConfig,
+
+    /// Options of the `COPY` commands copying the tables.
+    #[serde(default)]
+    pub copy: CopyConfig,
+
+    /// When batches of streamed events are flushed to the destination.
+    #[serde(default)]
+    pub batch_flush_mode: BatchFlushMode,
+
+    /// Publication to create on start if [`PipelineConfig::publication_name`] doesn't exist.
+    ///
+    /// If not set, the publication must be created before starting the pipeline.
+    #[serde(default)]
+    pub auto_create_publication: Option<AutoCreatePublicationConfig>,
+
+    /// How `NULL` values are written to the destination.
+    #[serde(default)]
+    pub null_policy: NullPolicy,
+
+    /// Whether the values of columns whose type isn't supported are written as their raw text,
+    /// instead of failing the copy or the streaming of their table.
+    ///
+    /// Only applies to the text and csv copy formats and to streamed changes.
+    #[serde(default)]
+    pub preserve_unsupported_types: bool,
+
+    /// LSN from which streaming starts, overriding the one stored in the apply worker's slot.
+    ///
+    /// Meant for disaster recovery, e.g. after manually seeding a destination up to a known LSN.
+    /// Starting fails if the LSN is before the slot's `restart_lsn`, since the WAL needed to
+    /// stream from it may be gone. Postgres never streams changes already confirmed by the slot,
+    /// so the override only skips changes when it is ahead of the slot.
+    #[serde(default)]
+    pub start_lsn: Option<String>,
+
+    /// Whether schema changes, added tables and dropped tables are sent to the destination as
+    /// metadata events, with the schemas before and after the change.
+    ///
+    /// Schema changes are detected from the relation messages of the apply worker, while added
+    /// and dropped tables are detected when the pipeline starts and by periodically reading the
+    /// tables of the publication while it's running.
+    #[serde(default)]
+    pub emit_metadata_events: bool,
+
+    /// Interval, in milliseconds, at which the current values of the sequences owned by the
+    /// replicated tables are sent to the destination as metadata events.
+    ///
+    /// Meant for failovers to the destination, which needs its sequences advanced past the
+    /// source's ones for new rows not to collide with replicated ones. A value is only sent again
+    /// when it changed. If not set, sequences are not replicated.
+    #[serde(default)]
+    pub sequence_sync_interval_ms: Option<u64>,
+
+    /// How failed writes of streamed changes to the destination are handled.
+    ///
+    /// If not set, a failed write isn't retried and fails the pipeline.
+    #[serde(default)]
+    pub destination_down: Option<DestinationDownConfig>,
+
+    /// Heartbeat keeping the replication slot advancing while the replicated tables are idle.
+    ///
+    /// If not set, no heartbeat is written.
+    #[serde(default)]
+    pub heartbeat: Option<HeartbeatConfig>,
+
+    /// Checksums comparing the rows copied from each table with the source.
+    ///
+    /// If not set, copied rows are not checked.
+    #[serde(default)]
+    pub consistency_check: Option<ConsistencyCheckConfig>,
+
+    /// Retry policy of the reconnections of the apply worker when its replication connection to
+    /// the source is lost, e.g. because the source restarted.
+    ///
+    /// The delays between attempts are jittered, and streaming resumes from the LSN last confirmed
+    /// to the slot, without copying the tables again. If not set, a lost connection fails the
+    /// pipeline.
+    #[serde(default)]
+    pub source_reconnect: Option<RetryConfig>,
+
+    /// Replication slot of the apply worker, which is reused if it exists.
+    ///
+    /// If not set, the slot is named after [`PipelineConfig::id`] and uses the `pgoutput` plugin.
+    #[serde(default)]
+    pub replication_slot: Option<ReplicationSlotConfig>,
 }
 
 impl PipelineConfig {
     /// Validates the [`PipelineConfig`].
     ///
-    /// This method checks that the [`PipelineConfig::pg_connection`] and [`PipelineConfig::max_table_sync_workers`] are valid.
+    /// This method checks that the [`PipelineConfig::pg_connection`], [`PipelineConfig::batch`],
+    /// [`PipelineConfig::max_table_sync_workers`],
+    /// [`PipelineConfig::skip_initial_snapshot`],
+    /// [`PipelineConfig::auto_create_publication`], [`PipelineConfig::start_lsn`],
+    /// [`PipelineConfig::sequence_sync_interval_ms`], [`PipelineConfig::destination_down`],
+    /// [`PipelineConfig::heartbeat`], [`PipelineConfig::consistency_check`] and
+    /// [`PipelineConfig::replication_slot`] are valid.
     ///
+    /// Returns [`ValidationError::InvalidHost`] if a host of [`PipelineConfig::pg_connection`] is
+    /// not valid.
+    /// Returns [`ValidationError::InvalidBatch`] if [`PipelineConfig::batch`] has a zero `max_size`
+    /// or `max_fill_ms`.
     /// Returns [`ValidationError::MaxTableSyncWorkersZero`] if [`PipelineConfig::max_table_sync_workers`] is zero.
+    /// Returns [`ValidationError::SkipInitialSnapshotRequiresStreamOnly`] if
+    /// [`PipelineConfig::skip_initial_snapshot`] is set outside of [`ReplicationMode::StreamOnly`].
+    /// Returns [`ValidationError::InvalidAutoCreatePublication`] if
+    /// [`PipelineConfig::auto_create_publication`] has no tables or no published operations.
+    /// Returns [`ValidationError::InvalidStartLsn`] if [`PipelineConfig::start_lsn`] is not a valid
+    /// LSN.
+    /// Returns [`ValidationError::SequenceSyncIntervalZero`] if
+    /// [`PipelineConfig::sequence_sync_interval_ms`] is zero.
+    /// Returns [`ValidationError::InvalidDestinationDown`] if [`PipelineConfig::destination_down`]
+    /// has a zero `resume_check_interval_ms`, `max_pause_ms` or `max_retained_wal_bytes`.
+    /// Returns [`ValidationError::InvalidHeartbeat`] if [`PipelineConfig::heartbeat`] has an empty
+    /// `schema` or `table_name`, or a zero `interval_ms`.
+    /// Returns [`ValidationError::InvalidConsistencyCheck`] if [`PipelineConfig::consistency_check`]
+    /// has a zero `range_size`.
+    /// Returns [`ValidationError::ConsistencyCheckRequiresTextCopy`] if
+    /// [`PipelineConfig::consistency_check`] is set with a [`PipelineConfig::copy`] format other than
+    /// [`CopyFormat::Text`], since the checksums are computed over rows in the text format.
+    /// Returns [`ValidationError::InvalidReplicationSlot`] if [`PipelineConfig::replication_slot`]
+    /// is not valid.
     pub fn validate(&self) -> Result<(), ValidationError> {
-        self.pg_connection.tls.validate()?;
+        self.pg_connection.validate()?;
+        self.batch.validate()?;
 
         if self.max_table_sync_workers == 0 {
             return Err(ValidationError::MaxTableSyncWorkersZero);
         }
 
+        if self.skip_initial_snapshot && self.mode != ReplicationMode::StreamOnly {
+            return Err(ValidationError::SkipInitialSnapshotRequiresStreamOnly);
+        }
+
+        if let Some(auto_create_publication) = &self.auto_create_publication
+            && (auto_create_publication.tables.is_empty()
+                || auto_create_publication.publish.is_empty())
+        {
+            return Err(ValidationError::InvalidAutoCreatePublication);
+        }
+
+        if let Some(start_lsn) = &self.start_lsn {
+            Self::parse_start_lsn(start_lsn)?;
+        }
+
+        if self.sequence_sync_interval_ms == Some(0) {
+            return Err(ValidationError::SequenceSyncIntervalZero);
+        }
+
+        if let Some(destination_down) = &self.destination_down
+            && (destination_down.resume_check_interval_ms == 0
+                || destination_down.max_pause_ms == 0
+                || destination_down.max_retained_wal_bytes == 0)
+        {
+            return Err(ValidationError::InvalidDestinationDown);
+        }
+
+        if let Some(heartbeat) = &self.heartbeat
+            && (heartbeat.schema.is_empty()
+                || heartbeat.table_name.is_empty()
+                || heartbeat.interval_ms == 0)
+        {
+            return Err(ValidationError::InvalidHeartbeat);
+        }
+
+        if let Some(consistency_check) = &self.consistency_check
+            && consistency_check.range_size == 0
+        {
+            return Err(ValidationError::InvalidConsistencyCheck);
+        }
+
+        if self.consistency_check.is_some() && self.copy.format != CopyFormat::Text {
+            return Err(ValidationError::ConsistencyCheckRequiresTextCopy);
+        }
+
+        if let Some(replication_slot) = &self.replication_slot {
+            replication_slot.validate()?;
+        }
+
         Ok(())
     }
+
+    /// Parses a [`PipelineConfig::start_lsn`] in the `X/X` text format used by Postgres.
+    pub fn parse_start_lsn(start_lsn: &str) -> Result<PgLsn, ValidationError> {
+        start_lsn
+            .parse()
+            .map_err(|_| ValidationError::InvalidStartLsn(start_lsn.to_string()))
+    }
 }
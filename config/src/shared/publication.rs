@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::ColumnFilterRule;
+
+/// An operation whose changes are published by a publication.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishOperation {
+    Insert,
+    Update,
+    Delete,
+    Truncate,
+}
+
+impl PublishOperation {
+    /// Returns the name of the operation as expected by the `publish` publication parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PublishOperation::Insert => "insert",
+            PublishOperation::Update => "update",
+            PublishOperation::Delete => "delete",
+            PublishOperation::Truncate => "truncate",
+        }
+    }
+}
+
+/// A table included in an automatically created publication.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PublicationTableConfig {
+    pub schema: String,
+    pub name: String,
+    /// Columns of the table which are published, or all of them if not set.
+    ///
+    /// The rule is turned into the column list of the table in the publication, so that the other
+    /// columns are neither copied nor streamed from the source. Primary key columns can't be
+    /// filtered out, since the changes of a row are identified by them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<ColumnFilterRule>,
+    /// SQL expression selecting the rows of the table which are published, e.g. `tenant_id = 42`,
+    /// or all of them if not set.
+    ///
+    /// The expression becomes the row filter of the table in the publication, so it's applied by
+    /// the source both to the initial copy and to the streamed changes. Updates are published as
+    /// inserts or deletes when a row starts or stops matching the filter, and truncates are always
+    /// published. Since updates and deletes are matched against the old row as identified by the
+    /// replica identity, the expression can only reference replica identity columns if updates or
+    /// deletes are published, otherwise Postgres rejects the updates and deletes of the table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_filter: Option<String>,
+}
+
+/// Publication created by a pipeline on start if it doesn't exist yet, instead of requiring it to
+/// be created manually.
+///
+/// If the publication already exists it is left untouched, including the columns it publishes, but
+/// starting fails if it doesn't include every table in [`AutoCreatePublicationConfig::tables`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AutoCreatePublicationConfig {
+    /// Tables included in the publication.
+    pub tables: Vec<PublicationTableConfig>,
+    /// Operations published by the publication, defaulting to all of them as Postgres does.
+    #[serde(default = "default_publish_operations")]
+    pub publish: Vec<PublishOperation>,
+    /// Whether changes to partitions are published as changes to their partitioned table.
+    #[serde(default)]
+    pub publish_via_partition_root: bool,
+}
+
+fn default_publish_operations() -> Vec<PublishOperation> {
+    vec![
+        PublishOperation::Insert,
+        PublishOperation::Update,
+        PublishOperation::Delete,
+        PublishOperation::Truncate,
+    ]
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::ValidationError;
+
+/// Maximum length of the name of a replication slot, `NAMEDATALEN - 1` in Postgres.
+const MAX_SLOT_NAME_LENGTH: usize = 63;
+
+/// Replication slot streamed from by the apply worker, instead of the one named after the
+/// pipeline.
+///
+/// The slot is used as is if it exists, e.g. because it was created outside of the pipeline, and
+/// created otherwise. The slots of the table sync workers are always named after the pipeline and
+/// created with the `pgoutput` plugin, since they are dropped once their table is synced.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplicationSlotConfig {
+    /// Name of the slot.
+    pub name: String,
+    /// Logical decoding output plugin of the slot.
+    ///
+    /// The plugin must be installed on the source and speak the protocol of `pgoutput`, e.g. a
+    /// fork of it, since the changes are decoded as `pgoutput` messages. An existing slot must
+    /// have been created with the same plugin.
+    #[serde(default = "default_plugin")]
+    pub plugin: String,
+}
+
+fn default_plugin() -> String {
+    ReplicationSlotConfig::DEFAULT_PLUGIN.to_string()
+}
+
+impl ReplicationSlotConfig {
+    /// The output plugin shipped with Postgres, used by default.
+    pub const DEFAULT_PLUGIN: &'static str = "pgoutput";
+
+    /// Validates the [`ReplicationSlotConfig`].
+    ///
+    /// Returns [`ValidationError::InvalidReplicationSlot`] if [`ReplicationSlotConfig::name`] is
+    /// not a valid slot name, i.e. made of 1 to 63 lowercase letters, digits and underscores, or
+    /// if [`ReplicationSlotConfig::plugin`] is empty.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let valid_name = !self.name.is_empty()
+            && self.name.len() <= MAX_SLOT_NAME_LENGTH
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid_name {
+            return Err(ValidationError::InvalidReplicationSlot(format!(
+                "`name` '{}' must be made of 1 to {MAX_SLOT_NAME_LENGTH} lowercase letters, digits and underscores",
+                self.name
+            )));
+        }
+
+        if self.plugin.is_empty() {
+            return Err(ValidationError::InvalidReplicationSlot(
+                "`plugin` must not be empty".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
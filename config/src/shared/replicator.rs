@@ -1,5 +1,7 @@
 use crate::shared::pipeline::PipelineConfig;
-use crate::shared::{DestinationConfig, SentryConfig, SupabaseConfig, ValidationError};
+use crate::shared::{
+    DestinationConfig, LagAlertConfig, MetricsConfig, SentryConfig, SupabaseConfig, ValidationError,
+};
 use serde::{Deserialize, Serialize};
 
 /// Configuration for the replicator service.
@@ -26,9 +28,33 @@ pub struct ReplicatorConfig {
     /// If provided, enables Supabase-specific features or reporting. If `None`, the replicator operates independently of Supabase.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supabase: Option<SupabaseConfig>,
+    /// Optional replication lag alert.
+    ///
+    /// If provided, the replicator monitors the lag of its replication slot and notifies a webhook when it falls behind.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lag_alert: Option<LagAlertConfig>,
+    /// Optional metrics listener.
+    ///
+    /// If provided, the replicator serves the metrics of its pipeline, like the number of copied rows, at `/metrics`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricsConfig>,
+    /// Time, in milliseconds, given to the pipeline on `SIGTERM` or `SIGINT` to write its buffered
+    /// rows to the destination and confirm its position, before the replicator exits without it.
+    ///
+    /// Should be shorter than the termination grace period of the pod, after which it's killed.
+    #[serde(default = "default_shutdown_timeout_ms")]
+    pub shutdown_timeout_ms: u64,
+}
+
+fn default_shutdown_timeout_ms() -> u64 {
+    ReplicatorConfig::DEFAULT_SHUTDOWN_TIMEOUT_MS
 }
 
 impl ReplicatorConfig {
+    /// Default [`ReplicatorConfig::shutdown_timeout_ms`], shorter than the default termination
+    /// grace period of 30 seconds of Kubernetes pods.
+    pub const DEFAULT_SHUTDOWN_TIMEOUT_MS: u64 = 25_000;
+
     /// Validates the loaded [`ReplicatorConfig`].
     ///
     /// Checks the validity of the configuration.
@@ -37,6 +63,14 @@ impl ReplicatorConfig {
     ///
     /// Returns [`ValidationError`] if validation fails.
     pub fn validate(&self) -> Result<(), ValidationError> {
-        self.pipeline.validate()
+        self.pipeline.validate()?;
+
+        if let Some(lag_alert) = &self.lag_alert
+            && (lag_alert.webhook_url.is_empty() || lag_alert.check_interval_ms == 0)
+        {
+            return Err(ValidationError::InvalidLagAlert);
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// `statement_timeout` applied to the Postgres sessions of a pipeline, depending on their purpose.
+///
+/// A value of `0` disables the timeout, as it does in Postgres.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct StatementTimeoutConfig {
+    /// Timeout, in milliseconds, of sessions running catalog and preflight queries.
+    pub catalog_ms: u64,
+    /// Timeout, in milliseconds, of sessions copying tables, which can legitimately run for hours
+    /// on large tables.
+    pub copy_ms: u64,
+}
+
+impl Default for StatementTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            catalog_ms: 30_000,
+            copy_ms: 0,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Benchmarks the parsing of the rows copied in the text format, on which the throughput of the
+//! initial table copies depends.
+//!
+//! Run with `cargo bench -p etl --bench table_row`.
+
+use config::shared::CopyConfig;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use etl::conversions::table_row::TableRowConverter;
+use postgres::schema::ColumnSchema;
+use std::hint::black_box;
+use tokio_postgres::types::Type;
+
+const ROWS: usize = 1_000;
+
+fn column_schemas(types: &[Type]) -> Vec<ColumnSchema> {
+    types
+        .iter()
+        .enumerate()
+        .map(|(i, typ)| ColumnSchema::new(format!("c{i}"), typ.clone(), -1, true, false))
+        .collect()
+}
+
+/// Returns `ROWS` rows of `values` joined like `COPY` does.
+fn rows(values: &[&str]) -> Vec<Vec<u8>> {
+    let mut row = values.join("\t").into_bytes();
+    row.push(b'\n');
+
+    vec![row; ROWS]
+}
+
+fn bench_rows(c: &mut Criterion, name: &str, rows: &[Vec<u8>], column_schemas: &[ColumnSchema]) {
+    let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+    let bytes = rows.iter().map(Vec::len).sum::<usize>();
+
+    let mut group = c.benchmark_group("table_row");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            for row in rows {
+                black_box(converter.try_from(black_box(row), column_schemas).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Rows of 100 columns of short values of common types, some of them `NULL`.
+fn wide_rows(c: &mut Criterion) {
+    let columns = [
+        (Type::INT8, "1234567890"),
+        (Type::TEXT, "alice@example.com"),
+        (Type::BOOL, "t"),
+        (Type::FLOAT8, "3.14159"),
+        (Type::TIMESTAMPTZ, "2024-05-01 12:34:56.789+00"),
+        (Type::NUMERIC, "12345.6789"),
+        (Type::UUID, "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"),
+        (Type::TEXT, "\\N"),
+        (Type::INT4, "42"),
+        (Type::DATE, "2024-05-01"),
+    ];
+    let (types, values): (Vec<_>, Vec<_>) = columns.into_iter().cycle().take(100).unzip();
+
+    bench_rows(c, "wide", &rows(&values), &column_schemas(&types));
+}
+
+/// Rows of a few text columns of 16 KiB each, without escapes.
+fn long_strings(c: &mut Criterion) {
+    let value = "lorem ipsum dolor sit amet ".repeat(16 * 1024 / 27);
+    let values = vec![value.as_str(); 4];
+
+    bench_rows(
+        c,
+        "long_strings",
+        &rows(&values),
+        &column_schemas(&[Type::TEXT; 4]),
+    );
+}
+
+/// Rows of text columns made mostly of escaped tabs, newlines and backslashes.
+fn escape_heavy(c: &mut Criterion) {
+    let value = "a\\tb\\nc\\\\d\\r".repeat(100);
+    let values = vec![value.as_str(); 8];
+
+    bench_rows(
+        c,
+        "escape_heavy",
+        &rows(&values),
+        &column_schemas(&[Type::TEXT; 8]),
+    );
+}
+
+criterion_group!(benches, wide_rows, long_strings, escape_heavy);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+//! Benchmarks the default [`Destination::write_table_rows_stream`], through which the rows of the
+//! initial table copies are batched before being written.
+//!
+//! Run with `cargo bench -p etl --bench write_table_rows_stream`.
+
+use config::shared::BatchConfig;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use etl::conversions::Cell;
+use etl::conversions::event::Event;
+use etl::conversions::table_row::TableRow;
+use etl::destination::base::{Destination, DestinationError};
+use futures::stream;
+use postgres::schema::{TableId, TableSchema};
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+
+const ROWS: usize = 100_000;
+
+/// A destination dropping the rows written to it, so that only the batching is measured.
+struct NoopDestination;
+
+impl Destination for NoopDestination {
+    async fn write_table_schema(&self, _table_schema: TableSchema) -> Result<(), DestinationError> {
+        Ok(())
+    }
+
+    async fn load_table_schemas(&self) -> Result<Vec<TableSchema>, DestinationError> {
+        Ok(vec![])
+    }
+
+    async fn write_table_rows(
+        &self,
+        _table_id: TableId,
+        table_rows: Vec<TableRow>,
+    ) -> Result<(), DestinationError> {
+        black_box(table_rows);
+        Ok(())
+    }
+
+    async fn write_events(&self, _events: Vec<Event>) -> Result<(), DestinationError> {
+        Ok(())
+    }
+}
+
+fn bench_batch_size(c: &mut Criterion, max_size: usize) {
+    let runtime = Runtime::new().unwrap();
+    let table_rows = (0..ROWS as i64)
+        .map(|i| TableRow::new(vec![Cell::I64(i), Cell::String(format!("row {i}"))]))
+        .collect::<Vec<_>>();
+    let batch_config = BatchConfig {
+        max_size,
+        max_fill_ms: 1000,
+    };
+
+    let mut group = c.benchmark_group("write_table_rows_stream");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.bench_function(format!("batches_of_{max_size}"), |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                NoopDestination
+                    .write_table_rows_stream(
+                        1,
+                        stream::iter(
+                            table_rows
+                                .clone()
+                                .into_iter()
+                                .map(Ok::<_, DestinationError>),
+                        ),
+                        batch_config.clone(),
+                    )
+                    .await
+                    .unwrap();
+            })
+        })
+    });
+    group.finish();
+}
+
+/// Rows written in small batches, as with a low `max_size`.
+fn small_batches(c: &mut Criterion) {
+    bench_batch_size(c, 100);
+}
+
+/// Rows written in large batches, as with the default `max_size`.
+fn large_batches(c: &mut Criterion) {
+    bench_batch_size(c, 1000);
+}
+
+criterion_group!(benches, small_batches, large_batches);
+criterion_main!(benches);
@@ -20,7 +20,10 @@ Usage:
 use std::error::Error;
 
 use clap::{Args, Parser};
-use config::shared::{BatchConfig, PgConnectionConfig, PipelineConfig, RetryConfig, TlsConfig};
+use config::shared::{
+    BatchConfig, BatchFlushMode, CopyConfig, NullPolicy, PgConnectionConfig, PipelineConfig,
+    ReplicationMode, RetryConfig, StatementTimeoutConfig, TlsConfig, TlsVerification,
+};
 use etl::{
     destination::bigquery::BigQueryDestination, pipeline::Pipeline,
     state::store::memory::MemoryStateStore,
@@ -140,6 +143,7 @@ async fn main_impl() -> Result<(), Box<dyn Error>> {
         tls: TlsConfig {
             trusted_root_certs: String::new(),
             enabled: false,
+            verification: TlsVerification::default(),
         },
     };
 
@@ -171,6 +175,22 @@ async fn main_impl() -> Result<(), Box<dyn Error>> {
         },
         publication_name: args.publication,
         max_table_sync_workers: args.bq_args.max_table_sync_workers,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
     };
 
     // Create the pipeline with state store and destination
@@ -0,0 +1,218 @@
+/*
+File Example
+
+This example demonstrates how to use the pipeline to stream
+data from PostgreSQL to files of newline-delimited JSON, one
+per table, using change data capture (CDC). It needs no cloud
+credentials, which makes it handy to try pipelines locally.
+
+Usage:
+    cargo run --example file -- \
+        --db-host localhost \
+        --db-port 5432 \
+        --db-name mydb \
+        --db-username postgres \
+        --db-password mypassword \
+        --directory ./etl_output \
+        --publication my_publication
+*/
+
+use std::error::Error;
+
+use clap::{Args, Parser};
+use config::shared::{
+    BatchConfig, BatchFlushMode, CopyConfig, NullPolicy, PgConnectionConfig, PipelineConfig,
+    ReplicationMode, RetryConfig, StatementTimeoutConfig, TlsConfig, TlsVerification,
+};
+use etl::{
+    destination::file::FileDestination, pipeline::Pipeline, state::store::memory::MemoryStateStore,
+};
+use tokio::signal;
+use tracing::{error, info};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+#[derive(Debug, Parser)]
+#[command(name = "file", version, about, arg_required_else_help = true)]
+struct AppArgs {
+    #[clap(flatten)]
+    db_args: DbArgs,
+
+    #[clap(flatten)]
+    file_args: FileArgs,
+
+    /// PostgreSQL publication name
+    #[arg(long)]
+    publication: String,
+}
+
+#[derive(Debug, Args)]
+struct DbArgs {
+    /// Host on which Postgres is running
+    #[arg(long)]
+    db_host: String,
+
+    /// Port on which Postgres is running
+    #[arg(long)]
+    db_port: u16,
+
+    /// Postgres database name
+    #[arg(long)]
+    db_name: String,
+
+    /// Postgres database user name
+    #[arg(long)]
+    db_username: String,
+
+    /// Postgres database user password
+    #[arg(long)]
+    db_password: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct FileArgs {
+    /// Directory in which the files of the tables are written
+    #[arg(long)]
+    directory: String,
+
+    /// Maximum batch size for processing events
+    #[arg(long, default_value = "1000")]
+    max_batch_size: usize,
+
+    /// Maximum time to wait for a batch to fill (in milliseconds)
+    #[arg(long, default_value = "5000")]
+    max_batch_fill_duration_ms: u64,
+
+    /// Maximum number of table sync workers
+    #[arg(long, default_value = "4")]
+    max_table_sync_workers: u16,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    if let Err(e) = main_impl().await {
+        error!("{e}");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn init_tracing() {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "file=info".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+}
+
+fn set_log_level() {
+    if std::env::var("RUST_LOG").is_err() {
+        unsafe {
+            std::env::set_var("RUST_LOG", "info");
+        }
+    }
+}
+
+async fn main_impl() -> Result<(), Box<dyn Error>> {
+    set_log_level();
+    init_tracing();
+
+    rustls::crypto::aws_lc_rs::default_provider()
+        .install_default()
+        .expect("failed to install default crypto provider");
+
+    let args = AppArgs::parse();
+
+    let pg_connection_config = PgConnectionConfig {
+        host: args.db_args.db_host,
+        port: args.db_args.db_port,
+        name: args.db_args.db_name,
+        username: args.db_args.db_username,
+        password: args.db_args.db_password.map(Into::into),
+        tls: TlsConfig {
+            trusted_root_certs: String::new(),
+            enabled: false,
+            verification: TlsVerification::default(),
+        },
+    };
+
+    let file_destination = FileDestination::new(&args.file_args.directory)?;
+
+    // Create in-memory state store for tracking table replication states
+    // The file destination keeps the table schemas in memory, so the tables are copied again
+    // whenever the example is restarted
+    let state_store = MemoryStateStore::new();
+
+    // Create pipeline configuration with all necessary settings
+    let pipeline_config = PipelineConfig {
+        id: 1, // Using a simple ID for the example
+        pg_connection: pg_connection_config,
+        batch: BatchConfig {
+            max_size: args.file_args.max_batch_size,
+            max_fill_ms: args.file_args.max_batch_fill_duration_ms,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 3,
+            initial_delay_ms: 1000,
+            max_delay_ms: 10000,
+            backoff_factor: 2.0,
+        },
+        publication_name: args.publication,
+        max_table_sync_workers: args.file_args.max_table_sync_workers,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    // Create the pipeline with state store and destination
+    let mut pipeline = Pipeline::new(1, pipeline_config, state_store, file_destination);
+
+    info!("starting file CDC pipeline...");
+
+    // Start the pipeline - this will:
+    // 1. Connect to PostgreSQL
+    // 2. Initialize table states based on the publication
+    // 3. Start apply and table sync workers
+    // 4. Begin streaming replication data
+    pipeline.start().await?;
+
+    info!("pipeline started successfully, press Ctrl+C to stop");
+
+    // Set up signal handler for graceful shutdown
+    let shutdown_signal = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+        info!("received Ctrl+C signal, initiating graceful shutdown...");
+    };
+
+    // Wait for either the pipeline to complete or a shutdown signal
+    tokio::select! {
+        result = pipeline.wait() => {
+            info!("pipeline completed normally");
+            result?;
+        }
+        _ = shutdown_signal => {
+            info!("shutting down pipeline...");
+        }
+    }
+
+    info!("pipeline stopped.");
+
+    Ok(())
+}
@@ -6,7 +6,7 @@ use gcp_bigquery_client::yup_oauth2::parse_service_account_key;
 use gcp_bigquery_client::{
     Client,
     error::BQError,
-    model::{query_request::QueryRequest, query_response::ResultSet},
+    model::{dataset::Dataset, query_request::QueryRequest, query_response::ResultSet},
     storage::{ColumnType, FieldDescriptor, StreamName, TableDescriptor},
 };
 use postgres::schema::ColumnSchema;
@@ -16,10 +16,15 @@ use tokio_postgres::types::Type;
 use tracing::info;
 
 use crate::conversions::Cell;
+use crate::conversions::enums::is_enum;
+use crate::conversions::geometry::is_geometry;
 use crate::conversions::table_row::TableRow;
+use crate::destination::compatibility::DestinationColumn;
 
 /// Maximum byte size for streaming data to BigQuery.
-const MAX_SIZE_BYTES: usize = 9 * 1024 * 1024;
+///
+/// Rows larger than this can't be streamed, since each request holds at least one row.
+pub const MAX_SIZE_BYTES: usize = 9 * 1024 * 1024;
 
 /// Trace identifier for ETL operations in BigQuery client.
 const ETL_TRACE_ID: &str = "ETL BigQueryClient";
@@ -27,6 +32,34 @@ const ETL_TRACE_ID: &str = "ETL BigQueryClient";
 /// Special column name for Change Data Capture operations in BigQuery.
 const BIGQUERY_CDC_SPECIAL_COLUMN: &str = "_CHANGE_TYPE";
 
+/// Offset added by PostgreSQL to the precision and scale encoded in a `numeric` type modifier.
+const NUMERIC_TYPE_MODIFIER_OFFSET: i32 = 4;
+
+/// The BigQuery type a PostgreSQL type is stored as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigQueryTypeMapping {
+    /// The BigQuery type, as written in DDL statements.
+    pub bigquery_type: String,
+    /// Whether some values of the PostgreSQL type can't be stored exactly by the BigQuery type.
+    pub lossy: bool,
+}
+
+impl BigQueryTypeMapping {
+    fn exact(bigquery_type: impl Into<String>) -> Self {
+        Self {
+            bigquery_type: bigquery_type.into(),
+            lossy: false,
+        }
+    }
+
+    fn lossy(bigquery_type: impl Into<String>) -> Self {
+        Self {
+            bigquery_type: bigquery_type.into(),
+            lossy: true,
+        }
+    }
+}
+
 /// Change Data Capture operation types for BigQuery streaming.
 #[derive(Debug)]
 pub enum BigQueryOperationType {
@@ -139,6 +172,44 @@ impl BigQueryClient {
         format!("`{}.{}.{}`", self.project_id, dataset_id, table_id)
     }
 
+    /// Creates a dataset, in the same location as the dataset `location_dataset_id`, if it does
+    /// not already exist.
+    ///
+    /// Returns `true` if the dataset was created, and `false` if the dataset already existed.
+    pub async fn create_dataset_if_missing(
+        &self,
+        dataset_id: &str,
+        location_dataset_id: &str,
+    ) -> Result<bool, BigQueryClientError> {
+        match self
+            .client
+            .dataset()
+            .get(&self.project_id, dataset_id)
+            .await
+        {
+            Ok(_) => return Ok(false),
+            Err(BQError::ResponseError { error }) if error.error.code == 404 => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let location = self
+            .client
+            .dataset()
+            .get(&self.project_id, location_dataset_id)
+            .await?
+            .location;
+        let mut dataset = Dataset::new(&self.project_id, dataset_id);
+        if let Some(location) = &location {
+            dataset = dataset.location(location);
+        }
+
+        info!("creating dataset {dataset_id} in BigQuery");
+
+        self.client.dataset().create(dataset).await?;
+
+        Ok(true)
+    }
+
     /// Creates a new table in the specified dataset if it does not already exist.
     ///
     /// Returns `true` if the table was created, and `false` if the table
@@ -225,6 +296,78 @@ impl BigQueryClient {
         Ok(exists)
     }
 
+    /// Returns the columns of a table, or `None` if the table doesn't exist.
+    ///
+    /// Types are named as in DDL statements, like the types returned by
+    /// [`BigQueryClient::bigquery_type_mapping`], with legacy names such as `INTEGER` replaced by
+    /// their standard equivalent and repeated columns reported as arrays.
+    pub async fn get_table_columns(
+        &self,
+        dataset_id: &str,
+        table_id: &str,
+    ) -> Result<Option<Vec<DestinationColumn>>, BigQueryClientError> {
+        let table = match self
+            .client
+            .table()
+            .get(&self.project_id, dataset_id, table_id, None)
+            .await
+        {
+            Ok(table) => table,
+            Err(BQError::ResponseError { error }) if error.error.code == 404 => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let columns = table
+            .schema
+            .fields
+            .unwrap_or_default()
+            .into_iter()
+            .map(|field| {
+                // The field type is serialized as its name, e.g. `"INTEGER"`.
+                let field_type = serde_json::to_value(&field.r#type)
+                    .ok()
+                    .and_then(|value| value.as_str().map(str::to_lowercase))
+                    .unwrap_or_default();
+                let field_type = match field_type.as_str() {
+                    "integer" => "int64".to_string(),
+                    "float" => "float64".to_string(),
+                    "boolean" => "bool".to_string(),
+                    "record" => "struct".to_string(),
+                    _ => field_type,
+                };
+
+                let mode = field.mode.unwrap_or_default().to_uppercase();
+                let typ = if mode == "REPEATED" {
+                    format!("array<{field_type}>")
+                } else {
+                    field_type
+                };
+
+                DestinationColumn {
+                    name: field.name,
+                    typ,
+                    nullable: mode != "REQUIRED",
+                }
+            })
+            .collect();
+
+        Ok(Some(columns))
+    }
+
+    /// Returns the columns a table must have to receive rows with the given column schemas.
+    pub fn expected_table_columns(column_schemas: &[ColumnSchema]) -> Vec<DestinationColumn> {
+        column_schemas
+            .iter()
+            .map(|column_schema| DestinationColumn {
+                name: column_schema.name.clone(),
+                typ: Self::bigquery_type_mapping(&column_schema.typ, column_schema.modifier)
+                    .bigquery_type,
+                // Arrays are repeated columns, which can't be `NULL` in BigQuery.
+                nullable: column_schema.nullable && !Self::is_array_type(&column_schema.typ),
+            })
+            .collect()
+    }
+
     /// Streams rows to a BigQuery table using the Storage Write API.
     ///
     /// This method is efficient for high-throughput ingestion. It batches rows
@@ -288,7 +431,7 @@ impl BigQueryClient {
         let mut column_spec = format!(
             "`{}` {}",
             column_schema.name,
-            Self::postgres_to_bigquery_type(&column_schema.typ)
+            Self::bigquery_type_mapping(&column_schema.typ, column_schema.modifier).bigquery_type
         );
 
         if !column_schema.nullable && !Self::is_array_type(&column_schema.typ) {
@@ -334,48 +477,101 @@ impl BigQueryClient {
         format!("options (max_staleness = interval {max_staleness_mins} minute)")
     }
 
-    /// Converts a PostgreSQL [`Type`] to its BigQuery equivalent data type string.
-    fn postgres_to_bigquery_type(typ: &Type) -> String {
+    /// Returns the BigQuery type used for a column of PostgreSQL [`Type`] with the given type
+    /// modifier, together with whether the mapping is lossy.
+    ///
+    /// `numeric` maps to `numeric` when its precision and scale fit, and to `bignumeric`
+    /// otherwise. Unconstrained `numeric` always maps to `bignumeric` and is lossy, since
+    /// PostgreSQL allows far more digits. Arrays are always lossy, since BigQuery arrays can't
+    /// hold `NULL` elements nor have more than one dimension. Types without a BigQuery equivalent
+    /// are stored as lossy strings.
+    pub fn bigquery_type_mapping(typ: &Type, modifier: i32) -> BigQueryTypeMapping {
         if Self::is_array_type(typ) {
             let element_type = match typ {
-                &Type::BOOL_ARRAY => "bool",
-                &Type::CHAR_ARRAY
-                | &Type::BPCHAR_ARRAY
-                | &Type::VARCHAR_ARRAY
-                | &Type::NAME_ARRAY
-                | &Type::TEXT_ARRAY => "string",
-                &Type::INT2_ARRAY | &Type::INT4_ARRAY | &Type::INT8_ARRAY => "int64",
-                &Type::FLOAT4_ARRAY | &Type::FLOAT8_ARRAY => "float64",
-                &Type::NUMERIC_ARRAY => "bignumeric",
-                &Type::DATE_ARRAY => "date",
-                &Type::TIME_ARRAY => "time",
-                &Type::TIMESTAMP_ARRAY | &Type::TIMESTAMPTZ_ARRAY => "timestamp",
-                &Type::UUID_ARRAY => "string",
-                &Type::JSON_ARRAY | &Type::JSONB_ARRAY => "json",
-                &Type::OID_ARRAY => "int64",
-                &Type::BYTEA_ARRAY => "bytes",
-                _ => "string",
+                &Type::BOOL_ARRAY => Type::BOOL,
+                &Type::CHAR_ARRAY => Type::CHAR,
+                &Type::BPCHAR_ARRAY => Type::BPCHAR,
+                &Type::VARCHAR_ARRAY => Type::VARCHAR,
+                &Type::NAME_ARRAY => Type::NAME,
+                &Type::TEXT_ARRAY => Type::TEXT,
+                &Type::INT2_ARRAY => Type::INT2,
+                &Type::INT4_ARRAY => Type::INT4,
+                &Type::INT8_ARRAY => Type::INT8,
+                &Type::FLOAT4_ARRAY => Type::FLOAT4,
+                &Type::FLOAT8_ARRAY => Type::FLOAT8,
+                &Type::NUMERIC_ARRAY => Type::NUMERIC,
+                &Type::MONEY_ARRAY => Type::MONEY,
+                &Type::DATE_ARRAY => Type::DATE,
+                &Type::TIME_ARRAY => Type::TIME,
+                &Type::TIMESTAMP_ARRAY => Type::TIMESTAMP,
+                &Type::TIMESTAMPTZ_ARRAY => Type::TIMESTAMPTZ,
+                &Type::UUID_ARRAY => Type::UUID,
+                &Type::JSON_ARRAY => Type::JSON,
+                &Type::JSONB_ARRAY => Type::JSONB,
+                &Type::OID_ARRAY => Type::OID,
+                &Type::PG_LSN_ARRAY => Type::PG_LSN,
+                &Type::BYTEA_ARRAY => Type::BYTEA,
+                _ => Type::TEXT,
             };
+            let element_mapping = Self::bigquery_type_mapping(&element_type, modifier);
 
-            return format!("array<{element_type}>");
+            return BigQueryTypeMapping::lossy(format!("array<{}>", element_mapping.bigquery_type));
         }
 
         match typ {
-            &Type::BOOL => "bool",
-            &Type::CHAR | &Type::BPCHAR | &Type::VARCHAR | &Type::NAME | &Type::TEXT => "string",
-            &Type::INT2 | &Type::INT4 | &Type::INT8 => "int64",
-            &Type::FLOAT4 | &Type::FLOAT8 => "float64",
-            &Type::NUMERIC => "bignumeric",
-            &Type::DATE => "date",
-            &Type::TIME => "time",
-            &Type::TIMESTAMP | &Type::TIMESTAMPTZ => "timestamp",
-            &Type::UUID => "string",
-            &Type::JSON | &Type::JSONB => "json",
-            &Type::OID => "int64",
-            &Type::BYTEA => "bytes",
-            _ => "string",
+            &Type::BOOL => BigQueryTypeMapping::exact("bool"),
+            &Type::CHAR | &Type::BPCHAR | &Type::VARCHAR | &Type::NAME | &Type::TEXT => {
+                BigQueryTypeMapping::exact("string")
+            }
+            &Type::INT2 | &Type::INT4 | &Type::INT8 | &Type::OID => {
+                BigQueryTypeMapping::exact("int64")
+            }
+            &Type::FLOAT4 | &Type::FLOAT8 => BigQueryTypeMapping::exact("float64"),
+            &Type::NUMERIC => Self::numeric_type_mapping(modifier),
+            // `money` values are 64-bit integers of cents, which fit a `numeric` exactly.
+            &Type::MONEY => BigQueryTypeMapping::exact("numeric"),
+            &Type::DATE => BigQueryTypeMapping::exact("date"),
+            &Type::TIME => BigQueryTypeMapping::exact("time"),
+            &Type::TIMESTAMP => BigQueryTypeMapping::exact("datetime"),
+            &Type::TIMESTAMPTZ => BigQueryTypeMapping::exact("timestamp"),
+            &Type::INTERVAL => BigQueryTypeMapping::exact("interval"),
+            &Type::UUID => BigQueryTypeMapping::exact("string"),
+            // BigQuery normalizes JSON, so the whitespace, key order and duplicate keys preserved
+            // by `json` are lost, while `jsonb` already normalized them.
+            &Type::JSON => BigQueryTypeMapping::lossy("json"),
+            &Type::JSONB => BigQueryTypeMapping::exact("json"),
+            &Type::BYTEA => BigQueryTypeMapping::exact("bytes"),
+            &Type::PG_LSN => BigQueryTypeMapping::exact("string"),
+            // BigQuery has no enums, but their labels are kept as is.
+            _ if is_enum(typ) => BigQueryTypeMapping::exact("string"),
+            // PostGIS values are kept as their raw EWKB, since a `geography` can't hold their SRID
+            // nor their extra dimensions.
+            _ if is_geometry(typ) => BigQueryTypeMapping::exact("bytes"),
+            _ => BigQueryTypeMapping::lossy("string"),
+        }
+    }
+
+    /// Returns the BigQuery type used for a `numeric` column with the given type modifier.
+    fn numeric_type_mapping(modifier: i32) -> BigQueryTypeMapping {
+        // An unconstrained `numeric` has no type modifier, otherwise the modifier encodes the
+        // precision in its upper 16 bits and the scale as an 11-bit signed integer in its lower
+        // bits, offset by the size of the varlena header.
+        if modifier < NUMERIC_TYPE_MODIFIER_OFFSET {
+            return BigQueryTypeMapping::lossy("bignumeric");
+        }
+
+        let modifier = modifier - NUMERIC_TYPE_MODIFIER_OFFSET;
+        let precision = (modifier >> 16) & 0xffff;
+        let scale = ((modifier & 0x7ff) ^ 1024) - 1024;
+        let integer_digits = precision - scale;
+
+        if scale <= 9 && integer_digits <= 29 {
+            BigQueryTypeMapping::exact("numeric")
+        } else if scale <= 38 && integer_digits <= 38 {
+            BigQueryTypeMapping::exact("bignumeric")
+        } else {
+            BigQueryTypeMapping::lossy("bignumeric")
         }
-        .to_string()
     }
 
     /// Returns true if the PostgreSQL [`Type`] represents an array type.
@@ -394,6 +590,7 @@ impl BigQueryClient {
                 | &Type::FLOAT4_ARRAY
                 | &Type::FLOAT8_ARRAY
                 | &Type::NUMERIC_ARRAY
+                | &Type::MONEY_ARRAY
                 | &Type::DATE_ARRAY
                 | &Type::TIME_ARRAY
                 | &Type::TIMESTAMP_ARRAY
@@ -403,6 +600,7 @@ impl BigQueryClient {
                 | &Type::JSONB_ARRAY
                 | &Type::OID_ARRAY
                 | &Type::BYTEA_ARRAY
+                | &Type::PG_LSN_ARRAY
         )
     }
 
@@ -427,6 +625,7 @@ impl BigQueryClient {
                 Type::FLOAT4 => ColumnType::Float,
                 Type::FLOAT8 => ColumnType::Double,
                 Type::NUMERIC => ColumnType::String,
+                Type::MONEY => ColumnType::String,
                 Type::DATE => ColumnType::String,
                 Type::TIME => ColumnType::String,
                 Type::TIMESTAMP => ColumnType::String,
@@ -448,6 +647,7 @@ impl BigQueryClient {
                 Type::FLOAT4_ARRAY => ColumnType::Float,
                 Type::FLOAT8_ARRAY => ColumnType::Double,
                 Type::NUMERIC_ARRAY => ColumnType::String,
+                Type::MONEY_ARRAY => ColumnType::String,
                 Type::DATE_ARRAY => ColumnType::String,
                 Type::TIME_ARRAY => ColumnType::String,
                 Type::TIMESTAMP_ARRAY => ColumnType::String,
@@ -457,6 +657,7 @@ impl BigQueryClient {
                 Type::JSONB_ARRAY => ColumnType::String,
                 Type::OID_ARRAY => ColumnType::Int32,
                 Type::BYTEA_ARRAY => ColumnType::Bytes,
+                _ if is_geometry(&column_schema.typ) => ColumnType::Bytes,
                 _ => ColumnType::String,
             };
 
@@ -499,63 +700,123 @@ impl fmt::Debug for BigQueryClient {
 #[cfg(test)]
 mod tests {
     use postgres::schema::ColumnSchema;
-    use tokio_postgres::types::Type;
+    use tokio_postgres::types::{Kind, Type};
 
     use super::*;
 
+    fn bigquery_type(typ: &Type) -> String {
+        BigQueryClient::bigquery_type_mapping(typ, -1).bigquery_type
+    }
+
+    /// Builds the type modifier PostgreSQL stores for `numeric(precision, scale)`.
+    fn numeric_modifier(precision: i32, scale: i32) -> i32 {
+        ((precision << 16) | (scale & 0x7ff)) + NUMERIC_TYPE_MODIFIER_OFFSET
+    }
+
     #[test]
     fn test_postgres_to_bigquery_type_basic_types() {
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::BOOL),
-            "bool"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::TEXT),
-            "string"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::INT4),
-            "int64"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::FLOAT8),
-            "float64"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::TIMESTAMP),
-            "timestamp"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::JSON),
-            "json"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::BYTEA),
-            "bytes"
-        );
+        assert_eq!(bigquery_type(&Type::BOOL), "bool");
+        assert_eq!(bigquery_type(&Type::TEXT), "string");
+        assert_eq!(bigquery_type(&Type::INT4), "int64");
+        assert_eq!(bigquery_type(&Type::FLOAT8), "float64");
+        assert_eq!(bigquery_type(&Type::TIMESTAMP), "datetime");
+        assert_eq!(bigquery_type(&Type::JSON), "json");
+        assert_eq!(bigquery_type(&Type::BYTEA), "bytes");
     }
 
     #[test]
     fn test_postgres_to_bigquery_type_array_types() {
+        assert_eq!(bigquery_type(&Type::BOOL_ARRAY), "array<bool>");
+        assert_eq!(bigquery_type(&Type::TEXT_ARRAY), "array<string>");
+        assert_eq!(bigquery_type(&Type::INT4_ARRAY), "array<int64>");
+        assert_eq!(bigquery_type(&Type::FLOAT8_ARRAY), "array<float64>");
+        assert_eq!(bigquery_type(&Type::TIMESTAMP_ARRAY), "array<datetime>");
+        assert_eq!(bigquery_type(&Type::TIMESTAMPTZ_ARRAY), "array<timestamp>");
+    }
+
+    #[test]
+    fn test_bigquery_type_mapping_per_type() {
+        let cases = [
+            (Type::BOOL, "bool", false),
+            (Type::CHAR, "string", false),
+            (Type::BPCHAR, "string", false),
+            (Type::VARCHAR, "string", false),
+            (Type::NAME, "string", false),
+            (Type::TEXT, "string", false),
+            (Type::INT2, "int64", false),
+            (Type::INT4, "int64", false),
+            (Type::INT8, "int64", false),
+            (Type::OID, "int64", false),
+            (Type::FLOAT4, "float64", false),
+            (Type::FLOAT8, "float64", false),
+            (Type::NUMERIC, "bignumeric", true),
+            (Type::MONEY, "numeric", false),
+            (Type::DATE, "date", false),
+            (Type::TIME, "time", false),
+            (Type::TIMESTAMP, "datetime", false),
+            (Type::TIMESTAMPTZ, "timestamp", false),
+            (Type::INTERVAL, "interval", false),
+            (Type::UUID, "string", false),
+            (Type::JSON, "json", true),
+            (Type::JSONB, "json", false),
+            (Type::BYTEA, "bytes", false),
+            (Type::PG_LSN, "string", false),
+            (Type::TIMETZ, "string", true),
+            (Type::INT4_ARRAY, "array<int64>", true),
+            (
+                Type::new(
+                    "mood".to_string(),
+                    16_390,
+                    Kind::Enum(vec!["sad".to_string(), "happy".to_string()]),
+                    "public".to_string(),
+                ),
+                "string",
+                false,
+            ),
+            (
+                Type::new(
+                    "geometry".to_string(),
+                    17_001,
+                    Kind::Simple,
+                    "public".to_string(),
+                ),
+                "bytes",
+                false,
+            ),
+        ];
+
+        for (typ, bigquery_type, lossy) in cases {
+            assert_eq!(
+                BigQueryClient::bigquery_type_mapping(&typ, -1),
+                BigQueryTypeMapping {
+                    bigquery_type: bigquery_type.to_string(),
+                    lossy,
+                },
+                "unexpected mapping for {typ}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_bigquery_type_mapping_numeric_precision() {
+        let mapping = |precision, scale| {
+            BigQueryClient::bigquery_type_mapping(
+                &Type::NUMERIC,
+                numeric_modifier(precision, scale),
+            )
+        };
+
+        assert_eq!(mapping(10, 2), BigQueryTypeMapping::exact("numeric"));
+        assert_eq!(mapping(38, 9), BigQueryTypeMapping::exact("numeric"));
+        assert_eq!(mapping(5, -3), BigQueryTypeMapping::exact("numeric"));
+        assert_eq!(mapping(39, 9), BigQueryTypeMapping::exact("bignumeric"));
+        assert_eq!(mapping(20, 10), BigQueryTypeMapping::exact("bignumeric"));
+        assert_eq!(mapping(76, 38), BigQueryTypeMapping::exact("bignumeric"));
+        assert_eq!(mapping(77, 38), BigQueryTypeMapping::lossy("bignumeric"));
+        assert_eq!(mapping(50, 40), BigQueryTypeMapping::lossy("bignumeric"));
         assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::BOOL_ARRAY),
-            "array<bool>"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::TEXT_ARRAY),
-            "array<string>"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::INT4_ARRAY),
-            "array<int64>"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::FLOAT8_ARRAY),
-            "array<float64>"
-        );
-        assert_eq!(
-            BigQueryClient::postgres_to_bigquery_type(&Type::TIMESTAMP_ARRAY),
-            "array<timestamp>"
+            BigQueryClient::bigquery_type_mapping(&Type::NUMERIC_ARRAY, numeric_modifier(10, 2)),
+            BigQueryTypeMapping::lossy("array<numeric>")
         );
     }
 
@@ -588,6 +849,16 @@ mod tests {
             ColumnSchema::new("tags".to_string(), Type::TEXT_ARRAY, -1, false, false);
         let array_spec = BigQueryClient::column_spec(&array_column);
         assert_eq!(array_spec, "`tags` array<string>");
+
+        let numeric_column = ColumnSchema::new(
+            "price".to_string(),
+            Type::NUMERIC,
+            numeric_modifier(12, 2),
+            true,
+            false,
+        );
+        let numeric_spec = BigQueryClient::column_spec(&numeric_column);
+        assert_eq!(numeric_spec, "`price` numeric");
     }
 
     #[test]
@@ -1,3 +1,4 @@
 pub mod future;
 pub mod shutdown;
+pub mod status;
 pub mod stream;
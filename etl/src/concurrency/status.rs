@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// The status of a pipeline, as published by its workers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PipelineStatus {
+    /// The pipeline keeps processing data.
+    Running,
+    /// The pipeline stopped processing data until the destination recovers.
+    Paused,
+}
+
+/// Publishes the [`PipelineStatus`] of a pipeline, as changed by its workers.
+///
+/// Each worker pauses and resumes the pipeline independently, so the pipeline is
+/// [`PipelineStatus::Paused`] as long as at least one of them is paused.
+#[derive(Debug, Clone)]
+pub struct StatusTx {
+    tx: watch::Sender<PipelineStatus>,
+    paused_workers: Arc<Mutex<usize>>,
+}
+
+impl StatusTx {
+    pub fn wrap(tx: watch::Sender<PipelineStatus>) -> Self {
+        Self {
+            tx,
+            paused_workers: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Marks the pipeline as paused by one more worker.
+    pub fn pause(&self) {
+        let mut paused_workers = self.paused_workers.lock().unwrap();
+        *paused_workers += 1;
+
+        self.tx.send_replace(PipelineStatus::Paused);
+    }
+
+    /// Marks the pipeline as resumed by a worker which previously called [`StatusTx::pause`].
+    pub fn resume(&self) {
+        let mut paused_workers = self.paused_workers.lock().unwrap();
+        *paused_workers = paused_workers.saturating_sub(1);
+
+        if *paused_workers == 0 {
+            self.tx.send_replace(PipelineStatus::Running);
+        }
+    }
+
+    pub fn subscribe(&self) -> StatusRx {
+        self.tx.subscribe()
+    }
+}
+
+pub type StatusRx = watch::Receiver<PipelineStatus>;
+
+pub fn create_status_channel() -> (StatusTx, StatusRx) {
+    let (tx, rx) = watch::channel(PipelineStatus::Running);
+    (StatusTx::wrap(tx), rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pipeline_is_paused_until_all_workers_resume() {
+        let (status_tx, status_rx) = create_status_channel();
+
+        status_tx.pause();
+        status_tx.pause();
+        assert_eq!(*status_rx.borrow(), PipelineStatus::Paused);
+
+        status_tx.resume();
+        assert_eq!(*status_rx.borrow(), PipelineStatus::Paused);
+
+        status_tx.resume();
+        assert_eq!(*status_rx.borrow(), PipelineStatus::Running);
+    }
+}
@@ -0,0 +1,424 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use core::str;
+use std::str::Utf8Error;
+use thiserror::Error;
+use tokio_postgres::types::{Field, FromSql, Kind, PgLsn, Type};
+use uuid::Uuid;
+
+use super::enums::{is_enum, parse_enum};
+use super::geometry::{check_ewkb, is_geometry};
+use super::hstore::is_hstore;
+use super::{ArrayCell, Cell, interval::PgInterval, numeric::PgNumeric};
+
+#[derive(Debug, Error)]
+pub enum FromBinaryError {
+    #[error("invalid string: {0}")]
+    InvalidString(#[from] Utf8Error),
+
+    #[error("invalid value: {0}")]
+    InvalidValue(#[from] Box<dyn std::error::Error + Sync + Send>),
+
+    #[error("unsupported type {0}")]
+    UnsupportedType(Type),
+}
+
+/// Converts values in the binary format of Postgres, as produced by the send function of their
+/// type, into [`Cell`]s.
+pub struct BinaryFormatConverter;
+
+impl BinaryFormatConverter {
+    pub fn try_from_bytes(typ: &Type, bytes: &[u8]) -> Result<Cell, FromBinaryError> {
+        if let Kind::Composite(fields) = typ.kind() {
+            return composite_from_bytes(fields, bytes);
+        }
+        if is_hstore(typ) {
+            return hstore_from_bytes(bytes);
+        }
+        // Enum values are sent as their label.
+        if is_enum(typ) {
+            return parse_enum(typ, str::from_utf8(bytes)?)
+                .map_err(|err| FromBinaryError::InvalidValue(err.into()));
+        }
+        // PostGIS values are sent as their EWKB.
+        if is_geometry(typ) {
+            check_ewkb(bytes).map_err(|err| FromBinaryError::InvalidValue(err.into()))?;
+            return Ok(Cell::Geometry(bytes.to_vec()));
+        }
+
+        match *typ {
+            Type::BOOL => Ok(Cell::Bool(from_sql(typ, bytes)?)),
+            Type::BOOL_ARRAY => Ok(Cell::Array(ArrayCell::Bool(from_sql(typ, bytes)?))),
+            // The `"char"` type is sent as its single byte, which is also its text representation.
+            Type::CHAR | Type::BPCHAR | Type::VARCHAR | Type::NAME | Type::TEXT => {
+                Ok(Cell::String(str::from_utf8(bytes)?.to_string()))
+            }
+            Type::CHAR_ARRAY
+            | Type::BPCHAR_ARRAY
+            | Type::VARCHAR_ARRAY
+            | Type::NAME_ARRAY
+            | Type::TEXT_ARRAY => Ok(Cell::Array(ArrayCell::String(from_sql(typ, bytes)?))),
+            Type::INT2 => Ok(Cell::I16(from_sql(typ, bytes)?)),
+            Type::INT2_ARRAY => Ok(Cell::Array(ArrayCell::I16(from_sql(typ, bytes)?))),
+            Type::INT4 => Ok(Cell::I32(from_sql(typ, bytes)?)),
+            Type::INT4_ARRAY => Ok(Cell::Array(ArrayCell::I32(from_sql(typ, bytes)?))),
+            Type::INT8 => Ok(Cell::I64(from_sql(typ, bytes)?)),
+            Type::INT8_ARRAY => Ok(Cell::Array(ArrayCell::I64(from_sql(typ, bytes)?))),
+            Type::FLOAT4 => Ok(Cell::F32(from_sql(typ, bytes)?)),
+            Type::FLOAT4_ARRAY => Ok(Cell::Array(ArrayCell::F32(from_sql(typ, bytes)?))),
+            Type::FLOAT8 => Ok(Cell::F64(from_sql(typ, bytes)?)),
+            Type::FLOAT8_ARRAY => Ok(Cell::Array(ArrayCell::F64(from_sql(typ, bytes)?))),
+            Type::NUMERIC => Ok(Cell::Numeric(from_sql::<PgNumeric>(typ, bytes)?)),
+            Type::NUMERIC_ARRAY => Ok(Cell::Array(ArrayCell::Numeric(from_sql(typ, bytes)?))),
+            Type::BYTEA => Ok(Cell::Bytes(bytes.to_vec())),
+            Type::BYTEA_ARRAY => Ok(Cell::Array(ArrayCell::Bytes(from_sql(typ, bytes)?))),
+            Type::DATE => Ok(Cell::Date(from_sql::<NaiveDate>(typ, bytes)?)),
+            Type::DATE_ARRAY => Ok(Cell::Array(ArrayCell::Date(from_sql(typ, bytes)?))),
+            Type::TIME => Ok(Cell::Time(from_sql::<NaiveTime>(typ, bytes)?)),
+            Type::TIME_ARRAY => Ok(Cell::Array(ArrayCell::Time(from_sql(typ, bytes)?))),
+            Type::TIMESTAMP => Ok(Cell::TimeStamp(from_sql::<NaiveDateTime>(typ, bytes)?)),
+            Type::TIMESTAMP_ARRAY => Ok(Cell::Array(ArrayCell::TimeStamp(from_sql(typ, bytes)?))),
+            Type::TIMESTAMPTZ => Ok(Cell::TimeStampTz(from_sql::<DateTime<Utc>>(typ, bytes)?)),
+            Type::TIMESTAMPTZ_ARRAY => {
+                Ok(Cell::Array(ArrayCell::TimeStampTz(from_sql(typ, bytes)?)))
+            }
+            Type::INTERVAL => Ok(Cell::Interval(from_sql::<PgInterval>(typ, bytes)?)),
+            Type::UUID => Ok(Cell::Uuid(from_sql::<Uuid>(typ, bytes)?)),
+            Type::UUID_ARRAY => Ok(Cell::Array(ArrayCell::Uuid(from_sql(typ, bytes)?))),
+            #[cfg(not(feature = "json_as_string"))]
+            Type::JSON | Type::JSONB => Ok(Cell::Json(from_sql(typ, bytes)?)),
+            #[cfg(not(feature = "json_as_string"))]
+            Type::JSON_ARRAY | Type::JSONB_ARRAY => {
+                Ok(Cell::Array(ArrayCell::Json(from_sql(typ, bytes)?)))
+            }
+            #[cfg(feature = "json_as_string")]
+            Type::JSON | Type::JSONB => Ok(Cell::String(json_to_string(typ, bytes)?)),
+            #[cfg(feature = "json_as_string")]
+            Type::JSON_ARRAY | Type::JSONB_ARRAY => {
+                let values: Vec<Option<RawJson>> = from_sql(typ, bytes)?;
+                let values = values
+                    .into_iter()
+                    .map(|value| value.map(|value| value.0))
+                    .collect();
+
+                Ok(Cell::Array(ArrayCell::String(values)))
+            }
+            Type::OID => Ok(Cell::U32(from_sql(typ, bytes)?)),
+            Type::OID_ARRAY => Ok(Cell::Array(ArrayCell::U32(from_sql(typ, bytes)?))),
+            // LSNs are kept in their `X/X` text representation, as in the text format.
+            Type::PG_LSN => Ok(Cell::String(from_sql::<PgLsn>(typ, bytes)?.to_string())),
+            Type::PG_LSN_ARRAY => {
+                let lsns: Vec<Option<PgLsn>> = from_sql(typ, bytes)?;
+                let lsns = lsns
+                    .into_iter()
+                    .map(|lsn| lsn.map(|lsn| lsn.to_string()))
+                    .collect();
+
+                Ok(Cell::Array(ArrayCell::String(lsns)))
+            }
+            #[cfg(feature = "unknown_types_to_bytes")]
+            _ => Ok(Cell::Bytes(bytes.to_vec())),
+            #[cfg(not(feature = "unknown_types_to_bytes"))]
+            _ => Err(FromBinaryError::UnsupportedType(typ.clone())),
+        }
+    }
+}
+
+/// Decodes `bytes` with the [`FromSql`] implementation of `T`.
+///
+/// Arrays are decoded as a `Vec` of their elements, and fail to decode if they have more than one
+/// dimension.
+fn from_sql<'a, T: FromSql<'a>>(typ: &Type, bytes: &'a [u8]) -> Result<T, FromBinaryError> {
+    Ok(T::from_sql(typ, bytes)?)
+}
+
+/// Decodes a value of a composite type, sent as its number of fields followed by the type oid,
+/// the length and the bytes of each field, with a length of -1 for `NULL`s.
+fn composite_from_bytes(fields: &[Field], bytes: &[u8]) -> Result<Cell, FromBinaryError> {
+    let (num_fields, mut bytes) = split_i32(bytes, "composite")?;
+    if num_fields as usize != fields.len() {
+        return Err(FromBinaryError::InvalidValue(
+            format!(
+                "expected {} composite fields but found {num_fields}",
+                fields.len()
+            )
+            .into(),
+        ));
+    }
+
+    let mut cells = Vec::with_capacity(fields.len());
+    for field in fields {
+        let (_type_oid, rest) = split_i32(bytes, "composite")?;
+        let (len, rest) = split_i32(rest, "composite")?;
+        if len < 0 {
+            cells.push(Cell::Null(field.type_().clone()));
+            bytes = rest;
+            continue;
+        }
+
+        let Some((value, rest)) = rest.split_at_checked(len as usize) else {
+            return Err(FromBinaryError::InvalidValue(
+                "truncated composite value".into(),
+            ));
+        };
+        cells.push(BinaryFormatConverter::try_from_bytes(field.type_(), value)?);
+        bytes = rest;
+    }
+
+    Ok(Cell::Composite(cells))
+}
+
+/// Decodes a value of the `hstore` type into a JSON object, sent as its number of pairs followed
+/// by the length and the bytes of each key and value, with a length of -1 for `NULL` values.
+fn hstore_from_bytes(bytes: &[u8]) -> Result<Cell, FromBinaryError> {
+    fn split_string(bytes: &[u8], len: i32) -> Result<(String, &[u8]), FromBinaryError> {
+        let (value, rest) = bytes
+            .split_at_checked(len as usize)
+            .ok_or_else(|| FromBinaryError::InvalidValue("truncated hstore value".into()))?;
+
+        Ok((str::from_utf8(value)?.to_string(), rest))
+    }
+
+    let (num_pairs, mut bytes) = split_i32(bytes, "hstore")?;
+    let mut map = serde_json::Map::new();
+    for _ in 0..num_pairs {
+        let (len, rest) = split_i32(bytes, "hstore")?;
+        if len < 0 {
+            return Err(FromBinaryError::InvalidValue(
+                "hstore keys can't be NULL".into(),
+            ));
+        }
+        let (key, rest) = split_string(rest, len)?;
+
+        let (len, rest) = split_i32(rest, "hstore")?;
+        let (value, rest) = if len < 0 {
+            (serde_json::Value::Null, rest)
+        } else {
+            let (value, rest) = split_string(rest, len)?;
+            (serde_json::Value::String(value), rest)
+        };
+
+        map.insert(key, value);
+        bytes = rest;
+    }
+
+    Ok(Cell::Json(serde_json::Value::Object(map)))
+}
+
+/// Splits the big endian `i32` at the start of `bytes` of a value of type `type_name`.
+fn split_i32<'a>(bytes: &'a [u8], type_name: &str) -> Result<(i32, &'a [u8]), FromBinaryError> {
+    let (value, rest) = bytes.split_first_chunk().ok_or_else(|| {
+        FromBinaryError::InvalidValue(format!("truncated {type_name} value").into())
+    })?;
+
+    Ok((i32::from_be_bytes(*value), rest))
+}
+
+/// Reads a `json` or `jsonb` value as its text, without parsing it.
+///
+/// The binary format of `jsonb` is its text preceded by a version byte.
+#[cfg(feature = "json_as_string")]
+fn json_to_string(typ: &Type, bytes: &[u8]) -> Result<String, FromBinaryError> {
+    let bytes = match (typ, bytes.split_first()) {
+        (&Type::JSONB, Some((&1, rest))) => rest,
+        (&Type::JSONB, _) => {
+            return Err(FromBinaryError::InvalidValue(
+                "unsupported jsonb version".into(),
+            ));
+        }
+        _ => bytes,
+    };
+
+    Ok(str::from_utf8(bytes)?.to_string())
+}
+
+/// The text of a `json` or `jsonb` element of an array.
+#[cfg(feature = "json_as_string")]
+struct RawJson(String);
+
+#[cfg(feature = "json_as_string")]
+impl<'a> FromSql<'a> for RawJson {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(RawJson(json_to_string(ty, raw)?))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, Type::JSON | Type::JSONB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn scalar_values_are_decoded() {
+        let cell = BinaryFormatConverter::try_from_bytes(&Type::INT8, &42i64.to_be_bytes());
+        assert_eq!(cell.unwrap(), Cell::I64(42));
+
+        let cell = BinaryFormatConverter::try_from_bytes(&Type::FLOAT8, &0.1f64.to_be_bytes());
+        assert_eq!(cell.unwrap(), Cell::F64(0.1));
+
+        let cell = BinaryFormatConverter::try_from_bytes(&Type::TEXT, "a\tb".as_bytes());
+        assert_eq!(cell.unwrap(), Cell::String("a\tb".to_string()));
+
+        let cell = BinaryFormatConverter::try_from_bytes(&Type::BYTEA, &[0, 1, 255]);
+        assert_eq!(cell.unwrap(), Cell::Bytes(vec![0, 1, 255]));
+
+        // The microseconds since 2000-01-01 00:00:00.
+        let cell =
+            BinaryFormatConverter::try_from_bytes(&Type::TIMESTAMP, &1_000_001i64.to_be_bytes());
+        assert_eq!(
+            cell.unwrap(),
+            Cell::TimeStamp(
+                NaiveDate::from_ymd_opt(2000, 1, 1)
+                    .unwrap()
+                    .and_hms_micro_opt(0, 0, 1, 1)
+                    .unwrap()
+            )
+        );
+
+        let cell =
+            BinaryFormatConverter::try_from_bytes(&Type::PG_LSN, &0x16_B374_D848u64.to_be_bytes());
+        assert_eq!(cell.unwrap(), Cell::String("16/B374D848".to_string()));
+
+        // The microseconds, days and months of the interval.
+        let bytes = [
+            (-1_500_000i64).to_be_bytes().as_slice(),
+            &3i32.to_be_bytes(),
+            &14i32.to_be_bytes(),
+        ]
+        .concat();
+        let cell = BinaryFormatConverter::try_from_bytes(&Type::INTERVAL, &bytes);
+        assert_eq!(
+            cell.unwrap(),
+            Cell::Interval(PgInterval::new(14, 3, -1_500_000))
+        );
+    }
+
+    #[test]
+    fn numerics_keep_all_their_digits() {
+        // 12345678.900000000000000001, as groups of 4 digits: 1234 5678 . 9000 0000 0000 0000 0100
+        let mut bytes = vec![];
+        for value in [7u16, 1, 0, 18] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        for digit in [1234u16, 5678, 9000, 0, 0, 0, 100] {
+            bytes.extend_from_slice(&digit.to_be_bytes());
+        }
+
+        let cell = BinaryFormatConverter::try_from_bytes(&Type::NUMERIC, &bytes).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Numeric(PgNumeric::Value(
+                BigDecimal::from_str("12345678.900000000000000001").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn arrays_with_nulls_are_decoded() {
+        // One dimension, with nulls, of 3 int4 elements starting at index 1.
+        let mut bytes = vec![];
+        for value in [1i32, 1, Type::INT4.oid() as i32, 3, 1] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes.extend_from_slice(&4i32.to_be_bytes());
+        bytes.extend_from_slice(&7i32.to_be_bytes());
+        bytes.extend_from_slice(&(-1i32).to_be_bytes());
+        bytes.extend_from_slice(&4i32.to_be_bytes());
+        bytes.extend_from_slice(&(-8i32).to_be_bytes());
+
+        let cell = BinaryFormatConverter::try_from_bytes(&Type::INT4_ARRAY, &bytes).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::I32(vec![Some(7), None, Some(-8)]))
+        );
+    }
+
+    #[test]
+    fn composites_are_decoded_by_field() {
+        let typ = Type::new(
+            "point2".to_string(),
+            100_000,
+            Kind::Composite(vec![
+                Field::new("x".to_string(), Type::INT4),
+                Field::new("label".to_string(), Type::TEXT),
+            ]),
+            "public".to_string(),
+        );
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&2i32.to_be_bytes());
+        bytes.extend_from_slice(&(Type::INT4.oid() as i32).to_be_bytes());
+        bytes.extend_from_slice(&4i32.to_be_bytes());
+        bytes.extend_from_slice(&7i32.to_be_bytes());
+        bytes.extend_from_slice(&(Type::TEXT.oid() as i32).to_be_bytes());
+        bytes.extend_from_slice(&(-1i32).to_be_bytes());
+
+        let cell = BinaryFormatConverter::try_from_bytes(&typ, &bytes).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Composite(vec![Cell::I32(7), Cell::Null(Type::TEXT)])
+        );
+
+        assert!(BinaryFormatConverter::try_from_bytes(&typ, &bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn hstores_are_decoded_into_json_objects() {
+        let typ = Type::new(
+            "hstore".to_string(),
+            16_385,
+            Kind::Simple,
+            "public".to_string(),
+        );
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&2i32.to_be_bytes());
+        for (value, len) in [("a=>b", 4i32), ("1", 1), ("c", 1)] {
+            bytes.extend_from_slice(&len.to_be_bytes());
+            bytes.extend_from_slice(value.as_bytes());
+        }
+        bytes.extend_from_slice(&(-1i32).to_be_bytes());
+
+        let cell = BinaryFormatConverter::try_from_bytes(&typ, &bytes).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Json(serde_json::json!({"a=>b": "1", "c": null}))
+        );
+
+        assert!(BinaryFormatConverter::try_from_bytes(&typ, &bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn geometries_are_kept_as_their_ewkb() {
+        let typ = Type::new(
+            "geometry".to_string(),
+            17_001,
+            Kind::Simple,
+            "public".to_string(),
+        );
+        // `POLYGON((0 0,1 0,1 1,0 0))`, with one ring of four points.
+        let mut ewkb = vec![0x01, 0x03, 0x00, 0x00, 0x00];
+        ewkb.extend_from_slice(&1u32.to_le_bytes());
+        ewkb.extend_from_slice(&4u32.to_le_bytes());
+        for (x, y) in [(0f64, 0f64), (1., 0.), (1., 1.), (0., 0.)] {
+            ewkb.extend_from_slice(&x.to_le_bytes());
+            ewkb.extend_from_slice(&y.to_le_bytes());
+        }
+
+        let cell = BinaryFormatConverter::try_from_bytes(&typ, &ewkb).unwrap();
+        assert_eq!(cell, Cell::Geometry(ewkb));
+
+        assert!(BinaryFormatConverter::try_from_bytes(&typ, b"POINT(1 2)").is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(BinaryFormatConverter::try_from_bytes(&Type::INT4, &[0, 1]).is_err());
+        assert!(BinaryFormatConverter::try_from_bytes(&Type::TEXT, &[0xff, 0xfe]).is_err());
+    }
+}
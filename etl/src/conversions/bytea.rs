@@ -0,0 +1,187 @@
+use thiserror::Error;
+
+use super::hex::{ByteaHexParseError, from_bytea_hex};
+
+#[derive(Debug, Error)]
+pub enum ByteaEscapeParseError {
+    #[error("unterminated escape sequence")]
+    UnterminatedEscape,
+
+    #[error("invalid escape sequence '\\{0}'")]
+    InvalidEscape(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ByteaParseError {
+    #[error("invalid hex bytea: {0}")]
+    Hex(#[from] ByteaHexParseError),
+
+    #[error("invalid escape bytea: {0}")]
+    Escape(#[from] ByteaEscapeParseError),
+}
+
+/// The text formats Postgres can emit `bytea` values in, selected by the `bytea_output` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteaOutput {
+    /// `\x` followed by two hex digits per byte, the Postgres default.
+    Hex,
+    /// Printable ASCII bytes as themselves, `\\` for a backslash and `\nnn` octal escapes for
+    /// all other bytes.
+    Escape,
+}
+
+impl ByteaOutput {
+    /// Detects the format of a `bytea` text value from its `\x` prefix.
+    ///
+    /// An escape formatted value can't start with `\x`, since a literal backslash is always
+    /// escaped as `\\`, so the detection is unambiguous.
+    pub fn detect(s: &str) -> ByteaOutput {
+        if s.starts_with("\\x") {
+            ByteaOutput::Hex
+        } else {
+            ByteaOutput::Escape
+        }
+    }
+}
+
+/// Parses a `bytea` text value in the given format.
+pub fn from_bytea_with_output(s: &str, output: ByteaOutput) -> Result<Vec<u8>, ByteaParseError> {
+    match output {
+        ByteaOutput::Hex => Ok(from_bytea_hex(s)?),
+        ByteaOutput::Escape => Ok(from_bytea_escape(s)?),
+    }
+}
+
+/// Parses a `bytea` text value, auto-detecting whether it is in the hex or escape format.
+///
+/// The replication connections always set `bytea_output` to `hex`, the detection is there for
+/// values coming from elsewhere.
+pub fn from_bytea(s: &str) -> Result<Vec<u8>, ByteaParseError> {
+    from_bytea_with_output(s, ByteaOutput::detect(s))
+}
+
+/// Parses a `bytea` text value in the escape format.
+pub fn from_bytea_escape(s: &str) -> Result<Vec<u8>, ByteaEscapeParseError> {
+    let bytes = s.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            result.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+
+        match bytes.get(i + 1) {
+            None => return Err(ByteaEscapeParseError::UnterminatedEscape),
+            Some(b'\\') => {
+                result.push(b'\\');
+                i += 2;
+            }
+            Some(_) => {
+                let Some(digits) = bytes.get(i + 1..i + 4) else {
+                    return Err(ByteaEscapeParseError::UnterminatedEscape);
+                };
+                let is_octal = matches!(digits[0], b'0'..=b'3')
+                    && digits[1..].iter().all(|d| matches!(d, b'0'..=b'7'));
+                if !is_octal {
+                    return Err(ByteaEscapeParseError::InvalidEscape(
+                        String::from_utf8_lossy(digits).into_owned(),
+                    ));
+                }
+
+                let value = digits
+                    .iter()
+                    .fold(0u8, |value, digit| (value << 3) | (digit - b'0'));
+                result.push(value);
+                i += 4;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BYTES: &[u8] = &[0x00, b'a', b'\\', b'Z', 0x7f, 0xde, 0xad, b' ', 0xff];
+
+    const HEX: &str = "\\x00615c5a7fdead20ff";
+
+    const ESCAPE: &str = "\\000a\\\\Z\\177\\336\\255 \\377";
+
+    #[test]
+    fn decodes_hex_and_escape_to_the_same_bytes() {
+        assert_eq!(
+            from_bytea_with_output(HEX, ByteaOutput::Hex).unwrap(),
+            BYTES
+        );
+        assert_eq!(
+            from_bytea_with_output(ESCAPE, ByteaOutput::Escape).unwrap(),
+            BYTES
+        );
+    }
+
+    #[test]
+    fn detects_format() {
+        assert_eq!(ByteaOutput::detect(HEX), ByteaOutput::Hex);
+        assert_eq!(ByteaOutput::detect(ESCAPE), ByteaOutput::Escape);
+        assert_eq!(from_bytea(HEX).unwrap(), BYTES);
+        assert_eq!(from_bytea(ESCAPE).unwrap(), BYTES);
+    }
+
+    #[test]
+    fn empty_values() {
+        assert_eq!(from_bytea("\\x").unwrap(), Vec::<u8>::new());
+        assert_eq!(from_bytea("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn escape_without_escapes_is_taken_verbatim() {
+        assert_eq!(from_bytea("hello").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn invalid_escapes_are_rejected() {
+        assert!(matches!(
+            from_bytea_escape("abc\\"),
+            Err(ByteaEscapeParseError::UnterminatedEscape)
+        ));
+        assert!(matches!(
+            from_bytea_escape("\\12"),
+            Err(ByteaEscapeParseError::UnterminatedEscape)
+        ));
+        assert!(matches!(
+            from_bytea_escape("\\400"),
+            Err(ByteaEscapeParseError::InvalidEscape(_))
+        ));
+        assert!(matches!(
+            from_bytea_escape("\\n00"),
+            Err(ByteaEscapeParseError::InvalidEscape(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_hex_digits_are_rejected() {
+        assert!(matches!(
+            from_bytea("\\x0"),
+            Err(ByteaParseError::Hex(ByteaHexParseError::OddNumerOfDigits))
+        ));
+        assert!(matches!(
+            from_bytea("\\x+f"),
+            Err(ByteaParseError::Hex(ByteaHexParseError::InvalidDigit(2)))
+        ));
+        assert!(matches!(
+            from_bytea("\\x0g"),
+            Err(ByteaParseError::Hex(ByteaHexParseError::InvalidDigit(3)))
+        ));
+        // A multibyte character must not be split into hex digits.
+        assert!(matches!(
+            from_bytea("\\xaé0"),
+            Err(ByteaParseError::Hex(ByteaHexParseError::InvalidDigit(3)))
+        ));
+    }
+}
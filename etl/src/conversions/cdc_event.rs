@@ -52,7 +52,7 @@ impl CdcEventConverter {
                 // In case of a null value, we store the type information since that will be used to
                 // correctly compute default values when needed.
                 TupleData::Null => Cell::Null(column_schema.typ.clone()),
-                TupleData::UnchangedToast => TextFormatConverter::default_value(&column_schema.typ),
+                TupleData::UnchangedToast => Cell::UnchangedToast(column_schema.typ.clone()),
                 TupleData::Binary(_) => {
                     return Err(CdcEventConversionError::BinaryFormatNotSupported);
                 }
@@ -0,0 +1,319 @@
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{BigDecimal, FromPrimitive};
+use chrono::{NaiveDateTime, NaiveTime};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+use crate::conversions::Cell;
+use crate::conversions::numeric::PgNumeric;
+
+/// A numeric cell value, widened so that values of different numeric variants can be compared.
+enum Number<'a> {
+    Integer(i128),
+    Float(f64),
+    Numeric(&'a PgNumeric),
+}
+
+impl Number<'_> {
+    fn from_cell(cell: &Cell) -> Option<Number<'_>> {
+        match cell {
+            Cell::I16(i) => Some(Number::Integer(*i as i128)),
+            Cell::I32(i) => Some(Number::Integer(*i as i128)),
+            Cell::U32(i) => Some(Number::Integer(*i as i128)),
+            Cell::I64(i) => Some(Number::Integer(*i as i128)),
+            Cell::F32(f) => Some(Number::Float(*f as f64)),
+            Cell::F64(f) => Some(Number::Float(*f)),
+            Cell::Numeric(n) => Some(Number::Numeric(n)),
+            _ => None,
+        }
+    }
+
+    /// Converts the number to a [`PgNumeric`], which represents every number exactly.
+    fn to_numeric(&self) -> PgNumeric {
+        match self {
+            Number::Integer(i) => PgNumeric::Value(BigDecimal::from(*i)),
+            Number::Float(f) if f.is_nan() => PgNumeric::NaN,
+            Number::Float(f) if *f == f64::INFINITY => PgNumeric::PositiveInf,
+            Number::Float(f) if *f == f64::NEG_INFINITY => PgNumeric::NegativeInf,
+            Number::Float(f) => PgNumeric::Value(
+                BigDecimal::from_f64(*f).expect("finite floats are representable as decimals"),
+            ),
+            Number::Numeric(n) => (*n).clone(),
+        }
+    }
+}
+
+/// Compares floats like Postgres does: `NaN` is equal to itself and greater than any other value.
+fn cmp_floats(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a
+            .partial_cmp(&b)
+            .expect("floats which are not NaN are ordered"),
+    }
+}
+
+/// Compares numerics like Postgres does: `-Infinity` < values < `Infinity` < `NaN`.
+fn cmp_numerics(a: &PgNumeric, b: &PgNumeric) -> Ordering {
+    fn rank(n: &PgNumeric) -> u8 {
+        match n {
+            PgNumeric::NegativeInf => 0,
+            PgNumeric::Value(_) => 1,
+            PgNumeric::PositiveInf => 2,
+            PgNumeric::NaN => 3,
+        }
+    }
+
+    match (a, b) {
+        (PgNumeric::Value(a), PgNumeric::Value(b)) => a.cmp(b),
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn cmp_numbers(a: &Number<'_>, b: &Number<'_>) -> Ordering {
+    match (a, b) {
+        (Number::Integer(a), Number::Integer(b)) => a.cmp(b),
+        (Number::Float(a), Number::Float(b)) => cmp_floats(*a, *b),
+        (a, b) => cmp_numerics(&a.to_numeric(), &b.to_numeric()),
+    }
+}
+
+/// Hashes a number so that numbers which compare equal across variants hash the same.
+///
+/// Integral values are hashed as integers and all other values in their normalized decimal
+/// form, so that e.g. `I32(1)`, `F64(1.0)` and `Numeric(1.00)` share the same hash.
+fn hash_number<H: Hasher>(number: &Number<'_>, state: &mut H) {
+    let value = match number {
+        Number::Integer(i) => {
+            0u8.hash(state);
+            i.hash(state);
+            return;
+        }
+        number => match number.to_numeric() {
+            PgNumeric::Value(value) => value,
+            PgNumeric::NaN => return 2u8.hash(state),
+            PgNumeric::PositiveInf => return 3u8.hash(state),
+            PgNumeric::NegativeInf => return 4u8.hash(state),
+        },
+    };
+
+    let (digits, scale) = value.normalized().into_bigint_and_exponent();
+    if scale <= 0 {
+        let integer = digits * BigInt::from(10).pow(scale.unsigned_abs() as u32);
+        if let Ok(integer) = i128::try_from(&integer) {
+            0u8.hash(state);
+            integer.hash(state);
+            return;
+        }
+    }
+
+    1u8.hash(state);
+    digits.hash(state);
+    scale.hash(state);
+}
+
+/// Returns the timestamp of date and timestamp cells, since a date compares to a timestamp as
+/// its midnight, as it does in Postgres.
+fn as_timestamp(cell: &Cell) -> Option<NaiveDateTime> {
+    match cell {
+        Cell::Date(date) => Some(date.and_time(NaiveTime::default())),
+        Cell::TimeStamp(timestamp) => Some(*timestamp),
+        _ => None,
+    }
+}
+
+impl Cell {
+    /// Compares the values of two cells, returning `None` when they are not comparable.
+    ///
+    /// Numbers (integers, floats and numerics) compare to each other by their exact value, dates
+    /// and timestamps compare to each other, and strings, booleans, times, timestamps with time
+    /// zone, uuids and bytes compare to values of the same variant. Strings and bytes are compared
+    /// bytewise, like with the `C` collation. Any other combination is not comparable.
+    ///
+    /// Like in Postgres, `NaN` equals itself and is greater than any other number.
+    ///
+    /// A `NULL` is not comparable to anything, including another `NULL`, following the SQL
+    /// semantics where comparing with `NULL` yields `NULL`. Callers which need a total order,
+    /// e.g. for range checkpoints, must decide where `NULL`s go; Postgres sorts them after every
+    /// other value in ascending order by default.
+    pub fn cmp_value(&self, other: &Cell) -> Option<Ordering> {
+        if let (Some(a), Some(b)) = (Number::from_cell(self), Number::from_cell(other)) {
+            return Some(cmp_numbers(&a, &b));
+        }
+
+        if let (Some(a), Some(b)) = (as_timestamp(self), as_timestamp(other)) {
+            return Some(a.cmp(&b));
+        }
+
+        match (self, other) {
+            (Cell::Bool(a), Cell::Bool(b)) => Some(a.cmp(b)),
+            (Cell::String(a), Cell::String(b)) => Some(a.cmp(b)),
+            (Cell::Time(a), Cell::Time(b)) => Some(a.cmp(b)),
+            (Cell::TimeStampTz(a), Cell::TimeStampTz(b)) => Some(a.cmp(b)),
+            (Cell::Uuid(a), Cell::Uuid(b)) => Some(a.cmp(b)),
+            (Cell::Bytes(a), Cell::Bytes(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    /// Feeds the value of the cell into `state`, for building dedup maps keyed by cells.
+    ///
+    /// Cells for which [`Cell::cmp_value`] returns [`Ordering::Equal`] hash the same, even across
+    /// variants. `NULL`s all hash the same, regardless of their type. Arrays are only hashed by
+    /// their element type, which is correct but makes them poor hash keys.
+    pub fn hash_value<H: Hasher>(&self, state: &mut H) {
+        if let Some(number) = Number::from_cell(self) {
+            return hash_number(&number, state);
+        }
+
+        if let Some(timestamp) = as_timestamp(self) {
+            mem::discriminant(&Cell::TimeStamp(timestamp)).hash(state);
+            return timestamp.hash(state);
+        }
+
+        mem::discriminant(self).hash(state);
+        match self {
+            Cell::Bool(b) => b.hash(state),
+            Cell::String(s) => s.hash(state),
+            Cell::Time(t) => t.hash(state),
+            Cell::TimeStampTz(t) => t.hash(state),
+            Cell::Uuid(u) => u.hash(state),
+            Cell::Json(j) => j.to_string().hash(state),
+            Cell::Bytes(b) | Cell::Geometry(b) => b.hash(state),
+            Cell::Array(a) => mem::discriminant(a).hash(state),
+            Cell::Enum { type_name, label } => (type_name, label).hash(state),
+            // Nulls only hash their discriminant, while numbers, dates and timestamps were
+            // handled above.
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::collections::hash_map::DefaultHasher;
+    use std::str::FromStr;
+    use tokio_postgres::types::Type;
+
+    fn hash(cell: &Cell) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        cell.hash_value(&mut hasher);
+        hasher.finish()
+    }
+
+    fn numeric(s: &str) -> Cell {
+        Cell::Numeric(PgNumeric::from_str(s).unwrap())
+    }
+
+    #[test]
+    fn numbers_compare_across_variants() {
+        let cases = [
+            (Cell::I16(1), Cell::I64(1), Ordering::Equal),
+            (Cell::I32(-1), Cell::U32(0), Ordering::Less),
+            (Cell::I64(i64::MAX), Cell::U32(u32::MAX), Ordering::Greater),
+            (Cell::I32(1), Cell::F64(1.0), Ordering::Equal),
+            (Cell::I32(1), Cell::F32(1.5), Ordering::Less),
+            (Cell::F64(2.5), numeric("2.50"), Ordering::Equal),
+            (Cell::I64(3), numeric("2.99"), Ordering::Greater),
+            (Cell::F64(f64::NAN), Cell::F64(f64::NAN), Ordering::Equal),
+            (
+                Cell::F64(f64::NAN),
+                Cell::F64(f64::INFINITY),
+                Ordering::Greater,
+            ),
+            (numeric("NaN"), Cell::F32(f32::NAN), Ordering::Equal),
+            (numeric("-Infinity"), Cell::I64(i64::MIN), Ordering::Less),
+            (
+                numeric("Infinity"),
+                Cell::F64(f64::INFINITY),
+                Ordering::Equal,
+            ),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(a.cmp_value(&b), Some(expected), "{a:?} vs {b:?}");
+            assert_eq!(b.cmp_value(&a), Some(expected.reverse()), "{b:?} vs {a:?}");
+        }
+    }
+
+    #[test]
+    fn dates_compare_to_timestamps() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+        let noon = date.and_hms_opt(12, 0, 0).unwrap();
+
+        assert_eq!(
+            Cell::Date(date).cmp_value(&Cell::TimeStamp(midnight)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            Cell::Date(date).cmp_value(&Cell::TimeStamp(noon)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(hash(&Cell::Date(date)), hash(&Cell::TimeStamp(midnight)));
+    }
+
+    #[test]
+    fn strings_compare_bytewise() {
+        let a = Cell::String("a".to_string());
+        let b = Cell::String("B".to_string());
+
+        assert_eq!(a.cmp_value(&b), Some(Ordering::Greater));
+        assert_eq!(a.cmp_value(&a.clone()), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn incomparable_variants_return_none() {
+        assert_eq!(Cell::String("1".to_string()).cmp_value(&Cell::I32(1)), None);
+        assert_eq!(Cell::Bool(true).cmp_value(&Cell::I32(1)), None);
+        assert_eq!(
+            Cell::Json(serde_json::json!(1)).cmp_value(&Cell::Json(serde_json::json!(1))),
+            None
+        );
+    }
+
+    #[test]
+    fn nulls_are_not_comparable() {
+        let null = Cell::Null(Type::INT4);
+
+        assert_eq!(null.cmp_value(&Cell::I32(1)), None);
+        assert_eq!(Cell::I32(1).cmp_value(&null), None);
+        assert_eq!(null.cmp_value(&Cell::Null(Type::INT4)), None);
+        assert_eq!(hash(&null), hash(&Cell::Null(Type::TEXT)));
+    }
+
+    #[test]
+    fn equal_numbers_hash_the_same() {
+        let equal_groups = [
+            vec![
+                Cell::I16(1),
+                Cell::I32(1),
+                Cell::U32(1),
+                Cell::I64(1),
+                Cell::F32(1.0),
+                Cell::F64(1.0),
+                numeric("1.000"),
+            ],
+            vec![Cell::F64(0.0), Cell::F64(-0.0), numeric("0"), Cell::I32(0)],
+            vec![Cell::F64(2.5), numeric("2.50")],
+            vec![Cell::F64(f64::NAN), numeric("NaN")],
+        ];
+
+        for group in equal_groups {
+            for a in &group {
+                for b in &group {
+                    assert_eq!(a.cmp_value(b), Some(Ordering::Equal), "{a:?} vs {b:?}");
+                    assert_eq!(hash(a), hash(b), "{a:?} vs {b:?}");
+                }
+            }
+        }
+
+        assert_ne!(hash(&Cell::I32(1)), hash(&Cell::I32(2)));
+        assert_ne!(hash(&Cell::I32(1)), hash(&Cell::String("1".to_string())));
+    }
+}
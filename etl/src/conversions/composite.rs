@@ -0,0 +1,238 @@
+use std::mem;
+use thiserror::Error;
+use tokio_postgres::types::Field;
+
+use crate::conversions::Cell;
+use crate::conversions::text::{FromTextError, TextFormatConverter};
+
+#[derive(Debug, Error)]
+pub enum CompositeParseError {
+    #[error("missing parentheses")]
+    MissingParentheses,
+
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    #[error("expected {expected} fields but found {actual}")]
+    NumFieldsMismatch { expected: usize, actual: usize },
+
+    #[error("invalid value of field `{field}`: {source}")]
+    InvalidField {
+        field: String,
+        source: Box<FromTextError>,
+    },
+}
+
+/// Parses a value of a composite type in the text format of Postgres, e.g. `(1,"a b",)`, into a
+/// [`Cell::Composite`] holding a cell per field of the type.
+///
+/// Fields are separated by commas, and may be quoted with double quotes, in which a double quote
+/// is written twice. A backslash escapes the following character, whether quoted or not. An empty
+/// unquoted field is a `NULL`, while `""` is an empty string. The fields of nested composites are
+/// quoted values which are parsed recursively.
+pub fn parse_composite(str: &str, fields: &[Field]) -> Result<Cell, FromTextError> {
+    let str = str
+        .strip_prefix('(')
+        .and_then(|str| str.strip_suffix(')'))
+        .ok_or(CompositeParseError::MissingParentheses)?;
+
+    // A composite without fields is written `()`, which would otherwise be read as a single
+    // `NULL` field.
+    let values = if fields.is_empty() && str.is_empty() {
+        vec![]
+    } else {
+        split_fields(str)?
+    };
+    if values.len() != fields.len() {
+        return Err(CompositeParseError::NumFieldsMismatch {
+            expected: fields.len(),
+            actual: values.len(),
+        }
+        .into());
+    }
+
+    let cells = fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| match value {
+            Some(value) => TextFormatConverter::try_from_str(field.type_(), &value).map_err(|e| {
+                CompositeParseError::InvalidField {
+                    field: field.name().to_string(),
+                    source: Box::new(e),
+                }
+                .into()
+            }),
+            None => Ok(Cell::Null(field.type_().clone())),
+        })
+        .collect::<Result<Vec<_>, FromTextError>>()?;
+
+    Ok(Cell::Composite(cells))
+}
+
+/// Splits the fields of a composite value stripped of its parentheses, unquoting and unescaping
+/// them, with `None` for `NULL`s.
+fn split_fields(str: &str) -> Result<Vec<Option<String>>, CompositeParseError> {
+    let mut values = vec![];
+    let mut value = String::new();
+    let mut is_null = true;
+    let mut in_quotes = false;
+    let mut chars = str.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    value.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' => {
+                in_quotes = true;
+                is_null = false;
+            }
+            '\\' => {
+                let c = chars.next().ok_or(CompositeParseError::UnexpectedEnd)?;
+                value.push(c);
+                is_null = false;
+            }
+            ',' if !in_quotes => {
+                values.push((!is_null).then(|| mem::take(&mut value)));
+                is_null = true;
+            }
+            c => {
+                value.push(c);
+                is_null = false;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(CompositeParseError::UnexpectedEnd);
+    }
+    values.push((!is_null).then_some(value));
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::{Kind, Type};
+
+    fn address_fields() -> Vec<Field> {
+        vec![
+            Field::new("street".to_string(), Type::TEXT),
+            Field::new("number".to_string(), Type::INT4),
+            Field::new("city".to_string(), Type::TEXT),
+        ]
+    }
+
+    #[test]
+    fn composites_are_parsed_by_field() {
+        let cell = parse_composite(r#"("Main St, North",12,Paris)"#, &address_fields()).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Composite(vec![
+                Cell::String("Main St, North".to_string()),
+                Cell::I32(12),
+                Cell::String("Paris".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_fields_are_nulls_and_quoted_ones_are_empty_strings() {
+        let cell = parse_composite(r#"(,,"")"#, &address_fields()).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Composite(vec![
+                Cell::Null(Type::TEXT),
+                Cell::Null(Type::INT4),
+                Cell::String(String::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn quotes_and_backslashes_are_unescaped() {
+        let cell = parse_composite(r#"("say ""hi""",1,a\,b\\c)"#, &address_fields()).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Composite(vec![
+                Cell::String(r#"say "hi""#.to_string()),
+                Cell::I32(1),
+                Cell::String(r"a,b\c".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn nested_composites_are_parsed() {
+        let address = Type::new(
+            "address".to_string(),
+            100_000,
+            Kind::Composite(address_fields()),
+            "public".to_string(),
+        );
+        let fields = vec![
+            Field::new("name".to_string(), Type::TEXT),
+            Field::new("address".to_string(), address),
+        ];
+
+        let cell = parse_composite(r#"(Ann,"(""1 Rue A"",3,)")"#, &fields).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Composite(vec![
+                Cell::String("Ann".to_string()),
+                Cell::Composite(vec![
+                    Cell::String("1 Rue A".to_string()),
+                    Cell::I32(3),
+                    Cell::Null(Type::TEXT),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn composites_are_converted_into_json_arrays() {
+        let cell = parse_composite(r#"("a ""b""",,Paris)"#, &address_fields()).unwrap();
+        assert_eq!(
+            cell.to_json(),
+            serde_json::json!([r#"a "b""#, null, "Paris"])
+        );
+    }
+
+    #[test]
+    fn malformed_composites_are_rejected() {
+        let fields = address_fields();
+
+        assert!(matches!(
+            parse_composite("a,1,b", &fields),
+            Err(FromTextError::InvalidComposite(
+                CompositeParseError::MissingParentheses
+            ))
+        ));
+        assert!(matches!(
+            parse_composite("(a,1)", &fields),
+            Err(FromTextError::InvalidComposite(
+                CompositeParseError::NumFieldsMismatch {
+                    expected: 3,
+                    actual: 2
+                }
+            ))
+        ));
+        assert!(matches!(
+            parse_composite(r#"("a,1,b)"#, &fields),
+            Err(FromTextError::InvalidComposite(
+                CompositeParseError::UnexpectedEnd
+            ))
+        ));
+        assert!(matches!(
+            parse_composite("(a,one,b)", &fields),
+            Err(FromTextError::InvalidComposite(CompositeParseError::InvalidField { field, .. }))
+                if field == "number"
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+use thiserror::Error;
+use tokio_postgres::types::{Kind, Type};
+
+use super::Cell;
+
+#[derive(Debug, Error)]
+#[error("`{label}` is not a label of the enum type {type_name}")]
+pub struct EnumParseError {
+    pub type_name: String,
+    pub label: String,
+}
+
+/// Returns whether `typ` is an enum type.
+///
+/// Enum types are user defined, so they are recognized by their kind, as looked up along with the
+/// schema of the table.
+pub fn is_enum(typ: &Type) -> bool {
+    matches!(typ.kind(), Kind::Enum(_))
+}
+
+/// Parses a value of the enum type `typ` from its label, which is both its text and binary
+/// format, into a [`Cell::Enum`].
+///
+/// The label is checked against the labels of the type, unless none were looked up.
+pub fn parse_enum(typ: &Type, label: &str) -> Result<Cell, EnumParseError> {
+    if let Kind::Enum(labels) = typ.kind()
+        && !labels.is_empty()
+        && !labels.iter().any(|known_label| known_label == label)
+    {
+        return Err(EnumParseError {
+            type_name: typ.name().to_string(),
+            label: label.to_string(),
+        });
+    }
+
+    Ok(Cell::Enum {
+        type_name: typ.name().to_string(),
+        label: label.to_string(),
+    })
+}
+
+/// Returns the default value of the enum type `typ`, which is its first label in sort order, or
+/// an empty label when the labels of the type weren't looked up.
+pub fn default_enum(typ: &Type) -> Cell {
+    let label = match typ.kind() {
+        Kind::Enum(labels) => labels.first().cloned().unwrap_or_default(),
+        _ => String::new(),
+    };
+
+    Cell::Enum {
+        type_name: typ.name().to_string(),
+        label,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mood() -> Type {
+        Type::new(
+            "mood".to_string(),
+            16_390,
+            Kind::Enum(vec![
+                "sad".to_string(),
+                "ok".to_string(),
+                "happy".to_string(),
+            ]),
+            "public".to_string(),
+        )
+    }
+
+    #[test]
+    fn known_labels_are_parsed_into_enums() {
+        assert_eq!(
+            parse_enum(&mood(), "ok").unwrap(),
+            Cell::Enum {
+                type_name: "mood".to_string(),
+                label: "ok".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_labels_are_rejected() {
+        let err = parse_enum(&mood(), "angry").unwrap_err();
+
+        assert_eq!(err.type_name, "mood");
+        assert_eq!(err.label, "angry");
+    }
+
+    #[test]
+    fn labels_are_not_checked_when_none_are_known() {
+        let typ = Type::new(
+            "mood".to_string(),
+            16_390,
+            Kind::Enum(vec![]),
+            "public".to_string(),
+        );
+
+        assert!(parse_enum(&typ, "angry").is_ok());
+    }
+
+    #[test]
+    fn the_default_value_is_the_first_label() {
+        assert_eq!(
+            default_enum(&mood()),
+            Cell::Enum {
+                type_name: "mood".to_string(),
+                label: "sad".to_string()
+            }
+        );
+        assert!(!is_enum(&Type::TEXT));
+    }
+}
@@ -3,6 +3,7 @@ use crate::conversions::table_row::TableRow;
 use crate::conversions::text::{FromTextError, TextFormatConverter};
 use crate::schema::cache::SchemaCache;
 use crate::state::store::base::StateStoreError;
+use config::shared::NullPolicy;
 use core::str;
 use postgres::schema::{ColumnSchema, TableId, TableName, TableSchema};
 use postgres::types::convert_type_oid_to_type;
@@ -144,11 +145,34 @@ pub struct DeleteEvent {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TruncateEvent {
+    /// The options of the `TRUNCATE`, as read by [`TruncateEvent::cascade`] and
+    /// [`TruncateEvent::restart_identity`].
     pub options: i8,
+    /// The ids of the truncated tables which are replicated by the pipeline.
     pub rel_ids: Vec<u32>,
 }
 
 impl TruncateEvent {
+    /// The bit of the options set by `TRUNCATE ... CASCADE`.
+    const CASCADE: i8 = 1;
+    /// The bit of the options set by `TRUNCATE ... RESTART IDENTITY`.
+    const RESTART_IDENTITY: i8 = 2;
+
+    /// Returns whether the tables were truncated with `CASCADE`.
+    ///
+    /// The tables truncated because they reference the truncated ones are part of
+    /// [`TruncateEvent::rel_ids`] too, if they are replicated, so destinations don't need to
+    /// follow the references themselves.
+    pub fn cascade(&self) -> bool {
+        self.options & Self::CASCADE != 0
+    }
+
+    /// Returns whether the sequences owned by the columns of the tables were restarted, with
+    /// `RESTART IDENTITY`.
+    pub fn restart_identity(&self) -> bool {
+        self.options & Self::RESTART_IDENTITY != 0
+    }
+
     pub fn from_protocol(truncate_body: &protocol::TruncateBody) -> Self {
         Self {
             options: truncate_body.options(),
@@ -162,6 +186,42 @@ pub struct KeepAliveEvent {
     pub reply: bool,
 }
 
+/// A change to the set of replicated tables or to their schemas, emitted when
+/// [`PipelineConfig::emit_metadata_events`](config::shared::PipelineConfig::emit_metadata_events)
+/// is enabled, or the value of a sequence, emitted when
+/// [`PipelineConfig::sequence_sync_interval_ms`](config::shared::PipelineConfig::sequence_sync_interval_ms)
+/// is set.
+///
+/// Metadata events are sent to the destination along with the other events, so that downstream
+/// systems can react to them, while the pipeline keeps acting on the changes as usual.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataEvent {
+    /// A table which isn't replicated by the pipeline started being published, with its schema as
+    /// read from the catalog once the pipeline noticed it, right before the table is copied.
+    TableAdded { table_schema: TableSchema },
+    /// A replicated table is no longer published, e.g. because it was dropped or removed from the
+    /// publication, with its last known schema if any.
+    TableDropped {
+        table_id: TableId,
+        table_schema: Option<TableSchema>,
+    },
+    /// The schema of a replicated table changed. The table keeps being replicated with the new
+    /// schema if the destination supports the change, see
+    /// [`Destination::write_table_schema_change`](crate::destination::base::Destination::write_table_schema_change).
+    SchemaChanged {
+        before: TableSchema,
+        after: TableSchema,
+    },
+    /// The current value of a sequence owned by a column of a replicated table, e.g. the
+    /// sequence of a `serial` or identity column.
+    SequenceValue {
+        table_id: TableId,
+        column_name: String,
+        sequence_name: TableName,
+        last_value: i64,
+    },
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Event {
     Begin(BeginEvent),
@@ -171,9 +231,31 @@ pub enum Event {
     Delete(DeleteEvent),
     Relation(RelationEvent),
     Truncate(TruncateEvent),
+    Metadata(MetadataEvent),
     Unsupported,
 }
 
+impl Event {
+    /// Applies `policy` to the `NULL`s of the rows carried by the event.
+    pub fn apply_null_policy(&mut self, policy: NullPolicy) {
+        match self {
+            Event::Insert(event) => event.table_row.apply_null_policy(policy),
+            Event::Update(event) => {
+                event.table_row.apply_null_policy(policy);
+                if let Some((_, old_table_row)) = &mut event.old_table_row {
+                    old_table_row.apply_null_policy(policy);
+                }
+            }
+            Event::Delete(event) => {
+                if let Some((_, old_table_row)) = &mut event.old_table_row {
+                    old_table_row.apply_null_policy(policy);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EventType {
     Begin,
@@ -183,6 +265,7 @@ pub enum EventType {
     Delete,
     Relation,
     Truncate,
+    Metadata,
     Unsupported,
 }
 
@@ -196,6 +279,7 @@ impl fmt::Display for EventType {
             Self::Delete => write!(f, "Delete"),
             Self::Relation => write!(f, "Relation"),
             Self::Truncate => write!(f, "Truncate"),
+            Self::Metadata => write!(f, "Metadata"),
             Self::Unsupported => write!(f, "Unsupported"),
         }
     }
@@ -211,6 +295,7 @@ impl From<&Event> for EventType {
             Event::Delete(_) => EventType::Delete,
             Event::Relation(_) => EventType::Relation,
             Event::Truncate(_) => EventType::Truncate,
+            Event::Metadata(_) => EventType::Metadata,
             &Event::Unsupported => EventType::Unsupported,
         }
     }
@@ -235,6 +320,7 @@ async fn get_table_schema(
 fn convert_tuple_to_row(
     column_schemas: &[ColumnSchema],
     tuple_data: &[protocol::TupleData],
+    preserve_unsupported_types: bool,
 ) -> Result<TableRow, EventConversionError> {
     let mut values = Vec::with_capacity(column_schemas.len());
 
@@ -252,15 +338,21 @@ fn convert_tuple_to_row(
             // In case of a null value, we store the type information since that will be used to
             // correctly compute default values when needed.
             protocol::TupleData::Null => Cell::Null(column_schema.typ.clone()),
-            protocol::TupleData::UnchangedToast => {
-                TextFormatConverter::default_value(&column_schema.typ)
-            }
+            // Large values which weren't changed by an update aren't sent, and are marked so that
+            // destinations don't overwrite them.
+            protocol::TupleData::UnchangedToast => Cell::UnchangedToast(column_schema.typ.clone()),
             protocol::TupleData::Binary(_) => {
                 return Err(EventConversionError::BinaryFormatNotSupported);
             }
             protocol::TupleData::Text(bytes) => {
                 let str = str::from_utf8(&bytes[..])?;
-                TextFormatConverter::try_from_str(&column_schema.typ, str)?
+                if preserve_unsupported_types
+                    && !TextFormatConverter::is_supported_type(&column_schema.typ)
+                {
+                    Cell::Unsupported(column_schema.typ.clone(), str.to_string())
+                } else {
+                    TextFormatConverter::try_from_str(&column_schema.typ, str)?
+                }
             }
         };
 
@@ -273,6 +365,7 @@ fn convert_tuple_to_row(
 async fn convert_insert_to_event(
     schema_cache: &SchemaCache,
     insert_body: &protocol::InsertBody,
+    preserve_unsupported_types: bool,
 ) -> Result<Event, EventConversionError> {
     let table_id = insert_body.rel_id();
     let table_schema = get_table_schema(schema_cache, table_id).await?;
@@ -280,6 +373,7 @@ async fn convert_insert_to_event(
     let table_row = convert_tuple_to_row(
         &table_schema.column_schemas,
         insert_body.tuple().tuple_data(),
+        preserve_unsupported_types,
     )?;
 
     Ok(Event::Insert(InsertEvent {
@@ -291,13 +385,15 @@ async fn convert_insert_to_event(
 async fn convert_update_to_event(
     schema_cache: &SchemaCache,
     update_body: &protocol::UpdateBody,
+    preserve_unsupported_types: bool,
 ) -> Result<Event, EventConversionError> {
     let table_id = update_body.rel_id();
     let table_schema = get_table_schema(schema_cache, table_id).await?;
 
-    let table_row = convert_tuple_to_row(
+    let mut table_row = convert_tuple_to_row(
         &table_schema.column_schemas,
         update_body.new_tuple().tuple_data(),
+        preserve_unsupported_types,
     )?;
 
     // We try to extract the old tuple by either taking the entire old tuple or the key of the old
@@ -308,11 +404,22 @@ async fn convert_update_to_event(
         Some(identity) => Some(convert_tuple_to_row(
             &table_schema.column_schemas,
             identity.tuple_data(),
+            preserve_unsupported_types,
         )?),
         None => None,
     }
     .map(|row| (is_key, row));
 
+    // The entire old tuple, sent with `REPLICA IDENTITY FULL`, has the unchanged large values
+    // which are missing from the new tuple.
+    if let Some((false, old_table_row)) = &old_table_row {
+        for (cell, old_cell) in table_row.values.iter_mut().zip(&old_table_row.values) {
+            if matches!(cell, Cell::UnchangedToast(_)) {
+                *cell = old_cell.clone();
+            }
+        }
+    }
+
     Ok(Event::Update(UpdateEvent {
         table_id,
         table_row,
@@ -323,6 +430,7 @@ async fn convert_update_to_event(
 async fn convert_delete_to_event(
     schema_cache: &SchemaCache,
     delete_body: &protocol::DeleteBody,
+    preserve_unsupported_types: bool,
 ) -> Result<Event, EventConversionError> {
     let table_id = delete_body.rel_id();
     let table_schema = get_table_schema(schema_cache, table_id).await?;
@@ -335,6 +443,7 @@ async fn convert_delete_to_event(
         Some(identity) => Some(convert_tuple_to_row(
             &table_schema.column_schemas,
             identity.tuple_data(),
+            preserve_unsupported_types,
         )?),
         None => None,
     }
@@ -346,9 +455,14 @@ async fn convert_delete_to_event(
     }))
 }
 
+/// Converts a message of the replication stream to an [`Event`].
+///
+/// The values of columns whose type isn't supported are kept as [`Cell::Unsupported`] holding
+/// their raw text if `preserve_unsupported_types` is set, and fail the conversion otherwise.
 pub async fn convert_message_to_event(
     schema_cache: &SchemaCache,
     message: &LogicalReplicationMessage,
+    preserve_unsupported_types: bool,
 ) -> Result<Event, EventConversionError> {
     match message {
         LogicalReplicationMessage::Begin(begin_body) => {
@@ -361,13 +475,13 @@ pub async fn convert_message_to_event(
             RelationEvent::from_protocol(relation_body)?,
         )),
         LogicalReplicationMessage::Insert(insert_body) => {
-            convert_insert_to_event(schema_cache, insert_body).await
+            convert_insert_to_event(schema_cache, insert_body, preserve_unsupported_types).await
         }
         LogicalReplicationMessage::Update(update_body) => {
-            convert_update_to_event(schema_cache, update_body).await
+            convert_update_to_event(schema_cache, update_body, preserve_unsupported_types).await
         }
         LogicalReplicationMessage::Delete(delete_body) => {
-            convert_delete_to_event(schema_cache, delete_body).await
+            convert_delete_to_event(schema_cache, delete_body, preserve_unsupported_types).await
         }
         LogicalReplicationMessage::Truncate(truncate_body) => {
             Ok(Event::Truncate(TruncateEvent::from_protocol(truncate_body)))
@@ -0,0 +1,104 @@
+use thiserror::Error;
+use tokio_postgres::types::{Kind, Type};
+
+use super::hex::{ByteaHexParseError, from_hex};
+
+#[derive(Debug, Error)]
+pub enum GeometryParseError {
+    #[error("invalid hex EWKB: {0}")]
+    Hex(#[from] ByteaHexParseError),
+
+    #[error("EWKB values must start with a byte order and a geometry type")]
+    Truncated,
+
+    #[error("invalid EWKB byte order {0}")]
+    InvalidByteOrder(u8),
+}
+
+/// Returns whether `typ` is the `geometry` or `geography` type of PostGIS.
+///
+/// Like `hstore`, these types are defined by an extension, so their oids differ between databases
+/// and they are recognized by their name, as looked up along with the schema of the table.
+pub fn is_geometry(typ: &Type) -> bool {
+    matches!(typ.name(), "geometry" | "geography") && matches!(typ.kind(), Kind::Simple)
+}
+
+/// Parses a PostGIS value in its text format, the hex encoding of its EWKB, into the EWKB.
+///
+/// The EWKB is kept as is, with its SRID and extra dimensions, for destinations to forward it
+/// without interpreting it.
+pub fn parse_geometry(str: &str) -> Result<Vec<u8>, GeometryParseError> {
+    let ewkb = from_hex(str)?;
+    check_ewkb(&ewkb)?;
+
+    Ok(ewkb)
+}
+
+/// Checks that `ewkb` starts like an EWKB value, which is also the binary format of PostGIS
+/// values, so that values in another format, e.g. WKT, are rejected rather than forwarded.
+pub fn check_ewkb(ewkb: &[u8]) -> Result<(), GeometryParseError> {
+    // A byte order followed by a 4 bytes geometry type.
+    if ewkb.len() < 5 {
+        return Err(GeometryParseError::Truncated);
+    }
+
+    match ewkb[0] {
+        0 | 1 => Ok(()),
+        byte_order => Err(GeometryParseError::InvalidByteOrder(byte_order)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `SRID=4326;POINT(1 2)`.
+    const POINT: &str = "0101000020E6100000000000000000F03F0000000000000040";
+
+    /// `POLYGON((0 0,1 0,1 1,0 0))`.
+    const POLYGON: &str = "0103000000010000000400000000000000000000000000000000000000000000000000F03F0000000000000000000000000000F03F000000000000F03F00000000000000000000000000000000";
+
+    #[test]
+    fn points_and_polygons_are_parsed_into_their_ewkb() {
+        let point = parse_geometry(POINT).unwrap();
+        assert_eq!(point.len(), 25);
+        // Little endian, with the SRID flag set on the point type.
+        assert_eq!(&point[..5], &[0x01, 0x01, 0x00, 0x00, 0x20]);
+        assert_eq!(&point[5..9], &4326u32.to_le_bytes());
+        assert_eq!(&point[9..17], &1f64.to_le_bytes());
+        assert_eq!(&point[17..], &2f64.to_le_bytes());
+
+        let polygon = parse_geometry(POLYGON).unwrap();
+        assert_eq!(polygon.len(), 9 + 4 + 4 * 16);
+        assert_eq!(&polygon[..5], &[0x01, 0x03, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn values_which_are_not_hex_ewkb_are_rejected() {
+        assert!(matches!(
+            parse_geometry("POINT(1 2)"),
+            Err(GeometryParseError::Hex(ByteaHexParseError::InvalidDigit(_)))
+        ));
+        assert!(matches!(
+            parse_geometry("0101"),
+            Err(GeometryParseError::Truncated)
+        ));
+        assert!(matches!(
+            parse_geometry("0201000000"),
+            Err(GeometryParseError::InvalidByteOrder(2))
+        ));
+    }
+
+    #[test]
+    fn geometry_types_are_recognized_by_their_name() {
+        let geography = Type::new(
+            "geography".to_string(),
+            17_002,
+            Kind::Simple,
+            "public".to_string(),
+        );
+
+        assert!(is_geometry(&geography));
+        assert!(!is_geometry(&Type::BYTEA));
+    }
+}
@@ -1,5 +1,3 @@
-use std::num::ParseIntError;
-
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -10,25 +8,46 @@ pub enum ByteaHexParseError {
     #[error("invalid byte")]
     OddNumerOfDigits,
 
-    #[error("parse int result: {0}")]
-    ParseInt(#[from] ParseIntError),
+    #[error("invalid hex digit at position {0}")]
+    InvalidDigit(usize),
 }
 
+/// Parses a `bytea` value in the hex format, `\x` followed by two hex digits per byte.
 pub fn from_bytea_hex(s: &str) -> Result<Vec<u8>, ByteaHexParseError> {
-    if s.len() < 2 || &s[..2] != "\\x" {
+    let Some(digits) = s.as_bytes().strip_prefix(b"\\x") else {
         return Err(ByteaHexParseError::InvalidPrefix);
-    }
+    };
 
-    let mut result = Vec::with_capacity((s.len() - 2) / 2);
-    let s = &s[2..];
+    decode_hex(digits, 2)
+}
+
+/// Parses two hex digits per byte without a prefix, like the text format of PostGIS geometries.
+pub fn from_hex(s: &str) -> Result<Vec<u8>, ByteaHexParseError> {
+    decode_hex(s.as_bytes(), 0)
+}
 
-    if s.len() % 2 != 0 {
+/// Decodes the hex `digits` found at `offset` in the parsed value, which positions the errors.
+///
+/// The digits are decoded byte by byte, so that values with non-ASCII characters or signs, which
+/// `u8::from_str_radix` would accept, are rejected rather than mangled.
+fn decode_hex(digits: &[u8], offset: usize) -> Result<Vec<u8>, ByteaHexParseError> {
+    if digits.len() % 2 != 0 {
         return Err(ByteaHexParseError::OddNumerOfDigits);
     }
 
-    for i in (0..s.len()).step_by(2) {
-        let val = u8::from_str_radix(&s[i..i + 2], 16)?;
-        result.push(val);
+    let digit = |i: usize| {
+        let value = match digits[i] {
+            d @ b'0'..=b'9' => d - b'0',
+            d @ b'a'..=b'f' => d - b'a' + 10,
+            d @ b'A'..=b'F' => d - b'A' + 10,
+            _ => return Err(ByteaHexParseError::InvalidDigit(i + offset)),
+        };
+        Ok(value)
+    };
+
+    let mut result = Vec::with_capacity(digits.len() / 2);
+    for i in (0..digits.len()).step_by(2) {
+        result.push((digit(i)? << 4) | digit(i + 1)?);
     }
 
     Ok(result)
@@ -0,0 +1,196 @@
+use serde_json::{Map, Value};
+use thiserror::Error;
+use tokio_postgres::types::{Kind, Type};
+
+#[derive(Debug, Error)]
+pub enum HstoreParseError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    #[error("unexpected character `{char}` at position {position}")]
+    UnexpectedChar { char: char, position: usize },
+
+    #[error("keys can't be NULL")]
+    NullKey,
+}
+
+/// Returns whether `typ` is the `hstore` type.
+///
+/// `hstore` is defined by an extension, so its oid differs between databases and the type is
+/// recognized by its name, as looked up along with the schema of the table.
+pub fn is_hstore(typ: &Type) -> bool {
+    typ.name() == "hstore" && matches!(typ.kind(), Kind::Simple)
+}
+
+/// Parses a value of the `hstore` type in its text format, e.g. `"a"=>"1", "b"=>NULL`, into a JSON
+/// object mapping each key to its value, or to `null` for `NULL` values.
+///
+/// Keys and values may be double quoted, in which case they can contain any character, and a
+/// backslash escapes the following character. An unquoted `NULL` value is a `NULL`, while
+/// `"NULL"` is a string. Duplicate keys keep their last value.
+pub fn parse_hstore(str: &str) -> Result<Map<String, Value>, HstoreParseError> {
+    let mut parser = Parser {
+        chars: str.char_indices().peekable(),
+    };
+    let mut map = Map::new();
+
+    parser.skip_whitespace();
+    if parser.chars.peek().is_none() {
+        return Ok(map);
+    }
+
+    loop {
+        let key = parser.parse_token()?.ok_or(HstoreParseError::NullKey)?;
+
+        parser.skip_whitespace();
+        parser.expect('=')?;
+        parser.expect('>')?;
+        parser.skip_whitespace();
+
+        let value = parser.parse_token()?;
+        map.insert(key, value.map_or(Value::Null, Value::String));
+
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => return Ok(map),
+            Some((_, ',')) => parser.skip_whitespace(),
+            Some((position, char)) => {
+                return Err(HstoreParseError::UnexpectedChar { char, position });
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), HstoreParseError> {
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((position, char)) => Err(HstoreParseError::UnexpectedChar { char, position }),
+            None => Err(HstoreParseError::UnexpectedEnd),
+        }
+    }
+
+    /// Parses a key or a value, unquoting and unescaping it, with `None` for an unquoted `NULL`.
+    fn parse_token(&mut self) -> Result<Option<String>, HstoreParseError> {
+        let mut token = String::new();
+
+        if self.chars.next_if(|(_, c)| *c == '"').is_some() {
+            loop {
+                match self.chars.next() {
+                    Some((_, '"')) => return Ok(Some(token)),
+                    Some((_, '\\')) => {
+                        let (_, c) = self.chars.next().ok_or(HstoreParseError::UnexpectedEnd)?;
+                        token.push(c);
+                    }
+                    Some((_, c)) => token.push(c),
+                    None => return Err(HstoreParseError::UnexpectedEnd),
+                }
+            }
+        }
+
+        // An unquoted token ends at whitespace, or at the separators which can follow it.
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|(_, c)| !c.is_whitespace() && *c != '=' && *c != ',')
+        {
+            if c == '\\' {
+                let (_, c) = self.chars.next().ok_or(HstoreParseError::UnexpectedEnd)?;
+                token.push(c);
+            } else {
+                token.push(c);
+            }
+        }
+
+        if token.is_empty() {
+            return match self.chars.peek() {
+                Some(&(position, char)) => Err(HstoreParseError::UnexpectedChar { char, position }),
+                None => Err(HstoreParseError::UnexpectedEnd),
+            };
+        }
+
+        Ok((!token.eq_ignore_ascii_case("null")).then_some(token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(str: &str) -> Value {
+        Value::Object(parse_hstore(str).unwrap())
+    }
+
+    #[test]
+    fn pairs_are_parsed_into_an_object() {
+        assert_eq!(
+            parse(r#""a"=>"1", "b"=>"two words""#),
+            json!({"a": "1", "b": "two words"})
+        );
+        assert_eq!(parse(""), json!({}));
+        assert_eq!(parse("a=>1,b => 2"), json!({"a": "1", "b": "2"}));
+    }
+
+    #[test]
+    fn null_values_are_nulls_unless_quoted() {
+        assert_eq!(
+            parse(r#""a"=>NULL, "b"=>"NULL", "c"=>null"#),
+            json!({"a": null, "b": "NULL", "c": null})
+        );
+    }
+
+    #[test]
+    fn quoted_tokens_may_contain_separators_and_escaped_quotes() {
+        assert_eq!(
+            parse(r#""a=>b"=>"c, d", "say \"hi\""=>"back\\slash""#),
+            json!({"a=>b": "c, d", r#"say "hi""#: r"back\slash"})
+        );
+    }
+
+    #[test]
+    fn malformed_hstores_are_rejected() {
+        assert!(matches!(
+            parse_hstore(r#""a"=>"1"#),
+            Err(HstoreParseError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            parse_hstore(r#""a"->"1""#),
+            Err(HstoreParseError::UnexpectedChar {
+                char: '-',
+                position: 3
+            })
+        ));
+        assert!(matches!(
+            parse_hstore(r#""a"=>"1" "b"=>"2""#),
+            Err(HstoreParseError::UnexpectedChar {
+                char: '"',
+                position: 9
+            })
+        ));
+        assert!(matches!(
+            parse_hstore(r#"NULL=>"1""#),
+            Err(HstoreParseError::NullKey)
+        ));
+    }
+
+    #[test]
+    fn only_types_named_hstore_are_hstores() {
+        let hstore = Type::new(
+            "hstore".to_string(),
+            16_385,
+            Kind::Simple,
+            "public".to_string(),
+        );
+
+        assert!(is_hstore(&hstore));
+        assert!(!is_hstore(&Type::TEXT));
+    }
+}
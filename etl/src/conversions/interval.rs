@@ -0,0 +1,492 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+const MICROSECONDS_PER_SECOND: i64 = 1_000_000;
+const MICROSECONDS_PER_MINUTE: i64 = 60 * MICROSECONDS_PER_SECOND;
+const MICROSECONDS_PER_HOUR: i64 = 60 * MICROSECONDS_PER_MINUTE;
+
+#[derive(Debug, Error)]
+pub enum IntervalParseError {
+    #[error("invalid number in interval: {0}")]
+    InvalidNumber(String),
+
+    #[error("invalid unit '{0}' in interval")]
+    InvalidUnit(char),
+
+    #[error("unknown unit '{0}' in interval")]
+    UnknownUnit(String),
+
+    #[error("missing unit after number in interval: {0}")]
+    MissingUnit(String),
+
+    #[error("interval out of range: {0}")]
+    OutOfRange(String),
+}
+
+/// A Postgres `interval`, stored the same way Postgres stores it: as separate months, days and
+/// microseconds, since the length of a month and of a day is not fixed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PgInterval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl PgInterval {
+    pub fn new(months: i32, days: i32, microseconds: i64) -> Self {
+        Self {
+            months,
+            days,
+            microseconds,
+        }
+    }
+}
+
+/// Parses an interval in the `iso_8601`, `postgres` or `postgres_verbose` `IntervalStyle`, e.g.
+/// `P1Y2M3DT4H5M6.5S`, `1 year 2 mons 3 days 04:05:06.5` or `@ 1 year 2 mons 3 days 4 hours 5
+/// mins 6.5 secs`.
+///
+/// The replication connection forces `IntervalStyle` to `iso_8601`, but the other styles are
+/// understood too, so that values formatted with the default style of Postgres can be parsed.
+impl FromStr for PgInterval {
+    type Err = IntervalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('P') {
+            Some(rest) => parse_iso_8601(s, rest),
+            None => parse_postgres(s),
+        }
+    }
+}
+
+/// Parses an interval in the `iso_8601` style, whose `P` designator was stripped from `rest`.
+///
+/// Each field carries its own sign, as emitted by Postgres (e.g. `P-1Y-2M3DT-4H`).
+fn parse_iso_8601(s: &str, rest: &str) -> Result<PgInterval, IntervalParseError> {
+    let out_of_range = || IntervalParseError::OutOfRange(s.to_string());
+
+    let mut interval = PgInterval::default();
+    let mut in_time = false;
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            'T' if number.is_empty() => in_time = true,
+            '0'..='9' | '.' | '-' | '+' => number.push(c),
+            unit => {
+                if number.is_empty() {
+                    return Err(IntervalParseError::MissingUnit(s.to_string()));
+                }
+
+                match (in_time, unit) {
+                    (false, 'Y') => {
+                        let months = parse_integer::<i32>(&number)?
+                            .checked_mul(12)
+                            .ok_or_else(out_of_range)?;
+                        interval.months = add_i32(interval.months, months, s)?;
+                    }
+                    (false, 'M') => {
+                        interval.months = add_i32(interval.months, parse_integer(&number)?, s)?;
+                    }
+                    (false, 'W') => {
+                        let days = parse_integer::<i32>(&number)?
+                            .checked_mul(7)
+                            .ok_or_else(out_of_range)?;
+                        interval.days = add_i32(interval.days, days, s)?;
+                    }
+                    (false, 'D') => {
+                        interval.days = add_i32(interval.days, parse_integer(&number)?, s)?;
+                    }
+                    (true, 'H') => {
+                        let microseconds = parse_integer::<i64>(&number)?
+                            .checked_mul(MICROSECONDS_PER_HOUR)
+                            .ok_or_else(out_of_range)?;
+                        interval.microseconds = add_i64(interval.microseconds, microseconds, s)?;
+                    }
+                    (true, 'M') => {
+                        let microseconds = parse_integer::<i64>(&number)?
+                            .checked_mul(MICROSECONDS_PER_MINUTE)
+                            .ok_or_else(out_of_range)?;
+                        interval.microseconds = add_i64(interval.microseconds, microseconds, s)?;
+                    }
+                    (true, 'S') => {
+                        let microseconds = parse_seconds(&number)?;
+                        interval.microseconds = add_i64(interval.microseconds, microseconds, s)?;
+                    }
+                    (_, unit) => return Err(IntervalParseError::InvalidUnit(unit)),
+                }
+
+                number.clear();
+            }
+        }
+    }
+
+    if !number.is_empty() {
+        return Err(IntervalParseError::MissingUnit(s.to_string()));
+    }
+
+    Ok(interval)
+}
+
+/// Parses an interval in the `postgres` style, e.g. `-1 years +2 mons 3 days -04:05:06.5`, or in
+/// the `postgres_verbose` style, e.g. `@ 1 year 2 mons 4 hours 5 mins 6.5 secs ago`.
+///
+/// Each number is followed by its unit, except for the time of the `postgres` style, whose sign
+/// applies to its hours, minutes and seconds. A trailing `ago` negates the whole interval.
+fn parse_postgres(s: &str) -> Result<PgInterval, IntervalParseError> {
+    let out_of_range = || IntervalParseError::OutOfRange(s.to_string());
+
+    let rest = s.strip_prefix('@').unwrap_or(s);
+    let mut tokens: Vec<&str> = rest.split_whitespace().collect();
+    let ago = tokens.last() == Some(&"ago");
+    if ago {
+        tokens.pop();
+    }
+    if tokens.is_empty() {
+        return Err(IntervalParseError::MissingUnit(s.to_string()));
+    }
+
+    let mut interval = PgInterval::default();
+    let mut tokens = tokens.into_iter();
+    while let Some(number) = tokens.next() {
+        if number.contains(':') {
+            interval.microseconds = add_i64(interval.microseconds, parse_time(number)?, s)?;
+            continue;
+        }
+
+        // A zero interval is written `@ 0` in the `postgres_verbose` style.
+        let Some(unit) = tokens.next() else {
+            if parse_seconds(number)? == 0 {
+                break;
+            }
+
+            return Err(IntervalParseError::MissingUnit(s.to_string()));
+        };
+
+        match unit {
+            "year" | "years" => {
+                let months = parse_integer::<i32>(number)?
+                    .checked_mul(12)
+                    .ok_or_else(out_of_range)?;
+                interval.months = add_i32(interval.months, months, s)?;
+            }
+            "mon" | "mons" => {
+                interval.months = add_i32(interval.months, parse_integer(number)?, s)?;
+            }
+            "day" | "days" => {
+                interval.days = add_i32(interval.days, parse_integer(number)?, s)?;
+            }
+            "hour" | "hours" => {
+                let microseconds = parse_integer::<i64>(number)?
+                    .checked_mul(MICROSECONDS_PER_HOUR)
+                    .ok_or_else(out_of_range)?;
+                interval.microseconds = add_i64(interval.microseconds, microseconds, s)?;
+            }
+            "min" | "mins" => {
+                let microseconds = parse_integer::<i64>(number)?
+                    .checked_mul(MICROSECONDS_PER_MINUTE)
+                    .ok_or_else(out_of_range)?;
+                interval.microseconds = add_i64(interval.microseconds, microseconds, s)?;
+            }
+            "sec" | "secs" => {
+                interval.microseconds = add_i64(interval.microseconds, parse_seconds(number)?, s)?;
+            }
+            unit => return Err(IntervalParseError::UnknownUnit(unit.to_string())),
+        }
+    }
+
+    if ago {
+        interval = PgInterval {
+            months: interval.months.checked_neg().ok_or_else(out_of_range)?,
+            days: interval.days.checked_neg().ok_or_else(out_of_range)?,
+            microseconds: interval
+                .microseconds
+                .checked_neg()
+                .ok_or_else(out_of_range)?,
+        };
+    }
+
+    Ok(interval)
+}
+
+/// Parses the time of an interval in the `postgres` style, e.g. `-04:05:06.5`, into microseconds.
+fn parse_time(time: &str) -> Result<i64, IntervalParseError> {
+    let invalid_number = || IntervalParseError::InvalidNumber(time.to_string());
+
+    let (negative, unsigned) = match time.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, time.strip_prefix('+').unwrap_or(time)),
+    };
+
+    let mut parts = unsigned.split(':');
+    let (Some(hours), Some(minutes), seconds, None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid_number());
+    };
+    if hours.starts_with(['-', '+']) || minutes.starts_with(['-', '+']) {
+        return Err(invalid_number());
+    }
+
+    let hours = parse_integer::<i64>(hours)?;
+    let minutes = parse_integer::<i64>(minutes)?;
+    let seconds = match seconds {
+        Some(seconds) if !seconds.starts_with(['-', '+']) => parse_seconds(seconds)?,
+        Some(_) => return Err(invalid_number()),
+        None => 0,
+    };
+
+    let microseconds = hours
+        .checked_mul(MICROSECONDS_PER_HOUR)
+        .zip(minutes.checked_mul(MICROSECONDS_PER_MINUTE))
+        .and_then(|(hours, minutes)| hours.checked_add(minutes))
+        .and_then(|microseconds| microseconds.checked_add(seconds))
+        .ok_or_else(invalid_number)?;
+
+    Ok(if negative {
+        -microseconds
+    } else {
+        microseconds
+    })
+}
+
+/// Formats the interval in the `iso_8601` `IntervalStyle`, the same way Postgres does.
+impl fmt::Display for PgInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.months == 0 && self.days == 0 && self.microseconds == 0 {
+            return write!(f, "PT0S");
+        }
+
+        write!(f, "P")?;
+
+        let years = self.months / 12;
+        let months = self.months % 12;
+        if years != 0 {
+            write!(f, "{years}Y")?;
+        }
+        if months != 0 {
+            write!(f, "{months}M")?;
+        }
+        if self.days != 0 {
+            write!(f, "{}D", self.days)?;
+        }
+
+        if self.microseconds != 0 {
+            write!(f, "T")?;
+
+            let hours = self.microseconds / MICROSECONDS_PER_HOUR;
+            let minutes = (self.microseconds % MICROSECONDS_PER_HOUR) / MICROSECONDS_PER_MINUTE;
+            let microseconds = self.microseconds % MICROSECONDS_PER_MINUTE;
+            if hours != 0 {
+                write!(f, "{hours}H")?;
+            }
+            if minutes != 0 {
+                write!(f, "{minutes}M")?;
+            }
+            if microseconds != 0 {
+                let sign = if microseconds < 0 { "-" } else { "" };
+                let microseconds = microseconds.unsigned_abs();
+                let seconds = microseconds / MICROSECONDS_PER_SECOND as u64;
+                let fraction = microseconds % MICROSECONDS_PER_SECOND as u64;
+                if fraction == 0 {
+                    write!(f, "{sign}{seconds}S")?;
+                } else {
+                    let fraction = format!("{fraction:06}");
+                    write!(f, "{sign}{seconds}.{}S", fraction.trim_end_matches('0'))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes an interval in the binary format of Postgres: its microseconds, days and months.
+impl<'a> FromSql<'a> for PgInterval {
+    fn from_sql(
+        _: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + 'static + Sync + Send>> {
+        let Ok(raw) = <[u8; 16]>::try_from(raw) else {
+            return Err(format!("invalid interval of {} bytes", raw.len()).into());
+        };
+        let (microseconds, rest) = raw.split_at(8);
+        let (days, months) = rest.split_at(4);
+
+        Ok(PgInterval {
+            months: i32::from_be_bytes(months.try_into()?),
+            days: i32::from_be_bytes(days.try_into()?),
+            microseconds: i64::from_be_bytes(microseconds.try_into()?),
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+fn parse_integer<T: FromStr>(number: &str) -> Result<T, IntervalParseError> {
+    number
+        .parse()
+        .map_err(|_| IntervalParseError::InvalidNumber(number.to_string()))
+}
+
+fn parse_seconds(number: &str) -> Result<i64, IntervalParseError> {
+    let invalid_number = || IntervalParseError::InvalidNumber(number.to_string());
+
+    let (negative, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, number.strip_prefix('+').unwrap_or(number)),
+    };
+
+    let (seconds, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if seconds.is_empty() || fraction.len() > 6 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid_number());
+    }
+
+    let seconds: i64 = seconds.parse().map_err(|_| invalid_number())?;
+    let fraction: i64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<6}")
+            .parse()
+            .map_err(|_| invalid_number())?
+    };
+
+    let microseconds = seconds
+        .checked_mul(MICROSECONDS_PER_SECOND)
+        .and_then(|microseconds| microseconds.checked_add(fraction))
+        .ok_or_else(invalid_number)?;
+
+    Ok(if negative {
+        -microseconds
+    } else {
+        microseconds
+    })
+}
+
+fn add_i32(value: i32, other: i32, s: &str) -> Result<i32, IntervalParseError> {
+    value
+        .checked_add(other)
+        .ok_or_else(|| IntervalParseError::OutOfRange(s.to_string()))
+}
+
+fn add_i64(value: i64, other: i64, s: &str) -> Result<i64, IntervalParseError> {
+    value
+        .checked_add(other)
+        .ok_or_else(|| IntervalParseError::OutOfRange(s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_iso_8601_intervals() {
+        let cases = [
+            ("PT0S", PgInterval::new(0, 0, 0)),
+            ("P1Y2M3D", PgInterval::new(14, 3, 0)),
+            (
+                "P1Y2M3DT4H5M6S",
+                PgInterval::new(14, 3, 4 * 3_600_000_000 + 5 * 60_000_000 + 6_000_000),
+            ),
+            ("PT0.5S", PgInterval::new(0, 0, 500_000)),
+            ("PT1.000001S", PgInterval::new(0, 0, 1_000_001)),
+            ("P2W", PgInterval::new(0, 14, 0)),
+            (
+                "P-1Y-2M3DT-4H-5M-6.5S",
+                PgInterval::new(-14, 3, -(4 * 3_600_000_000 + 5 * 60_000_000 + 6_500_000)),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<PgInterval>().unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn format_round_trips_iso_8601_intervals() {
+        for input in [
+            "PT0S",
+            "P1Y2M3D",
+            "P1Y2M3DT4H5M6S",
+            "PT0.5S",
+            "P-1Y-2M3DT-4H-5M-6.5S",
+        ] {
+            let interval = input.parse::<PgInterval>().unwrap();
+            assert_eq!(interval.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn parse_postgres_intervals() {
+        let cases = [
+            ("00:00:00", PgInterval::new(0, 0, 0)),
+            (
+                "1 year 2 mons 3 days 04:05:06.789",
+                PgInterval::new(14, 3, 4 * 3_600_000_000 + 5 * 60_000_000 + 6_789_000),
+            ),
+            ("1 day", PgInterval::new(0, 1, 0)),
+            ("-00:00:01.5", PgInterval::new(0, 0, -1_500_000)),
+            ("27:00:00", PgInterval::new(0, 0, 27 * 3_600_000_000)),
+            (
+                "-1 years -2 mons +3 days -04:05:06",
+                PgInterval::new(-14, 3, -(4 * 3_600_000_000 + 5 * 60_000_000 + 6_000_000)),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<PgInterval>().unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_postgres_verbose_intervals() {
+        let cases = [
+            ("@ 0", PgInterval::new(0, 0, 0)),
+            (
+                "@ 1 year 2 mons 3 days 4 hours 5 mins 6.789 secs",
+                PgInterval::new(14, 3, 4 * 3_600_000_000 + 5 * 60_000_000 + 6_789_000),
+            ),
+            (
+                "@ 1 year 2 mons -3 days 4 hours ago",
+                PgInterval::new(-14, 3, -4 * 3_600_000_000),
+            ),
+            ("@ 0.5 secs ago", PgInterval::new(0, 0, -500_000)),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<PgInterval>().unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn reject_malformed_intervals() {
+        assert!(matches!(
+            "1 fortnight".parse::<PgInterval>(),
+            Err(IntervalParseError::UnknownUnit(unit)) if unit == "fortnight"
+        ));
+        assert!(matches!(
+            "@ 1".parse::<PgInterval>(),
+            Err(IntervalParseError::MissingUnit(_))
+        ));
+        assert!(matches!(
+            "1:-2:03".parse::<PgInterval>(),
+            Err(IntervalParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            "P1".parse::<PgInterval>(),
+            Err(IntervalParseError::MissingUnit(_))
+        ));
+        assert!(matches!(
+            "P1H".parse::<PgInterval>(),
+            Err(IntervalParseError::InvalidUnit('H'))
+        ));
+        assert!(matches!(
+            "PT1.5M".parse::<PgInterval>(),
+            Err(IntervalParseError::InvalidNumber(_))
+        ));
+    }
+}
@@ -1,13 +1,23 @@
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use interval::PgInterval;
 use numeric::PgNumeric;
 use std::fmt::Debug;
 use tokio_postgres::types::Type;
 use uuid::Uuid;
 
+pub mod binary;
 pub mod bool;
+pub mod bytea;
 pub mod cdc_event;
+mod compare;
+pub mod composite;
+pub mod enums;
 pub mod event;
+pub mod geometry;
 pub mod hex;
+pub mod hstore;
+pub mod interval;
+pub mod null;
 pub mod numeric;
 pub mod table_row;
 pub mod text;
@@ -28,10 +38,33 @@ pub enum Cell {
     Time(NaiveTime),
     TimeStamp(NaiveDateTime),
     TimeStampTz(DateTime<Utc>),
+    /// An `interval`, with its months, days and microseconds kept apart like in Postgres.
+    Interval(PgInterval),
     Uuid(Uuid),
     Json(serde_json::Value),
     Bytes(Vec<u8>),
+    /// A PostGIS `geometry` or `geography`, as its raw EWKB.
+    Geometry(Vec<u8>),
     Array(ArrayCell),
+    /// The values of the fields of a composite type, in the order of the fields of the type.
+    Composite(Vec<Cell>),
+    /// A value of an enum type, with the name of the type and the label of the value.
+    Enum {
+        type_name: String,
+        label: String,
+    },
+    /// The raw text of a value whose type isn't supported, kept so that destinations can decide
+    /// whether to skip or forward it.
+    Unsupported(Type, String),
+    /// A value of the new row of an `UPDATE` which Postgres didn't send, since it's a large value
+    /// stored out of line (TOASTed) which the update didn't change. Tables with `REPLICA IDENTITY
+    /// FULL` send it in the old row instead, where it's taken from, so it's only found in the
+    /// updates of the other tables.
+    ///
+    /// Destinations must leave the column untouched rather than write a value for it, e.g. by
+    /// leaving it out of the `UPDATE` they run. Destinations which can only replace whole rows
+    /// can't apply such an update, and must fail rather than overwrite the value.
+    UnchangedToast(Type),
 }
 
 impl Cell {
@@ -85,6 +118,11 @@ impl Cell {
                 let s = t.format("%Y-%m-%d %H:%M:%S%.f%:z").to_string();
                 prost::encoding::string::encode(tag, &s, buf);
             }
+            // Intervals are sent in the ISO 8601 format, which BigQuery parses into an `INTERVAL`.
+            Cell::Interval(i) => {
+                let s = i.to_string();
+                prost::encoding::string::encode(tag, &s, buf);
+            }
             Cell::Uuid(u) => {
                 let s = u.to_string();
                 prost::encoding::string::encode(tag, &s, buf)
@@ -96,12 +134,22 @@ impl Cell {
             Cell::U32(i) => {
                 prost::encoding::uint32::encode(tag, i, buf);
             }
-            Cell::Bytes(b) => {
+            Cell::Bytes(b) | Cell::Geometry(b) => {
                 prost::encoding::bytes::encode(tag, b, buf);
             }
             Cell::Array(a) => {
                 a.clone().encode_prost(tag, buf);
             }
+            // BigQuery has no type matching a composite, whose fields are sent as a JSON array.
+            Cell::Composite(_) => {
+                let s = self.to_json().to_string();
+                prost::encoding::string::encode(tag, &s, buf);
+            }
+            Cell::Enum { label, .. } | Cell::Unsupported(_, label) => {
+                prost::encoding::string::encode(tag, label, buf);
+            }
+            // There is no value to send, so the field is left out.
+            Cell::UnchangedToast(_) => {}
         }
     }
 
@@ -141,6 +189,10 @@ impl Cell {
                 let s = t.format("%Y-%m-%d %H:%M:%S%.f%:z").to_string();
                 prost::encoding::string::encoded_len(tag, &s)
             }
+            Cell::Interval(i) => {
+                let s = i.to_string();
+                prost::encoding::string::encoded_len(tag, &s)
+            }
             Cell::Uuid(u) => {
                 let s = u.to_string();
                 prost::encoding::string::encoded_len(tag, &s)
@@ -150,14 +202,114 @@ impl Cell {
                 prost::encoding::string::encoded_len(tag, &s)
             }
             Cell::U32(i) => prost::encoding::uint32::encoded_len(tag, i),
-            Cell::Bytes(b) => prost::encoding::bytes::encoded_len(tag, b),
+            Cell::Bytes(b) | Cell::Geometry(b) => prost::encoding::bytes::encoded_len(tag, b),
             Cell::Array(array_cell) => array_cell.clone().encoded_len_prost(tag),
+            Cell::Composite(_) => {
+                let s = self.to_json().to_string();
+                prost::encoding::string::encoded_len(tag, &s)
+            }
+            Cell::Enum { label, .. } | Cell::Unsupported(_, label) => {
+                prost::encoding::string::encoded_len(tag, label)
+            }
+            Cell::UnchangedToast(_) => 0,
         }
     }
 
+    /// Merges the value of field `tag` of an encoded [`TableRow`](table_row::TableRow) into the
+    /// cell, whose variant tells how the value was encoded by [`Cell::encode_prost`].
+    ///
+    /// `NULL`s are encoded as the default value of their type, which is what a `NULL` cell is
+    /// decoded into. Composites are sent as JSON without the types of their fields, so they can't
+    /// be decoded.
+    #[cfg(feature = "bigquery")]
+    pub fn merge_prost(
+        &mut self,
+        tag: u32,
+        wire_type: prost::encoding::WireType,
+        buf: &mut impl bytes::Buf,
+        ctx: prost::encoding::DecodeContext,
+    ) -> Result<(), prost::DecodeError> {
+        use crate::conversions::text::TextFormatConverter;
+        use prost::encoding;
+
+        match self {
+            Cell::Null(typ) | Cell::UnchangedToast(typ) => {
+                let value = TextFormatConverter::default_value(typ);
+                *self = value;
+                self.merge_prost(tag, wire_type, buf, ctx)
+            }
+            Cell::Bool(b) => encoding::bool::merge(wire_type, b, buf, ctx),
+            Cell::String(s) | Cell::Enum { label: s, .. } | Cell::Unsupported(_, s) => {
+                encoding::string::merge(wire_type, s, buf, ctx)
+            }
+            Cell::I16(i) => {
+                let mut val = 0;
+                encoding::int32::merge(wire_type, &mut val, buf, ctx)?;
+                *i = i16::try_from(val)
+                    .map_err(|_| prost::DecodeError::new(format!("invalid int2 value `{val}`")))?;
+                Ok(())
+            }
+            Cell::I32(i) => encoding::int32::merge(wire_type, i, buf, ctx),
+            Cell::I64(i) => encoding::int64::merge(wire_type, i, buf, ctx),
+            Cell::F32(i) => encoding::float::merge(wire_type, i, buf, ctx),
+            Cell::F64(i) => encoding::double::merge(wire_type, i, buf, ctx),
+            Cell::Numeric(n) => {
+                *n = merge_parsed(wire_type, buf, ctx, "numeric", |s| s.parse().ok())?;
+                Ok(())
+            }
+            Cell::Date(t) => {
+                *t = merge_parsed(wire_type, buf, ctx, "date", |s| {
+                    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+                })?;
+                Ok(())
+            }
+            Cell::Time(t) => {
+                *t = merge_parsed(wire_type, buf, ctx, "time", |s| {
+                    NaiveTime::parse_from_str(s, "%H:%M:%S%.f").ok()
+                })?;
+                Ok(())
+            }
+            Cell::TimeStamp(t) => {
+                *t = merge_parsed(wire_type, buf, ctx, "timestamp", |s| {
+                    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").ok()
+                })?;
+                Ok(())
+            }
+            Cell::TimeStampTz(t) => {
+                *t = merge_parsed(wire_type, buf, ctx, "timestamptz", |s| {
+                    DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%:z")
+                        .ok()
+                        .map(|t| t.with_timezone(&Utc))
+                })?;
+                Ok(())
+            }
+            Cell::Interval(i) => {
+                *i = merge_parsed(wire_type, buf, ctx, "interval", |s| s.parse().ok())?;
+                Ok(())
+            }
+            Cell::Uuid(u) => {
+                *u = merge_parsed(wire_type, buf, ctx, "uuid", |s| s.parse().ok())?;
+                Ok(())
+            }
+            Cell::Json(j) => {
+                *j = merge_parsed(wire_type, buf, ctx, "json", |s| {
+                    serde_json::from_str(s).ok()
+                })?;
+                Ok(())
+            }
+            Cell::U32(i) => encoding::uint32::merge(wire_type, i, buf, ctx),
+            Cell::Bytes(b) | Cell::Geometry(b) => encoding::bytes::merge(wire_type, b, buf, ctx),
+            Cell::Array(a) => a.merge_prost(tag, wire_type, buf, ctx),
+            Cell::Composite(_) => Err(prost::DecodeError::new("composite values can't be decoded")),
+        }
+    }
+
+    /// Resets the value to the empty value of its variant, e.g. `0` or an empty string, keeping
+    /// what determines how it's encoded: the type of a `NULL`, the fields of a composite, and
+    /// whether a JSON value is an object or an array.
     pub fn clear(&mut self) {
         match self {
-            Cell::Null(_) => {}
+            Cell::Null(_) | Cell::UnchangedToast(_) => {}
             Cell::Bool(b) => *b = false,
             Cell::String(s) => s.clear(),
             Cell::I16(i) => *i = 0,
@@ -170,17 +322,86 @@ impl Cell {
             Cell::Time(t) => *t = NaiveTime::default(),
             Cell::TimeStamp(t) => *t = NaiveDateTime::default(),
             Cell::TimeStampTz(t) => *t = DateTime::<Utc>::default(),
+            Cell::Interval(i) => *i = PgInterval::default(),
             Cell::Uuid(u) => *u = Uuid::default(),
+            Cell::Json(serde_json::Value::Object(map)) => map.clear(),
+            Cell::Json(serde_json::Value::Array(vec)) => vec.clear(),
             Cell::Json(j) => *j = serde_json::Value::default(),
             Cell::U32(u) => *u = 0,
-            Cell::Bytes(b) => b.clear(),
+            Cell::Bytes(b) | Cell::Geometry(b) => b.clear(),
             Cell::Array(vec) => {
                 vec.clear();
             }
+            Cell::Composite(cells) => cells.iter_mut().for_each(Cell::clear),
+            Cell::Enum { label, .. } => label.clear(),
+            Cell::Unsupported(_, s) => s.clear(),
+        }
+    }
+
+    /// Converts the value into JSON, for destinations which have no type of their own for it.
+    ///
+    /// Numerics are converted into strings, to keep all their digits, and so are the values
+    /// without a JSON type, e.g. dates and intervals, which are formatted like when sent to BigQuery. Bytes are
+    /// written as hex, like Postgres does. Geometries are written as the hex of their EWKB without a
+    /// `\x` prefix, like PostGIS does.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Cell::Null(_) | Cell::UnchangedToast(_) => serde_json::Value::Null,
+            Cell::Bool(b) => (*b).into(),
+            Cell::String(s) => s.as_str().into(),
+            Cell::I16(i) => (*i).into(),
+            Cell::I32(i) => (*i).into(),
+            Cell::U32(i) => (*i).into(),
+            Cell::I64(i) => (*i).into(),
+            Cell::F32(f) => float_to_json(*f as f64),
+            Cell::F64(f) => float_to_json(*f),
+            Cell::Numeric(n) => n.to_string().into(),
+            Cell::Date(t) => t.format("%Y-%m-%d").to_string().into(),
+            Cell::Time(t) => t.format("%H:%M:%S%.f").to_string().into(),
+            Cell::TimeStamp(t) => t.format("%Y-%m-%d %H:%M:%S%.f").to_string().into(),
+            Cell::TimeStampTz(t) => t.format("%Y-%m-%d %H:%M:%S%.f%:z").to_string().into(),
+            Cell::Interval(i) => i.to_string().into(),
+            Cell::Uuid(u) => u.to_string().into(),
+            Cell::Json(j) => j.clone(),
+            Cell::Bytes(b) => bytes_to_json(b),
+            Cell::Geometry(b) => b
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>()
+                .into(),
+            Cell::Array(a) => a.to_json(),
+            Cell::Composite(cells) => cells.iter().map(Cell::to_json).collect(),
+            Cell::Enum { label, .. } => label.as_str().into(),
+            Cell::Unsupported(_, s) => s.as_str().into(),
         }
     }
 }
 
+/// Converts a float into JSON, as a string for the values which JSON can't represent, i.e. `NaN`
+/// and the infinities.
+fn float_to_json(f: f64) -> serde_json::Value {
+    serde_json::Number::from_f64(f).map_or_else(|| f.to_string().into(), serde_json::Value::Number)
+}
+
+/// Merges a value encoded as a string, and parses it with `parse` into a value of `type_name`.
+#[cfg(feature = "bigquery")]
+fn merge_parsed<T>(
+    wire_type: prost::encoding::WireType,
+    buf: &mut impl bytes::Buf,
+    ctx: prost::encoding::DecodeContext,
+    type_name: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Result<T, prost::DecodeError> {
+    let mut s = String::new();
+    prost::encoding::string::merge(wire_type, &mut s, buf, ctx)?;
+    parse(&s).ok_or_else(|| prost::DecodeError::new(format!("invalid {type_name} value `{s}`")))
+}
+
+fn bytes_to_json(bytes: &[u8]) -> serde_json::Value {
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("\\x{hex}").into()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ArrayCell {
     Null,
@@ -409,6 +630,167 @@ impl ArrayCell {
         }
     }
 
+    /// Merges the elements in field `tag` of an encoded [`TableRow`](table_row::TableRow) into
+    /// the array.
+    ///
+    /// The `NULL` elements of arrays aren't encoded, so only the other elements are decoded.
+    #[cfg(feature = "bigquery")]
+    pub fn merge_prost(
+        &mut self,
+        tag: u32,
+        wire_type: prost::encoding::WireType,
+        buf: &mut impl bytes::Buf,
+        ctx: prost::encoding::DecodeContext,
+    ) -> Result<(), prost::DecodeError> {
+        use prost::encoding;
+
+        match self {
+            ArrayCell::Null => encoding::skip_field(wire_type, tag, buf, ctx),
+            ArrayCell::Bool(vec) => {
+                let mut values = Vec::new();
+                encoding::bool::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+            ArrayCell::String(vec) => {
+                let mut values = Vec::new();
+                encoding::string::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+            ArrayCell::I16(vec) => {
+                let mut values = Vec::new();
+                encoding::int32::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                for val in values {
+                    let val = i16::try_from(val).map_err(|_| {
+                        prost::DecodeError::new(format!("invalid int2 value `{val}`"))
+                    })?;
+                    vec.push(Some(val));
+                }
+                Ok(())
+            }
+            ArrayCell::I32(vec) => {
+                let mut values = Vec::new();
+                encoding::int32::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+            ArrayCell::U32(vec) => {
+                let mut values = Vec::new();
+                encoding::uint32::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+            ArrayCell::I64(vec) => {
+                let mut values = Vec::new();
+                encoding::int64::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+            ArrayCell::F32(vec) => {
+                let mut values = Vec::new();
+                encoding::float::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+            ArrayCell::F64(vec) => {
+                let mut values = Vec::new();
+                encoding::double::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+            // The elements encoded as strings are never packed, so each field is one element.
+            ArrayCell::Numeric(vec) => {
+                let val = merge_parsed(wire_type, buf, ctx, "numeric", |s| s.parse().ok())?;
+                vec.push(Some(val));
+                Ok(())
+            }
+            ArrayCell::Date(vec) => {
+                let val = merge_parsed(wire_type, buf, ctx, "date", |s| {
+                    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+                })?;
+                vec.push(Some(val));
+                Ok(())
+            }
+            ArrayCell::Time(vec) => {
+                let val = merge_parsed(wire_type, buf, ctx, "time", |s| {
+                    NaiveTime::parse_from_str(s, "%H:%M:%S%.f").ok()
+                })?;
+                vec.push(Some(val));
+                Ok(())
+            }
+            ArrayCell::TimeStamp(vec) => {
+                let val = merge_parsed(wire_type, buf, ctx, "timestamp", |s| {
+                    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").ok()
+                })?;
+                vec.push(Some(val));
+                Ok(())
+            }
+            ArrayCell::TimeStampTz(vec) => {
+                let val = merge_parsed(wire_type, buf, ctx, "timestamptz", |s| {
+                    DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%:z")
+                        .ok()
+                        .map(|t| t.with_timezone(&Utc))
+                })?;
+                vec.push(Some(val));
+                Ok(())
+            }
+            ArrayCell::Uuid(vec) => {
+                let val = merge_parsed(wire_type, buf, ctx, "uuid", |s| s.parse().ok())?;
+                vec.push(Some(val));
+                Ok(())
+            }
+            ArrayCell::Json(vec) => {
+                let val = merge_parsed(wire_type, buf, ctx, "json", |s| {
+                    serde_json::from_str(s).ok()
+                })?;
+                vec.push(Some(val));
+                Ok(())
+            }
+            ArrayCell::Bytes(vec) => {
+                let mut values: Vec<Vec<u8>> = Vec::new();
+                encoding::bytes::merge_repeated(wire_type, &mut values, buf, ctx)?;
+                vec.extend(values.into_iter().map(Some));
+                Ok(())
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        fn elements<T>(
+            vec: &[Option<T>],
+            f: impl Fn(&T) -> serde_json::Value,
+        ) -> serde_json::Value {
+            vec.iter()
+                .map(|v| v.as_ref().map_or(serde_json::Value::Null, &f))
+                .collect()
+        }
+
+        match self {
+            ArrayCell::Null => serde_json::Value::Null,
+            ArrayCell::Bool(vec) => elements(vec, |b| (*b).into()),
+            ArrayCell::String(vec) => elements(vec, |s| s.as_str().into()),
+            ArrayCell::I16(vec) => elements(vec, |i| (*i).into()),
+            ArrayCell::I32(vec) => elements(vec, |i| (*i).into()),
+            ArrayCell::U32(vec) => elements(vec, |i| (*i).into()),
+            ArrayCell::I64(vec) => elements(vec, |i| (*i).into()),
+            ArrayCell::F32(vec) => elements(vec, |f| float_to_json(*f as f64)),
+            ArrayCell::F64(vec) => elements(vec, |f| float_to_json(*f)),
+            ArrayCell::Numeric(vec) => elements(vec, |n| n.to_string().into()),
+            ArrayCell::Date(vec) => elements(vec, |t| t.format("%Y-%m-%d").to_string().into()),
+            ArrayCell::Time(vec) => elements(vec, |t| t.format("%H:%M:%S%.f").to_string().into()),
+            ArrayCell::TimeStamp(vec) => {
+                elements(vec, |t| t.format("%Y-%m-%d %H:%M:%S%.f").to_string().into())
+            }
+            ArrayCell::TimeStampTz(vec) => elements(vec, |t| {
+                t.format("%Y-%m-%d %H:%M:%S%.f%:z").to_string().into()
+            }),
+            ArrayCell::Uuid(vec) => elements(vec, |u| u.to_string().into()),
+            ArrayCell::Json(vec) => elements(vec, |j| j.clone()),
+            ArrayCell::Bytes(vec) => elements(vec, |b| bytes_to_json(b)),
+        }
+    }
+
     fn clear(&mut self) {
         match self {
             ArrayCell::Null => {}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use config::shared::NullPolicy;
+use tokio_postgres::types::Type;
+
+use crate::conversions::Cell;
+use crate::conversions::text::TextFormatConverter;
+
+/// Returns the value that replaces a `NULL` of type `typ` under [`NullPolicy::TypeDefault`].
+///
+/// Values are the zero value of each type (e.g. an empty string, `0`, `false` or an empty
+/// array), except for dates and timestamps which default to the Unix epoch rather than to the
+/// minimum value representable by Postgres.
+pub fn default_cell(typ: &Type) -> Cell {
+    match *typ {
+        Type::DATE => Cell::Date(NaiveDate::default()),
+        Type::TIMESTAMP => Cell::TimeStamp(NaiveDateTime::default()),
+        Type::TIMESTAMPTZ => Cell::TimeStampTz(DateTime::<Utc>::default()),
+        _ => TextFormatConverter::default_value(typ),
+    }
+}
+
+/// Applies `policy` to the `NULL`s in `cells`.
+pub fn apply_null_policy(cells: &mut [Cell], policy: NullPolicy) {
+    if policy == NullPolicy::KeepNull {
+        return;
+    }
+
+    for cell in cells {
+        if let Cell::Null(typ) = cell {
+            *cell = default_cell(typ);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::numeric::PgNumeric;
+    use chrono::NaiveTime;
+
+    #[test]
+    fn default_cells_for_common_types() {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let cases = [
+            (Type::NUMERIC, Cell::Numeric(PgNumeric::default())),
+            (Type::INT4, Cell::I32(0)),
+            (Type::FLOAT8, Cell::F64(0.0)),
+            (Type::TEXT, Cell::String(String::new())),
+            (Type::VARCHAR, Cell::String(String::new())),
+            (Type::BOOL, Cell::Bool(false)),
+            (Type::DATE, Cell::Date(epoch)),
+            (
+                Type::TIMESTAMP,
+                Cell::TimeStamp(epoch.and_time(NaiveTime::MIN)),
+            ),
+            (
+                Type::TIMESTAMPTZ,
+                Cell::TimeStampTz(DateTime::<Utc>::from_naive_utc_and_offset(
+                    epoch.and_time(NaiveTime::MIN),
+                    Utc,
+                )),
+            ),
+        ];
+
+        for (typ, expected) in cases {
+            assert_eq!(default_cell(&typ), expected, "{typ}");
+        }
+    }
+
+    #[test]
+    fn keep_null_policy_leaves_nulls() {
+        let mut cells = vec![Cell::Null(Type::INT4), Cell::I32(1)];
+        apply_null_policy(&mut cells, NullPolicy::KeepNull);
+
+        assert_eq!(cells, vec![Cell::Null(Type::INT4), Cell::I32(1)]);
+    }
+
+    #[test]
+    fn type_default_policy_replaces_nulls() {
+        let mut cells = vec![
+            Cell::Null(Type::INT4),
+            Cell::String("a".to_string()),
+            Cell::Null(Type::TEXT),
+        ];
+        apply_null_policy(&mut cells, NullPolicy::TypeDefault);
+
+        assert_eq!(
+            cells,
+            vec![
+                Cell::I32(0),
+                Cell::String("a".to_string()),
+                Cell::String(String::new()),
+            ]
+        );
+    }
+}
@@ -10,8 +10,8 @@ use tokio_postgres::types::{FromSql, Type};
 /// A rust variant of the Postgres Numeric type. The full spectrum of Postgres'
 /// Numeric value range is supported.
 ///
-/// Represented as an Optional BigDecimal. None for 'NaN', Some(bigdecimal) for
-/// all other values.
+/// Values are kept as a BigDecimal, so that they are never rounded through a
+/// float, while 'NaN' and the infinities have their own variants.
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub enum PgNumeric {
     NaN,
@@ -90,13 +90,15 @@ impl<'a> FromSql<'a> for PgNumeric {
     }
 }
 
+/// Formats the numeric the same way Postgres does, without ever using scientific notation, so
+/// that destinations receive every digit of the value.
 impl Display for PgNumeric {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             PgNumeric::NaN => write!(f, "NaN"),
             PgNumeric::PositiveInf => write!(f, "Infinity"),
             PgNumeric::NegativeInf => write!(f, "-Infinity"),
-            PgNumeric::Value(n) => write!(f, "{n}"),
+            PgNumeric::Value(n) => write!(f, "{}", n.to_plain_string()),
         }
     }
 }
@@ -106,3 +108,117 @@ impl Default for PgNumeric {
         PgNumeric::Value(BigDecimal::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::text::TextFormatConverter;
+    use crate::conversions::{ArrayCell, Cell};
+
+    const PRECISE_NUMERICS: [&str; 6] = [
+        "12345678901234567890123456789012345678",
+        "123456789012345.6789",
+        "-1234567890123456789012345678.9012345678",
+        "0.00000000000000000000000000000000000001",
+        "100000000000000000000000000000000000000",
+        "1.10000000000000000000000000000000000000",
+    ];
+
+    #[test]
+    fn numerics_are_formatted_without_precision_loss() {
+        for input in PRECISE_NUMERICS {
+            let numeric: PgNumeric = input.parse().unwrap();
+            assert_eq!(numeric.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn numeric_text_round_trips_without_precision_loss() {
+        for input in PRECISE_NUMERICS {
+            let cell = TextFormatConverter::try_from_str(&Type::NUMERIC, input).unwrap();
+            let Cell::Numeric(numeric) = cell else {
+                panic!("expected a numeric cell, got {cell:?}");
+            };
+            assert_eq!(numeric.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn numeric_array_text_round_trips_without_precision_loss() {
+        let input = format!("{{{}}}", PRECISE_NUMERICS.join(","));
+
+        let cell = TextFormatConverter::try_from_str(&Type::NUMERIC_ARRAY, &input).unwrap();
+        let Cell::Array(ArrayCell::Numeric(numerics)) = cell else {
+            panic!("expected a numeric array cell, got {cell:?}");
+        };
+        let numerics: Vec<_> = numerics
+            .into_iter()
+            .map(|numeric| numeric.unwrap().to_string())
+            .collect();
+        assert_eq!(numerics, PRECISE_NUMERICS);
+    }
+
+    #[test]
+    fn special_numerics_round_trip() {
+        for (input, expected) in [
+            ("NaN", PgNumeric::NaN),
+            ("Infinity", PgNumeric::PositiveInf),
+            ("-Infinity", PgNumeric::NegativeInf),
+        ] {
+            let cell = TextFormatConverter::try_from_str(&Type::NUMERIC, input).unwrap();
+            assert_eq!(cell, Cell::Numeric(expected.clone()));
+            assert_eq!(expected.to_string(), input);
+        }
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::NUMERIC_ARRAY, "{NaN,1.5,NULL}").unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::Numeric(vec![
+                Some(PgNumeric::NaN),
+                Some("1.5".parse().unwrap()),
+                None,
+            ]))
+        );
+
+        // In the binary format, `NaN` is flagged by its sign field.
+        let mut raw = vec![];
+        for field in [0u16, 0, 0xC000, 0] {
+            raw.extend_from_slice(&field.to_be_bytes());
+        }
+        assert_eq!(
+            PgNumeric::from_sql(&Type::NUMERIC, &raw).unwrap(),
+            PgNumeric::NaN
+        );
+    }
+
+    #[cfg(feature = "bigquery")]
+    #[test]
+    fn numerics_are_encoded_for_bigquery_without_precision_loss() {
+        for input in PRECISE_NUMERICS {
+            let cell = Cell::Numeric(input.parse().unwrap());
+
+            let mut buf = Vec::new();
+            cell.encode_prost(1, &mut buf);
+
+            let mut expected = Vec::new();
+            prost::encoding::string::encode(1, &input.to_string(), &mut expected);
+            assert_eq!(buf, expected);
+            assert_eq!(cell.encoded_len_prost(1), expected.len());
+        }
+
+        let numerics = PRECISE_NUMERICS
+            .iter()
+            .map(|input| Some(input.parse().unwrap()))
+            .collect();
+        let cell = Cell::Array(ArrayCell::Numeric(numerics));
+
+        let mut buf = Vec::new();
+        cell.encode_prost(1, &mut buf);
+
+        let mut expected = Vec::new();
+        let inputs: Vec<String> = PRECISE_NUMERICS.iter().map(|s| s.to_string()).collect();
+        prost::encoding::string::encode_repeated(1, &inputs, &mut expected);
+        assert_eq!(buf, expected);
+    }
+}
@@ -1,14 +1,32 @@
+use config::shared::{CopyConfig, CopyFormat, NullPolicy};
 use core::str;
 use postgres::schema::ColumnSchema;
 use std::str::Utf8Error;
+use std::{iter, slice};
 use thiserror::Error;
 use tokio_postgres::types::Type;
 use tracing::error;
 
+use crate::conversions::binary::{BinaryFormatConverter, FromBinaryError};
+use crate::conversions::null::apply_null_policy;
 use crate::conversions::text::TextFormatConverter;
+use crate::metrics;
 
 use super::{Cell, text::FromTextError};
 
+/// Signature starting the header of the data produced by `COPY` in the binary format.
+const BINARY_SIGNATURE: &[u8] = b"PGCOPY\n\xff\r\n\0";
+
+/// Bit of the flags of the binary header telling that the rows include their OID.
+const BINARY_OIDS_FLAG: u32 = 1 << 16;
+
+/// Trailer ending the data produced by `COPY` in the binary format, a field count of -1.
+const BINARY_TRAILER: &[u8] = &[0xff, 0xff];
+
+/// Character quoting the values produced by `COPY` in the csv format, which is also the one
+/// escaping the quotes within a quoted value by doubling them.
+const CSV_QUOTE: u8 = b'"';
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TableRow {
     pub values: Vec<Cell>,
@@ -18,6 +36,33 @@ impl TableRow {
     pub fn new(values: Vec<Cell>) -> Self {
         Self { values }
     }
+
+    /// Applies `policy` to the `NULL`s of the row.
+    pub fn apply_null_policy(&mut self, policy: NullPolicy) {
+        apply_null_policy(&mut self.values, policy);
+    }
+
+    /// Decodes a row encoded with [`prost::Message`] whose columns are described by
+    /// `column_schemas`.
+    ///
+    /// The values are decoded into the default values of the types of the columns, so that the
+    /// missing fields, i.e. the empty arrays, are decoded too. `NULL`s are encoded as the default
+    /// value of their type, and the `NULL` elements of arrays are skipped, so they can't be told
+    /// apart after decoding.
+    #[cfg(feature = "bigquery")]
+    pub fn decode_prost(
+        buf: impl bytes::Buf,
+        column_schemas: &[ColumnSchema],
+    ) -> Result<TableRow, prost::DecodeError> {
+        let values = column_schemas
+            .iter()
+            .map(|column_schema| TextFormatConverter::default_value(&column_schema.typ))
+            .collect();
+        let mut row = TableRow::new(values);
+        prost::Message::merge(&mut row, buf)?;
+
+        Ok(row)
+    }
 }
 
 #[cfg(feature = "bigquery")]
@@ -33,17 +78,22 @@ impl prost::Message for TableRow {
         }
     }
 
+    /// Merges field `tag` into the value of the row at the same position, whose variant tells how
+    /// to decode it. The fields past the values of the row are skipped.
     fn merge_field(
         &mut self,
-        _tag: u32,
-        _wire_type: prost::encoding::WireType,
-        _buf: &mut impl bytes::Buf,
-        _ctx: prost::encoding::DecodeContext,
+        tag: u32,
+        wire_type: prost::encoding::WireType,
+        buf: &mut impl bytes::Buf,
+        ctx: prost::encoding::DecodeContext,
     ) -> Result<(), prost::DecodeError>
     where
         Self: Sized,
     {
-        unimplemented!("merge_field not implemented yet");
+        match self.values.get_mut(tag as usize - 1) {
+            Some(cell) => cell.merge_prost(tag, wire_type, buf, ctx),
+            None => prost::encoding::skip_field(wire_type, tag, buf, ctx),
+        }
     }
 
     fn encoded_len(&self) -> usize {
@@ -64,13 +114,25 @@ impl prost::Message for TableRow {
     }
 }
 
+/// Errors that can occur when converting a row produced by `COPY` into a [`TableRow`].
+///
+/// The errors about a single value name the column and its ordinal, i.e. its position in the row
+/// starting from 0.
 #[derive(Debug, Error)]
 pub enum TableRowConversionError {
-    #[error("unsupported type {0}")]
-    UnsupportedType(Type),
+    #[error("unsupported type {typ} of column `{column}` (ordinal {ordinal})")]
+    UnsupportedType {
+        column: String,
+        ordinal: usize,
+        typ: Type,
+    },
 
-    #[error("invalid string: {0}")]
-    InvalidString(#[from] Utf8Error),
+    #[error("invalid string in column `{column}` (ordinal {ordinal}): {source}")]
+    InvalidString {
+        column: String,
+        ordinal: usize,
+        source: Utf8Error,
+    },
 
     #[error("mismatch in num of columns in schema and row")]
     NumColsMismatch,
@@ -78,102 +140,1280 @@ pub enum TableRowConversionError {
     #[error("unterminated row")]
     UnterminatedRow,
 
-    #[error("invalid value: {0}")]
-    InvalidValue(#[from] FromTextError),
+    #[error("invalid value in column `{column}` (ordinal {ordinal}): {source}")]
+    InvalidValue {
+        column: String,
+        ordinal: usize,
+        source: FromTextError,
+    },
+
+    #[error("invalid binary value in column `{column}` (ordinal {ordinal}): {source}")]
+    InvalidBinaryValue {
+        column: String,
+        ordinal: usize,
+        source: FromBinaryError,
+    },
+
+    #[error("invalid binary COPY header")]
+    InvalidBinaryHeader,
+
+    #[error("truncated binary row")]
+    TruncatedBinaryRow,
 }
 
-pub struct TableRowConverter;
+impl TableRowConversionError {
+    /// A short name of the kind of the error, used to label the conversion errors metric.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnsupportedType { .. } => "unsupported_type",
+            Self::InvalidString { .. } => "invalid_string",
+            Self::NumColsMismatch => "num_cols_mismatch",
+            Self::UnterminatedRow => "unterminated_row",
+            Self::InvalidValue { .. } => "invalid_value",
+            Self::InvalidBinaryValue { .. } => "invalid_binary_value",
+            Self::InvalidBinaryHeader => "invalid_binary_header",
+            Self::TruncatedBinaryRow => "truncated_binary_row",
+        }
+    }
+
+    fn invalid_value(ordinal: usize, column_schema: &ColumnSchema, source: FromTextError) -> Self {
+        let column = column_schema.name.clone();
+        match source {
+            FromTextError::UnsupportedType(typ) => Self::UnsupportedType {
+                column,
+                ordinal,
+                typ,
+            },
+            source => Self::InvalidValue {
+                column,
+                ordinal,
+                source,
+            },
+        }
+    }
+
+    fn invalid_binary_value(
+        ordinal: usize,
+        column_schema: &ColumnSchema,
+        source: FromBinaryError,
+    ) -> Self {
+        let column = column_schema.name.clone();
+        match source {
+            FromBinaryError::UnsupportedType(typ) => Self::UnsupportedType {
+                column,
+                ordinal,
+                typ,
+            },
+            source => Self::InvalidBinaryValue {
+                column,
+                ordinal,
+                source,
+            },
+        }
+    }
+}
+
+/// Errors that can occur when checking that rows copied with a [`CopyConfig`] can be parsed.
+#[derive(Debug, Error)]
+pub enum CopyConfigError {
+    #[error("The COPY encoding '{0}' is not supported, only UTF8 can be parsed")]
+    UnsupportedEncoding(String),
+
+    #[error("The COPY delimiter {0:?} is not supported in the {1:?} format")]
+    InvalidDelimiter(char, CopyFormat),
+
+    #[error(
+        "The COPY null string {0:?} can't contain the delimiter, a newline, a carriage return or, in the csv format, a quote"
+    )]
+    InvalidNull(String),
+}
+
+/// Parses the rows produced by `COPY ... TO STDOUT` into [`TableRow`]s.
+#[derive(Debug, Clone)]
+pub struct TableRowConverter {
+    format: CopyFormat,
+    delimiter: char,
+    null: String,
+    preserve_unsupported_types: bool,
+}
 
 impl TableRowConverter {
-    // parses text produced by this code in Postgres: https://github.com/postgres/postgres/blob/263a3f5f7f508167dbeafc2aefd5835b41d77481/src/backend/commands/copyto.c#L988-L1134
+    /// Creates a [`TableRowConverter`] parsing rows copied with `config`.
+    ///
+    /// Fails if rows copied with `config` can't be parsed, so that a mismatch between the `COPY`
+    /// options and the parser is caught before copying any table.
+    pub fn new(config: &CopyConfig) -> Result<Self, CopyConfigError> {
+        let encoding = config.encoding.to_uppercase().replace(['-', '_'], "");
+        if encoding != "UTF8" {
+            return Err(CopyConfigError::UnsupportedEncoding(
+                config.encoding.clone(),
+            ));
+        }
+
+        // The delimiter and null options aren't used in the binary format, where the values are
+        // prefixed with their length.
+        let delimiter = config.delimiter;
+        match config.format {
+            CopyFormat::Text => {
+                // Postgres rejects the same delimiters, since they would be mistaken for escape
+                // sequences or row terminators in the text format.
+                if !delimiter.is_ascii()
+                    || matches!(delimiter, '\\' | '.' | '\n' | '\r')
+                    || delimiter.is_ascii_lowercase()
+                    || delimiter.is_ascii_digit()
+                {
+                    return Err(CopyConfigError::InvalidDelimiter(delimiter, config.format));
+                }
+
+                if config.null.contains([delimiter, '\n', '\r']) {
+                    return Err(CopyConfigError::InvalidNull(config.null.clone()));
+                }
+            }
+            CopyFormat::Csv => {
+                // The quote would be mistaken for the delimiter, and an unquoted null string
+                // containing it for a quoted value.
+                let quote = CSV_QUOTE as char;
+                if !delimiter.is_ascii() || matches!(delimiter, '\n' | '\r') || delimiter == quote {
+                    return Err(CopyConfigError::InvalidDelimiter(delimiter, config.format));
+                }
+
+                if config.null.contains([delimiter, quote, '\n', '\r']) {
+                    return Err(CopyConfigError::InvalidNull(config.null.clone()));
+                }
+            }
+            CopyFormat::Binary => {}
+        }
+
+        Ok(Self {
+            format: config.format,
+            delimiter,
+            null: config.null.clone(),
+            preserve_unsupported_types: false,
+        })
+    }
+
+    /// Sets whether the values of columns whose type isn't supported are kept as
+    /// [`Cell::Unsupported`] holding their raw text, so that a single exotic column doesn't fail
+    /// the copy of a whole table.
+    ///
+    /// Only applies to the text and csv formats. Otherwise, such values are converted according to
+    /// the `unknown_types_to_bytes` feature.
+    pub fn with_unsupported_types_preserved(mut self, preserve: bool) -> Self {
+        self.preserve_unsupported_types = preserve;
+        self
+    }
+
+    /// Parses a message of the data produced by `COPY` in the format of the converter.
+    ///
+    /// In the binary format, the header preceding the first row is skipped, and `None` is returned
+    /// for the trailer, which doesn't hold a row.
+    ///
+    /// Failed conversions are counted in the conversion errors metric.
+    pub fn try_from_copy_data(
+        &self,
+        data: &[u8],
+        column_schemas: &[ColumnSchema],
+    ) -> Result<Option<TableRow>, TableRowConversionError> {
+        self.convert_copy_data(data, column_schemas)
+            .inspect_err(|err| metrics::inc_conversion_errors(err.kind()))
+    }
+
+    fn convert_copy_data(
+        &self,
+        data: &[u8],
+        column_schemas: &[ColumnSchema],
+    ) -> Result<Option<TableRow>, TableRowConversionError> {
+        if self.format != CopyFormat::Binary {
+            return self.try_from(data, column_schemas).map(Some);
+        }
+
+        let data = strip_binary_header(data)?;
+        if data.is_empty() || data == BINARY_TRAILER {
+            return Ok(None);
+        }
+
+        Self::try_from_binary(data, column_schemas).map(Some)
+    }
+
+    /// Parses a row produced by `COPY` in the text or csv format into a [`TableRow`].
+    ///
+    /// Collects the cells of [`TableRowConverter::stream_cells`].
     pub fn try_from(
+        &self,
         row: &[u8],
         column_schemas: &[ColumnSchema],
     ) -> Result<TableRow, TableRowConversionError> {
         let mut values = Vec::with_capacity(column_schemas.len());
+        for cell in self.stream_cells(row, column_schemas) {
+            values.push(cell?);
+        }
 
-        let row_str = str::from_utf8(row)?;
-        let mut column_schemas_iter = column_schemas.iter();
-        let mut chars = row_str.chars();
-        let mut val_str = String::with_capacity(10);
-        let mut in_escape = false;
-        let mut row_terminated = false;
-        let mut done = false;
-
-        while !done {
-            loop {
-                match chars.next() {
-                    Some(c) => match c {
-                        c if in_escape => {
-                            if c == 'N' {
-                                val_str.push('\\');
-                                val_str.push(c);
-                            } else if c == 'b' {
-                                val_str.push(8 as char);
-                            } else if c == 'f' {
-                                val_str.push(12 as char);
-                            } else if c == 'n' {
-                                val_str.push('\n');
-                            } else if c == 'r' {
-                                val_str.push('\r');
-                            } else if c == 't' {
-                                val_str.push('\t');
-                            } else if c == 'v' {
-                                val_str.push(11 as char)
-                            } else {
-                                val_str.push(c);
-                            }
-                            in_escape = false;
-                        }
-                        '\t' => {
-                            break;
-                        }
-                        '\n' => {
-                            row_terminated = true;
-                            break;
-                        }
-                        '\\' => in_escape = true,
-                        c => {
-                            val_str.push(c);
-                        }
-                    },
-                    None => {
-                        if !row_terminated {
-                            return Err(TableRowConversionError::UnterminatedRow);
-                        }
-                        done = true;
-                        break;
-                    }
+        Ok(TableRow { values })
+    }
+
+    /// Parses the cells of a row produced by `COPY` in the text or csv format one at a time, so
+    /// that the earlier columns of a wide row can be processed before the later ones are parsed.
+    ///
+    /// The iterator stops after the first error.
+    pub fn stream_cells<'a>(
+        &'a self,
+        row: &'a [u8],
+        column_schemas: &'a [ColumnSchema],
+    ) -> RowCells<'a> {
+        RowCells {
+            // The delimiter of the text and csv formats is ASCII, which is checked when creating
+            // the converter.
+            delimiter: self.delimiter as u8,
+            csv: self.format == CopyFormat::Csv,
+            null: self.null.as_bytes(),
+            preserve_unsupported_types: self.preserve_unsupported_types,
+            row,
+            pos: 0,
+            column_schemas: column_schemas.iter().enumerate(),
+            value: Vec::new(),
+            row_terminated: false,
+            done: false,
+        }
+    }
+
+    /// Parses a row produced by `COPY` in the binary format, i.e. a field count followed by each
+    /// value prefixed with its length, -1 for a `NULL`.
+    pub fn try_from_binary(
+        row: &[u8],
+        column_schemas: &[ColumnSchema],
+    ) -> Result<TableRow, TableRowConversionError> {
+        let (field_count, mut rest) = split_i16(row)?;
+        if usize::try_from(field_count).ok() != Some(column_schemas.len()) {
+            return Err(TableRowConversionError::NumColsMismatch);
+        }
+
+        let mut values = Vec::with_capacity(column_schemas.len());
+        for (ordinal, column_schema) in column_schemas.iter().enumerate() {
+            let (len, after_len) = split_i32(rest)?;
+            // A negative length, always -1 in practice, is a `NULL`.
+            let Ok(len) = usize::try_from(len) else {
+                values.push(Cell::Null(column_schema.typ.clone()));
+                rest = after_len;
+                continue;
+            };
+            let Some((bytes, after_value)) = after_len.split_at_checked(len) else {
+                return Err(TableRowConversionError::TruncatedBinaryRow);
+            };
+
+            let value = match BinaryFormatConverter::try_from_bytes(&column_schema.typ, bytes) {
+                Ok(value) => value,
+                Err(e) => {
+                    error!(
+                        "error parsing column `{}` of type `{}` from {} bytes",
+                        column_schema.name,
+                        column_schema.typ,
+                        bytes.len()
+                    );
+                    return Err(TableRowConversionError::invalid_binary_value(
+                        ordinal,
+                        column_schema,
+                        e,
+                    ));
+                }
+            };
+
+            values.push(value);
+            rest = after_value;
+        }
+
+        if !rest.is_empty() {
+            return Err(TableRowConversionError::NumColsMismatch);
+        }
+
+        Ok(TableRow { values })
+    }
+}
+
+/// Iterator over the cells of a row produced by `COPY` in the text or csv format, created by
+/// [`TableRowConverter::stream_cells`].
+#[derive(Debug)]
+pub struct RowCells<'a> {
+    delimiter: u8,
+    csv: bool,
+    null: &'a [u8],
+    preserve_unsupported_types: bool,
+    row: &'a [u8],
+    pos: usize,
+    column_schemas: iter::Enumerate<slice::Iter<'a, ColumnSchema>>,
+    /// The unescaped bytes of the current value, only used for the values with escapes or quotes,
+    /// since the others are parsed from the row in place.
+    value: Vec<u8>,
+    row_terminated: bool,
+    done: bool,
+}
+
+/// The position of a value in a row, as delimited by [`RowCells`].
+struct RawValue {
+    start: usize,
+    end: usize,
+    /// Whether the value had escapes or quotes, in which case its unescaped bytes are in
+    /// [`RowCells::value`] rather than in the row.
+    unescaped: bool,
+    is_null: bool,
+}
+
+impl RowCells<'_> {
+    fn next_cell(&mut self) -> Option<Result<Cell, TableRowConversionError>> {
+        if self.row_terminated {
+            return None;
+        }
+
+        let raw_value = if self.csv {
+            self.next_csv_value()
+        } else {
+            self.next_text_value()
+        };
+        let raw_value = match raw_value {
+            Ok(raw_value) => raw_value,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let Some((ordinal, column_schema)) = self.column_schemas.next() else {
+            return Some(Err(TableRowConversionError::NumColsMismatch));
+        };
+
+        if raw_value.is_null {
+            // In case of a null value, we store the type information since that will be used to
+            // correctly compute default values when needed.
+            return Some(Ok(Cell::Null(column_schema.typ.clone())));
+        }
+
+        let value = if raw_value.unescaped {
+            &self.value[..]
+        } else {
+            &self.row[raw_value.start..raw_value.end]
+        };
+        let val_str = match str::from_utf8(value) {
+            Ok(val_str) => val_str,
+            Err(e) => {
+                return Some(Err(TableRowConversionError::InvalidString {
+                    column: column_schema.name.clone(),
+                    ordinal,
+                    source: e,
+                }));
+            }
+        };
+        if self.preserve_unsupported_types
+            && !TextFormatConverter::is_supported_type(&column_schema.typ)
+        {
+            return Some(Ok(Cell::Unsupported(
+                column_schema.typ.clone(),
+                val_str.to_string(),
+            )));
+        }
+
+        match TextFormatConverter::try_from_str(&column_schema.typ, val_str) {
+            Ok(value) => Some(Ok(value)),
+            Err(e) => {
+                error!(
+                    "error parsing column `{}` of type `{}` from text `{val_str}`",
+                    column_schema.name, column_schema.typ
+                );
+                Some(Err(TableRowConversionError::invalid_value(
+                    ordinal,
+                    column_schema,
+                    e,
+                )))
+            }
+        }
+    }
+
+    // parses text produced by this code in Postgres: https://github.com/postgres/postgres/blob/263a3f5f7f508167dbeafc2aefd5835b41d77481/src/backend/commands/copyto.c#L988-L1134
+    fn next_text_value(&mut self) -> Result<RawValue, TableRowConversionError> {
+        // The delimiter, the row terminator and the escapes are ASCII, so they can be matched on
+        // bytes since they never appear within a multibyte UTF-8 character. The bytes between
+        // them are skipped over at once, and only copied when the value has escapes.
+        let row = self.row;
+        let delimiter = self.delimiter;
+        let val_start = self.pos;
+        let mut escaped = false;
+        self.value.clear();
+        let val_end = loop {
+            let Some(special) = row[self.pos..]
+                .iter()
+                .position(|&byte| byte == delimiter || byte == b'\n' || byte == b'\\')
+                .map(|offset| self.pos + offset)
+            else {
+                return Err(TableRowConversionError::UnterminatedRow);
+            };
+
+            if row[special] != b'\\' {
+                if escaped {
+                    self.value.extend_from_slice(&row[self.pos..special]);
                 }
+                self.row_terminated = row[special] == b'\n';
+                self.pos = special + 1;
+                break special;
             }
 
-            if !done {
-                let Some(column_schema) = column_schemas_iter.next() else {
-                    return Err(TableRowConversionError::NumColsMismatch);
+            let Some(&byte) = row.get(special + 1) else {
+                return Err(TableRowConversionError::UnterminatedRow);
+            };
+            let run_start = if escaped { self.pos } else { val_start };
+            self.value.extend_from_slice(&row[run_start..special]);
+            escaped = true;
+            match byte {
+                b'N' => self.value.extend_from_slice(b"\\N"),
+                b'b' => self.value.push(8),
+                b'f' => self.value.push(12),
+                b'n' => self.value.push(b'\n'),
+                b'r' => self.value.push(b'\r'),
+                b't' => self.value.push(b'\t'),
+                b'v' => self.value.push(11),
+                byte => self.value.push(byte),
+            }
+            self.pos = special + 2;
+        };
+
+        Ok(RawValue {
+            start: val_start,
+            end: val_end,
+            unescaped: escaped,
+            // The null string is matched before unescaping, as Postgres writes it verbatim.
+            is_null: row[val_start..val_end] == *self.null,
+        })
+    }
+
+    // parses csv produced by `CopyAttributeOutCSV` in Postgres: https://github.com/postgres/postgres/blob/263a3f5f7f508167dbeafc2aefd5835b41d77481/src/backend/commands/copyto.c
+    fn next_csv_value(&mut self) -> Result<RawValue, TableRowConversionError> {
+        // Postgres quotes the values containing the delimiter, a quote, a newline or a carriage
+        // return, and doubles the quotes within them. Quotes are accepted anywhere in a value, like
+        // Postgres does when reading csv, and the bytes of the value are only copied once a quote
+        // is found.
+        let row = self.row;
+        let delimiter = self.delimiter;
+        let val_start = self.pos;
+        let mut quoted = false;
+        let mut in_quotes = false;
+        self.value.clear();
+        let val_end = loop {
+            if in_quotes {
+                let Some(quote) = row[self.pos..]
+                    .iter()
+                    .position(|&byte| byte == CSV_QUOTE)
+                    .map(|offset| self.pos + offset)
+                else {
+                    return Err(TableRowConversionError::UnterminatedRow);
                 };
 
-                let value = if val_str == "\\N" {
-                    // In case of a null value, we store the type information since that will be used to
-                    // correctly compute default values when needed.
-                    Cell::Null(column_schema.typ.clone())
+                self.value.extend_from_slice(&row[self.pos..quote]);
+                self.pos = quote + 1;
+                // A doubled quote is a quote within the value, otherwise the quoted part ends.
+                if row.get(self.pos) == Some(&CSV_QUOTE) {
+                    self.value.push(CSV_QUOTE);
+                    self.pos += 1;
                 } else {
-                    match TextFormatConverter::try_from_str(&column_schema.typ, &val_str) {
-                        Ok(value) => value,
-                        Err(e) => {
-                            error!(
-                                "error parsing column `{}` of type `{}` from text `{val_str}`",
-                                column_schema.name, column_schema.typ
-                            );
-                            return Err(e.into());
+                    in_quotes = false;
+                }
+
+                continue;
+            }
+
+            let Some(special) = row[self.pos..]
+                .iter()
+                .position(|&byte| byte == delimiter || byte == b'\n' || byte == CSV_QUOTE)
+                .map(|offset| self.pos + offset)
+            else {
+                return Err(TableRowConversionError::UnterminatedRow);
+            };
+
+            let run_start = if quoted { self.pos } else { val_start };
+            if row[special] != CSV_QUOTE {
+                if quoted {
+                    self.value.extend_from_slice(&row[run_start..special]);
+                }
+                self.row_terminated = row[special] == b'\n';
+                self.pos = special + 1;
+                break special;
+            }
+
+            self.value.extend_from_slice(&row[run_start..special]);
+            quoted = true;
+            in_quotes = true;
+            self.pos = special + 1;
+        };
+
+        Ok(RawValue {
+            start: val_start,
+            end: val_end,
+            unescaped: quoted,
+            // A quoted value is never a null, so that the null string can be told apart from a
+            // string with the same value, e.g. an empty string with the default null string.
+            is_null: !quoted && row[val_start..val_end] == *self.null,
+        })
+    }
+}
+
+impl Iterator for RowCells<'_> {
+    type Item = Result<Cell, TableRowConversionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let cell = self.next_cell();
+        if !matches!(cell, Some(Ok(_))) {
+            self.done = true;
+        }
+
+        cell
+    }
+}
+
+/// Strips the header preceding the first row of the data produced by `COPY` in the binary format,
+/// if `data` starts with it.
+///
+/// The header can't be mistaken for a row, since its first two bytes read as a field count greater
+/// than the maximum number of columns of a table.
+fn strip_binary_header(data: &[u8]) -> Result<&[u8], TableRowConversionError> {
+    let Some(rest) = data.strip_prefix(BINARY_SIGNATURE) else {
+        return Ok(data);
+    };
+
+    let (flags, rest) =
+        split_i32(rest).map_err(|_| TableRowConversionError::InvalidBinaryHeader)?;
+    if flags as u32 & BINARY_OIDS_FLAG != 0 {
+        return Err(TableRowConversionError::InvalidBinaryHeader);
+    }
+
+    let (extension_len, rest) =
+        split_i32(rest).map_err(|_| TableRowConversionError::InvalidBinaryHeader)?;
+    usize::try_from(extension_len)
+        .ok()
+        .and_then(|extension_len| rest.get(extension_len..))
+        .ok_or(TableRowConversionError::InvalidBinaryHeader)
+}
+
+fn split_i16(bytes: &[u8]) -> Result<(i16, &[u8]), TableRowConversionError> {
+    bytes
+        .split_first_chunk()
+        .map(|(value, rest)| (i16::from_be_bytes(*value), rest))
+        .ok_or(TableRowConversionError::TruncatedBinaryRow)
+}
+
+fn split_i32(bytes: &[u8]) -> Result<(i32, &[u8]), TableRowConversionError> {
+    bytes
+        .split_first_chunk()
+        .map(|(value, rest)| (i32::from_be_bytes(*value), rest))
+        .ok_or(TableRowConversionError::TruncatedBinaryRow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column_schemas() -> Vec<ColumnSchema> {
+        ["id", "name"]
+            .into_iter()
+            .zip([Type::INT4, Type::TEXT])
+            .map(|(name, typ)| ColumnSchema {
+                name: name.to_string(),
+                typ,
+                modifier: -1,
+                nullable: true,
+                primary: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rows_are_parsed_with_the_default_copy_config() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+
+        let row = converter
+            .try_from(b"1\ta\\tb\n", &column_schemas())
+            .unwrap();
+        assert_eq!(
+            row.values,
+            vec![Cell::I32(1), Cell::String("a\tb".to_string())]
+        );
+
+        let row = converter.try_from(b"2\t\\N\n", &column_schemas()).unwrap();
+        assert_eq!(row.values, vec![Cell::I32(2), Cell::Null(Type::TEXT)]);
+    }
+
+    #[test]
+    fn rows_are_parsed_with_a_custom_delimiter_and_null() {
+        let config = CopyConfig {
+            delimiter: ',',
+            null: "NULL".to_string(),
+            ..CopyConfig::default()
+        };
+        let converter = TableRowConverter::new(&config).unwrap();
+
+        let row = converter.try_from(b"1,NULL\n", &column_schemas()).unwrap();
+        assert_eq!(row.values, vec![Cell::I32(1), Cell::Null(Type::TEXT)]);
+
+        // A tab is just data when it's not the delimiter, and an escaped delimiter is data too.
+        let row = converter
+            .try_from(b"2,a\tb\\,c\n", &column_schemas())
+            .unwrap();
+        assert_eq!(
+            row.values,
+            vec![Cell::I32(2), Cell::String("a\tb,c".to_string())]
+        );
+    }
+
+    #[test]
+    fn csv_rows_are_parsed() {
+        let config = CopyConfig {
+            format: CopyFormat::Csv,
+            delimiter: ',',
+            null: String::new(),
+            ..CopyConfig::default()
+        };
+        let converter = TableRowConverter::new(&config).unwrap();
+
+        let row = converter.try_from(b"1,plain\n", &column_schemas()).unwrap();
+        assert_eq!(
+            row.values,
+            vec![Cell::I32(1), Cell::String("plain".to_string())]
+        );
+
+        // Quoted values hold the delimiter, newlines and doubled quotes, and backslashes are data.
+        let row = converter
+            .try_from(b"2,\"a,b\nsay \"\"hi\"\" \\N\"\n", &column_schemas())
+            .unwrap();
+        assert_eq!(
+            row.values,
+            vec![
+                Cell::I32(2),
+                Cell::String("a,b\nsay \"hi\" \\N".to_string())
+            ]
+        );
+
+        // Only an unquoted null string is a null, a quoted one is a string.
+        let row = converter.try_from(b"3,\n", &column_schemas()).unwrap();
+        assert_eq!(row.values, vec![Cell::I32(3), Cell::Null(Type::TEXT)]);
+        let row = converter.try_from(b"4,\"\"\n", &column_schemas()).unwrap();
+        assert_eq!(row.values, vec![Cell::I32(4), Cell::String(String::new())]);
+
+        // Quotes can start and end anywhere in a value.
+        let row = converter
+            .try_from(b"\"5\",a\"b,\"c\n", &column_schemas())
+            .unwrap();
+        assert_eq!(
+            row.values,
+            vec![Cell::I32(5), Cell::String("ab,c".to_string())]
+        );
+    }
+
+    #[test]
+    fn malformed_csv_rows_are_rejected() {
+        let config = CopyConfig {
+            format: CopyFormat::Csv,
+            delimiter: ',',
+            ..CopyConfig::default()
+        };
+        let converter = TableRowConverter::new(&config).unwrap();
+
+        // The quoted value isn't closed, so the newline is part of it.
+        assert!(matches!(
+            converter.try_from(b"1,\"open\n", &column_schemas()),
+            Err(TableRowConversionError::UnterminatedRow)
+        ));
+        assert!(matches!(
+            converter.try_from(b"1,a,b\n", &column_schemas()),
+            Err(TableRowConversionError::NumColsMismatch)
+        ));
+    }
+
+    #[test]
+    fn cells_are_streamed_before_the_end_of_the_row() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+        let column_schemas = column_schemas();
+
+        // The first cell is yielded even though the row is cut, and nothing follows the error.
+        let mut cells = converter.stream_cells("1\tnaïve\\nend".as_bytes(), &column_schemas);
+        assert_eq!(cells.next().unwrap().unwrap(), Cell::I32(1));
+        assert!(matches!(
+            cells.next(),
+            Some(Err(TableRowConversionError::UnterminatedRow))
+        ));
+        assert!(cells.next().is_none());
+
+        let cells = converter
+            .stream_cells("1\tnaïve\\nend\n".as_bytes(), &column_schemas)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            cells,
+            vec![Cell::I32(1), Cell::String("naïve\nend".to_string())]
+        );
+    }
+
+    #[test]
+    fn bytea_values_are_unescaped_once_before_being_decoded() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+        let column_schemas: Vec<_> = ["hex", "escape"]
+            .into_iter()
+            .map(|name| ColumnSchema {
+                name: name.to_string(),
+                typ: Type::BYTEA,
+                modifier: -1,
+                nullable: true,
+                primary: false,
+            })
+            .collect();
+
+        // `COPY` escapes the backslashes of both formats, which must not be mistaken for its own
+        // escapes like `\N` or `\t`.
+        let row = converter
+            .try_from(b"\\\\x00ff09\t\\\\000\\\\377\\\\011\n", &column_schemas)
+            .unwrap();
+        assert_eq!(
+            row.values,
+            vec![
+                Cell::Bytes(vec![0x00, 0xff, 0x09]),
+                Cell::Bytes(vec![0x00, 0xff, 0x09])
+            ]
+        );
+    }
+
+    /// Parses a row in the text format byte by byte, like the parser did before it skipped over
+    /// the bytes without escapes, to check that both parse rows the same.
+    fn parse_bytewise(row: &[u8], column_schemas: &[ColumnSchema]) -> Option<Vec<Cell>> {
+        let mut values = vec![];
+        let mut column_schemas = column_schemas.iter();
+        let mut pos = 0;
+        loop {
+            let val_start = pos;
+            let mut value = vec![];
+            let mut in_escape = false;
+            let row_terminated = loop {
+                let byte = *row.get(pos)?;
+                pos += 1;
+                match byte {
+                    byte if in_escape => {
+                        match byte {
+                            b'N' => value.extend_from_slice(b"\\N"),
+                            b'b' => value.push(8),
+                            b'f' => value.push(12),
+                            b'n' => value.push(b'\n'),
+                            b'r' => value.push(b'\r'),
+                            b't' => value.push(b'\t'),
+                            b'v' => value.push(11),
+                            byte => value.push(byte),
                         }
+                        in_escape = false;
                     }
-                };
+                    b'\t' => break false,
+                    b'\n' => break true,
+                    b'\\' => in_escape = true,
+                    byte => value.push(byte),
+                }
+            };
+
+            let column_schema = column_schemas.next()?;
+            if row[val_start..pos - 1] == *b"\\N" {
+                values.push(Cell::Null(column_schema.typ.clone()));
+            } else {
+                let value = str::from_utf8(&value).ok()?;
+                values.push(TextFormatConverter::try_from_str(&column_schema.typ, value).ok()?);
+            }
 
-                values.push(value);
-                val_str.clear();
+            if row_terminated {
+                return Some(values);
             }
         }
+    }
 
-        Ok(TableRow { values })
+    #[test]
+    fn rows_are_parsed_like_when_parsed_bytewise() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+        let column_schemas = column_schemas();
+        let tokens = [
+            "a", "1", "-", " ", "N", "é", "\\t", "\\n", "\\\\", "\\N", "\\x", "\\b", "\\v", "\\\t",
+            "\t", "\n",
+        ];
+
+        // A linear congruential generator, so that the rows are the same on every run.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) as usize % bound
+        };
+
+        for _ in 0..10_000 {
+            let mut row = vec![];
+            for _ in 0..next(12) {
+                row.extend_from_slice(tokens[next(tokens.len())].as_bytes());
+            }
+            if next(4) != 0 {
+                row.push(b'\n');
+            }
+
+            let values = converter.try_from(&row, &column_schemas).ok();
+            assert_eq!(
+                values.map(|row| row.values),
+                parse_bytewise(&row, &column_schemas),
+                "row {:?}",
+                String::from_utf8_lossy(&row)
+            );
+        }
+
+        // Wide rows with long values, with and without escapes.
+        let column_schemas: Vec<_> = (0..64)
+            .map(|i| ColumnSchema::new(format!("c{i}"), Type::TEXT, -1, true, false))
+            .collect();
+        for value in ["a".repeat(1_000), "a\\tb\\\\".repeat(200)] {
+            let mut row = vec![value; 64].join("\t").into_bytes();
+            row.push(b'\n');
+
+            let values = converter.try_from(&row, &column_schemas).unwrap().values;
+            assert_eq!(Some(values), parse_bytewise(&row, &column_schemas));
+        }
+    }
+
+    #[test]
+    fn conversion_errors_name_the_failing_column() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+        let mut column_schemas = column_schemas();
+        column_schemas.push(ColumnSchema::new(
+            "count".to_string(),
+            Type::INT8,
+            -1,
+            true,
+            false,
+        ));
+
+        let err = converter
+            .try_from(b"1\tname\tmany\n", &column_schemas)
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            TableRowConversionError::InvalidValue {
+                column,
+                ordinal: 2,
+                source: FromTextError::InvalidInt(_),
+            } if column == "count"
+        ));
+        assert!(err.to_string().contains("`count` (ordinal 2)"));
+
+        let err = converter
+            .try_from(b"1\t\xff\t2\n", &column_schemas)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TableRowConversionError::InvalidString { ordinal: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn invalid_uuids_name_the_failing_column() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+        let mut column_schemas = column_schemas();
+        column_schemas.push(ColumnSchema::new(
+            "external_id".to_string(),
+            Type::UUID,
+            -1,
+            true,
+            false,
+        ));
+
+        let row = converter
+            .try_from(
+                b"1\tname\ta0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11\n",
+                &column_schemas,
+            )
+            .unwrap();
+        assert_eq!(
+            row.values[2],
+            Cell::Uuid(uuid::Uuid::from_u128(
+                0xa0eebc99_9c0b_4ef8_bb6d_6bb9bd380a11
+            ))
+        );
+
+        let err = converter
+            .try_from(b"1\tname\tgarbage\n", &column_schemas)
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            TableRowConversionError::InvalidValue {
+                column,
+                ordinal: 2,
+                source: FromTextError::InvalidUuid(_),
+            } if column == "external_id"
+        ));
+    }
+
+    #[cfg(not(feature = "json_as_string"))]
+    #[test]
+    fn invalid_json_names_the_failing_column() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+        let mut column_schemas = column_schemas();
+        column_schemas.push(ColumnSchema::new(
+            "payload".to_string(),
+            Type::JSONB,
+            -1,
+            true,
+            false,
+        ));
+
+        let err = converter
+            .try_from(b"1\tname\t{\"a\":\n", &column_schemas)
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            TableRowConversionError::InvalidValue {
+                column,
+                ordinal: 2,
+                source: FromTextError::InvalidJson(_),
+            } if column == "payload"
+        ));
+    }
+
+    #[test]
+    fn unsupported_types_are_preserved_as_raw_text() {
+        let mut column_schemas = column_schemas();
+        column_schemas[1].typ = Type::POINT;
+
+        let converter = TableRowConverter::new(&CopyConfig::default())
+            .unwrap()
+            .with_unsupported_types_preserved(true);
+        let row = converter.try_from(b"1\t(1,2)\n", &column_schemas).unwrap();
+        assert_eq!(
+            row.values,
+            vec![
+                Cell::I32(1),
+                Cell::Unsupported(Type::POINT, "(1,2)".to_string())
+            ]
+        );
+
+        // Nulls keep their type like for any other column.
+        let row = converter.try_from(b"2\t\\N\n", &column_schemas).unwrap();
+        assert_eq!(row.values, vec![Cell::I32(2), Cell::Null(Type::POINT)]);
+    }
+
+    #[test]
+    fn copy_configs_which_cant_be_parsed_are_rejected() {
+        let configs = [
+            CopyConfig {
+                encoding: "LATIN1".to_string(),
+                ..CopyConfig::default()
+            },
+            CopyConfig {
+                delimiter: '\\',
+                ..CopyConfig::default()
+            },
+            CopyConfig {
+                delimiter: 'n',
+                ..CopyConfig::default()
+            },
+            CopyConfig {
+                delimiter: ',',
+                null: "a,b".to_string(),
+                ..CopyConfig::default()
+            },
+            CopyConfig {
+                format: CopyFormat::Csv,
+                delimiter: '"',
+                ..CopyConfig::default()
+            },
+            CopyConfig {
+                format: CopyFormat::Csv,
+                delimiter: ',',
+                null: "\"\"".to_string(),
+                ..CopyConfig::default()
+            },
+        ];
+
+        for config in configs {
+            assert!(TableRowConverter::new(&config).is_err(), "{config:?}");
+        }
+    }
+
+    /// Returns the binary row holding `values`, `None` being a `NULL`.
+    fn binary_row(values: &[Option<&[u8]>]) -> Vec<u8> {
+        let mut row = (values.len() as i16).to_be_bytes().to_vec();
+        for value in values {
+            match value {
+                Some(value) => {
+                    row.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                    row.extend_from_slice(value);
+                }
+                None => row.extend_from_slice(&(-1i32).to_be_bytes()),
+            }
+        }
+
+        row
+    }
+
+    #[test]
+    fn binary_rows_are_parsed() {
+        let config = CopyConfig {
+            format: CopyFormat::Binary,
+            ..CopyConfig::default()
+        };
+        let converter = TableRowConverter::new(&config).unwrap();
+
+        // The first message starts with the header, which has an extension area of 2 bytes here.
+        let mut data = BINARY_SIGNATURE.to_vec();
+        data.extend_from_slice(&0i32.to_be_bytes());
+        data.extend_from_slice(&2i32.to_be_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend(binary_row(&[Some(&1i32.to_be_bytes()), Some(b"a\tb")]));
+        let row = converter
+            .try_from_copy_data(&data, &column_schemas())
+            .unwrap();
+        assert_eq!(
+            row.unwrap().values,
+            vec![Cell::I32(1), Cell::String("a\tb".to_string())]
+        );
+
+        let data = binary_row(&[Some(&2i32.to_be_bytes()), None]);
+        let row = converter
+            .try_from_copy_data(&data, &column_schemas())
+            .unwrap();
+        assert_eq!(
+            row.unwrap().values,
+            vec![Cell::I32(2), Cell::Null(Type::TEXT)]
+        );
+
+        let row = converter
+            .try_from_copy_data(BINARY_TRAILER, &column_schemas())
+            .unwrap();
+        assert!(row.is_none());
+    }
+
+    #[test]
+    fn malformed_binary_rows_are_rejected() {
+        let column_schemas = column_schemas();
+
+        let row = binary_row(&[Some(&1i32.to_be_bytes())]);
+        assert!(matches!(
+            TableRowConverter::try_from_binary(&row, &column_schemas),
+            Err(TableRowConversionError::NumColsMismatch)
+        ));
+
+        let mut row = binary_row(&[Some(&1i32.to_be_bytes()), Some(b"name")]);
+        row.truncate(row.len() - 1);
+        assert!(matches!(
+            TableRowConverter::try_from_binary(&row, &column_schemas),
+            Err(TableRowConversionError::TruncatedBinaryRow)
+        ));
+
+        // A value whose length doesn't match its type.
+        let row = binary_row(&[Some(&1i16.to_be_bytes()), None]);
+        assert!(matches!(
+            TableRowConverter::try_from_binary(&row, &column_schemas),
+            Err(TableRowConversionError::InvalidBinaryValue { ordinal: 0, .. })
+        ));
+
+        // Rows including their OID aren't supported.
+        let mut data = BINARY_SIGNATURE.to_vec();
+        data.extend_from_slice(&BINARY_OIDS_FLAG.to_be_bytes());
+        data.extend_from_slice(&0i32.to_be_bytes());
+        assert!(matches!(
+            strip_binary_header(&data),
+            Err(TableRowConversionError::InvalidBinaryHeader)
+        ));
+    }
+
+    #[test]
+    fn encoding_names_are_normalized() {
+        for encoding in ["UTF8", "utf-8", "Utf_8"] {
+            let config = CopyConfig {
+                encoding: encoding.to_string(),
+                ..CopyConfig::default()
+            };
+            assert!(TableRowConverter::new(&config).is_ok());
+        }
+    }
+
+    fn custom_type() -> Type {
+        Type::new(
+            "custom".to_string(),
+            16_384,
+            tokio_postgres::types::Kind::Simple,
+            "public".to_string(),
+        )
+    }
+
+    fn row_with_every_variant() -> TableRow {
+        use crate::conversions::ArrayCell;
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let timestamp = NaiveDate::from_ymd_opt(2024, 5, 6)
+            .unwrap()
+            .and_hms_opt(7, 8, 9)
+            .unwrap();
+        TableRow::new(vec![
+            Cell::Null(Type::INT4),
+            Cell::Bool(true),
+            Cell::String("text".to_string()),
+            Cell::I16(-16),
+            Cell::I32(-32),
+            Cell::U32(32),
+            Cell::I64(-64),
+            Cell::F32(3.2),
+            Cell::F64(6.4),
+            Cell::Numeric("12.345".parse().unwrap()),
+            Cell::Date(timestamp.date()),
+            Cell::Time(timestamp.time()),
+            Cell::TimeStamp(timestamp),
+            Cell::TimeStampTz(Utc.from_utc_datetime(&timestamp)),
+            Cell::Interval(crate::conversions::interval::PgInterval::new(1, 2, 3)),
+            Cell::Uuid(uuid::Uuid::from_u128(1)),
+            Cell::Json(serde_json::json!({"a": "1"})),
+            Cell::Bytes(vec![1, 2, 3]),
+            Cell::Array(ArrayCell::I32(vec![Some(1), None, Some(3)])),
+            Cell::Composite(vec![Cell::I32(1), Cell::Null(Type::TEXT)]),
+            Cell::Unsupported(custom_type(), "raw".to_string()),
+        ])
+    }
+
+    #[test]
+    fn cleared_cells_keep_their_variant_and_type() {
+        use crate::conversions::ArrayCell;
+        use crate::conversions::interval::PgInterval;
+        use crate::conversions::numeric::PgNumeric;
+        use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+        let mut row = row_with_every_variant();
+        row.values.iter_mut().for_each(Cell::clear);
+
+        assert_eq!(
+            row.values,
+            vec![
+                Cell::Null(Type::INT4),
+                Cell::Bool(false),
+                Cell::String(String::new()),
+                Cell::I16(0),
+                Cell::I32(0),
+                Cell::U32(0),
+                Cell::I64(0),
+                Cell::F32(0.),
+                Cell::F64(0.),
+                Cell::Numeric(PgNumeric::default()),
+                Cell::Date(NaiveDate::default()),
+                Cell::Time(NaiveTime::default()),
+                Cell::TimeStamp(NaiveDateTime::default()),
+                Cell::TimeStampTz(DateTime::<Utc>::default()),
+                Cell::Interval(PgInterval::default()),
+                Cell::Uuid(uuid::Uuid::nil()),
+                Cell::Json(serde_json::json!({})),
+                Cell::Bytes(vec![]),
+                Cell::Array(ArrayCell::I32(vec![])),
+                Cell::Composite(vec![Cell::I32(0), Cell::Null(Type::TEXT)]),
+                Cell::Unsupported(custom_type(), String::new()),
+            ]
+        );
+    }
+
+    #[cfg(feature = "bigquery")]
+    #[test]
+    fn encoded_len_matches_the_encoding_of_cleared_rows() {
+        use prost::Message;
+
+        let mut row = row_with_every_variant();
+        let encoded = row.encode_to_vec();
+        assert_eq!(row.encoded_len(), encoded.len());
+
+        row.clear();
+        let cleared = row.encode_to_vec();
+        assert_eq!(row.encoded_len(), cleared.len());
+        assert!(cleared.len() < encoded.len());
+
+        // Clearing a cleared row changes nothing.
+        row.clear();
+        assert_eq!(row.encode_to_vec(), cleared);
+    }
+
+    #[cfg(feature = "bigquery")]
+    #[test]
+    fn encoded_rows_are_decoded_back() {
+        use crate::conversions::ArrayCell;
+        use crate::conversions::interval::PgInterval;
+        use chrono::{NaiveDate, TimeZone, Utc};
+        use prost::Message;
+
+        let timestamp = NaiveDate::from_ymd_opt(2024, 5, 6)
+            .unwrap()
+            .and_hms_micro_opt(7, 8, 9, 123_456)
+            .unwrap();
+        let (types, values): (Vec<_>, Vec<_>) = [
+            (Type::BOOL, Cell::Bool(true)),
+            (Type::TEXT, Cell::String("text".to_string())),
+            (Type::INT2, Cell::I16(-16)),
+            (Type::INT4, Cell::I32(-32)),
+            (Type::INT8, Cell::I64(-64)),
+            (Type::OID, Cell::U32(32)),
+            (Type::FLOAT4, Cell::F32(3.25)),
+            (Type::FLOAT8, Cell::F64(-6.5)),
+            (Type::NUMERIC, Cell::Numeric("12.345".parse().unwrap())),
+            (Type::DATE, Cell::Date(timestamp.date())),
+            (Type::TIME, Cell::Time(timestamp.time())),
+            (Type::TIMESTAMP, Cell::TimeStamp(timestamp)),
+            (
+                Type::TIMESTAMPTZ,
+                Cell::TimeStampTz(Utc.from_utc_datetime(&timestamp)),
+            ),
+            (
+                Type::INTERVAL,
+                Cell::Interval(PgInterval::new(14, 3, 4_000_000)),
+            ),
+            (Type::UUID, Cell::Uuid(uuid::Uuid::from_u128(1))),
+            (Type::BYTEA, Cell::Bytes(vec![0, 1, 255])),
+            (
+                Type::INT2_ARRAY,
+                Cell::Array(ArrayCell::I16(vec![Some(1), Some(-2)])),
+            ),
+            (
+                Type::TEXT_ARRAY,
+                Cell::Array(ArrayCell::String(vec![
+                    Some("a".to_string()),
+                    Some(String::new()),
+                ])),
+            ),
+            (
+                Type::DATE_ARRAY,
+                Cell::Array(ArrayCell::Date(vec![Some(timestamp.date())])),
+            ),
+            (Type::INT8_ARRAY, Cell::Array(ArrayCell::I64(vec![]))),
+        ]
+        .into_iter()
+        .unzip();
+        let column_schemas: Vec<_> = types
+            .into_iter()
+            .enumerate()
+            .map(|(i, typ)| ColumnSchema {
+                name: format!("column_{i}"),
+                typ,
+                modifier: -1,
+                nullable: true,
+                primary: false,
+            })
+            .collect();
+        let row = TableRow::new(values);
+
+        let decoded = TableRow::decode_prost(row.encode_to_vec().as_slice(), &column_schemas);
+        assert_eq!(decoded.unwrap(), row);
+    }
+
+    #[cfg(feature = "bigquery")]
+    #[test]
+    fn nulls_are_decoded_into_default_values_and_extra_fields_are_skipped() {
+        use prost::Message;
+
+        let row = TableRow::new(vec![
+            Cell::Null(Type::INT4),
+            Cell::String("a".to_string()),
+            Cell::I64(3),
+        ]);
+
+        let decoded = TableRow::decode_prost(row.encode_to_vec().as_slice(), &column_schemas());
+        assert_eq!(
+            decoded.unwrap().values,
+            vec![Cell::I32(0), Cell::String("a".to_string())]
+        );
     }
 }
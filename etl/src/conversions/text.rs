@@ -4,12 +4,17 @@ use std::num::{ParseFloatError, ParseIntError};
 use bigdecimal::ParseBigDecimalError;
 use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use thiserror::Error;
-use tokio_postgres::types::Type;
+use tokio_postgres::types::{Kind, Type};
 use uuid::Uuid;
 
-use crate::conversions::{bool::parse_bool, hex};
+use crate::conversions::composite::{CompositeParseError, parse_composite};
+use crate::conversions::enums::{EnumParseError, default_enum, is_enum, parse_enum};
+use crate::conversions::geometry::{GeometryParseError, is_geometry, parse_geometry};
+use crate::conversions::hstore::{HstoreParseError, is_hstore, parse_hstore};
+use crate::conversions::interval::{IntervalParseError, PgInterval};
+use crate::conversions::{bool::parse_bool, bytea};
 
-use super::{ArrayCell, Cell, bool::ParseBoolError, hex::ByteaHexParseError, numeric::PgNumeric};
+use super::{ArrayCell, Cell, bool::ParseBoolError, bytea::ByteaParseError, numeric::PgNumeric};
 
 #[derive(Debug, Error)]
 pub enum FromTextError {
@@ -26,7 +31,7 @@ pub enum FromTextError {
     InvalidNumeric(#[from] ParseBigDecimalError),
 
     #[error("invalid bytea: {0}")]
-    InvalidBytea(#[from] ByteaHexParseError),
+    InvalidBytea(#[from] ByteaParseError),
 
     #[error("invalid uuid: {0}")]
     InvalidUuid(#[from] uuid::Error),
@@ -37,11 +42,35 @@ pub enum FromTextError {
     #[error("invalid timestamp: {0} ")]
     InvalidTimestamp(#[from] chrono::ParseError),
 
+    #[error("invalid interval: {0}")]
+    InvalidInterval(#[from] IntervalParseError),
+
     #[error("invalid array: {0}")]
     InvalidArray(#[from] ArrayParseError),
 
+    #[error("invalid lsn: {0}")]
+    InvalidLsn(String),
+
+    #[error("invalid money: {0}")]
+    InvalidMoney(String),
+
+    #[error("invalid composite: {0}")]
+    InvalidComposite(#[from] CompositeParseError),
+
+    #[error("invalid hstore: {0}")]
+    InvalidHstore(#[from] HstoreParseError),
+
+    #[error("invalid enum: {0}")]
+    InvalidEnum(#[from] EnumParseError),
+
+    #[error("invalid geometry: {0}")]
+    InvalidGeometry(#[from] GeometryParseError),
+
     #[error("row get error: {0:?}")]
     RowGetError(#[from] Box<dyn std::error::Error + Sync + Send>),
+
+    #[error("unsupported type {0}")]
+    UnsupportedType(Type),
 }
 
 pub struct TextFormatConverter;
@@ -80,6 +109,8 @@ impl TextFormatConverter {
             Type::FLOAT8_ARRAY => Cell::Array(ArrayCell::F64(Vec::default())),
             Type::NUMERIC => Cell::Numeric(PgNumeric::default()),
             Type::NUMERIC_ARRAY => Cell::Array(ArrayCell::Numeric(Vec::default())),
+            Type::MONEY => Cell::Numeric(PgNumeric::default()),
+            Type::MONEY_ARRAY => Cell::Array(ArrayCell::Numeric(Vec::default())),
             Type::BYTEA => Cell::Bytes(Vec::default()),
             Type::BYTEA_ARRAY => Cell::Array(ArrayCell::Bytes(Vec::default())),
             Type::DATE => Cell::Date(NaiveDate::MIN),
@@ -93,22 +124,114 @@ impl TextFormatConverter {
                 Cell::TimeStampTz(val)
             }
             Type::TIMESTAMPTZ_ARRAY => Cell::Array(ArrayCell::TimeStampTz(Vec::default())),
+            Type::INTERVAL => Cell::Interval(PgInterval::default()),
             Type::UUID => Cell::Uuid(Uuid::default()),
             Type::UUID_ARRAY => Cell::Array(ArrayCell::Uuid(Vec::default())),
+            #[cfg(not(feature = "json_as_string"))]
             Type::JSON | Type::JSONB => Cell::Json(serde_json::Value::default()),
+            #[cfg(not(feature = "json_as_string"))]
             Type::JSON_ARRAY | Type::JSONB_ARRAY => Cell::Array(ArrayCell::Json(Vec::default())),
+            #[cfg(feature = "json_as_string")]
+            Type::JSON | Type::JSONB => Cell::String("null".to_string()),
+            #[cfg(feature = "json_as_string")]
+            Type::JSON_ARRAY | Type::JSONB_ARRAY => Cell::Array(ArrayCell::String(Vec::default())),
             Type::OID => Cell::U32(u32::default()),
             Type::OID_ARRAY => Cell::Array(ArrayCell::U32(Vec::default())),
+            Type::PG_LSN => Cell::String("0/0".to_string()),
+            Type::PG_LSN_ARRAY => Cell::Array(ArrayCell::String(Vec::default())),
+            _ if is_hstore(typ) => Cell::Json(serde_json::Value::Object(serde_json::Map::new())),
+            _ if is_enum(typ) => default_enum(typ),
+            _ if is_geometry(typ) => Cell::Geometry(Vec::default()),
             #[cfg(feature = "unknown_types_to_bytes")]
             _ => Cell::String(String::default()),
+            // Values of unsupported types are only kept as the raw text of an unsupported cell.
             #[cfg(not(feature = "unknown_types_to_bytes"))]
-            _ => Err(CdcEventConversionError::UnsupportedType(
-                typ.name().to_string(),
-            )),
+            _ => Cell::Unsupported(typ.clone(), String::default()),
         }
     }
 
+    /// Returns whether values of type `typ` are parsed into a [`Cell`] of their own type, rather
+    /// than being kept as strings or rejected depending on the `unknown_types_to_bytes` feature.
+    pub fn is_supported_type(typ: &Type) -> bool {
+        if matches!(typ.kind(), Kind::Composite(_))
+            || is_hstore(typ)
+            || is_enum(typ)
+            || is_geometry(typ)
+        {
+            return true;
+        }
+
+        matches!(
+            *typ,
+            Type::BOOL
+                | Type::BOOL_ARRAY
+                | Type::CHAR
+                | Type::BPCHAR
+                | Type::VARCHAR
+                | Type::NAME
+                | Type::TEXT
+                | Type::CHAR_ARRAY
+                | Type::BPCHAR_ARRAY
+                | Type::VARCHAR_ARRAY
+                | Type::NAME_ARRAY
+                | Type::TEXT_ARRAY
+                | Type::INT2
+                | Type::INT2_ARRAY
+                | Type::INT4
+                | Type::INT4_ARRAY
+                | Type::INT8
+                | Type::INT8_ARRAY
+                | Type::FLOAT4
+                | Type::FLOAT4_ARRAY
+                | Type::FLOAT8
+                | Type::FLOAT8_ARRAY
+                | Type::NUMERIC
+                | Type::NUMERIC_ARRAY
+                | Type::MONEY
+                | Type::MONEY_ARRAY
+                | Type::BYTEA
+                | Type::BYTEA_ARRAY
+                | Type::DATE
+                | Type::DATE_ARRAY
+                | Type::TIME
+                | Type::TIME_ARRAY
+                | Type::TIMESTAMP
+                | Type::TIMESTAMP_ARRAY
+                | Type::TIMESTAMPTZ
+                | Type::TIMESTAMPTZ_ARRAY
+                | Type::INTERVAL
+                | Type::UUID
+                | Type::UUID_ARRAY
+                | Type::JSON
+                | Type::JSONB
+                | Type::JSON_ARRAY
+                | Type::JSONB_ARRAY
+                | Type::OID
+                | Type::OID_ARRAY
+                | Type::PG_LSN
+                | Type::PG_LSN_ARRAY
+        )
+    }
+
     pub fn try_from_str(typ: &Type, str: &str) -> Result<Cell, FromTextError> {
+        // Composite types aren't built in, so they are recognized by the fields of their type,
+        // which are looked up along with the schema of the table.
+        if let Kind::Composite(fields) = typ.kind() {
+            return parse_composite(str, fields);
+        }
+        // Likewise, `hstore` comes from an extension and is recognized by the name of its type.
+        if is_hstore(typ) {
+            return Ok(Cell::Json(serde_json::Value::Object(parse_hstore(str)?)));
+        }
+        // Enum types are recognized by their labels, which are looked up like composite fields.
+        if is_enum(typ) {
+            return Ok(parse_enum(typ, str)?);
+        }
+        // PostGIS types come from an extension too, and are output as the hex of their EWKB.
+        if is_geometry(typ) {
+            return Ok(Cell::Geometry(parse_geometry(str)?));
+        }
+
         match *typ {
             Type::BOOL => Ok(Cell::Bool(parse_bool(str)?)),
             Type::BOOL_ARRAY => TextFormatConverter::parse_array(
@@ -154,10 +277,16 @@ impl TextFormatConverter {
                 |str| Ok(Some(str.parse()?)),
                 ArrayCell::Numeric,
             ),
-            Type::BYTEA => Ok(Cell::Bytes(hex::from_bytea_hex(str)?)),
+            Type::MONEY => Ok(Cell::Numeric(parse_money(str)?)),
+            Type::MONEY_ARRAY => TextFormatConverter::parse_array(
+                str,
+                |str| Ok(Some(parse_money(str)?)),
+                ArrayCell::Numeric,
+            ),
+            Type::BYTEA => Ok(Cell::Bytes(bytea::from_bytea(str)?)),
             Type::BYTEA_ARRAY => TextFormatConverter::parse_array(
                 str,
-                |str| Ok(Some(hex::from_bytea_hex(str)?)),
+                |str| Ok(Some(bytea::from_bytea(str)?)),
                 ArrayCell::Bytes,
             ),
             Type::DATE => {
@@ -192,46 +321,13 @@ impl TextFormatConverter {
                 },
                 ArrayCell::TimeStamp,
             ),
-            Type::TIMESTAMPTZ => {
-                let val =
-                    match DateTime::<FixedOffset>::parse_from_str(str, "%Y-%m-%d %H:%M:%S%.f%#z") {
-                        Ok(val) => val,
-                        Err(_) => {
-                            DateTime::<FixedOffset>::parse_from_str(str, "%Y-%m-%d %H:%M:%S%.f%:z")?
-                        }
-                    };
-                Ok(Cell::TimeStampTz(val.into()))
-            }
-            Type::TIMESTAMPTZ_ARRAY => {
-                match TextFormatConverter::parse_array(
-                    str,
-                    |str| {
-                        Ok(Some(
-                            DateTime::<FixedOffset>::parse_from_str(
-                                str,
-                                "%Y-%m-%d %H:%M:%S%.f%#z",
-                            )?
-                            .into(),
-                        ))
-                    },
-                    ArrayCell::TimeStampTz,
-                ) {
-                    Ok(val) => Ok(val),
-                    Err(_) => TextFormatConverter::parse_array(
-                        str,
-                        |str| {
-                            Ok(Some(
-                                DateTime::<FixedOffset>::parse_from_str(
-                                    str,
-                                    "%Y-%m-%d %H:%M:%S%.f%:z",
-                                )?
-                                .into(),
-                            ))
-                        },
-                        ArrayCell::TimeStampTz,
-                    ),
-                }
-            }
+            Type::TIMESTAMPTZ => Ok(Cell::TimeStampTz(parse_timestamptz(str)?)),
+            Type::TIMESTAMPTZ_ARRAY => TextFormatConverter::parse_array(
+                str,
+                |str| Ok(Some(parse_timestamptz(str)?)),
+                ArrayCell::TimeStampTz,
+            ),
+            Type::INTERVAL => Ok(Cell::Interval(str.parse()?)),
             Type::UUID => {
                 let val = Uuid::parse_str(str)?;
                 Ok(Cell::Uuid(val))
@@ -241,15 +337,27 @@ impl TextFormatConverter {
                 |str| Ok(Some(Uuid::parse_str(str)?)),
                 ArrayCell::Uuid,
             ),
+            // The values are parsed, since `jsonb` normalizes the whitespace and the order of the
+            // keys of its values, so that they can't be compared as strings.
+            #[cfg(not(feature = "json_as_string"))]
             Type::JSON | Type::JSONB => {
                 let val = serde_json::from_str(str)?;
                 Ok(Cell::Json(val))
             }
+            #[cfg(not(feature = "json_as_string"))]
             Type::JSON_ARRAY | Type::JSONB_ARRAY => TextFormatConverter::parse_array(
                 str,
                 |str| Ok(Some(serde_json::from_str(str)?)),
                 ArrayCell::Json,
             ),
+            #[cfg(feature = "json_as_string")]
+            Type::JSON | Type::JSONB => Ok(Cell::String(str.to_string())),
+            #[cfg(feature = "json_as_string")]
+            Type::JSON_ARRAY | Type::JSONB_ARRAY => TextFormatConverter::parse_array(
+                str,
+                |str| Ok(Some(str.to_string())),
+                ArrayCell::String,
+            ),
             Type::OID => {
                 let val: u32 = str.parse()?;
                 Ok(Cell::U32(val))
@@ -257,20 +365,34 @@ impl TextFormatConverter {
             Type::OID_ARRAY => {
                 TextFormatConverter::parse_array(str, |str| Ok(Some(str.parse()?)), ArrayCell::U32)
             }
+            Type::PG_LSN => Ok(Cell::String(parse_lsn(str)?)),
+            Type::PG_LSN_ARRAY => TextFormatConverter::parse_array(
+                str,
+                |str| Ok(Some(parse_lsn(str)?)),
+                ArrayCell::String,
+            ),
             #[cfg(feature = "unknown_types_to_bytes")]
             _ => Ok(Cell::String(str.to_string())),
             #[cfg(not(feature = "unknown_types_to_bytes"))]
-            _ => Err(CdcEventConversionError::UnsupportedType(
-                typ.name().to_string(),
-            )),
+            _ => Err(FromTextError::UnsupportedType(typ.clone())),
         }
     }
 
+    /// Parses an array in the text format of Postgres, e.g. `{1,NULL,3}`.
+    ///
+    /// The elements of multidimensional arrays, e.g. `{{1,2},{3,4}}`, are flattened in row-major
+    /// order, and the dimensions prefixing arrays whose lower bounds aren't 1, e.g. `[0:1]={1,2}`,
+    /// are ignored.
     fn parse_array<P, M, T>(str: &str, mut parse: P, m: M) -> Result<Cell, FromTextError>
     where
         P: FnMut(&str) -> Result<Option<T>, FromTextError>,
         M: FnOnce(Vec<Option<T>>) -> ArrayCell,
     {
+        let str = match str.strip_prefix('[') {
+            Some(dimensions) => dimensions.split_once('=').map_or(str, |(_, array)| array),
+            None => str,
+        };
+
         if str.len() < 2 {
             return Err(ArrayParseError::InputTooShort.into());
         }
@@ -303,6 +425,8 @@ impl TextFormatConverter {
                             in_quotes = !in_quotes;
                         }
                         '\\' => in_escape = true,
+                        // The braces of the inner arrays of a multidimensional array.
+                        '{' | '}' if !in_quotes => {}
                         ',' if !in_quotes => {
                             break;
                         }
@@ -330,6 +454,60 @@ impl TextFormatConverter {
     }
 }
 
+/// Parses a `timestamptz` in the `ISO` `DateStyle` into the instant it denotes, in UTC.
+///
+/// The value is parsed with the offset it's printed with, e.g. `-05` or `+05:30`, so the instant
+/// doesn't depend on the `TimeZone` of the session, which the replication connection sets to `UTC`
+/// anyway so that all the values are printed with the same `+00` offset.
+fn parse_timestamptz(str: &str) -> Result<DateTime<Utc>, FromTextError> {
+    let val = match DateTime::<FixedOffset>::parse_from_str(str, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        Ok(val) => val,
+        Err(_) => DateTime::<FixedOffset>::parse_from_str(str, "%Y-%m-%d %H:%M:%S%.f%:z")?,
+    };
+
+    Ok(val.with_timezone(&Utc))
+}
+
+/// Validates a `pg_lsn` value, written as two hex numbers of at most 8 digits separated by a
+/// slash, like `16/B374D848`.
+fn parse_lsn(str: &str) -> Result<String, FromTextError> {
+    let is_hex_part = |part: &str| {
+        !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_hexdigit())
+    };
+
+    match str.split_once('/') {
+        Some((high, low)) if is_hex_part(high) && is_hex_part(low) => Ok(str.to_string()),
+        _ => Err(FromTextError::InvalidLsn(str.to_string())),
+    }
+}
+
+/// Parses a `money` value into the amount it denotes, without its currency symbol nor its
+/// thousands separators.
+///
+/// The format of `money` values depends on `lc_monetary`, which the replication connection sets
+/// to `C` so that they are printed like `$1,234.56` and `-$1,234.56`. Negative amounts written in
+/// parentheses, like `($1,234.56)`, are accepted too since some locales print them that way.
+fn parse_money(str: &str) -> Result<PgNumeric, FromTextError> {
+    let is_negative = str.contains('-') || (str.starts_with('(') && str.ends_with(')'));
+    let amount: String = str
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    if !amount.chars().any(|c| c.is_ascii_digit()) {
+        return Err(FromTextError::InvalidMoney(str.to_string()));
+    }
+
+    let amount = if is_negative {
+        format!("-{amount}")
+    } else {
+        amount
+    };
+
+    amount
+        .parse()
+        .map_err(|_| FromTextError::InvalidMoney(str.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -356,4 +534,436 @@ mod tests {
             _ => panic!("unexpected cell"),
         }
     }
+
+    #[test]
+    fn parse_array_with_escaped_quotes() {
+        let cell = TextFormatConverter::try_from_str(
+            &Type::TEXT_ARRAY,
+            r#"{"a \"quoted\" word","back\\slash","{braces}, and commas",plain}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::String(vec![
+                Some("a \"quoted\" word".to_string()),
+                Some("back\\slash".to_string()),
+                Some("{braces}, and commas".to_string()),
+                Some("plain".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_empty_arrays_and_null_elements() {
+        let cell = TextFormatConverter::try_from_str(&Type::INT4_ARRAY, "{}").unwrap();
+        assert_eq!(cell, Cell::Array(ArrayCell::I32(vec![])));
+
+        let cell = TextFormatConverter::try_from_str(&Type::INT4_ARRAY, "{1,NULL,3}").unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::I32(vec![Some(1), None, Some(3)]))
+        );
+
+        let cell = TextFormatConverter::try_from_str(
+            &Type::UUID_ARRAY,
+            "{NULL,a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11}",
+        )
+        .unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::Uuid(vec![
+                None,
+                Some(Uuid::parse_str("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").unwrap()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_multidimensional_arrays_flattened() {
+        let cell =
+            TextFormatConverter::try_from_str(&Type::INT4_ARRAY, "{{1,2},{NULL,4}}").unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::I32(vec![Some(1), Some(2), None, Some(4)]))
+        );
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::TEXT_ARRAY, r#"{{"a,b",c},{"}",d}}"#).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::String(vec![
+                Some("a,b".to_string()),
+                Some("c".to_string()),
+                Some("}".to_string()),
+                Some("d".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_arrays_with_explicit_bounds() {
+        let cell = TextFormatConverter::try_from_str(&Type::INT4_ARRAY, "[0:1]={7,8}").unwrap();
+        assert_eq!(cell, Cell::Array(ArrayCell::I32(vec![Some(7), Some(8)])));
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::INT4_ARRAY, "[0:1][1:1]={{7},{8}}").unwrap();
+        assert_eq!(cell, Cell::Array(ArrayCell::I32(vec![Some(7), Some(8)])));
+    }
+
+    #[test]
+    fn parse_bytea_in_the_hex_and_escape_formats() {
+        let bytes = vec![0x00, b'a', 0x00, b'\\', 0x80, 0xff];
+
+        let cell = TextFormatConverter::try_from_str(&Type::BYTEA, "\\x0061005c80ff").unwrap();
+        assert_eq!(cell, Cell::Bytes(bytes.clone()));
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::BYTEA, "\\000a\\000\\\\\\200\\377").unwrap();
+        assert_eq!(cell, Cell::Bytes(bytes.clone()));
+
+        // The backslashes of the elements are escaped once more within the array.
+        let cell = TextFormatConverter::try_from_str(
+            &Type::BYTEA_ARRAY,
+            "{\"\\\\x0061005c80ff\",NULL,\"\\\\x\"}",
+        )
+        .unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::Bytes(vec![Some(bytes), None, Some(vec![])]))
+        );
+    }
+
+    #[test]
+    fn parse_lsn_as_string() {
+        let cell = TextFormatConverter::try_from_str(&Type::PG_LSN, "16/B374D848").unwrap();
+        assert_eq!(cell, Cell::String("16/B374D848".to_string()));
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::PG_LSN_ARRAY, "{0/0,FFFFFFFF/FFFFFFFF}")
+                .unwrap();
+        match cell {
+            Cell::Array(ArrayCell::String(v)) => {
+                assert_eq!(
+                    v,
+                    vec![
+                        Some("0/0".to_string()),
+                        Some("FFFFFFFF/FFFFFFFF".to_string())
+                    ]
+                );
+            }
+            _ => panic!("unexpected cell"),
+        }
+    }
+
+    #[test]
+    fn parse_malformed_lsn_fails() {
+        for lsn in [
+            "16B374D848",
+            "16/",
+            "/B374D848",
+            "16/B374D848/1",
+            "G/0",
+            "100000000/0",
+        ] {
+            let err = TextFormatConverter::try_from_str(&Type::PG_LSN, lsn).unwrap_err();
+            assert!(
+                matches!(err, FromTextError::InvalidLsn(_)),
+                "{lsn} was parsed"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_money_without_currency_symbol_nor_separators() {
+        let parse = |str: &str| TextFormatConverter::try_from_str(&Type::MONEY, str).unwrap();
+        let numeric = |str: &str| Cell::Numeric(str.parse().unwrap());
+
+        assert_eq!(parse("$1,234.56"), numeric("1234.56"));
+        assert_eq!(parse("$0.00"), numeric("0.00"));
+        assert_eq!(parse("-$1,234.56"), numeric("-1234.56"));
+        assert_eq!(parse("-$0.01"), numeric("-0.01"));
+        assert_eq!(parse("($5.00)"), numeric("-5.00"));
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::MONEY_ARRAY, r#"{"$1,234.56",-$0.01,NULL}"#)
+                .unwrap();
+        match cell {
+            Cell::Array(ArrayCell::Numeric(v)) => {
+                assert_eq!(
+                    v,
+                    vec![
+                        Some("1234.56".parse().unwrap()),
+                        Some("-0.01".parse().unwrap()),
+                        None
+                    ]
+                );
+            }
+            _ => panic!("unexpected cell"),
+        }
+
+        for money in ["$", "-$", "$1.2.3"] {
+            let err = TextFormatConverter::try_from_str(&Type::MONEY, money).unwrap_err();
+            assert!(
+                matches!(err, FromTextError::InvalidMoney(_)),
+                "{money} was parsed"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_oids_into_u32() {
+        let cell = TextFormatConverter::try_from_str(&Type::OID, "4294967295").unwrap();
+        assert_eq!(cell, Cell::U32(u32::MAX));
+
+        let cell = TextFormatConverter::try_from_str(&Type::OID_ARRAY, "{16384,NULL}").unwrap();
+        assert_eq!(cell, Cell::Array(ArrayCell::U32(vec![Some(16384), None])));
+
+        assert!(matches!(
+            TextFormatConverter::try_from_str(&Type::OID, "-1"),
+            Err(FromTextError::InvalidInt(_))
+        ));
+    }
+
+    #[test]
+    fn parse_uuids_in_the_canonical_form() {
+        let uuid = Uuid::from_u128(0xa0eebc99_9c0b_4ef8_bb6d_6bb9bd380a11);
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::UUID, "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")
+                .unwrap();
+        assert_eq!(cell, Cell::Uuid(uuid));
+
+        // Uuids are compared by value, not by the case of their text.
+        let cell =
+            TextFormatConverter::try_from_str(&Type::UUID, "A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11")
+                .unwrap();
+        assert_eq!(cell, Cell::Uuid(uuid));
+
+        for uuid in [
+            "",
+            "not-a-uuid",
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a1",
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a111",
+            "g0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            "a0eebc99-9c0b-4ef8-bb6d_6bb9bd380a11",
+        ] {
+            let err = TextFormatConverter::try_from_str(&Type::UUID, uuid).unwrap_err();
+            assert!(
+                matches!(err, FromTextError::InvalidUuid(_)),
+                "{uuid} was parsed"
+            );
+        }
+
+        let err = TextFormatConverter::try_from_str(&Type::UUID_ARRAY, "{not-a-uuid}").unwrap_err();
+        assert!(matches!(err, FromTextError::InvalidUuid(_)));
+    }
+
+    #[test]
+    fn parse_intervals_into_their_components() {
+        let cell =
+            TextFormatConverter::try_from_str(&Type::INTERVAL, "1 year 2 mons 3 days 04:05:06.789")
+                .unwrap();
+        assert_eq!(cell, Cell::Interval(PgInterval::new(14, 3, 14_706_789_000)));
+
+        let cell = TextFormatConverter::try_from_str(&Type::INTERVAL, "P-1M-2DT-3S").unwrap();
+        assert_eq!(cell, Cell::Interval(PgInterval::new(-1, -2, -3_000_000)));
+
+        assert!(matches!(
+            TextFormatConverter::try_from_str(&Type::INTERVAL, "1 fortnight"),
+            Err(FromTextError::InvalidInterval(_))
+        ));
+    }
+
+    #[test]
+    fn parse_timestamptz_across_dst_boundaries_into_utc() {
+        let utc = |str: &str| {
+            DateTime::parse_from_rfc3339(str)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let parse = |str: &str| TextFormatConverter::try_from_str(&Type::TIMESTAMPTZ, str).unwrap();
+
+        // Spring forward in America/New_York: 02:00 EST becomes 03:00 EDT, one microsecond later.
+        assert_eq!(
+            parse("2024-03-10 01:59:59.999999-05"),
+            Cell::TimeStampTz(utc("2024-03-10T06:59:59.999999Z"))
+        );
+        assert_eq!(
+            parse("2024-03-10 03:00:00-04"),
+            Cell::TimeStampTz(utc("2024-03-10T07:00:00Z"))
+        );
+
+        // Fall back: 01:30 happens twice, an hour apart.
+        assert_eq!(
+            parse("2024-11-03 01:30:00.5-04"),
+            Cell::TimeStampTz(utc("2024-11-03T05:30:00.5Z"))
+        );
+        assert_eq!(
+            parse("2024-11-03 01:30:00.5-05"),
+            Cell::TimeStampTz(utc("2024-11-03T06:30:00.5Z"))
+        );
+
+        // The same instant in the `UTC` session of the replication connection.
+        assert_eq!(
+            parse("2024-11-03 06:30:00.5+00"),
+            Cell::TimeStampTz(utc("2024-11-03T06:30:00.5Z"))
+        );
+    }
+
+    #[test]
+    fn parse_timestamptz_with_fractional_seconds_and_partial_hour_offsets() {
+        let utc = |str: &str| {
+            DateTime::parse_from_rfc3339(str)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::TIMESTAMPTZ, "2024-05-01 12:34:56.123+05:30")
+                .unwrap();
+        assert_eq!(cell, Cell::TimeStampTz(utc("2024-05-01T07:04:56.123Z")));
+
+        let cell = TextFormatConverter::try_from_str(
+            &Type::TIMESTAMPTZ_ARRAY,
+            r#"{"2024-05-01 12:34:56.000001+00","2024-05-01 12:34:56-09:30",NULL}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::TimeStampTz(vec![
+                Some(utc("2024-05-01T12:34:56.000001Z")),
+                Some(utc("2024-05-01T22:04:56Z")),
+                None,
+            ]))
+        );
+
+        assert!(matches!(
+            TextFormatConverter::try_from_str(&Type::TIMESTAMPTZ, "2024-05-01 12:34:56 EST"),
+            Err(FromTextError::InvalidTimestamp(_))
+        ));
+    }
+
+    #[test]
+    fn parse_hstores_into_json_objects() {
+        let hstore = Type::new(
+            "hstore".to_string(),
+            16_385,
+            Kind::Simple,
+            "public".to_string(),
+        );
+        assert!(TextFormatConverter::is_supported_type(&hstore));
+
+        let cell = TextFormatConverter::try_from_str(&hstore, r#""a=>b"=>"1", "c"=>NULL"#).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Json(serde_json::json!({"a=>b": "1", "c": null}))
+        );
+
+        assert!(matches!(
+            TextFormatConverter::try_from_str(&hstore, r#""a"=>"#),
+            Err(FromTextError::InvalidHstore(_))
+        ));
+    }
+
+    #[test]
+    fn parse_enums_into_their_labels() {
+        let mood = Type::new(
+            "mood".to_string(),
+            16_390,
+            Kind::Enum(vec!["sad".to_string(), "happy".to_string()]),
+            "public".to_string(),
+        );
+        assert!(TextFormatConverter::is_supported_type(&mood));
+
+        let cell = TextFormatConverter::try_from_str(&mood, "happy").unwrap();
+        assert_eq!(
+            cell,
+            Cell::Enum {
+                type_name: "mood".to_string(),
+                label: "happy".to_string()
+            }
+        );
+
+        assert!(matches!(
+            TextFormatConverter::try_from_str(&mood, "angry"),
+            Err(FromTextError::InvalidEnum(_))
+        ));
+    }
+
+    #[test]
+    fn parse_geometries_into_their_ewkb() {
+        let geometry = Type::new(
+            "geometry".to_string(),
+            17_001,
+            Kind::Simple,
+            "public".to_string(),
+        );
+        assert!(TextFormatConverter::is_supported_type(&geometry));
+
+        // `SRID=4326;POINT(1 2)`.
+        let cell = TextFormatConverter::try_from_str(
+            &geometry,
+            "0101000020E6100000000000000000F03F0000000000000040",
+        )
+        .unwrap();
+        let mut ewkb = vec![0x01, 0x01, 0x00, 0x00, 0x20];
+        ewkb.extend_from_slice(&4326u32.to_le_bytes());
+        ewkb.extend_from_slice(&1f64.to_le_bytes());
+        ewkb.extend_from_slice(&2f64.to_le_bytes());
+        assert_eq!(cell, Cell::Geometry(ewkb));
+        assert_eq!(
+            cell.to_json(),
+            "0101000020e6100000000000000000f03f0000000000000040"
+        );
+
+        assert!(matches!(
+            TextFormatConverter::try_from_str(&geometry, "POINT(1 2)"),
+            Err(FromTextError::InvalidGeometry(_))
+        ));
+    }
+
+    #[cfg(not(feature = "json_as_string"))]
+    #[test]
+    fn parse_json_into_structured_values() {
+        // `jsonb` normalizes whitespace and key order, so the values are compared once parsed.
+        let cell =
+            TextFormatConverter::try_from_str(&Type::JSONB, r#"{"b": [1, 2], "a":null}"#).unwrap();
+        assert_eq!(
+            cell,
+            Cell::Json(serde_json::json!({"a": null, "b": [1, 2]}))
+        );
+
+        let cell =
+            TextFormatConverter::try_from_str(&Type::JSON_ARRAY, r#"{"{\"a\": 1}",NULL,"\"b\""}"#)
+                .unwrap();
+        assert_eq!(
+            cell,
+            Cell::Array(ArrayCell::Json(vec![
+                Some(serde_json::json!({"a": 1})),
+                None,
+                Some(serde_json::json!("b")),
+            ]))
+        );
+
+        assert!(matches!(
+            TextFormatConverter::try_from_str(&Type::JSON, "{not json}"),
+            Err(FromTextError::InvalidJson(_))
+        ));
+    }
+
+    #[cfg(feature = "json_as_string")]
+    #[test]
+    fn parse_json_as_strings() {
+        let cell = TextFormatConverter::try_from_str(&Type::JSONB, r#"{"a": 1}"#).unwrap();
+        assert_eq!(cell, Cell::String(r#"{"a": 1}"#.to_string()));
+    }
+
+    #[cfg(not(feature = "unknown_types_to_bytes"))]
+    #[test]
+    fn unsupported_types_default_to_empty_unsupported_cells() {
+        assert_eq!(
+            TextFormatConverter::default_value(&Type::TSVECTOR),
+            Cell::Unsupported(Type::TSVECTOR, String::new())
+        );
+    }
 }
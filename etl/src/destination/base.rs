@@ -1,11 +1,18 @@
+use config::shared::BatchConfig;
+use futures::{Stream, StreamExt};
 use postgres::schema::{TableId, TableSchema};
 use std::future::Future;
+use std::pin::pin;
 use thiserror::Error;
 
-use crate::conversions::event::Event;
+use crate::concurrency::shutdown::{ShutdownResult, create_shutdown_channel};
+use crate::concurrency::stream::BatchStream;
+use crate::conversions::event::{Event, TruncateEvent};
 use crate::conversions::table_row::TableRow;
 #[cfg(feature = "bigquery")]
 use crate::destination::bigquery::BigQueryDestinationError;
+use crate::destination::column_filter::ColumnFilterError;
+use crate::destination::file::FileDestinationError;
 use crate::schema::cache::SchemaCache;
 
 #[derive(Debug, Error)]
@@ -13,6 +20,16 @@ pub enum DestinationError {
     #[cfg(feature = "bigquery")]
     #[error(transparent)]
     BigQuery(#[from] BigQueryDestinationError),
+
+    #[error(transparent)]
+    ColumnFilter(#[from] ColumnFilterError),
+
+    #[error(transparent)]
+    File(#[from] FileDestinationError),
+
+    /// The destination can't be reached, e.g. for destinations implemented outside of this crate.
+    #[error("The destination is unavailable: {0}")]
+    Unavailable(String),
 }
 
 pub trait Destination {
@@ -40,8 +57,223 @@ pub trait Destination {
         table_rows: Vec<TableRow>,
     ) -> impl Future<Output = Result<(), DestinationError>> + Send;
 
+    /// Writes the rows of a table as they are produced by `table_rows`, without requiring the
+    /// caller to buffer them first.
+    ///
+    /// `batch_config` is the configured batching, which destinations that batch internally can
+    /// use as a hint. By default, the stream is split into batches as described by
+    /// [`BatchConfig`], each of which is written with [`Destination::write_table_rows`], so that
+    /// destinations only implementing the latter keep working.
+    ///
+    /// The first error produced by `table_rows` is returned without writing the batch it belongs
+    /// to, so that rows are never written past a row which couldn't be produced. The rows of the
+    /// previous batches are already written by then.
+    fn write_table_rows_stream<S, E>(
+        &self,
+        table_id: TableId,
+        table_rows: S,
+        batch_config: BatchConfig,
+    ) -> impl Future<Output = Result<(), E>> + Send
+    where
+        Self: Sync,
+        S: Stream<Item = Result<TableRow, E>> + Send,
+        E: From<DestinationError> + Send,
+    {
+        async move {
+            // The rows are only batched here, the caller stops the stream itself on shutdown.
+            let (_shutdown_tx, shutdown_rx) = create_shutdown_channel();
+            let mut batches = pin!(BatchStream::wrap(table_rows, batch_config, shutdown_rx));
+            while let Some(batch) = batches.next().await {
+                let (ShutdownResult::Ok(table_rows) | ShutdownResult::Shutdown(table_rows)) = batch;
+                let table_rows = table_rows.into_iter().collect::<Result<Vec<_>, _>>()?;
+                self.write_table_rows(table_id, table_rows).await?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Changes the schema of a replicated table from `before` to `after`, once the columns of the
+    /// table were altered on the source, before any of its rows with the new columns is written.
+    ///
+    /// Returns whether the change is supported. The table keeps being replicated with the columns
+    /// of `after` if it is, and is skipped otherwise, so that none of its rows are written with
+    /// the wrong columns. By default, schema changes aren't supported.
+    fn write_table_schema_change(
+        &self,
+        _before: TableSchema,
+        _after: TableSchema,
+    ) -> impl Future<Output = Result<bool, DestinationError>> + Send {
+        async move { Ok(false) }
+    }
+
+    /// Writes the events streamed from the source, in the order they were committed.
+    ///
+    /// The rows of updates may have [`Cell::UnchangedToast`](crate::conversions::Cell::UnchangedToast)
+    /// values, for the columns which must be left untouched.
     fn write_events(
         &self,
         events: Vec<Event>,
     ) -> impl Future<Output = Result<(), DestinationError>> + Send;
+
+    /// Removes all the rows of the table with `table_id`, before it's copied again.
+    ///
+    /// By default, a `TRUNCATE` event of the table is written with [`Destination::write_events`],
+    /// so that destinations applying those keep working.
+    fn truncate_table(
+        &self,
+        table_id: TableId,
+    ) -> impl Future<Output = Result<(), DestinationError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let truncate_event = TruncateEvent {
+                options: 0,
+                rel_ids: vec![table_id],
+            };
+
+            self.write_events(vec![Event::Truncate(truncate_event)])
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::Cell;
+    use futures::stream;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct RecordingDestination {
+        batches: Mutex<Vec<(TableId, Vec<TableRow>)>>,
+    }
+
+    impl Destination for RecordingDestination {
+        async fn write_table_schema(
+            &self,
+            _table_schema: TableSchema,
+        ) -> Result<(), DestinationError> {
+            Ok(())
+        }
+
+        async fn load_table_schemas(&self) -> Result<Vec<TableSchema>, DestinationError> {
+            Ok(vec![])
+        }
+
+        async fn write_table_rows(
+            &self,
+            table_id: TableId,
+            table_rows: Vec<TableRow>,
+        ) -> Result<(), DestinationError> {
+            self.batches.lock().unwrap().push((table_id, table_rows));
+            Ok(())
+        }
+
+        async fn write_events(&self, _events: Vec<Event>) -> Result<(), DestinationError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn write_table_rows_stream_writes_rows_in_batches() {
+        let destination = RecordingDestination::default();
+        let table_id: TableId = 1;
+        let table_rows = (0..5).map(|i| TableRow::new(vec![Cell::I32(i)]));
+
+        destination
+            .write_table_rows_stream(
+                table_id,
+                stream::iter(table_rows.clone().map(Ok::<_, DestinationError>)),
+                BatchConfig {
+                    max_size: 2,
+                    max_fill_ms: 1000,
+                },
+            )
+            .await
+            .unwrap();
+
+        let batches = destination.batches.into_inner().unwrap();
+        let batch_sizes = batches
+            .iter()
+            .map(|(_, table_rows)| table_rows.len())
+            .collect::<Vec<_>>();
+        assert_eq!(batch_sizes, vec![2, 2, 1]);
+        assert!(batches.iter().all(|(id, _)| *id == table_id));
+        assert_eq!(
+            batches
+                .into_iter()
+                .flat_map(|(_, rows)| rows)
+                .collect::<Vec<_>>(),
+            table_rows.collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn write_table_rows_stream_writes_rows_once_max_fill_ms_elapsed() {
+        let destination = RecordingDestination::default();
+        let table_id: TableId = 1;
+        // The stream never ends, so the row can only be written once the batch timed out.
+        let table_rows = stream::iter(vec![Ok::<_, DestinationError>(TableRow::new(vec![
+            Cell::I32(1),
+        ]))])
+        .chain(stream::pending());
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(500),
+            destination.write_table_rows_stream(
+                table_id,
+                table_rows,
+                BatchConfig {
+                    max_size: 10,
+                    max_fill_ms: 10,
+                },
+            ),
+        )
+        .await;
+
+        assert!(result.is_err());
+        let batches = destination.batches.into_inner().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].1, vec![TableRow::new(vec![Cell::I32(1)])]);
+    }
+
+    #[tokio::test]
+    async fn write_table_rows_stream_stops_without_writing_the_batch_of_an_error() {
+        let destination = RecordingDestination::default();
+        let table_id: TableId = 1;
+        let table_rows = (0..5)
+            .map(|i| Ok(TableRow::new(vec![Cell::I32(i)])))
+            .chain([Err(DestinationError::Unavailable("row 5".to_string()))])
+            .chain((6..10).map(|i| Ok(TableRow::new(vec![Cell::I32(i)]))));
+
+        let result = destination
+            .write_table_rows_stream(
+                table_id,
+                stream::iter(table_rows),
+                BatchConfig {
+                    max_size: 2,
+                    max_fill_ms: 1000,
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(DestinationError::Unavailable(_))));
+        let table_rows = destination
+            .batches
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .flat_map(|(_, rows)| rows)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            table_rows,
+            (0..4)
+                .map(|i| TableRow::new(vec![Cell::I32(i)]))
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -1,21 +1,40 @@
+use config::shared::{IdentifierOverflowPolicy, OversizedRowPolicy, SchemaMapping};
 use gcp_bigquery_client::model::query_request::QueryRequest;
 use gcp_bigquery_client::storage::TableDescriptor;
 use postgres::schema::{ColumnSchema, TableId, TableName, TableSchema};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::Deref;
 use std::sync::{Arc, LazyLock};
 use thiserror::Error;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, SemaphorePermit};
 use tokio_postgres::types::Type;
 use tracing::{debug, info, warn};
 
-use crate::clients::bigquery::{BigQueryClient, BigQueryClientError, BigQueryOperationType};
+use crate::clients::bigquery::{
+    BigQueryClient, BigQueryClientError, BigQueryOperationType, MAX_SIZE_BYTES,
+};
 use crate::conversions::Cell;
 use crate::conversions::event::{Event, TruncateEvent};
 use crate::conversions::table_row::TableRow;
 use crate::destination::base::{Destination, DestinationError};
+use crate::destination::column_filter::{ColumnFilter, ColumnFilterError, ColumnProjection};
+use crate::destination::compatibility::{CompatibilityReport, check_compatibility};
+use crate::destination::identifier::{IdentifierError, IdentifierLimits, IdentifierMapper};
+use crate::destination::row_size::{RowSizeError, RowSizeGuard};
+use crate::destination::write_limit::WriteLimiter;
 use crate::schema::cache::SchemaCache;
 
+/// Length limits of BigQuery table and column names.
+///
+/// See <https://cloud.google.com/bigquery/docs/tables#table_naming> and
+/// <https://cloud.google.com/bigquery/docs/schemas#column_names>.
+pub const BIGQUERY_IDENTIFIER_LIMITS: IdentifierLimits = IdentifierLimits {
+    max_table_name_len: 1024,
+    max_column_name_len: 300,
+};
+
 /// Table name for storing ETL table schema metadata in BigQuery.
 const ETL_TABLE_SCHEMAS_NAME: &str = "etl_table_schemas";
 
@@ -92,6 +111,44 @@ pub enum BigQueryDestinationError {
     /// JSON serialization failed while processing table schema data.
     #[error("Failed to serialize table schema: {0}")]
     SerializationError(#[from] serde_json::Error),
+
+    /// The column filter can't be applied to a table.
+    #[error("Failed to filter the columns of a table: {0}")]
+    ColumnFilter(#[from] ColumnFilterError),
+
+    /// A table or column name can't be used in BigQuery.
+    #[error("Invalid BigQuery identifier: {0}")]
+    Identifier(#[from] IdentifierError),
+
+    /// A row is too large to be sent to BigQuery.
+    #[error("Invalid BigQuery row: {0}")]
+    RowSize(#[from] RowSizeError),
+
+    /// An update left a large value unchanged without sending it, while BigQuery replaces whole
+    /// rows and would overwrite it.
+    #[error(
+        "An update of table {0} didn't send an unchanged large value, which BigQuery would overwrite, set `REPLICA IDENTITY FULL` on the table for its updates to send it"
+    )]
+    UnchangedToast(TableId),
+
+    /// Two tables are written to the same BigQuery table, which would mix their rows.
+    #[error(
+        "The tables {0} and {1} are both written to the BigQuery table {2}, map the schemas to datasets to keep them apart"
+    )]
+    TableNameCollision(TableName, TableName, BigQueryTable),
+}
+
+/// The BigQuery table receiving the rows of a Postgres table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigQueryTable {
+    pub dataset_id: String,
+    pub table_id: String,
+}
+
+impl fmt::Display for BigQueryTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.dataset_id, self.table_id)
+    }
 }
 
 /// Internal state for [`BigQueryDestination`] wrapped in `Arc<RwLock<>>`.
@@ -146,6 +203,11 @@ impl Inner {
 #[derive(Debug, Clone)]
 pub struct BigQueryDestination {
     inner: Arc<RwLock<Inner>>,
+    column_filter: Option<ColumnFilter>,
+    write_limiter: Option<WriteLimiter>,
+    identifier_mapper: IdentifierMapper,
+    schema_mapping: SchemaMapping,
+    row_size_guard: RowSizeGuard,
 }
 
 impl BigQueryDestination {
@@ -169,6 +231,14 @@ impl BigQueryDestination {
 
         Ok(Self {
             inner: Arc::new(RwLock::new(inner)),
+            column_filter: None,
+            write_limiter: None,
+            identifier_mapper: IdentifierMapper::new(
+                BIGQUERY_IDENTIFIER_LIMITS,
+                IdentifierOverflowPolicy::default(),
+            ),
+            schema_mapping: SchemaMapping::default(),
+            row_size_guard: RowSizeGuard::new(MAX_SIZE_BYTES, OversizedRowPolicy::default()),
         })
     }
 
@@ -192,6 +262,14 @@ impl BigQueryDestination {
 
         Ok(Self {
             inner: Arc::new(RwLock::new(inner)),
+            column_filter: None,
+            write_limiter: None,
+            identifier_mapper: IdentifierMapper::new(
+                BIGQUERY_IDENTIFIER_LIMITS,
+                IdentifierOverflowPolicy::default(),
+            ),
+            schema_mapping: SchemaMapping::default(),
+            row_size_guard: RowSizeGuard::new(MAX_SIZE_BYTES, OversizedRowPolicy::default()),
         })
     }
 
@@ -219,16 +297,211 @@ impl BigQueryDestination {
 
         Ok(Self {
             inner: Arc::new(RwLock::new(inner)),
+            column_filter: None,
+            write_limiter: None,
+            identifier_mapper: IdentifierMapper::new(
+                BIGQUERY_IDENTIFIER_LIMITS,
+                IdentifierOverflowPolicy::default(),
+            ),
+            schema_mapping: SchemaMapping::default(),
+            row_size_guard: RowSizeGuard::new(MAX_SIZE_BYTES, OversizedRowPolicy::default()),
         })
     }
 
-    /// Loads BigQuery table ID and descriptor that are used for streaming operations.
+    /// Filters the columns sent to BigQuery with `column_filter`.
+    ///
+    /// Since rows are upserted by primary key, filtering out a primary key column fails when the
+    /// table schema is written. The metadata tables keep storing the whole source schema.
+    pub fn with_column_filter(mut self, column_filter: ColumnFilter) -> Self {
+        self.column_filter = Some(column_filter);
+        self
+    }
+
+    /// Limits the writes sent to BigQuery at the same time with `write_limiter`.
+    ///
+    /// Creating tables, streaming table rows and streaming events each hold a permit for their
+    /// whole duration. Clones of the limiter share its permits.
+    pub fn with_write_limiter(mut self, write_limiter: WriteLimiter) -> Self {
+        self.write_limiter = Some(write_limiter);
+        self
+    }
+
+    /// Applies `policy` to the table and column names exceeding BigQuery's length limits.
+    ///
+    /// By default, such names fail the write of the table schema, before any row is sent. Only
+    /// the names used in BigQuery are affected, the metadata tables keep the source names.
+    pub fn with_identifier_overflow(mut self, policy: IdentifierOverflowPolicy) -> Self {
+        self.identifier_mapper = IdentifierMapper::new(BIGQUERY_IDENTIFIER_LIMITS, policy);
+        self
+    }
+
+    /// Maps the schemas of the tables to BigQuery with `mapping`.
+    ///
+    /// By default, the tables of every schema are written to the dataset of the destination, with
+    /// their schema as a prefix of their name. The metadata tables always stay in the dataset of
+    /// the destination.
+    pub fn with_schema_mapping(mut self, mapping: SchemaMapping) -> Self {
+        self.schema_mapping = mapping;
+        self
+    }
+
+    /// Compares the BigQuery table receiving the rows of `table_schema` with the table this
+    /// destination would create for it.
+    ///
+    /// Returns `None` if the table doesn't exist yet, since it will be created with the right
+    /// columns when the table schema is written.
+    pub async fn verify_table_compatibility(
+        &self,
+        table_schema: &TableSchema,
+    ) -> Result<Option<CompatibilityReport>, BigQueryDestinationError> {
+        let inner = self.inner.read().await;
+
+        let column_schemas = match Self::projection(self.column_filter.as_ref(), table_schema)? {
+            Some(projection) => projection.column_schemas(&table_schema.column_schemas),
+            None => table_schema.column_schemas.clone(),
+        };
+        let column_schemas =
+            Self::bigquery_column_schemas(&self.identifier_mapper, column_schemas)?;
+        let table = Self::bigquery_table(
+            &self.identifier_mapper,
+            self.schema_mapping,
+            &inner.dataset_id,
+            &table_schema.name,
+        )?;
+
+        let Some(actual_columns) = inner
+            .client
+            .get_table_columns(&table.dataset_id, &table.table_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let expected_columns = BigQueryClient::expected_table_columns(&column_schemas);
+
+        Ok(Some(check_compatibility(
+            &expected_columns,
+            &actual_columns,
+        )))
+    }
+
+    /// Applies `policy` to the rows whose encoded size exceeds `max_size` bytes.
+    ///
+    /// The limit is capped to the maximum size of a write request, which is also the default
+    /// limit. By default, such rows fail the write of their whole batch.
+    pub fn with_row_size_limit(mut self, max_size: usize, policy: OversizedRowPolicy) -> Self {
+        self.row_size_guard = RowSizeGuard::new(max_size.min(MAX_SIZE_BYTES), policy);
+        self
+    }
+
+    /// Waits for a write permit, if the writes are limited.
+    async fn acquire_write_permit(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.write_limiter {
+            Some(write_limiter) => Some(write_limiter.acquire().await),
+            None => None,
+        }
+    }
+
+    /// Returns the projection of the columns of `table_schema` sent to BigQuery, if the table is
+    /// filtered.
+    fn projection(
+        column_filter: Option<&ColumnFilter>,
+        table_schema: &TableSchema,
+    ) -> Result<Option<ColumnProjection>, BigQueryDestinationError> {
+        let Some(column_filter) = column_filter else {
+            return Ok(None);
+        };
+
+        Ok(column_filter.projection(table_schema, true)?)
+    }
+
+    /// Returns the BigQuery table receiving the rows of the table named `table_name`, when the
+    /// dataset of the destination is `dataset_id`.
+    fn bigquery_table(
+        identifier_mapper: &IdentifierMapper,
+        schema_mapping: SchemaMapping,
+        dataset_id: &str,
+        table_name: &TableName,
+    ) -> Result<BigQueryTable, BigQueryDestinationError> {
+        let table = match schema_mapping {
+            SchemaMapping::Flatten => BigQueryTable {
+                dataset_id: dataset_id.to_owned(),
+                table_id: identifier_mapper
+                    .table_name(&table_name.as_bigquery_table_id())?
+                    .into_owned(),
+            },
+            // Postgres schema names are at most 63 bytes long, far below the limit of BigQuery
+            // dataset names.
+            SchemaMapping::Namespace => BigQueryTable {
+                dataset_id: table_name.schema.clone(),
+                table_id: identifier_mapper.table_name(&table_name.name)?.into_owned(),
+            },
+        };
+
+        Ok(table)
+    }
+
+    /// Fails if a table of the schema cache other than `table_schema` is written to `table`.
+    async fn check_table_name_collision(
+        &self,
+        inner: &Inner,
+        table_schema: &TableSchema,
+        table: &BigQueryTable,
+    ) -> Result<(), BigQueryDestinationError> {
+        let Some(schema_cache) = &inner.schema_cache else {
+            return Ok(());
+        };
+
+        let schema_cache = schema_cache.read_inner().await;
+        for other_table_schema in schema_cache.table_schemas() {
+            if other_table_schema.id == table_schema.id {
+                continue;
+            }
+
+            // The names of the other tables which can't be mapped fail when they are written.
+            let other_table = Self::bigquery_table(
+                &self.identifier_mapper,
+                self.schema_mapping,
+                &inner.dataset_id,
+                &other_table_schema.name,
+            );
+            if matches!(other_table, Ok(other_table) if other_table == *table) {
+                return Err(BigQueryDestinationError::TableNameCollision(
+                    table_schema.name.clone(),
+                    other_table_schema.name.clone(),
+                    table.clone(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renames the columns in `column_schemas` to their BigQuery names.
+    fn bigquery_column_schemas(
+        identifier_mapper: &IdentifierMapper,
+        mut column_schemas: Vec<ColumnSchema>,
+    ) -> Result<Vec<ColumnSchema>, BigQueryDestinationError> {
+        for column_schema in column_schemas.iter_mut() {
+            if let Cow::Owned(name) = identifier_mapper.column_name(&column_schema.name)? {
+                column_schema.name = name;
+            }
+        }
+
+        Ok(column_schemas)
+    }
+
+    /// Loads BigQuery table and descriptor that are used for streaming operations.
     ///
-    /// Returns the BigQuery-formatted table name and its column descriptor for streaming operations.
-    async fn load_table_id_and_descriptor<I: Deref<Target = Inner>>(
+    /// Returns the BigQuery table, its column descriptor for streaming operations and the
+    /// projection that rows must go through before being streamed.
+    async fn load_table_and_descriptor<I: Deref<Target = Inner>>(
         inner: &I,
         table_id: &TableId,
-    ) -> Result<(String, TableDescriptor), BigQueryDestinationError> {
+        column_filter: Option<&ColumnFilter>,
+        identifier_mapper: &IdentifierMapper,
+        schema_mapping: SchemaMapping,
+    ) -> Result<(BigQueryTable, TableDescriptor, Option<ColumnProjection>), BigQueryDestinationError>
+    {
         let schema_cache = inner
             .schema_cache
             .as_ref()
@@ -239,11 +512,21 @@ impl BigQueryDestination {
             .get_table_schema_ref(table_id)
             .ok_or(BigQueryDestinationError::MissingTableSchema(*table_id))?;
 
-        let table_id = table_schema.name.as_bigquery_table_id();
-        let table_descriptor =
-            BigQueryClient::column_schemas_to_table_descriptor(&table_schema.column_schemas);
+        let projection = Self::projection(column_filter, table_schema)?;
+        let table = Self::bigquery_table(
+            identifier_mapper,
+            schema_mapping,
+            &inner.dataset_id,
+            &table_schema.name,
+        )?;
+        let column_schemas = match &projection {
+            Some(projection) => projection.column_schemas(&table_schema.column_schemas),
+            None => table_schema.column_schemas.clone(),
+        };
+        let column_schemas = Self::bigquery_column_schemas(identifier_mapper, column_schemas)?;
+        let table_descriptor = BigQueryClient::column_schemas_to_table_descriptor(&column_schemas);
 
-        Ok((table_id, table_descriptor))
+        Ok((table, table_descriptor, projection))
     }
 
     /// Writes a table schema to BigQuery, creating the data table and storing metadata.
@@ -257,13 +540,33 @@ impl BigQueryDestination {
 
         let dataset_id = inner.dataset_id.clone();
 
-        // Create the actual data table
+        // Create the actual data table with only the columns that pass the column filter
+        let column_schemas = match Self::projection(self.column_filter.as_ref(), &table_schema)? {
+            Some(projection) => projection.column_schemas(&table_schema.column_schemas),
+            None => table_schema.column_schemas.clone(),
+        };
+        let column_schemas =
+            Self::bigquery_column_schemas(&self.identifier_mapper, column_schemas)?;
+        let table = Self::bigquery_table(
+            &self.identifier_mapper,
+            self.schema_mapping,
+            &dataset_id,
+            &table_schema.name,
+        )?;
+        self.check_table_name_collision(&inner, &table_schema, &table)
+            .await?;
+        if table.dataset_id != dataset_id {
+            inner
+                .client
+                .create_dataset_if_missing(&table.dataset_id, &dataset_id)
+                .await?;
+        }
         inner
             .client
             .create_table_if_missing(
-                &dataset_id,
-                &table_schema.name.as_bigquery_table_id(),
-                &table_schema.column_schemas,
+                &table.dataset_id,
+                &table.table_id,
+                &column_schemas,
                 inner.max_staleness_mins,
             )
             .await?;
@@ -435,18 +738,38 @@ impl BigQueryDestination {
     ) -> Result<(), BigQueryDestinationError> {
         let mut inner = self.inner.write().await;
 
-        let (table_id, table_descriptor) =
-            Self::load_table_id_and_descriptor(&inner, &table_id).await?;
+        let (table, table_descriptor, projection) = Self::load_table_and_descriptor(
+            &inner,
+            &table_id,
+            self.column_filter.as_ref(),
+            &self.identifier_mapper,
+            self.schema_mapping,
+        )
+        .await?;
 
-        let dataset_id = inner.dataset_id.clone();
         for table_row in table_rows.iter_mut() {
+            if let Some(projection) = &projection {
+                projection.apply(table_row);
+            }
             table_row
                 .values
                 .push(BigQueryOperationType::UPSERT.into_cell());
         }
+        let table_rows =
+            self.row_size_guard
+                .check_rows(table_id, table_rows, prost::Message::encoded_len)?;
+        if table_rows.is_empty() {
+            return Ok(());
+        }
+
         inner
             .client
-            .stream_rows(&dataset_id, table_id, &table_descriptor, table_rows)
+            .stream_rows(
+                &table.dataset_id,
+                table.table_id,
+                &table_descriptor,
+                table_rows,
+            )
             .await?;
 
         Ok(())
@@ -480,6 +803,17 @@ impl BigQueryDestination {
                         table_rows.push(insert.table_row);
                     }
                     Event::Update(mut update) => {
+                        // Rows are upserted as a whole, so an unchanged value must be known to
+                        // keep it.
+                        if update
+                            .table_row
+                            .values
+                            .iter()
+                            .any(|cell| matches!(cell, Cell::UnchangedToast(_)))
+                        {
+                            return Err(BigQueryDestinationError::UnchangedToast(update.table_id));
+                        }
+
                         update
                             .table_row
                             .values
@@ -511,14 +845,43 @@ impl BigQueryDestination {
             if !table_id_to_table_rows.is_empty() {
                 let mut inner = self.inner.write().await;
 
-                for (table_id, table_rows) in table_id_to_table_rows {
-                    let (table_id, table_descriptor) =
-                        Self::load_table_id_and_descriptor(&inner, &table_id).await?;
+                for (table_id, mut table_rows) in table_id_to_table_rows {
+                    let (table, table_descriptor, projection) = Self::load_table_and_descriptor(
+                        &inner,
+                        &table_id,
+                        self.column_filter.as_ref(),
+                        &self.identifier_mapper,
+                        self.schema_mapping,
+                    )
+                    .await?;
+
+                    if let Some(projection) = projection {
+                        // The operation type cell is the last value of each row, and it's not
+                        // part of the table schema, so we set it aside while projecting.
+                        for table_row in table_rows.iter_mut() {
+                            let operation_type = table_row.values.pop();
+                            projection.apply(table_row);
+                            table_row.values.extend(operation_type);
+                        }
+                    }
+
+                    let table_rows = self.row_size_guard.check_rows(
+                        table_id,
+                        table_rows,
+                        prost::Message::encoded_len,
+                    )?;
+                    if table_rows.is_empty() {
+                        continue;
+                    }
 
-                    let dataset_id = inner.dataset_id.clone();
                     inner
                         .client
-                        .stream_rows(&dataset_id, table_id, &table_descriptor, table_rows)
+                        .stream_rows(
+                            &table.dataset_id,
+                            table.table_id,
+                            &table_descriptor,
+                            table_rows,
+                        )
                         .await?;
                 }
             }
@@ -548,7 +911,6 @@ impl BigQueryDestination {
     /// Processes truncate events by executing `TRUNCATE TABLE` statements in BigQuery.
     ///
     /// Maps PostgreSQL table OIDs to BigQuery table names and issues truncate commands.
-    #[allow(dead_code)]
     async fn process_truncate_events(
         &self,
         truncate_events: Vec<TruncateEvent>,
@@ -566,12 +928,15 @@ impl BigQueryDestination {
                     .await;
 
                 if let Some(table_schema) = schema_cache.get_table_schema_ref(&table_id) {
+                    let table = Self::bigquery_table(
+                        &self.identifier_mapper,
+                        self.schema_mapping,
+                        &inner.dataset_id,
+                        &table_schema.name,
+                    )?;
                     inner
                         .client
-                        .truncate_table(
-                            &inner.dataset_id,
-                            &table_schema.name.as_bigquery_table_id(),
-                        )
+                        .truncate_table(&table.dataset_id, &table.table_id)
                         .await?;
                 } else {
                     info!(
@@ -635,6 +1000,7 @@ impl BigQueryDestination {
             Type::INT8 => "INT8".to_string(),
             Type::FLOAT4 => "FLOAT4".to_string(),
             Type::FLOAT8 => "FLOAT8".to_string(),
+            Type::NUMERIC => "NUMERIC".to_string(),
             Type::TEXT => "TEXT".to_string(),
             Type::VARCHAR => "VARCHAR".to_string(),
             Type::TIMESTAMP => "TIMESTAMP".to_string(),
@@ -642,6 +1008,7 @@ impl BigQueryDestination {
             Type::DATE => "DATE".to_string(),
             Type::TIME => "TIME".to_string(),
             Type::TIMETZ => "TIMETZ".to_string(),
+            Type::INTERVAL => "INTERVAL".to_string(),
             Type::BYTEA => "BYTEA".to_string(),
             Type::UUID => "UUID".to_string(),
             Type::JSON => "JSON".to_string(),
@@ -663,6 +1030,7 @@ impl BigQueryDestination {
             "INT8" => Ok(Type::INT8),
             "FLOAT4" => Ok(Type::FLOAT4),
             "FLOAT8" => Ok(Type::FLOAT8),
+            "NUMERIC" => Ok(Type::NUMERIC),
             "TEXT" => Ok(Type::TEXT),
             "VARCHAR" => Ok(Type::VARCHAR),
             "TIMESTAMP" => Ok(Type::TIMESTAMP),
@@ -670,6 +1038,7 @@ impl BigQueryDestination {
             "DATE" => Ok(Type::DATE),
             "TIME" => Ok(Type::TIME),
             "TIMETZ" => Ok(Type::TIMETZ),
+            "INTERVAL" => Ok(Type::INTERVAL),
             "BYTEA" => Ok(Type::BYTEA),
             "UUID" => Ok(Type::UUID),
             "JSON" => Ok(Type::JSON),
@@ -691,6 +1060,7 @@ impl Destination for BigQueryDestination {
     }
 
     async fn write_table_schema(&self, table_schema: TableSchema) -> Result<(), DestinationError> {
+        let _permit = self.acquire_write_permit().await;
         self.write_table_schema(table_schema).await?;
 
         Ok(())
@@ -707,16 +1077,30 @@ impl Destination for BigQueryDestination {
         table_id: TableId,
         table_rows: Vec<TableRow>,
     ) -> Result<(), DestinationError> {
+        let _permit = self.acquire_write_permit().await;
         self.write_table_rows(table_id, table_rows).await?;
 
         Ok(())
     }
 
     async fn write_events(&self, events: Vec<Event>) -> Result<(), DestinationError> {
+        let _permit = self.acquire_write_permit().await;
         self.write_events(events).await?;
 
         Ok(())
     }
+
+    /// Truncates the table, unlike the `TRUNCATE` events which are not applied yet.
+    async fn truncate_table(&self, table_id: TableId) -> Result<(), DestinationError> {
+        let _permit = self.acquire_write_permit().await;
+        let truncate_event = TruncateEvent {
+            options: 0,
+            rel_ids: vec![table_id],
+        };
+        self.process_truncate_events(vec![truncate_event]).await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -872,6 +1256,7 @@ mod tests {
             Type::INT8,
             Type::FLOAT4,
             Type::FLOAT8,
+            Type::NUMERIC,
             Type::TEXT,
             Type::VARCHAR,
             Type::TIMESTAMP,
@@ -879,6 +1264,7 @@ mod tests {
             Type::DATE,
             Type::TIME,
             Type::TIMETZ,
+            Type::INTERVAL,
             Type::BYTEA,
             Type::UUID,
             Type::JSON,
@@ -945,4 +1331,100 @@ mod tests {
         assert_eq!(column_rows[3].values[6], Cell::U32(3));
         assert_eq!(column_rows[4].values[6], Cell::U32(4));
     }
+
+    #[test]
+    fn test_bigquery_identifiers_with_overlong_names() {
+        let table_name = TableName::new("public".to_string(), "t".repeat(2000));
+        let column_schemas = vec![
+            ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+            ColumnSchema::new("c".repeat(400), Type::TEXT, -1, true, false),
+        ];
+
+        let mapper =
+            IdentifierMapper::new(BIGQUERY_IDENTIFIER_LIMITS, IdentifierOverflowPolicy::Error);
+        assert!(matches!(
+            BigQueryDestination::bigquery_table(
+                &mapper,
+                SchemaMapping::Flatten,
+                "dataset",
+                &table_name
+            ),
+            Err(BigQueryDestinationError::Identifier(_))
+        ));
+        assert!(matches!(
+            BigQueryDestination::bigquery_column_schemas(&mapper, column_schemas.clone()),
+            Err(BigQueryDestinationError::Identifier(_))
+        ));
+
+        let mapper = IdentifierMapper::new(
+            BIGQUERY_IDENTIFIER_LIMITS,
+            IdentifierOverflowPolicy::Truncate,
+        );
+        let table = BigQueryDestination::bigquery_table(
+            &mapper,
+            SchemaMapping::Flatten,
+            "dataset",
+            &table_name,
+        )
+        .unwrap();
+        assert_eq!(table.table_id.len(), 1024);
+        assert!(table.table_id.starts_with("public_ttt"));
+
+        let mapped = BigQueryDestination::bigquery_column_schemas(&mapper, column_schemas).unwrap();
+        assert_eq!(mapped[0].name, "id");
+        assert_eq!(mapped[1].name.len(), 300);
+        assert!(mapped[1].name.starts_with("ccc"));
+        assert_eq!(mapped[1].typ, Type::TEXT);
+    }
+
+    #[test]
+    fn test_bigquery_tables_with_schema_mapping() {
+        let mapper =
+            IdentifierMapper::new(BIGQUERY_IDENTIFIER_LIMITS, IdentifierOverflowPolicy::Error);
+        let table_name = TableName::new("analytics".to_string(), "events".to_string());
+
+        let table = BigQueryDestination::bigquery_table(
+            &mapper,
+            SchemaMapping::Flatten,
+            "dataset",
+            &table_name,
+        )
+        .unwrap();
+        assert_eq!(table.dataset_id, "dataset");
+        assert_eq!(table.table_id, "analytics_events");
+
+        let table = BigQueryDestination::bigquery_table(
+            &mapper,
+            SchemaMapping::Namespace,
+            "dataset",
+            &table_name,
+        )
+        .unwrap();
+        assert_eq!(table.dataset_id, "analytics");
+        assert_eq!(table.table_id, "events");
+
+        // Flattened names containing underscores can be the same, unlike namespaced names.
+        let other_table_name = TableName::new("analytics_events".to_string(), "x".to_string());
+        let table_name = TableName::new("analytics".to_string(), "events_x".to_string());
+        for (schema_mapping, same_table) in [
+            (SchemaMapping::Flatten, true),
+            (SchemaMapping::Namespace, false),
+        ] {
+            let table = BigQueryDestination::bigquery_table(
+                &mapper,
+                schema_mapping,
+                "dataset",
+                &table_name,
+            )
+            .unwrap();
+            let other_table = BigQueryDestination::bigquery_table(
+                &mapper,
+                schema_mapping,
+                "dataset",
+                &other_table_name,
+            )
+            .unwrap();
+            assert_eq!(table == other_table, same_table);
+        }
+    }
 }
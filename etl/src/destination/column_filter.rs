@@ -0,0 +1,268 @@
+use config::shared::{ColumnFilterConfig, ColumnFilterRule};
+use postgres::schema::{ColumnSchema, TableName, TableSchema};
+use std::collections::{BTreeMap, HashSet};
+use thiserror::Error;
+
+use crate::conversions::event::Event;
+use crate::conversions::table_row::TableRow;
+
+/// Errors that can occur while filtering the columns of a table.
+#[derive(Debug, Error)]
+pub enum ColumnFilterError {
+    #[error("The primary key column '{1}' of table {0} can't be filtered out")]
+    KeyColumnFiltered(TableName, String),
+
+    #[error("The column filter of table {0} filters out all of its columns")]
+    AllColumnsFiltered(TableName),
+}
+
+/// Filters the columns of the tables sent to a destination.
+///
+/// The filter is applied by destinations after rows are parsed and before they are encoded, so the
+/// schema cache and the schemas stored for restarts keep describing the source tables.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnFilter {
+    rules: BTreeMap<TableName, ColumnFilterRule>,
+}
+
+impl ColumnFilter {
+    /// Creates a new [`ColumnFilter`] from its configuration.
+    pub fn new(config: &ColumnFilterConfig) -> Self {
+        let rules = config
+            .tables
+            .iter()
+            .map(|table| {
+                (
+                    TableName::new(table.schema.clone(), table.name.clone()),
+                    table.rule.clone(),
+                )
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Returns the projection of the columns of `table_schema` which are kept, or `None` if the
+    /// table is sent whole.
+    ///
+    /// Destinations which upsert rows by primary key must set `keep_keys`, in which case filtering
+    /// out a primary key column is an error.
+    pub fn projection(
+        &self,
+        table_schema: &TableSchema,
+        keep_keys: bool,
+    ) -> Result<Option<ColumnProjection>, ColumnFilterError> {
+        let Some(rule) = self.rules.get(&table_schema.name) else {
+            return Ok(None);
+        };
+
+        ColumnProjection::new(table_schema, rule, keep_keys).map(Some)
+    }
+}
+
+/// The columns of a table kept by a [`ColumnFilterRule`], in their original order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnProjection {
+    indexes: Vec<usize>,
+}
+
+impl ColumnProjection {
+    /// Creates the projection of the columns of `table_schema` which are kept by `rule`.
+    ///
+    /// If `keep_keys` is set, filtering out a primary key column is an error.
+    pub fn new(
+        table_schema: &TableSchema,
+        rule: &ColumnFilterRule,
+        keep_keys: bool,
+    ) -> Result<Self, ColumnFilterError> {
+        let (columns, allow) = match rule {
+            ColumnFilterRule::Allow(columns) => (columns, true),
+            ColumnFilterRule::Deny(columns) => (columns, false),
+        };
+        let columns = columns.iter().map(String::as_str).collect::<HashSet<_>>();
+
+        let mut indexes = Vec::with_capacity(table_schema.column_schemas.len());
+        for (index, column_schema) in table_schema.column_schemas.iter().enumerate() {
+            if columns.contains(column_schema.name.as_str()) == allow {
+                indexes.push(index);
+            } else if keep_keys && column_schema.primary {
+                return Err(ColumnFilterError::KeyColumnFiltered(
+                    table_schema.name.clone(),
+                    column_schema.name.clone(),
+                ));
+            }
+        }
+
+        if indexes.is_empty() {
+            return Err(ColumnFilterError::AllColumnsFiltered(
+                table_schema.name.clone(),
+            ));
+        }
+
+        Ok(Self { indexes })
+    }
+
+    /// Returns the kept column schemas.
+    pub fn column_schemas(&self, column_schemas: &[ColumnSchema]) -> Vec<ColumnSchema> {
+        self.indexes
+            .iter()
+            .map(|&index| column_schemas[index].clone())
+            .collect()
+    }
+
+    /// Removes the values of the filtered out columns from `table_row`.
+    pub fn apply(&self, table_row: &mut TableRow) {
+        let mut values = std::mem::take(&mut table_row.values)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        table_row.values = self
+            .indexes
+            .iter()
+            .filter_map(|&index| values.get_mut(index).and_then(Option::take))
+            .collect();
+    }
+
+    /// Removes the values of the filtered out columns from the rows carried by `event`.
+    ///
+    /// Old rows containing only the key columns still have a value for every column, so they are
+    /// filtered the same way.
+    pub fn apply_to_event(&self, event: &mut Event) {
+        match event {
+            Event::Insert(event) => self.apply(&mut event.table_row),
+            Event::Update(event) => {
+                self.apply(&mut event.table_row);
+                if let Some((_, old_table_row)) = &mut event.old_table_row {
+                    self.apply(old_table_row);
+                }
+            }
+            Event::Delete(event) => {
+                if let Some((_, old_table_row)) = &mut event.old_table_row {
+                    self.apply(old_table_row);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::Cell;
+    use crate::conversions::event::InsertEvent;
+    use config::shared::TableColumnFilterConfig;
+    use tokio_postgres::types::Type;
+
+    fn users_schema() -> TableSchema {
+        TableSchema::new(
+            1,
+            TableName::new("public".to_string(), "users".to_string()),
+            vec![
+                ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+                ColumnSchema::new("name".to_string(), Type::TEXT, -1, true, false),
+                ColumnSchema::new("ssn".to_string(), Type::TEXT, -1, true, false),
+            ],
+        )
+    }
+
+    fn users_row() -> TableRow {
+        TableRow::new(vec![
+            Cell::I64(1),
+            Cell::String("alice".to_string()),
+            Cell::String("123-45-6789".to_string()),
+        ])
+    }
+
+    fn filter(rule: ColumnFilterRule) -> ColumnFilter {
+        ColumnFilter::new(&ColumnFilterConfig {
+            tables: vec![TableColumnFilterConfig {
+                schema: "public".to_string(),
+                name: "users".to_string(),
+                rule,
+            }],
+        })
+    }
+
+    #[test]
+    fn deny_list_drops_pii_column() {
+        let schema = users_schema();
+        let projection = filter(ColumnFilterRule::Deny(vec!["ssn".to_string()]))
+            .projection(&schema, true)
+            .unwrap()
+            .unwrap();
+
+        let column_names = projection
+            .column_schemas(&schema.column_schemas)
+            .into_iter()
+            .map(|column_schema| column_schema.name)
+            .collect::<Vec<_>>();
+        assert_eq!(column_names, vec!["id", "name"]);
+
+        let mut table_row = users_row();
+        projection.apply(&mut table_row);
+        assert_eq!(
+            table_row.values,
+            vec![Cell::I64(1), Cell::String("alice".to_string())]
+        );
+    }
+
+    #[test]
+    fn allow_list_keeps_only_listed_columns() {
+        let schema = users_schema();
+        let projection = filter(ColumnFilterRule::Allow(vec![
+            "id".to_string(),
+            "ssn".to_string(),
+        ]))
+        .projection(&schema, true)
+        .unwrap()
+        .unwrap();
+
+        let mut event = Event::Insert(InsertEvent {
+            table_id: schema.id,
+            table_row: users_row(),
+        });
+        projection.apply_to_event(&mut event);
+        let Event::Insert(event) = event else {
+            panic!("expected an insert event");
+        };
+        assert_eq!(
+            event.table_row.values,
+            vec![Cell::I64(1), Cell::String("123-45-6789".to_string())]
+        );
+    }
+
+    #[test]
+    fn unfiltered_tables_have_no_projection() {
+        let mut schema = users_schema();
+        schema.name = TableName::new("public".to_string(), "orders".to_string());
+
+        let projection = filter(ColumnFilterRule::Deny(vec!["ssn".to_string()]))
+            .projection(&schema, true)
+            .unwrap();
+        assert!(projection.is_none());
+    }
+
+    #[test]
+    fn key_columns_cant_be_filtered_for_upsert_destinations() {
+        let schema = users_schema();
+        let filter = filter(ColumnFilterRule::Deny(vec!["id".to_string()]));
+
+        assert!(matches!(
+            filter.projection(&schema, true),
+            Err(ColumnFilterError::KeyColumnFiltered(_, column)) if column == "id"
+        ));
+        assert!(filter.projection(&schema, false).unwrap().is_some());
+    }
+
+    #[test]
+    fn filtering_out_all_columns_is_rejected() {
+        let schema = users_schema();
+        let filter = filter(ColumnFilterRule::Allow(vec!["missing".to_string()]));
+
+        assert!(matches!(
+            filter.projection(&schema, false),
+            Err(ColumnFilterError::AllColumnsFiltered(_))
+        ));
+    }
+}
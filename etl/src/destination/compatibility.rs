@@ -0,0 +1,136 @@
+/// A column of a destination table, with its type as named by the destination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DestinationColumn {
+    pub name: String,
+    pub typ: String,
+    pub nullable: bool,
+}
+
+/// A column whose type in the destination differs from the type the source column maps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub column_name: String,
+    pub expected_type: String,
+    pub actual_type: String,
+}
+
+/// The result of comparing the columns a destination table must have to receive the rows of a
+/// source table with the columns it actually has.
+///
+/// Extra destination columns are not reported, since rows can be written without them as long as
+/// they are nullable or have a default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// Columns of the source table missing from the destination table.
+    pub missing_columns: Vec<String>,
+    /// Columns whose destination type differs from the type the source type maps to.
+    pub type_mismatches: Vec<TypeMismatch>,
+    /// Nullable source columns which are required in the destination table, so that rows with a
+    /// `NULL` in them would be rejected.
+    pub nullability_conflicts: Vec<String>,
+}
+
+impl CompatibilityReport {
+    /// Returns `true` if the rows of the source table can be written to the destination table.
+    pub fn is_compatible(&self) -> bool {
+        self.missing_columns.is_empty()
+            && self.type_mismatches.is_empty()
+            && self.nullability_conflicts.is_empty()
+    }
+}
+
+/// Compares the `expected` columns, derived from a source table, with the `actual` columns of the
+/// destination table.
+///
+/// Column names and types are compared case-insensitively, as destinations usually treat them.
+pub fn check_compatibility(
+    expected: &[DestinationColumn],
+    actual: &[DestinationColumn],
+) -> CompatibilityReport {
+    let mut report = CompatibilityReport::default();
+
+    for expected_column in expected {
+        let Some(actual_column) = actual
+            .iter()
+            .find(|column| column.name.eq_ignore_ascii_case(&expected_column.name))
+        else {
+            report.missing_columns.push(expected_column.name.clone());
+            continue;
+        };
+
+        if !actual_column.typ.eq_ignore_ascii_case(&expected_column.typ) {
+            report.type_mismatches.push(TypeMismatch {
+                column_name: expected_column.name.clone(),
+                expected_type: expected_column.typ.clone(),
+                actual_type: actual_column.typ.clone(),
+            });
+        }
+
+        if expected_column.nullable && !actual_column.nullable {
+            report
+                .nullability_conflicts
+                .push(expected_column.name.clone());
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, typ: &str, nullable: bool) -> DestinationColumn {
+        DestinationColumn {
+            name: name.to_string(),
+            typ: typ.to_string(),
+            nullable,
+        }
+    }
+
+    #[test]
+    fn matching_columns_are_compatible() {
+        let expected = [column("id", "int64", false), column("name", "string", true)];
+        // Names and types are compared case-insensitively, extra columns are ignored and a
+        // nullable destination column accepts a non-nullable source column.
+        let actual = [
+            column("ID", "INT64", true),
+            column("name", "STRING", true),
+            column("extra", "bool", true),
+        ];
+
+        let report = check_compatibility(&expected, &actual);
+
+        assert!(report.is_compatible());
+        assert_eq!(report, CompatibilityReport::default());
+    }
+
+    #[test]
+    fn incompatible_columns_are_reported() {
+        let expected = [
+            column("id", "int64", false),
+            column("name", "string", true),
+            column("age", "int64", true),
+            column("created_at", "timestamp", false),
+        ];
+        let actual = [
+            column("id", "int64", false),
+            column("name", "string", false),
+            column("age", "string", true),
+        ];
+
+        let report = check_compatibility(&expected, &actual);
+
+        assert!(!report.is_compatible());
+        assert_eq!(report.missing_columns, vec!["created_at".to_string()]);
+        assert_eq!(
+            report.type_mismatches,
+            vec![TypeMismatch {
+                column_name: "age".to_string(),
+                expected_type: "int64".to_string(),
+                actual_type: "string".to_string(),
+            }]
+        );
+        assert_eq!(report.nullability_conflicts, vec!["name".to_string()]);
+    }
+}
@@ -0,0 +1,370 @@
+use postgres::schema::{ColumnSchema, TableId, TableName, TableSchema};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tokio::task;
+
+use crate::conversions::Cell;
+use crate::conversions::event::Event;
+use crate::conversions::table_row::TableRow;
+use crate::destination::base::{Destination, DestinationError};
+use crate::schema::cache::SchemaCache;
+
+#[derive(Debug, Error)]
+pub enum FileDestinationError {
+    #[error("An IO error occurred while writing to the file destination: {0}")]
+    Io(#[from] io::Error),
+
+    /// The requested table schema was not found in the schema cache.
+    #[error("The table schema for table id {0} was not found in the schema cache")]
+    MissingTableSchema(TableId),
+
+    /// No schema cache has been injected into this destination instance.
+    #[error("The schema cache was not set on the destination")]
+    MissingSchemaCache,
+}
+
+#[derive(Debug)]
+struct Inner {
+    schema_cache: Option<SchemaCache>,
+    table_schemas: Vec<TableSchema>,
+}
+
+/// A destination which appends the rows and changes of each table to a file of newline-delimited
+/// JSON, named `<schema>.<table>.jsonl`, in a directory.
+///
+/// Every line is an object with an `op` of `schema`, `copy`, `insert`, `update`, `delete` or
+/// `truncate`, and the `row` and `old_row` it applies to, if any, with the values of the columns
+/// keyed by their names, as converted by [`Cell::to_json`]. The columns of updates which Postgres
+/// didn't send are left out of the row. Truncates also have the `cascade` and `restart_identity`
+/// options they were run with.
+///
+/// The files are only appended to, so that they can be inspected while the pipeline runs, which
+/// makes this destination meant for local testing rather than for production. The table schemas
+/// are kept in memory, so it should be used with a state store which isn't persisted either, e.g.
+/// [`MemoryStateStore`](crate::state::store::memory::MemoryStateStore).
+#[derive(Debug, Clone)]
+pub struct FileDestination {
+    directory: PathBuf,
+    inner: Arc<RwLock<Inner>>,
+}
+
+impl FileDestination {
+    /// Creates a destination writing to the files of `directory`, which is created if missing.
+    pub fn new(directory: impl Into<PathBuf>) -> Result<Self, FileDestinationError> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+
+        let inner = Inner {
+            schema_cache: None,
+            table_schemas: Vec::new(),
+        };
+
+        Ok(Self {
+            directory,
+            inner: Arc::new(RwLock::new(inner)),
+        })
+    }
+
+    /// Returns the path of the file to which the lines of the table `table_name` are appended.
+    pub fn table_path(&self, table_name: &TableName) -> PathBuf {
+        let file_name = format!("{}.{}.jsonl", table_name.schema, table_name.name)
+            .replace(['/', '\\', '\0'], "_");
+
+        self.directory.join(file_name)
+    }
+
+    async fn table_schema(&self, table_id: TableId) -> Result<TableSchema, FileDestinationError> {
+        let inner = self.inner.read().await;
+        let schema_cache = inner
+            .schema_cache
+            .as_ref()
+            .ok_or(FileDestinationError::MissingSchemaCache)?;
+
+        schema_cache
+            .get_table_schema(&table_id)
+            .await
+            .ok_or(FileDestinationError::MissingTableSchema(table_id))
+    }
+
+    /// Appends the columns of `table_schema` to the file of its table, whose following lines have
+    /// these columns.
+    async fn write_table_schema(
+        &self,
+        table_schema: TableSchema,
+    ) -> Result<(), FileDestinationError> {
+        let columns: Vec<_> = table_schema
+            .column_schemas
+            .iter()
+            .map(|column_schema| {
+                json!({
+                    "name": column_schema.name,
+                    "type": column_schema.typ.name(),
+                    "nullable": column_schema.nullable,
+                    "primary": column_schema.primary,
+                })
+            })
+            .collect();
+        let mut lines = String::new();
+        push_line(&mut lines, &json!({"op": "schema", "columns": columns}));
+        append(self.table_path(&table_schema.name), lines).await?;
+
+        let mut inner = self.inner.write().await;
+        inner.table_schemas.retain(|s| s.id != table_schema.id);
+        inner.table_schemas.push(table_schema);
+
+        Ok(())
+    }
+
+    async fn write_events(&self, events: Vec<Event>) -> Result<(), FileDestinationError> {
+        // The lines are grouped by file, in the order of the events, since the order of the
+        // changes only matters within a table.
+        let mut table_lines: HashMap<TableId, (PathBuf, String)> = HashMap::new();
+        for event in events {
+            let table_ids = match &event {
+                Event::Insert(event) => vec![event.table_id],
+                Event::Update(event) => vec![event.table_id],
+                Event::Delete(event) => vec![event.table_id],
+                Event::Truncate(event) => event.rel_ids.clone(),
+                _ => continue,
+            };
+
+            for table_id in table_ids {
+                let table_schema = self.table_schema(table_id).await?;
+                let column_schemas = &table_schema.column_schemas;
+                let (op, table_row, old_table_row) = match &event {
+                    Event::Insert(event) => ("insert", Some(&event.table_row), None),
+                    Event::Update(event) => (
+                        "update",
+                        Some(&event.table_row),
+                        event.old_table_row.as_ref(),
+                    ),
+                    Event::Delete(event) => ("delete", None, event.old_table_row.as_ref()),
+                    _ => ("truncate", None, None),
+                };
+
+                let mut line = json!({ "op": op });
+                if let Some(table_row) = table_row {
+                    line["row"] = row_to_json(column_schemas, table_row, false);
+                }
+                if let Some((is_key, old_table_row)) = old_table_row {
+                    line["old_row"] = row_to_json(column_schemas, old_table_row, *is_key);
+                }
+                if let Event::Truncate(event) = &event {
+                    line["cascade"] = event.cascade().into();
+                    line["restart_identity"] = event.restart_identity().into();
+                }
+
+                let (_, lines) = table_lines
+                    .entry(table_id)
+                    .or_insert_with(|| (self.table_path(&table_schema.name), String::new()));
+                push_line(lines, &line);
+            }
+        }
+
+        for (path, lines) in table_lines.into_values() {
+            append(path, lines).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Destination for FileDestination {
+    async fn inject(&self, schema_cache: SchemaCache) -> Result<(), DestinationError> {
+        let mut inner = self.inner.write().await;
+        inner.schema_cache = Some(schema_cache);
+
+        Ok(())
+    }
+
+    async fn write_table_schema(&self, table_schema: TableSchema) -> Result<(), DestinationError> {
+        self.write_table_schema(table_schema).await?;
+
+        Ok(())
+    }
+
+    async fn write_table_schema_change(
+        &self,
+        _before: TableSchema,
+        after: TableSchema,
+    ) -> Result<bool, DestinationError> {
+        self.write_table_schema(after).await?;
+
+        Ok(true)
+    }
+
+    async fn load_table_schemas(&self) -> Result<Vec<TableSchema>, DestinationError> {
+        let inner = self.inner.read().await;
+
+        Ok(inner.table_schemas.clone())
+    }
+
+    async fn write_table_rows(
+        &self,
+        table_id: TableId,
+        table_rows: Vec<TableRow>,
+    ) -> Result<(), DestinationError> {
+        let table_schema = self.table_schema(table_id).await?;
+
+        let mut lines = String::new();
+        for table_row in &table_rows {
+            let row = row_to_json(&table_schema.column_schemas, table_row, false);
+            push_line(&mut lines, &json!({"op": "copy", "row": row}));
+        }
+        append(self.table_path(&table_schema.name), lines).await?;
+
+        Ok(())
+    }
+
+    async fn write_events(&self, events: Vec<Event>) -> Result<(), DestinationError> {
+        self.write_events(events).await?;
+
+        Ok(())
+    }
+}
+
+/// Converts `table_row` into an object keyed by the names of its columns.
+///
+/// If `key_only` is set, the row only has the values of the replica identity, so the other columns
+/// are left out.
+fn row_to_json(column_schemas: &[ColumnSchema], table_row: &TableRow, key_only: bool) -> Value {
+    let mut row = Map::new();
+    for (column_schema, cell) in column_schemas.iter().zip(&table_row.values) {
+        if matches!(cell, Cell::UnchangedToast(_)) || (key_only && !column_schema.primary) {
+            continue;
+        }
+        row.insert(column_schema.name.clone(), cell.to_json());
+    }
+
+    Value::Object(row)
+}
+
+fn push_line(lines: &mut String, line: &Value) {
+    lines.push_str(&line.to_string());
+    lines.push('\n');
+}
+
+/// Appends `lines` to the file at `path`, creating the file if missing.
+///
+/// The lines are written with a single `write_all` to a file opened in append mode, which on local
+/// file systems writes them at once at the end of the file, so that they don't interleave with
+/// the lines appended to the same file by other workers. The file is written on the blocking
+/// threads of the runtime, so that the workers writing to this destination are not blocked.
+async fn append(path: PathBuf, lines: String) -> Result<(), FileDestinationError> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    task::spawn_blocking(move || {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(lines.as_bytes())
+    })
+    .await
+    .map_err(io::Error::from)??;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::event::{DeleteEvent, InsertEvent, TruncateEvent, UpdateEvent};
+    use std::path::Path;
+    use tokio_postgres::types::Type;
+    use uuid::Uuid;
+
+    fn read_lines(path: &Path) -> Vec<Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rows_and_events_are_appended_to_the_file_of_their_table() {
+        let directory =
+            std::env::temp_dir().join(format!("etl-file-destination-{}", Uuid::new_v4()));
+        let destination = FileDestination::new(&directory).unwrap();
+        let table_schema = TableSchema::new(
+            1,
+            TableName::new("public".to_string(), "users".to_string()),
+            vec![
+                ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+                ColumnSchema::new("name".to_string(), Type::TEXT, -1, true, false),
+            ],
+        );
+        let schema_cache = SchemaCache::new();
+        schema_cache.add_table_schema(table_schema.clone()).await;
+        destination.inject(schema_cache).await.unwrap();
+
+        destination
+            .write_table_schema(table_schema.clone())
+            .await
+            .unwrap();
+        destination
+            .write_table_rows(
+                1,
+                vec![TableRow::new(vec![
+                    Cell::I64(1),
+                    Cell::String("alice".to_string()),
+                ])],
+            )
+            .await
+            .unwrap();
+        destination
+            .write_events(vec![
+                Event::Insert(InsertEvent {
+                    table_id: 1,
+                    table_row: TableRow::new(vec![Cell::I64(2), Cell::Null(Type::TEXT)]),
+                }),
+                Event::Update(UpdateEvent {
+                    table_id: 1,
+                    table_row: TableRow::new(vec![Cell::I64(2), Cell::UnchangedToast(Type::TEXT)]),
+                    old_table_row: None,
+                }),
+                Event::Delete(DeleteEvent {
+                    table_id: 1,
+                    old_table_row: Some((
+                        true,
+                        TableRow::new(vec![Cell::I64(1), Cell::Null(Type::TEXT)]),
+                    )),
+                }),
+                Event::Truncate(TruncateEvent {
+                    options: 2,
+                    rel_ids: vec![1],
+                }),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            destination.load_table_schemas().await.unwrap(),
+            vec![table_schema.clone()]
+        );
+        let path = destination.table_path(&table_schema.name);
+        assert_eq!(path, directory.join("public.users.jsonl"));
+        assert_eq!(
+            read_lines(&path),
+            vec![
+                json!({"op": "schema", "columns": [
+                    {"name": "id", "type": "int8", "nullable": false, "primary": true},
+                    {"name": "name", "type": "text", "nullable": true, "primary": false},
+                ]}),
+                json!({"op": "copy", "row": {"id": 1, "name": "alice"}}),
+                json!({"op": "insert", "row": {"id": 2, "name": null}}),
+                json!({"op": "update", "row": {"id": 2}}),
+                json!({"op": "delete", "old_row": {"id": 1}}),
+                json!({"op": "truncate", "cascade": false, "restart_identity": true}),
+            ]
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+use config::shared::IdentifierOverflowPolicy;
+use std::borrow::Cow;
+use std::fmt;
+use thiserror::Error;
+
+/// Length, in bytes, of the suffix appended to truncated identifiers: an underscore followed by
+/// 16 hex digits.
+const HASH_SUFFIX_LEN: usize = 17;
+
+/// Errors that can occur when mapping identifiers to a destination.
+#[derive(Debug, Error)]
+pub enum IdentifierError {
+    #[error(
+        "The {kind} name '{identifier}' is {len} bytes long, which exceeds the destination limit of {max_len} bytes"
+    )]
+    TooLong {
+        kind: IdentifierKind,
+        identifier: String,
+        len: usize,
+        max_len: usize,
+    },
+}
+
+/// The kind of object an identifier names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierKind {
+    Table,
+    Column,
+}
+
+impl fmt::Display for IdentifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentifierKind::Table => write!(f, "table"),
+            IdentifierKind::Column => write!(f, "column"),
+        }
+    }
+}
+
+/// Maximum lengths, in bytes, of the identifiers accepted by a destination.
+///
+/// Limits expressed in characters by the destination can be used as is, since a string never has
+/// more characters than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdentifierLimits {
+    pub max_table_name_len: usize,
+    pub max_column_name_len: usize,
+}
+
+/// Maps identifiers derived from Postgres identifiers to identifiers accepted by a destination.
+#[derive(Debug, Clone)]
+pub struct IdentifierMapper {
+    limits: IdentifierLimits,
+    policy: IdentifierOverflowPolicy,
+}
+
+impl IdentifierMapper {
+    /// Creates a new [`IdentifierMapper`] applying `policy` to identifiers exceeding `limits`.
+    pub fn new(limits: IdentifierLimits, policy: IdentifierOverflowPolicy) -> Self {
+        Self { limits, policy }
+    }
+
+    /// Returns the destination name of a table.
+    pub fn table_name<'a>(&self, name: &'a str) -> Result<Cow<'a, str>, IdentifierError> {
+        self.fit(IdentifierKind::Table, name, self.limits.max_table_name_len)
+    }
+
+    /// Returns the destination name of a column.
+    pub fn column_name<'a>(&self, name: &'a str) -> Result<Cow<'a, str>, IdentifierError> {
+        self.fit(
+            IdentifierKind::Column,
+            name,
+            self.limits.max_column_name_len,
+        )
+    }
+
+    fn fit<'a>(
+        &self,
+        kind: IdentifierKind,
+        identifier: &'a str,
+        max_len: usize,
+    ) -> Result<Cow<'a, str>, IdentifierError> {
+        if identifier.len() <= max_len {
+            return Ok(Cow::Borrowed(identifier));
+        }
+
+        match self.policy {
+            IdentifierOverflowPolicy::Error => Err(IdentifierError::TooLong {
+                kind,
+                identifier: identifier.to_owned(),
+                len: identifier.len(),
+                max_len,
+            }),
+            IdentifierOverflowPolicy::Truncate => {
+                Ok(Cow::Owned(truncate_with_hash(identifier, max_len)))
+            }
+        }
+    }
+}
+
+/// Truncates `identifier` to at most `max_len` bytes, replacing its end with `_` followed by the
+/// hex encoded hash of the whole identifier.
+///
+/// The truncation happens on a character boundary, so the result can be shorter than `max_len`.
+/// `max_len` must be greater than the 17 bytes taken by the suffix.
+pub fn truncate_with_hash(identifier: &str, max_len: usize) -> String {
+    let mut prefix_len = max_len.saturating_sub(HASH_SUFFIX_LEN);
+    while !identifier.is_char_boundary(prefix_len) {
+        prefix_len -= 1;
+    }
+
+    format!(
+        "{}_{:016x}",
+        &identifier[..prefix_len],
+        fnv1a_64(identifier.as_bytes())
+    )
+}
+
+/// Computes the 64 bits FNV-1a hash of `bytes`.
+///
+/// The standard library hashers are not guaranteed to be stable across releases, while truncated
+/// names must stay the same for data to keep landing in the same destination tables and columns.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: IdentifierLimits = IdentifierLimits {
+        max_table_name_len: 64,
+        max_column_name_len: 32,
+    };
+
+    #[test]
+    fn names_within_limits_are_unchanged() {
+        let mapper = IdentifierMapper::new(LIMITS, IdentifierOverflowPolicy::Error);
+
+        assert_eq!(mapper.table_name("public_users").unwrap(), "public_users");
+        assert_eq!(mapper.column_name(&"a".repeat(32)).unwrap(), "a".repeat(32));
+    }
+
+    #[test]
+    fn overlong_names_are_rejected_by_default() {
+        let mapper = IdentifierMapper::new(LIMITS, IdentifierOverflowPolicy::default());
+
+        let err = mapper.column_name(&"a".repeat(33)).unwrap_err();
+        assert!(matches!(
+            err,
+            IdentifierError::TooLong {
+                kind: IdentifierKind::Column,
+                len: 33,
+                max_len: 32,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn overlong_names_are_truncated_to_valid_and_unique_names() {
+        let mapper = IdentifierMapper::new(LIMITS, IdentifierOverflowPolicy::Truncate);
+        let first = format!("public_{}_first", "x".repeat(100));
+        let second = format!("public_{}_second", "x".repeat(100));
+
+        let first_mapped = mapper.table_name(&first).unwrap();
+        let second_mapped = mapper.table_name(&second).unwrap();
+
+        assert_eq!(first_mapped.len(), 64);
+        assert_eq!(second_mapped.len(), 64);
+        assert!(first_mapped.starts_with("public_xxx"));
+        assert_ne!(first_mapped, second_mapped);
+        // The same name is always mapped to the same truncated name.
+        assert_eq!(mapper.table_name(&first).unwrap(), first_mapped);
+    }
+
+    #[test]
+    fn truncation_happens_on_a_char_boundary() {
+        // Each character takes 2 bytes, so the 15 bytes left for the prefix fit 7 characters.
+        let truncated = truncate_with_hash(&"é".repeat(40), 32);
+
+        assert_eq!(truncated.len(), 31);
+        assert!(truncated.starts_with(&"é".repeat(7)));
+    }
+}
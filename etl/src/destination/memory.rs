@@ -6,6 +6,7 @@ use tracing::info;
 use crate::conversions::event::Event;
 use crate::conversions::table_row::TableRow;
 use crate::destination::base::{Destination, DestinationError};
+use crate::destination::column_filter::{ColumnFilter, ColumnProjection};
 
 #[derive(Debug)]
 struct Inner {
@@ -14,9 +15,27 @@ struct Inner {
     table_rows: Vec<(TableId, Vec<TableRow>)>,
 }
 
+impl Inner {
+    /// Returns the projection of the columns of `table_id` kept by `column_filter`.
+    ///
+    /// Tables whose schema was never written are kept whole.
+    fn projection(
+        &self,
+        column_filter: &ColumnFilter,
+        table_id: TableId,
+    ) -> Result<Option<ColumnProjection>, DestinationError> {
+        let Some(table_schema) = self.table_schemas.iter().find(|s| s.id == table_id) else {
+            return Ok(None);
+        };
+
+        Ok(column_filter.projection(table_schema, false)?)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MemoryDestination {
     inner: Arc<RwLock<Inner>>,
+    column_filter: Option<ColumnFilter>,
 }
 
 impl MemoryDestination {
@@ -29,8 +48,17 @@ impl MemoryDestination {
 
         Self {
             inner: Arc::new(RwLock::new(inner)),
+            column_filter: None,
         }
     }
+
+    /// Filters the columns of the rows stored by this destination with `column_filter`.
+    ///
+    /// Table schemas are stored whole, since they are loaded back into the schema cache.
+    pub fn with_column_filter(mut self, column_filter: ColumnFilter) -> Self {
+        self.column_filter = Some(column_filter);
+        self
+    }
 }
 
 impl Default for MemoryDestination {
@@ -48,6 +76,19 @@ impl Destination for MemoryDestination {
         Ok(())
     }
 
+    async fn write_table_schema_change(
+        &self,
+        _before: TableSchema,
+        after: TableSchema,
+    ) -> Result<bool, DestinationError> {
+        let mut inner = self.inner.write().await;
+        info!("changing table schema to:");
+        info!("{:?}", after);
+        inner.table_schemas.retain(|s| s.id != after.id);
+        inner.table_schemas.push(after);
+        Ok(true)
+    }
+
     async fn load_table_schemas(&self) -> Result<Vec<TableSchema>, DestinationError> {
         let inner = self.inner.read().await;
         let schemas = inner.table_schemas.to_vec();
@@ -59,9 +100,16 @@ impl Destination for MemoryDestination {
     async fn write_table_rows(
         &self,
         table_id: TableId,
-        table_rows: Vec<TableRow>,
+        mut table_rows: Vec<TableRow>,
     ) -> Result<(), DestinationError> {
         let mut inner = self.inner.write().await;
+        if let Some(column_filter) = &self.column_filter
+            && let Some(projection) = inner.projection(column_filter, table_id)?
+        {
+            for table_row in table_rows.iter_mut() {
+                projection.apply(table_row);
+            }
+        }
         info!("writing a batch of {} table rows:", table_rows.len());
         for table_row in &table_rows {
             info!("  {:?}", table_row);
@@ -70,8 +118,21 @@ impl Destination for MemoryDestination {
         Ok(())
     }
 
-    async fn write_events(&self, events: Vec<Event>) -> Result<(), DestinationError> {
+    async fn write_events(&self, mut events: Vec<Event>) -> Result<(), DestinationError> {
         let mut inner = self.inner.write().await;
+        if let Some(column_filter) = &self.column_filter {
+            for event in events.iter_mut() {
+                let table_id = match event {
+                    Event::Insert(event) => event.table_id,
+                    Event::Update(event) => event.table_id,
+                    Event::Delete(event) => event.table_id,
+                    _ => continue,
+                };
+                if let Some(projection) = inner.projection(column_filter, table_id)? {
+                    projection.apply_to_event(event);
+                }
+            }
+        }
         info!("writing a batch of {} events:", events.len());
         for event in &events {
             info!("  {:?}", event);
@@ -80,3 +141,82 @@ impl Destination for MemoryDestination {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::Cell;
+    use crate::conversions::event::{DeleteEvent, InsertEvent};
+    use config::shared::{ColumnFilterConfig, ColumnFilterRule, TableColumnFilterConfig};
+    use postgres::schema::{ColumnSchema, TableName};
+    use tokio_postgres::types::Type;
+
+    #[tokio::test]
+    async fn column_filter_drops_pii_column() {
+        let column_filter = ColumnFilter::new(&ColumnFilterConfig {
+            tables: vec![TableColumnFilterConfig {
+                schema: "public".to_string(),
+                name: "users".to_string(),
+                rule: ColumnFilterRule::Deny(vec!["email".to_string()]),
+            }],
+        });
+        let destination = MemoryDestination::new().with_column_filter(column_filter);
+        let table_schema = TableSchema::new(
+            1,
+            TableName::new("public".to_string(), "users".to_string()),
+            vec![
+                ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+                ColumnSchema::new("email".to_string(), Type::TEXT, -1, true, false),
+            ],
+        );
+        let table_row = TableRow::new(vec![
+            Cell::I64(1),
+            Cell::String("alice@example.com".to_string()),
+        ]);
+
+        destination
+            .write_table_schema(table_schema.clone())
+            .await
+            .unwrap();
+        destination
+            .write_table_rows(1, vec![table_row.clone()])
+            .await
+            .unwrap();
+        destination
+            .write_events(vec![
+                Event::Insert(InsertEvent {
+                    table_id: 1,
+                    table_row: table_row.clone(),
+                }),
+                Event::Delete(DeleteEvent {
+                    table_id: 1,
+                    old_table_row: Some((false, table_row)),
+                }),
+            ])
+            .await
+            .unwrap();
+
+        // The schema is stored whole, so that it can be loaded back into the schema cache.
+        assert_eq!(
+            destination.load_table_schemas().await.unwrap(),
+            vec![table_schema]
+        );
+
+        let filtered_row = TableRow::new(vec![Cell::I64(1)]);
+        let inner = destination.inner.read().await;
+        assert_eq!(inner.table_rows, vec![(1, vec![filtered_row.clone()])]);
+        assert_eq!(
+            inner.events,
+            vec![
+                Event::Insert(InsertEvent {
+                    table_id: 1,
+                    table_row: filtered_row.clone(),
+                }),
+                Event::Delete(DeleteEvent {
+                    table_id: 1,
+                    old_table_row: Some((false, filtered_row)),
+                }),
+            ]
+        );
+    }
+}
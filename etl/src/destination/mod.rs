@@ -1,4 +1,10 @@
 pub mod base;
 #[cfg(feature = "bigquery")]
 pub mod bigquery;
+pub mod column_filter;
+pub mod compatibility;
+pub mod file;
+pub mod identifier;
 pub mod memory;
+pub mod row_size;
+pub mod write_limit;
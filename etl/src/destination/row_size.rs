@@ -0,0 +1,147 @@
+use config::shared::OversizedRowPolicy;
+use postgres::schema::TableId;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::conversions::table_row::TableRow;
+use crate::metrics::inc_oversized_rows;
+
+/// Errors that can occur when checking the size of the rows written to a destination.
+#[derive(Debug, Error)]
+pub enum RowSizeError {
+    #[error(
+        "A row of table {table_id} is {size} bytes long once encoded, which exceeds the limit of {max_size} bytes"
+    )]
+    RowTooLarge {
+        table_id: TableId,
+        size: usize,
+        max_size: usize,
+    },
+}
+
+/// Checks the encoded size of the rows written to a destination against a limit, so that a single
+/// oversized row doesn't get a whole batch rejected by the destination.
+///
+/// The skipped rows are counted by the `oversized_rows_total` metric.
+#[derive(Debug, Clone)]
+pub struct RowSizeGuard {
+    max_size: usize,
+    policy: OversizedRowPolicy,
+}
+
+impl RowSizeGuard {
+    /// Creates a new [`RowSizeGuard`] applying `policy` to the rows larger than `max_size` bytes.
+    pub fn new(max_size: usize, policy: OversizedRowPolicy) -> Self {
+        Self { max_size, policy }
+    }
+
+    /// Returns the maximum encoded size of a row, in bytes.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Applies the policy to the rows of `table_id` whose size, as computed by `encoded_len`,
+    /// exceeds the limit, and returns the rows to write.
+    pub fn check_rows<F>(
+        &self,
+        table_id: TableId,
+        mut table_rows: Vec<TableRow>,
+        encoded_len: F,
+    ) -> Result<Vec<TableRow>, RowSizeError>
+    where
+        F: Fn(&TableRow) -> usize,
+    {
+        match self.policy {
+            OversizedRowPolicy::Error => {
+                if let Some(size) = table_rows
+                    .iter()
+                    .map(&encoded_len)
+                    .find(|size| *size > self.max_size)
+                {
+                    return Err(RowSizeError::RowTooLarge {
+                        table_id,
+                        size,
+                        max_size: self.max_size,
+                    });
+                }
+            }
+            OversizedRowPolicy::Skip => table_rows.retain(|table_row| {
+                let size = encoded_len(table_row);
+                if size <= self.max_size {
+                    return true;
+                }
+
+                warn!(
+                    table_id,
+                    size,
+                    max_size = self.max_size,
+                    "skipping a row exceeding the destination row size limit"
+                );
+                inc_oversized_rows(table_id);
+
+                false
+            }),
+        }
+
+        Ok(table_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::Cell;
+    use crate::metrics::oversized_rows;
+
+    fn table_row(value: &str) -> TableRow {
+        TableRow::new(vec![Cell::I64(1), Cell::String(value.to_string())])
+    }
+
+    /// Sums the lengths of the string cells, as a stand-in for the encoded size of a row.
+    fn string_len(table_row: &TableRow) -> usize {
+        table_row
+            .values
+            .iter()
+            .map(|cell| match cell {
+                Cell::String(value) => value.len(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn oversized_rows_fail_the_batch_by_default() {
+        let guard = RowSizeGuard::new(10, OversizedRowPolicy::default());
+        let table_rows = vec![table_row("small"), table_row(&"x".repeat(11))];
+
+        let err = guard.check_rows(1, table_rows, string_len).unwrap_err();
+
+        assert!(matches!(
+            err,
+            RowSizeError::RowTooLarge {
+                table_id: 1,
+                size: 11,
+                max_size: 10,
+            }
+        ));
+    }
+
+    #[test]
+    fn oversized_rows_are_skipped_and_counted() {
+        let guard = RowSizeGuard::new(10, OversizedRowPolicy::Skip);
+        let before = oversized_rows(1);
+        let table_rows = vec![
+            table_row("first"),
+            table_row(&"x".repeat(11)),
+            table_row(&"x".repeat(10)),
+        ];
+
+        let table_rows = guard.check_rows(1, table_rows, string_len).unwrap();
+
+        assert_eq!(
+            table_rows,
+            vec![table_row("first"), table_row(&"x".repeat(10))]
+        );
+        assert_eq!(oversized_rows(1) - before, 1);
+    }
+}
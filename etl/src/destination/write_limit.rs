@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::debug;
+
+/// Limits the number of writes sent to a destination at the same time.
+///
+/// Clones share the same permits, so a single limiter can be handed to all the pipelines, or all
+/// the workers of a pipeline, writing to the same destination. Waiting writers are granted
+/// permits in the order in which they asked for them.
+#[derive(Debug, Clone)]
+pub struct WriteLimiter {
+    semaphore: Arc<Semaphore>,
+    max_permits: usize,
+}
+
+impl WriteLimiter {
+    /// Creates a new [`WriteLimiter`] allowing `max_permits` concurrent writes.
+    ///
+    /// At least one write is always allowed, so that writers can't wait forever.
+    pub fn new(max_permits: usize) -> Self {
+        let max_permits = max_permits.max(1);
+
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_permits)),
+            max_permits,
+        }
+    }
+
+    /// Waits until a write is allowed and returns the permit, which must be held until the write
+    /// completes.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("the write limiter semaphore is never closed");
+
+        debug!(
+            in_use = self.in_use(),
+            max_permits = self.max_permits,
+            "acquired a destination write permit"
+        );
+
+        permit
+    }
+
+    /// Returns the maximum number of concurrent writes.
+    pub fn max_permits(&self) -> usize {
+        self.max_permits
+    }
+
+    /// Returns the number of writes currently in progress.
+    pub fn in_use(&self) -> usize {
+        self.max_permits - self.semaphore.available_permits()
+    }
+
+    /// Returns the fraction, between `0.0` and `1.0`, of the permits currently in use.
+    pub fn utilization(&self) -> f64 {
+        self.in_use() as f64 / self.max_permits as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_writers_never_exceed_permits() {
+        let limiter = WriteLimiter::new(3);
+        let current = Arc::new(AtomicUsize::new(0));
+        let max_observed = Arc::new(AtomicUsize::new(0));
+
+        let mut handles = Vec::new();
+        for _ in 0..20 {
+            // Each writer gets its own clone, as each pipeline or worker would.
+            let limiter = limiter.clone();
+            let current = current.clone();
+            let max_observed = max_observed.clone();
+
+            handles.push(tokio::spawn(async move {
+                let _permit = limiter.acquire().await;
+
+                let writers = current.fetch_add(1, Ordering::SeqCst) + 1;
+                max_observed.fetch_max(writers, Ordering::SeqCst);
+                assert!(limiter.in_use() <= limiter.max_permits());
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                current.fetch_sub(1, Ordering::SeqCst);
+            }));
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(max_observed.load(Ordering::SeqCst), 3);
+        assert_eq!(limiter.in_use(), 0);
+        assert_eq!(limiter.utilization(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn utilization_reflects_permits_in_use() {
+        let limiter = WriteLimiter::new(4);
+
+        let first = limiter.acquire().await;
+        let _second = limiter.acquire().await;
+        assert_eq!(limiter.in_use(), 2);
+        assert_eq!(limiter.utilization(), 0.5);
+
+        drop(first);
+        assert_eq!(limiter.in_use(), 1);
+    }
+
+    #[test]
+    fn zero_permits_allow_one_write() {
+        assert_eq!(WriteLimiter::new(0).max_permits(), 1);
+    }
+}
@@ -3,6 +3,7 @@ pub mod concurrency;
 pub mod conversions;
 pub mod destination;
 pub mod encryption;
+pub mod metrics;
 pub mod pipeline;
 pub mod replication;
 pub mod schema;
@@ -0,0 +1,139 @@
+use postgres::schema::TableId;
+use prometheus::{Encoder, IntCounter, IntCounterVec, TextEncoder, register_int_counter_vec};
+use std::sync::LazyLock;
+
+/// Number of rows copied from the source during the initial copy of a table.
+static ROWS_COPIED_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "rows_copied_total",
+        "Number of rows copied during the initial copy of a table.",
+        &["table"]
+    )
+    .expect("the rows copied counter should be registered once")
+});
+
+/// Number of bytes of `COPY` data received from the source during the initial copy of a table.
+static BYTES_COPIED_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "bytes_copied_total",
+        "Number of bytes of COPY data received during the initial copy of a table.",
+        &["table"]
+    )
+    .expect("the bytes copied counter should be registered once")
+});
+
+/// Number of rows which failed to be converted, by kind of error.
+static CONVERSION_ERRORS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "conversion_errors_total",
+        "Number of rows which failed to be converted, by kind of error.",
+        &["type"]
+    )
+    .expect("the conversion errors counter should be registered once")
+});
+
+/// Number of rows which were not written to a destination because of their size, by table.
+static OVERSIZED_ROWS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "oversized_rows_total",
+        "Number of rows skipped because they exceeded the destination row size limit.",
+        &["table"]
+    )
+    .expect("the oversized rows counter should be registered once")
+});
+
+/// The counters of the initial copy of a single table.
+///
+/// The counters are looked up once, so that counting a row doesn't need to hash its labels.
+#[derive(Debug, Clone)]
+pub struct TableCopyMetrics {
+    rows_copied: IntCounter,
+    bytes_copied: IntCounter,
+}
+
+impl TableCopyMetrics {
+    pub fn for_table(table_id: TableId) -> Self {
+        let table = table_id.to_string();
+
+        Self {
+            rows_copied: ROWS_COPIED_TOTAL.with_label_values(&[&table]),
+            bytes_copied: BYTES_COPIED_TOTAL.with_label_values(&[&table]),
+        }
+    }
+
+    /// Counts a row received as `num_bytes` bytes of `COPY` data.
+    pub fn inc_row(&self, num_bytes: usize) {
+        self.rows_copied.inc();
+        self.bytes_copied.inc_by(num_bytes as u64);
+    }
+}
+
+/// Counts a row which failed to be converted with an error of kind `kind`.
+pub fn inc_conversion_errors(kind: &str) {
+    CONVERSION_ERRORS_TOTAL.with_label_values(&[kind]).inc();
+}
+
+/// Counts a row of the table with `table_id` which was skipped because of its size.
+pub fn inc_oversized_rows(table_id: TableId) {
+    OVERSIZED_ROWS_TOTAL
+        .with_label_values(&[&table_id.to_string()])
+        .inc();
+}
+
+/// Returns the number of rows of the table with `table_id` which were skipped because of their
+/// size.
+pub fn oversized_rows(table_id: TableId) -> u64 {
+    OVERSIZED_ROWS_TOTAL
+        .with_label_values(&[&table_id.to_string()])
+        .get()
+}
+
+/// The content type of the metrics encoded by [`encode_metrics`].
+pub const METRICS_CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+/// Encodes the metrics registered in the process in the Prometheus text format.
+pub fn encode_metrics() -> Result<Vec<u8>, prometheus::Error> {
+    let mut body = vec![];
+    TextEncoder::new().encode(&prometheus::gather(), &mut body)?;
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::table_row::TableRowConverter;
+    use config::shared::CopyConfig;
+    use postgres::schema::ColumnSchema;
+    use tokio_postgres::types::Type;
+
+    #[test]
+    fn rows_and_bytes_are_counted_per_table() {
+        let metrics = TableCopyMetrics::for_table(u32::MAX - 1);
+        metrics.inc_row(10);
+        metrics.inc_row(5);
+
+        let table = (u32::MAX - 1).to_string();
+        assert_eq!(ROWS_COPIED_TOTAL.with_label_values(&[&table]).get(), 2);
+        assert_eq!(BYTES_COPIED_TOTAL.with_label_values(&[&table]).get(), 15);
+    }
+
+    #[test]
+    fn conversion_errors_are_counted_by_kind() {
+        let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+        let column_schemas = vec![ColumnSchema::new(
+            "id".to_string(),
+            Type::INT4,
+            -1,
+            false,
+            true,
+        )];
+        let counter = CONVERSION_ERRORS_TOTAL.with_label_values(&["invalid_value"]);
+        let before = counter.get();
+
+        let result = converter.try_from_copy_data(b"one\n", &column_schemas);
+
+        assert!(result.is_err());
+        assert!(counter.get() > before);
+    }
+}
@@ -1,18 +1,31 @@
-use config::shared::PipelineConfig;
+use config::shared::{
+    AutoCreatePublicationConfig, HeartbeatConfig, PipelineConfig, PublishOperation,
+};
+use postgres::schema::{TableId, TableName};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::{Semaphore, watch};
 use tracing::{error, info};
 
 use crate::concurrency::shutdown::{ShutdownTx, create_shutdown_channel};
+use crate::concurrency::status::{StatusRx, StatusTx, create_status_channel};
+use crate::conversions::event::{Event, MetadataEvent};
+use crate::conversions::table_row::{CopyConfigError, TableRowConverter};
 use crate::destination::base::{Destination, DestinationError};
-use crate::replication::client::{PgReplicationClient, PgReplicationError};
+use crate::destination::column_filter::{ColumnFilterError, ColumnProjection};
+use crate::replication::client::{PgReplicationClient, PgReplicationError, PublicationTable};
 use crate::schema::cache::SchemaCache;
 use crate::state::store::base::{StateStore, StateStoreError};
 use crate::state::table::TableReplicationPhase;
 use crate::workers::apply::{ApplyWorker, ApplyWorkerError, ApplyWorkerHandle};
 use crate::workers::base::{Worker, WorkerHandle, WorkerWaitErrors};
+use crate::workers::heartbeat::{HeartbeatWorker, HeartbeatWorkerError, HeartbeatWorkerHandle};
 use crate::workers::pool::TableSyncWorkerPool;
+use crate::workers::sequence_sync::{
+    SequenceSyncWorker, SequenceSyncWorkerError, SequenceSyncWorkerHandle,
+};
 
 #[derive(Debug, Error)]
 pub enum PipelineError {
@@ -25,6 +38,12 @@ pub enum PipelineError {
     #[error("Apply worker failed to start in the pipeline: {0}")]
     ApplyWorkerFailedOnStart(#[from] ApplyWorkerError),
 
+    #[error("Sequence sync worker failed to start in the pipeline: {0}")]
+    SequenceSyncWorkerFailedOnStart(#[from] SequenceSyncWorkerError),
+
+    #[error("Heartbeat worker failed to start in the pipeline: {0}")]
+    HeartbeatWorkerFailedOnStart(#[from] HeartbeatWorkerError),
+
     #[error("An error happened in the state store: {0}")]
     StateStore(#[from] StateStoreError),
 
@@ -36,8 +55,28 @@ pub enum PipelineError {
     )]
     ShutdownFailed(#[from] watch::error::SendError<()>),
 
+    #[error("The pipeline did not shut down within {0:?}")]
+    ShutdownTimedOut(Duration),
+
     #[error("The publication '{0}' does not exist in the database")]
     MissingPublication(String),
+
+    #[error(
+        "The publication '{0}' cannot be created: the role needs the CREATE privilege on the database and to own the published tables"
+    )]
+    PublicationCreationNotPermitted(String),
+
+    #[error("The existing publication '{0}' does not include the tables {1}")]
+    PublicationMissingTables(String, String),
+
+    #[error("The columns of a table in the publication are invalid: {0}")]
+    InvalidPublicationColumns(#[from] ColumnFilterError),
+
+    #[error("The row filter of table {0} is invalid: {1}")]
+    InvalidRowFilter(TableName, String),
+
+    #[error("The COPY options can't be parsed: {0}")]
+    InvalidCopyConfig(#[from] CopyConfigError),
 }
 
 #[derive(Debug)]
@@ -48,6 +87,8 @@ enum PipelineWorkers {
         //  with workers management, which should not be done in the pipeline.
         apply_worker: ApplyWorkerHandle,
         pool: TableSyncWorkerPool,
+        sequence_sync_worker: Option<SequenceSyncWorkerHandle>,
+        heartbeat_worker: Option<HeartbeatWorkerHandle>,
     },
 }
 
@@ -61,6 +102,7 @@ pub struct Pipeline<S, D> {
     destination: D,
     workers: PipelineWorkers,
     shutdown_tx: ShutdownTx,
+    status_tx: StatusTx,
 }
 
 impl<S, D> Pipeline<S, D>
@@ -75,6 +117,7 @@ where
         // Here we are not taking the `shutdown_rx` since we will just extract it from the `shutdown_tx`
         // via the `subscribe` method. This is done to make the code cleaner.
         let (shutdown_tx, _) = create_shutdown_channel();
+        let (status_tx, _) = create_status_channel();
 
         Self {
             id,
@@ -83,6 +126,7 @@ where
             destination,
             workers: PipelineWorkers::NotStarted,
             shutdown_tx,
+            status_tx,
         }
     }
 
@@ -94,12 +138,22 @@ where
         self.shutdown_tx.clone()
     }
 
+    /// Returns a receiver of the pipeline's status, which is paused while the destination is down
+    /// if [`PipelineConfig::destination_down`] allows it.
+    pub fn status_rx(&self) -> StatusRx {
+        self.status_tx.subscribe()
+    }
+
     pub async fn start(&mut self) -> Result<(), PipelineError> {
         info!(
             "starting pipeline for publication '{}' with id {}",
             self.config.publication_name, self.id
         );
 
+        // We check that the rows copied with the configured options can be parsed before doing any
+        // work, instead of failing, or worse mis-parsing rows, once tables are being copied.
+        TableRowConverter::new(&self.config.copy)?;
+
         // We create the schema cache, which will be shared also with the destination.
         let schema_cache = SchemaCache::default();
 
@@ -109,15 +163,25 @@ where
         // We prepare the schema cache with table schemas loaded, in case there is the need.
         self.prepare_schema_cache(&schema_cache).await?;
 
-        // We create the first connection to Postgres.
+        // We synchronize the relation subscription states with the publication, to make sure we
+        // always know which tables to work with. The tables added to or removed from the
+        // publication afterwards are picked up by the apply worker.
+        //
+        // These preflight queries only touch the catalog, so they run on a short-lived connection
+        // with the short catalog timeout.
+        let catalog_client = PgReplicationClient::connect_with_statement_timeout(
+            self.config.pg_connection.clone(),
+            self.config.statement_timeout.catalog_ms,
+        )
+        .await?;
+        self.initialize_table_states(&catalog_client, &schema_cache)
+            .await?;
+        drop(catalog_client);
+
+        // We create the connection to Postgres used by the apply worker.
         let replication_client =
             PgReplicationClient::connect(self.config.pg_connection.clone()).await?;
 
-        // We synchronize the relation subscription states with the publication, to make sure we
-        // always know which tables to work with. Maybe in the future we also want to react in real
-        // time to new relation ids being sent over by the cdc event stream.
-        self.initialize_table_states(&replication_client).await?;
-
         // We create the table sync workers pool to manage all table sync workers in a central place.
         let pool = TableSyncWorkerPool::new();
 
@@ -133,13 +197,68 @@ where
             schema_cache,
             self.state_store.clone(),
             self.destination.clone(),
+            self.status_tx.clone(),
             self.shutdown_tx.subscribe(),
             table_sync_worker_permits,
         )
         .start()
         .await?;
 
-        self.workers = PipelineWorkers::Started { apply_worker, pool };
+        // We create and start the sequence sync worker, if sequences are replicated.
+        let sequence_sync_worker = match self.config.sequence_sync_interval_ms {
+            Some(sequence_sync_interval_ms) => {
+                let replication_client = PgReplicationClient::connect_with_statement_timeout(
+                    self.config.pg_connection.clone(),
+                    self.config.statement_timeout.catalog_ms,
+                )
+                .await?;
+
+                let sequence_sync_worker = SequenceSyncWorker::new(
+                    self.id,
+                    self.config.clone(),
+                    Duration::from_millis(sequence_sync_interval_ms),
+                    replication_client,
+                    self.destination.clone(),
+                    self.shutdown_tx.subscribe(),
+                )
+                .start()
+                .await?;
+
+                Some(sequence_sync_worker)
+            }
+            None => None,
+        };
+
+        // We create and start the heartbeat worker, if heartbeats are written.
+        let heartbeat_worker = match &self.config.heartbeat {
+            Some(heartbeat) => {
+                let replication_client = PgReplicationClient::connect_with_statement_timeout(
+                    self.config.pg_connection.clone(),
+                    self.config.statement_timeout.catalog_ms,
+                )
+                .await?;
+
+                let heartbeat_worker = HeartbeatWorker::new(
+                    self.id,
+                    heartbeat_table_name(heartbeat),
+                    Duration::from_millis(heartbeat.interval_ms),
+                    replication_client,
+                    self.shutdown_tx.subscribe(),
+                )
+                .start()
+                .await?;
+
+                Some(heartbeat_worker)
+            }
+            None => None,
+        };
+
+        self.workers = PipelineWorkers::Started {
+            apply_worker,
+            pool,
+            sequence_sync_worker,
+            heartbeat_worker,
+        };
 
         Ok(())
     }
@@ -156,33 +275,81 @@ where
     async fn initialize_table_states(
         &self,
         replication_client: &PgReplicationClient,
+        schema_cache: &SchemaCache,
     ) -> Result<(), PipelineError> {
         info!(
             "initializing table states for tables in publication '{}'",
             self.config.publication_name
         );
 
-        // We need to make sure that the publication exists.
-        if !replication_client
+        // We need to make sure that the publication exists, creating it if requested.
+        let publication_exists = replication_client
             .publication_exists(&self.config.publication_name)
-            .await?
-        {
-            error!(
-                "publication '{}' does not exist in the database",
-                self.config.publication_name
-            );
-            return Err(PipelineError::MissingPublication(
-                self.config.publication_name.clone(),
-            ));
+            .await?;
+        match &self.config.auto_create_publication {
+            Some(auto_create_publication) if publication_exists => {
+                self.check_publication_tables(replication_client, auto_create_publication)
+                    .await?;
+            }
+            Some(auto_create_publication) => {
+                self.create_publication(replication_client, auto_create_publication)
+                    .await?;
+            }
+            None if publication_exists => {}
+            None => {
+                error!(
+                    "publication '{}' does not exist in the database",
+                    self.config.publication_name
+                );
+                return Err(PipelineError::MissingPublication(
+                    self.config.publication_name.clone(),
+                ));
+            }
         }
 
+        let heartbeat_table_id = match &self.config.heartbeat {
+            Some(heartbeat) => Some(
+                self.prepare_heartbeat_table(replication_client, heartbeat)
+                    .await?,
+            ),
+            None => None,
+        };
+
         let table_ids = replication_client
             .get_publication_table_ids(&self.config.publication_name)
             .await?;
 
         self.state_store.load_table_replication_states().await?;
         let states = self.state_store.get_table_replication_states().await?;
+
+        if self.config.emit_metadata_events {
+            self.emit_table_dropped_events(schema_cache, &table_ids, &states)
+                .await?;
+        }
+
+        // The tables which are no longer published are not replicated anymore, the apply worker
+        // then handles the tables removed from the publication while the pipeline is running.
+        for table_id in states.keys() {
+            if !table_ids.contains(table_id) {
+                self.state_store
+                    .delete_table_replication_state(*table_id)
+                    .await?;
+            }
+        }
+
         for table_id in table_ids {
+            // The heartbeat table is only published for its changes to be confirmed, so it's
+            // skipped to be neither copied nor streamed to the destination.
+            if Some(table_id) == heartbeat_table_id {
+                if states.get(&table_id) != Some(&TableReplicationPhase::Skipped) {
+                    self.state_store
+                        .update_table_replication_state(table_id, TableReplicationPhase::Skipped)
+                        .await?;
+                }
+
+                continue;
+            }
+
             if !states.contains_key(&table_id) {
                 self.state_store
                     .update_table_replication_state(table_id, TableReplicationPhase::Init)
@@ -193,8 +360,185 @@ where
         Ok(())
     }
 
+    /// Creates the heartbeat table if it doesn't exist and adds it to the publication if it's not
+    /// in it yet, returning its id.
+    async fn prepare_heartbeat_table(
+        &self,
+        replication_client: &PgReplicationClient,
+        heartbeat: &HeartbeatConfig,
+    ) -> Result<TableId, PipelineError> {
+        let table_name = heartbeat_table_name(heartbeat);
+        let table_id = replication_client
+            .create_heartbeat_table(&table_name)
+            .await?;
+
+        let published_table_names = replication_client
+            .get_publication_table_names(&self.config.publication_name)
+            .await?;
+        if !published_table_names.contains(&table_name) {
+            replication_client
+                .add_table_to_publication(&self.config.publication_name, &table_name)
+                .await?;
+        }
+
+        Ok(table_id)
+    }
+
+    /// Sends a [`MetadataEvent::TableDropped`] event to the destination for each replicated table
+    /// which is no longer in the publication.
+    async fn emit_table_dropped_events(
+        &self,
+        schema_cache: &SchemaCache,
+        table_ids: &[TableId],
+        states: &HashMap<TableId, TableReplicationPhase>,
+    ) -> Result<(), PipelineError> {
+        let table_ids = table_ids.iter().collect::<HashSet<_>>();
+        let mut dropped_table_ids = states
+            .keys()
+            .filter(|table_id| !table_ids.contains(table_id))
+            .copied()
+            .collect::<Vec<_>>();
+        if dropped_table_ids.is_empty() {
+            return Ok(());
+        }
+        dropped_table_ids.sort_unstable();
+
+        let mut events = Vec::with_capacity(dropped_table_ids.len());
+        for table_id in dropped_table_ids {
+            info!(
+                "table {} is no longer in publication '{}'",
+                table_id, self.config.publication_name
+            );
+
+            events.push(Event::Metadata(MetadataEvent::TableDropped {
+                table_id,
+                table_schema: schema_cache.get_table_schema(&table_id).await,
+            }));
+        }
+
+        self.destination.write_events(events).await?;
+
+        Ok(())
+    }
+
+    async fn create_publication(
+        &self,
+        replication_client: &PgReplicationClient,
+        auto_create_publication: &AutoCreatePublicationConfig,
+    ) -> Result<(), PipelineError> {
+        let table_names = publication_table_names(auto_create_publication);
+
+        // We check the privileges upfront, to fail with a clear error instead of the one of the
+        // first missing privilege.
+        if !replication_client
+            .can_create_publication(&table_names)
+            .await?
+        {
+            error!(
+                "publication '{}' cannot be created with the privileges of the role",
+                self.config.publication_name
+            );
+            return Err(PipelineError::PublicationCreationNotPermitted(
+                self.config.publication_name.clone(),
+            ));
+        }
+
+        // The column rules are resolved against the current schemas of the tables, since the
+        // publication lists the published columns by name, and the row filters are validated
+        // against them, to report an invalid filter along with its table.
+        let mut tables = Vec::with_capacity(auto_create_publication.tables.len());
+        for (table, table_name) in auto_create_publication.tables.iter().zip(table_names) {
+            let column_names = match &table.columns {
+                Some(rule) => {
+                    let table_id = replication_client.get_table_id(&table_name).await?;
+                    let table_schema = replication_client.get_table_schema(table_id, None).await?;
+                    let column_names = ColumnProjection::new(&table_schema, rule, true)?
+                        .column_schemas(&table_schema.column_schemas)
+                        .into_iter()
+                        .map(|column_schema| column_schema.name)
+                        .collect();
+
+                    Some(column_names)
+                }
+                None => None,
+            };
+
+            let row_filter_error = match &table.row_filter {
+                Some(row_filter) => {
+                    replication_client
+                        .check_row_filter(&table_name, row_filter)
+                        .await?
+                }
+                None => None,
+            };
+            if let Some(message) = row_filter_error {
+                error!(
+                    "the row filter of table {} is invalid: {}",
+                    table_name, message
+                );
+                return Err(PipelineError::InvalidRowFilter(table_name, message));
+            }
+
+            tables.push(PublicationTable {
+                name: table_name,
+                column_names,
+                row_filter: table.row_filter.clone(),
+            });
+        }
+
+        let publish = auto_create_publication
+            .publish
+            .iter()
+            .map(PublishOperation::as_str)
+            .collect::<Vec<_>>();
+        replication_client
+            .create_publication(
+                &self.config.publication_name,
+                &tables,
+                &publish,
+                auto_create_publication.publish_via_partition_root,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn check_publication_tables(
+        &self,
+        replication_client: &PgReplicationClient,
+        auto_create_publication: &AutoCreatePublicationConfig,
+    ) -> Result<(), PipelineError> {
+        let published_table_names = replication_client
+            .get_publication_table_names(&self.config.publication_name)
+            .await?;
+
+        let missing_table_names = publication_table_names(auto_create_publication)
+            .into_iter()
+            .filter(|table_name| !published_table_names.contains(table_name))
+            .map(|table_name| table_name.to_string())
+            .collect::<Vec<_>>();
+        if !missing_table_names.is_empty() {
+            error!(
+                "publication '{}' exists but does not include the tables {:?}",
+                self.config.publication_name, missing_table_names
+            );
+            return Err(PipelineError::PublicationMissingTables(
+                self.config.publication_name.clone(),
+                missing_table_names.join(", "),
+            ));
+        }
+
+        Ok(())
+    }
+
     pub async fn wait(self) -> Result<(), PipelineError> {
-        let PipelineWorkers::Started { apply_worker, pool } = self.workers else {
+        let PipelineWorkers::Started {
+            apply_worker,
+            pool,
+            sequence_sync_worker,
+            heartbeat_worker,
+        } = self.workers
+        else {
             info!("pipeline was not started, nothing to wait for");
 
             return Ok(());
@@ -240,6 +584,31 @@ where
             info!("all table sync workers completed successfully");
         }
 
+        if let Some(sequence_sync_worker) = sequence_sync_worker {
+            info!("waiting for sequence sync worker to complete");
+
+            // The sequence sync worker is stopped by the same shutdown signal as the other workers.
+            if let Err(err) = sequence_sync_worker.wait().await {
+                errors.push(err);
+
+                info!("sequence sync worker completed with an error");
+            } else {
+                info!("sequence sync worker completed successfully");
+            }
+        }
+
+        if let Some(heartbeat_worker) = heartbeat_worker {
+            info!("waiting for heartbeat worker to complete");
+
+            if let Err(err) = heartbeat_worker.wait().await {
+                errors.push(err);
+
+                info!("heartbeat worker completed with an error");
+            } else {
+                info!("heartbeat worker completed successfully");
+            }
+        }
+
         if !errors.is_empty() {
             return Err(PipelineError::OneOrMoreWorkersFailed(WorkerWaitErrors(
                 errors,
@@ -263,3 +632,17 @@ where
         self.wait().await
     }
 }
+
+fn heartbeat_table_name(heartbeat: &HeartbeatConfig) -> TableName {
+    TableName::new(heartbeat.schema.clone(), heartbeat.table_name.clone())
+}
+
+fn publication_table_names(
+    auto_create_publication: &AutoCreatePublicationConfig,
+) -> Vec<TableName> {
+    auto_create_publication
+        .tables
+        .iter()
+        .map(|table| TableName::new(table.schema.clone(), table.name.clone()))
+        .collect()
+}
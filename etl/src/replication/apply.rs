@@ -1,18 +1,22 @@
 use crate::concurrency::shutdown::ShutdownRx;
-use crate::conversions::event::{Event, EventConversionError, EventType, convert_message_to_event};
+use crate::concurrency::status::StatusTx;
+use crate::conversions::event::{
+    Event, EventConversionError, EventType, MetadataEvent, convert_message_to_event,
+};
 use crate::destination::base::{Destination, DestinationError};
 use crate::pipeline::PipelineId;
 use crate::replication::client::{PgReplicationClient, PgReplicationError};
-use crate::replication::slot::{SlotError, get_slot_name};
+use crate::replication::destination_down::{DestinationDownError, PauseBudget, retry_delay};
+use crate::replication::slot::{SlotError, get_worker_slot_name};
 use crate::replication::stream::{EventsStream, EventsStreamError};
 use crate::schema::cache::SchemaCache;
 use crate::workers::apply::ApplyWorkerHookError;
 use crate::workers::base::WorkerType;
 use crate::workers::table_sync::TableSyncWorkerHookError;
 
-use config::shared::PipelineConfig;
+use config::shared::{BatchFlushMode, DestinationDownConfig, NullPolicy, PipelineConfig};
 use futures::StreamExt;
-use postgres::schema::TableId;
+use postgres::schema::{TableId, TableSchema};
 use postgres_replication::protocol;
 use postgres_replication::protocol::{LogicalReplicationMessage, ReplicationMessage};
 use std::future::Future;
@@ -22,7 +26,7 @@ use std::time::{Duration, Instant};
 use thiserror::Error;
 use tokio::pin;
 use tokio_postgres::types::PgLsn;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// The amount of milliseconds that pass between one refresh and the other of the system, in case no
 /// events or shutdown signal are received.
@@ -52,6 +56,9 @@ pub enum ApplyLoopError {
     #[error("An error occurred when interacting with the destination in the apply loop: {0}")]
     Destination(#[from] DestinationError),
 
+    #[error("The destination stayed down for too long in the apply loop: {0}")]
+    DestinationDown(#[from] DestinationDownError),
+
     #[error("A transaction should have started for the action ({0}) to be performed")]
     InvalidTransaction(String),
 
@@ -107,6 +114,21 @@ pub trait ApplyLoopHook {
         remote_final_lsn: PgLsn,
     ) -> impl Future<Output = Result<bool, Self::Error>> + Send;
 
+    /// Starts copying again the tables whose resync was requested.
+    ///
+    /// It's only called between transactions once all the events streamed so far were written to
+    /// the destination, so that none of the changes of a resynced table are written after it's
+    /// truncated.
+    fn process_table_resyncs(&self) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+
+    /// Starts copying the tables added to the publication and stops replicating the tables
+    /// removed from it.
+    ///
+    /// Like [`ApplyLoopHook::process_table_resyncs`], it's only called between transactions once
+    /// all the events streamed so far were written to the destination.
+    fn process_publication_changes(&self)
+    -> impl Future<Output = Result<bool, Self::Error>> + Send;
+
     fn worker_type(&self) -> WorkerType;
 }
 
@@ -181,14 +203,16 @@ struct HandleMessageResult {
     ///   duplicate events being sent. The commit event will be included in the
     ///   batch.
     /// * Set to [`EndBatch::Exclusive`] when a replication message indicates a change
-    ///   in schema. Since currently we are not handling any changes in schema, we
-    ///   mark the table as skipped in this case. The replication event will be excluded
-    ///   from the batch.
+    ///   in schema, so that the events preceding it are written before the change is
+    ///   passed to the destination. The replication event will be excluded from the
+    ///   batch. If metadata events are emitted, it's set to [`EndBatch::Inclusive`]
+    ///   instead and the batch ends with the [`MetadataEvent::SchemaChanged`] event.
     ///
     end_batch: Option<EndBatch>,
 
-    /// Set when a replication message indicates a change in schema, otherwise None
-    skip_table: Option<TableId>,
+    /// Set when a replication message indicates a change in schema, with the schema of the table
+    /// before and after the change, otherwise None
+    table_schema_change: Option<(TableSchema, TableSchema)>,
 }
 
 #[derive(Debug, Clone)]
@@ -212,17 +236,30 @@ struct ApplyLoopState {
     /// Last time when the batch was sent (or since when the apply loop started)
     last_batch_send_time: Instant,
 
+    /// Whether metadata events are emitted for the schema changes detected from relation messages.
+    emit_metadata_events: bool,
+
+    /// Whether the values of columns whose type isn't supported are kept as their raw text.
+    preserve_unsupported_types: bool,
+
     /// A batch of events to send to the destination
     events_batch: Vec<Event>,
 }
 
 impl ApplyLoopState {
-    fn new(next_status_update: StatusUpdate, events_batch: Vec<Event>) -> Self {
+    fn new(
+        next_status_update: StatusUpdate,
+        emit_metadata_events: bool,
+        preserve_unsupported_types: bool,
+        events_batch: Vec<Event>,
+    ) -> Self {
         Self {
             last_commit_end_lsn: None,
             remote_final_lsn: None,
             next_status_update,
             last_batch_send_time: Instant::now(),
+            emit_metadata_events,
+            preserve_unsupported_types,
             events_batch,
         }
     }
@@ -256,6 +293,7 @@ pub async fn start_apply_loop<D, T>(
     schema_cache: SchemaCache,
     destination: D,
     hook: T,
+    status_tx: StatusTx,
     mut shutdown_rx: ShutdownRx,
 ) -> Result<ApplyLoopResult, ApplyLoopError>
 where
@@ -282,7 +320,11 @@ where
 
     // We compute the slot name for the replication slot that we are going to use for the logical
     // replication. At this point we assume that the slot already exists.
-    let slot_name = get_slot_name(pipeline_id, hook.worker_type())?;
+    let slot_name = get_worker_slot_name(
+        pipeline_id,
+        hook.worker_type(),
+        config.replication_slot.as_ref(),
+    )?;
 
     // We start the logical replication stream with the supplied parameters at a given lsn. That
     // lsn is the last lsn from which we need to start fetching events.
@@ -294,8 +336,14 @@ where
     pin!(logical_replication_stream);
 
     // We initialize the shared state that is used throughout the loop to track progress.
+    // Metadata events are only emitted by the apply worker, which sees the changes of all tables,
+    // so that each change is emitted once.
+    let emit_metadata_events =
+        config.emit_metadata_events && matches!(hook.worker_type(), WorkerType::Apply);
     let mut state = ApplyLoopState::new(
         first_status_update,
+        emit_metadata_events,
+        config.preserve_unsupported_types,
         Vec::with_capacity(config.batch.max_size),
     );
 
@@ -309,6 +357,19 @@ where
             _ = shutdown_rx.changed() => {
                 info!("shutting down apply worker while waiting for incoming events");
 
+                flush_on_shutdown(
+                    &mut state,
+                    logical_replication_stream.as_mut(),
+                    &destination,
+                    &hook,
+                    config.batch.max_size,
+                    config.batch_flush_mode,
+                    &config,
+                    &status_tx,
+                    &mut shutdown_rx,
+                )
+                .await?;
+
                 return Ok(ApplyLoopResult::ApplyStopped);
             }
 
@@ -322,6 +383,11 @@ where
                     &hook,
                     config.batch.max_size,
                     max_batch_fill_duration,
+                    config.batch_flush_mode,
+                    config.null_policy,
+                    &config,
+                    &status_tx,
+                    &mut shutdown_rx,
                 )
                 .await?;
 
@@ -360,6 +426,14 @@ where
                     if !continue_loop {
                         break Ok(ApplyLoopResult::ApplyStopped);
                     }
+
+                    if state.events_batch.is_empty() {
+                        let continue_loop = hook.process_table_resyncs().await?
+                            && hook.process_publication_changes().await?;
+                        if !continue_loop {
+                            break Ok(ApplyLoopResult::ApplyStopped);
+                        }
+                    }
                 }
             }
         }
@@ -369,13 +443,18 @@ where
 #[expect(clippy::too_many_arguments)]
 async fn handle_replication_message_batch<D, T>(
     state: &mut ApplyLoopState,
-    events_stream: Pin<&mut EventsStream>,
+    mut events_stream: Pin<&mut EventsStream>,
     message: ReplicationMessage<LogicalReplicationMessage>,
     schema_cache: &SchemaCache,
     destination: &D,
     hook: &T,
     max_batch_size: usize,
     max_batch_fill_duration: Duration,
+    batch_flush_mode: BatchFlushMode,
+    null_policy: NullPolicy,
+    config: &PipelineConfig,
+    status_tx: &StatusTx,
+    shutdown_rx: &mut ShutdownRx,
 ) -> Result<bool, ApplyLoopError>
 where
     D: Destination + Clone + Send + 'static,
@@ -383,48 +462,80 @@ where
     ApplyLoopError: From<<T as ApplyLoopHook>::Error>,
 {
     let result =
-        handle_replication_message(state, events_stream, message, schema_cache, hook).await?;
+        handle_replication_message(state, events_stream.as_mut(), message, schema_cache, hook)
+            .await?;
 
-    if let Some(event) = result.event
+    if let Some(mut event) = result.event
         && matches!(result.end_batch, None | Some(EndBatch::Inclusive))
     {
+        event.apply_null_policy(null_policy);
         state.events_batch.push(event);
         state.update_last_commit_end_lsn(result.end_lsn);
     }
 
     try_send_batch(
         state,
+        events_stream,
         result.end_batch,
-        result.skip_table,
+        result.table_schema_change,
         destination,
         hook,
         max_batch_size,
         max_batch_fill_duration,
+        batch_flush_mode,
+        config,
+        status_tx,
+        shutdown_rx,
+        false,
     )
     .await
 }
 
+#[expect(clippy::too_many_arguments)]
 async fn try_send_batch<D, T>(
     state: &mut ApplyLoopState,
+    events_stream: Pin<&mut EventsStream>,
     end_batch: Option<EndBatch>,
-    skip_table: Option<TableId>,
+    table_schema_change: Option<(TableSchema, TableSchema)>,
     destination: &D,
     hook: &T,
     max_batch_size: usize,
     max_batch_fill_duration: Duration,
+    batch_flush_mode: BatchFlushMode,
+    config: &PipelineConfig,
+    status_tx: &StatusTx,
+    shutdown_rx: &mut ShutdownRx,
+    shutting_down: bool,
 ) -> Result<bool, ApplyLoopError>
 where
     D: Destination + Clone + Send + 'static,
     T: ApplyLoopHook,
     ApplyLoopError: From<<T as ApplyLoopHook>::Error>,
 {
-    let elapsed = state.last_batch_send_time.elapsed();
-    // `elapsed` could be zero in case current time is earlier than `last_batch_send_time`.
-    // We send the batch even in this case to make sure `last_batch_send_time` is reset to
-    // a new value and to avoid getting stuck with some events in the batch.
-    let time_to_send_batch = elapsed.is_zero() || elapsed > max_batch_fill_duration;
+    let batch_full_or_expired = || {
+        let elapsed = state.last_batch_send_time.elapsed();
+        // `elapsed` could be zero in case current time is earlier than `last_batch_send_time`.
+        // We send the batch even in this case to make sure `last_batch_send_time` is reset to
+        // a new value and to avoid getting stuck with some events in the batch.
+        let time_to_send_batch = elapsed.is_zero() || elapsed > max_batch_fill_duration;
+
+        time_to_send_batch || state.events_batch.len() >= max_batch_size
+    };
+    let send_batch = match batch_flush_mode {
+        BatchFlushMode::SizeOrTime => batch_full_or_expired(),
+        // A commit has been added to the batch if and only if its end lsn is tracked, in which
+        // case the batch ends with a whole transaction.
+        BatchFlushMode::TransactionBoundary => state.last_commit_end_lsn.is_some(),
+        // Outside of a transaction, the batch ends with a whole transaction as soon as it holds
+        // a commit, so the transactions received until the limits are reached are sent together.
+        BatchFlushMode::CoalescedTransactions => {
+            !state.handling_transaction()
+                && state.last_commit_end_lsn.is_some()
+                && batch_full_or_expired()
+        }
+    };
 
-    if time_to_send_batch || state.events_batch.len() >= max_batch_size || end_batch.is_some() {
+    if send_batch || end_batch.is_some() {
         if !state.events_batch.is_empty() {
             // TODO: figure out if we can send a slice to the destination instead of a vec
             // that would allow use to avoid new allocations of the `events_batch` vec and
@@ -437,14 +548,35 @@ where
                 events_batch.len()
             );
 
-            destination.write_events(events_batch).await?;
+            let written = write_events_batch(
+                state,
+                events_stream,
+                destination,
+                events_batch,
+                config,
+                status_tx,
+                shutdown_rx,
+                shutting_down,
+            )
+            .await?;
+            if !written {
+                // The pipeline was shut down while the destination was down. The position isn't
+                // advanced, so the batch will be streamed again once the pipeline restarts.
+                return Ok(true);
+            }
+
             state.last_batch_send_time = Instant::now();
         }
 
         let mut end_loop = false;
-        if let Some(table_id) = skip_table {
-            info!("skipping table {} due to schema change", table_id);
-            end_loop |= !hook.skip_table(table_id).await?;
+        if let Some((before, after)) = table_schema_change {
+            let table_id = after.id;
+            if destination.write_table_schema_change(before, after).await? {
+                info!("applied the schema change of table {}", table_id);
+            } else {
+                info!("skipping table {} due to schema change", table_id);
+                end_loop |= !hook.skip_table(table_id).await?;
+            }
         }
 
         // At this point, the `last_commit_end_lsn` will contain the LSN of the next byte in the WAL after
@@ -478,12 +610,253 @@ where
                 .await?;
         }
 
+        if !state.handling_transaction() {
+            end_loop |= !hook.process_table_resyncs().await?;
+            end_loop |= !hook.process_publication_changes().await?;
+        }
+
         return Ok(end_loop);
     }
 
     Ok(false)
 }
 
+/// Writes the events buffered when the pipeline is shut down to the destination and confirms the
+/// end of their last commit to Postgres, so that they're not streamed again once the pipeline
+/// restarts.
+///
+/// Unless batches are sent regardless of transaction boundaries, the events of a transaction whose
+/// commit wasn't received yet are dropped rather than written partially, since they're streamed
+/// again from the confirmed position. Likewise, a failed write isn't retried and the events are
+/// streamed again once the pipeline restarts.
+#[expect(clippy::too_many_arguments)]
+async fn flush_on_shutdown<D, T>(
+    state: &mut ApplyLoopState,
+    mut events_stream: Pin<&mut EventsStream>,
+    destination: &D,
+    hook: &T,
+    max_batch_size: usize,
+    batch_flush_mode: BatchFlushMode,
+    config: &PipelineConfig,
+    status_tx: &StatusTx,
+    shutdown_rx: &mut ShutdownRx,
+) -> Result<(), ApplyLoopError>
+where
+    D: Destination + Clone + Send + 'static,
+    T: ApplyLoopHook,
+    ApplyLoopError: From<<T as ApplyLoopHook>::Error>,
+{
+    if batch_flush_mode != BatchFlushMode::SizeOrTime && state.handling_transaction() {
+        let committed_events = state
+            .events_batch
+            .iter()
+            .rposition(|event| matches!(event, Event::Commit(_)))
+            .map_or(0, |last_commit| last_commit + 1);
+        state.events_batch.truncate(committed_events);
+    }
+
+    if state.events_batch.is_empty() && state.last_commit_end_lsn.is_none() {
+        return Ok(());
+    }
+
+    info!(
+        "flushing {} buffered events before shutting down",
+        state.events_batch.len()
+    );
+    try_send_batch(
+        state,
+        events_stream.as_mut(),
+        Some(EndBatch::Exclusive),
+        None,
+        destination,
+        hook,
+        max_batch_size,
+        Duration::ZERO,
+        batch_flush_mode,
+        config,
+        status_tx,
+        shutdown_rx,
+        true,
+    )
+    .await?;
+
+    events_stream
+        .send_status_update(
+            state.next_status_update.write_lsn,
+            state.next_status_update.flush_lsn,
+            state.next_status_update.apply_lsn,
+            false,
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Writes a batch of events to the destination, handling failed writes as configured by
+/// [`PipelineConfig::destination_down`].
+///
+/// When `shutting_down` is set, the batch is flushed after the shutdown signal was already
+/// received, which `shutdown_rx` doesn't report again. A failed write is then neither retried nor
+/// waited on, so that the shutdown isn't held up by a destination which is down.
+///
+/// Returns `false` if the pipeline was shut down before the batch could be written.
+#[expect(clippy::too_many_arguments)]
+async fn write_events_batch<D>(
+    state: &ApplyLoopState,
+    events_stream: Pin<&mut EventsStream>,
+    destination: &D,
+    events_batch: Vec<Event>,
+    config: &PipelineConfig,
+    status_tx: &StatusTx,
+    shutdown_rx: &mut ShutdownRx,
+    shutting_down: bool,
+) -> Result<bool, ApplyLoopError>
+where
+    D: Destination + Clone + Send + 'static,
+{
+    let Some(destination_down) = &config.destination_down else {
+        destination.write_events(events_batch).await?;
+
+        return Ok(true);
+    };
+
+    if shutting_down {
+        return match destination.write_events(events_batch).await {
+            Ok(()) => Ok(true),
+            Err(err) => {
+                warn!(
+                    "failed to write batch to destination while shutting down: {}",
+                    err
+                );
+
+                Ok(false)
+            }
+        };
+    }
+
+    let mut attempt = 0;
+    let err = loop {
+        match destination.write_events(events_batch.clone()).await {
+            Ok(()) => return Ok(true),
+            Err(err) if attempt < destination_down.retry.max_attempts => {
+                let delay = retry_delay(&destination_down.retry, attempt);
+                warn!(
+                    "failed to write batch to destination (attempt {}), retrying in {:?}: {}",
+                    attempt + 1,
+                    delay,
+                    err
+                );
+                attempt += 1;
+
+                tokio::select! {
+                    biased;
+
+                    _ = shutdown_rx.changed() => return Ok(false),
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+            Err(err) => break err,
+        }
+    };
+
+    if !destination_down.pause_on_destination_down {
+        return Err(err.into());
+    }
+
+    error!(
+        "destination is down, pausing the pipeline at lsn {}: {}",
+        state.next_status_update.flush_lsn, err
+    );
+
+    status_tx.pause();
+    let result = wait_for_destination_recovery(
+        state,
+        events_stream,
+        destination,
+        events_batch,
+        destination_down,
+        config,
+        shutdown_rx,
+    )
+    .await;
+    status_tx.resume();
+
+    result
+}
+
+/// Retries writing a batch of events to a destination which is down, until it succeeds or the
+/// pipeline can't stay paused anymore.
+///
+/// The position confirmed to Postgres is kept while waiting, so that the WAL of the events not
+/// written yet is retained.
+async fn wait_for_destination_recovery<D>(
+    state: &ApplyLoopState,
+    mut events_stream: Pin<&mut EventsStream>,
+    destination: &D,
+    events_batch: Vec<Event>,
+    destination_down: &DestinationDownConfig,
+    config: &PipelineConfig,
+    shutdown_rx: &mut ShutdownRx,
+) -> Result<bool, ApplyLoopError>
+where
+    D: Destination + Clone + Send + 'static,
+{
+    let budget = PauseBudget::new(destination_down, Instant::now());
+    let resume_check_interval = Duration::from_millis(destination_down.resume_check_interval_ms);
+
+    // The replication connection is busy streaming, so the current WAL position is read with
+    // another connection.
+    let catalog_client = PgReplicationClient::connect_with_statement_timeout(
+        config.pg_connection.clone(),
+        config.statement_timeout.catalog_ms,
+    )
+    .await?;
+
+    loop {
+        tokio::select! {
+            biased;
+
+            _ = shutdown_rx.changed() => {
+                info!("shutting down apply loop while the destination is down");
+
+                return Ok(false);
+            }
+            _ = tokio::time::sleep(resume_check_interval) => {}
+        }
+
+        // We keep confirming the same position, which also keeps the replication connection from
+        // timing out while no messages are read.
+        events_stream
+            .as_mut()
+            .send_status_update(
+                state.next_status_update.write_lsn,
+                state.next_status_update.flush_lsn,
+                state.next_status_update.apply_lsn,
+                true,
+            )
+            .await?;
+
+        let current_wal_lsn = catalog_client.get_current_wal_lsn().await?;
+        let retained_wal_bytes = u64::from(current_wal_lsn)
+            .saturating_sub(u64::from(state.next_status_update.flush_lsn));
+        budget.check(Instant::now(), retained_wal_bytes)?;
+
+        match destination.write_events(events_batch.clone()).await {
+            Ok(()) => {
+                info!("destination recovered, resuming the pipeline");
+
+                return Ok(true);
+            }
+            Err(err) => {
+                warn!(
+                    "destination is still down ({} bytes of WAL retained): {}",
+                    retained_wal_bytes, err
+                );
+            }
+        }
+    }
+}
+
 async fn handle_replication_message<T>(
     state: &mut ApplyLoopState,
     events_stream: Pin<&mut EventsStream>,
@@ -546,7 +919,8 @@ where
 {
     // We perform the conversion of the message to our own event format which is used downstream
     // by the destination.
-    let event = convert_message_to_event(schema_cache, &message).await?;
+    let event =
+        convert_message_to_event(schema_cache, &message, state.preserve_unsupported_types).await?;
 
     let event_type = EventType::from(&event);
     debug!("message converted to event type {}", event_type);
@@ -597,7 +971,7 @@ async fn handle_begin_message(
         event: Some(Event::Begin(event)),
         end_lsn: None,
         end_batch: None,
-        skip_table: None,
+        table_schema_change: None,
     })
 }
 
@@ -705,18 +1079,39 @@ where
     // If no table schema is found, it means that something went wrong and we throw an error, which is
     // dealt with differently based on the worker type.
     // TODO: explore how to deal with applying relation messages to the schema (creating it if missing).
-    let schema_cache = schema_cache.read_inner().await;
-    let Some(existing_table_schema) = schema_cache.get_table_schema_ref(&message.rel_id()) else {
+    let Some(existing_table_schema) = schema_cache.get_table_schema(&message.rel_id()).await else {
         return Err(ApplyLoopError::MissingTableSchema(message.rel_id()));
     };
 
-    // We compare the table schema from the relation message with the existing schema (if any).
-    // The purpose of this comparison is that we want to throw an error and stop the processing
-    // of any table that incurs in a schema change after the initial table sync is performed.
+    // We compare the table schema from the relation message with the existing schema, since
+    // Postgres sends a relation message before the first change of a table whose columns were
+    // altered, e.g. when a column is added.
     if !existing_table_schema.partial_eq(&event.table_schema) {
+        let table_schema = changed_table_schema(&existing_table_schema, event.table_schema);
+        info!("the schema of table {} changed", message.rel_id());
+
+        // The rows following the relation message have the new columns, so they're converted
+        // with the new schema. If the destination doesn't support the change, the table is
+        // skipped and its rows aren't converted anymore.
+        schema_cache.add_table_schema(table_schema.clone()).await;
+
+        // The schema change is sent with the events preceding it, before it's written to the
+        // destination.
+        if state.emit_metadata_events {
+            return Ok(HandleMessageResult {
+                event: Some(Event::Metadata(MetadataEvent::SchemaChanged {
+                    before: existing_table_schema.clone(),
+                    after: table_schema.clone(),
+                })),
+                end_batch: Some(EndBatch::Inclusive),
+                table_schema_change: Some((existing_table_schema, table_schema)),
+                ..Default::default()
+            });
+        }
+
         return Ok(HandleMessageResult {
             end_batch: Some(EndBatch::Exclusive),
-            skip_table: Some(message.rel_id()),
+            table_schema_change: Some((existing_table_schema, table_schema)),
             ..Default::default()
         });
     }
@@ -725,10 +1120,33 @@ where
         event: Some(Event::Relation(event)),
         end_lsn: None,
         end_batch: None,
-        skip_table: None,
+        table_schema_change: None,
     })
 }
 
+/// Returns the schema of a table after a change of its columns, as read from a relation message.
+///
+/// Relation messages only have the oids of the types of the columns, and no nullability, so the
+/// columns which kept their type keep what was read from the catalog about them, e.g. the fields of
+/// composite types.
+fn changed_table_schema(
+    existing_table_schema: &TableSchema,
+    mut table_schema: TableSchema,
+) -> TableSchema {
+    for column_schema in table_schema.column_schemas.iter_mut() {
+        if let Some(existing_column_schema) = existing_table_schema
+            .column_schemas
+            .iter()
+            .find(|c| c.name == column_schema.name && c.typ.oid() == column_schema.typ.oid())
+        {
+            column_schema.typ = existing_column_schema.typ.clone();
+            column_schema.nullable = existing_column_schema.nullable;
+        }
+    }
+
+    table_schema
+}
+
 async fn handle_insert_message<T>(
     state: &mut ApplyLoopState,
     event: Event,
@@ -763,7 +1181,7 @@ where
         event: Some(Event::Insert(event)),
         end_lsn: None,
         end_batch: None,
-        skip_table: None,
+        table_schema_change: None,
     })
 }
 
@@ -801,7 +1219,7 @@ where
         event: Some(Event::Update(event)),
         end_lsn: None,
         end_batch: None,
-        skip_table: None,
+        table_schema_change: None,
     })
 }
 
@@ -839,7 +1257,7 @@ where
         event: Some(Event::Delete(event)),
         end_lsn: None,
         end_batch: None,
-        skip_table: None,
+        table_schema_change: None,
     })
 }
 
@@ -875,12 +1293,17 @@ where
             rel_ids.push(table_id)
         }
     }
+    // Like their other changes, a truncate of tables whose changes are all skipped isn't sent to
+    // the destination.
+    if rel_ids.is_empty() {
+        return Ok(HandleMessageResult::default());
+    }
     event.rel_ids = rel_ids;
 
     Ok(HandleMessageResult {
         event: Some(Event::Truncate(event)),
         end_lsn: None,
         end_batch: None,
-        skip_table: None,
+        table_schema_change: None,
     })
 }
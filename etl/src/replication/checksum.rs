@@ -0,0 +1,259 @@
+use md5::{Digest, Md5};
+use postgres::schema::ColumnSchema;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use tokio_postgres::types::Type;
+
+use crate::conversions::Cell;
+use crate::conversions::table_row::TableRow;
+
+/// Checksum of the rows of a table whose primary key is in a range of values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RangeChecksum {
+    /// Index of the range, i.e. the primary key values of its rows divided by the range size,
+    /// rounded down.
+    pub range_index: i64,
+    /// Number of rows in the range.
+    pub row_count: u64,
+    /// Sum, wrapping around on overflow, of the first 8 bytes of the MD5 hash of each row as
+    /// produced by `COPY` in the text format, read as a big endian integer.
+    ///
+    /// The sum doesn't depend on the order of the rows, so it can be computed by Postgres and
+    /// while streaming the rows of a `COPY`.
+    pub checksum: u64,
+}
+
+/// A range of primary key values, from `start` included to `end` excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl fmt::Display for KeyRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+/// Formats `key_ranges` as a comma separated list.
+pub fn display_key_ranges(key_ranges: &[KeyRange]) -> String {
+    key_ranges
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Checksums of the rows copied from a table, split by ranges of primary key values.
+#[derive(Debug, Clone)]
+pub struct RangeChecksums {
+    key_column: String,
+    key_index: usize,
+    range_size: i64,
+    ranges: BTreeMap<i64, RangeChecksum>,
+}
+
+impl RangeChecksums {
+    /// Creates empty checksums for the rows of a table with `column_schemas`, split by ranges of
+    /// `range_size` primary key values.
+    ///
+    /// Returns `None` if the table doesn't have a single integer primary key column.
+    pub fn for_table(column_schemas: &[ColumnSchema], range_size: u64) -> Option<Self> {
+        let mut primary_columns = column_schemas
+            .iter()
+            .enumerate()
+            .filter(|(_, column_schema)| column_schema.primary);
+        let (key_index, key_column) = primary_columns.next()?;
+        if primary_columns.next().is_some()
+            || !matches!(key_column.typ, Type::INT2 | Type::INT4 | Type::INT8)
+        {
+            return None;
+        }
+
+        Some(Self {
+            key_column: key_column.name.clone(),
+            key_index,
+            range_size: i64::try_from(range_size).unwrap_or(i64::MAX),
+            ranges: BTreeMap::new(),
+        })
+    }
+
+    /// Returns the name of the primary key column the ranges are made of.
+    pub fn key_column(&self) -> &str {
+        &self.key_column
+    }
+
+    /// Returns the number of consecutive primary key values covered by each range.
+    pub fn range_size(&self) -> i64 {
+        self.range_size
+    }
+
+    /// Adds a row, as produced by `COPY` in the text format, to the checksum of its range.
+    ///
+    /// `table_row` is the conversion of the row, from which the primary key is read.
+    pub fn add_row(&mut self, row: &[u8], table_row: &TableRow) {
+        let key = match table_row.values.get(self.key_index) {
+            Some(Cell::I16(key)) => i64::from(*key),
+            Some(Cell::I32(key)) => i64::from(*key),
+            Some(Cell::I64(key)) => *key,
+            // A primary key is never `NULL`, but if the key can't be read, the row is left out and
+            // its range is reported as mismatched.
+            _ => return,
+        };
+
+        let row = row.strip_suffix(b"\n").unwrap_or(row);
+        let range_index = key.div_euclid(self.range_size);
+        let range = self.ranges.entry(range_index).or_insert(RangeChecksum {
+            range_index,
+            ..Default::default()
+        });
+        range.row_count += 1;
+        range.checksum = range.checksum.wrapping_add(row_hash(row));
+    }
+
+    /// Returns the key ranges whose checksums differ from the `source` ones, including the ranges
+    /// which have rows on a single side.
+    pub fn mismatched_ranges(&self, source: &[RangeChecksum]) -> Vec<KeyRange> {
+        let source = source
+            .iter()
+            .map(|range| (range.range_index, range))
+            .collect::<BTreeMap<_, _>>();
+        let range_indexes = self
+            .ranges
+            .keys()
+            .chain(source.keys())
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        range_indexes
+            .into_iter()
+            .filter(|range_index| self.ranges.get(range_index) != source.get(range_index).copied())
+            .map(|range_index| self.key_range(range_index))
+            .collect()
+    }
+
+    fn key_range(&self, range_index: i64) -> KeyRange {
+        let start = range_index.saturating_mul(self.range_size);
+
+        KeyRange {
+            start,
+            end: start.saturating_add(self.range_size),
+        }
+    }
+}
+
+/// Returns the first 8 bytes of the MD5 hash of `row`, read as a big endian integer, which is
+/// what `('x' || substr(md5(row), 1, 16))::bit(64)::bigint` computes in Postgres.
+fn row_hash(row: &[u8]) -> u64 {
+    let digest = Md5::digest(row);
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&digest[..8]);
+
+    u64::from_be_bytes(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column_schemas() -> Vec<ColumnSchema> {
+        vec![
+            ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+            ColumnSchema::new("name".to_string(), Type::TEXT, -1, true, false),
+        ]
+    }
+
+    /// Returns the checksums of the rows with the keys `keys`, as produced by `COPY`.
+    fn checksums(keys: impl IntoIterator<Item = i64>) -> RangeChecksums {
+        let mut checksums = RangeChecksums::for_table(&column_schemas(), 10).unwrap();
+        for key in keys {
+            add_row(&mut checksums, key, &format!("name_{key}"));
+        }
+
+        checksums
+    }
+
+    fn add_row(checksums: &mut RangeChecksums, key: i64, name: &str) {
+        let row = format!("{key}\t{name}\n");
+        let table_row = TableRow::new(vec![Cell::I64(key), Cell::String(name.to_string())]);
+        checksums.add_row(row.as_bytes(), &table_row);
+    }
+
+    fn source_checksums(checksums: &RangeChecksums) -> Vec<RangeChecksum> {
+        checksums.ranges.values().copied().collect()
+    }
+
+    #[test]
+    fn only_tables_with_a_single_integer_key_are_checked() {
+        let mut column_schemas = column_schemas();
+        assert!(RangeChecksums::for_table(&column_schemas, 10).is_some());
+
+        column_schemas[1].primary = true;
+        assert!(RangeChecksums::for_table(&column_schemas, 10).is_none());
+
+        column_schemas[0].primary = false;
+        assert!(RangeChecksums::for_table(&column_schemas, 10).is_none());
+    }
+
+    #[test]
+    fn rows_are_split_by_key_ranges() {
+        let checksums = checksums(-5..25);
+
+        let ranges = checksums
+            .ranges
+            .values()
+            .map(|range| (range.range_index, range.row_count))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(-1, 5), (0, 10), (1, 10), (2, 5)]);
+    }
+
+    #[test]
+    fn identical_rows_have_no_mismatched_ranges() {
+        let source = source_checksums(&checksums(0..100));
+
+        // The order in which the rows are copied doesn't matter.
+        assert!(
+            checksums((0..100).rev())
+                .mismatched_ranges(&source)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn a_discrepancy_is_localized_to_its_range() {
+        let source = source_checksums(&checksums(0..100));
+
+        // A row with different values.
+        let mut changed = checksums((0..100).filter(|key| *key != 42));
+        add_row(&mut changed, 42, "changed");
+        assert_eq!(
+            changed.mismatched_ranges(&source),
+            vec![KeyRange { start: 40, end: 50 }]
+        );
+
+        // A missing row and a duplicated one.
+        let mut missing = checksums((0..100).filter(|key| *key != 7));
+        add_row(&mut missing, 95, "name_95");
+        assert_eq!(
+            missing.mismatched_ranges(&source),
+            vec![
+                KeyRange { start: 0, end: 10 },
+                KeyRange {
+                    start: 90,
+                    end: 100
+                }
+            ]
+        );
+
+        // A whole range missing on one side.
+        let extra = checksums(0..110);
+        assert_eq!(
+            extra.mismatched_ranges(&source),
+            vec![KeyRange {
+                start: 100,
+                end: 110
+            }]
+        );
+    }
+}
@@ -1,9 +1,10 @@
-use config::shared::{IntoConnectOptions, PgConnectionConfig};
+use config::shared::{
+    CopyConfig, CopyFormat, IntoConnectOptions, PgConnectionConfig, ReplicationSlotConfig,
+};
 use pg_escape::{quote_identifier, quote_literal};
 use postgres::schema::{ColumnSchema, TableId, TableName, TableSchema};
 use postgres::types::convert_type_oid_to_type;
 use postgres_replication::LogicalReplicationStream;
-use rustls::ClientConfig;
 use std::collections::HashMap;
 use std::fmt;
 use std::io::BufReader;
@@ -13,11 +14,59 @@ use tokio_postgres::error::SqlState;
 use tokio_postgres::tls::MakeTlsConnect;
 use tokio_postgres::{
     Client, Config, Connection, CopyOutStream, NoTls, SimpleQueryMessage, SimpleQueryRow, Socket,
-    config::ReplicationMode, types::PgLsn,
+    config::ReplicationMode,
+    types::{Field, Kind, PgLsn, Type},
 };
 use tokio_postgres_rustls::MakeRustlsConnect;
 use tracing::{Instrument, error, info, warn};
 
+use crate::replication::checksum::RangeChecksum;
+use crate::replication::tls;
+
+/// Session parameters set on every replication connection.
+///
+/// These make the text representation of values sent by Postgres (both in COPY and in logical
+/// replication messages) deterministic, independently of the server or role defaults. The text
+/// converters in [`crate::conversions`] rely on them.
+const SESSION_PARAMETERS: &[(&str, &str)] = &[
+    // Intervals are parsed by `PgInterval` which only understands the `iso_8601` style.
+    ("IntervalStyle", "iso_8601"),
+    // `bytea` values are always emitted in the hex format, so they don't depend on the server's
+    // `bytea_output` default.
+    ("bytea_output", "hex"),
+    // Text values are parsed as UTF-8, so the server transcodes them from the database encoding,
+    // e.g. `LATIN1`, whatever the role or database default `client_encoding` is.
+    ("client_encoding", "UTF8"),
+    // Dates and timestamps are parsed in the `ISO` style, e.g. `2024-05-01 12:34:56+00`.
+    ("DateStyle", "ISO"),
+    // `timestamptz` values are printed in UTC whatever the server's or role's `TimeZone`, so
+    // that they are all parsed the same way, including across daylight saving time changes.
+    ("TimeZone", "UTC"),
+    // `money` values are printed like `-$1,234.56` whatever the server's `lc_monetary`, so that
+    // their currency symbol, separators and sign are always the ones stripped when parsing them.
+    ("lc_monetary", "C"),
+];
+
+/// Sets the [`SESSION_PARAMETERS`] as startup options of the connection, together with the
+/// `statement_timeout` if one is provided.
+///
+/// When no `statement_timeout` is provided, the server, database or role default applies.
+fn set_session_parameters(config: &mut Config, statement_timeout_ms: Option<u64>) {
+    let mut options = SESSION_PARAMETERS
+        .iter()
+        .map(|(name, value)| format!("-c {name}={value}"))
+        .collect::<Vec<_>>();
+    if let Some(statement_timeout_ms) = statement_timeout_ms {
+        options.push(format!("-c statement_timeout={statement_timeout_ms}"));
+    }
+
+    config.options(options.join(" "));
+}
+
+/// Output plugin of the slots created by the pipeline, unless another one is configured for the
+/// slot of the apply worker.
+const DEFAULT_OUTPUT_PLUGIN: &str = ReplicationSlotConfig::DEFAULT_PLUGIN;
+
 /// Spawns a background task to monitor a PostgreSQL connection until it terminates.
 ///
 /// The task will log when the connection terminates, either successfully or with an error.
@@ -62,6 +111,18 @@ pub enum PgReplicationError {
     #[error("Replication slot '{0}' already exists in database")]
     SlotAlreadyExists(String),
 
+    #[error(
+        "Replication slot '{slot_name}' uses the output plugin '{actual}' instead of '{expected}'"
+    )]
+    SlotPluginMismatch {
+        slot_name: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Output plugin '{0}' is not installed on the source database")]
+    OutputPluginNotInstalled(String),
+
     #[error("Invalid replication slot response: missing required fields in server response")]
     SlotResponseInvalid,
 
@@ -93,6 +154,25 @@ pub enum PgReplicationError {
     Io(#[from] std::io::Error),
 }
 
+/// The current value of a sequence owned by a column of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedSequenceValue {
+    pub table_id: TableId,
+    pub column_name: String,
+    pub sequence_name: TableName,
+    pub last_value: i64,
+}
+
+/// A table included in a publication created by [`PgReplicationClient::create_publication`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicationTable {
+    pub name: TableName,
+    /// Names of the published columns, or `None` to publish all of them.
+    pub column_names: Option<Vec<String>>,
+    /// Expression selecting the published rows, or `None` to publish all of them.
+    pub row_filter: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateSlotResult {
     pub consistent_point: PgLsn,
@@ -101,6 +181,10 @@ pub struct CreateSlotResult {
 #[derive(Debug, Clone)]
 pub struct GetSlotResult {
     pub confirmed_flush_lsn: PgLsn,
+    /// The oldest LSN whose WAL is still retained for the slot.
+    pub restart_lsn: PgLsn,
+    /// The logical decoding output plugin of the slot.
+    pub plugin: String,
 }
 
 #[derive(Debug, Clone)]
@@ -167,16 +251,62 @@ impl PgReplicationSlotTransaction {
         self.client.get_table_schema(table_id, publication).await
     }
 
+    /// Retrieves the row filter of the supplied table in the publication `publication`, if any.
+    pub async fn get_row_filter(
+        &self,
+        table_id: TableId,
+        publication: &str,
+    ) -> PgReplicationResult<Option<String>> {
+        self.client.get_row_filter(table_id, publication).await
+    }
+
+    /// Retrieves the estimated number of rows of the supplied table, if any.
+    pub async fn get_estimated_row_count(
+        &self,
+        table_id: TableId,
+    ) -> PgReplicationResult<Option<u64>> {
+        self.client.get_estimated_row_count(table_id).await
+    }
+
     /// Creates a COPY stream for reading data from the specified table.
     ///
-    /// The stream will include only the columns specified in `column_schemas`.
+    /// The stream will include only the columns specified in `column_schemas` of the rows matching
+    /// `row_filter`, copied with the options of `copy_config`.
     pub async fn get_table_copy_stream(
         &self,
         table_id: TableId,
         column_schemas: &[ColumnSchema],
+        row_filter: Option<&str>,
+        copy_config: &CopyConfig,
     ) -> PgReplicationResult<CopyOutStream> {
         self.client
-            .get_table_copy_stream(table_id, column_schemas)
+            .get_table_copy_stream(table_id, column_schemas, row_filter, copy_config)
+            .await
+    }
+
+    /// Computes the checksums of the rows of the specified table, split by ranges of
+    /// `range_size` values of the `key_column` integer column.
+    ///
+    /// The rows are checksummed as they would be produced by a COPY stream created with the same
+    /// `column_schemas`, `row_filter` and `copy_config`.
+    pub async fn get_table_range_checksums(
+        &self,
+        table_id: TableId,
+        column_schemas: &[ColumnSchema],
+        row_filter: Option<&str>,
+        key_column: &str,
+        range_size: i64,
+        copy_config: &CopyConfig,
+    ) -> PgReplicationResult<Vec<RangeChecksum>> {
+        self.client
+            .get_table_range_checksums(
+                table_id,
+                column_schemas,
+                row_filter,
+                key_column,
+                range_size,
+                copy_config,
+            )
             .await
     }
 
@@ -209,18 +339,46 @@ impl PgReplicationClient {
     ///
     /// The connection is configured for logical replication mode
     pub async fn connect(pg_connection_config: PgConnectionConfig) -> PgReplicationResult<Self> {
+        Self::connect_internal(pg_connection_config, None).await
+    }
+
+    /// Establishes a connection to PostgreSQL like [`PgReplicationClient::connect`], overriding
+    /// the `statement_timeout` of the session.
+    ///
+    /// A `statement_timeout_ms` of `0` disables the timeout, which is needed by sessions copying
+    /// large tables, since they must not be killed by a timeout meant for catalog queries.
+    pub async fn connect_with_statement_timeout(
+        pg_connection_config: PgConnectionConfig,
+        statement_timeout_ms: u64,
+    ) -> PgReplicationResult<Self> {
+        Self::connect_internal(pg_connection_config, Some(statement_timeout_ms)).await
+    }
+
+    async fn connect_internal(
+        pg_connection_config: PgConnectionConfig,
+        statement_timeout_ms: Option<u64>,
+    ) -> PgReplicationResult<Self> {
         match pg_connection_config.tls.enabled {
-            true => PgReplicationClient::connect_tls(pg_connection_config).await,
-            false => PgReplicationClient::connect_no_tls(pg_connection_config).await,
+            true => {
+                PgReplicationClient::connect_tls(pg_connection_config, statement_timeout_ms).await
+            }
+            false => {
+                PgReplicationClient::connect_no_tls(pg_connection_config, statement_timeout_ms)
+                    .await
+            }
         }
     }
 
     /// Establishes a connection to PostgreSQL without TLS encryption.
     ///
     /// The connection is configured for logical replication mode.
-    async fn connect_no_tls(pg_connection_config: PgConnectionConfig) -> PgReplicationResult<Self> {
+    async fn connect_no_tls(
+        pg_connection_config: PgConnectionConfig,
+        statement_timeout_ms: Option<u64>,
+    ) -> PgReplicationResult<Self> {
         let mut config: Config = pg_connection_config.clone().with_db();
         config.replication_mode(ReplicationMode::Logical);
+        set_session_parameters(&mut config, statement_timeout_ms);
 
         let (client, connection) = config.connect(NoTls).await?;
         spawn_postgres_connection::<NoTls>(connection);
@@ -235,9 +393,13 @@ impl PgReplicationClient {
     /// Establishes a TLS-encrypted connection to PostgreSQL.
     ///
     /// The connection is configured for logical replication mode
-    async fn connect_tls(pg_connection_config: PgConnectionConfig) -> PgReplicationResult<Self> {
+    async fn connect_tls(
+        pg_connection_config: PgConnectionConfig,
+        statement_timeout_ms: Option<u64>,
+    ) -> PgReplicationResult<Self> {
         let mut config: Config = pg_connection_config.clone().with_db();
         config.replication_mode(ReplicationMode::Logical);
+        set_session_parameters(&mut config, statement_timeout_ms);
 
         let mut root_store = rustls::RootCertStore::empty();
         if pg_connection_config.tls.enabled {
@@ -249,9 +411,7 @@ impl PgReplicationClient {
             }
         };
 
-        let tls_config = ClientConfig::builder()
-            .with_root_certificates(root_store)
-            .with_no_client_auth();
+        let tls_config = tls::client_config(root_store, pg_connection_config.tls.verification)?;
 
         let (client, connection) = config.connect(MakeRustlsConnect::new(tls_config)).await?;
         spawn_postgres_connection::<MakeRustlsConnect>(connection);
@@ -272,14 +432,17 @@ impl PgReplicationClient {
         // TODO: check if we want to consume the client and return it on commit to avoid any other
         //  operations on a connection that has started a transaction.
         let transaction = PgReplicationSlotTransaction::new(self.clone()).await?;
-        let slot = self.create_slot_internal(slot_name, true).await?;
+        let slot = self
+            .create_slot_internal(slot_name, DEFAULT_OUTPUT_PLUGIN, true)
+            .await?;
 
         Ok((transaction, slot))
     }
 
     /// Creates a new logical replication slot with the specified name and no snapshot.
     pub async fn create_slot(&self, slot_name: &str) -> PgReplicationResult<CreateSlotResult> {
-        self.create_slot_internal(slot_name, false).await
+        self.create_slot_internal(slot_name, DEFAULT_OUTPUT_PLUGIN, false)
+            .await
     }
 
     /// Gets the slot by `slot_name`.
@@ -287,7 +450,7 @@ impl PgReplicationClient {
     /// Returns an error in case of failure or missing slot.
     pub async fn get_slot(&self, slot_name: &str) -> PgReplicationResult<GetSlotResult> {
         let query = format!(
-            r#"select confirmed_flush_lsn from pg_replication_slots where slot_name = {};"#,
+            r#"select confirmed_flush_lsn, restart_lsn, plugin from pg_replication_slots where slot_name = {};"#,
             quote_literal(slot_name)
         );
 
@@ -300,8 +463,15 @@ impl PgReplicationClient {
                     "pg_replication_slots",
                 )
                 .await?;
+                let restart_lsn =
+                    Self::get_row_value::<PgLsn>(&row, "restart_lsn", "pg_replication_slots")
+                        .await?;
+                let plugin =
+                    Self::get_row_value::<String>(&row, "plugin", "pg_replication_slots").await?;
                 let slot = GetSlotResult {
                     confirmed_flush_lsn,
+                    restart_lsn,
+                    plugin,
                 };
 
                 return Ok(slot);
@@ -311,10 +481,30 @@ impl PgReplicationClient {
         Err(PgReplicationError::SlotNotFound(slot_name.to_string()))
     }
 
-    /// Gets an existing replication slot or creates a new one if it doesn't exist.
+    /// Gets the current WAL write position of the database.
+    pub async fn get_current_wal_lsn(&self) -> PgReplicationResult<PgLsn> {
+        let query = "select pg_current_wal_lsn() as current_wal_lsn;";
+
+        let results = self.client.simple_query(query).await?;
+        for result in results {
+            if let SimpleQueryMessage::Row(row) = result {
+                return Self::get_row_value::<PgLsn>(&row, "current_wal_lsn", "pg_current_wal_lsn")
+                    .await;
+            }
+        }
+
+        Err(PgReplicationError::ColumnNotFound(
+            "current_wal_lsn".to_string(),
+            "pg_current_wal_lsn".to_string(),
+        ))
+    }
+
+    /// Gets an existing replication slot or creates a new one with the output plugin `plugin` if
+    /// it doesn't exist.
     ///
     /// This method first attempts to get the slot by name. If the slot doesn't exist,
-    /// it creates a new one.
+    /// it creates a new one. An existing slot must use `plugin`, otherwise
+    /// [`PgReplicationError::SlotPluginMismatch`] is returned.
     ///
     /// Returns a tuple containing:
     /// - A boolean indicating whether the slot was created (true) or already existed (false)
@@ -323,17 +513,29 @@ impl PgReplicationClient {
     pub async fn get_or_create_slot(
         &self,
         slot_name: &str,
+        plugin: &str,
     ) -> PgReplicationResult<GetOrCreateSlotResult> {
         match self.get_slot(slot_name).await {
             Ok(slot) => {
+                if slot.plugin != plugin {
+                    return Err(PgReplicationError::SlotPluginMismatch {
+                        slot_name: slot_name.to_string(),
+                        expected: plugin.to_string(),
+                        actual: slot.plugin,
+                    });
+                }
+
                 info!("using existing replication slot '{}'", slot_name);
 
                 Ok(GetOrCreateSlotResult::GetSlot(slot))
             }
             Err(PgReplicationError::SlotNotFound(_)) => {
-                info!("creating new replication slot '{}'", slot_name);
+                info!(
+                    "creating new replication slot '{}' with output plugin '{}'",
+                    slot_name, plugin
+                );
 
-                let create_result = self.create_slot_internal(slot_name, false).await?;
+                let create_result = self.create_slot_internal(slot_name, plugin, false).await?;
 
                 Ok(GetOrCreateSlotResult::CreateSlot(create_result))
             }
@@ -389,6 +591,151 @@ impl PgReplicationClient {
         Ok(false)
     }
 
+    /// Checks whether the role of the connection can create a publication for `table_names`,
+    /// which requires the `CREATE` privilege on the database and owning every table.
+    ///
+    /// Tables that don't exist are ignored, so that creating the publication reports them.
+    pub async fn can_create_publication(
+        &self,
+        table_names: &[TableName],
+    ) -> PgReplicationResult<bool> {
+        let table_oids = table_names
+            .iter()
+            .map(|table_name| {
+                format!(
+                    "to_regclass({})",
+                    quote_literal(&table_name.as_quoted_identifier())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let can_create_publication_query = format!(
+            "select 1 as can_create
+            where has_database_privilege(current_database(), 'CREATE')
+            and not exists (
+                select 1 from pg_class c
+                where c.oid = any(array[{table_oids}]::oid[])
+                and not pg_has_role(c.relowner, 'USAGE')
+            );"
+        );
+        for msg in self
+            .client
+            .simple_query(&can_create_publication_query)
+            .await?
+        {
+            if let SimpleQueryMessage::Row(_) = msg {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Creates a publication for `tables`, publishing the `publish` operations.
+    ///
+    /// Tables with column names are published with a column list, so that only these columns are
+    /// copied and streamed, and tables with a row filter only publish the rows matching it.
+    pub async fn create_publication(
+        &self,
+        publication_name: &str,
+        tables: &[PublicationTable],
+        publish: &[&str],
+        publish_via_partition_root: bool,
+    ) -> PgReplicationResult<()> {
+        info!("creating publication '{}'", publication_name);
+
+        let tables = tables
+            .iter()
+            .map(|table| {
+                let mut table_query = table.name.as_quoted_identifier();
+                if let Some(column_names) = &table.column_names {
+                    let column_names = column_names
+                        .iter()
+                        .map(|column_name| quote_identifier(column_name))
+                        .collect::<Vec<_>>();
+                    table_query.push_str(&format!(" ({})", column_names.join(", ")));
+                }
+                if let Some(row_filter) = &table.row_filter {
+                    table_query.push_str(&format!(" where ({row_filter})"));
+                }
+
+                table_query
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let create_publication_query = format!(
+            "create publication {} for table {tables} with (publish = {}, publish_via_partition_root = {publish_via_partition_root});",
+            quote_identifier(publication_name),
+            quote_literal(&publish.join(", ")),
+        );
+        self.client.simple_query(&create_publication_query).await?;
+
+        Ok(())
+    }
+
+    /// Adds the table `table_name` to the publication `publication_name`.
+    pub async fn add_table_to_publication(
+        &self,
+        publication_name: &str,
+        table_name: &TableName,
+    ) -> PgReplicationResult<()> {
+        info!(
+            "adding table {} to publication '{}'",
+            table_name, publication_name
+        );
+
+        let add_table_query = format!(
+            "alter publication {} add table {};",
+            quote_identifier(publication_name),
+            table_name.as_quoted_identifier()
+        );
+        self.client.simple_query(&add_table_query).await?;
+
+        Ok(())
+    }
+
+    /// Creates the heartbeat table `table_name` if it doesn't exist and returns its OID.
+    ///
+    /// The table holds one row per pipeline, with the time of its last heartbeat.
+    pub async fn create_heartbeat_table(
+        &self,
+        table_name: &TableName,
+    ) -> PgReplicationResult<TableId> {
+        let table_name = table_name.as_quoted_identifier();
+        let create_table_query = format!(
+            "create table if not exists {table_name} (pipeline_id bigint primary key, last_heartbeat_at timestamptz not null);
+            select {}::regclass::oid as table_id;",
+            quote_literal(&table_name)
+        );
+
+        for msg in self.client.simple_query(&create_table_query).await? {
+            if let SimpleQueryMessage::Row(row) = msg {
+                return Self::get_row_value::<TableId>(&row, "table_id", "pg_class").await;
+            }
+        }
+
+        Err(PgReplicationError::ColumnNotFound(
+            "table_id".to_string(),
+            "pg_class".to_string(),
+        ))
+    }
+
+    /// Writes a heartbeat of the pipeline `pipeline_id` to the heartbeat table `table_name`.
+    pub async fn write_heartbeat(
+        &self,
+        table_name: &TableName,
+        pipeline_id: u64,
+    ) -> PgReplicationResult<()> {
+        let write_heartbeat_query = format!(
+            "insert into {} (pipeline_id, last_heartbeat_at) values ({}, now())
+            on conflict (pipeline_id) do update set last_heartbeat_at = excluded.last_heartbeat_at;",
+            table_name.as_quoted_identifier(),
+            pipeline_id as i64
+        );
+        self.client.simple_query(&write_heartbeat_query).await?;
+
+        Ok(())
+    }
+
     /// Retrieves the names of all tables included in a publication.
     pub async fn get_publication_table_names(
         &self,
@@ -441,6 +788,71 @@ impl PgReplicationClient {
         Ok(table_oids)
     }
 
+    /// Retrieves the current values of the sequences owned by a column of the tables `table_ids`.
+    ///
+    /// A sequence is owned by a column when it was created for a `serial` or identity column, or
+    /// with `owned by`, so sequences not tied to one of the tables are never returned. Sequences
+    /// which were never used, or which the role can't read, are not returned either.
+    pub async fn get_owned_sequence_values(
+        &self,
+        table_ids: &[TableId],
+    ) -> PgReplicationResult<Vec<OwnedSequenceValue>> {
+        if table_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let table_ids = table_ids
+            .iter()
+            .map(|table_id| table_id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        // Dependencies of type 'a' link the sequences of serial columns, or set with `owned by`,
+        // to their column, while dependencies of type 'i' link the sequences of identity columns.
+        let sequences_query = format!(
+            "select d.refobjid as table_id,
+                a.attname as column_name,
+                ps.schemaname as sequence_schema,
+                ps.sequencename as sequence_name,
+                ps.last_value
+            from pg_depend d
+            join pg_class s on s.oid = d.objid and s.relkind = 'S'
+            join pg_namespace n on n.oid = s.relnamespace
+            join pg_sequences ps on ps.schemaname = n.nspname and ps.sequencename = s.relname
+            join pg_attribute a on a.attrelid = d.refobjid and a.attnum = d.refobjsubid
+            where d.classid = 'pg_class'::regclass
+            and d.refclassid = 'pg_class'::regclass
+            and d.deptype in ('a', 'i')
+            and d.refobjid in ({table_ids})
+            and ps.last_value is not null
+            order by d.refobjid, a.attnum;"
+        );
+
+        let mut sequence_values = vec![];
+        for msg in self.client.simple_query(&sequences_query).await? {
+            if let SimpleQueryMessage::Row(row) = msg {
+                let table_id =
+                    Self::get_row_value::<TableId>(&row, "table_id", "pg_depend").await?;
+                let column_name =
+                    Self::get_row_value::<String>(&row, "column_name", "pg_attribute").await?;
+                let schema =
+                    Self::get_row_value::<String>(&row, "sequence_schema", "pg_sequences").await?;
+                let name =
+                    Self::get_row_value::<String>(&row, "sequence_name", "pg_sequences").await?;
+                let last_value =
+                    Self::get_row_value::<i64>(&row, "last_value", "pg_sequences").await?;
+
+                sequence_values.push(OwnedSequenceValue {
+                    table_id,
+                    column_name,
+                    sequence_name: TableName { schema, name },
+                    last_value,
+                });
+            }
+        }
+
+        Ok(sequence_values)
+    }
+
     /// Starts a logical replication stream from the specified publication and slot.
     ///
     /// The stream will begin reading changes from the provided `start_lsn`.
@@ -499,12 +911,13 @@ impl PgReplicationClient {
         Ok(())
     }
 
-    /// Internal helper method to create a replication slot.
+    /// Internal helper method to create a replication slot decoded by the output plugin `plugin`.
     ///
     /// The `use_snapshot` parameter determines whether to use a snapshot for the slot creation.
     async fn create_slot_internal(
         &self,
         slot_name: &str,
+        plugin: &str,
         use_snapshot: bool,
     ) -> PgReplicationResult<CreateSlotResult> {
         // Do not convert the query or the options to lowercase, since the lexer for
@@ -517,8 +930,9 @@ impl PgReplicationClient {
             "NOEXPORT_SNAPSHOT"
         };
         let query = format!(
-            r#"CREATE_REPLICATION_SLOT {} LOGICAL pgoutput {}"#,
+            r#"CREATE_REPLICATION_SLOT {} LOGICAL {} {}"#,
             quote_identifier(slot_name),
+            quote_identifier(plugin),
             snapshot_option
         );
         match self.client.simple_query(&query).await {
@@ -542,6 +956,12 @@ impl PgReplicationClient {
                     if *code == SqlState::DUPLICATE_OBJECT {
                         return Err(PgReplicationError::SlotAlreadyExists(slot_name.to_string()));
                     }
+                    // The shared library of the plugin is loaded when the slot is created.
+                    if *code == SqlState::UNDEFINED_FILE {
+                        return Err(PgReplicationError::OutputPluginNotInstalled(
+                            plugin.to_string(),
+                        ));
+                    }
                 }
 
                 return Err(err.into());
@@ -585,7 +1005,10 @@ impl PgReplicationClient {
     ///
     /// If a publication is specified, only columns included in that publication
     /// will be returned.
-    async fn get_table_schema(
+    ///
+    /// When called outside of a [`PgReplicationSlotTransaction`], the schema is read from the
+    /// latest committed state of the catalog instead of the snapshot of a slot.
+    pub async fn get_table_schema(
         &self,
         table_id: TableId,
         publication: Option<&str>,
@@ -600,6 +1023,97 @@ impl PgReplicationClient {
         })
     }
 
+    /// Retrieves the OID of the table `table_name`.
+    pub async fn get_table_id(&self, table_name: &TableName) -> PgReplicationResult<TableId> {
+        let table_id_query = format!(
+            "select c.oid as table_id from pg_class c where c.oid = to_regclass({});",
+            quote_literal(&table_name.as_quoted_identifier())
+        );
+
+        for message in self.client.simple_query(&table_id_query).await? {
+            if let SimpleQueryMessage::Row(row) = message {
+                return Self::get_row_value::<TableId>(&row, "table_id", "pg_class").await;
+            }
+        }
+
+        Err(PgReplicationError::TableNotFound(table_name.clone()))
+    }
+
+    /// Retrieves the row filter of a table in the publication `publication`, if any.
+    pub async fn get_row_filter(
+        &self,
+        table_id: TableId,
+        publication: &str,
+    ) -> PgReplicationResult<Option<String>> {
+        let row_filter_query = format!(
+            "select pg_get_expr(r.prqual, r.prrelid) as row_filter
+            from pg_publication_rel r
+            join pg_publication p on r.prpubid = p.oid
+            where p.pubname = {}
+            and r.prrelid = {table_id}
+            and r.prqual is not null;",
+            quote_literal(publication)
+        );
+
+        for message in self.client.simple_query(&row_filter_query).await? {
+            if let SimpleQueryMessage::Row(row) = message {
+                let row_filter =
+                    Self::get_row_value::<String>(&row, "row_filter", "pg_publication_rel").await?;
+                return Ok(Some(row_filter));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Retrieves the number of rows of a table estimated by the planner statistics in
+    /// `pg_class.reltuples`.
+    ///
+    /// Returns `None` if the table was never vacuumed nor analyzed, in which case there's no
+    /// estimate. The estimate ignores the row filter of the table, if any.
+    pub async fn get_estimated_row_count(
+        &self,
+        table_id: TableId,
+    ) -> PgReplicationResult<Option<u64>> {
+        let estimated_row_count_query = format!(
+            "select c.reltuples::bigint as estimated_rows from pg_class c where c.oid = {table_id};"
+        );
+
+        for message in self.client.simple_query(&estimated_row_count_query).await? {
+            if let SimpleQueryMessage::Row(row) = message {
+                let estimated_rows =
+                    Self::get_row_value::<i64>(&row, "estimated_rows", "pg_class").await?;
+                // Since Postgres 14, `reltuples` is -1 until the table is vacuumed or analyzed.
+                return Ok(u64::try_from(estimated_rows).ok());
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Checks that `row_filter` is a valid filter of the rows of the table `table_name`, returning
+    /// the error reported by Postgres if it isn't.
+    ///
+    /// The filter is validated against the schema of the table without reading any row.
+    pub async fn check_row_filter(
+        &self,
+        table_name: &TableName,
+        row_filter: &str,
+    ) -> PgReplicationResult<Option<String>> {
+        let check_row_filter_query = format!(
+            "select 1 from {} where ({row_filter}) limit 0;",
+            table_name.as_quoted_identifier()
+        );
+
+        match self.client.simple_query(&check_row_filter_query).await {
+            Ok(_) => Ok(None),
+            Err(err) => match err.as_db_error() {
+                Some(db_error) => Ok(Some(db_error.message().to_string())),
+                None => Err(err.into()),
+            },
+        }
+    }
+
     /// Loads the table name and schema information for a given table OID.
     ///
     /// Returns a `TableName` containing both the schema and table name.
@@ -697,7 +1211,7 @@ impl PgReplicationClient {
                 let primary =
                     Self::get_row_value::<String>(&row, "primary", "pg_index").await? == "t";
 
-                let typ = convert_type_oid_to_type(type_oid);
+                let typ = self.get_type(type_oid).await?;
 
                 column_schemas.push(ColumnSchema {
                     name,
@@ -712,13 +1226,116 @@ impl PgReplicationClient {
         Ok(column_schemas)
     }
 
+    /// Returns the [`Type`] of `type_oid`, with the fields of composite types, and recursively of
+    /// the composite types of their fields, and with the labels of enum types in their sort order,
+    /// so that their values can be parsed.
+    ///
+    /// Other types which aren't built in, such as the `hstore` type of its extension, are returned
+    /// with their name and schema, so that the ones which are supported can be recognized.
+    async fn get_type(&self, type_oid: u32) -> PgReplicationResult<Type> {
+        if let Some(typ) = Type::from_oid(type_oid) {
+            return Ok(typ);
+        }
+
+        let fields_query = format!(
+            "select t.typname, n.nspname, a.attname, a.atttypid
+            from pg_type t
+            join pg_namespace n on n.oid = t.typnamespace
+            join pg_attribute a on a.attrelid = t.typrelid
+            where t.oid = {type_oid}
+            and t.typtype = 'c'
+            and a.attnum > 0::int2
+            and not a.attisdropped
+            order by a.attnum
+            ",
+        );
+
+        let mut name_and_schema = None;
+        let mut fields = vec![];
+
+        for message in self.client.simple_query(&fields_query).await? {
+            if let SimpleQueryMessage::Row(row) = message {
+                if name_and_schema.is_none() {
+                    let name = Self::get_row_value::<String>(&row, "typname", "pg_type").await?;
+                    let schema =
+                        Self::get_row_value::<String>(&row, "nspname", "pg_namespace").await?;
+                    name_and_schema = Some((name, schema));
+                }
+
+                let field_name =
+                    Self::get_row_value::<String>(&row, "attname", "pg_attribute").await?;
+                let field_type_oid =
+                    Self::get_row_value::<u32>(&row, "atttypid", "pg_attribute").await?;
+                let field_type = Box::pin(self.get_type(field_type_oid)).await?;
+
+                fields.push(Field::new(field_name, field_type));
+            }
+        }
+
+        if let Some((name, schema)) = name_and_schema {
+            return Ok(Type::new(name, type_oid, Kind::Composite(fields), schema));
+        }
+
+        // Composite types without fields have no row in `pg_attribute`, and are looked up here
+        // along with the types which aren't composites.
+        let type_query = format!(
+            "select t.typname, n.nspname, t.typtype
+            from pg_type t
+            join pg_namespace n on n.oid = t.typnamespace
+            where t.oid = {type_oid}
+            ",
+        );
+
+        for message in self.client.simple_query(&type_query).await? {
+            if let SimpleQueryMessage::Row(row) = message {
+                let name = Self::get_row_value::<String>(&row, "typname", "pg_type").await?;
+                let schema = Self::get_row_value::<String>(&row, "nspname", "pg_namespace").await?;
+                let kind = match Self::get_row_value::<String>(&row, "typtype", "pg_type")
+                    .await?
+                    .as_str()
+                {
+                    "c" => Kind::Composite(vec![]),
+                    "e" => Kind::Enum(self.get_enum_labels(type_oid).await?),
+                    _ => Kind::Simple,
+                };
+
+                return Ok(Type::new(name, type_oid, kind, schema));
+            }
+        }
+
+        Ok(convert_type_oid_to_type(type_oid))
+    }
+
+    /// Returns the labels of the enum type `type_oid`, in their sort order.
+    async fn get_enum_labels(&self, type_oid: u32) -> PgReplicationResult<Vec<String>> {
+        let labels_query = format!(
+            "select enumlabel
+            from pg_enum
+            where enumtypid = {type_oid}
+            order by enumsortorder
+            ",
+        );
+
+        let mut labels = vec![];
+        for message in self.client.simple_query(&labels_query).await? {
+            if let SimpleQueryMessage::Row(row) = message {
+                labels.push(Self::get_row_value::<String>(&row, "enumlabel", "pg_enum").await?);
+            }
+        }
+
+        Ok(labels)
+    }
+
     /// Creates a COPY stream for reading data from a table using its OID.
     ///
-    /// The stream will include only the specified columns and use text format.
+    /// The stream will include only the specified columns of the rows matching `row_filter` and
+    /// use the options of `copy_config`.
     pub async fn get_table_copy_stream(
         &self,
         table_id: TableId,
         column_schemas: &[ColumnSchema],
+        row_filter: Option<&str>,
+        copy_config: &CopyConfig,
     ) -> PgReplicationResult<CopyOutStream> {
         let column_list = column_schemas
             .iter()
@@ -728,18 +1345,106 @@ impl PgReplicationClient {
 
         let table_name = self.get_table_name(table_id).await?;
 
-        // TODO: allow passing in format binary or text
-        let copy_query = format!(
-            r#"copy {} ({}) to stdout with (format text);"#,
-            table_name.as_quoted_identifier(),
-            column_list
-        );
+        let format = match copy_config.format {
+            CopyFormat::Text => "text",
+            CopyFormat::Csv => "csv",
+            CopyFormat::Binary => "binary",
+        };
+        let mut options = vec![format!("format {format}")];
+        // Postgres rejects the delimiter and null options in the binary format.
+        if copy_config.format != CopyFormat::Binary {
+            options.push(format!(
+                "delimiter {}",
+                quote_literal(&copy_config.delimiter.to_string())
+            ));
+            options.push(format!("null {}", quote_literal(&copy_config.null)));
+        }
+        options.push(format!("encoding {}", quote_literal(&copy_config.encoding)));
+        let options = options.join(", ");
+
+        // Rows can only be filtered by copying the results of a query.
+        let copy_query = match row_filter {
+            Some(row_filter) => format!(
+                r#"copy (select {} from {} where ({row_filter})) to stdout with ({options});"#,
+                column_list,
+                table_name.as_quoted_identifier(),
+            ),
+            None => format!(
+                r#"copy {} ({}) to stdout with ({options});"#,
+                table_name.as_quoted_identifier(),
+                column_list,
+            ),
+        };
 
         let stream = self.client.copy_out_simple(&copy_query).await?;
 
         Ok(stream)
     }
 
+    /// Computes the checksums of the rows of a table using its OID, split by ranges of
+    /// `range_size` values of the `key_column` integer column.
+    ///
+    /// Each row is hashed as the line produced by `COPY` in the text format with the options of
+    /// `copy_config`, transcoded to its encoding, so that the checksums can be compared with the ones computed while
+    /// streaming a COPY of the table, see
+    /// [`RangeChecksums`](crate::replication::checksum::RangeChecksums). Only the rows matching
+    /// `row_filter` are checksummed.
+    pub async fn get_table_range_checksums(
+        &self,
+        table_id: TableId,
+        column_schemas: &[ColumnSchema],
+        row_filter: Option<&str>,
+        key_column: &str,
+        range_size: i64,
+        copy_config: &CopyConfig,
+    ) -> PgReplicationResult<Vec<RangeChecksum>> {
+        let table_name = self.get_table_name(table_id).await?;
+        let row_filter = row_filter
+            .map(|row_filter| format!("where ({row_filter})"))
+            .unwrap_or_default();
+
+        let row_text = column_schemas
+            .iter()
+            .map(|col| copy_text_column(&quote_identifier(&col.name), copy_config))
+            .collect::<Vec<_>>()
+            .join(&format!(
+                " || {} || ",
+                quote_literal(&copy_config.delimiter.to_string())
+            ));
+        let checksums_query = format!(
+            "select floor({key_column}::numeric / {range_size})::bigint as range_index,
+                count(*) as row_count,
+                sum(('x' || substr(md5(convert_to({row_text}, {encoding})), 1, 16))::bit(64)::bigint) as checksum
+            from {}
+            {row_filter}
+            group by 1;",
+            table_name.as_quoted_identifier(),
+            encoding = quote_literal(&copy_config.encoding),
+            key_column = quote_identifier(key_column),
+        );
+
+        let table_name = table_name.to_string();
+        let mut checksums = vec![];
+        for msg in self.client.simple_query(&checksums_query).await? {
+            if let SimpleQueryMessage::Row(row) = msg {
+                let range_index =
+                    Self::get_row_value::<i64>(&row, "range_index", &table_name).await?;
+                let row_count = Self::get_row_value::<u64>(&row, "row_count", &table_name).await?;
+                // The sum of the signed hashes is exact, so we wrap it around like the sum of the
+                // hashes of the copied rows.
+                let checksum = Self::get_row_value::<i128>(&row, "checksum", &table_name).await?;
+
+                checksums.push(RangeChecksum {
+                    range_index,
+                    row_count,
+                    checksum: checksum.rem_euclid(1 << 64) as u64,
+                });
+            }
+        }
+
+        Ok(checksums)
+    }
+
     /// Helper function to extract a value from a SimpleQueryMessage::Row
     ///
     /// Returns an error if the column is not found or if the value cannot be parsed to the target type.
@@ -767,3 +1472,51 @@ impl PgReplicationClient {
         })
     }
 }
+
+/// Returns an SQL expression producing the value of `column` as written by `COPY` in the text
+/// format with the options of `copy_config`.
+///
+/// Mirrors the escaping of `COPY`: backslashes, the delimiter and the control characters with a
+/// short escape sequence are escaped, and `NULL`s are replaced by the `NULL` string. `format`
+/// produces the value with the output function of its type, as `COPY` does.
+fn copy_text_column(column: &str, copy_config: &CopyConfig) -> String {
+    const CONTROL_ESCAPES: [(u8, &str); 6] = [
+        (8, "\\b"),
+        (12, "\\f"),
+        (10, "\\n"),
+        (13, "\\r"),
+        (9, "\\t"),
+        (11, "\\v"),
+    ];
+
+    let mut value = format!(
+        "replace(format('%s', {column}), {}, {})",
+        quote_literal("\\"),
+        quote_literal("\\\\")
+    );
+
+    // Delimiters which are control characters with a short escape sequence are escaped below.
+    let delimiter = copy_config.delimiter;
+    if !CONTROL_ESCAPES
+        .iter()
+        .any(|(control, _)| delimiter == *control as char)
+    {
+        value = format!(
+            "replace({value}, {}, {})",
+            quote_literal(&delimiter.to_string()),
+            quote_literal(&format!("\\{delimiter}"))
+        );
+    }
+
+    for (control, escape) in CONTROL_ESCAPES {
+        value = format!(
+            "replace({value}, chr({control}), {})",
+            quote_literal(escape)
+        );
+    }
+
+    format!(
+        "case when {column} is null then {} else {value} end",
+        quote_literal(&copy_config.null)
+    )
+}
@@ -0,0 +1,111 @@
+use config::shared::{DestinationDownConfig, RetryConfig};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Errors returned when the destination stays down for longer than the pipeline tolerates.
+#[derive(Debug, Error)]
+pub enum DestinationDownError {
+    #[error("The destination has been down for {0:?}, which exceeds the maximum pause of {1:?}")]
+    MaxPauseExceeded(Duration, Duration),
+
+    #[error(
+        "The destination is down and {0} bytes of WAL are retained, which exceeds the maximum of {1} bytes"
+    )]
+    MaxRetainedWalExceeded(u64, u64),
+}
+
+/// Returns the delay before the retry following the failed attempt number `attempt`, starting
+/// from `0`.
+///
+/// The delay grows exponentially from [`RetryConfig::initial_delay_ms`] by
+/// [`RetryConfig::backoff_factor`], up to [`RetryConfig::max_delay_ms`].
+pub fn retry_delay(retry: &RetryConfig, attempt: u32) -> Duration {
+    let delay_ms =
+        retry.initial_delay_ms as f64 * (retry.backoff_factor as f64).powi(attempt as i32);
+
+    Duration::from_millis(delay_ms.min(retry.max_delay_ms as f64) as u64)
+}
+
+/// Tracks how long a pipeline has been paused because the destination is down, and fails it once
+/// the pause or the WAL retained for it exceed the configured maximums.
+#[derive(Debug)]
+pub struct PauseBudget {
+    max_pause: Duration,
+    max_retained_wal_bytes: u64,
+    paused_since: Instant,
+}
+
+impl PauseBudget {
+    /// Creates a new [`PauseBudget`] for a pipeline paused at `now`.
+    pub fn new(config: &DestinationDownConfig, now: Instant) -> Self {
+        Self {
+            max_pause: Duration::from_millis(config.max_pause_ms),
+            max_retained_wal_bytes: config.max_retained_wal_bytes,
+            paused_since: now,
+        }
+    }
+
+    /// Checks that the pipeline can stay paused at `now`, while `retained_wal_bytes` of WAL are
+    /// retained for it.
+    pub fn check(&self, now: Instant, retained_wal_bytes: u64) -> Result<(), DestinationDownError> {
+        let paused_for = now.saturating_duration_since(self.paused_since);
+        if paused_for > self.max_pause {
+            return Err(DestinationDownError::MaxPauseExceeded(
+                paused_for,
+                self.max_pause,
+            ));
+        }
+
+        if retained_wal_bytes > self.max_retained_wal_bytes {
+            return Err(DestinationDownError::MaxRetainedWalExceeded(
+                retained_wal_bytes,
+                self.max_retained_wal_bytes,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_grows_exponentially_up_to_the_maximum() {
+        let retry = RetryConfig {
+            max_attempts: 10,
+            initial_delay_ms: 100,
+            max_delay_ms: 1_000,
+            backoff_factor: 2.0,
+        };
+
+        assert_eq!(retry_delay(&retry, 0), Duration::from_millis(100));
+        assert_eq!(retry_delay(&retry, 1), Duration::from_millis(200));
+        assert_eq!(retry_delay(&retry, 3), Duration::from_millis(800));
+        assert_eq!(retry_delay(&retry, 4), Duration::from_millis(1_000));
+        assert_eq!(retry_delay(&retry, 100), Duration::from_millis(1_000));
+    }
+
+    #[test]
+    fn pause_budget_fails_once_a_maximum_is_exceeded() {
+        let config = DestinationDownConfig {
+            pause_on_destination_down: true,
+            max_pause_ms: 1_000,
+            max_retained_wal_bytes: 1024,
+            ..Default::default()
+        };
+        let now = Instant::now();
+        let budget = PauseBudget::new(&config, now);
+
+        assert!(budget.check(now + Duration::from_millis(500), 1024).is_ok());
+        assert!(matches!(
+            budget.check(now + Duration::from_millis(1_500), 0),
+            Err(DestinationDownError::MaxPauseExceeded(_, _))
+        ));
+        assert!(matches!(
+            budget.check(now, 1025),
+            Err(DestinationDownError::MaxRetainedWalExceeded(1025, 1024))
+        ));
+    }
+}
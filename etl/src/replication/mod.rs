@@ -1,6 +1,10 @@
 pub mod apply;
+pub mod checksum;
 pub mod client;
 pub mod common;
+pub mod destination_down;
+pub mod reconnect;
 pub mod slot;
 pub mod stream;
 pub mod table_sync;
+pub mod tls;
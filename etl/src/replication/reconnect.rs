@@ -0,0 +1,85 @@
+use std::time::Duration;
+use tokio_postgres::error::SqlState;
+
+use crate::replication::apply::ApplyLoopError;
+use crate::replication::client::PgReplicationError;
+use crate::replication::stream::EventsStreamError;
+
+/// Returns whether `err` means that the connection to the source was lost, e.g. because the
+/// source restarted, so that the apply loop can be restarted on a new connection.
+pub fn is_connection_lost(err: &ApplyLoopError) -> bool {
+    match err {
+        ApplyLoopError::PgReplication(err) => is_pg_replication_connection_lost(err),
+        ApplyLoopError::LogicalReplicationStreamFailed(EventsStreamError::TableCopyFailed(err)) => {
+            is_client_connection_lost(err)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether `err` means that the connection to the source was lost or couldn't be
+/// established.
+pub fn is_pg_replication_connection_lost(err: &PgReplicationError) -> bool {
+    match err {
+        PgReplicationError::Client(err) => is_client_connection_lost(err),
+        PgReplicationError::Io(_) => true,
+        _ => false,
+    }
+}
+
+fn is_client_connection_lost(err: &tokio_postgres::Error) -> bool {
+    if err.is_closed() {
+        return true;
+    }
+
+    // Connections are terminated with an error of the `57P` class when the server shuts down or
+    // is starting up, and with one of the `08` class on connection failures. The slot stays in
+    // use until the server notices that the previous connection was lost, so streaming can only
+    // be restarted once it's released.
+    if let Some(code) = err.code() {
+        return *code == SqlState::ADMIN_SHUTDOWN
+            || *code == SqlState::CRASH_SHUTDOWN
+            || *code == SqlState::CANNOT_CONNECT_NOW
+            || *code == SqlState::OBJECT_IN_USE
+            || code.code().starts_with("08");
+    }
+
+    std::error::Error::source(err).is_some_and(|source| source.is::<std::io::Error>())
+}
+
+/// Jitters `delay` by scaling it with `random`, a number between `0` and `1`, into a delay between
+/// half of `delay` and `delay`.
+///
+/// Jittering keeps the pipelines which lost their connection to the same source at the same time
+/// from all reconnecting at once.
+pub fn jitter(delay: Duration, random: f64) -> Duration {
+    delay.mul_f64(0.5 + random.clamp(0.0, 1.0) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jittered_delays_are_between_half_the_delay_and_the_delay() {
+        let delay = Duration::from_millis(1_000);
+
+        assert_eq!(jitter(delay, 0.0), Duration::from_millis(500));
+        assert_eq!(jitter(delay, 0.5), Duration::from_millis(750));
+        assert_eq!(jitter(delay, 1.0), delay);
+        assert_eq!(jitter(delay, 2.0), delay);
+    }
+
+    #[test]
+    fn only_connection_errors_are_lost_connections() {
+        let io_error = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(is_connection_lost(&ApplyLoopError::PgReplication(
+            PgReplicationError::Io(io_error)
+        )));
+
+        assert!(!is_connection_lost(&ApplyLoopError::PgReplication(
+            PgReplicationError::SlotNotFound("slot".to_string())
+        )));
+        assert!(!is_connection_lost(&ApplyLoopError::MismatchedTableSchema));
+    }
+}
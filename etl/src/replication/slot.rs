@@ -1,3 +1,5 @@
+use config::shared::ReplicationSlotConfig;
+use postgres::schema::TableId;
 use thiserror::Error;
 
 use crate::pipeline::PipelineId;
@@ -40,6 +42,34 @@ pub fn get_slot_name(
     Ok(slot_name)
 }
 
+/// Returns the name of the replication slot of a worker of the pipeline with id `pipeline_id`.
+///
+/// This is the name of `replication_slot` for the apply worker if it's set, see
+/// [`config::shared::PipelineConfig::replication_slot`], and the name generated by
+/// [`get_slot_name`] otherwise.
+pub fn get_worker_slot_name(
+    pipeline_id: PipelineId,
+    worker_type: WorkerType,
+    replication_slot: Option<&ReplicationSlotConfig>,
+) -> Result<String, SlotError> {
+    match (worker_type, replication_slot) {
+        (WorkerType::Apply, Some(replication_slot)) => Ok(replication_slot.name.clone()),
+        (worker_type, _) => get_slot_name(pipeline_id, worker_type),
+    }
+}
+
+/// Returns whether `slot_name` is the name of a replication slot created by the pipeline with id
+/// `pipeline_id`, either for its apply worker or for one of its table sync workers.
+pub fn is_pipeline_slot(slot_name: &str, pipeline_id: PipelineId) -> bool {
+    if slot_name == format!("{APPLY_WORKER_PREFIX}_{pipeline_id}") {
+        return true;
+    }
+
+    slot_name
+        .strip_prefix(&format!("{TABLE_SYNC_PREFIX}_{pipeline_id}_"))
+        .is_some_and(|table_id| table_id.parse::<TableId>().is_ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,6 +82,46 @@ mod tests {
         assert!(result.len() <= MAX_SLOT_NAME_LENGTH);
     }
 
+    #[test]
+    fn test_pipeline_slots_are_recognized() {
+        let apply = get_slot_name(1, WorkerType::Apply).unwrap();
+        let table_sync = get_slot_name(1, WorkerType::TableSync { table_id: 123 }).unwrap();
+        assert!(is_pipeline_slot(&apply, 1));
+        assert!(is_pipeline_slot(&table_sync, 1));
+
+        // The slots of a pipeline whose id starts with the same digits aren't included.
+        let other_apply = get_slot_name(12, WorkerType::Apply).unwrap();
+        let other_table_sync = get_slot_name(12, WorkerType::TableSync { table_id: 3 }).unwrap();
+        assert!(!is_pipeline_slot(&other_apply, 1));
+        assert!(!is_pipeline_slot(&other_table_sync, 1));
+        assert!(!is_pipeline_slot("unrelated_slot", 1));
+    }
+
+    #[test]
+    fn test_configured_slot_name_is_only_used_by_the_apply_worker() {
+        let replication_slot = ReplicationSlotConfig {
+            name: "external_slot".to_string(),
+            plugin: ReplicationSlotConfig::DEFAULT_PLUGIN.to_string(),
+        };
+
+        let apply = get_worker_slot_name(1, WorkerType::Apply, Some(&replication_slot)).unwrap();
+        assert_eq!(apply, "external_slot");
+
+        let table_sync = get_worker_slot_name(
+            1,
+            WorkerType::TableSync { table_id: 123 },
+            Some(&replication_slot),
+        )
+        .unwrap();
+        assert_eq!(
+            table_sync,
+            get_slot_name(1, WorkerType::TableSync { table_id: 123 }).unwrap()
+        );
+
+        let default = get_worker_slot_name(1, WorkerType::Apply, None).unwrap();
+        assert_eq!(default, get_slot_name(1, WorkerType::Apply).unwrap());
+    }
+
     #[test]
     fn test_table_sync_slot_name() {
         let pipeline_id = 1;
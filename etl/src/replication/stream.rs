@@ -1,4 +1,6 @@
 use crate::conversions::table_row::{TableRow, TableRowConversionError, TableRowConverter};
+use crate::metrics::TableCopyMetrics;
+use crate::replication::checksum::RangeChecksums;
 use futures::{Stream, ready};
 use pin_project_lite::pin_project;
 use postgres::schema::ColumnSchema;
@@ -40,34 +42,71 @@ pin_project! {
         #[pin]
         stream: CopyOutStream,
         column_schemas: &'a [ColumnSchema],
+        converter: &'a TableRowConverter,
+        checksums: Option<&'a mut RangeChecksums>,
+        metrics: Option<TableCopyMetrics>,
     }
 }
 
 impl<'a> TableCopyStream<'a> {
     /// Creates a new [`TableCopyStream`] from a [`CopyOutStream`] and column schemas.
     ///
-    /// The column schemas are used to convert the raw PostgreSQL data into [`TableRow`]s.
-    pub fn wrap(stream: CopyOutStream, column_schemas: &'a [ColumnSchema]) -> Self {
+    /// The column schemas are used by `converter` to convert the raw PostgreSQL data into
+    /// [`TableRow`]s.
+    pub fn wrap(
+        stream: CopyOutStream,
+        column_schemas: &'a [ColumnSchema],
+        converter: &'a TableRowConverter,
+    ) -> Self {
         Self {
             stream,
             column_schemas,
+            converter,
+            checksums: None,
+            metrics: None,
         }
     }
+
+    /// Adds every row yielded by the stream to `checksums`.
+    pub fn with_checksums(mut self, checksums: &'a mut RangeChecksums) -> Self {
+        self.checksums = Some(checksums);
+        self
+    }
+
+    /// Counts every row yielded by the stream, and its size, in `metrics`.
+    pub fn with_metrics(mut self, metrics: TableCopyMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
 }
 
 impl<'a> Stream for TableCopyStream<'a> {
     type Item = Result<TableRow, TableCopyStreamError>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let this = self.project();
-        match ready!(this.stream.poll_next(cx)) {
-            // TODO: allow pluggable table row conversion based on if the data is in text or binary format.
-            Some(Ok(row)) => match TableRowConverter::try_from(&row, this.column_schemas) {
-                Ok(row) => Poll::Ready(Some(Ok(row))),
-                Err(err) => Poll::Ready(Some(Err(err.into()))),
-            },
-            Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
-            None => Poll::Ready(None),
+        let mut this = self.project();
+        loop {
+            let row = match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(row)) => row,
+                Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                None => return Poll::Ready(None),
+            };
+
+            match this.converter.try_from_copy_data(&row, this.column_schemas) {
+                Ok(Some(table_row)) => {
+                    if let Some(checksums) = this.checksums.as_deref_mut() {
+                        checksums.add_row(&row, &table_row);
+                    }
+                    if let Some(metrics) = this.metrics.as_ref() {
+                        metrics.inc_row(row.len());
+                    }
+
+                    return Poll::Ready(Some(Ok(table_row)));
+                }
+                // The binary format ends with a trailer which isn't a row.
+                Ok(None) => continue,
+                Err(err) => return Poll::Ready(Some(Err(err.into()))),
+            }
         }
     }
 }
@@ -1,24 +1,29 @@
-use crate::concurrency::shutdown::{ShutdownResult, ShutdownRx};
-use crate::concurrency::stream::BatchStream;
+use crate::concurrency::shutdown::ShutdownRx;
+use crate::conversions::table_row::{CopyConfigError, TableRowConverter};
 use crate::destination::base::{Destination, DestinationError};
+use crate::metrics::TableCopyMetrics;
 use crate::pipeline::PipelineId;
+use crate::replication::checksum::{KeyRange, RangeChecksums, display_key_ranges};
 use crate::replication::client::{PgReplicationClient, PgReplicationError};
 use crate::replication::slot::{SlotError, get_slot_name};
 use crate::replication::stream::{TableCopyStream, TableCopyStreamError};
 use crate::schema::cache::SchemaCache;
 use crate::state::store::base::{StateStore, StateStoreError};
-use crate::state::table::{TableReplicationPhase, TableReplicationPhaseType};
+use crate::state::table::{TableCopyProgress, TableReplicationPhase, TableReplicationPhaseType};
 use crate::workers::base::WorkerType;
 use crate::workers::table_sync::{TableSyncWorkerState, TableSyncWorkerStateError};
-use config::shared::PipelineConfig;
-use futures::StreamExt;
+use config::shared::{PipelineConfig, ReplicationMode};
+use futures::{StreamExt, future};
 use postgres::schema::TableId;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::pin;
 use tokio_postgres::types::PgLsn;
 use tracing::{error, info, warn};
 
+/// Minimum time between two reports of the progress of a table copy.
+const COPY_PROGRESS_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Error)]
 pub enum TableSyncError {
     #[error("Invalid replication phase '{0}': expected Init, DataSync, or FinishedCopy")]
@@ -41,6 +46,19 @@ pub enum TableSyncError {
 
     #[error("An error happened in the table copy stream")]
     TableCopyStream(#[from] TableCopyStreamError),
+
+    #[error("The COPY options can't be parsed: {0}")]
+    InvalidCopyConfig(#[from] CopyConfigError),
+
+    #[error(
+        "The rows copied from table {table_id} don't match the source for the values of '{key_column}' in {}",
+        display_key_ranges(.key_ranges)
+    )]
+    ChecksumMismatch {
+        table_id: TableId,
+        key_column: String,
+        key_ranges: Vec<KeyRange>,
+    },
 }
 
 #[derive(Debug)]
@@ -64,7 +82,7 @@ pub async fn start_table_sync<S, D>(
 ) -> Result<TableSyncResult, TableSyncError>
 where
     S: StateStore + Clone + Send + 'static,
-    D: Destination + Clone + Send + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
 {
     info!("starting table sync for table {}", table_id);
 
@@ -149,72 +167,235 @@ where
                     .await?;
             }
 
-            // We create the slot with a transaction, since we need to have a consistent snapshot of the database
-            // before copying the schema and tables.
-            //
-            // If a slot already exists at this point, we could delete it and try to recover, but it means
-            // that the state was somehow reset without the slot being deleted, and we want to surface this.
-            let (transaction, slot) = replication_client
-                .create_slot_with_transaction(&slot_name)
-                .await?;
-
-            // We copy the table schema and write it both to the state store and destination.
-            //
-            // Note that we write the schema in both places:
-            // - State store -> we write here because the table schema is used across table copying and cdc
-            //  for correct decoding, thus we rely on our own state store to preserve this information.
-            // - Destination -> we write here because some consumers might want to have the schema of incoming
-            //  data.
-            info!("fetching table schema for table {}", table_id);
-            let table_schema = transaction
-                .get_table_schema(table_id, Some(&config.publication_name))
-                .await?;
-            schema_cache.add_table_schema(table_schema.clone()).await;
-            destination.write_table_schema(table_schema.clone()).await?;
-
-            // We create the copy table stream.
-            let table_copy_stream = transaction
-                .get_table_copy_stream(table_id, &table_schema.column_schemas)
-                .await?;
-            let table_copy_stream =
-                TableCopyStream::wrap(table_copy_stream, &table_schema.column_schemas);
-            let table_copy_stream =
-                BatchStream::wrap(table_copy_stream, config.batch.clone(), shutdown_rx.clone());
-            pin!(table_copy_stream);
-
-            info!("starting table copy stream for table {}", table_id);
-            // We start consuming the table stream. If any error occurs, we will bail the entire copy since
-            // we want to be fully consistent.
-            let mut rows_copied = 0;
-            while let Some(result) = table_copy_stream.next().await {
-                match result {
-                    ShutdownResult::Ok(table_rows) => {
-                        let table_rows = table_rows.into_iter().collect::<Result<Vec<_>, _>>()?;
-                        rows_copied += table_rows.len();
-                        destination.write_table_rows(table_id, table_rows).await?;
+            let consistent_point = if config.skip_initial_snapshot {
+                // When skipping the initial snapshot (only allowed in `stream_only` mode), we create
+                // the slot without exporting a snapshot, so that no transaction is kept open on the
+                // source. The schema is read outside of any snapshot and no data is copied, since the
+                // destination is assumed to have been seeded externally.
+                //
+                // If a slot already exists at this point, we could delete it and try to recover, but it means
+                // that the state was somehow reset without the slot being deleted, and we want to surface this.
+                let slot = replication_client.create_slot(&slot_name).await?;
+
+                info!(
+                    "fetching table schema for table {} without a snapshot",
+                    table_id
+                );
+                let table_schema = replication_client
+                    .get_table_schema(table_id, Some(&config.publication_name))
+                    .await?;
+                schema_cache.add_table_schema(table_schema.clone()).await;
+                destination.write_table_schema(table_schema).await?;
+
+                info!(
+                    "skipped initial snapshot for table {}, streaming from {}",
+                    table_id, slot.consistent_point
+                );
+
+                slot.consistent_point
+            } else {
+                // We create the slot with a transaction, since we need to have a consistent snapshot of the database
+                // before copying the schema and tables.
+                //
+                // If a slot already exists at this point, we could delete it and try to recover, but it means
+                // that the state was somehow reset without the slot being deleted, and we want to surface this.
+                let (transaction, slot) = replication_client
+                    .create_slot_with_transaction(&slot_name)
+                    .await?;
+
+                // We copy the table schema and write it both to the state store and destination.
+                //
+                // Note that we write the schema in both places:
+                // - State store -> we write here because the table schema is used across table copying and cdc
+                //  for correct decoding, thus we rely on our own state store to preserve this information.
+                // - Destination -> we write here because some consumers might want to have the schema of incoming
+                //  data.
+                info!("fetching table schema for table {}", table_id);
+                let table_schema = transaction
+                    .get_table_schema(table_id, Some(&config.publication_name))
+                    .await?;
+                schema_cache.add_table_schema(table_schema.clone()).await;
+                destination.write_table_schema(table_schema.clone()).await?;
+
+                match config.mode {
+                    ReplicationMode::CopyAndStream => {
+                        // We create the copy table stream, which only copies the rows matching the
+                        // row filter of the table in the publication, as they are the only ones
+                        // whose changes are streamed.
+                        let converter = TableRowConverter::new(&config.copy)?
+                            .with_unsupported_types_preserved(config.preserve_unsupported_types);
+                        let row_filter = transaction
+                            .get_row_filter(table_id, &config.publication_name)
+                            .await?;
+                        let estimated_rows = transaction.get_estimated_row_count(table_id).await?;
+                        let table_copy_stream = transaction
+                            .get_table_copy_stream(
+                                table_id,
+                                &table_schema.column_schemas,
+                                row_filter.as_deref(),
+                                &config.copy,
+                            )
+                            .await?;
+                        let mut table_copy_stream = TableCopyStream::wrap(
+                            table_copy_stream,
+                            &table_schema.column_schemas,
+                            &converter,
+                        )
+                        .with_metrics(TableCopyMetrics::for_table(table_id));
+
+                        // If requested, we checksum the rows handed to the destination, to compare
+                        // them with the source once the copy is done.
+                        let mut checksums = config.consistency_check.as_ref().and_then(|check| {
+                            let checksums = RangeChecksums::for_table(
+                                &table_schema.column_schemas,
+                                check.range_size,
+                            );
+                            if checksums.is_none() {
+                                warn!(
+                                    "skipping the consistency check of table {} since it doesn't have a single integer primary key column",
+                                    table_id
+                                );
+                            }
+
+                            checksums
+                        });
+                        if let Some(checksums) = &mut checksums {
+                            table_copy_stream = table_copy_stream.with_checksums(checksums);
+                        }
+
+                        info!("starting table copy stream for table {}", table_id);
+                        // We stream the rows straight into the destination, which is free to batch them as it
+                        // sees fit. The stream ends early if a shutdown is requested or if a row fails to be
+                        // decoded, in which case the destination stops before writing the rows of the failed
+                        // batch and we bail the entire copy since we want to be fully consistent.
+                        let mut copy_shutdown_rx = shutdown_rx.clone();
+                        let mut progress = TableCopyProgress {
+                            rows_copied: 0,
+                            estimated_rows,
+                        };
+                        report_copy_progress(&state_store, table_id, progress).await;
+                        let null_policy = config.null_policy;
+                        let table_rows = table_copy_stream
+                            .take_until(async move {
+                                let _ = copy_shutdown_rx.changed().await;
+                            })
+                            .scan(false, |failed, result| {
+                                if *failed {
+                                    return future::ready(None);
+                                }
+
+                                let table_row = match result {
+                                    Ok(mut table_row) => {
+                                        table_row.apply_null_policy(null_policy);
+                                        Ok(table_row)
+                                    }
+                                    Err(err) => {
+                                        *failed = true;
+                                        Err(TableSyncError::from(err))
+                                    }
+                                };
+
+                                future::ready(Some(table_row))
+                            })
+                            .then({
+                                // The progress is reported at regular intervals rather than for
+                                // each row, since it's written to the state store.
+                                let progress = &mut progress;
+                                let state_store = state_store.clone();
+                                let mut last_progress_report = Instant::now();
+                                move |table_row| {
+                                    if table_row.is_ok() {
+                                        progress.rows_copied += 1;
+                                    }
+                                    let report = (last_progress_report.elapsed()
+                                        >= COPY_PROGRESS_REPORT_INTERVAL)
+                                        .then(|| {
+                                            last_progress_report = Instant::now();
+                                            (state_store.clone(), *progress)
+                                        });
+
+                                    async move {
+                                        if let Some((state_store, progress)) = report {
+                                            info!("copying table {}: {}", table_id, progress);
+                                            report_copy_progress(&state_store, table_id, progress)
+                                                .await;
+                                        }
+
+                                        table_row
+                                    }
+                                }
+                            });
+                        destination
+                            .write_table_rows_stream(table_id, table_rows, config.batch.clone())
+                            .await?;
+
+                        if shutdown_rx.has_changed().unwrap_or(false) {
+                            // If we received a shutdown in the middle of a table copy, we bail knowing
+                            // that the system can automatically recover if a table copy has failed in
+                            // the middle of processing.
+                            info!(
+                                "shutting down table sync worker for table {} during table copy",
+                                table_id
+                            );
+
+                            return Ok(TableSyncResult::SyncStopped);
+                        }
+
+                        info!("completed table copy for table {} ({})", table_id, progress);
+                        report_copy_progress(&state_store, table_id, progress).await;
+
+                        if let Some(checksums) = checksums {
+                            // The source checksums are computed within the same snapshot as the
+                            // copy, so any difference comes from the copied rows.
+                            let source_checksums = transaction
+                                .get_table_range_checksums(
+                                    table_id,
+                                    &table_schema.column_schemas,
+                                    row_filter.as_deref(),
+                                    checksums.key_column(),
+                                    checksums.range_size(),
+                                    &config.copy,
+                                )
+                                .await?;
+                            let key_ranges = checksums.mismatched_ranges(&source_checksums);
+                            if !key_ranges.is_empty() {
+                                error!(
+                                    "the rows copied from table {} don't match the source in {} key ranges of '{}': {}",
+                                    table_id,
+                                    key_ranges.len(),
+                                    checksums.key_column(),
+                                    display_key_ranges(&key_ranges)
+                                );
+
+                                return Err(TableSyncError::ChecksumMismatch {
+                                    table_id,
+                                    key_column: checksums.key_column().to_string(),
+                                    key_ranges,
+                                });
+                            }
+
+                            info!(
+                                "the rows copied from table {} match the source in all {} key ranges",
+                                table_id,
+                                source_checksums.len()
+                            );
+                        }
                     }
-                    ShutdownResult::Shutdown(_) => {
-                        // If we received a shutdown in the middle of a table copy, we bail knowing
-                        // that the system can automatically recover if a table copy has failed in
-                        // the middle of processing.
+                    ReplicationMode::StreamOnly => {
                         info!(
-                            "shutting down table sync worker for table {} during table copy",
+                            "skipping table copy for table {} since the pipeline is stream only",
                             table_id
                         );
-
-                        return Ok(TableSyncResult::SyncStopped);
                     }
                 }
-            }
 
-            // We commit the transaction before starting the apply loop, otherwise it will fail
-            // since no transactions can be running while replication is started.
-            transaction.commit().await?;
+                // We commit the transaction before starting the apply loop, otherwise it will fail
+                // since no transactions can be running while replication is started.
+                transaction.commit().await?;
+
+                slot.consistent_point
+            };
 
-            info!(
-                "completed table copy for table {} ({} rows copied)",
-                table_id, rows_copied
-            );
             // We mark that we finished the copy of the table schema and data.
             {
                 let mut inner = table_sync_worker_state.get_inner().write().await;
@@ -223,7 +404,7 @@ where
                     .await?;
             }
 
-            slot.consistent_point
+            consistent_point
         }
         TableReplicationPhaseType::FinishedCopy => {
             let slot = replication_client.get_slot(&slot_name).await?;
@@ -266,3 +447,22 @@ where
 
     Ok(TableSyncResult::SyncCompleted { start_lsn })
 }
+
+/// Stores the progress of the copy of the table with `table_id`.
+///
+/// The progress is only informative, so failing to store it doesn't fail the copy.
+async fn report_copy_progress<S: StateStore>(
+    state_store: &S,
+    table_id: TableId,
+    progress: TableCopyProgress,
+) {
+    if let Err(err) = state_store
+        .update_table_copy_progress(table_id, progress)
+        .await
+    {
+        warn!(
+            "failed to store the copy progress of table {}: {}",
+            table_id, err
+        );
+    }
+}
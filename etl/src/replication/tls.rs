@@ -0,0 +1,112 @@
+use config::shared::TlsVerification;
+use rustls::client::WebPkiServerVerifier;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, aws_lc_rs, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, Error, RootCertStore, SignatureScheme,
+};
+use std::sync::Arc;
+
+/// Builds the [`ClientConfig`] of a connection whose server certificate is verified as described
+/// by `verification`, against the certificates of `root_store`.
+pub fn client_config(
+    root_store: RootCertStore,
+    verification: TlsVerification,
+) -> Result<ClientConfig, Error> {
+    let verifier = match verification {
+        TlsVerification::VerifyFull => {
+            return Ok(ClientConfig::builder()
+                .with_root_certificates(root_store)
+                .with_no_client_auth());
+        }
+        TlsVerification::VerifyCa => {
+            let provider = Arc::new(aws_lc_rs::default_provider());
+            let inner =
+                WebPkiServerVerifier::builder_with_provider(Arc::new(root_store), provider.clone())
+                    .build()
+                    .map_err(|e| Error::General(e.to_string()))?;
+
+            PartialServerCertVerifier {
+                inner: Some(inner),
+                provider,
+            }
+        }
+        TlsVerification::Require => PartialServerCertVerifier {
+            inner: None,
+            provider: Arc::new(aws_lc_rs::default_provider()),
+        },
+    };
+
+    Ok(ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+/// A [`ServerCertVerifier`] for the `require` and `verify-ca` modes, which don't check that the
+/// certificate of the server is issued for the host connected to.
+///
+/// Without an inner verifier, any certificate is accepted. The handshake signatures are always
+/// verified, so that the server must still own the key of the certificate it presents.
+#[derive(Debug)]
+struct PartialServerCertVerifier {
+    inner: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PartialServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let Some(inner) = &self.inner else {
+            return Ok(ServerCertVerified::assertion());
+        };
+
+        match inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+            Err(Error::InvalidCertificate(CertificateError::NotValidForName)) => {
+                Ok(ServerCertVerified::assertion())
+            }
+            result => result,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
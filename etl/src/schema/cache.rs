@@ -12,6 +12,10 @@ impl Inner {
     pub fn get_table_schema_ref(&self, table_id: &TableId) -> Option<&TableSchema> {
         self.table_schemas.get(table_id)
     }
+
+    pub fn table_schemas(&self) -> impl Iterator<Item = &TableSchema> {
+        self.table_schemas.values()
+    }
 }
 
 // TODO: implement eviction of the entries if they go over a certain threshold.
@@ -1,12 +1,13 @@
 use postgres::schema::TableId;
 use std::{collections::HashMap, future::Future};
 use thiserror::Error;
+use tokio_postgres::types::PgLsn;
 
 use crate::{
     replication::slot::SlotError,
     state::{
         store::postgres::{FromTableStateError, ToTableStateError},
-        table::TableReplicationPhase,
+        table::{TableCopyProgress, TableReplicationPhase},
     },
 };
 
@@ -21,6 +22,9 @@ pub enum StateStoreError {
     #[error("Invalid confirmed flush lsn value in state store: {0}")]
     InvalidConfirmedFlushLsn(String),
 
+    #[error("Invalid snapshot lsn value in state store: {0}")]
+    InvalidSnapshotLsn(String),
+
     #[error("Missing slot in state store: {0}")]
     MissingSlot(String),
 
@@ -49,7 +53,8 @@ pub trait StateStore {
         &self,
     ) -> impl Future<Output = Result<HashMap<TableId, TableReplicationPhase>, StateStoreError>> + Send;
 
-    /// Loads the table replication states from the persistent state into the cache.
+    /// Loads the table replication states, along with the snapshot LSN, from the persistent state
+    /// into the cache.
     /// This should called once at program start to load the state into the cache
     /// and then use only the `get_X` methods to access the state. Updating the state
     /// by calling the `update_table_replication_state` updates in both the cache and
@@ -65,4 +70,51 @@ pub trait StateStore {
         table_id: TableId,
         state: TableReplicationPhase,
     ) -> impl Future<Output = Result<(), StateStoreError>> + Send;
+
+    /// Removes the table replication state for the table with `table_id` from both the cache as
+    /// well as the persistent store, e.g. once the table is no longer published.
+    fn delete_table_replication_state(
+        &self,
+        table_id: TableId,
+    ) -> impl Future<Output = Result<(), StateStoreError>> + Send;
+
+    /// Returns the LSN of the consistent point of the apply worker slot from the cache, if it was
+    /// stored.
+    ///
+    /// Does not load any new data into the cache.
+    fn get_snapshot_lsn(
+        &self,
+    ) -> impl Future<Output = Result<Option<PgLsn>, StateStoreError>> + Send;
+
+    /// Stores the LSN of the consistent point of the apply worker slot, from which the changes are
+    /// streamed after the initial snapshot, in both the cache as well as the persistent store.
+    fn update_snapshot_lsn(
+        &self,
+        lsn: PgLsn,
+    ) -> impl Future<Output = Result<(), StateStoreError>> + Send;
+
+    /// Returns the ids of the tables whose resync was requested but not started yet.
+    ///
+    /// Resyncs are requested from outside the pipeline, so they are always read from the
+    /// persistent store.
+    fn load_table_resync_requests(
+        &self,
+    ) -> impl Future<Output = Result<Vec<TableId>, StateStoreError>> + Send;
+
+    /// Marks the resync of the table with `table_id` as started in the persistent store, so that
+    /// it's not returned by [`StateStore::load_table_resync_requests`] anymore.
+    fn start_table_resync(
+        &self,
+        table_id: TableId,
+    ) -> impl Future<Output = Result<(), StateStoreError>> + Send;
+
+    /// Stores the progress of the copy of the table with `table_id` in the persistent store.
+    ///
+    /// The number of rows copied is also stored with the resync of the table, if it's being
+    /// resynced.
+    fn update_table_copy_progress(
+        &self,
+        table_id: TableId,
+        progress: TableCopyProgress,
+    ) -> impl Future<Output = Result<(), StateStoreError>> + Send;
 }
@@ -2,13 +2,17 @@ use postgres::schema::TableId;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_postgres::types::PgLsn;
 
 use crate::state::store::base::{StateStore, StateStoreError};
-use crate::state::table::TableReplicationPhase;
+use crate::state::table::{TableCopyProgress, TableReplicationPhase};
 
 #[derive(Debug)]
 struct Inner {
     table_replication_states: HashMap<TableId, TableReplicationPhase>,
+    snapshot_lsn: Option<PgLsn>,
+    table_resync_requests: Vec<TableId>,
+    table_copy_progress: HashMap<TableId, TableCopyProgress>,
 }
 
 #[derive(Debug, Clone)]
@@ -20,12 +24,30 @@ impl MemoryStateStore {
     pub fn new() -> Self {
         let inner = Inner {
             table_replication_states: HashMap::new(),
+            snapshot_lsn: None,
+            table_resync_requests: Vec::new(),
+            table_copy_progress: HashMap::new(),
         };
 
         Self {
             inner: Arc::new(RwLock::new(inner)),
         }
     }
+
+    /// Requests the resync of the table with `table_id`, as the api does for the Postgres store.
+    pub async fn request_table_resync(&self, table_id: TableId) {
+        let mut inner = self.inner.write().await;
+        if !inner.table_resync_requests.contains(&table_id) {
+            inner.table_resync_requests.push(table_id);
+        }
+    }
+
+    /// Returns the progress of the copy of each table.
+    pub async fn get_table_copy_progress(&self) -> HashMap<TableId, TableCopyProgress> {
+        let inner = self.inner.read().await;
+
+        inner.table_copy_progress.clone()
+    }
 }
 
 impl Default for MemoryStateStore {
@@ -67,4 +89,47 @@ impl StateStore for MemoryStateStore {
         inner.table_replication_states.insert(table_id, state);
         Ok(())
     }
+
+    async fn delete_table_replication_state(
+        &self,
+        table_id: TableId,
+    ) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.table_replication_states.remove(&table_id);
+        Ok(())
+    }
+
+    async fn get_snapshot_lsn(&self) -> Result<Option<PgLsn>, StateStoreError> {
+        let inner = self.inner.read().await;
+
+        Ok(inner.snapshot_lsn)
+    }
+
+    async fn update_snapshot_lsn(&self, lsn: PgLsn) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.snapshot_lsn = Some(lsn);
+        Ok(())
+    }
+
+    async fn load_table_resync_requests(&self) -> Result<Vec<TableId>, StateStoreError> {
+        let inner = self.inner.read().await;
+
+        Ok(inner.table_resync_requests.clone())
+    }
+
+    async fn start_table_resync(&self, table_id: TableId) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.table_resync_requests.retain(|id| *id != table_id);
+        Ok(())
+    }
+
+    async fn update_table_copy_progress(
+        &self,
+        table_id: TableId,
+        progress: TableCopyProgress,
+    ) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.table_copy_progress.insert(table_id, progress);
+        Ok(())
+    }
 }
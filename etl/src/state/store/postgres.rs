@@ -16,7 +16,7 @@ use crate::{
     pipeline::PipelineId,
     state::{
         store::base::{StateStore, StateStoreError},
-        table::TableReplicationPhase,
+        table::{TableCopyProgress, TableReplicationPhase},
     },
 };
 
@@ -76,6 +76,7 @@ pub struct ReplicationStateRow {
 #[derive(Debug)]
 struct Inner {
     table_states: HashMap<TableId, TableReplicationPhase>,
+    snapshot_lsn: Option<PgLsn>,
 }
 
 /// A state store which saves the replication state in the source
@@ -91,6 +92,7 @@ impl PostgresStateStore {
     pub fn new(pipeline_id: PipelineId, source_config: PgConnectionConfig) -> PostgresStateStore {
         let inner = Inner {
             table_states: HashMap::new(),
+            snapshot_lsn: None,
         };
         PostgresStateStore {
             pipeline_id,
@@ -159,6 +161,155 @@ impl PostgresStateStore {
         Ok(())
     }
 
+    async fn delete_replication_state(
+        &self,
+        pipeline_id: PipelineId,
+        table_id: TableId,
+    ) -> sqlx::Result<()> {
+        let pool = self.connect_to_source().await?;
+        sqlx::query(
+            r#"
+            delete from etl.replication_state
+            where pipeline_id = $1 and table_id = $2
+        "#,
+        )
+        .bind(pipeline_id as i64)
+        .bind(SqlxTableId(table_id))
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_snapshot_lsn_row(
+        &self,
+        pool: &PgPool,
+        pipeline_id: PipelineId,
+    ) -> sqlx::Result<Option<String>> {
+        sqlx::query_scalar(
+            r#"
+            select snapshot_lsn
+            from etl.pipeline_state
+            where pipeline_id = $1
+            "#,
+        )
+        .bind(pipeline_id as i64)
+        .fetch_optional(pool)
+        .await
+    }
+
+    async fn update_snapshot_lsn_row(
+        &self,
+        pipeline_id: PipelineId,
+        snapshot_lsn: String,
+    ) -> sqlx::Result<()> {
+        let pool = self.connect_to_source().await?;
+        sqlx::query(
+            r#"
+            insert into etl.pipeline_state (pipeline_id, snapshot_lsn)
+            values ($1, $2)
+            on conflict (pipeline_id)
+            do update set snapshot_lsn = $2
+        "#,
+        )
+        .bind(pipeline_id as i64)
+        .bind(snapshot_lsn)
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_table_resync_request_rows(
+        &self,
+        pool: &PgPool,
+        pipeline_id: PipelineId,
+    ) -> sqlx::Result<Vec<SqlxTableId>> {
+        sqlx::query_scalar(
+            r#"
+            select table_id
+            from etl.table_resyncs
+            where pipeline_id = $1 and started_at is null
+            order by requested_at
+            "#,
+        )
+        .bind(pipeline_id as i64)
+        .fetch_all(pool)
+        .await
+    }
+
+    async fn update_table_resync_started_at(
+        &self,
+        pipeline_id: PipelineId,
+        table_id: TableId,
+    ) -> sqlx::Result<()> {
+        let pool = self.connect_to_source().await?;
+        sqlx::query(
+            r#"
+            update etl.table_resyncs
+            set started_at = now(), rows_copied = 0
+            where pipeline_id = $1 and table_id = $2
+        "#,
+        )
+        .bind(pipeline_id as i64)
+        .bind(SqlxTableId(table_id))
+        .execute(&pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn upsert_table_copy_progress_row(
+        &self,
+        pool: &PgPool,
+        pipeline_id: PipelineId,
+        table_id: TableId,
+        progress: TableCopyProgress,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            insert into etl.table_copy_progress (pipeline_id, table_id, rows_copied, estimated_rows)
+            values ($1, $2, $3, $4)
+            on conflict (pipeline_id, table_id)
+            do update set
+                rows_copied = excluded.rows_copied,
+                estimated_rows = excluded.estimated_rows,
+                updated_at = now()
+        "#,
+        )
+        .bind(pipeline_id as i64)
+        .bind(SqlxTableId(table_id))
+        .bind(progress.rows_copied as i64)
+        .bind(progress.estimated_rows.map(|rows| rows as i64))
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_table_resync_rows_copied(
+        &self,
+        pool: &PgPool,
+        pipeline_id: PipelineId,
+        table_id: TableId,
+        rows_copied: u64,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            update etl.table_resyncs
+            set rows_copied = $3
+            where pipeline_id = $1 and table_id = $2 and started_at is not null
+        "#,
+        )
+        .bind(pipeline_id as i64)
+        .bind(SqlxTableId(table_id))
+        .bind(rows_copied as i64)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     async fn replication_phase_from_state(
         &self,
         state: &TableState,
@@ -212,8 +363,18 @@ impl StateStore for PostgresStateStore {
                 .await?;
             table_states.insert(row.table_id.0, phase);
         }
+        let snapshot_lsn = self
+            .get_snapshot_lsn_row(&pool, self.pipeline_id)
+            .await?
+            .map(|lsn_str| {
+                lsn_str
+                    .parse::<PgLsn>()
+                    .map_err(|_| StateStoreError::InvalidSnapshotLsn(lsn_str))
+            })
+            .transpose()?;
         let mut inner = self.inner.write().await;
         inner.table_states = table_states.clone();
+        inner.snapshot_lsn = snapshot_lsn;
 
         info!(
             "loaded {} table replication states from postgres state store",
@@ -235,4 +396,63 @@ impl StateStore for PostgresStateStore {
         inner.table_states.insert(table_id, state);
         Ok(())
     }
+
+    async fn delete_table_replication_state(
+        &self,
+        table_id: TableId,
+    ) -> Result<(), StateStoreError> {
+        self.delete_replication_state(self.pipeline_id, table_id)
+            .await?;
+        let mut inner = self.inner.write().await;
+        inner.table_states.remove(&table_id);
+        Ok(())
+    }
+
+    async fn get_snapshot_lsn(&self) -> Result<Option<PgLsn>, StateStoreError> {
+        let inner = self.inner.read().await;
+        Ok(inner.snapshot_lsn)
+    }
+
+    async fn update_snapshot_lsn(&self, lsn: PgLsn) -> Result<(), StateStoreError> {
+        self.update_snapshot_lsn_row(self.pipeline_id, lsn.to_string())
+            .await?;
+        let mut inner = self.inner.write().await;
+        inner.snapshot_lsn = Some(lsn);
+        Ok(())
+    }
+
+    async fn load_table_resync_requests(&self) -> Result<Vec<TableId>, StateStoreError> {
+        let pool = self.connect_to_source().await?;
+        let table_ids = self
+            .get_table_resync_request_rows(&pool, self.pipeline_id)
+            .await?
+            .into_iter()
+            .map(|table_id| table_id.0)
+            .collect();
+        Ok(table_ids)
+    }
+
+    async fn start_table_resync(&self, table_id: TableId) -> Result<(), StateStoreError> {
+        self.update_table_resync_started_at(self.pipeline_id, table_id)
+            .await?;
+        Ok(())
+    }
+
+    async fn update_table_copy_progress(
+        &self,
+        table_id: TableId,
+        progress: TableCopyProgress,
+    ) -> Result<(), StateStoreError> {
+        let pool = self.connect_to_source().await?;
+        self.upsert_table_copy_progress_row(&pool, self.pipeline_id, table_id, progress)
+            .await?;
+        self.update_table_resync_rows_copied(
+            &pool,
+            self.pipeline_id,
+            table_id,
+            progress.rows_copied,
+        )
+        .await?;
+        Ok(())
+    }
 }
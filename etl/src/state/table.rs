@@ -127,3 +127,31 @@ impl fmt::Display for TableReplicationPhaseType {
         }
     }
 }
+
+/// The progress of the copy of a table by its table sync worker.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct TableCopyProgress {
+    /// The number of rows copied so far.
+    pub rows_copied: u64,
+    /// The number of rows of the table estimated by Postgres when the copy started, if any.
+    ///
+    /// It's only an estimate, so the copy may end with more or less rows, and it ignores the row
+    /// filter of the table.
+    pub estimated_rows: Option<u64>,
+}
+
+impl fmt::Display for TableCopyProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.estimated_rows {
+            Some(estimated_rows) if estimated_rows > 0 => {
+                let percentage = self.rows_copied as f64 / estimated_rows as f64 * 100.0;
+                write!(
+                    f,
+                    "{} of ~{} rows copied (~{:.0}%)",
+                    self.rows_copied, estimated_rows, percentage
+                )
+            }
+            _ => write!(f, "{} rows copied", self.rows_copied),
+        }
+    }
+}
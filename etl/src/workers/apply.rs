@@ -1,19 +1,27 @@
-use config::shared::PipelineConfig;
+use config::shared::{PipelineConfig, ReplicationSlotConfig, RetryConfig, ValidationError};
 use postgres::schema::TableId;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::sync::Semaphore;
+use tokio::sync::{Mutex as AsyncMutex, Semaphore};
 use tokio::task::JoinHandle;
 use tokio_postgres::types::PgLsn;
-use tracing::{Instrument, debug, error, info};
+use tracing::{Instrument, debug, error, info, warn};
 
 use crate::concurrency::shutdown::ShutdownRx;
-use crate::destination::base::Destination;
+use crate::concurrency::status::StatusTx;
+use crate::conversions::event::{Event, MetadataEvent};
+use crate::destination::base::{Destination, DestinationError};
 use crate::pipeline::PipelineId;
 use crate::replication::apply::{ApplyLoopError, ApplyLoopHook, start_apply_loop};
-use crate::replication::client::{PgReplicationClient, PgReplicationError};
+use crate::replication::client::{GetOrCreateSlotResult, PgReplicationClient, PgReplicationError};
 use crate::replication::common::get_table_replication_states;
-use crate::replication::slot::{SlotError, get_slot_name};
+use crate::replication::destination_down::retry_delay;
+use crate::replication::reconnect::{
+    is_connection_lost, is_pg_replication_connection_lost, jitter,
+};
+use crate::replication::slot::{SlotError, get_worker_slot_name};
 use crate::schema::cache::SchemaCache;
 use crate::state::store::base::{StateStore, StateStoreError};
 use crate::state::table::{TableReplicationPhase, TableReplicationPhaseType};
@@ -23,6 +31,12 @@ use crate::workers::table_sync::{
     TableSyncWorker, TableSyncWorkerError, TableSyncWorkerState, TableSyncWorkerStateError,
 };
 
+/// Minimum time between two reads of the table resync requests from the state store.
+const TABLE_RESYNCS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Minimum time between two reads of the tables of the publication from the source.
+const PUBLICATION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Error)]
 pub enum ApplyWorkerError {
     #[error("An error occurred while interacting with the state store: {0}")]
@@ -36,6 +50,14 @@ pub enum ApplyWorkerError {
 
     #[error("Could not generate slot name in the apply loop: {0}")]
     Slot(#[from] SlotError),
+
+    #[error("The start LSN override is invalid: {0}")]
+    InvalidStartLsn(#[from] ValidationError),
+
+    #[error(
+        "The start LSN override {0} is before the restart LSN {1} of the slot, the WAL needed to stream from it may be gone"
+    )]
+    StartLsnBeforeRestartLsn(PgLsn, PgLsn),
 }
 
 #[derive(Debug, Error)]
@@ -51,6 +73,9 @@ pub enum ApplyWorkerHookError {
 
     #[error("A Postgres replication error occurred in the apply worker: {0}")]
     PgReplication(#[from] PgReplicationError),
+
+    #[error("An error occurred while truncating a table in the destination: {0}")]
+    Destination(#[from] DestinationError),
 }
 
 #[derive(Debug)]
@@ -81,6 +106,7 @@ pub struct ApplyWorker<S, D> {
     schema_cache: SchemaCache,
     state_store: S,
     destination: D,
+    status_tx: StatusTx,
     shutdown_rx: ShutdownRx,
     table_sync_worker_permits: Arc<Semaphore>,
 }
@@ -95,6 +121,7 @@ impl<S, D> ApplyWorker<S, D> {
         schema_cache: SchemaCache,
         state_store: S,
         destination: D,
+        status_tx: StatusTx,
         shutdown_rx: ShutdownRx,
         table_sync_worker_permits: Arc<Semaphore>,
     ) -> Self {
@@ -106,6 +133,7 @@ impl<S, D> ApplyWorker<S, D> {
             schema_cache,
             state_store,
             destination,
+            status_tx,
             shutdown_rx,
             table_sync_worker_permits,
         }
@@ -128,28 +156,71 @@ where
             publication_name = self.config.publication_name
         );
         let apply_worker = async move {
-            let start_lsn = get_start_lsn(self.pipeline_id, &self.replication_client).await?;
-
-            start_apply_loop(
+            let mut start_lsn = get_start_lsn(
                 self.pipeline_id,
-                start_lsn,
-                self.config.clone(),
-                self.replication_client.clone(),
-                self.schema_cache.clone(),
-                self.destination.clone(),
-                ApplyWorkerHook::new(
-                    self.pipeline_id,
-                    self.config,
-                    self.pool,
-                    self.schema_cache,
-                    self.state_store,
-                    self.destination,
-                    self.shutdown_rx.clone(),
-                    self.table_sync_worker_permits.clone(),
-                ),
-                self.shutdown_rx,
+                &self.config,
+                &self.replication_client,
+                &self.state_store,
             )
             .await?;
+            let mut replication_client = self.replication_client;
+            let mut shutdown_rx = self.shutdown_rx;
+
+            loop {
+                let result = start_apply_loop(
+                    self.pipeline_id,
+                    start_lsn,
+                    self.config.clone(),
+                    replication_client.clone(),
+                    self.schema_cache.clone(),
+                    self.destination.clone(),
+                    ApplyWorkerHook::new(
+                        self.pipeline_id,
+                        self.config.clone(),
+                        self.pool.clone(),
+                        self.schema_cache.clone(),
+                        self.state_store.clone(),
+                        self.destination.clone(),
+                        self.status_tx.clone(),
+                        shutdown_rx.clone(),
+                        self.table_sync_worker_permits.clone(),
+                    ),
+                    self.status_tx.clone(),
+                    shutdown_rx.clone(),
+                )
+                .await;
+
+                let err = match result {
+                    Ok(_) => break,
+                    Err(err) => err,
+                };
+                let Some(source_reconnect) = &self.config.source_reconnect else {
+                    return Err(err.into());
+                };
+                if !is_connection_lost(&err) {
+                    return Err(err.into());
+                }
+
+                match reconnect(
+                    self.pipeline_id,
+                    &self.config,
+                    source_reconnect,
+                    &mut shutdown_rx,
+                    err,
+                )
+                .await?
+                {
+                    Some((client, lsn)) => {
+                        replication_client = client;
+                        start_lsn = lsn;
+                    }
+                    None => {
+                        info!("shutting down apply worker while reconnecting to the source");
+
+                        return Ok(());
+                    }
+                }
+            }
 
             info!("apply worker completed successfully");
 
@@ -165,11 +236,22 @@ where
     }
 }
 
-async fn get_start_lsn(
+/// Returns the LSN from which the apply worker starts streaming.
+///
+/// This is the LSN stored in the apply worker's slot, unless [`PipelineConfig::start_lsn`]
+/// overrides it. When the slot is created, its consistent point is stored in `state_store` as the
+/// LSN from which the changes are streamed after the initial snapshot.
+async fn get_start_lsn<S: StateStore>(
     pipeline_id: PipelineId,
+    config: &PipelineConfig,
     replication_client: &PgReplicationClient,
+    state_store: &S,
 ) -> Result<PgLsn, ApplyWorkerError> {
-    let slot_name = get_slot_name(pipeline_id, WorkerType::Apply)?;
+    let slot_name = get_worker_slot_name(
+        pipeline_id,
+        WorkerType::Apply,
+        config.replication_slot.as_ref(),
+    )?;
     // TODO: validate that we only create the slot when we first start replication which
     //  means when all tables are in the Init state. In any other case we should raise an
     //  error because that means the apply slot was deleted and creating a fresh slot now
@@ -179,12 +261,110 @@ async fn get_start_lsn(
     //  In this case, the apply worker slot is missing not because someone deleted it but
     //  because it was never created in the first place. The answer here might be to create
     //  the apply worker slot as the first thing, before starting table sync workers.
-    let slot = replication_client.get_or_create_slot(&slot_name).await?;
-    let start_lsn = slot.get_start_lsn();
+    let plugin = config
+        .replication_slot
+        .as_ref()
+        .map_or(ReplicationSlotConfig::DEFAULT_PLUGIN, |replication_slot| {
+            &replication_slot.plugin
+        });
+    let slot = replication_client
+        .get_or_create_slot(&slot_name, plugin)
+        .await?;
+    let slot_start_lsn = slot.get_start_lsn();
+    if let GetOrCreateSlotResult::CreateSlot(slot) = &slot {
+        state_store
+            .update_snapshot_lsn(slot.consistent_point)
+            .await?;
+    }
+
+    let Some(start_lsn) = &config.start_lsn else {
+        return Ok(slot_start_lsn);
+    };
+
+    // WAL before the slot's restart LSN may have been removed, so we can't stream from there.
+    let start_lsn = PipelineConfig::parse_start_lsn(start_lsn)?;
+    let restart_lsn = replication_client.get_slot(&slot_name).await?.restart_lsn;
+    if start_lsn < restart_lsn {
+        return Err(ApplyWorkerError::StartLsnBeforeRestartLsn(
+            start_lsn,
+            restart_lsn,
+        ));
+    }
+
+    warn!(
+        %start_lsn,
+        %slot_start_lsn,
+        %restart_lsn,
+        "starting replication from a manually overridden start lsn"
+    );
 
     Ok(start_lsn)
 }
 
+/// Reconnects to the source after the replication connection of the apply worker was lost with
+/// `err`, retrying with exponential backoff and jitter as configured by `retry`.
+///
+/// Returns the new client and the LSN confirmed to the slot, from which streaming resumes, or
+/// `None` if the worker is shut down while waiting to reconnect.
+async fn reconnect(
+    pipeline_id: PipelineId,
+    config: &PipelineConfig,
+    retry: &RetryConfig,
+    shutdown_rx: &mut ShutdownRx,
+    err: ApplyLoopError,
+) -> Result<Option<(PgReplicationClient, PgLsn)>, ApplyWorkerError> {
+    let slot_name = get_worker_slot_name(
+        pipeline_id,
+        WorkerType::Apply,
+        config.replication_slot.as_ref(),
+    )?;
+
+    let mut last_err = ApplyWorkerError::from(err);
+    for attempt in 0..retry.max_attempts {
+        let delay = jitter(retry_delay(retry, attempt), rand::random());
+        warn!(
+            "lost the replication connection to the source (attempt {} of {}), reconnecting in {:?}: {}",
+            attempt + 1,
+            retry.max_attempts,
+            delay,
+            last_err
+        );
+
+        tokio::select! {
+            biased;
+
+            _ = shutdown_rx.changed() => return Ok(None),
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        let result = async {
+            let replication_client =
+                PgReplicationClient::connect(config.pg_connection.clone()).await?;
+            let slot = replication_client.get_slot(&slot_name).await?;
+
+            Ok::<_, PgReplicationError>((replication_client, slot.confirmed_flush_lsn))
+        }
+        .await;
+
+        match result {
+            Ok((replication_client, confirmed_flush_lsn)) => {
+                info!(
+                    "reconnected to the source, resuming replication from lsn {}",
+                    confirmed_flush_lsn
+                );
+
+                return Ok(Some((replication_client, confirmed_flush_lsn)));
+            }
+            // Only the failures to reach the source are retried, e.g. a missing slot won't come
+            // back.
+            Err(err) if is_pg_replication_connection_lost(&err) => last_err = err.into(),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Err(last_err)
+}
+
 #[derive(Debug)]
 struct ApplyWorkerHook<S, D> {
     pipeline_id: PipelineId,
@@ -193,8 +373,14 @@ struct ApplyWorkerHook<S, D> {
     schema_cache: SchemaCache,
     state_store: S,
     destination: D,
+    status_tx: StatusTx,
     shutdown_rx: ShutdownRx,
     table_sync_worker_permits: Arc<Semaphore>,
+    last_table_resyncs_check: Mutex<Option<Instant>>,
+    last_publication_check: Mutex<Option<Instant>>,
+    /// The connection used to read the tables of the publication, opened on the first check and
+    /// opened again after it's lost.
+    catalog_client: AsyncMutex<Option<PgReplicationClient>>,
 }
 
 impl<S, D> ApplyWorkerHook<S, D> {
@@ -206,6 +392,7 @@ impl<S, D> ApplyWorkerHook<S, D> {
         schema_cache: SchemaCache,
         state_store: S,
         destination: D,
+        status_tx: StatusTx,
         shutdown_rx: ShutdownRx,
         table_sync_worker_permits: Arc<Semaphore>,
     ) -> Self {
@@ -216,8 +403,12 @@ impl<S, D> ApplyWorkerHook<S, D> {
             schema_cache,
             state_store,
             destination,
+            status_tx,
             shutdown_rx,
             table_sync_worker_permits,
+            last_table_resyncs_check: Mutex::new(None),
+            last_publication_check: Mutex::new(None),
+            catalog_client: AsyncMutex::new(None),
         }
     }
 }
@@ -236,6 +427,7 @@ where
             self.schema_cache.clone(),
             self.state_store.clone(),
             self.destination.clone(),
+            self.status_tx.clone(),
             self.shutdown_rx.clone(),
             self.table_sync_worker_permits.clone(),
         );
@@ -276,6 +468,101 @@ where
             .await
     }
 
+    /// Adds the replication state of the tables which started being published, so that they are
+    /// copied by table sync workers, and removes the one of the tables which stopped being
+    /// published, so that their changes are not applied anymore.
+    async fn apply_publication_changes(
+        &self,
+        catalog_client: &PgReplicationClient,
+    ) -> Result<(), ApplyWorkerHookError> {
+        let published_table_ids = catalog_client
+            .get_publication_table_ids(&self.config.publication_name)
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let states = self.state_store.get_table_replication_states().await?;
+
+        let mut added_table_ids = published_table_ids
+            .iter()
+            .filter(|table_id| !states.contains_key(table_id))
+            .copied()
+            .collect::<Vec<_>>();
+        added_table_ids.sort_unstable();
+
+        let mut dropped_table_ids = Vec::new();
+        for &table_id in states.keys() {
+            if published_table_ids.contains(&table_id) {
+                continue;
+            }
+
+            // A table sync worker would store the state of its table again, so the table is only
+            // removed once it's done syncing.
+            let has_active_worker = {
+                let pool = self.pool.read().await;
+                pool.get_active_worker_state(table_id).is_some()
+            };
+            if has_active_worker {
+                debug!(
+                    "postponing the removal of table {} until it's done syncing",
+                    table_id
+                );
+
+                continue;
+            }
+
+            dropped_table_ids.push(table_id);
+        }
+        dropped_table_ids.sort_unstable();
+
+        if added_table_ids.is_empty() && dropped_table_ids.is_empty() {
+            return Ok(());
+        }
+
+        // The metadata events are written before the states are changed, so that the rows of an
+        // added table are written after its event.
+        if self.config.emit_metadata_events {
+            let mut events = Vec::with_capacity(added_table_ids.len() + dropped_table_ids.len());
+            for &table_id in &added_table_ids {
+                let table_schema = catalog_client
+                    .get_table_schema(table_id, Some(&self.config.publication_name))
+                    .await?;
+                events.push(Event::Metadata(MetadataEvent::TableAdded { table_schema }));
+            }
+            for &table_id in &dropped_table_ids {
+                events.push(Event::Metadata(MetadataEvent::TableDropped {
+                    table_id,
+                    table_schema: self.schema_cache.get_table_schema(&table_id).await,
+                }));
+            }
+
+            self.destination.write_events(events).await?;
+        }
+
+        for table_id in added_table_ids {
+            info!(
+                "table {} was added to publication '{}', it will be copied by a table sync worker",
+                table_id, self.config.publication_name
+            );
+
+            self.state_store
+                .update_table_replication_state(table_id, TableReplicationPhase::Init)
+                .await?;
+        }
+
+        for table_id in dropped_table_ids {
+            info!(
+                "table {} is no longer in publication '{}', its changes will not be replicated anymore",
+                table_id, self.config.publication_name
+            );
+
+            self.state_store
+                .delete_table_replication_state(table_id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     async fn handle_existing_worker(
         &self,
         table_id: TableId,
@@ -463,6 +750,115 @@ where
         Ok(should_apply_changes)
     }
 
+    /// Truncates the destination tables whose resync was requested and resets their replication
+    /// phase to `Init`, so that a table sync worker copies them again from a fresh snapshot and
+    /// catches up with the apply worker, while the other tables keep being streamed.
+    ///
+    /// Tables which are still syncing are only resynced once they are done.
+    async fn process_table_resyncs(&self) -> Result<bool, Self::Error> {
+        {
+            let mut last_check = self.last_table_resyncs_check.lock().unwrap();
+            if last_check
+                .is_some_and(|last_check| last_check.elapsed() < TABLE_RESYNCS_CHECK_INTERVAL)
+            {
+                return Ok(true);
+            }
+            *last_check = Some(Instant::now());
+        }
+
+        for table_id in self.state_store.load_table_resync_requests().await? {
+            let is_done = self
+                .state_store
+                .get_table_replication_state(table_id)
+                .await?
+                .is_some_and(|phase| phase.as_type().is_done());
+            let has_active_worker = {
+                let pool = self.pool.read().await;
+                pool.get_active_worker_state(table_id).is_some()
+            };
+            if !is_done || has_active_worker {
+                debug!(
+                    "postponing the resync of table {} until it's done syncing",
+                    table_id
+                );
+
+                continue;
+            }
+
+            info!(
+                "resyncing table {}, it will be copied again by a table sync worker",
+                table_id
+            );
+
+            // The table is truncated before its phase is reset, so that if we fail in between, the
+            // resync is still requested and the table is truncated again.
+            self.destination.truncate_table(table_id).await?;
+            self.state_store
+                .update_table_replication_state(table_id, TableReplicationPhase::Init)
+                .await?;
+            self.state_store.start_table_resync(table_id).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Starts copying the tables added to the publication while the pipeline is running, and
+    /// stops applying the changes of the tables removed from it.
+    ///
+    /// An added table is copied from a snapshot taken once it's published, while its changes
+    /// streamed until then are skipped, as they are part of the snapshot. The rows of a removed
+    /// table are kept in the destination, so it's copied again on top of them if it's added back.
+    async fn process_publication_changes(&self) -> Result<bool, Self::Error> {
+        {
+            let mut last_check = self.last_publication_check.lock().unwrap();
+            if last_check
+                .is_some_and(|last_check| last_check.elapsed() < PUBLICATION_CHECK_INTERVAL)
+            {
+                return Ok(true);
+            }
+            *last_check = Some(Instant::now());
+        }
+
+        let mut catalog_client = self.catalog_client.lock().await;
+        let result = async {
+            let client = match catalog_client.take() {
+                Some(client) => client,
+                None => {
+                    PgReplicationClient::connect_with_statement_timeout(
+                        self.config.pg_connection.clone(),
+                        self.config.statement_timeout.catalog_ms,
+                    )
+                    .await?
+                }
+            };
+            self.apply_publication_changes(&client).await?;
+
+            Ok::<_, ApplyWorkerHookError>(client)
+        }
+        .await;
+
+        match result {
+            Ok(client) => {
+                *catalog_client = Some(client);
+
+                Ok(true)
+            }
+            // The changes are streamed regardless, so the publication is checked again later on a
+            // new connection.
+            Err(ApplyWorkerHookError::PgReplication(err))
+                if is_pg_replication_connection_lost(&err) =>
+            {
+                warn!(
+                    "could not read the tables of publication '{}', checking again later: {}",
+                    self.config.publication_name, err
+                );
+
+                Ok(true)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     fn worker_type(&self) -> WorkerType {
         WorkerType::Apply
     }
@@ -5,6 +5,8 @@ use thiserror::Error;
 use tokio::task;
 
 use crate::workers::apply::ApplyWorkerError;
+use crate::workers::heartbeat::HeartbeatWorkerError;
+use crate::workers::sequence_sync::SequenceSyncWorkerError;
 use crate::workers::table_sync::TableSyncWorkerError;
 
 /// Represents all possible errors that can occur while waiting for a worker to complete.
@@ -38,6 +40,20 @@ pub enum WorkerWaitError {
     /// This variant wraps the specific error returned by the table sync worker.
     #[error("Table sync worker terminated with an error: {0}")]
     TableSyncWorkerFailed(#[from] TableSyncWorkerError),
+
+    /// The sequence sync worker encountered an error that was propagated via the handle's return
+    /// value.
+    ///
+    /// This variant wraps the specific error returned by the sequence sync worker.
+    #[error("Sequence sync worker terminated with an error: {0}")]
+    SequenceSyncWorkerFailed(#[from] SequenceSyncWorkerError),
+
+    /// The heartbeat worker encountered an error that was propagated via the handle's return
+    /// value.
+    ///
+    /// This variant wraps the specific error returned by the heartbeat worker.
+    #[error("Heartbeat worker terminated with an error: {0}")]
+    HeartbeatWorkerFailed(#[from] HeartbeatWorkerError),
 }
 
 #[derive(Debug)]
@@ -0,0 +1,113 @@
+use postgres::schema::TableName;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{Instrument, debug, info};
+
+use crate::concurrency::shutdown::ShutdownRx;
+use crate::pipeline::PipelineId;
+use crate::replication::client::{PgReplicationClient, PgReplicationError};
+use crate::workers::base::{Worker, WorkerHandle, WorkerWaitError};
+
+#[derive(Debug, Error)]
+pub enum HeartbeatWorkerError {
+    #[error("A Postgres replication error occurred in the heartbeat worker: {0}")]
+    PgReplication(#[from] PgReplicationError),
+}
+
+#[derive(Debug)]
+pub struct HeartbeatWorkerHandle {
+    handle: Option<JoinHandle<Result<(), HeartbeatWorkerError>>>,
+}
+
+impl WorkerHandle<()> for HeartbeatWorkerHandle {
+    fn state(&self) {}
+
+    async fn wait(mut self) -> Result<(), WorkerWaitError> {
+        let Some(handle) = self.handle.take() else {
+            return Ok(());
+        };
+
+        handle.await??;
+
+        Ok(())
+    }
+}
+
+/// A worker which periodically writes a heartbeat to the heartbeat table.
+///
+/// The heartbeat table is in the publication, so each heartbeat is a change that the apply worker
+/// confirms, which advances the replication slot even when no replicated table changes.
+#[derive(Debug)]
+pub struct HeartbeatWorker {
+    pipeline_id: PipelineId,
+    table_name: TableName,
+    interval: Duration,
+    replication_client: PgReplicationClient,
+    shutdown_rx: ShutdownRx,
+}
+
+impl HeartbeatWorker {
+    pub fn new(
+        pipeline_id: PipelineId,
+        table_name: TableName,
+        interval: Duration,
+        replication_client: PgReplicationClient,
+        shutdown_rx: ShutdownRx,
+    ) -> Self {
+        Self {
+            pipeline_id,
+            table_name,
+            interval,
+            replication_client,
+            shutdown_rx,
+        }
+    }
+}
+
+impl Worker<HeartbeatWorkerHandle, ()> for HeartbeatWorker {
+    type Error = HeartbeatWorkerError;
+
+    async fn start(mut self) -> Result<HeartbeatWorkerHandle, Self::Error> {
+        info!("starting heartbeat worker");
+
+        let heartbeat_worker_span = tracing::info_span!(
+            "heartbeat_worker",
+            pipeline_id = self.pipeline_id,
+            table_name = %self.table_name
+        );
+        let heartbeat_worker = async move {
+            let mut interval = tokio::time::interval(self.interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    biased;
+
+                    // Shutdown signal received, exit loop.
+                    _ = self.shutdown_rx.changed() => {
+                        info!("shutting down heartbeat worker");
+
+                        return Ok(());
+                    }
+
+                    _ = interval.tick() => {}
+                }
+
+                debug!("writing heartbeat to table {}", self.table_name);
+
+                self.replication_client
+                    .write_heartbeat(&self.table_name, self.pipeline_id)
+                    .await?;
+            }
+        }
+        .instrument(heartbeat_worker_span.or_current());
+
+        let handle = tokio::spawn(heartbeat_worker);
+
+        Ok(HeartbeatWorkerHandle {
+            handle: Some(handle),
+        })
+    }
+}
@@ -1,4 +1,6 @@
 pub mod apply;
 pub mod base;
+pub mod heartbeat;
 pub mod pool;
+pub mod sequence_sync;
 pub mod table_sync;
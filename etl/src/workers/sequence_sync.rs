@@ -0,0 +1,183 @@
+use config::shared::PipelineConfig;
+use postgres::schema::TableName;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{Instrument, debug, info};
+
+use crate::concurrency::shutdown::ShutdownRx;
+use crate::conversions::event::{Event, MetadataEvent};
+use crate::destination::base::{Destination, DestinationError};
+use crate::pipeline::PipelineId;
+use crate::replication::client::{OwnedSequenceValue, PgReplicationClient, PgReplicationError};
+use crate::workers::base::{Worker, WorkerHandle, WorkerWaitError};
+
+#[derive(Debug, Error)]
+pub enum SequenceSyncWorkerError {
+    #[error("A Postgres replication error occurred in the sequence sync worker: {0}")]
+    PgReplication(#[from] PgReplicationError),
+
+    #[error("An error occurred while writing sequence values to the destination: {0}")]
+    Destination(#[from] DestinationError),
+}
+
+#[derive(Debug)]
+pub struct SequenceSyncWorkerHandle {
+    handle: Option<JoinHandle<Result<(), SequenceSyncWorkerError>>>,
+}
+
+impl WorkerHandle<()> for SequenceSyncWorkerHandle {
+    fn state(&self) {}
+
+    async fn wait(mut self) -> Result<(), WorkerWaitError> {
+        let Some(handle) = self.handle.take() else {
+            return Ok(());
+        };
+
+        handle.await??;
+
+        Ok(())
+    }
+}
+
+/// A worker which periodically sends the values of the sequences owned by the replicated tables
+/// to the destination, as [`MetadataEvent::SequenceValue`] events.
+///
+/// The replicated tables are the ones in the publication at the time of each check. A value is
+/// only sent when it changed since it was last sent by the worker.
+#[derive(Debug)]
+pub struct SequenceSyncWorker<D> {
+    pipeline_id: PipelineId,
+    config: Arc<PipelineConfig>,
+    interval: Duration,
+    replication_client: PgReplicationClient,
+    destination: D,
+    shutdown_rx: ShutdownRx,
+}
+
+impl<D> SequenceSyncWorker<D> {
+    pub fn new(
+        pipeline_id: PipelineId,
+        config: Arc<PipelineConfig>,
+        interval: Duration,
+        replication_client: PgReplicationClient,
+        destination: D,
+        shutdown_rx: ShutdownRx,
+    ) -> Self {
+        Self {
+            pipeline_id,
+            config,
+            interval,
+            replication_client,
+            destination,
+            shutdown_rx,
+        }
+    }
+}
+
+impl<D> Worker<SequenceSyncWorkerHandle, ()> for SequenceSyncWorker<D>
+where
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    type Error = SequenceSyncWorkerError;
+
+    async fn start(mut self) -> Result<SequenceSyncWorkerHandle, Self::Error> {
+        info!("starting sequence sync worker");
+
+        let sequence_sync_worker_span = tracing::info_span!(
+            "sequence_sync_worker",
+            pipeline_id = self.pipeline_id,
+            publication_name = self.config.publication_name
+        );
+        let sequence_sync_worker = async move {
+            let mut last_values = BTreeMap::new();
+
+            let mut interval = tokio::time::interval(self.interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    biased;
+
+                    // Shutdown signal received, exit loop.
+                    _ = self.shutdown_rx.changed() => {
+                        info!("shutting down sequence sync worker");
+
+                        return Ok(());
+                    }
+
+                    _ = interval.tick() => {}
+                }
+
+                sync_sequence_values(
+                    &self.config,
+                    &self.replication_client,
+                    &self.destination,
+                    &mut last_values,
+                )
+                .await?;
+            }
+        }
+        .instrument(sequence_sync_worker_span.or_current());
+
+        let handle = tokio::spawn(sequence_sync_worker);
+
+        Ok(SequenceSyncWorkerHandle {
+            handle: Some(handle),
+        })
+    }
+}
+
+/// Sends the values of the sequences owned by the published tables which changed since they were
+/// last sent, and records them in `last_values`.
+async fn sync_sequence_values<D: Destination>(
+    config: &PipelineConfig,
+    replication_client: &PgReplicationClient,
+    destination: &D,
+    last_values: &mut BTreeMap<TableName, i64>,
+) -> Result<(), SequenceSyncWorkerError> {
+    let table_ids = replication_client
+        .get_publication_table_ids(&config.publication_name)
+        .await?;
+    let changed_values = replication_client
+        .get_owned_sequence_values(&table_ids)
+        .await?
+        .into_iter()
+        .filter(|value| last_values.get(&value.sequence_name) != Some(&value.last_value))
+        .collect::<Vec<_>>();
+    if changed_values.is_empty() {
+        return Ok(());
+    }
+
+    debug!("sending {} changed sequence values", changed_values.len());
+
+    let events = changed_values
+        .iter()
+        .cloned()
+        .map(|value| {
+            let OwnedSequenceValue {
+                table_id,
+                column_name,
+                sequence_name,
+                last_value,
+            } = value;
+
+            Event::Metadata(MetadataEvent::SequenceValue {
+                table_id,
+                column_name,
+                sequence_name,
+                last_value,
+            })
+        })
+        .collect();
+    destination.write_events(events).await?;
+
+    for value in changed_values {
+        last_values.insert(value.sequence_name, value.last_value);
+    }
+
+    Ok(())
+}
@@ -10,6 +10,7 @@ use tracing::{Instrument, debug, error, info, warn};
 
 use crate::concurrency::future::ReactiveFuture;
 use crate::concurrency::shutdown::{ShutdownResult, ShutdownRx};
+use crate::concurrency::status::StatusTx;
 use crate::destination::base::Destination;
 use crate::pipeline::PipelineId;
 use crate::replication::apply::{ApplyLoopError, ApplyLoopHook, start_apply_loop};
@@ -248,6 +249,7 @@ pub struct TableSyncWorker<S, D> {
     schema_cache: SchemaCache,
     state_store: S,
     destination: D,
+    status_tx: StatusTx,
     shutdown_rx: ShutdownRx,
     run_permit: Arc<Semaphore>,
 }
@@ -262,6 +264,7 @@ impl<S, D> TableSyncWorker<S, D> {
         schema_cache: SchemaCache,
         state_store: S,
         destination: D,
+        status_tx: StatusTx,
         shutdown_rx: ShutdownRx,
         run_permit: Arc<Semaphore>,
     ) -> Self {
@@ -273,6 +276,7 @@ impl<S, D> TableSyncWorker<S, D> {
             schema_cache,
             state_store,
             destination,
+            status_tx,
             shutdown_rx,
             run_permit,
         }
@@ -350,8 +354,14 @@ where
             //
             // Note that this connection must be tied to the lifetime of this worker, otherwise
             // there will be problems when cleaning up the replication slot.
-            let replication_client =
-                PgReplicationClient::connect(self.config.pg_connection.clone()).await?;
+            //
+            // Since this connection copies the table, it uses the copy timeout, which must not kill
+            // a long running copy.
+            let replication_client = PgReplicationClient::connect_with_statement_timeout(
+                self.config.pg_connection.clone(),
+                self.config.statement_timeout.copy_ms,
+            )
+            .await?;
 
             let result = start_table_sync(
                 self.pipeline_id,
@@ -386,6 +396,7 @@ where
                 self.schema_cache,
                 self.destination,
                 TableSyncWorkerHook::new(self.table_id, state_clone, self.state_store),
+                self.status_tx,
                 self.shutdown_rx,
             )
             .await?;
@@ -546,6 +557,16 @@ where
         Ok(should_apply_changes)
     }
 
+    /// Resyncs are started by the apply worker.
+    async fn process_table_resyncs(&self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+
+    /// Changes to the publication are handled by the apply worker.
+    async fn process_publication_changes(&self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+
     fn worker_type(&self) -> WorkerType {
         WorkerType::TableSync {
             table_id: self.table_id,
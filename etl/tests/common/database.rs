@@ -1,4 +1,4 @@
-use config::shared::{PgConnectionConfig, TlsConfig};
+use config::shared::{PgConnectionConfig, TlsConfig, TlsVerification};
 use postgres::schema::TableName;
 use postgres::tokio::test_utils::PgDatabase;
 use secrecy::Secret;
@@ -33,7 +33,27 @@ pub fn test_table_name(name: &str) -> TableName {
 ///
 /// Panics if the test schema cannot be created.
 pub async fn spawn_database() -> PgDatabase<Client> {
-    let options = PgConnectionConfig {
+    let database = PgDatabase::new(test_database_config()).await;
+    create_test_schema(&database).await;
+
+    database
+}
+
+/// Creates a new test database instance with a unique name, which uses the server `encoding`
+/// instead of the server's default one.
+///
+/// # Panics
+///
+/// Panics if the test schema cannot be created.
+pub async fn spawn_database_with_encoding(encoding: &str) -> PgDatabase<Client> {
+    let database = PgDatabase::new_with_encoding(test_database_config(), encoding).await;
+    create_test_schema(&database).await;
+
+    database
+}
+
+fn test_database_config() -> PgConnectionConfig {
+    PgConnectionConfig {
         host: "localhost".to_owned(),
         port: 5430,
         // We create a random database name to avoid conflicts with existing databases.
@@ -43,12 +63,12 @@ pub async fn spawn_database() -> PgDatabase<Client> {
         tls: TlsConfig {
             trusted_root_certs: String::new(),
             enabled: false,
+            verification: TlsVerification::default(),
         },
-    };
-
-    let database = PgDatabase::new(options).await;
+    }
+}
 
-    // Create the test schema.
+async fn create_test_schema(database: &PgDatabase<Client>) {
     database
         .client
         .as_ref()
@@ -56,6 +76,4 @@ pub async fn spawn_database() -> PgDatabase<Client> {
         .execute(&format!("CREATE SCHEMA {TEST_DATABASE_SCHEMA}"), &[])
         .await
         .expect("Failed to create test schema");
-
-    database
 }
@@ -1,4 +1,4 @@
-use etl::conversions::event::{Event, EventType};
+use etl::conversions::event::{Event, EventType, MetadataEvent};
 use postgres::schema::TableId;
 use std::collections::HashMap;
 
@@ -53,3 +53,13 @@ pub fn check_events_count(events: &[Event], conditions: Vec<(EventType, u64)>) -
 
     true
 }
+
+pub fn get_metadata_events(events: &[Event]) -> Vec<MetadataEvent> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Metadata(event) => Some(event.clone()),
+            _ => None,
+        })
+        .collect()
+}
@@ -1,4 +1,8 @@
-use config::shared::{BatchConfig, PgConnectionConfig, PipelineConfig, RetryConfig};
+use config::shared::{
+    AutoCreatePublicationConfig, BatchConfig, BatchFlushMode, ConsistencyCheckConfig, CopyConfig,
+    DestinationDownConfig, HeartbeatConfig, NullPolicy, PgConnectionConfig, PipelineConfig,
+    ReplicationMode, ReplicationSlotConfig, RetryConfig, StatementTimeoutConfig,
+};
 use etl::destination::base::Destination;
 use etl::pipeline::{Pipeline, PipelineId};
 use etl::state::store::base::StateStore;
@@ -39,6 +43,22 @@ where
         },
         publication_name,
         max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
     };
 
     Pipeline::new(pipeline_id, config, state_store, destination)
@@ -73,6 +93,599 @@ where
         },
         publication_name,
         max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_mode<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    mode: ReplicationMode,
+    skip_initial_snapshot: bool,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode,
+        skip_initial_snapshot,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_batch_flush_mode<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    batch: BatchConfig,
+    batch_flush_mode: BatchFlushMode,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch,
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode,
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_auto_create_publication<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    auto_create_publication: AutoCreatePublicationConfig,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: Some(auto_create_publication),
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_start_lsn<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    start_lsn: String,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: Some(start_lsn),
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_metadata_events<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: true,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_sequence_sync<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    sequence_sync_interval_ms: u64,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: Some(sequence_sync_interval_ms),
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_destination_down<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    destination_down: DestinationDownConfig,
+    batch_config: Option<BatchConfig>,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let batch = batch_config.unwrap_or(BatchConfig {
+        max_size: 1,
+        max_fill_ms: 1000,
+    });
+
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch,
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: Some(destination_down),
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_heartbeat<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    heartbeat: HeartbeatConfig,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: Some(heartbeat),
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_copy<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    copy: CopyConfig,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy,
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_consistency_check<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    consistency_check: ConsistencyCheckConfig,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: Some(consistency_check),
+        source_reconnect: None,
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_source_reconnect<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    source_reconnect: RetryConfig,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: Some(source_reconnect),
+        replication_slot: None,
+    };
+
+    Pipeline::new(pipeline_id, config, state_store, destination)
+}
+
+pub fn create_pipeline_with_replication_slot<S, D>(
+    pg_connection_config: &PgConnectionConfig,
+    pipeline_id: PipelineId,
+    publication_name: String,
+    state_store: S,
+    destination: D,
+    replication_slot: ReplicationSlotConfig,
+) -> Pipeline<S, D>
+where
+    S: StateStore + Clone + Send + Sync + 'static,
+    D: Destination + Clone + Send + Sync + 'static,
+{
+    let config = PipelineConfig {
+        id: pipeline_id,
+        pg_connection: pg_connection_config.clone(),
+        batch: BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        apply_worker_init_retry: RetryConfig {
+            max_attempts: 2,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            backoff_factor: 2.0,
+        },
+        publication_name,
+        max_table_sync_workers: 1,
+        mode: ReplicationMode::CopyAndStream,
+        skip_initial_snapshot: false,
+        statement_timeout: StatementTimeoutConfig::default(),
+        copy: CopyConfig::default(),
+        batch_flush_mode: BatchFlushMode::default(),
+        auto_create_publication: None,
+        null_policy: NullPolicy::default(),
+        preserve_unsupported_types: false,
+        start_lsn: None,
+        emit_metadata_events: false,
+        sequence_sync_interval_ms: None,
+        destination_down: None,
+        heartbeat: None,
+        consistency_check: None,
+        source_reconnect: None,
+        replication_slot: Some(replication_slot),
     };
 
     Pipeline::new(pipeline_id, config, state_store, destination)
@@ -1,11 +1,12 @@
 use etl::state::store::base::{StateStore, StateStoreError};
-use etl::state::table::{TableReplicationPhase, TableReplicationPhaseType};
+use etl::state::table::{TableCopyProgress, TableReplicationPhase, TableReplicationPhaseType};
 use postgres::schema::TableId;
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 use tokio::runtime::Handle;
 use tokio::sync::{Notify, RwLock};
+use tokio_postgres::types::PgLsn;
 
 type TableStateCondition = Box<dyn Fn(&TableReplicationPhase) -> bool + Send + Sync>;
 
@@ -15,10 +16,14 @@ pub enum StateStoreMethod {
     GetTableReplicationStates,
     LoadTableReplicationStates,
     StoreTableReplicationState,
+    DeleteTableReplicationState,
 }
 
 struct Inner {
     table_replication_states: HashMap<TableId, TableReplicationPhase>,
+    snapshot_lsn: Option<PgLsn>,
+    table_resync_requests: Vec<TableId>,
+    table_copy_progress: HashMap<TableId, TableCopyProgress>,
     table_state_conditions: Vec<(TableId, TableStateCondition, Arc<Notify>)>,
     method_call_notifiers: HashMap<StateStoreMethod, Vec<Arc<Notify>>>,
 }
@@ -58,6 +63,9 @@ impl TestStateStore {
     pub fn new() -> Self {
         let inner = Inner {
             table_replication_states: HashMap::new(),
+            snapshot_lsn: None,
+            table_resync_requests: Vec::new(),
+            table_copy_progress: HashMap::new(),
             table_state_conditions: Vec::new(),
             method_call_notifiers: HashMap::new(),
         };
@@ -72,6 +80,23 @@ impl TestStateStore {
         inner.table_replication_states.clone()
     }
 
+    pub async fn request_table_resync(&self, table_id: TableId) {
+        let mut inner = self.inner.write().await;
+        if !inner.table_resync_requests.contains(&table_id) {
+            inner.table_resync_requests.push(table_id);
+        }
+    }
+
+    pub async fn get_table_resync_requests(&self) -> Vec<TableId> {
+        let inner = self.inner.read().await;
+        inner.table_resync_requests.clone()
+    }
+
+    pub async fn get_table_copy_progress(&self) -> HashMap<TableId, TableCopyProgress> {
+        let inner = self.inner.read().await;
+        inner.table_copy_progress.clone()
+    }
+
     pub async fn notify_on_replication_state<F>(
         &self,
         table_id: TableId,
@@ -156,6 +181,50 @@ impl StateStore for TestStateStore {
             .await;
         Ok(())
     }
+
+    async fn delete_table_replication_state(
+        &self,
+        table_id: TableId,
+    ) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.table_replication_states.remove(&table_id);
+        inner
+            .dispatch_method_notification(StateStoreMethod::DeleteTableReplicationState)
+            .await;
+        Ok(())
+    }
+
+    async fn get_snapshot_lsn(&self) -> Result<Option<PgLsn>, StateStoreError> {
+        let inner = self.inner.read().await;
+        Ok(inner.snapshot_lsn)
+    }
+
+    async fn update_snapshot_lsn(&self, lsn: PgLsn) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.snapshot_lsn = Some(lsn);
+        Ok(())
+    }
+
+    async fn load_table_resync_requests(&self) -> Result<Vec<TableId>, StateStoreError> {
+        let inner = self.inner.read().await;
+        Ok(inner.table_resync_requests.clone())
+    }
+
+    async fn start_table_resync(&self, table_id: TableId) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.table_resync_requests.retain(|id| *id != table_id);
+        Ok(())
+    }
+
+    async fn update_table_copy_progress(
+        &self,
+        table_id: TableId,
+        progress: TableCopyProgress,
+    ) -> Result<(), StateStoreError> {
+        let mut inner = self.inner.write().await;
+        inner.table_copy_progress.insert(table_id, progress);
+        Ok(())
+    }
 }
 
 impl fmt::Debug for TestStateStore {
@@ -255,4 +324,38 @@ where
             .update_table_replication_state(table_id, state)
             .await
     }
+
+    async fn delete_table_replication_state(
+        &self,
+        table_id: TableId,
+    ) -> Result<(), StateStoreError> {
+        self.trigger_fault(&self.config.store_table_replication_state)?;
+        self.inner.delete_table_replication_state(table_id).await
+    }
+
+    async fn get_snapshot_lsn(&self) -> Result<Option<PgLsn>, StateStoreError> {
+        self.inner.get_snapshot_lsn().await
+    }
+
+    async fn update_snapshot_lsn(&self, lsn: PgLsn) -> Result<(), StateStoreError> {
+        self.inner.update_snapshot_lsn(lsn).await
+    }
+
+    async fn load_table_resync_requests(&self) -> Result<Vec<TableId>, StateStoreError> {
+        self.inner.load_table_resync_requests().await
+    }
+
+    async fn start_table_resync(&self, table_id: TableId) -> Result<(), StateStoreError> {
+        self.inner.start_table_resync(table_id).await
+    }
+
+    async fn update_table_copy_progress(
+        &self,
+        table_id: TableId,
+        progress: TableCopyProgress,
+    ) -> Result<(), StateStoreError> {
+        self.inner
+            .update_table_copy_progress(table_id, progress)
+            .await
+    }
 }
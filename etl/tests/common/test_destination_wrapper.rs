@@ -18,11 +18,14 @@ type TableRowCondition = Box<dyn Fn(&HashMap<TableId, Vec<TableRow>>) -> bool +
 struct Inner<D> {
     wrapped_destination: D,
     events: Vec<Event>,
+    event_batches: Vec<Vec<Event>>,
     table_schemas: Vec<TableSchema>,
     table_rows: HashMap<TableId, Vec<TableRow>>,
     event_conditions: Vec<(EventCondition, Arc<Notify>)>,
     table_schema_conditions: Vec<(SchemaCondition, Arc<Notify>)>,
     table_row_conditions: Vec<(TableRowCondition, Arc<Notify>)>,
+    events_unavailable: bool,
+    schema_changes_unsupported: bool,
 }
 
 impl<D> Inner<D> {
@@ -85,11 +88,14 @@ impl<D> TestDestinationWrapper<D> {
         let inner = Inner {
             wrapped_destination: destination,
             events: Vec::new(),
+            event_batches: Vec::new(),
             table_schemas: Vec::new(),
             table_rows: HashMap::new(),
             event_conditions: Vec::new(),
             table_schema_conditions: Vec::new(),
             table_row_conditions: Vec::new(),
+            events_unavailable: false,
+            schema_changes_unsupported: false,
         };
 
         Self {
@@ -114,6 +120,11 @@ impl<D> TestDestinationWrapper<D> {
         self.inner.read().await.events.clone()
     }
 
+    /// Get all events that have been written, grouped by the batch in which they were written
+    pub async fn get_event_batches(&self) -> Vec<Vec<Event>> {
+        self.inner.read().await.event_batches.clone()
+    }
+
     /// Wait for a specific condition on events
     pub async fn notify_on_events<F>(&self, condition: F) -> Arc<Notify>
     where
@@ -134,6 +145,16 @@ impl<D> TestDestinationWrapper<D> {
             .await
     }
 
+    /// Makes writes of events fail as if the destination was down, until it's set back to `false`
+    pub async fn set_events_unavailable(&self, events_unavailable: bool) {
+        self.inner.write().await.events_unavailable = events_unavailable;
+    }
+
+    /// Makes the destination reject the changes of table schemas, as if it didn't support them
+    pub async fn set_schema_changes_unsupported(&self, schema_changes_unsupported: bool) {
+        self.inner.write().await.schema_changes_unsupported = schema_changes_unsupported;
+    }
+
     /// Wait for a specific condition on schemas
     pub async fn notify_on_schemas<F>(&self, condition: F) -> Arc<Notify>
     where
@@ -185,6 +206,37 @@ impl<D: Destination + Send + Sync + Clone> Destination for TestDestinationWrappe
         result
     }
 
+    async fn write_table_schema_change(
+        &self,
+        before: TableSchema,
+        after: TableSchema,
+    ) -> Result<bool, DestinationError> {
+        let destination = {
+            let inner = self.inner.read().await;
+            if inner.schema_changes_unsupported {
+                return Ok(false);
+            }
+
+            inner.wrapped_destination.clone()
+        };
+
+        let result = destination
+            .write_table_schema_change(before, after.clone())
+            .await;
+
+        {
+            let mut inner = self.inner.write().await;
+            if let Ok(true) = result {
+                inner.table_schemas.retain(|s| s.id != after.id);
+                inner.table_schemas.push(after);
+            }
+
+            inner.check_conditions().await;
+        }
+
+        result
+    }
+
     async fn load_table_schemas(&self) -> Result<Vec<TableSchema>, DestinationError> {
         let destination = {
             let inner = self.inner.read().await;
@@ -227,6 +279,12 @@ impl<D: Destination + Send + Sync + Clone> Destination for TestDestinationWrappe
     async fn write_events(&self, events: Vec<Event>) -> Result<(), DestinationError> {
         let destination = {
             let inner = self.inner.read().await;
+            if inner.events_unavailable {
+                return Err(DestinationError::Unavailable(
+                    "events can't be written".to_string(),
+                ));
+            }
+
             inner.wrapped_destination.clone()
         };
 
@@ -235,7 +293,8 @@ impl<D: Destination + Send + Sync + Clone> Destination for TestDestinationWrappe
         {
             let mut inner = self.inner.write().await;
             if result.is_ok() {
-                inner.events.extend(events);
+                inner.events.extend(events.clone());
+                inner.event_batches.push(events);
             }
 
             inner.check_conditions().await;
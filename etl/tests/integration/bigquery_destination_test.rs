@@ -1,11 +1,14 @@
 use config::shared::BatchConfig;
 use etl::conversions::event::EventType;
 use etl::destination::base::Destination;
+use etl::destination::compatibility::TypeMismatch;
 use etl::encryption::bigquery::install_crypto_provider_once;
 use etl::pipeline::PipelineId;
 use etl::state::table::TableReplicationPhaseType;
+use postgres::schema::{ColumnSchema, TableName, TableSchema};
 use rand::random;
 use telemetry::init_test_tracing;
+use tokio_postgres::types::Type;
 
 use crate::common::bigquery::setup_bigquery_connection;
 use crate::common::database::spawn_database;
@@ -395,3 +398,96 @@ async fn test_table_truncate_with_batching() {
         vec![BigQueryOrder::new(2, "description_2"),]
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_compatibility_with_matching_table() {
+    init_test_tracing();
+    install_crypto_provider_once();
+
+    let bigquery_database = setup_bigquery_connection().await;
+    let destination = bigquery_database.build_destination().await;
+
+    let table_schema = TableSchema::new(
+        1,
+        TableName::new("test".to_string(), "compatible".to_string()),
+        vec![
+            ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+            ColumnSchema::new("name".to_string(), Type::TEXT, -1, true, false),
+            ColumnSchema::new("tags".to_string(), Type::TEXT_ARRAY, -1, true, false),
+        ],
+    );
+
+    // A table which doesn't exist yet is created with the right columns, so there is nothing to
+    // report.
+    let report = destination
+        .verify_table_compatibility(&table_schema)
+        .await
+        .unwrap();
+    assert!(report.is_none());
+
+    destination
+        .write_table_schema(table_schema.clone())
+        .await
+        .unwrap();
+
+    let report = destination
+        .verify_table_compatibility(&table_schema)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(report.is_compatible());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_compatibility_with_mismatching_table() {
+    init_test_tracing();
+    install_crypto_provider_once();
+
+    let bigquery_database = setup_bigquery_connection().await;
+    let destination = bigquery_database.build_destination().await;
+
+    let table_name = TableName::new("test".to_string(), "incompatible".to_string());
+    let destination_schema = TableSchema::new(
+        1,
+        table_name.clone(),
+        vec![
+            ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+            ColumnSchema::new("name".to_string(), Type::TEXT, -1, false, false),
+            ColumnSchema::new("age".to_string(), Type::TEXT, -1, true, false),
+        ],
+    );
+    destination
+        .write_table_schema(destination_schema)
+        .await
+        .unwrap();
+
+    // The source table has a new column, a column with another type and a column which became
+    // nullable.
+    let source_schema = TableSchema::new(
+        1,
+        table_name,
+        vec![
+            ColumnSchema::new("id".to_string(), Type::INT8, -1, false, true),
+            ColumnSchema::new("name".to_string(), Type::TEXT, -1, true, false),
+            ColumnSchema::new("age".to_string(), Type::INT4, -1, true, false),
+            ColumnSchema::new("created_at".to_string(), Type::TIMESTAMPTZ, -1, true, false),
+        ],
+    );
+    let report = destination
+        .verify_table_compatibility(&source_schema)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(!report.is_compatible());
+    assert_eq!(report.missing_columns, vec!["created_at".to_string()]);
+    assert_eq!(
+        report.type_mismatches,
+        vec![TypeMismatch {
+            column_name: "age".to_string(),
+            expected_type: "int64".to_string(),
+            actual_type: "string".to_string(),
+        }]
+    );
+    assert_eq!(report.nullability_conflicts, vec!["name".to_string()]);
+}
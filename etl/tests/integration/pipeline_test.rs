@@ -1,18 +1,44 @@
-use etl::conversions::event::EventType;
+use config::shared::{
+    AutoCreatePublicationConfig, BatchConfig, BatchFlushMode, ColumnFilterRule,
+    ConsistencyCheckConfig, CopyConfig, CopyFormat, DestinationDownConfig, HeartbeatConfig,
+    PublicationTableConfig, PublishOperation, ReplicationMode, ReplicationSlotConfig, RetryConfig,
+};
+use etl::concurrency::status::PipelineStatus;
+use etl::conversions::event::{Event, EventType, InsertEvent, MetadataEvent};
+use etl::conversions::table_row::{CopyConfigError, TableRow};
+use etl::conversions::{ArrayCell, Cell};
 use etl::destination::memory::MemoryDestination;
+use etl::metrics::encode_metrics;
 use etl::pipeline::{PipelineError, PipelineId};
+use etl::replication::apply::ApplyLoopError;
+use etl::replication::client::PgReplicationError;
+use etl::replication::destination_down::DestinationDownError;
 use etl::replication::slot::get_slot_name;
-use etl::state::table::TableReplicationPhaseType;
+use etl::state::store::base::StateStore;
+use etl::state::table::{TableCopyProgress, TableReplicationPhaseType};
+use etl::workers::apply::ApplyWorkerError;
 use etl::workers::base::{WorkerType, WorkerWaitError};
-use postgres::schema::ColumnSchema;
-use postgres::tokio::test_utils::TableModification;
+use postgres::schema::{ColumnSchema, TableName, TableSchema};
+use postgres::tokio::test_utils::{PgDatabase, TableModification, id_column_schema};
 use rand::random;
+use std::time::Duration;
 use telemetry::init_test_tracing;
-use tokio_postgres::types::Type;
+use tokio_postgres::Client;
+use tokio_postgres::types::{PgLsn, Type};
 
-use crate::common::database::spawn_database;
-use crate::common::event::{group_events_by_type, group_events_by_type_and_table_id};
-use crate::common::pipeline::create_pipeline;
+use crate::common::database::{spawn_database, spawn_database_with_encoding, test_table_name};
+use crate::common::event::{
+    get_metadata_events, group_events_by_type, group_events_by_type_and_table_id,
+};
+use crate::common::pipeline::{
+    create_pipeline, create_pipeline_with, create_pipeline_with_auto_create_publication,
+    create_pipeline_with_batch_flush_mode, create_pipeline_with_consistency_check,
+    create_pipeline_with_copy, create_pipeline_with_destination_down,
+    create_pipeline_with_heartbeat, create_pipeline_with_metadata_events,
+    create_pipeline_with_mode, create_pipeline_with_replication_slot,
+    create_pipeline_with_sequence_sync, create_pipeline_with_source_reconnect,
+    create_pipeline_with_start_lsn, test_slot_name,
+};
 use crate::common::state_store::{
     FaultConfig, FaultInjectingStateStore, FaultType, TestStateStore,
 };
@@ -481,26 +507,15 @@ async fn test_table_schema_copy_survives_restarts() {
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn test_table_copy() {
+async fn test_snapshot_lsn_is_stored_when_apply_slot_is_created() {
     init_test_tracing();
-    let mut database = spawn_database().await;
-    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
-
-    // Insert initial test data.
-    let rows_inserted = 10;
-    insert_mock_data(
-        &mut database,
-        &database_schema.users_schema().name,
-        &database_schema.orders_schema().name,
-        1..=rows_inserted,
-        false,
-    )
-    .await;
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
 
     let state_store = TestStateStore::new();
     let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
 
-    // Start pipeline from scratch.
+    // We start the pipeline from scratch, which creates the apply worker slot.
     let pipeline_id: PipelineId = random();
     let mut pipeline = create_pipeline(
         &database.config,
@@ -510,71 +525,72 @@ async fn test_table_copy() {
         destination.clone(),
     );
 
-    // Register notifications for table copy completion.
     let users_state_notify = state_store
         .notify_on_replication_phase(
             database_schema.users_schema().id,
-            TableReplicationPhaseType::SyncDone,
-        )
-        .await;
-    let orders_state_notify = state_store
-        .notify_on_replication_phase(
-            database_schema.orders_schema().id,
-            TableReplicationPhaseType::SyncDone,
+            TableReplicationPhaseType::Ready,
         )
         .await;
 
     pipeline.start().await.unwrap();
 
     users_state_notify.notified().await;
-    orders_state_notify.notified().await;
 
     pipeline.shutdown_and_wait().await.unwrap();
 
-    // Verify copied data.
-    let table_rows = destination.get_table_rows().await;
-    let users_table_rows = table_rows.get(&database_schema.users_schema().id).unwrap();
-    let orders_table_rows = table_rows.get(&database_schema.orders_schema().id).unwrap();
-    assert_eq!(users_table_rows.len(), rows_inserted);
-    assert_eq!(orders_table_rows.len(), rows_inserted);
-
-    // Verify age sum calculation.
-    let expected_age_sum = get_n_integers_sum(rows_inserted);
-    let age_sum =
-        get_users_age_sum_from_rows(&destination, database_schema.users_schema().id).await;
-    assert_eq!(age_sum, expected_age_sum);
+    let snapshot_lsn = state_store.get_snapshot_lsn().await.unwrap().unwrap();
 
-    // Check that the replication slots for the two tables have been removed.
-    let users_replication_slot = get_slot_name(
-        pipeline_id,
-        WorkerType::TableSync {
-            table_id: database_schema.users_schema().id,
-        },
-    )
-    .unwrap();
-    let orders_replication_slot = get_slot_name(
+    // We restart the pipeline, which reuses the slot and streams a change past the snapshot.
+    let mut pipeline = create_pipeline(
+        &database.config,
         pipeline_id,
-        WorkerType::TableSync {
-            table_id: database_schema.orders_schema().id,
-        },
-    )
-    .unwrap();
-    assert!(
-        !database
-            .replication_slot_exists(&users_replication_slot)
-            .await
-            .unwrap()
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
     );
-    assert!(
-        !database
-            .replication_slot_exists(&orders_replication_slot)
-            .await
-            .unwrap()
+
+    pipeline.start().await.unwrap();
+
+    let insert_events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+
+    database
+        .insert_values(
+            database_schema.users_schema().name,
+            &["name", "age"],
+            &[&"user_1", &1i32],
+        )
+        .await
+        .unwrap();
+
+    insert_events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // The snapshot LSN is only stored when the slot is created, while the slot keeps confirming
+    // the streamed changes.
+    assert_eq!(
+        state_store.get_snapshot_lsn().await.unwrap(),
+        Some(snapshot_lsn)
     );
+    let slot_name = get_slot_name(pipeline_id, WorkerType::Apply).unwrap();
+    let confirmed_flush_lsn: PgLsn = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one(
+            "select confirmed_flush_lsn from pg_replication_slots where slot_name = $1",
+            &[&slot_name],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert!(confirmed_flush_lsn >= snapshot_lsn);
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn test_table_copy_and_sync() {
+async fn test_table_copy() {
     init_test_tracing();
     let mut database = spawn_database().await;
     let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
@@ -590,6 +606,18 @@ async fn test_table_copy_and_sync() {
     )
     .await;
 
+    // Only the users table is analyzed, so the number of rows of the orders table isn't estimated.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(&format!(
+            "analyze {}",
+            database_schema.users_schema().name.as_quoted_identifier()
+        ))
+        .await
+        .unwrap();
+
     let state_store = TestStateStore::new();
     let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
 
@@ -603,7 +631,7 @@ async fn test_table_copy_and_sync() {
         destination.clone(),
     );
 
-    // Register notifications for initial table copy completion.
+    // Register notifications for table copy completion.
     let users_state_notify = state_store
         .notify_on_replication_phase(
             database_schema.users_schema().id,
@@ -622,52 +650,9 @@ async fn test_table_copy_and_sync() {
     users_state_notify.notified().await;
     orders_state_notify.notified().await;
 
-    // Insert additional data to test streaming.
-    insert_mock_data(
-        &mut database,
-        &database_schema.users_schema().name,
-        &database_schema.orders_schema().name,
-        (rows_inserted + 1)..=(rows_inserted + 2),
-        true,
-    )
-    .await;
-
-    // Register notifications for ready state.
-    let users_state_notify = state_store
-        .notify_on_replication_phase(
-            database_schema.users_schema().id,
-            TableReplicationPhaseType::Ready,
-        )
-        .await;
-    let orders_state_notify = state_store
-        .notify_on_replication_phase(
-            database_schema.orders_schema().id,
-            TableReplicationPhaseType::Ready,
-        )
-        .await;
-
-    // We wait for all the inserts to be received.
-    let events_notify = destination
-        .wait_for_events_count(vec![(EventType::Insert, 8)])
-        .await;
-
-    // Insert more data to test apply worker processing.
-    insert_mock_data(
-        &mut database,
-        &database_schema.users_schema().name,
-        &database_schema.orders_schema().name,
-        (rows_inserted + 3)..=(rows_inserted + 4),
-        true,
-    )
-    .await;
-
-    users_state_notify.notified().await;
-    orders_state_notify.notified().await;
-    events_notify.notified().await;
-
     pipeline.shutdown_and_wait().await.unwrap();
 
-    // Verify initial table copy data.
+    // Verify copied data.
     let table_rows = destination.get_table_rows().await;
     let users_table_rows = table_rows.get(&database_schema.users_schema().id).unwrap();
     let orders_table_rows = table_rows.get(&database_schema.orders_schema().id).unwrap();
@@ -680,43 +665,33 @@ async fn test_table_copy_and_sync() {
         get_users_age_sum_from_rows(&destination, database_schema.users_schema().id).await;
     assert_eq!(age_sum, expected_age_sum);
 
-    // Get all the events that were produced to the destination and assert them individually by table
-    // since the only thing we are guaranteed is that the order of operations is preserved within the
-    // same table but not across tables given the asynchronous nature of the pipeline (e.g., we could
-    // start streaming earlier on a table for data which was inserted after another table which was
-    // modified before this one)
-    let events = destination.get_events().await;
-    let grouped_events = group_events_by_type_and_table_id(&events);
-    let users_inserts = grouped_events
-        .get(&(EventType::Insert, database_schema.users_schema().id))
-        .unwrap();
-    let orders_inserts = grouped_events
-        .get(&(EventType::Insert, database_schema.orders_schema().id))
-        .unwrap();
+    // Verify the reported copy progress.
+    let copy_progress = state_store.get_table_copy_progress().await;
+    assert_eq!(
+        copy_progress[&database_schema.users_schema().id],
+        TableCopyProgress {
+            rows_copied: rows_inserted as u64,
+            estimated_rows: Some(rows_inserted as u64),
+        }
+    );
+    assert_eq!(
+        copy_progress[&database_schema.orders_schema().id],
+        TableCopyProgress {
+            rows_copied: rows_inserted as u64,
+            estimated_rows: None,
+        }
+    );
 
-    // Build expected events for verification
-    let expected_users_inserts = build_expected_users_inserts(
-        11,
+    // Verify the copy counters, which the replicator serves to be scraped.
+    let metrics = String::from_utf8(encode_metrics().unwrap()).unwrap();
+    for table_id in [
         database_schema.users_schema().id,
-        vec![
-            ("user_11", 11),
-            ("user_12", 12),
-            ("user_13", 13),
-            ("user_14", 14),
-        ],
-    );
-    let expected_orders_inserts = build_expected_orders_inserts(
-        11,
         database_schema.orders_schema().id,
-        vec![
-            "description_11",
-            "description_12",
-            "description_13",
-            "description_14",
-        ],
-    );
-    assert_eq!(*users_inserts, expected_users_inserts);
-    assert_eq!(*orders_inserts, expected_orders_inserts);
+    ] {
+        assert!(metrics.contains(&format!(
+            "rows_copied_total{{table=\"{table_id}\"}} {rows_inserted}"
+        )));
+    }
 
     // Check that the replication slots for the two tables have been removed.
     let users_replication_slot = get_slot_name(
@@ -748,226 +723,3452 @@ async fn test_table_copy_and_sync() {
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn test_table_copy_and_sync_with_changed_schema_in_table_sync_worker() {
+async fn test_table_copy_with_custom_copy_options() {
     init_test_tracing();
     let database = spawn_database().await;
-    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
 
-    // Insert data in the table.
+    let table_name = test_table_name("notes");
+    let table_id = database
+        .create_table(table_name.clone(), &[("note", "text")])
+        .await
+        .unwrap();
     database
-        .insert_values(
-            database_schema.orders_schema().name.clone(),
-            &["description"],
-            &[&"description_1"],
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "insert into {} (note) values ('a,b'), (null), ('tab\there')",
+                table_name.as_quoted_identifier()
+            ),
+            &[],
         )
         .await
         .unwrap();
+    let publication_name = "test_pub".to_string();
+    database
+        .create_publication(&publication_name, &[table_name.clone()])
+        .await
+        .unwrap();
 
     let state_store = TestStateStore::new();
     let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
 
-    // Start pipeline from scratch.
+    // The delimiter and the null string appear in the copied values, so they must round-trip.
+    let copy = CopyConfig {
+        delimiter: ',',
+        null: "NULL".to_string(),
+        ..CopyConfig::default()
+    };
     let pipeline_id: PipelineId = random();
-    let mut pipeline = create_pipeline(
+    let mut pipeline = create_pipeline_with_copy(
         &database.config,
         pipeline_id,
-        database_schema.publication_name(),
+        publication_name,
         state_store.clone(),
         destination.clone(),
+        copy,
     );
 
-    // Register notifications for initial table copy completion.
-    let orders_state_notify = state_store
-        .notify_on_replication_phase(
-            database_schema.orders_schema().id,
-            TableReplicationPhaseType::FinishedCopy,
-        )
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::SyncDone)
         .await;
 
     pipeline.start().await.unwrap();
 
-    orders_state_notify.notified().await;
+    table_state_notify.notified().await;
 
-    // Register notification for the skipped state.
-    let orders_state_notify = state_store
-        .notify_on_replication_phase(
-            database_schema.orders_schema().id,
-            TableReplicationPhaseType::Skipped,
-        )
-        .await;
+    pipeline.shutdown_and_wait().await.unwrap();
 
-    // Change the schema of orders by adding a new column.
+    let table_rows = destination.get_table_rows().await;
+    let mut notes = table_rows
+        .get(&table_id)
+        .unwrap()
+        .iter()
+        .map(|row| row.values[1].clone())
+        .collect::<Vec<_>>();
+    notes.sort_by_key(|note| format!("{note:?}"));
+    assert_eq!(
+        notes,
+        vec![
+            Cell::Null(Type::TEXT),
+            Cell::String("a,b".to_string()),
+            Cell::String("tab\there".to_string()),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_with_csv_format() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("notes");
+    let table_id = database
+        .create_table(table_name.clone(), &[("note", "text")])
+        .await
+        .unwrap();
     database
-        .alter_table(
-            database_schema.orders_schema().name.clone(),
-            &[TableModification::AddColumn {
-                name: "date",
-                data_type: "integer",
-            }],
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "insert into {} (note) values ('a,b'), ('say \"hi\"'), (E'two\nlines'), (''), (null)",
+                table_name.as_quoted_identifier()
+            ),
+            &[],
         )
         .await
         .unwrap();
-
-    // Insert new data in the table.
+    let publication_name = "test_pub".to_string();
     database
-        .insert_values(
-            database_schema.orders_schema().name.clone(),
-            &["description", "date"],
-            &[&"description_with_date", &(10i32)],
-        )
+        .create_publication(&publication_name, &[table_name.clone()])
         .await
         .unwrap();
 
-    orders_state_notify.notified().await;
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
 
-    pipeline.shutdown_and_wait().await.unwrap();
+    // With the default null string of the csv format, the empty string is only told apart from
+    // the null by its quotes.
+    let copy = CopyConfig {
+        format: CopyFormat::Csv,
+        delimiter: ',',
+        null: String::new(),
+        ..CopyConfig::default()
+    };
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_copy(
+        &database.config,
+        pipeline_id,
+        publication_name,
+        state_store.clone(),
+        destination.clone(),
+        copy,
+    );
 
-    // We assert that the schema is the initial one.
-    let table_schemas = destination.get_table_schemas().await;
-    assert_eq!(table_schemas.len(), 1);
-    assert_eq!(table_schemas[0], database_schema.orders_schema());
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::SyncDone)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let table_rows = destination.get_table_rows().await;
+    let mut notes = table_rows
+        .get(&table_id)
+        .unwrap()
+        .iter()
+        .map(|row| row.values[1].clone())
+        .collect::<Vec<_>>();
+    notes.sort_by_key(|note| format!("{note:?}"));
+    assert_eq!(
+        notes,
+        vec![
+            Cell::Null(Type::TEXT),
+            Cell::String(String::new()),
+            Cell::String("a,b".to_string()),
+            Cell::String("say \"hi\"".to_string()),
+            Cell::String("two\nlines".to_string()),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_with_binary_format() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("notes");
+    let table_id = database
+        .create_table(
+            table_name.clone(),
+            &[("note", "text"), ("tags", "int4[]"), ("data", "bytea")],
+        )
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "insert into {} (note, tags, data) values ('tab\there', '{{1,null,3}}', '\\x00ff'), (null, null, null)",
+                table_name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+    let publication_name = "test_pub".to_string();
+    database
+        .create_publication(&publication_name, &[table_name.clone()])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let copy = CopyConfig {
+        format: CopyFormat::Binary,
+        ..CopyConfig::default()
+    };
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_copy(
+        &database.config,
+        pipeline_id,
+        publication_name,
+        state_store.clone(),
+        destination.clone(),
+        copy,
+    );
+
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::SyncDone)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let table_rows = destination.get_table_rows().await;
+    let mut values = table_rows
+        .get(&table_id)
+        .unwrap()
+        .iter()
+        .map(|row| row.values[1..].to_vec())
+        .collect::<Vec<_>>();
+    values.sort_by_key(|values| format!("{values:?}"));
+    assert_eq!(
+        values,
+        vec![
+            vec![
+                Cell::Null(Type::TEXT),
+                Cell::Null(Type::INT4_ARRAY),
+                Cell::Null(Type::BYTEA),
+            ],
+            vec![
+                Cell::String("tab\there".to_string()),
+                Cell::Array(ArrayCell::I32(vec![Some(1), None, Some(3)])),
+                Cell::Bytes(vec![0x00, 0xff]),
+            ],
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_with_consistency_check() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("notes");
+    let table_id = database
+        .create_table(table_name.clone(), &[("note", "text")])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "insert into {} (note) select case when g % 3 = 0 then null else E'note\\t' || g end from generate_series(1, 50) g",
+                table_name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+    let publication_name = "test_pub".to_string();
+    database
+        .create_publication(&publication_name, &[table_name.clone()])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_consistency_check(
+        &database.config,
+        pipeline_id,
+        publication_name,
+        state_store.clone(),
+        destination.clone(),
+        ConsistencyCheckConfig { range_size: 10 },
+    );
+
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::SyncDone)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // The copied rows match the source, so the table is synced.
+    let table_rows = destination.get_table_rows().await;
+    assert_eq!(table_rows.get(&table_id).unwrap().len(), 50);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_and_sync_from_non_utf8_database() {
+    init_test_tracing();
+    let database = spawn_database_with_encoding("LATIN1").await;
+
+    let table_name = test_table_name("notes");
+    let table_id = database
+        .create_table(table_name.clone(), &[("note", "text not null")])
+        .await
+        .unwrap();
+    let insert_note = format!(
+        "insert into {} (note) values ($1)",
+        table_name.as_quoted_identifier()
+    );
+    // The value is stored in LATIN1, where `é` is a single byte which isn't valid UTF-8.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(&insert_note, &[&"café"])
+        .await
+        .unwrap();
+    let publication_name = "test_pub".to_string();
+    database
+        .create_publication(&publication_name, &[table_name.clone()])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        publication_name,
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::SyncDone)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    let insert_event_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(&insert_note, &[&"déjà vu"])
+        .await
+        .unwrap();
+
+    insert_event_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Both the copied and the streamed values are transcoded to UTF-8 by the server.
+    let table_rows = destination.get_table_rows().await;
+    let copied_rows = table_rows.get(&table_id).unwrap();
+    assert_eq!(copied_rows.len(), 1);
+    assert_eq!(copied_rows[0].values[1], Cell::String("café".to_string()));
+
+    let events = destination.get_events().await;
+    let streamed_rows = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Insert(insert) => Some(&insert.table_row),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(streamed_rows.len(), 1);
+    assert_eq!(
+        streamed_rows[0].values[1],
+        Cell::String("déjà vu".to_string())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_fails_when_copy_options_cant_be_parsed() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Rows copied in another encoding than UTF8 can't be parsed, so the copy is rejected before
+    // any work is done.
+    let copy = CopyConfig {
+        encoding: "LATIN1".to_string(),
+        ..CopyConfig::default()
+    };
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_copy(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        copy,
+    );
+
+    let err = pipeline.start().await.unwrap_err();
+    assert!(matches!(
+        err,
+        PipelineError::InvalidCopyConfig(CopyConfigError::UnsupportedEncoding(encoding))
+            if encoding == "LATIN1"
+    ));
+    assert!(destination.get_table_schemas().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_and_sync() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    // Insert initial test data.
+    let rows_inserted = 10;
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=rows_inserted,
+        false,
+    )
+    .await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline from scratch.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    // Register notifications for initial table copy completion.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::SyncDone,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::SyncDone,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    // Insert additional data to test streaming.
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        (rows_inserted + 1)..=(rows_inserted + 2),
+        true,
+    )
+    .await;
+
+    // Register notifications for ready state.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    // We wait for all the inserts to be received.
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 8)])
+        .await;
+
+    // Insert more data to test apply worker processing.
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        (rows_inserted + 3)..=(rows_inserted + 4),
+        true,
+    )
+    .await;
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Verify initial table copy data.
+    let table_rows = destination.get_table_rows().await;
+    let users_table_rows = table_rows.get(&database_schema.users_schema().id).unwrap();
+    let orders_table_rows = table_rows.get(&database_schema.orders_schema().id).unwrap();
+    assert_eq!(users_table_rows.len(), rows_inserted);
+    assert_eq!(orders_table_rows.len(), rows_inserted);
+
+    // Verify age sum calculation.
+    let expected_age_sum = get_n_integers_sum(rows_inserted);
+    let age_sum =
+        get_users_age_sum_from_rows(&destination, database_schema.users_schema().id).await;
+    assert_eq!(age_sum, expected_age_sum);
+
+    // Get all the events that were produced to the destination and assert them individually by table
+    // since the only thing we are guaranteed is that the order of operations is preserved within the
+    // same table but not across tables given the asynchronous nature of the pipeline (e.g., we could
+    // start streaming earlier on a table for data which was inserted after another table which was
+    // modified before this one)
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let users_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.users_schema().id))
+        .unwrap();
+    let orders_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.orders_schema().id))
+        .unwrap();
+
+    // Build expected events for verification
+    let expected_users_inserts = build_expected_users_inserts(
+        11,
+        database_schema.users_schema().id,
+        vec![
+            ("user_11", 11),
+            ("user_12", 12),
+            ("user_13", 13),
+            ("user_14", 14),
+        ],
+    );
+    let expected_orders_inserts = build_expected_orders_inserts(
+        11,
+        database_schema.orders_schema().id,
+        vec![
+            "description_11",
+            "description_12",
+            "description_13",
+            "description_14",
+        ],
+    );
+    assert_eq!(*users_inserts, expected_users_inserts);
+    assert_eq!(*orders_inserts, expected_orders_inserts);
+
+    // Check that the replication slots for the two tables have been removed.
+    let users_replication_slot = get_slot_name(
+        pipeline_id,
+        WorkerType::TableSync {
+            table_id: database_schema.users_schema().id,
+        },
+    )
+    .unwrap();
+    let orders_replication_slot = get_slot_name(
+        pipeline_id,
+        WorkerType::TableSync {
+            table_id: database_schema.orders_schema().id,
+        },
+    )
+    .unwrap();
+    assert!(
+        !database
+            .replication_slot_exists(&users_replication_slot)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !database
+            .replication_slot_exists(&orders_replication_slot)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_and_sync_of_same_named_tables_in_different_schemas() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let client = database.client.as_ref().unwrap();
+    client
+        .execute("create schema analytics", &[])
+        .await
+        .unwrap();
+
+    let test_events = test_table_name("events");
+    let analytics_events = TableName::new("analytics".to_string(), "events".to_string());
+    let mut table_ids = vec![];
+    for table_name in [&test_events, &analytics_events] {
+        let table_id = database
+            .create_table(table_name.clone(), &[("name", "text not null")])
+            .await
+            .unwrap();
+        client
+            .execute(
+                &format!(
+                    "insert into {} (name) values ($1)",
+                    table_name.as_quoted_identifier()
+                ),
+                &[&format!("copied into {}", table_name.schema)],
+            )
+            .await
+            .unwrap();
+        table_ids.push(table_id);
+    }
+    let (test_table_id, analytics_table_id) = (table_ids[0], table_ids[1]);
+    let publication_name = "test_pub".to_string();
+    database
+        .create_publication(
+            &publication_name,
+            &[test_events.clone(), analytics_events.clone()],
+        )
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        publication_name,
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let test_state_notify = state_store
+        .notify_on_replication_phase(test_table_id, TableReplicationPhaseType::Ready)
+        .await;
+    let analytics_state_notify = state_store
+        .notify_on_replication_phase(analytics_table_id, TableReplicationPhaseType::Ready)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    test_state_notify.notified().await;
+    analytics_state_notify.notified().await;
+
+    let insert_events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+    client
+        .execute(
+            &format!(
+                "insert into {} (name) values ('streamed into analytics')",
+                analytics_events.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+
+    insert_events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // The destination receives the schema of each table with its own schema name.
+    let mut table_schemas = destination.get_table_schemas().await;
+    table_schemas.sort_by_key(|table_schema| table_schema.id);
+    let mut expected_table_names = vec![
+        (test_table_id, test_events),
+        (analytics_table_id, analytics_events),
+    ];
+    expected_table_names.sort_by_key(|(table_id, _)| *table_id);
+    assert_eq!(
+        table_schemas
+            .into_iter()
+            .map(|table_schema| (table_schema.id, table_schema.name))
+            .collect::<Vec<_>>(),
+        expected_table_names
+    );
+
+    // The rows of each table are kept apart.
+    let table_rows = destination.get_table_rows().await;
+    assert_eq!(
+        table_rows[&test_table_id][0].values[1],
+        Cell::String("copied into test".to_string())
+    );
+    assert_eq!(
+        table_rows[&analytics_table_id][0].values[1],
+        Cell::String("copied into analytics".to_string())
+    );
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let analytics_inserts = &grouped_events[&(EventType::Insert, analytics_table_id)];
+    assert_eq!(analytics_inserts.len(), 1);
+    assert!(!grouped_events.contains_key(&(EventType::Insert, test_table_id)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_resync_copies_the_table_again_while_streaming() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+    let users_table_id = database_schema.users_schema().id;
+    let orders_table_id = database_schema.orders_schema().id;
+
+    // Insert initial test data.
+    let rows_inserted = 10;
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=rows_inserted,
+        false,
+    )
+    .await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline from scratch.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(users_table_id, TableReplicationPhaseType::SyncDone)
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(orders_table_id, TableReplicationPhaseType::SyncDone)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    // We stream some changes, so that the tables become ready.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(users_table_id, TableReplicationPhaseType::Ready)
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(orders_table_id, TableReplicationPhaseType::Ready)
+        .await;
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 4)])
+        .await;
+
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        (rows_inserted + 1)..=(rows_inserted + 2),
+        true,
+    )
+    .await;
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+    events_notify.notified().await;
+
+    // We resync the users table, which is copied again while the orders table keeps streaming.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(users_table_id, TableReplicationPhaseType::SyncDone)
+        .await;
+
+    state_store.request_table_resync(users_table_id).await;
+
+    users_state_notify.notified().await;
+
+    // The changes of both tables are streamed after the resync.
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 8)])
+        .await;
+
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        (rows_inserted + 3)..=(rows_inserted + 4),
+        true,
+    )
+    .await;
+
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // The users table was truncated and copied again with the rows streamed before the resync.
+    let table_rows = destination.get_table_rows().await;
+    let users_table_rows = table_rows.get(&users_table_id).unwrap();
+    let orders_table_rows = table_rows.get(&orders_table_id).unwrap();
+    assert_eq!(users_table_rows.len(), rows_inserted + rows_inserted + 2);
+    assert_eq!(orders_table_rows.len(), rows_inserted);
+
+    let events = destination.get_events().await;
+    let truncate_events: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Truncate(event) => Some(event.rel_ids.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(truncate_events, vec![vec![users_table_id]]);
+
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let users_inserts = grouped_events
+        .get(&(EventType::Insert, users_table_id))
+        .unwrap();
+    let expected_users_inserts = build_expected_users_inserts(
+        11,
+        users_table_id,
+        vec![
+            ("user_11", 11),
+            ("user_12", 12),
+            ("user_13", 13),
+            ("user_14", 14),
+        ],
+    );
+    assert_eq!(*users_inserts, expected_users_inserts);
+
+    // The progress of the resync is reported once the copy is done.
+    assert!(state_store.get_table_resync_requests().await.is_empty());
+    let copy_progress = state_store.get_table_copy_progress().await;
+    assert_eq!(
+        copy_progress[&users_table_id].rows_copied,
+        (rows_inserted + 2) as u64
+    );
+    assert_eq!(
+        copy_progress[&orders_table_id].rows_copied,
+        rows_inserted as u64
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_stream_only_without_initial_snapshot() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    // Insert data which is assumed to be already seeded in the destination.
+    let rows_inserted = 10;
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=rows_inserted,
+        false,
+    )
+    .await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline from scratch, skipping the initial snapshot.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_mode(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        ReplicationMode::StreamOnly,
+        true,
+    );
+
+    // Register notifications for the end of the table sync.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::SyncDone,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::SyncDone,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    // Register notifications for ready state.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    // We wait for all the inserts to be received.
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 4)])
+        .await;
+
+    // Insert additional data to test streaming.
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        (rows_inserted + 1)..=(rows_inserted + 2),
+        true,
+    )
+    .await;
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Verify that the schemas were written but that no rows were copied.
+    let table_schemas = destination.get_table_schemas().await;
+    assert!(
+        table_schemas
+            .iter()
+            .any(|schema| schema.id == database_schema.users_schema().id)
+    );
+    assert!(
+        table_schemas
+            .iter()
+            .any(|schema| schema.id == database_schema.orders_schema().id)
+    );
+    let table_rows = destination.get_table_rows().await;
+    assert!(table_rows.values().all(|table_rows| table_rows.is_empty()));
+
+    // Verify that only the changes made after the slot creation were streamed.
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let users_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.users_schema().id))
+        .unwrap();
+    let orders_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.orders_schema().id))
+        .unwrap();
+
+    let expected_users_inserts = build_expected_users_inserts(
+        11,
+        database_schema.users_schema().id,
+        vec![("user_11", 11), ("user_12", 12)],
+    );
+    let expected_orders_inserts = build_expected_orders_inserts(
+        11,
+        database_schema.orders_schema().id,
+        vec!["description_11", "description_12"],
+    );
+    assert_eq!(*users_inserts, expected_users_inserts);
+    assert_eq!(*orders_inserts, expected_orders_inserts);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_is_flushed_as_one_batch() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline from scratch, flushing on transaction boundaries with a batch size of 1 which
+    // would otherwise split the transaction.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_batch_flush_mode(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        BatchConfig {
+            max_size: 1,
+            max_fill_ms: 1000,
+        },
+        BatchFlushMode::TransactionBoundary,
+    );
+
+    // Register notifications for ready state.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    // We wait for all the inserts to be received.
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 6)])
+        .await;
+
+    // Insert multiple rows in a single transaction.
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=3,
+        true,
+    )
+    .await;
+
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Verify that the whole transaction was written in a single batch.
+    let event_batches = destination.get_event_batches().await;
+    let transaction_batch = event_batches
+        .iter()
+        .find(|events| events.iter().any(|event| matches!(event, Event::Insert(_))))
+        .unwrap();
+    // Relation events are sent by Postgres within the transaction before the first change of each
+    // table, so we do not check them.
+    let event_types: Vec<_> = transaction_batch
+        .iter()
+        .map(EventType::from)
+        .filter(|event_type| *event_type != EventType::Relation)
+        .collect();
+
+    let mut expected_event_types = vec![EventType::Begin];
+    expected_event_types.extend(std::iter::repeat_n(EventType::Insert, 6));
+    expected_event_types.push(EventType::Commit);
+    assert_eq!(event_types, expected_event_types);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_small_transactions_are_coalesced_into_fewer_batches() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline from scratch, coalescing transactions until a batch holds 9 events. The fill
+    // time is long enough for the batches to only be flushed because of their size.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_batch_flush_mode(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        BatchConfig {
+            max_size: 9,
+            max_fill_ms: 10_000,
+        },
+        BatchFlushMode::CoalescedTransactions,
+    );
+
+    // Register notifications for ready state.
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    // We wait for all the inserts to be received.
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 12)])
+        .await;
+
+    // Insert each row in its own transaction, alternating between the two tables.
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=6,
+        false,
+    )
+    .await;
+
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Verify that the 12 transactions were written in fewer batches, each ending with a whole
+    // transaction.
+    let event_batches = destination.get_event_batches().await;
+    let transaction_batches: Vec<_> = event_batches
+        .iter()
+        .filter(|events| events.iter().any(|event| matches!(event, Event::Insert(_))))
+        .collect();
+    assert!(transaction_batches.len() < 12);
+    for events in transaction_batches {
+        assert!(matches!(events.last(), Some(Event::Commit(_))));
+    }
+
+    // Verify that the rows of each table were received in order.
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let users_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.users_schema().id))
+        .unwrap();
+    let orders_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.orders_schema().id))
+        .unwrap();
+
+    let expected_users_inserts = build_expected_users_inserts(
+        1,
+        database_schema.users_schema().id,
+        vec![
+            ("user_1", 1),
+            ("user_2", 2),
+            ("user_3", 3),
+            ("user_4", 4),
+            ("user_5", 5),
+            ("user_6", 6),
+        ],
+    );
+    let expected_orders_inserts = build_expected_orders_inserts(
+        1,
+        database_schema.orders_schema().id,
+        vec![
+            "description_1",
+            "description_2",
+            "description_3",
+            "description_4",
+            "description_5",
+            "description_6",
+        ],
+    );
+    assert_eq!(*users_inserts, expected_users_inserts);
+    assert_eq!(*orders_inserts, expected_orders_inserts);
+}
+
+fn auto_create_publication_config(table_names: &[&TableName]) -> AutoCreatePublicationConfig {
+    AutoCreatePublicationConfig {
+        tables: table_names
+            .iter()
+            .map(|table_name| PublicationTableConfig {
+                schema: table_name.schema.clone(),
+                name: table_name.name.clone(),
+                columns: None,
+                row_filter: None,
+            })
+            .collect(),
+        publish: vec![
+            PublishOperation::Insert,
+            PublishOperation::Update,
+            PublishOperation::Delete,
+        ],
+        publish_via_partition_root: false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unchanged_toasted_values_of_updates_are_not_overwritten() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("documents");
+    let table_id = database
+        .create_table(
+            table_name.clone(),
+            &[("payload", "text not null"), ("version", "bigint not null")],
+        )
+        .await
+        .unwrap();
+    // Without compression, the payload is large enough to be stored out of line.
+    database
+        .alter_table(
+            table_name.clone(),
+            &[TableModification::AlterColumn {
+                name: "payload",
+                alteration: "set storage external",
+            }],
+        )
+        .await
+        .unwrap();
+    let payload = "x".repeat(10_000);
+    database
+        .insert_values(
+            table_name.clone(),
+            &["payload", "version"],
+            &[&payload, &1i64],
+        )
+        .await
+        .unwrap();
+    database
+        .create_publication("test_pub_toast", &[table_name.clone()])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        "test_pub_toast".to_string(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::Ready)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    let update_event_notify = destination
+        .wait_for_events_count(vec![(EventType::Update, 1)])
+        .await;
+
+    database
+        .update_values(table_name.clone(), &["version"], &["2"])
+        .await
+        .unwrap();
+
+    update_event_notify.notified().await;
+
+    // With the entire old row sent, the unchanged payload is taken from it.
+    let update_event_notify = destination
+        .wait_for_events_count(vec![(EventType::Update, 2)])
+        .await;
+
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "alter table {} replica identity full",
+                table_name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+    database
+        .update_values(table_name.clone(), &["version"], &["3"])
+        .await
+        .unwrap();
+
+    update_event_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let events = destination.get_events().await;
+    let updated_rows = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Update(update) => Some(&update.table_row),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(updated_rows.len(), 2);
+    assert_eq!(
+        updated_rows[0].values,
+        vec![Cell::I64(1), Cell::UnchangedToast(Type::TEXT), Cell::I64(2)]
+    );
+    assert_eq!(
+        updated_rows[1].values,
+        vec![Cell::I64(1), Cell::String(payload), Cell::I64(3)]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_truncates_are_sent_to_the_destination_with_their_options() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    let truncate_events_notify = destination
+        .wait_for_events_count(vec![(EventType::Truncate, 2)])
+        .await;
+
+    let client = database.client.as_ref().unwrap();
+    client
+        .execute(
+            &format!(
+                "truncate table {} restart identity",
+                database_schema.users_schema().name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            &format!(
+                "truncate table {}, {} cascade",
+                database_schema.users_schema().name.as_quoted_identifier(),
+                database_schema.orders_schema().name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+
+    truncate_events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let events = destination.get_events().await;
+    let truncate_events: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Truncate(event) => Some(event),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(truncate_events.len(), 2);
+
+    assert_eq!(
+        truncate_events[0].rel_ids,
+        vec![database_schema.users_schema().id]
+    );
+    assert!(truncate_events[0].restart_identity());
+    assert!(!truncate_events[0].cascade());
+
+    let mut rel_ids = truncate_events[1].rel_ids.clone();
+    rel_ids.sort();
+    let mut expected_rel_ids = vec![
+        database_schema.users_schema().id,
+        database_schema.orders_schema().id,
+    ];
+    expected_rel_ids.sort();
+    assert_eq!(rel_ids, expected_rel_ids);
+    assert!(!truncate_events[1].restart_identity());
+    assert!(truncate_events[1].cascade());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_creates_missing_publication() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("users");
+    let table_id = database
+        .create_table(table_name.clone(), &[("name", "text not null")])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline from scratch, without creating the publication beforehand.
+    let publication_name = "test_auto_pub";
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_auto_create_publication(
+        &database.config,
+        pipeline_id,
+        publication_name.to_string(),
+        state_store.clone(),
+        destination.clone(),
+        auto_create_publication_config(&[&table_name]),
+    );
+
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::Ready)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Verify that the publication was created with the requested tables and operations.
+    let client = database.client.as_ref().unwrap();
+    let published_tables = client
+        .query(
+            "select schemaname, tablename from pg_publication_tables where pubname = $1",
+            &[&publication_name],
+        )
+        .await
+        .unwrap();
+    assert_eq!(published_tables.len(), 1);
+    assert_eq!(published_tables[0].get::<_, String>(0), table_name.schema);
+    assert_eq!(published_tables[0].get::<_, String>(1), table_name.name);
+
+    let publication = client
+        .query_one(
+            "select pubinsert, pubupdate, pubdelete, pubtruncate from pg_publication where pubname = $1",
+            &[&publication_name],
+        )
+        .await
+        .unwrap();
+    assert!(publication.get::<_, bool>(0));
+    assert!(publication.get::<_, bool>(1));
+    assert!(publication.get::<_, bool>(2));
+    assert!(!publication.get::<_, bool>(3));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_reuses_existing_publication() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline with a publication which already includes the requested table.
+    let users_table_name = database_schema.users_schema().name;
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_auto_create_publication(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        auto_create_publication_config(&[&users_table_name]),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Verify that the existing publication was left untouched.
+    let published_tables_count: i64 = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one(
+            "select count(*) from pg_publication_tables where pubname = $1",
+            &[&database_schema.publication_name()],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(published_tables_count, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_fails_when_existing_publication_misses_tables() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
+
+    let users_table_name = test_table_name("users");
+    database
+        .create_table(users_table_name.clone(), &[("name", "text not null")])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start pipeline with a publication which doesn't include the requested table.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_auto_create_publication(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        auto_create_publication_config(&[&users_table_name]),
+    );
+
+    let err = pipeline.start().await.unwrap_err();
+    assert!(matches!(err, PipelineError::PublicationMissingTables(..)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_publishes_only_the_filtered_columns() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("documents");
+    let table_id = database
+        .create_table(
+            table_name.clone(),
+            &[("title", "text not null"), ("content", "bytea")],
+        )
+        .await
+        .unwrap();
+    database
+        .insert_values(
+            table_name.clone(),
+            &["title", "content"],
+            &[&"copied", &vec![0u8; 1024]],
+        )
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start the pipeline with a publication which leaves out the large column.
+    let mut auto_create_publication = auto_create_publication_config(&[&table_name]);
+    auto_create_publication.tables[0].columns =
+        Some(ColumnFilterRule::Deny(vec!["content".to_string()]));
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_auto_create_publication(
+        &database.config,
+        pipeline_id,
+        "test_filtered_pub".to_string(),
+        state_store.clone(),
+        destination.clone(),
+        auto_create_publication,
+    );
+
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::Ready)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    let insert_event_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+
+    database
+        .insert_values(
+            table_name.clone(),
+            &["title", "content"],
+            &[&"streamed", &vec![0u8; 1024]],
+        )
+        .await
+        .unwrap();
+
+    insert_event_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Neither the schema nor the copied and streamed rows include the filtered out column.
+    let table_schemas = destination.get_table_schemas().await;
+    assert_eq!(table_schemas.len(), 1);
+    let column_names = table_schemas[0]
+        .column_schemas
+        .iter()
+        .map(|column_schema| column_schema.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(column_names, vec!["id", "title"]);
+
+    let table_rows = destination.get_table_rows().await;
+    let copied_rows = table_rows.get(&table_id).unwrap();
+    assert_eq!(copied_rows.len(), 1);
+    assert_eq!(
+        copied_rows[0].values,
+        vec![Cell::I64(1), Cell::String("copied".to_string())]
+    );
+
+    let events = destination.get_events().await;
+    let streamed_rows = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Insert(insert) => Some(&insert.table_row),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(streamed_rows.len(), 1);
+    assert_eq!(
+        streamed_rows[0].values,
+        vec![Cell::I64(2), Cell::String("streamed".to_string())]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_fails_when_publication_filters_out_primary_key() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("documents");
+    database
+        .create_table(table_name.clone(), &[("title", "text not null")])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let mut auto_create_publication = auto_create_publication_config(&[&table_name]);
+    auto_create_publication.tables[0].columns =
+        Some(ColumnFilterRule::Allow(vec!["title".to_string()]));
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_auto_create_publication(
+        &database.config,
+        pipeline_id,
+        "test_filtered_pub".to_string(),
+        state_store.clone(),
+        destination.clone(),
+        auto_create_publication,
+    );
+
+    let err = pipeline.start().await.unwrap_err();
+    assert!(matches!(err, PipelineError::InvalidPublicationColumns(..)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_publishes_only_the_filtered_rows() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("orders");
+    let table_id = database
+        .create_table(table_name.clone(), &[("tenant_id", "bigint not null")])
+        .await
+        .unwrap();
+    for tenant_id in [7i64, 42] {
+        database
+            .insert_values(table_name.clone(), &["tenant_id"], &[&tenant_id])
+            .await
+            .unwrap();
+    }
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Only inserts are published, so the filter can reference columns outside of the replica
+    // identity.
+    let mut auto_create_publication = auto_create_publication_config(&[&table_name]);
+    auto_create_publication.tables[0].row_filter = Some("tenant_id = 42".to_string());
+    auto_create_publication.publish = vec![PublishOperation::Insert];
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_auto_create_publication(
+        &database.config,
+        pipeline_id,
+        "test_filtered_pub".to_string(),
+        state_store.clone(),
+        destination.clone(),
+        auto_create_publication,
+    );
+
+    let table_state_notify = state_store
+        .notify_on_replication_phase(table_id, TableReplicationPhaseType::Ready)
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    table_state_notify.notified().await;
+
+    let insert_event_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+
+    for tenant_id in [7i64, 42] {
+        database
+            .insert_values(table_name.clone(), &["tenant_id"], &[&tenant_id])
+            .await
+            .unwrap();
+    }
+
+    insert_event_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Only the rows of the tenant are copied and streamed.
+    let table_rows = destination.get_table_rows().await;
+    let copied_rows = table_rows.get(&table_id).unwrap();
+    assert_eq!(copied_rows.len(), 1);
+    assert_eq!(copied_rows[0].values, vec![Cell::I64(2), Cell::I64(42)]);
+
+    let events = destination.get_events().await;
+    let streamed_rows = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Insert(insert) => Some(&insert.table_row),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(streamed_rows.len(), 1);
+    assert_eq!(streamed_rows[0].values, vec![Cell::I64(4), Cell::I64(42)]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_fails_when_row_filter_is_invalid() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_name = test_table_name("orders");
+    database
+        .create_table(table_name.clone(), &[("tenant_id", "bigint not null")])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let mut auto_create_publication = auto_create_publication_config(&[&table_name]);
+    auto_create_publication.tables[0].row_filter = Some("missing_column = 42".to_string());
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_auto_create_publication(
+        &database.config,
+        pipeline_id,
+        "test_filtered_pub".to_string(),
+        state_store.clone(),
+        destination.clone(),
+        auto_create_publication,
+    );
+
+    let err = pipeline.start().await.unwrap_err();
+    assert!(matches!(err, PipelineError::InvalidRowFilter(..)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_starts_streaming_from_start_lsn_override() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // Start the pipeline a first time to copy the tables and create the apply worker slot.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 4)])
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=2,
+        true,
+    )
+    .await;
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // These rows are assumed to be already seeded in the destination, so the override skips them.
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        3..=4,
+        true,
+    )
+    .await;
+    let start_lsn: String = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one("select pg_current_wal_lsn()::text", &[])
+        .await
+        .unwrap()
+        .get(0);
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        5..=6,
+        true,
+    )
+    .await;
+
+    // Restart the pipeline from the override.
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+    let mut pipeline = create_pipeline_with_start_lsn(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        start_lsn,
+    );
+
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 4)])
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Verify that only the changes committed after the override were streamed.
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let users_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.users_schema().id))
+        .unwrap();
+    let orders_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.orders_schema().id))
+        .unwrap();
+
+    let expected_users_inserts = build_expected_users_inserts(
+        5,
+        database_schema.users_schema().id,
+        vec![("user_5", 5), ("user_6", 6)],
+    );
+    let expected_orders_inserts = build_expected_orders_inserts(
+        5,
+        database_schema.orders_schema().id,
+        vec!["description_5", "description_6"],
+    );
+    assert_eq!(*users_inserts, expected_users_inserts);
+    assert_eq!(*orders_inserts, expected_orders_inserts);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_fails_when_start_lsn_override_is_before_restart_lsn() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // The slot is created when the pipeline starts, so its restart LSN is way past `0/1`.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_start_lsn(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store,
+        destination,
+        "0/1".to_string(),
+    );
+
+    pipeline.start().await.unwrap();
+
+    match pipeline.wait().await.err().unwrap() {
+        PipelineError::OneOrMoreWorkersFailed(err) => {
+            assert!(matches!(
+                err.0.as_slice(),
+                [WorkerWaitError::ApplyWorkerFailed(
+                    ApplyWorkerError::StartLsnBeforeRestartLsn(_, _)
+                )]
+            ));
+        }
+        other => panic!("Expected OneOrMoreWorkersFailed error, but got: {other:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_and_sync_with_changed_schema_in_table_sync_worker() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
+
+    // Insert data in the table.
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description"],
+            &[&"description_1"],
+        )
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+    // The destination doesn't support schema changes, so the table is skipped once it changes.
+    destination.set_schema_changes_unsupported(true).await;
+
+    // Start pipeline from scratch.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    // Register notifications for initial table copy completion.
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::FinishedCopy,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    orders_state_notify.notified().await;
+
+    // Register notification for the skipped state.
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Skipped,
+        )
+        .await;
+
+    // Change the schema of orders by adding a new column.
+    database
+        .alter_table(
+            database_schema.orders_schema().name.clone(),
+            &[TableModification::AddColumn {
+                name: "date",
+                data_type: "integer",
+            }],
+        )
+        .await
+        .unwrap();
+
+    // Insert new data in the table.
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description", "date"],
+            &[&"description_with_date", &(10i32)],
+        )
+        .await
+        .unwrap();
+
+    orders_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // We assert that the schema is the initial one.
+    let table_schemas = destination.get_table_schemas().await;
+    assert_eq!(table_schemas.len(), 1);
+    assert_eq!(table_schemas[0], database_schema.orders_schema());
+
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type(&events);
+
+    // We assert that only one `Commit` message was received, since the apply worker doesn't filter
+    // transaction control operations by table id, so those should always go out for each apply +
+    // table sync worker. And since we are skipping a table on the table schema change, we only expect
+    // the first `Commit` to be sent by the apply worker.
+    assert_eq!(grouped_events.get(&EventType::Commit).unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_and_sync_with_changed_schema_in_apply_worker() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
+
+    // Insert data in the table.
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description"],
+            &[&"description_1"],
+        )
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+    // The destination doesn't support schema changes, so the table is skipped once it changes.
+    destination.set_schema_changes_unsupported(true).await;
+
+    // Start pipeline from scratch.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    // Register notifications for initial table copy completion.
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::FinishedCopy,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    orders_state_notify.notified().await;
+
+    // Register notification for the sync done state.
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::SyncDone,
+        )
+        .await;
+
+    // Insert new data in the table.
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description"],
+            &[&"description_2"],
+        )
+        .await
+        .unwrap();
+
+    orders_state_notify.notified().await;
+
+    // Register notification for the ready state.
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    // Insert new data in the table.
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description"],
+            &[&"description_3"],
+        )
+        .await
+        .unwrap();
+
+    orders_state_notify.notified().await;
+
+    // Register notification for the skipped state.
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Skipped,
+        )
+        .await;
+
+    // Change the schema of orders by adding a new column.
+    database
+        .alter_table(
+            database_schema.orders_schema().name.clone(),
+            &[TableModification::AddColumn {
+                name: "date",
+                data_type: "integer",
+            }],
+        )
+        .await
+        .unwrap();
+
+    // Insert new data in the table.
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description", "date"],
+            &[&"description_with_date", &(10i32)],
+        )
+        .await
+        .unwrap();
+
+    orders_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // We assert that the schema is the initial one.
+    let table_schemas = destination.get_table_schemas().await;
+    assert_eq!(table_schemas.len(), 1);
+    assert_eq!(table_schemas[0], database_schema.orders_schema());
+
+    // We check that we got the insert events after the first data of the table has been copied.
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let orders_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.orders_schema().id))
+        .unwrap();
+
+    let expected_orders_inserts = build_expected_orders_inserts(
+        2,
+        database_schema.orders_schema().id,
+        vec!["description_2", "description_3"],
+    );
+    assert_eq!(*orders_inserts, expected_orders_inserts);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_keeps_being_replicated_after_a_column_is_added() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    orders_state_notify.notified().await;
+
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description"],
+            &[&"description_1"],
+        )
+        .await
+        .unwrap();
+
+    events_notify.notified().await;
+
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 2)])
+        .await;
+
+    // Change the schema of orders by adding a new column while it's streamed.
+    database
+        .alter_table(
+            database_schema.orders_schema().name.clone(),
+            &[TableModification::AddColumn {
+                name: "date",
+                data_type: "integer",
+            }],
+        )
+        .await
+        .unwrap();
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description", "date"],
+            &[&"description_with_date", &(10i32)],
+        )
+        .await
+        .unwrap();
+
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // The rows following the change are converted with the new column.
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let orders_inserts = grouped_events
+        .get(&(EventType::Insert, database_schema.orders_schema().id))
+        .unwrap();
+    let mut expected_orders_inserts =
+        build_expected_orders_inserts(1, database_schema.orders_schema().id, vec!["description_1"]);
+    expected_orders_inserts.push(Event::Insert(InsertEvent {
+        table_id: database_schema.orders_schema().id,
+        table_row: TableRow::new(vec![
+            Cell::I64(2),
+            Cell::String("description_with_date".to_string()),
+            Cell::I32(10),
+        ]),
+    }));
+    assert_eq!(*orders_inserts, expected_orders_inserts);
+
+    // The destination has the new schema, in which the new column comes from the relation message
+    // and has no nullability information.
+    let orders_schema = database_schema.orders_schema();
+    let mut expected_column_schemas = orders_schema.column_schemas.clone();
+    expected_column_schemas.push(ColumnSchema::new(
+        "date".to_string(),
+        Type::INT4,
+        -1,
+        false,
+        false,
+    ));
+    assert_eq!(
+        destination.get_table_schemas().await,
+        vec![TableSchema::new(
+            orders_schema.id,
+            orders_schema.name.clone(),
+            expected_column_schemas,
+        )]
+    );
+
+    let table_replication_states = state_store.get_table_replication_states().await;
+    assert_eq!(
+        table_replication_states
+            .get(&orders_schema.id)
+            .unwrap()
+            .as_type(),
+        TableReplicationPhaseType::Ready
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_schema_change_is_emitted_as_metadata_event() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_metadata_events(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    orders_state_notify.notified().await;
+
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Metadata, 1), (EventType::Insert, 1)])
+        .await;
+
+    // Change the schema of orders by adding a new column.
+    database
+        .alter_table(
+            database_schema.orders_schema().name.clone(),
+            &[TableModification::AddColumn {
+                name: "date",
+                data_type: "integer",
+            }],
+        )
+        .await
+        .unwrap();
+    database
+        .insert_values(
+            database_schema.orders_schema().name.clone(),
+            &["description", "date"],
+            &[&"description_with_date", &(10i32)],
+        )
+        .await
+        .unwrap();
+
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // The new column comes from the relation message, which has no nullability information.
+    let orders_schema = database_schema.orders_schema();
+    let mut expected_column_schemas = orders_schema.column_schemas.clone();
+    expected_column_schemas.push(ColumnSchema::new(
+        "date".to_string(),
+        Type::INT4,
+        -1,
+        false,
+        false,
+    ));
+    let expected_after = TableSchema::new(
+        orders_schema.id,
+        orders_schema.name.clone(),
+        expected_column_schemas,
+    );
+
+    let events = destination.get_events().await;
+    assert_eq!(
+        get_metadata_events(&events),
+        vec![MetadataEvent::SchemaChanged {
+            before: orders_schema,
+            after: expected_after,
+        }]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_added_to_publication_is_emitted_as_metadata_event() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_metadata_events(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    orders_state_notify.notified().await;
+
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Metadata, 1)])
+        .await;
+
+    // Add a new table to the publication while the pipeline is running.
+    let customers_table_name = test_table_name("customers");
+    let customers_table_id = database
+        .create_table(customers_table_name.clone(), &[("name", "text not null")])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "alter publication {} add table {}",
+                database_schema.publication_name(),
+                customers_table_name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+    let customers_state_notify = state_store
+        .notify_on_replication_phase(customers_table_id, TableReplicationPhaseType::Ready)
+        .await;
+    database
+        .insert_values(customers_table_name.clone(), &["name"], &[&"customer_1"])
+        .await
+        .unwrap();
+
+    events_notify.notified().await;
+    customers_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let expected_table_schema = TableSchema::new(
+        customers_table_id,
+        customers_table_name,
+        vec![
+            id_column_schema(),
+            ColumnSchema::new("name".to_string(), Type::TEXT, -1, false, false),
+        ],
+    );
+
+    let events = destination.get_events().await;
+    assert_eq!(
+        get_metadata_events(&events),
+        vec![MetadataEvent::TableAdded {
+            table_schema: expected_table_schema,
+        }]
+    );
+
+    // The new table is replicated without restarting the pipeline, its row being either copied or
+    // streamed depending on when the pipeline noticed the table.
+    let copied_rows = destination
+        .get_table_rows()
+        .await
+        .get(&customers_table_id)
+        .map_or(0, Vec::len);
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let streamed_rows = grouped_events
+        .get(&(EventType::Insert, customers_table_id))
+        .map_or(0, Vec::len);
+    assert_eq!(copied_rows + streamed_rows, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_removed_from_publication_is_emitted_as_metadata_event() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_metadata_events(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // Remove the users table from the publication while the pipeline is stopped.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "alter publication {} drop table {}",
+                database_schema.publication_name(),
+                database_schema.users_schema().name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+
+    let mut pipeline = create_pipeline_with_metadata_events(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    // The dropped tables are detected when the pipeline starts.
+    pipeline.start().await.unwrap();
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let events = destination.get_events().await;
+    assert_eq!(
+        get_metadata_events(&events),
+        vec![MetadataEvent::TableDropped {
+            table_id: database_schema.users_schema().id,
+            table_schema: Some(database_schema.users_schema()),
+        }]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tables_added_to_and_removed_from_publication_while_running() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+    let orders_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.orders_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+    orders_state_notify.notified().await;
+
+    // Add a table with rows to the publication while the pipeline is running, it's copied without
+    // restarting the pipeline.
+    let customers_table_name = test_table_name("customers");
+    let customers_table_id = database
+        .create_table(customers_table_name.clone(), &[("name", "text not null")])
+        .await
+        .unwrap();
+    database
+        .insert_values(customers_table_name.clone(), &["name"], &[&"customer_1"])
+        .await
+        .unwrap();
+
+    let customers_state_notify = state_store
+        .notify_on_replication_phase(customers_table_id, TableReplicationPhaseType::Ready)
+        .await;
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "alter publication {} add table {}",
+                database_schema.publication_name(),
+                customers_table_name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+
+    customers_state_notify.notified().await;
+
+    // Its changes are then streamed like the ones of the other tables.
+    let insert_events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+    database
+        .insert_values(customers_table_name.clone(), &["name"], &[&"customer_2"])
+        .await
+        .unwrap();
+
+    insert_events_notify.notified().await;
+
+    // Remove a table from the publication while the pipeline is running, its changes are not
+    // streamed anymore.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            &format!(
+                "alter publication {} drop table {}",
+                database_schema.publication_name(),
+                database_schema.users_schema().name.as_quoted_identifier()
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while state_store
+            .get_table_replication_states()
+            .await
+            .contains_key(&database_schema.users_schema().id)
+        {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("the table removed from the publication is still replicated");
+
+    let insert_events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 2)])
+        .await;
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=1,
+        false,
+    )
+    .await;
+
+    insert_events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let table_rows = destination.get_table_rows().await;
+    let customers_rows = &table_rows[&customers_table_id];
+    assert_eq!(customers_rows.len(), 1);
+    assert_eq!(
+        customers_rows[0].values[1],
+        Cell::String("customer_1".to_string())
+    );
+
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type_and_table_id(&events);
+    let customers_inserts = &grouped_events[&(EventType::Insert, customers_table_id)];
+    assert_eq!(customers_inserts.len(), 1);
+    let orders_inserts = &grouped_events[&(EventType::Insert, database_schema.orders_schema().id)];
+    assert_eq!(orders_inserts.len(), 1);
+    assert!(!grouped_events.contains_key(&(EventType::Insert, database_schema.users_schema().id)));
+
+    // The state of the table removed from the publication is deleted, so that it's not replicated
+    // once the pipeline restarts either.
+    let states = state_store.get_table_replication_states().await;
+    assert!(!states.contains_key(&database_schema.users_schema().id));
+    assert_eq!(
+        states.get(&customers_table_id).map(|state| state.as_type()),
+        Some(TableReplicationPhaseType::Ready)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_owned_sequence_values_are_emitted_as_metadata_events() {
+    init_test_tracing();
+    let mut database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::Both).await;
+
+    // We insert rows, which advances the sequences owned by the `id` columns of the tables.
+    insert_mock_data(
+        &mut database,
+        &database_schema.users_schema().name,
+        &database_schema.orders_schema().name,
+        1..=3,
+        false,
+    )
+    .await;
+
+    // We create sequences that are not owned by a published table, which must not be replicated.
+    let unpublished_table_name = test_table_name("unpublished");
+    database
+        .create_table(unpublished_table_name.clone(), &[("name", "text not null")])
+        .await
+        .unwrap();
+    database
+        .insert_values(unpublished_table_name, &["name"], &[&"name_1"])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(
+            "create sequence test.standalone_seq; select nextval('test.standalone_seq');",
+        )
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_sequence_sync(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        100,
+    );
+
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Metadata, 2)])
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    events_notify.notified().await;
+
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let mut sequence_values = get_metadata_events(&destination.get_events().await)
+        .into_iter()
+        .map(|event| match event {
+            MetadataEvent::SequenceValue {
+                table_id,
+                column_name,
+                sequence_name,
+                last_value,
+            } => (table_id, column_name, sequence_name, last_value),
+            event => panic!("unexpected metadata event: {event:?}"),
+        })
+        .collect::<Vec<_>>();
+    sequence_values.sort();
+
+    // The values didn't change after the first check, so they are sent only once.
+    let users_table_id = database_schema.users_schema().id;
+    let orders_table_id = database_schema.orders_schema().id;
+    let mut expected_sequence_values = vec![
+        (
+            users_table_id,
+            "id".to_string(),
+            test_table_name(&format!(
+                "{}_id_seq",
+                database_schema.users_schema().name.name
+            )),
+            3,
+        ),
+        (
+            orders_table_id,
+            "id".to_string(),
+            test_table_name(&format!(
+                "{}_id_seq",
+                database_schema.orders_schema().name.name
+            )),
+            3,
+        ),
+    ];
+    expected_sequence_values.sort();
+    assert_eq!(sequence_values, expected_sequence_values);
+}
+
+/// Returns the `confirmed_flush_lsn` of the apply worker's slot of the pipeline.
+async fn get_apply_slot_confirmed_flush_lsn(
+    database: &PgDatabase<Client>,
+    pipeline_id: PipelineId,
+) -> PgLsn {
+    let slot_name = get_slot_name(pipeline_id, WorkerType::Apply).unwrap();
+
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one(
+            "select confirmed_flush_lsn from pg_replication_slots where slot_name = $1",
+            &[&slot_name],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+/// Waits until the apply worker's slot of the pipeline has sent the WAL up to `lsn`.
+async fn wait_for_apply_slot_sent_lsn(
+    database: &PgDatabase<Client>,
+    pipeline_id: PipelineId,
+    lsn: PgLsn,
+) {
+    let slot_name = get_slot_name(pipeline_id, WorkerType::Apply).unwrap();
+    tokio::time::timeout(Duration::from_secs(30), async {
+        loop {
+            let sent_lsn: Option<PgLsn> = database
+                .client
+                .as_ref()
+                .unwrap()
+                .query_opt(
+                    "select s.sent_lsn from pg_stat_replication s \
+                    join pg_replication_slots r on r.active_pid = s.pid \
+                    where r.slot_name = $1",
+                    &[&slot_name],
+                )
+                .await
+                .unwrap()
+                .and_then(|row| row.get(0));
+            if sent_lsn.is_some_and(|sent_lsn| sent_lsn >= lsn) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("the WAL was not streamed to the pipeline");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_flushes_buffered_events_on_shutdown() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // The batch is neither full nor expired before the pipeline is shut down.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        Some(BatchConfig {
+            max_size: 1000,
+            max_fill_ms: 3_600_000,
+        }),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+
+    let lsn_before_insert: PgLsn = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one("select pg_current_wal_lsn()", &[])
+        .await
+        .unwrap()
+        .get(0);
+    database
+        .insert_values(
+            database_schema.users_schema().name,
+            &["name", "age"],
+            &[&"user_1", &1i32],
+        )
+        .await
+        .unwrap();
+    let lsn_after_insert: PgLsn = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one("select pg_current_wal_lsn()", &[])
+        .await
+        .unwrap()
+        .get(0);
+
+    // We wait for the insert to be streamed to the pipeline, which buffers it.
+    wait_for_apply_slot_sent_lsn(&database, pipeline_id, lsn_after_insert).await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    assert!(
+        !group_events_by_type(&destination.get_events().await).contains_key(&EventType::Insert)
+    );
+
+    tokio::time::timeout(Duration::from_secs(30), pipeline.shutdown_and_wait())
+        .await
+        .expect("the pipeline did not shut down in time")
+        .unwrap();
+
+    // The buffered insert is written on shutdown and its commit is confirmed to the slot, so that
+    // it's not streamed again once the pipeline restarts.
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type(&events);
+    assert_eq!(grouped_events.get(&EventType::Insert).unwrap().len(), 1);
+    assert!(get_apply_slot_confirmed_flush_lsn(&database, pipeline_id).await > lsn_before_insert);
+}
+
+fn pause_on_destination_down_config(max_pause_ms: u64) -> DestinationDownConfig {
+    DestinationDownConfig {
+        retry: RetryConfig {
+            max_attempts: 1,
+            initial_delay_ms: 10,
+            max_delay_ms: 10,
+            backoff_factor: 1.0,
+        },
+        pause_on_destination_down: true,
+        resume_check_interval_ms: 100,
+        max_pause_ms,
+        ..Default::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_pauses_while_destination_is_down_and_resumes() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_destination_down(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        pause_on_destination_down_config(60_000),
+        None,
+    );
+    let mut status_rx = pipeline.status_rx();
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+
+    // The destination goes down before a row is inserted.
+    destination.set_events_unavailable(true).await;
+    let lsn_before_insert: PgLsn = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one("select pg_current_wal_lsn()", &[])
+        .await
+        .unwrap()
+        .get(0);
+    database
+        .insert_values(
+            database_schema.users_schema().name.clone(),
+            &["name", "age"],
+            &[&"user_1", &1],
+        )
+        .await
+        .unwrap();
+
+    status_rx
+        .wait_for(|status| *status == PipelineStatus::Paused)
+        .await
+        .unwrap();
+
+    // While paused, the pipeline holds its position, so the insert is not confirmed to the slot.
+    assert!(get_apply_slot_confirmed_flush_lsn(&database, pipeline_id).await <= lsn_before_insert);
+
+    // The destination recovers and the pipeline resumes from where it was paused.
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
+    destination.set_events_unavailable(false).await;
+
+    events_notify.notified().await;
+    status_rx
+        .wait_for(|status| *status == PipelineStatus::Running)
+        .await
+        .unwrap();
+
+    pipeline.shutdown_and_wait().await.unwrap();
 
     let events = destination.get_events().await;
     let grouped_events = group_events_by_type(&events);
+    assert_eq!(grouped_events.get(&EventType::Insert).unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipeline_fails_when_destination_is_down_for_longer_than_max_pause() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_destination_down(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        pause_on_destination_down_config(300),
+        None,
+    );
+    let mut status_rx = pipeline.status_rx();
 
-    // We assert that only one `Commit` message was received, since the apply worker doesn't filter
-    // transaction control operations by table id, so those should always go out for each apply +
-    // table sync worker. And since we are skipping a table on the table schema change, we only expect
-    // the first `Commit` to be sent by the apply worker.
-    assert_eq!(grouped_events.get(&EventType::Commit).unwrap().len(), 1);
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+
+    destination.set_events_unavailable(true).await;
+    database
+        .insert_values(
+            database_schema.users_schema().name.clone(),
+            &["name", "age"],
+            &[&"user_1", &1],
+        )
+        .await
+        .unwrap();
+
+    status_rx
+        .wait_for(|status| *status == PipelineStatus::Paused)
+        .await
+        .unwrap();
+    // The pipeline stops being paused once it fails.
+    status_rx
+        .wait_for(|status| *status == PipelineStatus::Running)
+        .await
+        .unwrap();
+
+    match pipeline.shutdown_and_wait().await.err().unwrap() {
+        PipelineError::OneOrMoreWorkersFailed(err) => {
+            assert!(matches!(
+                err.0.as_slice(),
+                [WorkerWaitError::ApplyWorkerFailed(
+                    ApplyWorkerError::ApplyLoop(ApplyLoopError::DestinationDown(
+                        DestinationDownError::MaxPauseExceeded(_, _)
+                    ))
+                )]
+            ));
+        }
+        other => panic!("Expected OneOrMoreWorkersFailed error, but got: {other:?}"),
+    }
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn test_table_copy_and_sync_with_changed_schema_in_apply_worker() {
+async fn test_pipeline_shuts_down_when_destination_is_down_while_flushing() {
     init_test_tracing();
     let database = spawn_database().await;
-    let database_schema = setup_test_database_schema(&database, TableSelection::OrdersOnly).await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
 
-    // Insert data in the table.
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    // The batch is neither full nor expired before the pipeline is shut down, and the pipeline
+    // would stay paused for longer than the test if the flush waited for the destination.
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_destination_down(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        pause_on_destination_down_config(3_600_000),
+        Some(BatchConfig {
+            max_size: 1000,
+            max_fill_ms: 3_600_000,
+        }),
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+
+    let lsn_before_insert: PgLsn = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one("select pg_current_wal_lsn()", &[])
+        .await
+        .unwrap()
+        .get(0);
     database
         .insert_values(
-            database_schema.orders_schema().name.clone(),
-            &["description"],
-            &[&"description_1"],
+            database_schema.users_schema().name.clone(),
+            &["name", "age"],
+            &[&"user_1", &1],
         )
         .await
         .unwrap();
+    let lsn_after_insert: PgLsn = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one("select pg_current_wal_lsn()", &[])
+        .await
+        .unwrap()
+        .get(0);
+
+    wait_for_apply_slot_sent_lsn(&database, pipeline_id, lsn_after_insert).await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    destination.set_events_unavailable(true).await;
+
+    tokio::time::timeout(Duration::from_secs(30), pipeline.shutdown_and_wait())
+        .await
+        .expect("the pipeline did not shut down in time")
+        .unwrap();
+
+    // The buffered insert couldn't be written, so it's not confirmed to the slot and is streamed
+    // again once the pipeline restarts.
+    assert!(
+        !group_events_by_type(&destination.get_events().await).contains_key(&EventType::Insert)
+    );
+    assert!(get_apply_slot_confirmed_flush_lsn(&database, pipeline_id).await <= lsn_before_insert);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_apply_worker_reconnects_when_replication_connection_is_lost() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
 
     let state_store = TestStateStore::new();
     let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
 
-    // Start pipeline from scratch.
     let pipeline_id: PipelineId = random();
-    let mut pipeline = create_pipeline(
+    let mut pipeline = create_pipeline_with_source_reconnect(
         &database.config,
         pipeline_id,
         database_schema.publication_name(),
         state_store.clone(),
         destination.clone(),
+        RetryConfig {
+            max_attempts: 10,
+            initial_delay_ms: 100,
+            max_delay_ms: 1_000,
+            backoff_factor: 2.0,
+        },
     );
 
-    // Register notifications for initial table copy completion.
-    let orders_state_notify = state_store
+    let users_state_notify = state_store
         .notify_on_replication_phase(
-            database_schema.orders_schema().id,
-            TableReplicationPhaseType::FinishedCopy,
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
         )
         .await;
 
     pipeline.start().await.unwrap();
 
-    orders_state_notify.notified().await;
+    users_state_notify.notified().await;
 
-    // Register notification for the sync done state.
-    let orders_state_notify = state_store
-        .notify_on_replication_phase(
-            database_schema.orders_schema().id,
-            TableReplicationPhaseType::SyncDone,
+    // The replication connection of the apply worker is terminated, like when the source restarts.
+    let slot_name = get_slot_name(pipeline_id, WorkerType::Apply).unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            "select pg_terminate_backend(active_pid) from pg_replication_slots where slot_name = $1",
+            &[&slot_name],
         )
-        .await;
+        .await
+        .unwrap();
 
-    // Insert new data in the table.
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
     database
         .insert_values(
-            database_schema.orders_schema().name.clone(),
-            &["description"],
-            &[&"description_2"],
+            database_schema.users_schema().name.clone(),
+            &["name", "age"],
+            &[&"user_1", &1],
         )
         .await
         .unwrap();
 
-    orders_state_notify.notified().await;
+    events_notify.notified().await;
 
-    // Register notification for the ready state.
-    let orders_state_notify = state_store
+    // The apply worker reconnected instead of failing, so the pipeline shuts down cleanly.
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    let events = destination.get_events().await;
+    let grouped_events = group_events_by_type(&events);
+    assert_eq!(grouped_events.get(&EventType::Insert).unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_heartbeat_advances_slot_while_replicated_tables_are_idle() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    // A busy table which is not in the publication.
+    let busy_table_name = test_table_name("busy");
+    database
+        .create_table(busy_table_name.clone(), &[("value", "bigint not null")])
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let heartbeat_table_name = test_table_name("heartbeat");
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_heartbeat(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        HeartbeatConfig {
+            schema: heartbeat_table_name.schema.clone(),
+            table_name: heartbeat_table_name.name.clone(),
+            interval_ms: 200,
+        },
+    );
+
+    let users_state_notify = state_store
         .notify_on_replication_phase(
-            database_schema.orders_schema().id,
+            database_schema.users_schema().id,
             TableReplicationPhaseType::Ready,
         )
         .await;
 
-    // Insert new data in the table.
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+
+    // Only the busy table changes, so without heartbeats there would be no change to confirm.
     database
-        .insert_values(
-            database_schema.orders_schema().name.clone(),
-            &["description"],
-            &[&"description_3"],
-        )
+        .insert_generate_series(busy_table_name, &["value"], 1, 10_000, 1)
         .await
         .unwrap();
+    let lsn_after_busy_writes: PgLsn = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one("select pg_current_wal_lsn()", &[])
+        .await
+        .unwrap()
+        .get(0);
 
-    orders_state_notify.notified().await;
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while get_apply_slot_confirmed_flush_lsn(&database, pipeline_id).await
+            <= lsn_after_busy_writes
+        {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("the slot did not advance past the busy writes");
 
-    // Register notification for the skipped state.
-    let orders_state_notify = state_store
-        .notify_on_replication_phase(
-            database_schema.orders_schema().id,
-            TableReplicationPhaseType::Skipped,
+    pipeline.shutdown_and_wait().await.unwrap();
+
+    // The heartbeat table is published but neither copied nor streamed to the destination.
+    let heartbeats: i64 = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query_one(
+            &format!(
+                "select count(*) from {}",
+                heartbeat_table_name.as_quoted_identifier()
+            ),
+            &[],
         )
-        .await;
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(heartbeats, 1);
 
-    // Change the schema of orders by adding a new column.
+    let table_schemas = destination.get_table_schemas().await;
+    assert_eq!(table_schemas, vec![database_schema.users_schema()]);
+
+    let grouped_events = group_events_by_type(&destination.get_events().await);
+    assert!(!grouped_events.contains_key(&EventType::Insert));
+    assert!(!grouped_events.contains_key(&EventType::Update));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_apply_worker_streams_from_configured_replication_slot() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    // The slot is created ahead of time, like when it is provisioned outside of the pipeline.
+    let slot_name = test_slot_name("apply");
     database
-        .alter_table(
-            database_schema.orders_schema().name.clone(),
-            &[TableModification::AddColumn {
-                name: "date",
-                data_type: "integer",
-            }],
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            "select pg_create_logical_replication_slot($1, 'pgoutput')",
+            &[&slot_name],
         )
         .await
         .unwrap();
 
-    // Insert new data in the table.
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_replication_slot(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store.clone(),
+        destination.clone(),
+        ReplicationSlotConfig {
+            name: slot_name.clone(),
+            plugin: ReplicationSlotConfig::DEFAULT_PLUGIN.to_string(),
+        },
+    );
+
+    let users_state_notify = state_store
+        .notify_on_replication_phase(
+            database_schema.users_schema().id,
+            TableReplicationPhaseType::Ready,
+        )
+        .await;
+
+    pipeline.start().await.unwrap();
+
+    users_state_notify.notified().await;
+
+    let events_notify = destination
+        .wait_for_events_count(vec![(EventType::Insert, 1)])
+        .await;
     database
         .insert_values(
-            database_schema.orders_schema().name.clone(),
-            &["description", "date"],
-            &[&"description_with_date", &(10i32)],
+            database_schema.users_schema().name.clone(),
+            &["name", "age"],
+            &[&"user_1", &1],
         )
         .await
         .unwrap();
 
-    orders_state_notify.notified().await;
+    events_notify.notified().await;
 
     pipeline.shutdown_and_wait().await.unwrap();
 
-    // We assert that the schema is the initial one.
-    let table_schemas = destination.get_table_schemas().await;
-    assert_eq!(table_schemas.len(), 1);
-    assert_eq!(table_schemas[0], database_schema.orders_schema());
-
-    // We check that we got the insert events after the first data of the table has been copied.
     let events = destination.get_events().await;
-    let grouped_events = group_events_by_type_and_table_id(&events);
-    let orders_inserts = grouped_events
-        .get(&(EventType::Insert, database_schema.orders_schema().id))
+    let grouped_events = group_events_by_type(&events);
+    assert_eq!(grouped_events.get(&EventType::Insert).unwrap().len(), 1);
+
+    // The apply worker used the configured slot instead of creating one with the default name.
+    let default_slot_name = get_slot_name(pipeline_id, WorkerType::Apply).unwrap();
+    let slots = database
+        .client
+        .as_ref()
+        .unwrap()
+        .query(
+            "select slot_name from pg_replication_slots where slot_name = any($1)",
+            &[&vec![slot_name.clone(), default_slot_name]],
+        )
+        .await
         .unwrap();
+    let slot_names: Vec<String> = slots.iter().map(|row| row.get(0)).collect();
+    assert_eq!(slot_names, vec![slot_name]);
+}
 
-    let expected_orders_inserts = build_expected_orders_inserts(
-        2,
-        database_schema.orders_schema().id,
-        vec!["description_2", "description_3"],
+#[tokio::test(flavor = "multi_thread")]
+async fn test_apply_worker_fails_when_configured_slot_uses_another_plugin() {
+    init_test_tracing();
+    let database = spawn_database().await;
+    let database_schema = setup_test_database_schema(&database, TableSelection::UsersOnly).await;
+
+    let slot_name = test_slot_name("apply");
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .execute(
+            "select pg_create_logical_replication_slot($1, 'test_decoding')",
+            &[&slot_name],
+        )
+        .await
+        .unwrap();
+
+    let state_store = TestStateStore::new();
+    let destination = TestDestinationWrapper::wrap(MemoryDestination::new());
+
+    let pipeline_id: PipelineId = random();
+    let mut pipeline = create_pipeline_with_replication_slot(
+        &database.config,
+        pipeline_id,
+        database_schema.publication_name(),
+        state_store,
+        destination,
+        ReplicationSlotConfig {
+            name: slot_name,
+            plugin: ReplicationSlotConfig::DEFAULT_PLUGIN.to_string(),
+        },
     );
-    assert_eq!(*orders_inserts, expected_orders_inserts);
+
+    pipeline.start().await.unwrap();
+
+    match pipeline.wait().await.err().unwrap() {
+        PipelineError::OneOrMoreWorkersFailed(err) => {
+            assert!(matches!(
+                err.0.as_slice(),
+                [WorkerWaitError::ApplyWorkerFailed(
+                    ApplyWorkerError::PgReplication(PgReplicationError::SlotPluginMismatch { .. })
+                )]
+            ));
+        }
+        other => panic!("Expected OneOrMoreWorkersFailed error, but got: {other:?}"),
+    }
 }
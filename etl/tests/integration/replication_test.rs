@@ -1,13 +1,20 @@
+use chrono::{DateTime, Utc};
+use config::shared::CopyConfig;
+use etl::conversions::Cell;
+use etl::conversions::table_row::TableRowConverter;
+use etl::replication::checksum::{KeyRange, RangeChecksums};
 use etl::replication::client::{PgReplicationClient, PgReplicationError};
+use etl::replication::stream::TableCopyStream;
 use futures::StreamExt;
-use postgres::schema::ColumnSchema;
-use postgres::tokio::test_utils::{TableModification, id_column_schema};
+use pg_escape::quote_identifier;
+use postgres::schema::{ColumnSchema, TableId, TableSchema};
+use postgres::tokio::test_utils::{PgDatabase, TableModification, id_column_schema};
 use postgres_replication::LogicalReplicationStream;
 use postgres_replication::protocol::{LogicalReplicationMessage, ReplicationMessage};
 use telemetry::init_test_tracing;
 use tokio::pin;
-use tokio_postgres::CopyOutStream;
-use tokio_postgres::types::{ToSql, Type};
+use tokio_postgres::types::{Kind, ToSql, Type};
+use tokio_postgres::{Client, CopyOutStream};
 
 use crate::common::database::{spawn_database, test_table_name};
 use crate::common::pipeline::test_slot_name;
@@ -346,6 +353,8 @@ async fn test_table_copy_stream_is_consistent() {
                 nullable: true,
                 primary: false,
             }],
+            None,
+            &CopyConfig::default(),
         )
         .await
         .unwrap();
@@ -359,6 +368,662 @@ async fn test_table_copy_stream_is_consistent() {
     assert_eq!(rows_count, expected_rows_count as u64);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_stream_only_copies_rows_matching_the_row_filter() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_id = database
+        .create_table(test_table_name("table_1"), &[("age", "integer")])
+        .await
+        .unwrap();
+    database
+        .insert_generate_series(test_table_name("table_1"), &["age"], 1, 100, 1)
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(&format!(
+            "create publication my_publication for table {} where (age > 90);",
+            test_table_name("table_1").as_quoted_identifier()
+        ))
+        .await
+        .unwrap();
+
+    let client = PgReplicationClient::connect(database.config.clone())
+        .await
+        .unwrap();
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await
+        .unwrap();
+
+    let row_filter = transaction
+        .get_row_filter(table_id, "my_publication")
+        .await
+        .unwrap();
+    assert_eq!(row_filter.as_deref(), Some("(age > 90)"));
+
+    let table_schema = transaction
+        .get_table_schema(table_id, Some("my_publication"))
+        .await
+        .unwrap();
+    let stream = transaction
+        .get_table_copy_stream(
+            table_id,
+            &table_schema.column_schemas,
+            row_filter.as_deref(),
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+    let rows_count = count_stream_rows(stream).await;
+    transaction.commit().await.unwrap();
+
+    assert_eq!(rows_count, 10);
+}
+
+/// Copies the whole of `table_id` with a client connected with the given `statement_timeout`,
+/// returning the number of rows copied.
+async fn copy_table_with_statement_timeout(
+    database: &PgDatabase<Client>,
+    table_id: TableId,
+    statement_timeout_ms: Option<u64>,
+) -> Result<u64, PgReplicationError> {
+    let client = match statement_timeout_ms {
+        Some(statement_timeout_ms) => {
+            PgReplicationClient::connect_with_statement_timeout(
+                database.config.clone(),
+                statement_timeout_ms,
+            )
+            .await?
+        }
+        None => PgReplicationClient::connect(database.config.clone()).await?,
+    };
+
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await?;
+    let stream = transaction
+        .get_table_copy_stream(
+            table_id,
+            &[ColumnSchema {
+                name: "age".to_string(),
+                typ: Type::INT4,
+                modifier: -1,
+                nullable: true,
+                primary: false,
+            }],
+            None,
+            &CopyConfig::default(),
+        )
+        .await?;
+
+    pin!(stream);
+    let mut rows_count = 0;
+    while let Some(row) = stream.next().await {
+        row?;
+        rows_count += 1;
+    }
+
+    transaction.commit().await?;
+
+    Ok(rows_count)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_copy_is_not_terminated_by_catalog_timeout() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_1_id = database
+        .create_table(test_table_name("table_1"), &[("age", "integer")])
+        .await
+        .unwrap();
+
+    let expected_rows_count = 200_000;
+    database
+        .insert_generate_series(
+            test_table_name("table_1"),
+            &["age"],
+            1,
+            expected_rows_count,
+            1,
+        )
+        .await
+        .unwrap();
+
+    // We make every new session of the database inherit a very short timeout, like a short global
+    // timeout meant for catalog queries would.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .simple_query(&format!(
+            "alter database {} set statement_timeout = '1ms'",
+            quote_identifier(&database.config.name)
+        ))
+        .await
+        .unwrap();
+
+    // A session using the short timeout can't copy the table.
+    let result = copy_table_with_statement_timeout(&database, table_1_id, None).await;
+    assert!(result.is_err());
+
+    // A session meant for copying, with the timeout disabled, copies the table fully.
+    let rows_count = copy_table_with_statement_timeout(&database, table_1_id, Some(0))
+        .await
+        .unwrap();
+    assert_eq!(rows_count, expected_rows_count as u64);
+}
+
+/// Returns the checksums of the rows of `table_schema` produced by `stream`, split by ranges of
+/// 100 ids.
+async fn checksum_stream_rows(stream: CopyOutStream, table_schema: &TableSchema) -> RangeChecksums {
+    let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+    let mut checksums = RangeChecksums::for_table(&table_schema.column_schemas, 100).unwrap();
+
+    {
+        let stream = TableCopyStream::wrap(stream, &table_schema.column_schemas, &converter)
+            .with_checksums(&mut checksums);
+        pin!(stream);
+        while let Some(row) = stream.next().await {
+            row.unwrap();
+        }
+    }
+
+    checksums
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_table_range_checksums_localize_discrepancies() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_1_id = database
+        .create_table(
+            test_table_name("table_1"),
+            &[("name", "text"), ("active", "boolean"), ("tags", "text[]")],
+        )
+        .await
+        .unwrap();
+
+    // The values contain characters escaped by `COPY` as well as `NULL`s, which the source
+    // checksums must hash as `COPY` produces them.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .simple_query(&format!(
+            r#"insert into {} (name, active, tags)
+            select case when g % 7 = 0 then null else E'row\t' || g || E'\\\n"x"' end,
+                g % 2 = 0,
+                array['a,b', null, E'c\td']
+            from generate_series(1, 300) g"#,
+            test_table_name("table_1").as_quoted_identifier()
+        ))
+        .await
+        .unwrap();
+
+    let client = PgReplicationClient::connect(database.config.clone())
+        .await
+        .unwrap();
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await
+        .unwrap();
+    let table_schema = transaction
+        .get_table_schema(table_1_id, None)
+        .await
+        .unwrap();
+    let source_checksums = transaction
+        .get_table_range_checksums(
+            table_1_id,
+            &table_schema.column_schemas,
+            None,
+            "id",
+            100,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(source_checksums.len(), 4);
+
+    // The rows copied within the same snapshot match the source in every range.
+    let stream = transaction
+        .get_table_copy_stream(
+            table_1_id,
+            &table_schema.column_schemas,
+            None,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+    let checksums = checksum_stream_rows(stream, &table_schema).await;
+    assert!(checksums.mismatched_ranges(&source_checksums).is_empty());
+
+    transaction.commit().await.unwrap();
+
+    // We inject a discrepancy by changing a row after the snapshot and copying the table again
+    // outside of it.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .simple_query(&format!(
+            "update {} set active = not active where id = 150",
+            test_table_name("table_1").as_quoted_identifier()
+        ))
+        .await
+        .unwrap();
+    let stream = client
+        .get_table_copy_stream(
+            table_1_id,
+            &table_schema.column_schemas,
+            None,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+    let checksums = checksum_stream_rows(stream, &table_schema).await;
+
+    // Only the range of the changed row is reported.
+    assert_eq!(
+        checksums.mismatched_ranges(&source_checksums),
+        vec![KeyRange {
+            start: 100,
+            end: 200
+        }]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_composite_columns_are_copied_by_field() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(
+            "create type test.city as (name text, zip integer);
+            create type test.address as (street text, city test.city);",
+        )
+        .await
+        .unwrap();
+    let table_id = database
+        .create_table(test_table_name("people"), &[("address", "test.address")])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(
+            "insert into test.people (address) values
+                (row('1 Main St, North', row('Paris', 75001)::test.city)::test.address),
+                (row(null, row('a \"b\"', null)::test.city)::test.address);",
+        )
+        .await
+        .unwrap();
+
+    let client = PgReplicationClient::connect(database.config.clone())
+        .await
+        .unwrap();
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await
+        .unwrap();
+    let table_schemas = transaction
+        .get_table_schemas(&[table_id], None)
+        .await
+        .unwrap();
+    let table_schema = &table_schemas[&table_id];
+    let stream = transaction
+        .get_table_copy_stream(
+            table_id,
+            &table_schema.column_schemas,
+            None,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+
+    let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+    let stream = TableCopyStream::wrap(stream, &table_schema.column_schemas, &converter);
+    pin!(stream);
+    let mut addresses = vec![];
+    while let Some(row) = stream.next().await {
+        let mut row = row.unwrap();
+        addresses.push(row.values.pop().unwrap());
+    }
+    transaction.commit().await.unwrap();
+
+    // The composite has the fields of its type, with the nested composite parsed as well.
+    assert_eq!(
+        addresses,
+        vec![
+            Cell::Composite(vec![
+                Cell::String("1 Main St, North".to_string()),
+                Cell::Composite(vec![Cell::String("Paris".to_string()), Cell::I32(75001),]),
+            ]),
+            Cell::Composite(vec![
+                Cell::Null(Type::TEXT),
+                Cell::Composite(vec![
+                    Cell::String("a \"b\"".to_string()),
+                    Cell::Null(Type::INT4),
+                ]),
+            ]),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_hstore_columns_are_copied_as_json_objects() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute("create extension if not exists hstore;")
+        .await
+        .unwrap();
+    let table_id = database
+        .create_table(test_table_name("items"), &[("metadata", "hstore")])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(
+            r#"insert into test.items (metadata) values
+                ('"a=>b"=>"1, 2", "say \"hi\""=>NULL'::hstore);"#,
+        )
+        .await
+        .unwrap();
+
+    let client = PgReplicationClient::connect(database.config.clone())
+        .await
+        .unwrap();
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await
+        .unwrap();
+    let table_schemas = transaction
+        .get_table_schemas(&[table_id], None)
+        .await
+        .unwrap();
+    let table_schema = &table_schemas[&table_id];
+    // The oid of `hstore` is assigned when the extension is created, so the type is looked up.
+    assert_eq!(table_schema.column_schemas[1].typ.name(), "hstore");
+
+    let stream = transaction
+        .get_table_copy_stream(
+            table_id,
+            &table_schema.column_schemas,
+            None,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+
+    let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+    let stream = TableCopyStream::wrap(stream, &table_schema.column_schemas, &converter);
+    pin!(stream);
+    let mut values = vec![];
+    while let Some(row) = stream.next().await {
+        let mut row = row.unwrap();
+        values.push(row.values.pop().unwrap());
+    }
+    transaction.commit().await.unwrap();
+
+    assert_eq!(
+        values,
+        vec![Cell::Json(serde_json::json!({
+            "a=>b": "1, 2",
+            r#"say "hi""#: null,
+        }))]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_enum_columns_are_copied_with_their_labels() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute("create type test.mood as enum ('sad', 'ok', 'happy');")
+        .await
+        .unwrap();
+    let table_id = database
+        .create_table(test_table_name("people"), &[("mood", "test.mood")])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute("insert into test.people (mood) values ('happy'), ('sad');")
+        .await
+        .unwrap();
+
+    let client = PgReplicationClient::connect(database.config.clone())
+        .await
+        .unwrap();
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await
+        .unwrap();
+    let table_schemas = transaction
+        .get_table_schemas(&[table_id], None)
+        .await
+        .unwrap();
+    let table_schema = &table_schemas[&table_id];
+    // The labels of the enum are looked up in their sort order, to validate the copied values.
+    assert_eq!(
+        table_schema.column_schemas[1].typ.kind(),
+        &Kind::Enum(vec![
+            "sad".to_string(),
+            "ok".to_string(),
+            "happy".to_string()
+        ])
+    );
+
+    let stream = transaction
+        .get_table_copy_stream(
+            table_id,
+            &table_schema.column_schemas,
+            None,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+
+    let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+    let stream = TableCopyStream::wrap(stream, &table_schema.column_schemas, &converter);
+    pin!(stream);
+    let mut values = vec![];
+    while let Some(row) = stream.next().await {
+        let mut row = row.unwrap();
+        values.push(row.values.pop().unwrap());
+    }
+    transaction.commit().await.unwrap();
+
+    assert_eq!(
+        values,
+        vec![
+            Cell::Enum {
+                type_name: "mood".to_string(),
+                label: "happy".to_string()
+            },
+            Cell::Enum {
+                type_name: "mood".to_string(),
+                label: "sad".to_string()
+            },
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_timestamptz_columns_are_copied_in_utc_whatever_the_database_time_zone() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_id = database
+        .create_table(test_table_name("events"), &[("at", "timestamptz")])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(
+            "insert into test.events (at) values
+                ('2024-03-10 01:59:59.999999-05'),
+                ('2024-03-10 03:00:00-04'),
+                ('2024-11-03 01:30:00.5-04'),
+                ('2024-11-03 01:30:00.5-05');",
+        )
+        .await
+        .unwrap();
+
+    // New sessions default to a time zone with daylight saving time and a non ISO date style,
+    // which the replication connection must override.
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .simple_query(&format!(
+            "alter database {} set timezone = 'America/New_York'; \
+            alter database {} set datestyle = 'SQL, DMY';",
+            quote_identifier(&database.config.name),
+            quote_identifier(&database.config.name)
+        ))
+        .await
+        .unwrap();
+
+    let client = PgReplicationClient::connect(database.config.clone())
+        .await
+        .unwrap();
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await
+        .unwrap();
+    let table_schemas = transaction
+        .get_table_schemas(&[table_id], None)
+        .await
+        .unwrap();
+    let table_schema = &table_schemas[&table_id];
+
+    let stream = transaction
+        .get_table_copy_stream(
+            table_id,
+            &table_schema.column_schemas,
+            None,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+
+    let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+    let stream = TableCopyStream::wrap(stream, &table_schema.column_schemas, &converter);
+    pin!(stream);
+    let mut values = vec![];
+    while let Some(row) = stream.next().await {
+        let mut row = row.unwrap();
+        values.push(row.values.pop().unwrap());
+    }
+    transaction.commit().await.unwrap();
+
+    let utc = |str: &str| {
+        Cell::TimeStampTz(
+            DateTime::parse_from_rfc3339(str)
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+    };
+    assert_eq!(
+        values,
+        vec![
+            utc("2024-03-10T06:59:59.999999Z"),
+            utc("2024-03-10T07:00:00Z"),
+            utc("2024-11-03T05:30:00.5Z"),
+            utc("2024-11-03T06:30:00.5Z"),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_money_columns_are_copied_as_numerics() {
+    init_test_tracing();
+    let database = spawn_database().await;
+
+    let table_id = database
+        .create_table(test_table_name("payments"), &[("amount", "money")])
+        .await
+        .unwrap();
+    database
+        .client
+        .as_ref()
+        .unwrap()
+        .batch_execute(
+            "insert into test.payments (amount) values
+                (1234.56),
+                (-1234.56),
+                (-0.01);",
+        )
+        .await
+        .unwrap();
+
+    let client = PgReplicationClient::connect(database.config.clone())
+        .await
+        .unwrap();
+    let (transaction, _) = client
+        .create_slot_with_transaction(&test_slot_name("my_slot"))
+        .await
+        .unwrap();
+    let table_schemas = transaction
+        .get_table_schemas(&[table_id], None)
+        .await
+        .unwrap();
+    let table_schema = &table_schemas[&table_id];
+
+    let stream = transaction
+        .get_table_copy_stream(
+            table_id,
+            &table_schema.column_schemas,
+            None,
+            &CopyConfig::default(),
+        )
+        .await
+        .unwrap();
+
+    let converter = TableRowConverter::new(&CopyConfig::default()).unwrap();
+    let stream = TableCopyStream::wrap(stream, &table_schema.column_schemas, &converter);
+    pin!(stream);
+    let mut values = vec![];
+    while let Some(row) = stream.next().await {
+        let mut row = row.unwrap();
+        values.push(row.values.pop().unwrap());
+    }
+    transaction.commit().await.unwrap();
+
+    let numeric = |str: &str| Cell::Numeric(str.parse().unwrap());
+    assert_eq!(
+        values,
+        vec![numeric("1234.56"), numeric("-1234.56"), numeric("-0.01")]
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_publication_creation_and_check() {
     init_test_tracing();
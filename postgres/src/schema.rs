@@ -99,9 +99,12 @@ impl ColumnSchema {
     /// relation messages received via CDC. The reason for skipping the `nullable` field is that
     /// unfortunately Postgres doesn't seem to propagate nullable information of a column via
     /// relation messages.
+    ///
+    /// Types are compared by oid, since relation messages don't describe them any further, e.g.
+    /// with the fields of composite types.
     fn partial_eq(&self, other: &ColumnSchema) -> bool {
         self.name == other.name
-            && self.typ == other.typ
+            && self.typ.oid() == other.typ.oid()
             && self.modifier == other.modifier
             && self.primary == other.primary
     }
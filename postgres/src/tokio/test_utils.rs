@@ -285,6 +285,18 @@ impl PgDatabase<Client> {
         }
     }
 
+    /// Creates a new database using the server `encoding`, e.g. `LATIN1`, instead of the server's
+    /// default one.
+    pub async fn new_with_encoding(config: PgConnectionConfig, encoding: &str) -> Self {
+        let client = create_pg_database_with_encoding(&config, Some(encoding)).await;
+
+        Self {
+            config,
+            client: Some(client),
+            destroy_on_drop: true,
+        }
+    }
+
     /// Begins a new transaction.
     ///
     /// Returns a `Transaction` object that can be used to execute queries within the transaction.
@@ -339,6 +351,19 @@ pub fn id_column_schema() -> ColumnSchema {
 /// creates a new database, and returns a [`Client`] connected to the new database.
 /// Panics if the connection fails or if database creation fails.
 pub async fn create_pg_database(config: &PgConnectionConfig) -> Client {
+    create_pg_database_with_encoding(config, None).await
+}
+
+/// Creates a new PostgreSQL database with the given server `encoding` and returns a client
+/// connected to it.
+///
+/// When an `encoding` is given, the database is created from `template0` with the `C` locale,
+/// which is compatible with every encoding. Panics if the connection fails or if database
+/// creation fails.
+pub async fn create_pg_database_with_encoding(
+    config: &PgConnectionConfig,
+    encoding: Option<&str>,
+) -> Client {
     // Create the database via a single connection
     let (client, connection) = {
         let config: tokio_postgres::Config = config.without_db();
@@ -356,8 +381,15 @@ pub async fn create_pg_database(config: &PgConnectionConfig) -> Client {
     });
 
     // Create the database
+    let create_database_query = match encoding {
+        Some(encoding) => format!(
+            r#"create database "{}" encoding '{encoding}' lc_collate 'C' lc_ctype 'C' template template0;"#,
+            config.name
+        ),
+        None => format!(r#"create database "{}";"#, config.name),
+    };
     client
-        .execute(&*format!(r#"create database "{}";"#, config.name), &[])
+        .execute(&create_database_query, &[])
         .await
         .expect("Failed to create database");
 
@@ -10,3 +10,9 @@ pub fn convert_type_oid_to_type(type_oid: u32) -> Type {
         "pg_catalog".to_string(),
     ))
 }
+
+/// Converts a type oid to a [`Type`], naming the types which aren't built in with the `name` and
+/// `schema` read from `pg_type`, e.g. the types defined by extensions.
+pub fn convert_named_type_oid_to_type(type_oid: u32, name: String, schema: String) -> Type {
+    Type::from_oid(type_oid).unwrap_or_else(|| Type::new(name, type_oid, Kind::Simple, schema))
+}
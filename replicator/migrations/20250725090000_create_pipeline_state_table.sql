@@ -0,0 +1,7 @@
+create table
+    etl.pipeline_state (
+        pipeline_id bigint primary key,
+        -- The consistent point of the apply worker slot, from which the changes are streamed after
+        -- the initial snapshot. Stored as text like `replication_state.sync_done_lsn`.
+        snapshot_lsn text not null
+    );
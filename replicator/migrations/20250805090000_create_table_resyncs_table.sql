@@ -0,0 +1,12 @@
+create table
+    etl.table_resyncs (
+        pipeline_id bigint not null,
+        table_id oid not null,
+        requested_at timestamptz not null default now(),
+        -- Set by the pipeline once it truncated the table in the destination and reset its state to
+        -- `init`, so that the table is copied again.
+        started_at timestamptz null,
+        -- The number of rows copied so far by the table sync worker of the table.
+        rows_copied bigint not null default 0,
+        primary key (pipeline_id, table_id)
+    );
@@ -0,0 +1,13 @@
+create table
+    etl.table_copy_progress (
+        pipeline_id bigint not null,
+        table_id oid not null,
+        -- The number of rows copied so far by the table sync worker of the table.
+        rows_copied bigint not null,
+        -- The number of rows of the table estimated from `pg_class.reltuples` when the copy
+        -- started, which is null if the table was never vacuumed nor analyzed.
+        estimated_rows bigint null,
+        -- When the progress was last reported, to tell a stuck copy apart from a slow one.
+        updated_at timestamptz not null default now(),
+        primary key (pipeline_id, table_id)
+    );
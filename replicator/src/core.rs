@@ -1,18 +1,23 @@
 use crate::config::load_replicator_config;
+use crate::lag_alert::spawn_lag_alert_monitor;
+use crate::metrics::spawn_metrics_listener;
 use crate::migrations::migrate_state_store;
 use config::shared::{
-    BatchConfig, DestinationConfig, PgConnectionConfig, PipelineConfig, ReplicatorConfig,
-    RetryConfig,
+    BatchConfig, DestinationConfig, LagAlertConfig, PgConnectionConfig, PipelineConfig,
+    ReplicatorConfig, RetryConfig,
 };
 use etl::destination::bigquery::BigQueryDestination;
+use etl::destination::column_filter::ColumnFilter;
 use etl::destination::memory::MemoryDestination;
+use etl::destination::write_limit::WriteLimiter;
 use etl::encryption::bigquery::install_crypto_provider_once;
-use etl::pipeline::Pipeline;
+use etl::pipeline::{Pipeline, PipelineError};
 use etl::state::store::base::StateStore;
 use etl::state::store::postgres::PostgresStateStore;
 use etl::{destination::base::Destination, pipeline::PipelineId};
 use secrecy::ExposeSecret;
 use std::fmt;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
 pub async fn start_replicator() -> anyhow::Result<()> {
@@ -20,6 +25,7 @@ pub async fn start_replicator() -> anyhow::Result<()> {
     let replicator_config = load_replicator_config()?;
 
     log_config(&replicator_config);
+    let shutdown_timeout = Duration::from_millis(replicator_config.shutdown_timeout_ms);
 
     // We initialize the state store, which for the replicator is not configurable.
     let state_store = init_state_store(
@@ -28,6 +34,26 @@ pub async fn start_replicator() -> anyhow::Result<()> {
     )
     .await?;
 
+    // We monitor the replication lag for as long as the pipeline runs, if an alert is configured.
+    let _lag_alert_monitor = replicator_config.lag_alert.clone().map(|lag_alert| {
+        spawn_lag_alert_monitor(
+            lag_alert,
+            replicator_config.pipeline.id,
+            replicator_config.pipeline.replication_slot.clone(),
+            replicator_config
+                .supabase
+                .as_ref()
+                .map(|supabase| supabase.project_ref.clone()),
+            &replicator_config.pipeline.pg_connection,
+        )
+    });
+
+    // We serve the metrics of the pipeline for as long as it runs, if a listener is configured.
+    let _metrics_listener = match &replicator_config.metrics {
+        Some(metrics) => Some(spawn_metrics_listener(metrics).await?),
+        None => None,
+    };
+
     // For each destination, we start the pipeline. This is more verbose due to static dispatch, but
     // we prefer more performance at the cost of ergonomics.
     match &replicator_config.destination {
@@ -40,23 +66,47 @@ pub async fn start_replicator() -> anyhow::Result<()> {
                 state_store,
                 destination,
             );
-            start_pipeline(pipeline).await?;
+            start_pipeline(pipeline, shutdown_timeout).await?;
         }
         DestinationConfig::BigQuery {
             project_id,
             dataset_id,
             service_account_key,
             max_staleness_mins,
+            column_filter,
+            max_concurrent_writes,
+            identifier_overflow,
+            row_size_limit,
+            schema_mapping,
         } => {
             install_crypto_provider_once();
 
-            let destination = BigQueryDestination::new_with_key(
+            let mut destination = BigQueryDestination::new_with_key(
                 project_id.clone(),
                 dataset_id.clone(),
                 service_account_key.expose_secret(),
                 *max_staleness_mins,
             )
             .await?;
+            if let Some(column_filter) = column_filter {
+                destination = destination.with_column_filter(ColumnFilter::new(column_filter));
+            }
+            if let Some(max_concurrent_writes) = max_concurrent_writes {
+                destination = destination
+                    .with_write_limiter(WriteLimiter::new(*max_concurrent_writes as usize));
+            }
+            if let Some(identifier_overflow) = identifier_overflow {
+                destination = destination.with_identifier_overflow(*identifier_overflow);
+            }
+            if let Some(row_size_limit) = row_size_limit {
+                destination = destination.with_row_size_limit(
+                    row_size_limit.max_bytes as usize,
+                    row_size_limit.policy,
+                );
+            }
+            if let Some(schema_mapping) = schema_mapping {
+                destination = destination.with_schema_mapping(*schema_mapping);
+            }
 
             let pipeline = Pipeline::new(
                 replicator_config.pipeline.id,
@@ -64,7 +114,7 @@ pub async fn start_replicator() -> anyhow::Result<()> {
                 state_store,
                 destination,
             );
-            start_pipeline(pipeline).await?;
+            start_pipeline(pipeline, shutdown_timeout).await?;
         }
     }
 
@@ -76,6 +126,9 @@ pub async fn start_replicator() -> anyhow::Result<()> {
 fn log_config(config: &ReplicatorConfig) {
     log_destination_config(&config.destination);
     log_pipeline_config(&config.pipeline);
+    if let Some(lag_alert) = &config.lag_alert {
+        log_lag_alert_config(lag_alert);
+    }
 }
 
 fn log_destination_config(config: &DestinationConfig) {
@@ -88,10 +141,23 @@ fn log_destination_config(config: &DestinationConfig) {
             dataset_id,
             service_account_key: _,
             max_staleness_mins,
+            column_filter,
+            max_concurrent_writes,
+            identifier_overflow,
+            row_size_limit,
+            schema_mapping,
         } => {
             debug!(
                 project_id,
-                dataset_id, max_staleness_mins, "using bigquery destination config"
+                dataset_id,
+                max_staleness_mins,
+                column_filter_tables = column_filter.as_ref().map(|c| c.tables.len()),
+                max_concurrent_writes,
+                identifier_overflow = identifier_overflow.map(|p| format!("{p:?}")),
+                max_row_size_bytes = row_size_limit.map(|l| l.max_bytes),
+                oversized_row_policy = row_size_limit.map(|l| format!("{:?}", l.policy)),
+                schema_mapping = schema_mapping.map(|m| format!("{m:?}")),
+                "using bigquery destination config"
             )
         }
     }
@@ -138,6 +204,16 @@ fn log_apply_worker_init_retry(config: &RetryConfig) {
     )
 }
 
+fn log_lag_alert_config(config: &LagAlertConfig) {
+    debug!(
+        max_lag_bytes = config.max_lag_bytes,
+        duration_ms = config.duration_ms,
+        repeat_interval_ms = config.repeat_interval_ms,
+        check_interval_ms = config.check_interval_ms,
+        "lag alert config"
+    )
+}
+
 async fn init_state_store(
     pipeline_id: PipelineId,
     pg_connection_config: PgConnectionConfig,
@@ -147,7 +223,10 @@ async fn init_state_store(
 }
 
 #[tracing::instrument(skip(pipeline), fields(pipeline_id = pipeline.id()))]
-async fn start_pipeline<S, D>(mut pipeline: Pipeline<S, D>) -> anyhow::Result<()>
+async fn start_pipeline<S, D>(
+    mut pipeline: Pipeline<S, D>,
+    shutdown_timeout: Duration,
+) -> anyhow::Result<()>
 where
     S: StateStore + Clone + Send + Sync + 'static,
     D: Destination + Clone + Send + Sync + fmt::Debug + 'static,
@@ -155,47 +234,47 @@ where
     // Start the pipeline.
     pipeline.start().await?;
 
-    // Spawn a task to listen for shutdown signals and trigger shutdown.
     let shutdown_tx = pipeline.shutdown_tx();
-    let shutdown_handle = tokio::spawn(async move {
-        use tokio::signal::unix::{SignalKind, signal};
-
-        // Listen for SIGTERM, sent by Kubernetes before SIGKILL during pod termination.
-        //
-        // If the process is killed before shutdown completes, the pipeline may become corrupted,
-        // depending on the state store and destination implementations.
-        let mut sigterm =
-            signal(SignalKind::terminate()).expect("failed to register SIGTERM handler");
-
-        tokio::select! {
-            _ = tokio::signal::ctrl_c() => {
-                info!("SIGINT (Ctrl+C) received, shutting down pipeline");
-            }
-            _ = sigterm.recv() => {
-                info!("SIGTERM received, shutting down pipeline");
+    let wait = pipeline.wait();
+    tokio::pin!(wait);
+
+    // Wait for the pipeline to finish, either on its own or after a shutdown signal.
+    let result = tokio::select! {
+        result = &mut wait => result,
+        _ = shutdown_signal() => {
+            // The workers write their buffered events to the destination and confirm their
+            // position before stopping. If they don't within the timeout, the replicator exits
+            // anyway, so that the logs are flushed before the process is killed, and the events
+            // not confirmed yet are streamed again on restart.
+            if let Err(e) = shutdown_tx.shutdown() {
+                warn!("failed to send shutdown signal: {:?}", e);
             }
-        }
 
-        if let Err(e) = shutdown_tx.shutdown() {
-            warn!("failed to send shutdown signal: {:?}", e);
-            return;
+            tokio::time::timeout(shutdown_timeout, wait)
+                .await
+                .unwrap_or(Err(PipelineError::ShutdownTimedOut(shutdown_timeout)))
         }
-
-        info!("pipeline shutdown successfully")
-    });
-
-    // Wait for the pipeline to finish (either normally or via shutdown).
-    let result = pipeline.wait().await;
-
-    // Ensure the shutdown task is finished before returning.
-    // If the pipeline finished before Ctrl+C, we want to abort the shutdown task.
-    // If Ctrl+C was pressed, the shutdown task will have already triggered shutdown.
-    // We don't care about the result of the shutdown_handle, but we should abort it if it's still running.
-    shutdown_handle.abort();
-    let _ = shutdown_handle.await;
+    };
 
     // Propagate any pipeline error as anyhow error.
     result?;
 
     Ok(())
 }
+
+/// Waits for a `SIGTERM`, sent by Kubernetes before `SIGKILL` during pod termination, or a
+/// `SIGINT`.
+async fn shutdown_signal() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to register SIGTERM handler");
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {
+            info!("SIGINT (Ctrl+C) received, shutting down pipeline");
+        }
+        _ = sigterm.recv() => {
+            info!("SIGTERM received, shutting down pipeline");
+        }
+    }
+}
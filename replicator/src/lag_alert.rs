@@ -0,0 +1,314 @@
+use config::shared::{
+    IntoConnectOptions, LagAlertConfig, PgConnectionConfig, ReplicationSlotConfig,
+};
+use etl::pipeline::PipelineId;
+use etl::replication::slot::get_worker_slot_name;
+use etl::workers::base::WorkerType;
+use serde::Serialize;
+use sqlx::PgPool;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+/// Errors that can occur while monitoring the replication lag.
+#[derive(Debug, Error)]
+pub enum LagAlertError {
+    #[error("Failed to query the replication lag: {0}")]
+    Query(#[from] sqlx::Error),
+
+    #[error("Failed to send the lag alert to the webhook: {0}")]
+    Webhook(#[from] reqwest::Error),
+}
+
+/// The body sent to the webhook when the replication lag exceeds its threshold.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LagAlert {
+    pub pipeline_id: PipelineId,
+    pub tenant_id: Option<String>,
+    pub lag_bytes: u64,
+    pub max_lag_bytes: u64,
+}
+
+/// Tracks how long the lag has been above its threshold and debounces repeated alerts.
+#[derive(Debug)]
+struct LagAlertState {
+    max_lag_bytes: u64,
+    duration: Duration,
+    repeat_interval: Duration,
+    above_since: Option<Instant>,
+    last_alert_at: Option<Instant>,
+}
+
+impl LagAlertState {
+    fn new(config: &LagAlertConfig) -> Self {
+        Self {
+            max_lag_bytes: config.max_lag_bytes,
+            duration: Duration::from_millis(config.duration_ms),
+            repeat_interval: Duration::from_millis(config.repeat_interval_ms),
+            above_since: None,
+            last_alert_at: None,
+        }
+    }
+
+    /// Records a lag measurement taken at `now` and returns whether an alert must be sent.
+    ///
+    /// An alert is sent once the lag stayed above the threshold for the configured duration, and
+    /// then at most once per repeat interval until the lag goes back below the threshold.
+    fn observe(&mut self, lag_bytes: u64, now: Instant) -> bool {
+        if lag_bytes <= self.max_lag_bytes {
+            self.above_since = None;
+            self.last_alert_at = None;
+
+            return false;
+        }
+
+        let above_since = *self.above_since.get_or_insert(now);
+        if now.duration_since(above_since) < self.duration {
+            return false;
+        }
+
+        if let Some(last_alert_at) = self.last_alert_at
+            && now.duration_since(last_alert_at) < self.repeat_interval
+        {
+            return false;
+        }
+
+        self.last_alert_at = Some(now);
+
+        true
+    }
+}
+
+/// Sends lag alerts to a webhook.
+#[derive(Debug, Clone)]
+struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    fn new(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+
+    async fn notify(&self, alert: &LagAlert) -> Result<(), LagAlertError> {
+        self.client
+            .post(&self.url)
+            .json(alert)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+/// Handle to the lag alert monitor task, which is aborted when the handle is dropped.
+#[derive(Debug)]
+pub struct LagAlertMonitorHandle(JoinHandle<()>);
+
+impl Drop for LagAlertMonitorHandle {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Spawns a task which periodically checks the lag of the pipeline's replication slot, which is
+/// `replication_slot` if set, and alerts the configured webhook when it falls behind.
+pub fn spawn_lag_alert_monitor(
+    config: LagAlertConfig,
+    pipeline_id: PipelineId,
+    replication_slot: Option<ReplicationSlotConfig>,
+    tenant_id: Option<String>,
+    pg_connection_config: &PgConnectionConfig,
+) -> LagAlertMonitorHandle {
+    let options: PgConnectOptions = pg_connection_config.with_db();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect_lazy_with(options);
+
+    let handle = tokio::spawn(async move {
+        let Ok(slot_name) =
+            get_worker_slot_name(pipeline_id, WorkerType::Apply, replication_slot.as_ref())
+        else {
+            warn!(
+                pipeline_id,
+                "the lag alert monitor could not compute the slot name"
+            );
+            return;
+        };
+
+        info!(
+            pipeline_id,
+            max_lag_bytes = config.max_lag_bytes,
+            duration_ms = config.duration_ms,
+            "starting the replication lag alert monitor"
+        );
+
+        let mut state = LagAlertState::new(&config);
+        let notifier = WebhookNotifier::new(config.webhook_url.clone());
+
+        let mut interval = tokio::time::interval(Duration::from_millis(config.check_interval_ms));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            let lag_bytes = match get_slot_lag(&pool, &slot_name).await {
+                Ok(Some(lag_bytes)) => lag_bytes,
+                // The slot is created by the apply worker, so it might not exist yet.
+                Ok(None) => continue,
+                Err(err) => {
+                    warn!("failed to check the replication lag: {err}");
+                    continue;
+                }
+            };
+
+            debug!(pipeline_id, lag_bytes, "checked the replication lag");
+
+            if !state.observe(lag_bytes, Instant::now()) {
+                continue;
+            }
+
+            let alert = LagAlert {
+                pipeline_id,
+                tenant_id: tenant_id.clone(),
+                lag_bytes,
+                max_lag_bytes: config.max_lag_bytes,
+            };
+
+            warn!(
+                pipeline_id,
+                tenant_id = alert.tenant_id,
+                lag_bytes,
+                max_lag_bytes = config.max_lag_bytes,
+                "replication lag is above the alert threshold"
+            );
+
+            if let Err(err) = notifier.notify(&alert).await {
+                warn!("failed to send the replication lag alert: {err}");
+            }
+        }
+    });
+
+    LagAlertMonitorHandle(handle)
+}
+
+/// Returns the lag, in bytes, between the current WAL position and the position confirmed by
+/// `slot_name`, or `None` if the slot doesn't exist or hasn't confirmed any position yet.
+async fn get_slot_lag(pool: &PgPool, slot_name: &str) -> Result<Option<u64>, LagAlertError> {
+    let lag_bytes: Option<Option<i64>> = sqlx::query_scalar(
+        r#"
+        select pg_wal_lsn_diff(pg_current_wal_lsn(), confirmed_flush_lsn)::bigint
+        from pg_replication_slots
+        where slot_name = $1
+        "#,
+    )
+    .bind(slot_name)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(lag_bytes.flatten().map(|lag_bytes| lag_bytes.max(0) as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config(webhook_url: String) -> LagAlertConfig {
+        LagAlertConfig {
+            webhook_url,
+            max_lag_bytes: 1024,
+            duration_ms: 60_000,
+            repeat_interval_ms: 300_000,
+            check_interval_ms: 10_000,
+        }
+    }
+
+    #[test]
+    fn alerts_only_after_lag_stays_above_threshold_for_duration() {
+        let mut state = LagAlertState::new(&config(String::new()));
+        let start = Instant::now();
+
+        assert!(!state.observe(2048, start));
+        assert!(!state.observe(2048, start + Duration::from_secs(30)));
+        assert!(state.observe(2048, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn repeated_alerts_are_debounced() {
+        let mut state = LagAlertState::new(&config(String::new()));
+        let start = Instant::now();
+
+        assert!(!state.observe(2048, start));
+        assert!(state.observe(2048, start + Duration::from_secs(60)));
+        assert!(!state.observe(4096, start + Duration::from_secs(120)));
+        assert!(!state.observe(4096, start + Duration::from_secs(359)));
+        assert!(state.observe(4096, start + Duration::from_secs(360)));
+    }
+
+    #[test]
+    fn recovering_resets_the_alert() {
+        let mut state = LagAlertState::new(&config(String::new()));
+        let start = Instant::now();
+
+        assert!(!state.observe(2048, start));
+        assert!(state.observe(2048, start + Duration::from_secs(60)));
+        assert!(!state.observe(512, start + Duration::from_secs(70)));
+        assert!(!state.observe(2048, start + Duration::from_secs(80)));
+        assert!(state.observe(2048, start + Duration::from_secs(140)));
+    }
+
+    #[tokio::test]
+    async fn webhook_receives_the_alert() {
+        let server = MockServer::start().await;
+        let alert = LagAlert {
+            pipeline_id: 42,
+            tenant_id: Some("abcdefghijklmnopqrst".to_string()),
+            lag_bytes: 2048,
+            max_lag_bytes: 1024,
+        };
+
+        Mock::given(method("POST"))
+            .and(path("/alerts"))
+            .and(body_json(&alert))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notifier = WebhookNotifier::new(format!("{}/alerts", server.uri()));
+        notifier.notify(&alert).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn webhook_errors_are_reported() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let notifier = WebhookNotifier::new(server.uri());
+        let alert = LagAlert {
+            pipeline_id: 42,
+            tenant_id: None,
+            lag_bytes: 2048,
+            max_lag_bytes: 1024,
+        };
+
+        assert!(matches!(
+            notifier.notify(&alert).await,
+            Err(LagAlertError::Webhook(_))
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use ::config::Environment;
 use std::sync::Arc;
-use telemetry::init_tracing;
+use telemetry::{TracingConfig, init_tracing};
 use thiserror::__private::AsDynError;
 use tracing::{error, info};
 
@@ -9,22 +9,28 @@ use crate::core::start_replicator;
 
 mod config;
 mod core;
+mod lag_alert;
+mod metrics;
 mod migrations;
 
 fn main() -> anyhow::Result<()> {
     // Initialize tracing from the binary name
-    let _log_flusher = init_tracing(env!("CARGO_BIN_NAME"))?;
+    let _log_flusher = init_tracing(env!("CARGO_BIN_NAME"), &TracingConfig::default())?;
 
     // Initialize Sentry before the async runtime starts
     let _sentry_guard = init_sentry()?;
 
     // We start the runtime.
-    tokio::runtime::Builder::new_multi_thread()
+    let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
-        .build()?
-        .block_on(async_main())?;
+        .build()?;
+    let result = runtime.block_on(async_main());
 
-    Ok(())
+    // The workers still running after a timed out shutdown are not waited for, so that the logs
+    // are flushed before the process is killed.
+    runtime.shutdown_background();
+
+    result
 }
 
 async fn async_main() -> anyhow::Result<()> {
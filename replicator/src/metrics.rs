@@ -0,0 +1,133 @@
+use config::shared::MetricsConfig;
+use etl::metrics::{METRICS_CONTENT_TYPE, encode_metrics};
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Maximum size of the head of a request, which is all that is read of it.
+const MAX_REQUEST_HEAD_BYTES: usize = 8 * 1024;
+
+/// Handle to the metrics listener task, which is aborted when the handle is dropped.
+#[derive(Debug)]
+pub struct MetricsListenerHandle {
+    local_addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MetricsListenerHandle {
+    /// Returns the address the listener is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for MetricsListenerHandle {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Binds a listener on the configured address and spawns a task serving the metrics registered
+/// in the process, like the counters of the table copies, at `GET /metrics`.
+///
+/// The metrics are registered in the process running the pipeline, so they can only be scraped
+/// from the replicator.
+pub async fn spawn_metrics_listener(config: &MetricsConfig) -> io::Result<MetricsListenerHandle> {
+    let listener = TcpListener::bind((config.host.as_str(), config.port)).await?;
+    let local_addr = listener.local_addr()?;
+
+    info!(%local_addr, "serving metrics at /metrics");
+
+    let handle = tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    warn!("failed to accept a metrics connection: {err}");
+                    continue;
+                }
+            };
+
+            tokio::spawn(async move {
+                if let Err(err) = serve_metrics(stream).await {
+                    warn!("failed to serve the metrics: {err}");
+                }
+            });
+        }
+    });
+
+    Ok(MetricsListenerHandle { local_addr, handle })
+}
+
+/// Answers a single HTTP request on `stream`, then closes it.
+async fn serve_metrics(mut stream: TcpStream) -> io::Result<()> {
+    let mut head = Vec::with_capacity(1024);
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() >= MAX_REQUEST_HEAD_BYTES {
+            break;
+        }
+
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+
+    let (status, content_type, body) = if head.starts_with(b"GET /metrics ") {
+        match encode_metrics() {
+            Ok(body) => ("200 OK", METRICS_CONTENT_TYPE, body),
+            Err(err) => (
+                "500 Internal Server Error",
+                "text/plain",
+                format!("the metrics could not be encoded: {err}").into_bytes(),
+            ),
+        }
+    } else {
+        ("404 Not Found", "text/plain", b"not found".to_vec())
+    };
+
+    let response_head = format!(
+        "HTTP/1.1 {status}\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(response_head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use etl::metrics::TableCopyMetrics;
+
+    #[tokio::test]
+    async fn copy_counters_are_served() {
+        let listener = spawn_metrics_listener(&MetricsConfig {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+        })
+        .await
+        .unwrap();
+        // A copy of the table counts its rows as it streams them.
+        TableCopyMetrics::for_table(42).inc_row(10);
+
+        let response = reqwest::get(format!("http://{}/metrics", listener.local_addr()))
+            .await
+            .unwrap();
+
+        assert!(response.status().is_success());
+        let body = response.text().await.unwrap();
+        assert!(body.contains("rows_copied_total{table=\"42\"} 1"));
+        assert!(body.contains("bytes_copied_total{table=\"42\"} 10"));
+
+        let response = reqwest::get(format!("http://{}/other", listener.local_addr()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+}
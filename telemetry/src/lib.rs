@@ -1,18 +1,30 @@
 use config::Environment;
+use log_compression::{LogCompressor, LogFiles};
+use opentelemetry::KeyValue;
+use opentelemetry::trace::{TraceError, TracerProvider as _};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{Resource, runtime, trace::TracerProvider};
 use std::io::Error;
+use std::path::PathBuf;
 use std::{
     backtrace::{Backtrace, BacktraceStatus},
     panic::PanicHookInfo,
     sync::Once,
 };
 use thiserror::Error;
+use tracing::Subscriber;
 use tracing::subscriber::{SetGlobalDefaultError, set_global_default};
 use tracing_appender::{
     non_blocking::WorkerGuard,
     rolling::{self, InitError},
 };
 use tracing_log::{LogTracer, log_tracer::SetLoggerError};
-use tracing_subscriber::{EnvFilter, FmtSubscriber, fmt};
+use tracing_subscriber::filter::{LevelFilter, ParseError};
+use tracing_subscriber::{EnvFilter, Layer, fmt, layer::SubscriberExt, registry::LookupSpan};
+
+pub use tracing_appender::rolling::Rotation;
+
+mod log_compression;
 
 #[derive(Debug, Error)]
 pub enum TracingError {
@@ -27,12 +39,93 @@ pub enum TracingError {
 
     #[error("an io error occurred: {0}")]
     Io(#[from] Error),
+
+    #[error("no OTLP endpoint was given and {OTEL_EXPORTER_OTLP_ENDPOINT} is not set")]
+    MissingOtlpEndpoint,
+
+    #[error("failed to build the OTLP span exporter: {0}")]
+    OtlpExporter(#[from] TraceError),
+
+    #[error("invalid default log filter: {0}")]
+    InvalidDefaultLogFilter(#[from] ParseError),
 }
 
+/// The environment variable read for the OTLP endpoint when none is given to
+/// [`init_tracing_with_otlp`].
+const OTEL_EXPORTER_OTLP_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// The environment variable which, when set to `1` or `true`, makes the process abort on panics by
+/// default, see [`TracingConfig::abort_on_panic`].
+const ABORT_ON_PANIC: &str = "ABORT_ON_PANIC";
+
+/// Flushes the logs written to files and the spans exported via OTLP when dropped.
 #[must_use]
-pub enum LogFlusher {
-    Flusher(WorkerGuard),
-    NullFlusher,
+pub struct LogFlusher {
+    _file_guard: Option<WorkerGuard>,
+    _log_compressor: Option<LogCompressor>,
+    tracer_provider: Option<TracerProvider>,
+}
+
+impl Drop for LogFlusher {
+    fn drop(&mut self) {
+        // Shutting down the provider exports the spans which are still buffered.
+        if let Some(tracer_provider) = self.tracer_provider.take()
+            && let Err(err) = tracer_provider.shutdown()
+        {
+            eprintln!("failed to shut down the OTLP span exporter: {err}");
+        }
+    }
+}
+
+/// Configuration of the log files written in production.
+#[derive(Debug, Clone)]
+pub struct TracingConfig {
+    /// The directory in which the log files are written.
+    pub directory: PathBuf,
+    /// How often a new log file is started.
+    pub rotation: Rotation,
+    /// The maximum number of log files kept, the oldest ones are deleted first. The compressed
+    /// log files are counted too.
+    pub max_log_files: usize,
+    /// The prefix of the log file names, the app name if not set.
+    pub filename_prefix: Option<String>,
+    /// The suffix of the log file names.
+    pub filename_suffix: String,
+    /// Whether the log files are gzip-compressed once they're rotated out, in which case `.gz` is
+    /// appended to their names. Only the file being written to is kept uncompressed.
+    ///
+    /// The directory is checked for rotated files periodically by a background thread, so they
+    /// are compressed a little while after being rotated out.
+    pub compress_rotated_files: bool,
+    /// Whether the process is aborted after a panic is logged, so that a supervisor restarts it
+    /// rather than it running on with a panicked task or thread. Defaults to whether the
+    /// `ABORT_ON_PANIC` environment variable is set to `1` or `true`.
+    ///
+    /// Logs buffered for the log files are lost on abort, but the panic is still printed to
+    /// stderr by the previous panic hook, which runs before aborting.
+    pub abort_on_panic: bool,
+    /// The filter applied to the logs when the `RUST_LOG` environment variable is not set, or
+    /// can't be parsed, written in the same syntax, e.g. `warn` or `warn,etl=info`. Defaults to
+    /// `info`.
+    ///
+    /// If `None`, nothing is logged unless `RUST_LOG` is set.
+    pub default_log_filter: Option<String>,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("logs"),
+            rotation: Rotation::DAILY,
+            max_log_files: 5,
+            filename_prefix: None,
+            filename_suffix: "log".to_string(),
+            compress_rotated_files: true,
+            abort_on_panic: std::env::var(ABORT_ON_PANIC)
+                .is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true")),
+            default_log_filter: Some("info".to_string()),
+        }
+    }
 }
 
 static INIT_TEST_TRACING: Once = Once::new();
@@ -48,14 +141,56 @@ pub fn init_test_tracing() {
             // Needed because if no env is set, it defaults to prod, which logs to files instead of terminal,
             // and we need to log to terminal when `ENABLE_TRACING` env var is set.
             Environment::Dev.set();
-            let _log_flusher =
-                init_tracing("test").expect("Failed to initialize tracing for tests");
+            let _log_flusher = init_tracing("test", &TracingConfig::default())
+                .expect("Failed to initialize tracing for tests");
         }
     });
 }
 
 /// Initializes tracing for the application.
-pub fn init_tracing(app_name: &str) -> Result<LogFlusher, TracingError> {
+///
+/// In production, the logs are written to the files described by `config`.
+pub fn init_tracing(app_name: &str, config: &TracingConfig) -> Result<LogFlusher, TracingError> {
+    init_tracing_with_provider(app_name, config, None)
+}
+
+/// Initializes tracing for the application like [`init_tracing`], and also exports the spans via
+/// OTLP to the collector at `endpoint`.
+///
+/// If `endpoint` is not given, the `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable is used
+/// instead. The spans are exported in batches by a task of the current Tokio runtime, so this
+/// function must be called from within a runtime.
+pub fn init_tracing_with_otlp(
+    app_name: &str,
+    config: &TracingConfig,
+    endpoint: Option<&str>,
+) -> Result<LogFlusher, TracingError> {
+    let endpoint = match endpoint {
+        Some(endpoint) => endpoint.to_string(),
+        None => std::env::var(OTEL_EXPORTER_OTLP_ENDPOINT)
+            .map_err(|_| TracingError::MissingOtlpEndpoint)?,
+    };
+
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+    let tracer_provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            app_name.to_string(),
+        )]))
+        .build();
+
+    init_tracing_with_provider(app_name, config, Some(tracer_provider))
+}
+
+fn init_tracing_with_provider(
+    app_name: &str,
+    config: &TracingConfig,
+    tracer_provider: Option<TracerProvider>,
+) -> Result<LogFlusher, TracingError> {
     // Initialize the log tracer to capture logs from the `log` crate
     // and send them to the `tracing` subscriber. This captures logs
     // from libraries that use the `log` crate.
@@ -63,33 +198,83 @@ pub fn init_tracing(app_name: &str) -> Result<LogFlusher, TracingError> {
 
     let is_prod = Environment::load()?.is_prod();
 
-    // Set the default log level to `info` if not specified in the `RUST_LOG` environment variable.
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => default_log_filter(config)?,
+    };
 
-    let log_flusher = if is_prod {
-        configure_prod_tracing(filter, app_name)?
+    let otlp_layer = tracer_provider.as_ref().map(|tracer_provider| {
+        let tracer = tracer_provider.tracer(app_name.to_string());
+        tracing_opentelemetry::layer().with_tracer(tracer)
+    });
+
+    let (fmt_layer, file_guard, log_compressor) = if is_prod {
+        let (fmt_layer, file_guard) = prod_fmt_layer(app_name, config)?;
+        let log_compressor = if config.compress_rotated_files {
+            Some(LogCompressor::spawn(LogFiles {
+                directory: config.directory.clone(),
+                prefix: config
+                    .filename_prefix
+                    .as_deref()
+                    .unwrap_or(app_name)
+                    .to_string(),
+                suffix: config.filename_suffix.clone(),
+                max_log_files: config.max_log_files,
+            })?)
+        } else {
+            None
+        };
+        (fmt_layer, Some(file_guard), log_compressor)
     } else {
-        configure_dev_tracing(filter)?
+        (dev_fmt_layer(), None, None)
     };
 
-    set_tracing_panic_hook();
+    // The filter is applied to every layer, so filtered out spans are not exported either.
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(otlp_layer)
+        .with(fmt_layer);
+
+    set_global_default(subscriber)?;
+
+    set_tracing_panic_hook(config.abort_on_panic);
 
     // Return the log flusher to ensure logs are flushed before the application exits
     // without this the logs in memory may not be flushed to the file.
-    Ok(log_flusher)
+    Ok(LogFlusher {
+        _file_guard: file_guard,
+        _log_compressor: log_compressor,
+        tracer_provider,
+    })
 }
 
-fn configure_prod_tracing(filter: EnvFilter, app_name: &str) -> Result<LogFlusher, TracingError> {
-    let filename_suffix = "log";
-    let log_dir = "logs";
+/// Builds the filter used when `RUST_LOG` doesn't set one, see
+/// [`TracingConfig::default_log_filter`].
+fn default_log_filter(config: &TracingConfig) -> Result<EnvFilter, TracingError> {
+    let filter = match &config.default_log_filter {
+        Some(directives) => EnvFilter::builder().parse(directives)?,
+        None => EnvFilter::builder()
+            .with_default_directive(LevelFilter::OFF.into())
+            .parse("")?,
+    };
+
+    Ok(filter)
+}
+
+fn prod_fmt_layer<S>(
+    app_name: &str,
+    config: &TracingConfig,
+) -> Result<(Box<dyn Layer<S> + Send + Sync>, WorkerGuard), TracingError>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + 'static,
+{
+    let filename_prefix = config.filename_prefix.as_deref().unwrap_or(app_name);
     let file_appender = rolling::Builder::new()
-        .filename_prefix(app_name)
-        .filename_suffix(filename_suffix)
-        // rotate the log file every day
-        .rotation(rolling::Rotation::DAILY)
-        // keep a maximum of 5 log files
-        .max_log_files(5)
-        .build(log_dir)?;
+        .filename_prefix(filename_prefix)
+        .filename_suffix(&config.filename_suffix)
+        .rotation(config.rotation.clone())
+        .max_log_files(config.max_log_files)
+        .build(&config.directory)?;
 
     // Create a non-blocking appender to avoid blocking the logging thread
     // when writing to the file. This is important for performance.
@@ -102,22 +287,20 @@ fn configure_prod_tracing(filter: EnvFilter, app_name: &str) -> Result<LogFlushe
         // Disable target to reduce noise in the logs
         .with_target(false);
 
-    let subscriber_builder = FmtSubscriber::builder()
+    let layer = fmt::layer()
         .event_format(format)
         .with_writer(file_appender)
         .json()
         .with_current_span(true)
-        .with_span_list(true)
-        .with_env_filter(filter);
-
-    let subscriber = subscriber_builder.finish();
-
-    set_global_default(subscriber)?;
+        .with_span_list(true);
 
-    Ok(LogFlusher::Flusher(guard))
+    Ok((layer.boxed(), guard))
 }
 
-fn configure_dev_tracing(filter: EnvFilter) -> Result<LogFlusher, TracingError> {
+fn dev_fmt_layer<S>() -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + 'static,
+{
     let format = fmt::format()
         // Emit the log level in the log output
         .with_level(true)
@@ -131,26 +314,23 @@ fn configure_dev_tracing(filter: EnvFilter) -> Result<LogFlusher, TracingError>
         .with_file(false)
         .with_target(true);
 
-    let subscriber_builder = FmtSubscriber::builder()
-        .event_format(format)
-        .with_env_filter(filter);
-
-    let subscriber = subscriber_builder.finish();
-
-    set_global_default(subscriber)?;
-
-    Ok(LogFlusher::NullFlusher)
+    fmt::layer().event_format(format).boxed()
 }
 
 /// The default panic hook logs the panic information to stderr, which means
 /// it will not be sent to our logging system. This function replaces the default panic
 /// hook with a custom one that logs the panic information using `tracing`.
-/// It also calls the original panic hook after logging the panic information.
-fn set_tracing_panic_hook() {
+/// It also calls the original panic hook after logging the panic information, and then aborts
+/// the process if `abort_on_panic` is set.
+fn set_tracing_panic_hook(abort_on_panic: bool) {
     let prev_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
         panic_hook(info);
         prev_hook(info);
+
+        if abort_on_panic {
+            std::process::abort();
+        }
     }));
 }
 
@@ -187,3 +367,51 @@ fn panic_hook(panic_info: &PanicHookInfo) {
         "a panic occurred",
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_log_filter_max_level(directives: Option<&str>) -> Option<LevelFilter> {
+        let config = TracingConfig {
+            default_log_filter: directives.map(str::to_string),
+            ..TracingConfig::default()
+        };
+
+        default_log_filter(&config).unwrap().max_level_hint()
+    }
+
+    #[test]
+    fn default_log_filter_is_info_out_of_the_box() {
+        assert_eq!(
+            TracingConfig::default().default_log_filter.as_deref(),
+            Some("info")
+        );
+        assert_eq!(
+            default_log_filter_max_level(Some("info")),
+            Some(LevelFilter::INFO)
+        );
+    }
+
+    #[test]
+    fn default_log_filter_can_be_changed_or_disabled() {
+        assert_eq!(
+            default_log_filter_max_level(Some("warn")),
+            Some(LevelFilter::WARN)
+        );
+        assert_eq!(
+            default_log_filter_max_level(Some("warn,etl=debug")),
+            Some(LevelFilter::DEBUG)
+        );
+        assert_eq!(default_log_filter_max_level(None), Some(LevelFilter::OFF));
+
+        let config = TracingConfig {
+            default_log_filter: Some("etl=loud".to_string()),
+            ..TracingConfig::default()
+        };
+        assert!(matches!(
+            default_log_filter(&config),
+            Err(TracingError::InvalidDefaultLogFilter(_))
+        ));
+    }
+}
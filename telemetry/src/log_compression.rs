@@ -0,0 +1,182 @@
+use flate2::{Compression, write::GzEncoder};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How often the log directory is checked for rotated log files to compress.
+const COMPRESSION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The extension appended to the names of the compressed log files.
+const COMPRESSED_EXTENSION: &str = "gz";
+
+/// The log files written by a rolling appender, named `{prefix}.{date}.{suffix}`.
+#[derive(Debug, Clone)]
+pub(crate) struct LogFiles {
+    pub directory: PathBuf,
+    pub prefix: String,
+    pub suffix: String,
+    pub max_log_files: usize,
+}
+
+impl LogFiles {
+    fn is_log_file(&self, file_name: &str) -> bool {
+        file_name.starts_with(&format!("{}.", self.prefix))
+            && (self.suffix.is_empty() || file_name.ends_with(&format!(".{}", self.suffix)))
+    }
+}
+
+/// Stops the thread compressing the rotated log files when dropped.
+pub(crate) struct LogCompressor {
+    _stop_tx: Sender<()>,
+}
+
+impl LogCompressor {
+    /// Spawns a thread which periodically gzip-compresses the log files which were rotated out,
+    /// see [`compress_rotated_log_files`].
+    pub(crate) fn spawn(log_files: LogFiles) -> io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        thread::Builder::new()
+            .name("log-compressor".to_string())
+            .spawn(move || {
+                // Nothing is ever sent, so the loop only ends once the sender is dropped.
+                while let Err(RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(COMPRESSION_INTERVAL)
+                {
+                    if let Err(err) = compress_rotated_log_files(&log_files) {
+                        tracing::warn!(error = %err, "failed to compress the rotated log files");
+                    }
+                }
+            })?;
+
+        Ok(Self { _stop_tx: stop_tx })
+    }
+}
+
+/// Compresses the log files of `log_files` except the newest one, which is still written to, and
+/// deletes the oldest compressed files so that at most `max_log_files` files are kept, counting the
+/// compressed ones.
+///
+/// The dates in the names of the log files sort in chronological order, so the files are ordered
+/// by name.
+pub(crate) fn compress_rotated_log_files(log_files: &LogFiles) -> io::Result<()> {
+    let mut uncompressed_files = vec![];
+    let mut compressed_files = vec![];
+    for entry in fs::read_dir(&log_files.directory)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+
+        let Ok(file_name) = entry.file_name().into_string() else {
+            continue;
+        };
+
+        if let Some(compressed_file_name) =
+            file_name.strip_suffix(&format!(".{COMPRESSED_EXTENSION}"))
+        {
+            if log_files.is_log_file(compressed_file_name) {
+                compressed_files.push(file_name);
+            }
+        } else if log_files.is_log_file(&file_name) {
+            uncompressed_files.push(file_name);
+        }
+    }
+
+    uncompressed_files.sort();
+    uncompressed_files.pop();
+    for file_name in uncompressed_files {
+        let compressed_file_name = format!("{file_name}.{COMPRESSED_EXTENSION}");
+        compress_file(
+            &log_files.directory.join(&file_name),
+            &log_files.directory.join(&compressed_file_name),
+        )?;
+        compressed_files.push(compressed_file_name);
+    }
+
+    // The file being written to is kept besides the compressed files.
+    compressed_files.sort();
+    compressed_files.dedup();
+    let excess = compressed_files
+        .len()
+        .saturating_sub(log_files.max_log_files.saturating_sub(1));
+    for file_name in &compressed_files[..excess] {
+        fs::remove_file(log_files.directory.join(file_name))?;
+    }
+
+    Ok(())
+}
+
+/// Compresses the file at `path` to `compressed_path` and deletes it.
+///
+/// The file is first compressed to a temporary file which is then renamed, so that a partially
+/// compressed file is never mistaken for a complete one if the process stops midway.
+fn compress_file(path: &Path, compressed_path: &Path) -> io::Result<()> {
+    let mut temporary_path = compressed_path.as_os_str().to_owned();
+    temporary_path.push(".tmp");
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(&temporary_path)?),
+        Compression::default(),
+    );
+    io::copy(&mut reader, &mut encoder)?;
+    encoder.finish()?.into_inner()?.sync_all()?;
+
+    fs::rename(&temporary_path, compressed_path)?;
+    fs::remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn rotated_log_files_are_compressed_and_pruned() {
+        let directory =
+            std::env::temp_dir().join(format!("telemetry_log_compression_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        fs::write(directory.join("app.2025-08-01.log.gz"), b"").unwrap();
+        for date in ["2025-08-02", "2025-08-03", "2025-08-04"] {
+            fs::write(directory.join(format!("app.{date}.log")), date).unwrap();
+        }
+        fs::write(directory.join("other.2025-08-01.log"), b"other").unwrap();
+
+        let log_files = LogFiles {
+            directory: directory.clone(),
+            prefix: "app".to_string(),
+            suffix: "log".to_string(),
+            max_log_files: 3,
+        };
+        compress_rotated_log_files(&log_files).unwrap();
+
+        let mut file_names: Vec<_> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        file_names.sort();
+        assert_eq!(
+            file_names,
+            vec![
+                "app.2025-08-03.log.gz",
+                "app.2025-08-04.log",
+                "other.2025-08-01.log",
+            ]
+        );
+
+        let mut content = String::new();
+        GzDecoder::new(File::open(directory.join("app.2025-08-03.log.gz")).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "2025-08-03");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}